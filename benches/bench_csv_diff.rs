@@ -1,14 +1,14 @@
 #![cfg(feature = "rayon-threads")]
 
-use ::csv_diff::csv::Csv;
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ::csv_diff::csv::{Csv, CsvBuilder};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use csv_diff::csv_diff;
-use std::{fmt::Display, io::Cursor};
+use std::io::Cursor;
 use utils::csv_generator::CsvGenerator;
 
 fn criterion_benchmark(c: &mut Criterion) {
     let csv_byte_diff_local = csv_diff::CsvByteDiffLocal::new().expect("must be constructable");
-    let mut csv_byte_diff = csv_diff::CsvByteDiff::new().expect("must be constructable");
+    let csv_byte_diff = csv_diff::CsvByteDiff::new().expect("must be constructable");
 
     let mut bench_group_csv_diff_equal_csv = c.benchmark_group("csv_diff_equal_csv");
 
@@ -86,5 +86,89 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, criterion_benchmark);
+/// Exercises the loops, that are vectorized with the feature `nightly-simd`: hashing a composite key of adjacent columns
+/// and finding the modified fields of records, that share their key. Compare
+/// `cargo bench --bench bench_csv_diff -- hot_loops` with
+/// `cargo +nightly bench --bench bench_csv_diff --features nightly-simd -- hot_loops`.
+fn hot_loops_benchmark(c: &mut Criterion) {
+    let csv_byte_diff_local = csv_diff::CsvByteDiffLocalBuilder::new()
+        .primary_key_columns([0, 1, 2])
+        .build()
+        .expect("must be constructable");
+
+    let mut bench_group_hot_loops = c.benchmark_group("hot_loops");
+
+    for csv_gen in [
+        CsvGenerator::new(10_000, 9),
+        CsvGenerator::new(10_000, 50),
+        CsvGenerator::new(100_000, 9),
+    ] {
+        let (csv_left, csv_right) = (csv_gen.generate(), csv_gen.generate());
+
+        bench_group_hot_loops.measurement_time(std::time::Duration::from_secs(20));
+        bench_group_hot_loops
+            .throughput(Throughput::Bytes((csv_left.len() + csv_right.len()) as u64));
+        bench_group_hot_loops.bench_with_input(
+            BenchmarkId::from_parameter(format!(
+                "csv_byte_diff_local_composite_key/{} <> {}",
+                csv_gen, csv_gen
+            )),
+            &(&csv_left, &csv_right),
+            |b, (csv_left, csv_right)| {
+                b.iter(|| {
+                    csv_byte_diff_local
+                        .diff(
+                            Csv::with_reader_seek(Cursor::new(csv_left.as_slice())),
+                            Csv::with_reader_seek(Cursor::new(csv_right.as_slice())),
+                        )
+                        .unwrap();
+                });
+            },
+        );
+    }
+}
+
+/// Compares the parsing backends on the 1,000,000 rows benchmark, where parsing is the dominant cost:
+/// `cargo bench --bench bench_csv_diff --features simd-parser -- parser`.
+fn parser_benchmark(c: &mut Criterion) {
+    let csv_byte_diff_local = csv_diff::CsvByteDiffLocal::new().expect("must be constructable");
+    let csv_builders = [
+        ("csv_crate", CsvBuilder::new()),
+        #[cfg(feature = "simd-parser")]
+        ("simd", CsvBuilder::new().simd_parser(true)),
+    ];
+
+    let mut bench_group_parser = c.benchmark_group("parser");
+
+    let csv_gen = CsvGenerator::new(1_000_000, 9);
+    let csv_data = csv_gen.generate();
+    bench_group_parser.measurement_time(std::time::Duration::from_secs(150));
+    bench_group_parser.throughput(Throughput::Bytes((csv_data.len() * 2) as u64));
+    for (parser, csv_builder) in csv_builders {
+        bench_group_parser.bench_with_input(
+            BenchmarkId::from_parameter(format!(
+                "csv_byte_diff_local_{}/{} <> {}",
+                parser, csv_gen, csv_gen
+            )),
+            &csv_data,
+            |b, csv_data| {
+                b.iter(|| {
+                    csv_byte_diff_local
+                        .diff(
+                            csv_builder.build_with_reader_seek(Cursor::new(csv_data.as_slice())),
+                            csv_builder.build_with_reader_seek(Cursor::new(csv_data.as_slice())),
+                        )
+                        .unwrap();
+                });
+            },
+        );
+    }
+}
+
+criterion_group!(
+    benches,
+    criterion_benchmark,
+    hot_loops_benchmark,
+    parser_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,309 @@
+use crate::diff_row::DiffByteRecord;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// Consumes the differences of a comparison and only keeps aggregate statistics of them
+/// (number of added, deleted and modified records, and how often each column has been modified),
+/// so that the result can be shared with parties, that must not see any record.
+///
+/// The records are dropped as soon as they have been counted, so it can be used as the sink of
+/// a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator) without buffering anything.
+///
+/// Optionally,
+/// - the counts are broken down by the value of a [`group_by_column`](AggregateDiffSink::group_by_column)
+///   (which should be a coarse category, because its values are part of the result),
+/// - groups with fewer than [`min_group_size`](AggregateDiffSink::min_group_size) differences are suppressed and
+/// - [Laplace noise](https://en.wikipedia.org/wiki/Additive_noise_differential_privacy_mechanisms#Laplace_Mechanism)
+///   is added to every count (see [`noise`](AggregateDiffSink::noise)).
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::aggregate_diff::AggregateDiffSink;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit\n\
+3,peanut,nut";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,berry\n\
+4,walnut,nut";
+
+let diff_aggregate = AggregateDiffSink::new().group_by_column(2).consume(
+    CsvByteDiff::new()?.diff(
+        Csv::with_reader(csv_left.as_bytes()),
+        Csv::with_reader(csv_right.as_bytes()),
+    ),
+)?;
+
+assert_eq!(diff_aggregate.total().modified(), 1);
+assert_eq!(diff_aggregate.total().modified_by_column(2), 1);
+let nut = diff_aggregate.group(b"nut").expect("nut group");
+assert_eq!((nut.added(), nut.deleted()), (1, 1));
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Clone, Default)]
+pub struct AggregateDiffSink {
+    group_by_column: Option<usize>,
+    min_group_size: u64,
+    noise_epsilon: Option<f64>,
+    noise_seed: Option<u64>,
+}
+
+impl AggregateDiffSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Additionally count the differences per value of column `column`. A `Modify` is counted in the group of its add half.
+    pub fn group_by_column(mut self, column: usize) -> Self {
+        self.group_by_column = Some(column);
+        self
+    }
+
+    /// Leave out all groups, that have fewer than `min_group_size` differences (before any noise has been added).
+    /// The total counts always include them.
+    pub fn min_group_size(mut self, min_group_size: u64) -> Self {
+        self.min_group_size = min_group_size;
+        self
+    }
+
+    /// Add noise, drawn from a Laplace distribution with scale `1 / epsilon`, to every count of the result
+    /// (rounded and clamped to `0`). A smaller `epsilon` means more noise.
+    ///
+    /// Every single count is `epsilon`-differentially private with respect to adding or removing one difference;
+    /// releasing several counts adds up their privacy loss. Values of `epsilon`, that are not positive, are ignored.
+    pub fn noise(mut self, epsilon: f64) -> Self {
+        self.noise_epsilon = Some(epsilon).filter(|&epsilon| epsilon > 0.0);
+        self
+    }
+
+    /// Seed the random number generator of the [`noise`](AggregateDiffSink::noise), so that the result is reproducible
+    /// (by default, it is seeded from the operating system). Never reuse a seed, when the result leaves the trust boundary.
+    pub fn noise_seed(mut self, seed: u64) -> Self {
+        self.noise_seed = Some(seed);
+        self
+    }
+
+    /// Consume all `diff_records` and return their aggregate statistics or the first error, that occurred when comparing.
+    pub fn consume<I>(&self, diff_records: I) -> csv::Result<DiffAggregate>
+    where
+        I: IntoIterator<Item = csv::Result<DiffByteRecord>>,
+    {
+        let mut diff_aggregate = DiffAggregate::default();
+        for diff_record in diff_records {
+            let diff_record = diff_record?;
+            diff_aggregate.total.record(&diff_record);
+            if let Some(column) = self.group_by_column {
+                let byte_record = match &diff_record {
+                    DiffByteRecord::Add(add) | DiffByteRecord::Modify { add, .. } => {
+                        add.byte_record()
+                    }
+                    DiffByteRecord::Delete(delete) => delete.byte_record(),
+                    DiffByteRecord::Move { record, .. } => record,
+                };
+                let group = byte_record.get(column).unwrap_or_default();
+                match diff_aggregate.by_group.get_mut(group) {
+                    Some(diff_counts) => diff_counts.record(&diff_record),
+                    None => {
+                        let mut diff_counts = DiffCounts::default();
+                        diff_counts.record(&diff_record);
+                        diff_aggregate.by_group.insert(group.to_vec(), diff_counts);
+                    }
+                }
+            }
+        }
+        let min_group_size = self.min_group_size;
+        diff_aggregate
+            .by_group
+            .retain(|_, diff_counts| diff_counts.differences() >= min_group_size);
+        if let Some(epsilon) = self.noise_epsilon {
+            let mut rng = match self.noise_seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            let mut add_noise = |count: &mut u64| {
+                let noisy = *count as f64 + laplace(&mut rng, 1.0 / epsilon);
+                *count = noisy.round().max(0.0) as u64;
+            };
+            diff_aggregate.total.for_each_count(&mut add_noise);
+            for diff_counts in diff_aggregate.by_group.values_mut() {
+                diff_counts.for_each_count(&mut add_noise);
+            }
+        }
+        Ok(diff_aggregate)
+    }
+}
+
+/// Draw a sample of a Laplace distribution with mean `0` and scale `scale`.
+fn laplace<R: Rng>(rng: &mut R, scale: f64) -> f64 {
+    let u: f64 = rng.gen_range(-0.5..0.5);
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+}
+
+/// The aggregate statistics of a comparison, see [`AggregateDiffSink`]. It contains no record and no field,
+/// except for the values of the [`group_by_column`](AggregateDiffSink::group_by_column).
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct DiffAggregate {
+    total: DiffCounts,
+    by_group: BTreeMap<Vec<u8>, DiffCounts>,
+}
+
+impl DiffAggregate {
+    /// The counts of all differences.
+    pub fn total(&self) -> &DiffCounts {
+        &self.total
+    }
+
+    /// The counts of the differences, whose group column has the value `group`.
+    pub fn group(&self, group: &[u8]) -> Option<&DiffCounts> {
+        self.by_group.get(group)
+    }
+
+    /// Iterate over all groups and their counts, ordered by the value of the group column.
+    pub fn groups(&self) -> impl Iterator<Item = (&[u8], &DiffCounts)> + '_ {
+        self.by_group
+            .iter()
+            .map(|(group, diff_counts)| (group.as_slice(), diff_counts))
+    }
+}
+
+/// Number of added, deleted, modified and moved records (and of modifications per column) of a [`DiffAggregate`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct DiffCounts {
+    added: u64,
+    deleted: u64,
+    modified: u64,
+    moved: u64,
+    modified_by_column: BTreeMap<usize, u64>,
+}
+
+impl DiffCounts {
+    pub fn added(&self) -> u64 {
+        self.added
+    }
+
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    pub fn modified(&self) -> u64 {
+        self.modified
+    }
+
+    pub fn moved(&self) -> u64 {
+        self.moved
+    }
+
+    /// Number of `Modify`s, where the column at `column_idx` has been changed.
+    pub fn modified_by_column(&self, column_idx: usize) -> u64 {
+        self.modified_by_column
+            .get(&column_idx)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of all differences (`added + deleted + modified + moved`).
+    pub fn differences(&self) -> u64 {
+        self.added + self.deleted + self.modified + self.moved
+    }
+
+    fn record(&mut self, diff_record: &DiffByteRecord) {
+        match diff_record {
+            DiffByteRecord::Add(_) => self.added += 1,
+            DiffByteRecord::Delete(_) => self.deleted += 1,
+            DiffByteRecord::Modify { field_indices, .. } => {
+                self.modified += 1;
+                for &field_idx in field_indices {
+                    *self.modified_by_column.entry(field_idx).or_default() += 1;
+                }
+            }
+            DiffByteRecord::Move { .. } => self.moved += 1,
+        }
+    }
+
+    fn for_each_count(&mut self, f: &mut impl FnMut(&mut u64)) {
+        f(&mut self.added);
+        f(&mut self.deleted);
+        f(&mut self.modified);
+        f(&mut self.moved);
+        self.modified_by_column.values_mut().for_each(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    fn diff_records() -> Vec<csv::Result<DiffByteRecord>> {
+        let record =
+            |fields: Vec<&str>, line| ByteRecordLineInfo::new(csv::ByteRecord::from(fields), line);
+        vec![
+            Ok(DiffByteRecord::Add(record(vec!["1", "lemon", "fruit"], 2))),
+            Ok(DiffByteRecord::Delete(record(
+                vec!["2", "peanut", "nut"],
+                3,
+            ))),
+            Ok(DiffByteRecord::Modify {
+                delete: record(vec!["3", "cherry", "fruit"], 4),
+                add: record(vec!["3", "Cherry", "fruit"], 4),
+                field_indices: vec![1],
+            }),
+        ]
+    }
+
+    #[test]
+    fn min_group_size_suppresses_small_groups_but_not_total() -> csv::Result<()> {
+        let diff_aggregate = AggregateDiffSink::new()
+            .group_by_column(2)
+            .min_group_size(2)
+            .consume(diff_records())?;
+
+        assert_eq!(diff_aggregate.total().differences(), 3);
+        assert_eq!(diff_aggregate.total().modified_by_column(1), 1);
+        assert_eq!(
+            diff_aggregate
+                .groups()
+                .map(|(group, diff_counts)| (group, diff_counts.differences()))
+                .collect::<Vec<_>>(),
+            vec![(b"fruit".as_slice(), 2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn noise_with_same_seed_is_reproducible() -> csv::Result<()> {
+        let diff_records = || (0..1_000).flat_map(|_| diff_records());
+        let sink = AggregateDiffSink::new().noise(0.5).noise_seed(42);
+
+        let diff_aggregate = sink.consume(diff_records())?;
+
+        assert_eq!(diff_aggregate, sink.consume(diff_records())?);
+        assert!(diff_aggregate.total().added().abs_diff(1_000) <= 30);
+        Ok(())
+    }
+
+    #[test]
+    fn consume_stops_at_first_error() {
+        let diff_records =
+            diff_records()
+                .into_iter()
+                .chain(std::iter::once(Err(csv::Error::from(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "broken",
+                )))));
+
+        assert!(AggregateDiffSink::new().consume(diff_records).is_err());
+    }
+}
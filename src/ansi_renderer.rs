@@ -0,0 +1,228 @@
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::unified_diff::write_field;
+use std::io::{self, Write};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const BOLD_GREEN: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s with ANSI colors for a terminal.
+///
+/// The layout is the same as the one of the [`UnifiedDiffRenderer`](crate::unified_diff::UnifiedDiffRenderer):
+/// - a [`Delete`](crate::diff_row::DiffByteRecord::Delete) is rendered in red,
+/// - an [`Add`](crate::diff_row::DiffByteRecord::Add) is rendered in green and
+/// - a [`Modify`](crate::diff_row::DiffByteRecord::Modify) has a yellow prefix, while only its changed fields
+///   (see its `field_indices`) are emphasized: the old value as `[-old-]` in bold red and the new value as `{+new+}` in bold green.
+/// - a [`Move`](crate::diff_row::DiffByteRecord::Move) is rendered in cyan.
+///
+/// Colors are always emitted, so it is up to the caller to only use this renderer, when the output is a terminal.
+///
+/// This is only available with the Cargo feature `ansi-renderer`.
+/// # Example
+/// ```
+/// use csv_diff::ansi_renderer::AnsiRenderer;
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+///
+/// let diff_byte_records = vec![DiffByteRecord::Modify {
+///     delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "fruit"]), 2),
+///     add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "nut"]), 2),
+///     field_indices: vec![2],
+/// }];
+///
+/// assert_eq!(
+///     AnsiRenderer::new().render(&diff_byte_records),
+///     "\x1b[33m~ 2 | \x1b[0m1,lemon,\x1b[1;31m[-fruit-]\x1b[0m\x1b[1;32m{+nut+}\x1b[0m\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnsiRenderer {
+    delimiter: u8,
+}
+
+impl Default for AnsiRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiRenderer {
+    pub fn new() -> Self {
+        Self { delimiter: b',' }
+    }
+
+    /// Set the delimiter, that is used to separate fields (default: `,`).
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Render all `diff_records` into a `String`.
+    pub fn render<'a, I>(&self, diff_records: I) -> String
+    where
+        I: IntoIterator<Item = &'a DiffByteRecord>,
+    {
+        let mut buf = Vec::new();
+        self.render_to(diff_records, &mut buf)
+            .expect("writing to a Vec never fails");
+        // we only ever write valid UTF-8
+        String::from_utf8(buf).expect("is utf8")
+    }
+
+    /// Render all `diff_records` into `wtr` (e.g. a locked [`Stdout`](std::io::Stdout)).
+    pub fn render_to<'a, I, W>(&self, diff_records: I, wtr: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a DiffByteRecord>,
+        W: Write,
+    {
+        for diff_record in diff_records {
+            self.render_diff_byte_record(diff_record, wtr)?;
+        }
+        Ok(())
+    }
+
+    /// Render a single `DiffByteRecord` (including a trailing newline) into `wtr`.
+    pub fn render_diff_byte_record<W: Write>(
+        &self,
+        diff_record: &DiffByteRecord,
+        wtr: &mut W,
+    ) -> io::Result<()> {
+        match diff_record {
+            DiffByteRecord::Delete(brli) => self.render_one_side(RED, b'-', brli, wtr),
+            DiffByteRecord::Add(brli) => self.render_one_side(GREEN, b'+', brli, wtr),
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                if delete.line() == add.line() {
+                    write!(wtr, "{}~ {} | {}", YELLOW, delete.line(), RESET)?;
+                } else {
+                    write!(
+                        wtr,
+                        "{}~ {} -> {} | {}",
+                        YELLOW,
+                        delete.line(),
+                        add.line(),
+                        RESET
+                    )?;
+                }
+                let (fields_deleted, fields_added) = (delete.byte_record(), add.byte_record());
+                let num_of_fields = fields_deleted.len().max(fields_added.len());
+                let mut field_indices = field_indices.iter().peekable();
+                for idx in 0..num_of_fields {
+                    if idx > 0 {
+                        wtr.write_all(&[self.delimiter])?;
+                    }
+                    let is_changed = field_indices.next_if_eq(&&idx).is_some();
+                    match (fields_deleted.get(idx), fields_added.get(idx)) {
+                        (Some(field_deleted), Some(_)) if !is_changed => {
+                            write_field(field_deleted, self.delimiter, wtr)?
+                        }
+                        (field_deleted, field_added) => {
+                            if let Some(field_deleted) = field_deleted {
+                                write!(wtr, "{}[-", BOLD_RED)?;
+                                write_field(field_deleted, self.delimiter, wtr)?;
+                                write!(wtr, "-]{}", RESET)?;
+                            }
+                            if let Some(field_added) = field_added {
+                                write!(wtr, "{}{{+", BOLD_GREEN)?;
+                                write_field(field_added, self.delimiter, wtr)?;
+                                write!(wtr, "+}}{}", RESET)?;
+                            }
+                        }
+                    }
+                }
+                wtr.write_all(b"\n")
+            }
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => {
+                write!(wtr, "{}> {} -> {} | ", CYAN, from_line, to_line)?;
+                self.write_record(record, wtr)?;
+                writeln!(wtr, "{}", RESET)
+            }
+        }
+    }
+
+    fn render_one_side<W: Write>(
+        &self,
+        color: &str,
+        prefix: u8,
+        brli: &ByteRecordLineInfo,
+        wtr: &mut W,
+    ) -> io::Result<()> {
+        write!(wtr, "{}{} {} | ", color, prefix as char, brli.line())?;
+        self.write_record(brli.byte_record(), wtr)?;
+        // reset before the newline, so that a pager doesn't carry the color over to the next line
+        writeln!(wtr, "{}", RESET)
+    }
+
+    fn write_record<W: Write>(&self, byte_record: &csv::ByteRecord, wtr: &mut W) -> io::Result<()> {
+        for (idx, field) in byte_record.iter().enumerate() {
+            if idx > 0 {
+                wtr.write_all(&[self.delimiter])?;
+            }
+            write_field(field, self.delimiter, wtr)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rayon-threads")]
+impl crate::parallel_render::DiffRecordFormatter for AnsiRenderer {
+    fn format_diff_byte_record(
+        &self,
+        diff_record: &DiffByteRecord,
+        mut wtr: &mut dyn Write,
+    ) -> io::Result<()> {
+        self.render_diff_byte_record(diff_record, &mut wtr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn render_add_in_green_and_delete_in_red() {
+        let diff_records = vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b", "c"]),
+                2,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e;f", "g"]),
+                3,
+            )),
+        ];
+
+        assert_eq!(
+            AnsiRenderer::new().delimiter(b';').render(&diff_records),
+            "\x1b[31m- 2 | a;b;c\x1b[0m\n\
+             \x1b[32m+ 3 | d;\"e;f\";g\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn render_modify_emphasizes_only_changed_fields() {
+        let diff_records = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "c", "d"]), 5),
+            field_indices: vec![1],
+        }];
+
+        assert_eq!(
+            AnsiRenderer::new().render(&diff_records),
+            "\x1b[33m~ 2 -> 5 | \x1b[0ma,\
+             \x1b[1;31m[-b-]\x1b[0m\x1b[1;32m{+x+}\x1b[0m,c,\
+             \x1b[1;32m{+d+}\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::csv::Csv;
+use crate::csv_hasher::CsvHasherExt;
+use crate::error_code::ErrorCode;
+use std::io::Read;
+use thiserror::Error;
+use xxhash_rust::xxh3::xxh3_128;
+
+/// Estimate the number of differences between two [CSVs](https://en.wikipedia.org/wiki/Comma-separated_values)
+/// with a fixed, small amount of memory (in contrast to the exact engines [`CsvByteDiff`](crate::csv_diff::CsvByteDiff)
+/// and [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), whose memory usage grows with the number of differences).
+///
+/// Both CSVs are read once and summarized in two _count sketches_ (the signed variant of a count-min sketch):
+/// one of the primary keys and one of the primary keys together with the hash of their records.
+/// Because both sides are added to the same sketches with opposite signs, records, that are equal, cancel each other out
+/// and the sketches only estimate, how many keys exist on one side only (added/deleted) and how many records differ (modified).
+/// No record is ever emitted.
+///
+/// The estimates are within [`error_bound`](ApproxDiffEstimate::error_bound) of the exact counts
+/// with a probability of at least `1 - failure_probability` (see [`ApproxCsvDiffBuilder`]).
+/// They assume, that primary keys are unique within each CSV.
+///
+/// This is useful as a cheap check, that decides, whether the exact comparison must be run at all.
+/// # Example: run the exact comparison only, if there might be too many differences
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{approx_diff::ApproxCsvDiff, csv::Csv, csv_diff::CsvByteDiffLocal};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,nut\n\
+2,strawberry,fruit";
+
+let estimate = ApproxCsvDiff::new().diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+)?;
+
+assert_eq!(estimate.modified(), 1);
+assert_eq!(estimate.added() + estimate.deleted(), 0);
+
+if estimate.differences() + estimate.error_bound() > 0 {
+    let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+        Csv::with_reader_seek(csv_left.as_bytes()),
+        Csv::with_reader_seek(csv_right.as_bytes()),
+    )?;
+    assert_eq!(diff_byte_records.as_slice().len(), 1);
+}
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Clone)]
+pub struct ApproxCsvDiff {
+    primary_key_columns: Vec<usize>,
+    relative_error: f64,
+    failure_probability: f64,
+}
+
+impl Default for ApproxCsvDiff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApproxCsvDiff {
+    /// Create an `ApproxCsvDiff` with column 0 as primary key, a relative error of `0.05`
+    /// and a failure probability of `0.01`.
+    pub fn new() -> Self {
+        Self {
+            primary_key_columns: vec![0],
+            relative_error: 0.05,
+            failure_probability: 0.01,
+        }
+    }
+
+    /// Read `csv_left` and `csv_right` completely and estimate the number of differences between them.
+    pub fn diff<R: Read>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<ApproxDiffEstimate> {
+        let mut keys_sketch = CountSketch::new(self.relative_error, self.failure_probability);
+        let mut records_sketch = CountSketch::new(self.relative_error, self.failure_probability);
+        let rows_left = self.add_to_sketches(csv_left, 1, &mut keys_sketch, &mut records_sketch)?;
+        let rows_right =
+            self.add_to_sketches(csv_right, -1, &mut keys_sketch, &mut records_sketch)?;
+
+        // every key, that only exists on one side, contributes 1 to both estimates;
+        // every modified record contributes 2 (its left and its right version) to the records estimate only
+        let keys_f2 = keys_sketch.estimate_f2();
+        let records_f2 = records_sketch.estimate_f2().max(keys_f2);
+        let rows_diff = rows_right as f64 - rows_left as f64;
+        let to_count = |estimate: f64| estimate.round().max(0.0) as u64;
+        Ok(ApproxDiffEstimate {
+            rows_left,
+            rows_right,
+            added: to_count((keys_f2 + rows_diff) / 2.0),
+            deleted: to_count((keys_f2 - rows_diff) / 2.0),
+            modified: to_count((records_f2 - keys_f2) / 2.0),
+            error_bound: (self.relative_error * records_f2).ceil() as u64,
+        })
+    }
+
+    fn add_to_sketches<R: Read>(
+        &self,
+        csv: Csv<R>,
+        sign: i64,
+        keys_sketch: &mut CountSketch,
+        records_sketch: &mut CountSketch,
+    ) -> csv::Result<u64> {
+        let mut csv_reader = csv.into_csv_reader();
+        let mut csv_record = csv::ByteRecord::new();
+        let mut num_of_rows = 0;
+        let mut key_and_record_hash = [0; 32];
+        while csv_reader.read_byte_record(&mut csv_record)? {
+            num_of_rows += 1;
+            let key = csv_record.hash_key_fields(&self.primary_key_columns);
+            key_and_record_hash[..16].copy_from_slice(&key.to_le_bytes());
+            key_and_record_hash[16..].copy_from_slice(&csv_record.hash_record().to_le_bytes());
+            keys_sketch.update(key, sign);
+            records_sketch.update(xxh3_128(&key_and_record_hash), sign);
+        }
+        Ok(num_of_rows)
+    }
+}
+
+/// Create an [`ApproxCsvDiff`] with configuration options.
+#[derive(Debug, Clone)]
+pub struct ApproxCsvDiffBuilder {
+    approx_csv_diff: ApproxCsvDiff,
+}
+
+impl Default for ApproxCsvDiffBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApproxCsvDiffBuilder {
+    pub fn new() -> Self {
+        Self {
+            approx_csv_diff: ApproxCsvDiff::new(),
+        }
+    }
+
+    pub fn primary_key_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        self.approx_csv_diff.primary_key_columns = columns.into_iter().collect();
+        self.approx_csv_diff.primary_key_columns.sort_unstable();
+        self.approx_csv_diff.primary_key_columns.dedup();
+        self
+    }
+
+    /// Set the error of the estimates (between `0.0` and `1.0`, exclusive), relative to the number of differing records
+    /// (default: `0.05`). The memory usage grows with `1 / relative_error²`.
+    pub fn relative_error(mut self, relative_error: f64) -> Self {
+        self.approx_csv_diff.relative_error = relative_error;
+        self
+    }
+
+    /// Set the probability (between `0.0` and `1.0`, exclusive), that an estimate is _not_ within the error bound
+    /// (default: `0.01`). The memory usage grows with `ln(1 / failure_probability)`.
+    pub fn failure_probability(mut self, failure_probability: f64) -> Self {
+        self.approx_csv_diff.failure_probability = failure_probability;
+        self
+    }
+
+    pub fn build(self) -> Result<ApproxCsvDiff, ApproxCsvDiffBuilderError> {
+        let approx_csv_diff = self.approx_csv_diff;
+        if approx_csv_diff.primary_key_columns.is_empty() {
+            Err(ApproxCsvDiffBuilderError::NoPrimaryKeyColumns)
+        } else if !(approx_csv_diff.relative_error > 0.0 && approx_csv_diff.relative_error < 1.0) {
+            Err(ApproxCsvDiffBuilderError::InvalidRelativeError(
+                approx_csv_diff.relative_error,
+            ))
+        } else if !(approx_csv_diff.failure_probability > 0.0
+            && approx_csv_diff.failure_probability < 1.0)
+        {
+            Err(ApproxCsvDiffBuilderError::InvalidFailureProbability(
+                approx_csv_diff.failure_probability,
+            ))
+        } else {
+            Ok(approx_csv_diff)
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Clone)]
+pub enum ApproxCsvDiffBuilderError {
+    #[error("No primary key columns have been specified. You need to provide at least one column index.")]
+    NoPrimaryKeyColumns,
+    #[error("the relative error must be between 0.0 and 1.0 (exclusive), but is {0}")]
+    InvalidRelativeError(f64),
+    #[error("the failure probability must be between 0.0 and 1.0 (exclusive), but is {0}")]
+    InvalidFailureProbability(f64),
+}
+
+impl ErrorCode for ApproxCsvDiffBuilderError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NoPrimaryKeyColumns => "CSVDIFF_NO_PRIMARY_KEY",
+            Self::InvalidRelativeError(_) => "CSVDIFF_INVALID_RELATIVE_ERROR",
+            Self::InvalidFailureProbability(_) => "CSVDIFF_INVALID_FAILURE_PROBABILITY",
+        }
+    }
+}
+
+/// The estimated number of differences between two CSVs, see [`ApproxCsvDiff`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ApproxDiffEstimate {
+    rows_left: u64,
+    rows_right: u64,
+    added: u64,
+    deleted: u64,
+    modified: u64,
+    error_bound: u64,
+}
+
+impl ApproxDiffEstimate {
+    /// Exact number of records of the left CSV (excluding headers).
+    pub fn rows_left(&self) -> u64 {
+        self.rows_left
+    }
+
+    /// Exact number of records of the right CSV (excluding headers).
+    pub fn rows_right(&self) -> u64 {
+        self.rows_right
+    }
+
+    /// Estimated number of records, that only exist in the right CSV.
+    pub fn added(&self) -> u64 {
+        self.added
+    }
+
+    /// Estimated number of records, that only exist in the left CSV.
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    /// Estimated number of records, that exist in both CSVs, but are different.
+    pub fn modified(&self) -> u64 {
+        self.modified
+    }
+
+    /// Estimated number of all differences (`added + deleted + modified`).
+    pub fn differences(&self) -> u64 {
+        self.added + self.deleted + self.modified
+    }
+
+    /// The maximum absolute error of each of the estimated counts (with the configured probability).
+    pub fn error_bound(&self) -> u64 {
+        self.error_bound
+    }
+}
+
+/// A count sketch, that estimates the second frequency moment (the sum of all squared frequencies) of a stream of
+/// items with signed updates. Each row maps an item to one counter and a random sign, so that collisions cancel out
+/// on average; the median of all rows is returned.
+struct CountSketch {
+    width: usize,
+    counters: Vec<Vec<i64>>,
+}
+
+impl CountSketch {
+    fn new(relative_error: f64, failure_probability: f64) -> Self {
+        // each row fails with a probability of at most 1/4 (Chebyshev) and the median of all rows
+        // fails with a probability of at most `failure_probability` (Chernoff)
+        let width = (8.0 / (relative_error * relative_error)).ceil() as usize;
+        let depth = ((8.0 * (1.0 / failure_probability).ln()).ceil() as usize).max(1) | 1;
+        Self {
+            width,
+            counters: vec![vec![0; width]; depth],
+        }
+    }
+
+    /// `item` must already be a (well distributed) hash.
+    fn update(&mut self, item: u128, delta: i64) {
+        let h1 = item as u64;
+        let h2 = (item >> 64) as u64;
+        for (row_idx, row) in self.counters.iter_mut().enumerate() {
+            let h = mix64(h1.wrapping_add((row_idx as u64).wrapping_mul(h2)));
+            let sign = if h >> 63 == 0 { 1 } else { -1 };
+            row[(h % self.width as u64) as usize] += sign * delta;
+        }
+    }
+
+    fn estimate_f2(&self) -> f64 {
+        let mut row_estimates: Vec<_> = self
+            .counters
+            .iter()
+            .map(|row| row.iter().map(|&c| (c as f64) * (c as f64)).sum::<f64>())
+            .collect();
+        row_estimates.sort_unstable_by(|a, b| a.total_cmp(b));
+        row_estimates[row_estimates.len() / 2]
+    }
+}
+
+/// The finalizer of SplitMix64, so that the rows of a [`CountSketch`] are independent of each other.
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn csv(ids: impl Iterator<Item = u64>, value: impl Fn(u64) -> &'static str) -> String {
+        std::iter::once("id,value".to_string())
+            .chain(ids.map(|id| format!("{},{}", id, value(id))))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn diff_equal_csvs_estimates_no_differences() -> csv::Result<()> {
+        let csv_data = csv(0..1_000, |_| "a");
+
+        let estimate = ApproxCsvDiff::new().diff(
+            Csv::with_reader(csv_data.as_bytes()),
+            Csv::with_reader(csv_data.as_bytes()),
+        )?;
+
+        assert_eq!(estimate.differences(), 0);
+        assert_eq!(estimate.error_bound(), 0);
+        assert_eq!(estimate.rows_left(), 1_000);
+        Ok(())
+    }
+
+    #[test]
+    fn diff_estimates_are_within_error_bound() -> Result<(), Box<dyn std::error::Error>> {
+        let csv_left = csv(0..10_000, |_| "a");
+        let csv_right = csv(5_000..15_000, |id| if id % 10 == 0 { "b" } else { "a" });
+
+        let estimate = ApproxCsvDiffBuilder::new()
+            .relative_error(0.1)
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )?;
+
+        let error_bound = estimate.error_bound();
+        assert!(
+            estimate.added().abs_diff(5_000) <= error_bound,
+            "{:?}",
+            estimate
+        );
+        assert!(
+            estimate.deleted().abs_diff(5_000) <= error_bound,
+            "{:?}",
+            estimate
+        );
+        assert!(
+            estimate.modified().abs_diff(500) <= error_bound,
+            "{:?}",
+            estimate
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn build_with_invalid_relative_error_is_err() {
+        let res = ApproxCsvDiffBuilder::new().relative_error(0.0).build();
+
+        assert_eq!(
+            res.unwrap_err(),
+            ApproxCsvDiffBuilderError::InvalidRelativeError(0.0)
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use crate::cancellation::CancellationToken;
+use crate::csv_diff::{CsvByteDiffBuilderError, DEFAULT_CHANNEL_CAPACITY};
+use crate::csv_parse_result::{
+    CsvByteRecordWithHash, CsvLeftRightParseResult, CsvParseResult, CsvParseResultLeft,
+    CsvParseResultRight, RecordHash,
+};
+use crate::diff_result::DiffByteRecordsIterator;
+use crate::empty_csv::CsvHeaderInfo;
+use crate::field_comparator::{FieldComparator, FieldComparators, IgnoreComparator};
+use crate::key_stats::{KeyStats, KeyStatsCollector};
+use crate::simd::KeyHasher;
+use crate::task_panic::{catch_panic, TaskPanicError};
+use arrow_array::RecordBatch;
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::ArrowError;
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io;
+use std::sync::Arc;
+use xxhash_rust::xxh3::xxh3_128;
+
+/// Compare two streams of Arrow [`RecordBatch`]es by primary key, without converting them to CSV first.
+///
+/// Every row is turned into a [`csv::ByteRecord`] directly, which is then hashed and compared exactly like the
+/// records of [`CsvByteDiff`](crate::csv_diff::CsvByteDiff), so the result is the same [`DiffByteRecordsIterator`].
+/// The column names of the first batch are treated as the header and rows are numbered like the lines of a CSV
+/// with a header (the first row of the first batch is on line 2).
+///
+/// Values are formatted with [`arrow_cast::display`]: `NULL` becomes an empty field, strings are taken as they are
+/// and binary values are hex-encoded.
+///
+/// Both streams are consumed on their own thread. This is only available with the Cargo feature `arrow`.
+/// # Example
+/// ```
+/// use csv_diff::arrow_diff::ArrowRecordBatchDiff;
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+/// use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+/// use std::sync::Arc;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let batch_left = RecordBatch::try_from_iter(vec![
+///     ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+///     ("name", Arc::new(StringArray::from(vec!["lemon", "strawberry"])) as ArrayRef),
+/// ])?;
+/// let batch_right = RecordBatch::try_from_iter(vec![
+///     ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+///     ("name", Arc::new(StringArray::from(vec!["lemon", "cherry"])) as ArrayRef),
+/// ])?;
+///
+/// let diff_byte_records = ArrowRecordBatchDiff::new()
+///     .diff(vec![Ok(batch_left)], vec![Ok(batch_right)])
+///     .collect::<csv::Result<Vec<_>>>()?;
+///
+/// assert_eq!(
+///     diff_byte_records,
+///     vec![DiffByteRecord::Modify {
+///         delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "strawberry"]), 3),
+///         add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "cherry"]), 3),
+///         field_indices: vec![1],
+///     }]
+/// );
+/// Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrowRecordBatchDiff {
+    primary_key_columns: HashSet<usize>,
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+}
+
+impl Default for ArrowRecordBatchDiff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrowRecordBatchDiff {
+    /// Create an `ArrowRecordBatchDiff` with column 0 as primary key.
+    pub fn new() -> Self {
+        Self {
+            primary_key_columns: std::iter::once(0).collect(),
+            field_comparators: FieldComparators::default(),
+            cancellation_token: CancellationToken::default(),
+        }
+    }
+
+    /// Compare the record batches of `batches_left` with those of `batches_right` lazily.
+    ///
+    /// An error of a stream is returned as [`csv::Error`] of kind [`csv::ErrorKind::Io`].
+    pub fn diff<L, R>(&self, batches_left: L, batches_right: R) -> DiffByteRecordsIterator
+    where
+        L: IntoIterator<Item = Result<RecordBatch, ArrowError>>,
+        L::IntoIter: Send + 'static,
+        R: IntoIterator<Item = Result<RecordBatch, ArrowError>>,
+        R::IntoIter: Send + 'static,
+    {
+        let (sender_right, receiver) = bounded(DEFAULT_CHANNEL_CAPACITY);
+        let sender_left = sender_right.clone();
+        let (sender_records_recycle, receiver_records_recycle) = unbounded();
+        let (sender_header_info_left, receiver_header_info_left) = bounded(1);
+        let (sender_header_info_right, receiver_header_info_right) = bounded(1);
+        let (sender_key_stats_left, receiver_key_stats_left) = bounded(1);
+        let (sender_key_stats_right, receiver_key_stats_right) = bounded(1);
+        // record batches have no malformed records, that could be skipped
+        let (_, receiver_skipped_records_left) = bounded(1);
+        let (_, receiver_skipped_records_right) = bounded(1);
+        let (sender_panic, receiver_panic) = unbounded();
+
+        spawn_convert_hash_and_send::<_, CsvParseResultLeft<CsvByteRecordWithHash>>(
+            batches_left.into_iter(),
+            RecordBatchSender {
+                sender: sender_left,
+                receiver_records_recycle: receiver_records_recycle.clone(),
+                sender_header_info: sender_header_info_left,
+                sender_key_stats: sender_key_stats_left,
+                sender_panic: sender_panic.clone(),
+                cancellation_token: self.cancellation_token.clone(),
+            },
+            self.primary_key_columns.clone(),
+        );
+        spawn_convert_hash_and_send::<_, CsvParseResultRight<CsvByteRecordWithHash>>(
+            batches_right.into_iter(),
+            RecordBatchSender {
+                sender: sender_right,
+                receiver_records_recycle,
+                sender_header_info: sender_header_info_right,
+                sender_key_stats: sender_key_stats_right,
+                sender_panic,
+                cancellation_token: self.cancellation_token.clone(),
+            },
+            self.primary_key_columns.clone(),
+        );
+
+        DiffByteRecordsIterator::new(
+            receiver,
+            sender_records_recycle,
+            receiver_header_info_left,
+            receiver_header_info_right,
+            receiver_key_stats_left,
+            receiver_key_stats_right,
+            receiver_skipped_records_left,
+            receiver_skipped_records_right,
+            receiver_panic,
+        )
+        .with_field_comparators(self.field_comparators.clone())
+        .with_cancellation_token(self.cancellation_token.clone())
+    }
+}
+
+/// Create an [`ArrowRecordBatchDiff`] with configuration options.
+#[derive(Debug, Default)]
+pub struct ArrowRecordBatchDiffBuilder {
+    arrow_record_batch_diff: ArrowRecordBatchDiff,
+}
+
+impl ArrowRecordBatchDiffBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn primary_key_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        self.arrow_record_batch_diff.primary_key_columns = columns.into_iter().collect();
+        self
+    }
+
+    /// Compare the fields of column `column` with `comparator` instead of byte-wise.
+    ///
+    /// See [`CsvByteDiffBuilder::field_comparator`](crate::csv_diff::CsvByteDiffBuilder::field_comparator).
+    pub fn field_comparator(
+        mut self,
+        column: usize,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.arrow_record_batch_diff
+            .field_comparators
+            .insert(column, Arc::new(comparator));
+        self
+    }
+
+    /// Don't report changes in the columns `columns` as [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+    ///
+    /// See [`CsvByteDiffBuilder::ignore_columns_for_modify`](crate::csv_diff::CsvByteDiffBuilder::ignore_columns_for_modify).
+    pub fn ignore_columns_for_modify(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        let ignore_comparator: Arc<dyn FieldComparator> = Arc::new(IgnoreComparator);
+        for column in columns {
+            self.arrow_record_batch_diff
+                .field_comparators
+                .insert(column, Arc::clone(&ignore_comparator));
+        }
+        self
+    }
+
+    /// Check `cancellation_token` while comparing, so that the comparison can be cancelled from another thread.
+    ///
+    /// See [`CancellationToken`](crate::cancellation::CancellationToken) for more information.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.arrow_record_batch_diff.cancellation_token = cancellation_token;
+        self
+    }
+
+    pub fn build(self) -> Result<ArrowRecordBatchDiff, CsvByteDiffBuilderError> {
+        if self.arrow_record_batch_diff.primary_key_columns.is_empty() {
+            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+        } else {
+            Ok(self.arrow_record_batch_diff)
+        }
+    }
+}
+
+/// The channels of one side of an [`ArrowRecordBatchDiff`].
+struct RecordBatchSender {
+    sender: Sender<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
+    receiver_records_recycle: Receiver<csv::ByteRecord>,
+    sender_header_info: Sender<CsvHeaderInfo>,
+    sender_key_stats: Sender<KeyStats>,
+    sender_panic: Sender<TaskPanicError>,
+    cancellation_token: CancellationToken,
+}
+
+fn spawn_convert_hash_and_send<I, P>(
+    batches: I,
+    record_batch_sender: RecordBatchSender,
+    primary_key_columns: HashSet<usize>,
+) where
+    I: Iterator<Item = Result<RecordBatch, ArrowError>> + Send + 'static,
+    P: CsvParseResult<CsvLeftRightParseResult<CsvByteRecordWithHash>, CsvByteRecordWithHash>,
+{
+    std::thread::spawn(move || {
+        // keep the channel connected until a panic has been reported, so that the receiving side
+        // doesn't mistake a panic for the regular end of the record batches
+        let _sender_keep_alive = record_batch_sender.sender.clone();
+        let sender_panic = record_batch_sender.sender_panic.clone();
+        let res = catch_panic(move || {
+            convert_hash_and_send::<I, P>(batches, record_batch_sender, &primary_key_columns)
+        });
+        if let Err(task_panic_err) = res {
+            // we ignore any sending errors - the receiver is gone anyway
+            let _ = sender_panic.send(task_panic_err);
+        }
+    });
+}
+
+fn convert_hash_and_send<I, P>(
+    batches: I,
+    record_batch_sender: RecordBatchSender,
+    primary_key_columns: &HashSet<usize>,
+) where
+    I: Iterator<Item = Result<RecordBatch, ArrowError>>,
+    P: CsvParseResult<CsvLeftRightParseResult<CsvByteRecordWithHash>, CsvByteRecordWithHash>,
+{
+    let RecordBatchSender {
+        sender,
+        receiver_records_recycle,
+        sender_header_info,
+        sender_key_stats,
+        cancellation_token,
+        ..
+    } = record_batch_sender;
+    let send_err = |err: ArrowError| {
+        // we ignore any sending errors - the receiver is gone anyway
+        let _ = sender.send(
+            P::new(CsvByteRecordWithHash::new(
+                Err(io::Error::new(io::ErrorKind::Other, err).into()),
+                RecordHash::new(0, 0),
+            ))
+            .into_payload(),
+        );
+    };
+    let fields_as_key: Vec<_> = primary_key_columns.iter().copied().collect();
+    let mut key_hasher = KeyHasher::default();
+    let mut key_stats_collector = KeyStatsCollector::new(primary_key_columns);
+    let mut sender_header_info = Some(sender_header_info);
+    let mut line = 1;
+    let mut field = String::new();
+    let format_options = FormatOptions::default();
+
+    'batches: for batch in batches {
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(err) => {
+                send_err(err);
+                break;
+            }
+        };
+        if let Some(sender_header_info) = sender_header_info.take() {
+            let headers = batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect();
+            // we ignore any sending errors - the receiver is only interested in it for the run report
+            let _ = sender_header_info.send(CsvHeaderInfo::from_headers(headers));
+        }
+        let formatters = match batch
+            .columns()
+            .iter()
+            .map(|column| ArrayFormatter::try_new(column.as_ref(), &format_options))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(formatters) => formatters,
+            Err(err) => {
+                send_err(err);
+                break;
+            }
+        };
+        for row in 0..batch.num_rows() {
+            if cancellation_token.should_stop() {
+                break 'batches;
+            }
+            let mut record = receiver_records_recycle
+                .try_recv()
+                .unwrap_or_else(|_| csv::ByteRecord::new());
+            record.clear();
+            for formatter in &formatters {
+                field.clear();
+                if let Err(err) = write!(field, "{}", formatter.value(row)) {
+                    send_err(ArrowError::ExternalError(Box::new(err)));
+                    break 'batches;
+                }
+                record.push_field(field.as_bytes());
+            }
+            line += 1;
+            let mut pos = csv::Position::new();
+            pos.set_line(line).set_record(line - 1);
+            record.set_position(Some(pos));
+
+            let key = key_hasher.hash_key_fields(&record, &fields_as_key);
+            let hash_record = xxh3_128(record.as_slice());
+            key_stats_collector.record(&record);
+            if sender
+                .send(
+                    P::new(CsvByteRecordWithHash::new(
+                        Ok(record),
+                        RecordHash::new(key, hash_record),
+                    ))
+                    .into_payload(),
+                )
+                .is_err()
+            {
+                // when the receiver is gone, it doesn't make sense to continue here
+                break 'batches;
+            }
+        }
+    }
+    if let Some(sender_header_info) = sender_header_info {
+        // there has been no batch at all, which is like an empty CSV
+        let _ = sender_header_info.send(CsvHeaderInfo::from_headers(csv::ByteRecord::new()));
+    }
+    // we ignore any sending errors - the receiver is only interested in it for the run report
+    let _ = sender_key_stats.send(key_stats_collector.finish());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    use crate::error_code::ErrorCode;
+    use arrow_array::{ArrayRef, Int64Array, StringArray};
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+
+    fn fruits(ids: Vec<i64>, names: Vec<Option<&str>>) -> Result<RecordBatch, ArrowError> {
+        RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(Int64Array::from(ids)) as ArrayRef),
+            ("name", Arc::new(StringArray::from(names)) as ArrayRef),
+        ])
+    }
+
+    #[test]
+    fn diff_batches_rows_are_numbered_across_batches() -> Result<(), Box<dyn Error>> {
+        let batches_left = vec![
+            fruits(vec![1, 2], vec![Some("lemon"), Some("cherry")]),
+            fruits(vec![3], vec![Some("apple")]),
+        ];
+        let batches_right = vec![fruits(
+            vec![1, 2, 4],
+            vec![Some("lemon"), None, Some("kiwi")],
+        )];
+
+        let mut diff_byte_records = ArrowRecordBatchDiff::new()
+            .diff(batches_left, batches_right)
+            .collect::<csv::Result<Vec<_>>>()?;
+        diff_byte_records.sort_by_key(|diff_byte_record| match diff_byte_record {
+            DiffByteRecord::Add(add) => (add.line(), 1),
+            DiffByteRecord::Modify { delete, .. } => (delete.line(), 0),
+            DiffByteRecord::Delete(delete) => (delete.line(), 0),
+            DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+        });
+
+        assert_eq!(
+            diff_byte_records,
+            vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "cherry"]), 3),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", ""]), 3),
+                    field_indices: vec![1]
+                },
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "apple"]),
+                    4
+                )),
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["4", "kiwi"]),
+                    4
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diff_batches_with_err_in_stream_is_io_err() {
+        let batches_left = vec![fruits(vec![1], vec![Some("lemon")])];
+        let batches_right = vec![Err(ArrowError::ComputeError("broken".to_owned()))];
+
+        let res = ArrowRecordBatchDiffBuilder::new()
+            .primary_key_columns([0])
+            .build()
+            .unwrap()
+            .diff(batches_left, batches_right)
+            .collect::<csv::Result<Vec<_>>>();
+
+        assert_eq!(res.unwrap_err().code(), "CSVDIFF_IO");
+    }
+}
@@ -0,0 +1,263 @@
+//! Compare two CSV files from the command line (see `csv-diff --help`).
+//!
+//! Exits with `0`, if the CSVs are equal, with `1`, if they differ, and with `2` on any error.
+
+use csv_diff::csv::Csv;
+use csv_diff::csv_diff::CsvByteDiffLocalBuilder;
+use csv_diff::diff_result::{CsvWriteStyle, DiffByteRecords};
+use csv_diff::error_code::ErrorCode;
+use csv_diff::json_lines::JsonLinesWriter;
+use csv_diff::unified_diff::UnifiedDiffRenderer;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Compare two CSV files by their primary key.
+
+Usage: csv-diff [OPTIONS] <LEFT> <RIGHT>
+
+Options:
+  -k, --key <COLUMNS>        Comma-separated indices of the primary key columns, starting at 0 [default: 0]
+  -d, --delimiter <CHAR>     Field delimiter of both CSVs (use '\\t' for tabs) [default: ,]
+      --no-headers           Treat the first line as a record instead of a header
+  -f, --format <FORMAT>      Output format: unified, csv or json [default: unified]
+  -q, --quiet                Don't print the differences, only set the exit code
+  -h, --help                 Print this help
+  -V, --version              Print the version
+
+Exit codes: 0 = equal, 1 = different, 2 = error";
+
+const EXIT_EQUAL: u8 = 0;
+const EXIT_DIFFERENT: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum OutputFormat {
+    Unified,
+    Csv,
+    Json,
+}
+
+#[derive(Debug)]
+struct Args {
+    key: Vec<usize>,
+    delimiter: u8,
+    has_headers: bool,
+    format: OutputFormat,
+    is_quiet: bool,
+    left: OsString,
+    right: OsString,
+}
+
+#[derive(Debug)]
+enum Command {
+    Diff(Args),
+    Help,
+    Version,
+}
+
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n\nFor more information, try '--help'.", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn parse_args(mut args: impl Iterator<Item = OsString>) -> Result<Command, UsageError> {
+    let mut key = vec![0];
+    let mut delimiter = b',';
+    let mut has_headers = true;
+    let mut format = OutputFormat::Unified;
+    let mut is_quiet = false;
+    let mut paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let arg_str = arg.to_string_lossy();
+        // `--opt=value` is the same as `--opt value`
+        let (name, inline_value) = match arg_str.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+            _ => (arg_str.as_ref(), None),
+        };
+        let mut value = |name: &str| match inline_value.clone() {
+            Some(value) => Ok(value),
+            None => args
+                .next()
+                .map(|value| value.to_string_lossy().into_owned())
+                .ok_or_else(|| UsageError(format!("missing value for '{}'", name))),
+        };
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "-k" | "--key" => key = parse_key(&value(name)?)?,
+            "-d" | "--delimiter" => delimiter = parse_delimiter(&value(name)?)?,
+            "--no-headers" => has_headers = false,
+            "-f" | "--format" => {
+                format = match value(name)?.as_str() {
+                    "unified" => OutputFormat::Unified,
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    other => return Err(UsageError(format!("unknown output format '{}'", other))),
+                }
+            }
+            "-q" | "--quiet" => is_quiet = true,
+            _ if name.starts_with('-') => {
+                return Err(UsageError(format!("unknown option '{}'", arg_str)))
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    let mut paths = paths.into_iter();
+    match (paths.next(), paths.next(), paths.next()) {
+        (Some(left), Some(right), None) => Ok(Command::Diff(Args {
+            key,
+            delimiter,
+            has_headers,
+            format,
+            is_quiet,
+            left,
+            right,
+        })),
+        (_, _, None) => Err(UsageError(
+            "expected the paths of the LEFT and the RIGHT CSV".to_owned(),
+        )),
+        _ => Err(UsageError("expected exactly two paths".to_owned())),
+    }
+}
+
+fn parse_key(columns: &str) -> Result<Vec<usize>, UsageError> {
+    columns
+        .split(',')
+        .map(|column| {
+            column
+                .trim()
+                .parse()
+                .map_err(|_| UsageError(format!("invalid key column '{}'", column)))
+        })
+        .collect()
+}
+
+fn parse_delimiter(delimiter: &str) -> Result<u8, UsageError> {
+    match delimiter {
+        "\\t" | "\t" => Ok(b'\t'),
+        _ if delimiter.len() == 1 && delimiter.is_ascii() => Ok(delimiter.as_bytes()[0]),
+        _ => Err(UsageError(format!(
+            "the delimiter must be a single ASCII character, but is '{}'",
+            delimiter
+        ))),
+    }
+}
+
+fn open_csv(path: &OsString, args: &Args) -> csv::Result<Csv<File>> {
+    let csv_reader = csv::ReaderBuilder::new()
+        .delimiter(args.delimiter)
+        .has_headers(args.has_headers)
+        .from_path(path)?;
+    Ok(csv_reader.into())
+}
+
+fn diff(args: &Args) -> Result<DiffByteRecords, Box<dyn std::error::Error>> {
+    let mut diff_byte_records = CsvByteDiffLocalBuilder::new()
+        .primary_key_columns(args.key.iter().copied())
+        .build()?
+        .diff(open_csv(&args.left, args)?, open_csv(&args.right, args)?)?;
+    diff_byte_records.sort_by_line();
+    Ok(diff_byte_records)
+}
+
+fn write_diff<W: Write>(
+    diff_byte_records: &DiffByteRecords,
+    args: &Args,
+    wtr: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match args.format {
+        OutputFormat::Unified => {
+            let mut wtr = wtr;
+            let renderer = UnifiedDiffRenderer::new().delimiter(args.delimiter);
+            wtr.write_all(renderer.render(diff_byte_records.iter()).as_bytes())?;
+            wtr.flush()?;
+        }
+        OutputFormat::Csv => {
+            let mut csv_writer = csv::WriterBuilder::new()
+                .delimiter(args.delimiter)
+                .flexible(true)
+                .from_writer(wtr);
+            diff_byte_records.write_csv(&mut csv_writer, CsvWriteStyle::Split)?;
+        }
+        OutputFormat::Json => {
+            let mut json_lines_writer = JsonLinesWriter::new(wtr);
+            for diff_record in diff_byte_records.iter() {
+                json_lines_writer.write_diff_byte_record(diff_record)?;
+            }
+            json_lines_writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<bool, Box<dyn std::error::Error>> {
+    let diff_byte_records = diff(args)?;
+    let is_equal = diff_byte_records.as_slice().is_empty();
+    if !args.is_quiet && !is_equal {
+        write_diff(
+            &diff_byte_records,
+            args,
+            io::BufWriter::new(io::stdout().lock()),
+        )?;
+    }
+    Ok(is_equal)
+}
+
+fn is_broken_pipe(err: &(dyn std::error::Error + 'static)) -> bool {
+    let io_err = match err.downcast_ref::<csv::Error>() {
+        Some(csv_err) => match csv_err.kind() {
+            csv::ErrorKind::Io(io_err) => io_err,
+            _ => return false,
+        },
+        None => match err.downcast_ref::<io::Error>() {
+            Some(io_err) => io_err,
+            None => return false,
+        },
+    };
+    io_err.kind() == io::ErrorKind::BrokenPipe
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args_os().skip(1)) {
+        Ok(Command::Diff(args)) => args,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::from(EXIT_EQUAL);
+        }
+        Ok(Command::Version) => {
+            println!("csv-diff {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::from(EXIT_EQUAL);
+        }
+        Err(usage_err) => {
+            eprintln!("csv-diff: {}", usage_err);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    match run(&args) {
+        Ok(true) => ExitCode::from(EXIT_EQUAL),
+        Ok(false) => ExitCode::from(EXIT_DIFFERENT),
+        Err(err) => {
+            // a closed pipe (e.g. `csv-diff a.csv b.csv | head`) is not an error of the comparison
+            if is_broken_pipe(err.as_ref()) {
+                return ExitCode::from(EXIT_DIFFERENT);
+            }
+            match err.downcast_ref::<csv::Error>() {
+                Some(csv_err) => eprintln!("csv-diff: {} ({})", csv_err, csv_err.code()),
+                None => eprintln!("csv-diff: {}", err),
+            }
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A byte order mark (BOM), that is found at the start of CSV data.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Bom {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Bom {
+    /// The bytes of the byte order mark.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => b"\xef\xbb\xbf",
+            Self::Utf16Le => b"\xff\xfe",
+            Self::Utf16Be => b"\xfe\xff",
+        }
+    }
+
+    fn detect(prefix: &[u8]) -> Option<Self> {
+        [Self::Utf8, Self::Utf16Le, Self::Utf16Be]
+            .into_iter()
+            .find(|bom| prefix.starts_with(bom.as_bytes()))
+    }
+}
+
+/// A reader, that strips a UTF-8 or UTF-16 byte order mark (BOM) at the start of the wrapped reader,
+/// so that it doesn't become part of the first header or record (and the first key never matches).
+///
+/// Note that only the BOM is stripped; UTF-16 data isn't transcoded
+/// (see `TranscodingReader` with the Cargo feature `encoding` for that).
+/// It implements [`Seek`] if the wrapped reader does, with all positions relative to the data after the BOM,
+/// so it can be used with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal) as well as with
+/// [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+/// Usually, it is created with [`Csv::with_reader_bom_stripped`](crate::csv::Csv::with_reader_bom_stripped)
+/// or [`Csv::with_reader_seek_bom_stripped`](crate::csv::Csv::with_reader_seek_bom_stripped).
+#[derive(Debug)]
+pub struct BomStrippingReader<R> {
+    inner: R,
+    bom: Option<Bom>,
+    detected: bool,
+    // the bytes, that have been read from `inner` in order to detect the BOM
+    prefix: [u8; 3],
+    prefix_pos: usize,
+    prefix_len: usize,
+}
+
+impl<R> BomStrippingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bom: None,
+            detected: false,
+            prefix: [0; 3],
+            prefix_pos: 0,
+            prefix_len: 0,
+        }
+    }
+
+    /// The byte order mark, that has been stripped. This is always `None` before the first read or seek.
+    pub fn bom(&self) -> Option<Bom> {
+        self.bom
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn bom_len(&self) -> usize {
+        self.bom.map_or(0, |bom| bom.as_bytes().len())
+    }
+}
+
+impl<R: Read> BomStrippingReader<R> {
+    fn detect_bom(&mut self) -> io::Result<()> {
+        if self.detected {
+            return Ok(());
+        }
+        while self.prefix_len < self.prefix.len() {
+            match self.inner.read(&mut self.prefix[self.prefix_len..]) {
+                Ok(0) => break,
+                Ok(bytes_read) => self.prefix_len += bytes_read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        self.bom = Bom::detect(&self.prefix[..self.prefix_len]);
+        self.prefix_pos = self.bom_len();
+        self.detected = true;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BomStrippingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.detect_bom()?;
+        if self.prefix_pos < self.prefix_len {
+            let prefix = &self.prefix[self.prefix_pos..self.prefix_len];
+            let len = prefix.len().min(buf.len());
+            buf[..len].copy_from_slice(&prefix[..len]);
+            self.prefix_pos += len;
+            return Ok(len);
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read + Seek> Seek for BomStrippingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.detect_bom()?;
+        let bom_len = self.bom_len() as u64;
+        let inner_pos = match pos {
+            SeekFrom::Start(offset) => self.inner.seek(SeekFrom::Start(offset + bom_len))?,
+            SeekFrom::Current(offset) => {
+                // `inner` is ahead by the bytes of the prefix, that haven't been read yet
+                let unread = (self.prefix_len - self.prefix_pos) as i64;
+                self.inner.seek(SeekFrom::Current(offset - unread))?
+            }
+            SeekFrom::End(offset) => self.inner.seek(SeekFrom::End(offset))?,
+        };
+        self.prefix_pos = self.prefix_len;
+        inner_pos.checked_sub(bom_len).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a position before the start of the data",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Cursor;
+
+    #[test]
+    fn utf8_bom_is_stripped_and_positions_are_relative_to_data() -> io::Result<()> {
+        let mut reader = BomStrippingReader::new(Cursor::new(b"\xef\xbb\xbfid,name\n1,lemon"));
+
+        let mut data = String::new();
+        reader.read_to_string(&mut data)?;
+        let pos = reader.seek(SeekFrom::Start(8))?;
+        let mut data_after_seek = String::new();
+        reader.read_to_string(&mut data_after_seek)?;
+
+        assert_eq!(reader.bom(), Some(Bom::Utf8));
+        assert_eq!(data, "id,name\n1,lemon");
+        assert_eq!(pos, 8);
+        assert_eq!(data_after_seek, "1,lemon");
+        Ok(())
+    }
+
+    #[test]
+    fn utf16_bom_is_stripped_and_short_data_without_bom_is_kept() -> io::Result<()> {
+        let mut utf16_data = Vec::new();
+        let mut utf16_reader = BomStrippingReader::new(b"\xfe\xff\x00a".as_slice());
+        utf16_reader.read_to_end(&mut utf16_data)?;
+        let mut short_data = Vec::new();
+        let mut short_reader = BomStrippingReader::new(b"\xef\xbb".as_slice());
+        short_reader.read_to_end(&mut short_data)?;
+
+        assert_eq!(
+            (utf16_reader.bom(), utf16_data),
+            (Some(Bom::Utf16Be), b"\x00a".to_vec())
+        );
+        assert_eq!(
+            (short_reader.bom(), short_data),
+            (None, b"\xef\xbb".to_vec())
+        );
+        Ok(())
+    }
+}
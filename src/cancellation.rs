@@ -0,0 +1,166 @@
+use crate::error_code::ErrorCode;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Cancels a running comparison of CSVs from another thread.
+///
+/// A token is registered with [`CsvByteDiffLocalBuilder::cancellation_token`](crate::csv_diff::CsvByteDiffLocalBuilder::cancellation_token)
+/// or [`CsvByteDiffBuilder::cancellation_token`](crate::csv_diff::CsvByteDiffBuilder::cancellation_token).
+/// The hashing tasks check it between records and stop reading, as soon as it has been cancelled.
+/// The comparison then fails with a [`CancelledError`] (after all tasks have finished), so no thread is left behind.
+///
+/// Clones of a token share their state, so cancelling one of them cancels all of them.
+/// A token, that has been cancelled, stays cancelled; use a new token for the next comparison.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffBuilder, csv::Csv};
+use csv_diff::cancellation::CancellationToken;
+use csv_diff::error_code::ErrorCode;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,nut";
+
+let cancellation_token = CancellationToken::new();
+let mut diff_iter = CsvByteDiffBuilder::new()
+    .cancellation_token(cancellation_token.clone())
+    .build()?
+    .diff(
+        Csv::with_reader(csv_left.as_bytes()),
+        Csv::with_reader(csv_right.as_bytes()),
+    );
+
+// usually, this is done from another thread
+cancellation_token.cancel();
+
+let err = diff_iter.next().expect("an error").unwrap_err();
+assert_eq!(err.code(), "CSVDIFF_CANCELLED");
+assert!(diff_iter.next().is_none());
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all comparisons, that use this token (or one of its clones).
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Create a clone of this token, that additionally times out at `deadline`.
+    pub(crate) fn with_deadline(&self, deadline: Instant) -> Self {
+        Self {
+            is_cancelled: Arc::clone(&self.is_cancelled),
+            deadline: Some(deadline),
+        }
+    }
+
+    pub(crate) fn is_timed_out(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
+    /// Whether the hashing tasks should stop reading, because the comparison has been cancelled or has timed out.
+    pub(crate) fn should_stop(&self) -> bool {
+        self.is_cancelled() || self.is_timed_out()
+    }
+
+    /// Return a [`CancelledError`], if this token has been cancelled.
+    pub(crate) fn check(&self) -> Result<(), CancelledError> {
+        if self.is_cancelled() {
+            Err(CancelledError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The comparison has been cancelled with a [`CancellationToken`].
+///
+/// When comparing CSVs, it is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is a `CancelledError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+#[error("the comparison of the CSVs has been cancelled")]
+pub struct CancelledError;
+
+impl ErrorCode for CancelledError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_CANCELLED"
+    }
+}
+
+impl From<CancelledError> for csv::Error {
+    fn from(err: CancelledError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+/// The comparison has not finished within the [`max_duration`](crate::csv_diff::CsvByteDiffBuilder::max_duration) of its builder.
+///
+/// It is returned as the last item of a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator),
+/// after all differences, that have been found before the deadline, so the result so far is never lost.
+/// Like [`CancelledError`], it is wrapped in a [`csv::Error`] of kind [`csv::ErrorKind::Io`].
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+#[error("the comparison of the CSVs has timed out")]
+pub struct TimedOutError;
+
+impl ErrorCode for TimedOutError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_TIMED_OUT"
+    }
+}
+
+impl From<TimedOutError> for csv::Error {
+    fn from(err: TimedOutError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_clone_cancels_original() {
+        let cancellation_token = CancellationToken::new();
+
+        cancellation_token.clone().cancel();
+
+        assert!(cancellation_token.is_cancelled());
+        assert_eq!(cancellation_token.check(), Err(CancelledError));
+    }
+
+    #[test]
+    fn with_deadline_in_the_past_is_timed_out_but_not_cancelled() {
+        let cancellation_token = CancellationToken::new();
+
+        let with_deadline = cancellation_token.with_deadline(Instant::now());
+
+        assert!(with_deadline.is_timed_out());
+        assert!(with_deadline.should_stop());
+        assert!(!with_deadline.is_cancelled());
+        assert!(!cancellation_token.should_stop());
+    }
+}
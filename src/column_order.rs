@@ -0,0 +1,80 @@
+/// The order, in which the columns of a CSV have been compared, when its columns have been put into
+/// the canonical order (sorted by header name, see [`CsvByteDiffBuilder::canonical_column_order`](crate::csv_diff::CsvByteDiffBuilder::canonical_column_order)).
+///
+/// Column `i` of the compared (and emitted) records is column [`original_idx(i)`](ColumnOrder::original_idx) of the CSV.
+/// Columns with the same header name keep their relative order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ColumnOrder {
+    original_indices: Vec<usize>,
+}
+
+impl ColumnOrder {
+    pub(crate) fn by_header_name(headers: &csv::ByteRecord) -> Self {
+        let mut original_indices: Vec<usize> = (0..headers.len()).collect();
+        original_indices.sort_by_key(|&idx| &headers[idx]);
+        Self { original_indices }
+    }
+
+    /// The index of the column in the CSV, that has been compared as column `canonical_idx`.
+    pub fn original_idx(&self, canonical_idx: usize) -> Option<usize> {
+        self.original_indices.get(canonical_idx).copied()
+    }
+
+    /// The original column indices in canonical order.
+    pub fn original_indices(&self) -> &[usize] {
+        &self.original_indices
+    }
+
+    /// Whether the columns of the CSV already are in canonical order.
+    pub fn is_identity(&self) -> bool {
+        self.original_indices
+            .iter()
+            .enumerate()
+            .all(|(canonical_idx, &original_idx)| canonical_idx == original_idx)
+    }
+
+    /// Put the fields of `csv_record` into canonical order, using `scratch` as intermediate buffer.
+    ///
+    /// Fields without header (of a record, that has more fields than the header) are appended in their original order.
+    pub(crate) fn apply(&self, csv_record: &mut csv::ByteRecord, scratch: &mut csv::ByteRecord) {
+        if self.is_identity() {
+            return;
+        }
+        scratch.clear();
+        for &original_idx in &self.original_indices {
+            if let Some(field) = csv_record.get(original_idx) {
+                scratch.push_field(field);
+            }
+        }
+        for field in csv_record.iter().skip(self.original_indices.len()) {
+            scratch.push_field(field);
+        }
+        scratch.set_position(csv_record.position().cloned());
+        std::mem::swap(csv_record, scratch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn apply_sorts_fields_by_header_name_and_keeps_position() {
+        let column_order =
+            ColumnOrder::by_header_name(&csv::ByteRecord::from(vec!["name", "id", "kind", "id"]));
+        let mut csv_record = csv::ByteRecord::from(vec!["lemon", "1", "fruit", "2", "extra"]);
+        let mut pos = csv::Position::new();
+        pos.set_line(3);
+        csv_record.set_position(Some(pos));
+
+        column_order.apply(&mut csv_record, &mut csv::ByteRecord::new());
+
+        assert_eq!(column_order.original_indices(), &[1, 3, 2, 0]);
+        assert_eq!(
+            csv_record,
+            csv::ByteRecord::from(vec!["1", "2", "fruit", "lemon", "extra"])
+        );
+        assert_eq!(csv_record.position().map(csv::Position::line), Some(3));
+    }
+}
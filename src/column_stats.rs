@@ -0,0 +1,147 @@
+use crate::diff_row::DiffByteRecord;
+use std::collections::BTreeMap;
+
+/// How often each column has been modified, i.e. how often each column index appears in the `field_indices`
+/// of [`DiffByteRecord::Modify`](crate::diff_row::DiffByteRecord::Modify)s.
+///
+/// For eagerly compared CSVs, it can be created with [`DiffByteRecords::column_modification_frequencies`](crate::diff_result::DiffByteRecords::column_modification_frequencies).
+/// When streaming, it can be collected from the emitted `DiffByteRecord`s (it implements `Extend` and `FromIterator`).
+/// # Example
+/// ```
+/// use csv_diff::column_stats::ColumnModificationFrequencies;
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+///
+/// let diff_byte_records = vec![
+///     DiffByteRecord::Modify {
+///         delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "1.5"]), 2),
+///         add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "Lemon", "1.6"]), 2),
+///         field_indices: vec![1, 2],
+///     },
+///     DiffByteRecord::Modify {
+///         delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "cherry", "3.0"]), 3),
+///         add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "cherry", "2.9"]), 3),
+///         field_indices: vec![2],
+///     },
+/// ];
+///
+/// let frequencies: ColumnModificationFrequencies = diff_byte_records.iter().collect();
+///
+/// assert_eq!(frequencies.get(2), 2);
+/// assert_eq!(frequencies.get(0), 0);
+/// assert_eq!(frequencies.most_frequent(), vec![(2, 2), (1, 1)]);
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ColumnModificationFrequencies {
+    by_column: BTreeMap<usize, u64>,
+}
+
+impl ColumnModificationFrequencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account for the given difference. Only `Modify`s are taken into account.
+    pub fn record(&mut self, diff_record: &DiffByteRecord) {
+        if let DiffByteRecord::Modify { field_indices, .. } = diff_record {
+            for &field_idx in field_indices {
+                *self.by_column.entry(field_idx).or_default() += 1;
+            }
+        }
+    }
+
+    /// Number of modifications of the column at `column_idx`.
+    pub fn get(&self, column_idx: usize) -> u64 {
+        self.by_column.get(&column_idx).copied().unwrap_or_default()
+    }
+
+    /// Iterate over all modified columns and their number of modifications, ordered by column index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.by_column
+            .iter()
+            .map(|(&column_idx, &count)| (column_idx, count))
+    }
+
+    /// Return all modified columns and their number of modifications, where the most frequently modified
+    /// column comes first (ties are ordered by column index).
+    pub fn most_frequent(&self) -> Vec<(usize, u64)> {
+        let mut most_frequent = self.iter().collect::<Vec<_>>();
+        most_frequent.sort_by(|(idx_a, count_a), (idx_b, count_b)| {
+            count_b.cmp(count_a).then(idx_a.cmp(idx_b))
+        });
+        most_frequent
+    }
+
+    /// Number of columns, that have been modified at least once.
+    pub fn len(&self) -> usize {
+        self.by_column.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_column.is_empty()
+    }
+}
+
+impl<'a> Extend<&'a DiffByteRecord> for ColumnModificationFrequencies {
+    fn extend<T: IntoIterator<Item = &'a DiffByteRecord>>(&mut self, iter: T) {
+        for diff_record in iter {
+            self.record(diff_record);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a DiffByteRecord> for ColumnModificationFrequencies {
+    fn from_iter<T: IntoIterator<Item = &'a DiffByteRecord>>(iter: T) -> Self {
+        let mut frequencies = Self::new();
+        frequencies.extend(iter);
+        frequencies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_result::DiffByteRecords;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn add_and_delete_are_not_counted() {
+        let diff_records = DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b"]),
+                2,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["c", "d"]),
+                3,
+            )),
+        ]);
+
+        let frequencies = diff_records.column_modification_frequencies();
+
+        assert!(frequencies.is_empty());
+        assert_eq!(frequencies.most_frequent(), vec![]);
+    }
+
+    #[test]
+    fn most_frequent_ties_are_ordered_by_column_idx() {
+        let modify = |field_indices: Vec<usize>| DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c", "d"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["w", "x", "y", "z"]), 2),
+            field_indices,
+        };
+        let diff_records = DiffByteRecords::from(vec![
+            modify(vec![3, 1]),
+            modify(vec![1, 2, 3]),
+            modify(vec![0]),
+        ]);
+
+        let frequencies = diff_records.column_modification_frequencies();
+
+        assert_eq!(frequencies.len(), 4);
+        assert_eq!(
+            frequencies.most_frequent(),
+            vec![(1, 2), (3, 2), (0, 1), (2, 1)]
+        );
+    }
+}
@@ -1,9 +1,31 @@
-use std::io::{Cursor, Read, Seek};
+use crate::bom::BomStrippingReader;
+use crate::dialect::{Dialect, DEFAULT_SNIFF_LEN};
+use crate::empty_csv::PhysicalLines;
+use crate::error_policy::ErrorPolicy;
+#[cfg(feature = "simd-parser")]
+use crate::simd_parser::SimdParser;
+use crate::spooled::{SpooledReader, DEFAULT_SPOOL_MEMORY_THRESHOLD};
+use crate::throttle::ThrottledReader;
+use std::fmt;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::num::NonZeroU64;
+use std::sync::Arc;
 
 pub struct Csv<R> {
     csv_reader: csv::Reader<R>,
+    dialect: Option<Dialect>,
+    physical_lines: Option<PhysicalLines>,
+    error_policy: ErrorPolicy,
+    line_offset: u64,
+    normalizer: Option<RecordNormalizer>,
+    #[cfg(feature = "simd-parser")]
+    simd_parser: Option<SimdParser>,
 }
 
+/// A reader, that first yields the bytes, that have been inspected in order to detect the [`Dialect`] of CSV data,
+/// and then the rest of the wrapped reader (see [`Csv::with_reader_sniffed`]).
+pub type SniffedReader<R> = io::Chain<Cursor<Vec<u8>>, R>;
+
 impl<R: Read + Seek + Send> Csv<R> {
     /// Create a new `Csv` with something that can read Csv data and implements [`CsvReadSeek`].
     /// # Example: use `Csv` together with `CsvByteDiffLocal` to compare CSV data
@@ -42,19 +64,191 @@ Ok(())
     pub fn with_reader_seek<RSeek: CsvReadSeek<R>>(reader: RSeek) -> Self {
         Self {
             csv_reader: csv::Reader::from_reader(reader.into_read_seek()),
+            dialect: None,
+            physical_lines: None,
+            error_policy: ErrorPolicy::default(),
+            line_offset: 0,
+            normalizer: None,
+            #[cfg(feature = "simd-parser")]
+            simd_parser: None,
         }
     }
+
+    /// Like [`with_reader_seek`](Csv::with_reader_seek), but detects the [`Dialect`] (delimiter and quote character)
+    /// of the CSV data by inspecting its first [`DEFAULT_SNIFF_LEN`] bytes (see [`Dialect::sniff`]).
+    ///
+    /// The detected dialect is available with [`dialect`](Csv::dialect).
+    /// # Example: compare CSV data of unknown dialects
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv, dialect::Dialect};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = Csv::with_reader_seek_sniffed("id;name;kind\n1;lemon;fruit\n2;strawberry;fruit")?;
+let csv_right = Csv::with_reader_seek_sniffed("id\tname\tkind\n1\tlemon\tfruit\n2\tstrawberry\tnut")?;
+
+assert_eq!(csv_left.dialect(), Some(Dialect::new(b';', b'"')));
+assert_eq!(csv_right.dialect(), Some(Dialect::new(b'\t', b'"')));
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(csv_left, csv_right)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_reader_seek_sniffed<RSeek: CsvReadSeek<R>>(reader: RSeek) -> io::Result<Self> {
+        CsvBuilder::new().build_with_reader_seek_sniffed(reader)
+    }
 }
 
 impl<R: Read> Csv<R> {
+    /// Create a new `Csv` with something that can read Csv data.
+    ///
+    /// This is meant to be used with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff), so the underlying reader is created
+    /// with a larger buffer ([`STREAMING_BUFFER_CAPACITY`]) than the default of the `csv` crate,
+    /// in order to reduce the number of read calls.
+    /// Use [`CsvBuilder`] to choose a different buffer capacity.
     pub fn with_reader(reader: R) -> Self {
-        Self {
-            csv_reader: csv::Reader::from_reader(reader),
-        }
+        CsvBuilder::new()
+            .buffer_capacity(STREAMING_BUFFER_CAPACITY)
+            .build_with_reader(reader)
+    }
+
+    /// Return the header of this `Csv` (or its first record, when it is read without headers), e.g. in order to inspect it
+    /// before comparing.
+    ///
+    /// The header is only read once and then cached by the underlying [`csv::Reader`] (see [`csv::Reader::byte_headers`]),
+    /// so this neither consumes any record nor changes the position of any record.
+    /// For an empty CSV, the header is empty.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let mut csv_left = Csv::with_reader_seek("id,name,kind\n1,lemon,fruit");
+let csv_right = Csv::with_reader_seek("id,name,kind\n1,lemon,nut");
+
+assert_eq!(csv_left.headers()?, &csv::ByteRecord::from(vec!["id", "name", "kind"]));
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(csv_left, csv_right)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn headers(&mut self) -> csv::Result<&csv::ByteRecord> {
+        self.csv_reader.byte_headers()
+    }
+}
+
+impl<R: Read> Csv<SniffedReader<R>> {
+    /// Like [`with_reader`](Csv::with_reader), but detects the [`Dialect`] (delimiter and quote character)
+    /// of the CSV data by inspecting its first [`DEFAULT_SNIFF_LEN`] bytes (see [`Dialect::sniff`]).
+    ///
+    /// The detected dialect is available with [`dialect`](Csv::dialect).
+    pub fn with_reader_sniffed(reader: R) -> io::Result<Self> {
+        CsvBuilder::new()
+            .buffer_capacity(STREAMING_BUFFER_CAPACITY)
+            .build_with_reader_sniffed(reader)
+    }
+}
+
+impl Csv<SpooledReader> {
+    /// Create a new `Csv` with something that can read Csv data, but can't seek in it (e.g. stdin or a pipe),
+    /// in order to compare it with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal).
+    ///
+    /// `reader` is read completely and its data is kept in memory or spooled to a temporary file
+    /// (see [`SpooledReader`]). Use [`CsvBuilder::spool_memory_threshold`] to choose how much data is kept in memory.
+    pub fn with_reader_spooled<R: Read>(reader: R) -> io::Result<Self> {
+        CsvBuilder::new().build_with_reader_spooled(reader)
+    }
+}
+
+impl<R: Read> Csv<BomStrippingReader<R>> {
+    /// Like [`with_reader`](Csv::with_reader), but strips a UTF-8 or UTF-16 byte order mark at the start of `reader`
+    /// (see [`BomStrippingReader`]).
+    pub fn with_reader_bom_stripped(reader: R) -> Self {
+        CsvBuilder::new()
+            .buffer_capacity(STREAMING_BUFFER_CAPACITY)
+            .build_with_reader_bom_stripped(reader)
+    }
+}
+
+impl<R: Read + Seek + Send> Csv<BomStrippingReader<R>> {
+    /// Like [`with_reader_seek`](Csv::with_reader_seek), but strips a UTF-8 or UTF-16 byte order mark at the start
+    /// of `reader` (see [`BomStrippingReader`]).
+    /// # Example: compare CSV data, where only one side starts with a BOM
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "\u{feff}id,name,kind\n1,lemon,fruit";
+let csv_data_right = "id,name,kind\n1,lemon,fruit";
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek_bom_stripped(csv_data_left.as_bytes()),
+    Csv::with_reader_seek_bom_stripped(csv_data_right.as_bytes()),
+)?;
+
+assert!(diff_byte_records.as_slice().is_empty());
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_reader_seek_bom_stripped<RSeek: CsvReadSeek<R>>(reader: RSeek) -> Self {
+        CsvBuilder::new().build_with_reader_seek_bom_stripped(reader)
     }
 }
 
 impl<R> Csv<R> {
+    /// The dialect, that has been detected, when this `Csv` was created with sniffing
+    /// (e.g. with [`Csv::with_reader_sniffed`]), or `None` otherwise.
+    pub fn dialect(&self) -> Option<Dialect> {
+        self.dialect
+    }
+
+    /// How to derive the physical lines of records, if lines starting with a comment character are skipped
+    /// (see [`CsvBuilder::comment`]).
+    pub(crate) fn physical_lines(&self) -> Option<PhysicalLines> {
+        self.physical_lines.clone()
+    }
+
+    /// How to handle malformed records (see [`CsvBuilder::error_policy`]).
+    pub(crate) fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    /// The number of lines, that is added to the line of every record (see [`CsvBuilder::line_offset`]).
+    pub(crate) fn line_offset(&self) -> u64 {
+        self.line_offset
+    }
+
+    /// The transformation, that is applied to every record before it is hashed (see [`CsvBuilder::normalize`]).
+    pub(crate) fn normalizer(&self) -> Option<RecordNormalizer> {
+        self.normalizer.clone()
+    }
+
+    /// How to tokenize the records instead of with the `csv` crate (see [`CsvBuilder::simd_parser`]).
+    #[cfg(feature = "simd-parser")]
+    pub(crate) fn simd_parser(&self) -> Option<SimdParser> {
+        self.simd_parser
+    }
+
+    pub(crate) fn with_error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
     pub fn into_csv_reader(self) -> csv::Reader<R> {
         self.csv_reader
     }
@@ -62,7 +256,538 @@ impl<R> Csv<R> {
 
 impl<R> From<csv::Reader<R>> for Csv<R> {
     fn from(rdr: csv::Reader<R>) -> Self {
-        Self { csv_reader: rdr }
+        Self {
+            csv_reader: rdr,
+            dialect: None,
+            physical_lines: None,
+            error_policy: ErrorPolicy::default(),
+            line_offset: 0,
+            normalizer: None,
+            #[cfg(feature = "simd-parser")]
+            simd_parser: None,
+        }
+    }
+}
+
+/// A transformation of records before they are hashed (see [`CsvBuilder::normalize`]).
+#[derive(Clone)]
+pub(crate) struct RecordNormalizer(Arc<dyn Fn(&mut csv::ByteRecord) + Send + Sync>);
+
+impl RecordNormalizer {
+    /// Transform `byte_record` in place. Its position is kept, even if the transformation replaces the record.
+    pub(crate) fn apply(&self, byte_record: &mut csv::ByteRecord) {
+        let position = byte_record.position().cloned();
+        (self.0)(byte_record);
+        byte_record.set_position(position);
+    }
+}
+
+impl fmt::Debug for RecordNormalizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecordNormalizer")
+    }
+}
+
+/// The buffer capacity (in bytes) of CSV readers created with [`Csv::with_reader`].
+pub const STREAMING_BUFFER_CAPACITY: usize = 64 * (1 << 10);
+
+/// Create a [`Csv`] with configuration options for the underlying CSV reader.
+/// # Example: create a `Csv` with a buffer capacity of 1 MiB
+/// ```
+/// use csv_diff::csv::CsvBuilder;
+///
+/// let csv = CsvBuilder::new()
+///     .buffer_capacity(1 << 20)
+///     .build_with_reader("id,name\n1,lemon".as_bytes());
+/// # let _ = csv;
+/// ```
+#[derive(Debug)]
+pub struct CsvBuilder {
+    buffer_capacity: Option<usize>,
+    dialect: Option<Dialect>,
+    comment: Option<u8>,
+    flexible: bool,
+    error_policy: ErrorPolicy,
+    line_offset: u64,
+    normalizer: Option<RecordNormalizer>,
+    sniff_len: usize,
+    max_bytes_per_second: Option<NonZeroU64>,
+    spool_memory_threshold: usize,
+    #[cfg(feature = "simd-parser")]
+    is_simd_parser: bool,
+}
+
+impl Default for CsvBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvBuilder {
+    pub fn new() -> Self {
+        Self {
+            buffer_capacity: None,
+            dialect: None,
+            comment: None,
+            flexible: false,
+            error_policy: ErrorPolicy::Abort,
+            line_offset: 0,
+            normalizer: None,
+            sniff_len: DEFAULT_SNIFF_LEN,
+            max_bytes_per_second: None,
+            spool_memory_threshold: DEFAULT_SPOOL_MEMORY_THRESHOLD,
+            #[cfg(feature = "simd-parser")]
+            is_simd_parser: false,
+        }
+    }
+
+    /// Set the capacity (in bytes) of the buffer, that is used when reading CSV data
+    /// (see [`csv::ReaderBuilder::buffer_capacity`]).
+    ///
+    /// A larger buffer means fewer, but bigger read calls, which can speed up reading from network filesystems.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the delimiter and quote character of the CSV data (default is [`Dialect::default`]).
+    ///
+    /// This has no effect on `Csv`s created with sniffing (e.g. with [`build_with_reader_sniffed`](CsvBuilder::build_with_reader_sniffed)),
+    /// since they use the detected dialect.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    /// Skip lines, that start with `comment` (e.g. `Some(b'#')`), like [`csv::ReaderBuilder::comment`]; `None` (the default)
+    /// disables comments.
+    ///
+    /// Comment lines are still counted, so the line numbers of [`ByteRecordLineInfo`](crate::diff_row::ByteRecordLineInfo)
+    /// refer to the physical lines of the file, when comparing with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff)
+    /// or [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal).
+    /// # Example: compare CSV data with comments
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder, diff_row::LineNum};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = [
+    "# exported at 2024-01-01",
+    "id,name,kind",
+    "1,lemon,fruit",
+    "# the next line has been fixed manually",
+    "2,strawberry,fruit",
+]
+.join("\n");
+let csv_data_right = "id,name,kind\n\
+                    1,lemon,fruit\n\
+                    2,strawberry,nut";
+let csv_builder = CsvBuilder::new().comment(Some(b'#'));
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    csv_builder.build_with_reader_seek(csv_data_left.as_bytes()),
+    csv_builder.build_with_reader_seek(csv_data_right.as_bytes()),
+)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+assert!(matches!(
+    diff_byte_records.as_slice()[0].line_num(),
+    LineNum::BothSides { for_deleted: 5, for_added: 3 }
+));
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn comment(mut self, comment: Option<u8>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Tolerate records with a different number of fields than the headers (or the first record), like
+    /// [`csv::ReaderBuilder::flexible`]; this is disabled by default, so that such records result in an error
+    /// with the error code `CSVDIFF_RAGGED_ROW`.
+    ///
+    /// When comparing records with a different number of fields, the shorter one is padded with empty fields,
+    /// so e.g. `1,lemon` and `1,lemon,` are equal, but `1,lemon` and `1,lemon,fruit` differ in the third field.
+    /// # Example: compare CSV data with ragged trailing columns
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder, diff_row::DiffByteRecord};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,kind,note\n\
+                     1,lemon,fruit\n\
+                     2,strawberry,fruit,";
+let csv_data_right = "id,name,kind,note\n\
+                      1,lemon,fruit,\n\
+                      2,strawberry,fruit,red";
+let csv_builder = CsvBuilder::new().flexible(true);
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    csv_builder.build_with_reader_seek(csv_data_left.as_bytes()),
+    csv_builder.build_with_reader_seek(csv_data_right.as_bytes()),
+)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+assert!(matches!(
+    &diff_byte_records.as_slice()[0],
+    DiffByteRecord::Modify { field_indices, .. } if field_indices == &vec![3]
+));
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn flexible(mut self, flexible: bool) -> Self {
+        self.flexible = flexible;
+        self
+    }
+
+    /// Set how to handle malformed records, e.g. records with a different number of fields than the header
+    /// (default is [`ErrorPolicy::Abort`]).
+    ///
+    /// With [`ErrorPolicy::SkipAndReport`], malformed records are skipped and reported in the
+    /// [`DiffRunReport`](crate::run_report::DiffRunReport) of a streaming comparison or by
+    /// [`CsvByteDiffLocal::diff_with_skipped_records`](crate::csv_diff::CsvByteDiffLocal::diff_with_skipped_records).
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Add `line_offset` to the line of every record (default is `0`), e.g. when the CSV data is a chunk, that has been
+    /// sliced out of a bigger file, so that the lines of [`ByteRecordLineInfo`](crate::diff_row::ByteRecordLineInfo)
+    /// (and of [`SkippedRecord`](crate::error_policy::SkippedRecord)s) refer to the original file.
+    ///
+    /// The offset is the line of the original file, that precedes the first line of the chunk. For a chunk with a header,
+    /// whose first record has been on line 1001 of the original file, the offset is `999`.
+    /// # Example: compare a chunk of a CSV with the original file
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::{Csv, CsvBuilder}, diff_row::LineNum};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_original = "id,name,kind\n\
+                         1,lemon,fruit\n\
+                         2,strawberry,fruit\n\
+                         3,cherry,fruit";
+// the header and the last two records of the original file
+let csv_data_chunk = "id,name,kind\n\
+                      2,strawberry,fruit\n\
+                      3,cherry,nut";
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek(csv_data_original.as_bytes()),
+    CsvBuilder::new()
+        .line_offset(1)
+        .build_with_reader_seek(csv_data_chunk.as_bytes()),
+)?;
+
+assert!(diff_byte_records.as_slice().iter().any(|diff_record| matches!(
+    diff_record.line_num(),
+    LineNum::BothSides { for_deleted: 4, for_added: 4 }
+)));
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn line_offset(mut self, line_offset: u64) -> Self {
+        self.line_offset = line_offset;
+        self
+    }
+
+    /// Transform every record with `normalize` before its key and its fields are hashed, e.g. in order to trim whitespace
+    /// or to lowercase fields, so that records, which only differ in that regard, are considered equal.
+    ///
+    /// The records, that are emitted as differences, are the normalized records.
+    /// The header is not normalized.
+    /// # Example: ignore differences in case
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,kind\n\
+                     1,lemon,fruit\n\
+                     2,strawberry,fruit";
+let csv_data_right = "id,name,kind\n\
+                      1,Lemon,Fruit\n\
+                      2,STRAWBERRY,fruit";
+
+let lowercase = |byte_record: &mut csv::ByteRecord| {
+    *byte_record = byte_record
+        .iter()
+        .map(|field| field.to_ascii_lowercase())
+        .collect();
+};
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    CsvBuilder::new()
+        .normalize(lowercase)
+        .build_with_reader_seek(csv_data_left.as_bytes()),
+    CsvBuilder::new()
+        .normalize(lowercase)
+        .build_with_reader_seek(csv_data_right.as_bytes()),
+)?;
+
+assert!(diff_byte_records.as_slice().is_empty());
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn normalize<F>(mut self, normalize: F) -> Self
+    where
+        F: Fn(&mut csv::ByteRecord) + Send + Sync + 'static,
+    {
+        self.normalizer = Some(RecordNormalizer(Arc::new(normalize)));
+        self
+    }
+
+    /// Set the number of bytes at the start of the CSV data, that are inspected in order to detect its dialect
+    /// (default is [`DEFAULT_SNIFF_LEN`]).
+    ///
+    /// This only has an effect on `Csv`s created with [`build_with_reader_sniffed`](CsvBuilder::build_with_reader_sniffed)
+    /// or [`build_with_reader_seek_sniffed`](CsvBuilder::build_with_reader_seek_sniffed).
+    pub fn sniff_len(mut self, sniff_len: usize) -> Self {
+        self.sniff_len = sniff_len;
+        self
+    }
+
+    /// Limit the throughput of the reader to at most `bytes_per_second` bytes per second, e.g. to not saturate
+    /// shared network storage. A value of `0` removes the limit.
+    ///
+    /// This only has an effect on `Csv`s created with [`build_with_throttled_reader`](CsvBuilder::build_with_throttled_reader)
+    /// or [`build_with_throttled_reader_seek`](CsvBuilder::build_with_throttled_reader_seek).
+    pub fn max_bytes_per_second(mut self, bytes_per_second: u64) -> Self {
+        self.max_bytes_per_second = NonZeroU64::new(bytes_per_second);
+        self
+    }
+
+    /// Set the number of bytes, up to which the data of a non-seekable reader is kept in memory, before it is
+    /// spooled to a temporary file (default is [`DEFAULT_SPOOL_MEMORY_THRESHOLD`]).
+    ///
+    /// This only has an effect on `Csv`s created with [`build_with_reader_spooled`](CsvBuilder::build_with_reader_spooled).
+    pub fn spool_memory_threshold(mut self, memory_threshold: usize) -> Self {
+        self.spool_memory_threshold = memory_threshold;
+        self
+    }
+
+    /// Tokenize the records with a parser, that finds delimiters, quotes and line terminators with the vectorized search
+    /// of the `memchr` crate, instead of with the `csv` crate (default is `false`). For large CSVs, parsing is usually
+    /// the dominant cost of comparing them with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff)
+    /// or [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), which both use this parser.
+    ///
+    /// The records and their lines are the same as with the `csv` crate, but only the dialect of
+    /// [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180) is supported, i.e. quotes within quoted fields are escaped
+    /// by doubling them. A record with a different number of fields than the header is reported as an I/O error
+    /// (with the error code `CSVDIFF_IO`), which can't be skipped with [`ErrorPolicy::SkipAndReport`].
+    ///
+    /// The `csv` crate is still used, if [`comment`](CsvBuilder::comment) is set or if the header has already been read
+    /// with [`Csv::headers`].
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,kind\n\
+                     1,lemon,fruit\n\
+                     2,\"straw\"\"berry\",fruit";
+let csv_data_right = "id,name,kind\n\
+                      1,lemon,fruit\n\
+                      2,\"straw\"\"berry\",nut";
+let csv_builder = CsvBuilder::new().simd_parser(true);
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    csv_builder.build_with_reader_seek(csv_data_left.as_bytes()),
+    csv_builder.build_with_reader_seek(csv_data_right.as_bytes()),
+)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+Ok(())
+# }
+```
+"##
+    )]
+    #[cfg(feature = "simd-parser")]
+    pub fn simd_parser(mut self, simd_parser: bool) -> Self {
+        self.is_simd_parser = simd_parser;
+        self
+    }
+
+    pub fn build_with_reader<R: Read>(&self, reader: R) -> Csv<R> {
+        Csv {
+            csv_reader: self.csv_reader_builder(self.dialect).from_reader(reader),
+            dialect: None,
+            physical_lines: self.physical_lines(self.dialect),
+            error_policy: self.error_policy,
+            line_offset: self.line_offset,
+            normalizer: self.normalizer.clone(),
+            #[cfg(feature = "simd-parser")]
+            simd_parser: self.simd_parser_with_dialect(self.dialect),
+        }
+    }
+
+    pub fn build_with_reader_seek<R, RSeek>(&self, reader: RSeek) -> Csv<R>
+    where
+        R: Read + Seek + Send,
+        RSeek: CsvReadSeek<R>,
+    {
+        Csv {
+            csv_reader: self
+                .csv_reader_builder(self.dialect)
+                .from_reader_seek(reader),
+            dialect: None,
+            physical_lines: self.physical_lines(self.dialect),
+            error_policy: self.error_policy,
+            line_offset: self.line_offset,
+            normalizer: self.normalizer.clone(),
+            #[cfg(feature = "simd-parser")]
+            simd_parser: self.simd_parser_with_dialect(self.dialect),
+        }
+    }
+
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but detects the dialect of the CSV data
+    /// (see [`Csv::with_reader_sniffed`]).
+    pub fn build_with_reader_sniffed<R: Read>(
+        &self,
+        mut reader: R,
+    ) -> io::Result<Csv<SniffedReader<R>>> {
+        let (dialect, sample) = Dialect::sniff_reader(&mut reader, self.sniff_len)?;
+        Ok(Csv {
+            csv_reader: self
+                .csv_reader_builder(Some(dialect))
+                .from_reader(Cursor::new(sample).chain(reader)),
+            dialect: Some(dialect),
+            physical_lines: self.physical_lines(Some(dialect)),
+            error_policy: self.error_policy,
+            line_offset: self.line_offset,
+            normalizer: self.normalizer.clone(),
+            #[cfg(feature = "simd-parser")]
+            simd_parser: self.simd_parser_with_dialect(Some(dialect)),
+        })
+    }
+
+    /// Like [`build_with_reader_seek`](CsvBuilder::build_with_reader_seek), but detects the dialect of the CSV data
+    /// (see [`Csv::with_reader_seek_sniffed`]).
+    ///
+    /// The reader is rewound to its start after inspecting the data.
+    pub fn build_with_reader_seek_sniffed<R, RSeek>(&self, reader: RSeek) -> io::Result<Csv<R>>
+    where
+        R: Read + Seek + Send,
+        RSeek: CsvReadSeek<R>,
+    {
+        let mut reader = reader.into_read_seek();
+        let (dialect, _) = Dialect::sniff_reader(&mut reader, self.sniff_len)?;
+        reader.seek(SeekFrom::Start(0))?;
+        Ok(Csv {
+            csv_reader: self
+                .csv_reader_builder(Some(dialect))
+                .from_reader_seek(reader),
+            dialect: Some(dialect),
+            physical_lines: self.physical_lines(Some(dialect)),
+            error_policy: self.error_policy,
+            line_offset: self.line_offset,
+            normalizer: self.normalizer.clone(),
+            #[cfg(feature = "simd-parser")]
+            simd_parser: self.simd_parser_with_dialect(Some(dialect)),
+        })
+    }
+
+    #[cfg(feature = "simd-parser")]
+    fn simd_parser_with_dialect(&self, dialect: Option<Dialect>) -> Option<SimdParser> {
+        let dialect = dialect.unwrap_or_default();
+        (self.is_simd_parser && self.comment.is_none()).then(|| {
+            SimdParser::new(
+                dialect.delimiter(),
+                dialect.quote(),
+                self.flexible,
+                self.buffer_capacity,
+            )
+        })
+    }
+
+    fn physical_lines(&self, dialect: Option<Dialect>) -> Option<PhysicalLines> {
+        self.comment
+            .map(|_| PhysicalLines::new(dialect.unwrap_or_default().quote()))
+    }
+
+    fn csv_reader_builder(&self, dialect: Option<Dialect>) -> csv::ReaderBuilder {
+        let mut csv_reader_builder = csv::ReaderBuilder::new();
+        csv_reader_builder
+            .comment(self.comment)
+            .flexible(self.flexible);
+        if let Some(capacity) = self.buffer_capacity {
+            csv_reader_builder.buffer_capacity(capacity);
+        }
+        if let Some(dialect) = dialect {
+            csv_reader_builder
+                .delimiter(dialect.delimiter())
+                .quote(dialect.quote());
+        }
+        csv_reader_builder
+    }
+
+    /// Like [`build_with_reader_seek`](CsvBuilder::build_with_reader_seek), but makes the non-seekable `reader`
+    /// seekable by reading it into a [`SpooledReader`] first (see [`Csv::with_reader_spooled`]).
+    pub fn build_with_reader_spooled<R: Read>(&self, reader: R) -> io::Result<Csv<SpooledReader>> {
+        Ok(
+            self.build_with_reader_seek(SpooledReader::with_memory_threshold(
+                reader,
+                self.spool_memory_threshold,
+            )?),
+        )
+    }
+
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but strips a UTF-8 or UTF-16 byte order mark
+    /// at the start of `reader` (see [`BomStrippingReader`]).
+    pub fn build_with_reader_bom_stripped<R: Read>(&self, reader: R) -> Csv<BomStrippingReader<R>> {
+        self.build_with_reader(BomStrippingReader::new(reader))
+    }
+
+    /// Like [`build_with_reader_seek`](CsvBuilder::build_with_reader_seek), but strips a UTF-8 or UTF-16
+    /// byte order mark at the start of `reader` (see [`BomStrippingReader`]).
+    pub fn build_with_reader_seek_bom_stripped<R, RSeek>(
+        &self,
+        reader: RSeek,
+    ) -> Csv<BomStrippingReader<R>>
+    where
+        R: Read + Seek + Send,
+        RSeek: CsvReadSeek<R>,
+    {
+        self.build_with_reader_seek(BomStrippingReader::new(reader.into_read_seek()))
+    }
+
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but wraps `reader` in a [`ThrottledReader`],
+    /// which is limited by [`max_bytes_per_second`](CsvBuilder::max_bytes_per_second) (if set).
+    pub fn build_with_throttled_reader<R: Read>(&self, reader: R) -> Csv<ThrottledReader<R>> {
+        self.build_with_reader(ThrottledReader::with_limit(
+            reader,
+            self.max_bytes_per_second,
+        ))
+    }
+
+    /// Like [`build_with_reader_seek`](CsvBuilder::build_with_reader_seek), but wraps `reader` in a [`ThrottledReader`],
+    /// which is limited by [`max_bytes_per_second`](CsvBuilder::max_bytes_per_second) (if set).
+    pub fn build_with_throttled_reader_seek<R, RSeek>(
+        &self,
+        reader: RSeek,
+    ) -> Csv<ThrottledReader<R>>
+    where
+        R: Read + Seek + Send,
+        RSeek: CsvReadSeek<R>,
+    {
+        self.build_with_reader_seek(ThrottledReader::with_limit(
+            reader.into_read_seek(),
+            self.max_bytes_per_second,
+        ))
     }
 }
 
@@ -94,6 +819,7 @@ where
 }
 
 pub trait CsvReaderBuilderExt<R: Read + Seek + Send> {
+    #[allow(clippy::wrong_self_convention)]
     fn from_reader_seek<RSeek: CsvReadSeek<R>>(&self, reader: RSeek) -> csv::Reader<R>;
 }
 
@@ -102,3 +828,76 @@ impl<R: Read + Seek + Send> CsvReaderBuilderExt<R> for csv::ReaderBuilder {
         self.from_reader(reader.into_read_seek())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn csv_builder_with_small_buffer_capacity_reads_all_records() -> csv::Result<()> {
+        let csv_data = "id,name\n1,lemon\n2,strawberry with a long name";
+
+        let mut csv_reader = CsvBuilder::new()
+            .buffer_capacity(4)
+            .build_with_reader_seek(csv_data.as_bytes())
+            .into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "strawberry with a long name"])
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn headers_does_not_consume_any_record() -> csv::Result<()> {
+        for has_headers in [true, false] {
+            let mut csv = Csv::from(
+                csv::ReaderBuilder::new()
+                    .has_headers(has_headers)
+                    .from_reader("id,name\n1,lemon".as_bytes()),
+            );
+
+            let headers = csv.headers()?.clone();
+            let mut csv_reader = csv.into_csv_reader();
+            let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+            assert_eq!(headers, csv::ByteRecord::from(vec!["id", "name"]));
+            assert_eq!(
+                records.last(),
+                Some(&csv::ByteRecord::from(vec!["1", "lemon"]))
+            );
+            assert_eq!(records.len(), if has_headers { 1 } else { 2 });
+            assert_eq!(
+                records[0].position().map(|pos| pos.line()),
+                Some(if has_headers { 2 } else { 1 })
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn csv_builder_sniffed_reads_all_records_beyond_sniff_len() -> csv::Result<()> {
+        let csv = CsvBuilder::new()
+            .sniff_len(10)
+            .build_with_reader_sniffed("id|name\n1|lemon\n2|cherry".as_bytes())?;
+        let dialect = csv.dialect();
+        let mut csv_reader = csv.into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(dialect, Some(Dialect::new(b'|', b'"')));
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "cherry"])
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use crate::csv::Csv;
+use crate::cancellation::CancellationToken;
+use crate::csv::{Csv, RecordNormalizer};
 use crate::csv_hash_comparer::CsvHashComparer;
 use crate::csv_hash_receiver_comparer::CsvHashReceiverStreamComparer;
 #[cfg(not(feature = "rayon-threads"))]
@@ -8,7 +9,7 @@ use crate::csv_hash_task_spawner::CsvHashTaskSpawnerLocalBuilder;
 use crate::csv_hash_task_spawner::CsvHashTaskSpawnerLocalRayon;
 use crate::csv_hash_task_spawner::{
     CsvHashTaskLineSenders, CsvHashTaskSenderWithRecycleReceiver, CsvHashTaskSpawner,
-    CsvHashTaskSpawnerLocal,
+    CsvHashTaskSpawnerLocal, HashedCsvReader,
 };
 #[cfg(feature = "crossbeam-threads")]
 use crate::csv_hash_task_spawner::{
@@ -16,23 +17,44 @@ use crate::csv_hash_task_spawner::{
 };
 #[cfg(feature = "rayon-threads")]
 use crate::csv_hash_task_spawner::{CsvHashTaskSpawnerLocalBuilderRayon, CsvHashTaskSpawnerRayon};
-use crate::csv_parse_result::{CsvLeftRightParseResult, RecordHashWithPosition};
-use crate::diff_result::{DiffByteRecords, DiffByteRecordsIterator};
+use crate::csv_parse_result::{
+    CsvLeftRightParseResult, CsvParseResultLeft, CsvParseResultRight, RecordHashWithPosition,
+};
+use crate::diff_result::{
+    AnnotatedByteRecordsIterator, DiffByteRecords, DiffByteRecordsIterator, DiffHeaders, DiffReport,
+};
+use crate::diff_row::{AnnotatedByteRecord, ByteRecordLineInfo, DiffByteRecord, DiffByteRecordRef};
+use crate::empty_csv::{CsvHeaderInfo, EmptyCsvSemantics};
+use crate::error_code::ErrorCode;
+use crate::error_policy::{ErrorPolicy, SkippedRecords};
+use crate::field_comparator::{FieldComparator, FieldComparators, IgnoreComparator, IgnorePattern};
+use crate::flush_threshold::FlushThreshold;
+use crate::hash_index::{self, HashIndex, HashIndexConfig, HashIndexMismatchError};
+use crate::hash_snapshot::{HashSnapshot, HashSnapshotDiffRecord};
+use crate::preset::Preset;
+use crate::resumable_diff::{Checkpoint, DiffByteRecordsResumable, DEFAULT_CHECKPOINT_INTERVAL};
+use crate::run_report::DiffRunReport;
+use crate::task_panic::catch_panic;
+#[cfg(any(feature = "rayon-threads", feature = "crossbeam-threads"))]
 use crate::thread_scope_strategy::*;
-use crossbeam_channel::{bounded, Receiver};
-use csv::Reader;
-use std::cell::RefCell;
-use std::io::{Read, Seek};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use std::io::{self, Read, Seek};
 use std::marker::PhantomData;
+use std::num::NonZeroU64;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{collections::HashSet, iter::Iterator};
 use thiserror::Error;
 
+/// The number of hashed records, that the hashing tasks of a [`CsvByteDiff`] may send ahead of the comparison,
+/// unless another capacity has been set with [`CsvByteDiffBuilder::channel_capacity`].
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 10_000;
+
 /// Compare two [CSVs](https://en.wikipedia.org/wiki/Comma-separated_values) lazily with each other (for the eager-/blocking-based variant, see [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal)).
 ///
 /// Use this instead of [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), when:
 /// - you own your CSV data and you want to use an [`Iterator`](`crate::diff_result::DiffByteRecordsIterator`) for the differences,
-/// so you don't have to read all differences into memory
+///   so you don't have to read all differences into memory
 /// - your CSV data structure does __not__ support [`Seek`].
 ///
 /// By default, `CsvByteDiff` uses a [rayon thread-pool](https://docs.rs/rayon/1.5.0/rayon/struct.ThreadPool.html) to compare differences.
@@ -84,19 +106,62 @@ Ok(())
 )]
 #[derive(Debug)]
 pub struct CsvByteDiff<T: CsvHashTaskSpawner> {
+    // empty, if the whole record acts as the key
     primary_key_columns: HashSet<usize>,
-    // TODO: try to find a way to remove interior mutability in `diff` method
-    hash_task_spawner: RefCell<Option<T>>,
+    field_comparators: FieldComparators,
+    empty_csv_semantics: EmptyCsvSemantics,
+    cancellation_token: CancellationToken,
+    max_duration: Option<Duration>,
+    memory_budget: Option<usize>,
+    canonical_column_order: bool,
+    max_diffs: Option<usize>,
+    key_sample: Option<NonZeroU64>,
+    checkpoint_interval: u64,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    hash_seed: Option<u64>,
+    channel_capacity: usize,
+    flush_threshold: FlushThreshold,
+    recycle_capacity: Option<usize>,
+    hash_task_spawner: T,
 }
 
 #[cfg(feature = "rayon-threads")]
 impl CsvByteDiff<CsvHashTaskSpawnerRayon> {
     pub fn new() -> Result<Self, CsvDiffNewError> {
+        Self::with_thread_pool_builder(rayon::ThreadPoolBuilder::new())
+    }
+
+    /// Like [`new`](CsvByteDiff::new), but the rayon thread-pool has `num_threads` threads
+    /// (instead of one thread per CPU core), e.g. so that other work on a shared host is not starved.
+    /// If `num_threads` is `0`, the default number of threads is used.
+    pub fn with_num_threads(num_threads: usize) -> Result<Self, CsvDiffNewError> {
+        Self::with_thread_pool_builder(rayon::ThreadPoolBuilder::new().num_threads(num_threads))
+    }
+
+    /// Like [`new`](CsvByteDiff::new), but the rayon thread-pool is built with `thread_pool_builder`,
+    /// e.g. to set the number of threads or their names.
+    pub fn with_thread_pool_builder(
+        thread_pool_builder: rayon::ThreadPoolBuilder,
+    ) -> Result<Self, CsvDiffNewError> {
         let mut instance = Self {
             primary_key_columns: HashSet::new(),
-            hash_task_spawner: RefCell::new(Some(CsvHashTaskSpawnerRayon::with_thread_pool_owned(
-                rayon::ThreadPoolBuilder::new().build()?,
-            ))),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_duration: None,
+            memory_budget: None,
+            canonical_column_order: false,
+            max_diffs: None,
+            key_sample: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            flush_threshold: FlushThreshold::default(),
+            recycle_capacity: None,
+            hash_task_spawner: CsvHashTaskSpawnerRayon::with_thread_pool_owned(
+                thread_pool_builder.build()?,
+            ),
         };
         instance.primary_key_columns.insert(0);
         Ok(instance)
@@ -114,35 +179,328 @@ where
     ) -> DiffByteRecordsIterator {
         use crossbeam_channel::unbounded;
 
-        let (sender_right, receiver) = bounded(10_000);
+        let (sender_right, receiver) = if self.hash_task_spawner.is_sequential() {
+            unbounded()
+        } else {
+            bounded(self.channel_capacity)
+        };
         let sender_left = sender_right.clone();
 
-        let (sender_csv_recycle, receiver_csv_recycle) = unbounded();
-
-        let hts = self.hash_task_spawner.take().take();
+        let (sender_csv_recycle, receiver_csv_recycle) = match self.recycle_capacity {
+            Some(recycle_capacity) => bounded(recycle_capacity),
+            None => unbounded(),
+        };
+        let (sender_header_info_left, receiver_header_info_left) = bounded(1);
+        let (sender_header_info_right, receiver_header_info_right) = bounded(1);
+        let (sender_key_stats_left, receiver_key_stats_left) = bounded(1);
+        let (sender_key_stats_right, receiver_key_stats_right) = bounded(1);
+        let (sender_skipped_records_left, receiver_skipped_records_left) = bounded(1);
+        let (sender_skipped_records_right, receiver_skipped_records_right) = bounded(1);
+        let (sender_panic, receiver_panic) = unbounded();
+        let cancellation_token = match self.max_duration {
+            Some(max_duration) => self
+                .cancellation_token
+                .with_deadline(Instant::now() + max_duration),
+            None => self.cancellation_token.clone(),
+        };
 
-        let (hash_task_spawner, receiver_diff_byte_record_iter) =
-            // TODO: remove unwrap!!!
-            hts.unwrap().spawn_hashing_tasks_and_send_result(
+        let receiver_diff_byte_record_iter =
+            self.hash_task_spawner.spawn_hashing_tasks_and_send_result(
                 CsvHashTaskSenderWithRecycleReceiver::new(
                     sender_left,
                     csv_left,
-                    receiver_csv_recycle.clone()
-                ),
+                    receiver_csv_recycle.clone(),
+                    sender_header_info_left,
+                    sender_key_stats_left,
+                    sender_skipped_records_left,
+                    sender_panic.clone(),
+                    cancellation_token.clone(),
+                )
+                .canonical_column_order(self.canonical_column_order)
+                .primary_key_all_columns_except(self.primary_key_excluded_columns.clone())
+                .field_canonicalizer(self.field_comparators.canonicalizer().clone())
+                .hash_seed(self.hash_seed),
                 CsvHashTaskSenderWithRecycleReceiver::new(
                     sender_right,
                     csv_right,
-                    receiver_csv_recycle
+                    receiver_csv_recycle,
+                    sender_header_info_right,
+                    sender_key_stats_right,
+                    sender_skipped_records_right,
+                    sender_panic,
+                    cancellation_token.clone(),
+                )
+                .canonical_column_order(self.canonical_column_order)
+                .primary_key_all_columns_except(self.primary_key_excluded_columns.clone())
+                .field_canonicalizer(self.field_comparators.canonicalizer().clone())
+                .hash_seed(self.hash_seed),
+                CsvHashReceiverStreamComparer::new(
+                    receiver,
+                    sender_csv_recycle,
+                    receiver_header_info_left,
+                    receiver_header_info_right,
+                    receiver_key_stats_left,
+                    receiver_key_stats_right,
+                    receiver_skipped_records_left,
+                    receiver_skipped_records_right,
+                    receiver_panic,
                 ),
-                CsvHashReceiverStreamComparer::new(receiver, sender_csv_recycle),
                 self.primary_key_columns.clone(),
             );
 
-        let mut hash_task_spawner_mut = self.hash_task_spawner.borrow_mut();
-        *hash_task_spawner_mut = Some(hash_task_spawner);
+        receiver_diff_byte_record_iter
+            .recv()
+            .unwrap()
+            .with_field_comparators(self.field_comparators.clone())
+            .with_empty_csv_semantics(self.empty_csv_semantics)
+            .with_cancellation_token(cancellation_token)
+            .with_memory_budget(self.memory_budget)
+            .with_max_diffs(self.max_diffs)
+            .with_key_sample(self.key_sample)
+            .with_hash_seed(self.hash_seed)
+            .with_flush_threshold(self.flush_threshold)
+    }
+
+    /// Like [`diff`](CsvByteDiff::diff), but additionally emits the records, that are equal in both CSVs, as
+    /// [`AnnotatedByteRecord::Equal`](crate::diff_row::AnnotatedByteRecord::Equal), so that you get the full annotated dataset
+    /// instead of only the differences.
+    ///
+    /// A `Modify`, whose changes are all ignored by the field comparators, is emitted as `Equal` as well.
+    /// Equal records are never counted towards [`max_diffs`](CsvByteDiffBuilder::max_diffs).
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+    use csv_diff::diff_row::AnnotatedByteRecord;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_left = "\
+    id,name,kind\n\
+    1,lemon,fruit\n\
+    2,strawberry,fruit";
+    let csv_right = "\
+    id,name,kind\n\
+    1,lemon,fruit\n\
+    2,strawberry,nut";
+
+    let annotated_records = CsvByteDiff::new()?
+        .diff_with_equal_records(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        )
+        .collect::<csv::Result<Vec<_>>>()?;
+
+    assert_eq!(annotated_records.len(), 2);
+    assert_eq!(annotated_records.iter().filter(|rec| rec.is_equal()).count(), 1);
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_equal_records<R: Read + Send + 'static>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> AnnotatedByteRecordsIterator {
+        self.diff(csv_left, csv_right).with_equal_records()
+    }
+
+    /// Compares `csv_left` with `csv_right` until the end, collecting all differences as well as _all_ parse errors
+    /// of both CSVs into a [`DiffReport`], instead of stopping at the first malformed record.
+    ///
+    /// Malformed records are skipped (regardless of the [`ErrorPolicy`] of the `Csv`s), so they are not part of the differences.
+    /// Errors, after which reading can't continue (e.g. I/O errors), are still returned as `Err`.
+    pub fn diff_with_report<R: Read + Send + 'static>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<DiffReport> {
+        let mut diff_byte_records_iter = self.diff(
+            csv_left.with_error_policy(ErrorPolicy::SkipAndReport),
+            csv_right.with_error_policy(ErrorPolicy::SkipAndReport),
+        );
+        let diff_byte_records = diff_byte_records_iter
+            .by_ref()
+            .collect::<csv::Result<Vec<_>>>()?;
+        Ok(DiffReport::new(
+            DiffByteRecords(diff_byte_records, diff_byte_records_iter.diff_headers()),
+            diff_byte_records_iter.run_report(),
+        ))
+    }
+
+    /// Like [`diff`](CsvByteDiff::diff), but for CSVs, whose order matters (e.g. CSVs, that are treated as documents):
+    /// a record, that is equal in both CSVs, but whose position has changed relative to the other equal records,
+    /// is additionally emitted as [`DiffByteRecord::Move`](crate::diff_row::DiffByteRecord::Move).
+    ///
+    /// Only the fewest records, that explain the new order, are reported as moved: the equal records, that keep
+    /// their relative order, form the longest such sequence. Records, that have only been shifted by an added
+    /// or a deleted record, are therefore not moved.
+    ///
+    /// Moves can only be determined, after both CSVs have been read completely, so all equal records are kept in memory
+    /// and the differences are collected into [`DiffByteRecords`] (with the `Move`s last).
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+    use csv_diff::diff_row::DiffByteRecord;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_left = "\
+    id,name,kind\n\
+    1,lemon,fruit\n\
+    2,strawberry,fruit\n\
+    3,cherry,fruit";
+    let csv_right = "\
+    id,name,kind\n\
+    2,strawberry,fruit\n\
+    3,cherry,fruit\n\
+    1,lemon,fruit";
+
+    let diff_byte_records = CsvByteDiff::new()?.diff_with_moves(
+        Csv::with_reader(csv_left.as_bytes()),
+        Csv::with_reader(csv_right.as_bytes()),
+    )?;
+
+    assert_eq!(
+        diff_byte_records.as_slice(),
+        &[DiffByteRecord::Move {
+            record: csv::ByteRecord::from(vec!["1", "lemon", "fruit"]),
+            from_line: 2,
+            to_line: 4,
+        }]
+    );
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_moves<R: Read + Send + 'static>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<DiffByteRecords> {
+        let mut annotated_records_iter = self.diff_with_equal_records(csv_left, csv_right);
+        let mut diff_records = Vec::new();
+        let mut equal_records = Vec::new();
+        for annotated_record in annotated_records_iter.by_ref() {
+            match annotated_record? {
+                AnnotatedByteRecord::Equal { left, right } => equal_records.push((left, right)),
+                AnnotatedByteRecord::Diff(diff_record) => diff_records.push(diff_record),
+            }
+        }
+        diff_records.extend(moved_records(equal_records));
+        Ok(DiffByteRecords(
+            diff_records,
+            annotated_records_iter.diff_headers(),
+        ))
+    }
+
+    /// Like [`diff`](CsvByteDiff::diff), but the comparison can be resumed after a crash, e.g. for comparisons,
+    /// that take hours, because the CSVs are read from network storage.
+    ///
+    /// Every [`checkpoint_interval`](CsvByteDiffBuilder::checkpoint_interval) records, the read positions of both CSVs
+    /// and the records, that have not been matched yet, are written to `checkpoint`. If `checkpoint` already exists,
+    /// both CSVs are seeked to its positions and the comparison continues from there; otherwise it starts from the beginning.
+    /// A checkpoint, that has been written for CSVs of another length or with other primary key columns, is rejected with an
+    /// [`InvalidCheckpointError`](crate::resumable_diff::InvalidCheckpointError).
+    /// The checkpoint is removed, when the returned iterator is exhausted. See [`DiffByteRecordsResumable`] for
+    /// which differences are emitted again, when the comparison is resumed.
+    ///
+    /// The CSVs are read on the thread, that advances the iterator (not by the hashing tasks), so apart from the primary key,
+    /// only the field comparators, the [redacted columns](CsvByteDiffBuilder::redact_columns) and the [`cancellation_token`](CsvByteDiffBuilder::cancellation_token)
+    /// are used. Primary keys are assumed to be unique within each CSV.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+    use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    use csv_diff::resumable_diff::Checkpoint;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_left = "\
+    id,name,kind\n\
+    1,lemon,fruit\n\
+    2,strawberry,fruit";
+    let csv_right = "\
+    id,name,kind\n\
+    1,lemon,nut\n\
+    2,strawberry,fruit";
+    let tmp_dir = tempfile::tempdir()?;
+    let checkpoint = Checkpoint::new(tmp_dir.path().join("diff.checkpoint"));
+
+    let diff_byte_records = CsvByteDiff::new()?
+        .diff_resumable(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+            checkpoint.clone(),
+        )?
+        .collect::<csv::Result<Vec<_>>>()?;
+
+    assert_eq!(
+        diff_byte_records,
+        vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "fruit"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "nut"]), 2),
+            field_indices: vec![2],
+        }]
+    );
+    assert!(!checkpoint.exists());
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_resumable<R: Read + Seek>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+        checkpoint: Checkpoint,
+    ) -> csv::Result<DiffByteRecordsResumable<R>> {
+        DiffByteRecordsResumable::new(
+            csv_left,
+            csv_right,
+            &self.primary_key_columns,
+            self.primary_key_excluded_columns.as_ref(),
+            self.field_comparators.clone(),
+            self.cancellation_token.clone(),
+            checkpoint,
+            self.checkpoint_interval,
+        )
+    }
+}
 
-        receiver_diff_byte_record_iter.recv().unwrap()
+/// Return the `equal_records` (pairs of the left and the right record), that are not part of the longest sequence of
+/// records, whose order is the same in both CSVs, as [`DiffByteRecord::Move`]s (in the order of the left CSV).
+fn moved_records(
+    mut equal_records: Vec<(ByteRecordLineInfo, ByteRecordLineInfo)>,
+) -> Vec<DiffByteRecord> {
+    equal_records.sort_unstable_by_key(|(left, _)| left.line());
+    // the longest increasing subsequence of the right lines: `run_ends[len - 1]` is the record with the smallest
+    // right line, that ends an increasing sequence of length `len`
+    let mut run_ends: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; equal_records.len()];
+    for (idx, (_, right)) in equal_records.iter().enumerate() {
+        let len = run_ends.partition_point(|&end| equal_records[end].1.line() < right.line());
+        predecessors[idx] = len.checked_sub(1).map(|prev| run_ends[prev]);
+        if len == run_ends.len() {
+            run_ends.push(idx);
+        } else {
+            run_ends[len] = idx;
+        }
+    }
+    let mut is_in_order = vec![false; equal_records.len()];
+    let mut idx_maybe = run_ends.last().copied();
+    while let Some(idx) = idx_maybe {
+        is_in_order[idx] = true;
+        idx_maybe = predecessors[idx];
     }
+    equal_records
+        .into_iter()
+        .zip(is_in_order)
+        .filter(|(_, is_in_order)| !is_in_order)
+        .map(|((left, right), _)| DiffByteRecord::Move {
+            from_line: left.line(),
+            to_line: right.line(),
+            record: left.into_byte_record(),
+        })
+        .collect()
 }
 
 /// Create a [`CsvByteDiff`](CsvByteDiff) with configuration options.
@@ -205,6 +563,21 @@ Ok(())
 #[cfg_attr(feature = "rayon-threads", derive(Default))]
 pub struct CsvByteDiffBuilder<T: CsvHashTaskSpawner> {
     primary_key_columns: HashSet<usize>,
+    field_comparators: FieldComparators,
+    empty_csv_semantics: EmptyCsvSemantics,
+    cancellation_token: CancellationToken,
+    max_duration: Option<Duration>,
+    memory_budget: Option<usize>,
+    canonical_column_order: bool,
+    max_diffs: Option<usize>,
+    key_sample: Option<NonZeroU64>,
+    checkpoint_interval: u64,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    hash_seed: Option<u64>,
+    channel_capacity: usize,
+    flush_threshold: FlushThreshold,
+    recycle_capacity: Option<usize>,
+    whole_row_as_key: bool,
     #[cfg(feature = "rayon-threads")]
     hash_task_spawner: Option<CsvHashTaskSpawnerRayon>,
     #[cfg(feature = "rayon-threads")]
@@ -224,6 +597,21 @@ where
     {
         Self {
             primary_key_columns: std::iter::once(0).collect(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_duration: None,
+            memory_budget: None,
+            canonical_column_order: false,
+            max_diffs: None,
+            key_sample: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            flush_threshold: FlushThreshold::default(),
+            recycle_capacity: None,
+            whole_row_as_key: false,
             hash_task_spawner: csv_hash_task_spawner_builder.build(),
         }
     }
@@ -233,12 +621,325 @@ where
         self
     }
 
+    /// Compare the fields of column `column` with `comparator` instead of byte-wise.
+    ///
+    /// Records, whose byte-wise changed fields are all equal according to their comparators, are not reported as modified.
+    /// See [`FieldComparator`](crate::field_comparator::FieldComparator) for more information.
+    pub fn field_comparator(
+        mut self,
+        column: usize,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.field_comparators.insert(column, Arc::new(comparator));
+        self
+    }
+
+    /// Like [`field_comparator`](Self::field_comparator), but for the column named `header`, which is looked up
+    /// in the header of the left CSV (or of the right CSV, if the left one has none), when comparing.
+    ///
+    /// It replaces a comparator of the same column, that has been registered by index.
+    /// If there is no column named `header`, the comparator is not used.
+    pub fn field_comparator_by_header(
+        mut self,
+        header: impl AsRef<[u8]>,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.field_comparators
+            .insert_by_header(header.as_ref().to_vec(), Arc::new(comparator));
+        self
+    }
+
+    /// Don't report changes in the columns `columns` as [`Modify`](crate::diff_row::DiffByteRecord::Modify)
+    /// and don't include them in `field_indices`, but still emit their current values as part of the records.
+    ///
+    /// This registers an [`IgnoreComparator`](crate::field_comparator::IgnoreComparator) for each of the columns
+    /// (replacing any other comparator of those columns).
+    pub fn ignore_columns_for_modify(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        let ignore_comparator: Arc<dyn FieldComparator> = Arc::new(IgnoreComparator);
+        for column in columns {
+            self.field_comparators
+                .insert(column, Arc::clone(&ignore_comparator));
+        }
+        self
+    }
+
+    /// Compare the fields of the columns `columns` by their numeric value instead of byte-wise, so that different
+    /// representations of the same number (e.g. `1.0`, `1.00` and `+1`) are equal
+    /// (see [`NumericComparator`](crate::field_comparator::NumericComparator)).
+    ///
+    /// The fields are canonicalized before they are hashed, so this also applies to key columns, and the columns
+    /// only appear in `field_indices`, if their values differ. The emitted records keep their original fields.
+    pub fn numeric_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        for column in columns {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_numeric_column(column);
+        }
+        self
+    }
+
+    /// Treat the fields `tokens` (e.g. `NULL`, `N/A` or `-`) in all columns as equivalent to an empty field,
+    /// e.g. for exports from different databases, which represent missing values differently.
+    ///
+    /// Like [`numeric_columns`](Self::numeric_columns), this is applied before the fields are hashed and
+    /// the emitted records keep their original fields.
+    pub fn null_tokens<N: Into<Vec<u8>>>(mut self, tokens: impl IntoIterator<Item = N>) -> Self {
+        for token in tokens {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_null_token(None, token.into());
+        }
+        self
+    }
+
+    /// Like [`null_tokens`](Self::null_tokens), but only for the fields of column `column`.
+    pub fn null_tokens_for_column<N: Into<Vec<u8>>>(
+        mut self,
+        column: usize,
+        tokens: impl IntoIterator<Item = N>,
+    ) -> Self {
+        for token in tokens {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_null_token(Some(column), token.into());
+        }
+        self
+    }
+
+    /// Blank the parts of the fields of column `column`, that match `ignore_pattern`, before comparing them,
+    /// e.g. to ignore the volatile suffix of a session id, while still comparing its prefix.
+    /// Several patterns of the same column are applied in the order they have been added.
+    ///
+    /// Like [`numeric_columns`](Self::numeric_columns), this is applied before the fields are hashed and
+    /// the emitted records keep their original fields.
+    pub fn ignore_pattern(mut self, column: usize, ignore_pattern: IgnorePattern) -> Self {
+        self.field_comparators
+            .canonicalizer_mut()
+            .insert_ignore_pattern(column, ignore_pattern);
+        self
+    }
+
+    /// Replace the fields of the columns `columns` with `***` in the emitted records, e.g. so that a report of
+    /// the differences can be shared without leaking personal data.
+    ///
+    /// The records are still compared by their real fields, so a change in a redacted column is reported as
+    /// [`Modify`](crate::diff_row::DiffByteRecord::Modify) with the column in its `field_indices`,
+    /// even though both of its fields read `***`.
+    pub fn redact_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        for column in columns {
+            self.field_comparators.insert_redacted_column(column);
+        }
+        self
+    }
+
+    /// Set how a completely empty CSV is compared with a non-empty CSV (default is [`EmptyCsvSemantics::NoRecords`]).
+    ///
+    /// See [`EmptyCsvSemantics`](crate::empty_csv::EmptyCsvSemantics) for more information.
+    pub fn empty_csv_semantics(mut self, empty_csv_semantics: EmptyCsvSemantics) -> Self {
+        self.empty_csv_semantics = empty_csv_semantics;
+        self
+    }
+
+    /// Check `cancellation_token` while comparing, so that the comparison can be cancelled from another thread.
+    ///
+    /// See [`CancellationToken`](crate::cancellation::CancellationToken) for more information.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Stop every comparison after `max_duration` (measured from the call to [`diff`](CsvByteDiff::diff)).
+    ///
+    /// All differences, that have been found until then, are still emitted, followed by a
+    /// [`TimedOutError`](crate::cancellation::TimedOutError) as the last item of the iterator.
+    /// Records, that have not been matched yet, are not reported, because they can't be classified
+    /// without reading the rest of the CSVs. The [`DiffRunReport`](crate::run_report::DiffRunReport)
+    /// of such a run is marked as [`timed_out`](crate::run_report::DiffRunReport::timed_out).
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Limit the memory (in bytes), that is used for records, which haven't been matched with a record
+    /// of the other CSV yet (default: unlimited).
+    ///
+    /// When the limit is exceeded, all unmatched records are spilled to temporary files (see [`std::env::temp_dir`]).
+    /// After both CSVs have been read, the spilled records are merged in partitions, so that only a fraction of them
+    /// must be held in memory at a time. This keeps the memory usage bounded for CSVs, that are mostly disjoint
+    /// or whose matching records are far apart, at the cost of writing and reading them once more.
+    ///
+    /// The memory usage of records is estimated; buffers of readers and channels are not included.
+    /// There is no such option for [`CsvByteDiffLocal`], because it only holds hashes and positions of records in memory.
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Put the columns of both CSVs into a canonical order (sorted by header name) before comparing them (default: `false`),
+    /// so that CSVs, whose columns have only been reordered, are equal.
+    ///
+    /// All column indices (e.g. of the primary key, of field comparators and in the emitted records) then refer to the
+    /// canonical order. The orders, that have been detected, are reported in the [`DiffRunReport`](crate::run_report::DiffRunReport)
+    /// (see [`columns_reordered`](crate::run_report::DiffRunReport::columns_reordered)).
+    /// CSVs without headers are compared in their original order.
+    ///
+    /// There is no such option for [`CsvByteDiffLocal`], because it reads the records, that are emitted, a second time.
+    pub fn canonical_column_order(mut self, canonical_column_order: bool) -> Self {
+        self.canonical_column_order = canonical_column_order;
+        self
+    }
+
+    /// Stop every comparison, after `max_diffs` differences have been emitted (default: unlimited).
+    ///
+    /// The iterator then returns `None` and the hashing tasks stop reading the CSVs, so that you don't pay for
+    /// the full comparison, when you only need to know, whether more than `max_diffs` records differ.
+    /// Records, that have not been matched yet, are not reported. The [`DiffRunReport`](crate::run_report::DiffRunReport)
+    /// of such a run is marked with [`max_diffs_reached`](crate::run_report::DiffRunReport::max_diffs_reached).
+    pub fn max_diffs(mut self, max_diffs: usize) -> Self {
+        self.max_diffs = Some(max_diffs);
+        self
+    }
+
+    /// Only compare the records of a deterministic sample of roughly one in `one_in` primary keys
+    /// (those, whose key hash is a multiple of `one_in`), e.g. for a quick smoke check of enormous CSVs.
+    /// A value of `0` or `1` compares all records (default).
+    ///
+    /// The CSVs are still read completely, but the records of all other keys are dropped right after they have been hashed,
+    /// so they are neither kept in memory nor emitted or counted. The same keys are sampled on both sides,
+    /// so a sampled difference is an actual difference, and a different [`hash_seed`](Self::hash_seed) samples
+    /// different keys. The [`DiffRunReport`](crate::run_report::DiffRunReport)
+    /// of such a run is marked as [`approximate`](crate::run_report::DiffRunReport::is_approximate).
+    pub fn sample_keys(mut self, one_in: u64) -> Self {
+        self.key_sample = NonZeroU64::new(one_in).filter(|one_in| one_in.get() > 1);
+        self
+    }
+
+    /// Set the number of records, after which [`diff_resumable`](CsvByteDiff::diff_resumable) writes its checkpoint
+    /// (default: [`DEFAULT_CHECKPOINT_INTERVAL`]). A value of `0` only writes checkpoints on request
+    /// (see [`DiffByteRecordsResumable::checkpoint`]).
+    ///
+    /// Every checkpoint holds all records, that have not been matched yet, so writing it takes longer,
+    /// the more the CSVs differ.
+    pub fn checkpoint_interval(mut self, checkpoint_interval: u64) -> Self {
+        self.checkpoint_interval = checkpoint_interval;
+        self
+    }
+
+    /// Set the number of hashed records, that the hashing tasks may send ahead of the comparison
+    /// (default: [`DEFAULT_CHANNEL_CAPACITY`]).
+    ///
+    /// The records are compared, while the [`DiffByteRecordsIterator`] is advanced, so when its consumer is slow
+    /// (e.g. because it writes every difference to a database), the hashing tasks stop reading the CSVs,
+    /// as soon as this many records are waiting. A larger capacity lets them read further ahead and keeps the threads busy
+    /// at the cost of holding more records in memory; a smaller capacity saves memory. A capacity of `0` hands over
+    /// every record directly.
+    ///
+    /// The other channels of a comparison only ever carry a single message (e.g. the header of a CSV), so they
+    /// don't need to be configured. There is no such option for [`CsvByteDiffLocal`], because it sends only hashes
+    /// and positions of records to its comparison.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// Set, how often the records, that have been matched, are flushed from the hash maps of the comparison
+    /// (default: every [one percent of the rows](FlushThreshold::default), that have been read so far).
+    ///
+    /// Flush more often to save memory, when the CSVs are mostly equal and in the same order, and less often
+    /// (or [never](FlushThreshold::Unlimited)), when they are very different, because every flush scans all records,
+    /// that are still unmatched.
+    pub fn flush_threshold(mut self, flush_threshold: FlushThreshold) -> Self {
+        self.flush_threshold = flush_threshold;
+        self
+    }
+
+    /// Tune the channel capacity, the flush threshold and the recycling of records together for speed or memory usage
+    /// (see [`Preset`]).
+    ///
+    /// Options, that are set after the preset (e.g. [`channel_capacity`](Self::channel_capacity)), override its values.
+    pub fn preset(mut self, preset: Preset) -> Self {
+        self.channel_capacity = preset.channel_capacity();
+        self.flush_threshold = preset.flush_threshold();
+        self.recycle_capacity = preset.recycle_capacity();
+        self
+    }
+
+    /// Use the whole record as the key instead of the [`primary_key_columns`](Self::primary_key_columns) (default: `false`),
+    /// e.g. for CSVs without any usable primary key.
+    ///
+    /// Records then only match, when they are equal, which turns the comparison into a pure set difference:
+    /// differences are only reported as [`Add`](crate::diff_row::DiffByteRecord::Add) or
+    /// [`Delete`](crate::diff_row::DiffByteRecord::Delete) and never as [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+    pub fn whole_row_as_key(mut self, whole_row_as_key: bool) -> Self {
+        self.whole_row_as_key = whole_row_as_key;
+        self
+    }
+
+    /// Use all columns except `columns` as the primary key (instead of the [`primary_key_columns`](Self::primary_key_columns)),
+    /// e.g. for wide CSVs, where only a few columns hold values, that might change.
+    ///
+    /// The columns of the key are resolved, when comparing, against the header of each CSV (or, without a header,
+    /// against its first record). If all columns are excluded, the whole record acts as the key
+    /// (see [`whole_row_as_key`](Self::whole_row_as_key)).
+    pub fn primary_key_all_columns_except(
+        mut self,
+        columns: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        self.primary_key_excluded_columns = Some(columns.into_iter().collect());
+        self
+    }
+
+    /// Seed the hashes of keys and records and the internal hash maps with `hash_seed` (default: unseeded).
+    ///
+    /// Without a seed, the hashes are predictable, so CSVs could be crafted, whose different keys (or records)
+    /// have the same hash and would therefore be matched (or considered equal). Use a secret seed
+    /// (e.g. [`random_hash_seed`](Self::random_hash_seed)), when comparing untrusted input, e.g. user-uploaded files.
+    pub fn hash_seed(mut self, hash_seed: u64) -> Self {
+        self.hash_seed = Some(hash_seed);
+        self
+    }
+
+    /// Seed the hashes with a random seed (see [`hash_seed`](Self::hash_seed)).
+    pub fn random_hash_seed(self) -> Self {
+        self.hash_seed(rand::random())
+    }
+
+    fn has_primary_key(&self) -> bool {
+        self.whole_row_as_key
+            || self.primary_key_excluded_columns.is_some()
+            || !self.primary_key_columns.is_empty()
+    }
+
+    fn primary_key_columns_or_whole_row(&self) -> HashSet<usize> {
+        if self.whole_row_as_key {
+            HashSet::new()
+        } else {
+            self.primary_key_columns.clone()
+        }
+    }
+
     #[cfg(not(feature = "rayon-threads"))]
     pub fn build(self) -> Result<CsvByteDiff<T>, CsvByteDiffBuilderError> {
-        if !self.primary_key_columns.is_empty() {
+        if self.has_primary_key() {
             Ok(CsvByteDiff {
-                primary_key_columns: self.primary_key_columns,
-                hash_task_spawner: RefCell::new(Some(self.hash_task_spawner)),
+                primary_key_columns: self.primary_key_columns_or_whole_row(),
+                field_comparators: self.field_comparators,
+                empty_csv_semantics: self.empty_csv_semantics,
+                cancellation_token: self.cancellation_token,
+                max_duration: self.max_duration,
+                memory_budget: self.memory_budget,
+                canonical_column_order: self.canonical_column_order,
+                max_diffs: self.max_diffs,
+                key_sample: self.key_sample,
+                checkpoint_interval: self.checkpoint_interval,
+                primary_key_excluded_columns: self
+                    .primary_key_excluded_columns
+                    .filter(|_| !self.whole_row_as_key),
+                hash_seed: self.hash_seed,
+                channel_capacity: self.channel_capacity,
+                flush_threshold: self.flush_threshold,
+                recycle_capacity: self.recycle_capacity,
+                hash_task_spawner: self.hash_task_spawner,
             })
         } else {
             Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
@@ -251,8 +952,23 @@ impl CsvByteDiffBuilder<CsvHashTaskSpawnerRayon> {
     pub fn new() -> Self {
         Self {
             primary_key_columns: std::iter::once(0).collect(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_duration: None,
+            memory_budget: None,
+            canonical_column_order: false,
+            max_diffs: None,
+            key_sample: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            flush_threshold: FlushThreshold::default(),
+            recycle_capacity: None,
+            whole_row_as_key: false,
             hash_task_spawner: None,
-            _phantom: PhantomData::default(),
+            _phantom: PhantomData,
         }
     }
 
@@ -263,14 +979,30 @@ impl CsvByteDiffBuilder<CsvHashTaskSpawnerRayon> {
 
     #[cfg(feature = "rayon-threads")]
     pub fn build(self) -> Result<CsvByteDiff<CsvHashTaskSpawnerRayon>, CsvByteDiffBuilderError> {
-        if !self.primary_key_columns.is_empty() {
+        if self.has_primary_key() {
             Ok(CsvByteDiff {
-                primary_key_columns: self.primary_key_columns,
+                primary_key_columns: self.primary_key_columns_or_whole_row(),
+                field_comparators: self.field_comparators,
+                empty_csv_semantics: self.empty_csv_semantics,
+                cancellation_token: self.cancellation_token,
+                max_duration: self.max_duration,
+                memory_budget: self.memory_budget,
+                canonical_column_order: self.canonical_column_order,
+                max_diffs: self.max_diffs,
+                key_sample: self.key_sample,
+                checkpoint_interval: self.checkpoint_interval,
+                primary_key_excluded_columns: self
+                    .primary_key_excluded_columns
+                    .filter(|_| !self.whole_row_as_key),
+                hash_seed: self.hash_seed,
+                channel_capacity: self.channel_capacity,
+                flush_threshold: self.flush_threshold,
+                recycle_capacity: self.recycle_capacity,
                 hash_task_spawner: match self.hash_task_spawner {
-                    Some(x) => RefCell::new(Some(x)),
-                    None => RefCell::new(Some(CsvHashTaskSpawnerRayon::with_thread_pool_owned(
+                    Some(x) => x,
+                    None => CsvHashTaskSpawnerRayon::with_thread_pool_owned(
                         rayon::ThreadPoolBuilder::new().build()?,
-                    ))),
+                    ),
                 },
             })
         } else {
@@ -338,7 +1070,16 @@ Ok(())
 )]
 #[derive(Debug)]
 pub struct CsvByteDiffLocal<T: CsvHashTaskSpawnerLocal> {
+    // empty, if the whole record acts as the key
     primary_key_columns: HashSet<usize>,
+    field_comparators: FieldComparators,
+    empty_csv_semantics: EmptyCsvSemantics,
+    cancellation_token: CancellationToken,
+    max_diffs: Option<usize>,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    hash_seed: Option<u64>,
+    is_chunked_hashing: bool,
+    flush_threshold: FlushThreshold,
     hash_task_spawner: T,
 }
 
@@ -402,6 +1143,15 @@ Ok(())
 #[cfg_attr(feature = "rayon-threads", derive(Default))]
 pub struct CsvByteDiffLocalBuilder<'tp, T: CsvHashTaskSpawnerLocal> {
     primary_key_columns: HashSet<usize>,
+    field_comparators: FieldComparators,
+    empty_csv_semantics: EmptyCsvSemantics,
+    cancellation_token: CancellationToken,
+    max_diffs: Option<usize>,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    hash_seed: Option<u64>,
+    is_chunked_hashing: bool,
+    flush_threshold: FlushThreshold,
+    whole_row_as_key: bool,
     #[cfg(feature = "rayon-threads")]
     hash_task_spawner: Option<CsvHashTaskSpawnerLocalRayon<'tp>>,
     #[cfg(feature = "rayon-threads")]
@@ -423,8 +1173,17 @@ where
     {
         Self {
             primary_key_columns: std::iter::once(0).collect(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_diffs: None,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            is_chunked_hashing: false,
+            flush_threshold: FlushThreshold::default(),
+            whole_row_as_key: false,
             hash_task_spawner: csv_hash_task_spawner_builder.build(),
-            _phantom: PhantomData::default(),
+            _phantom: PhantomData,
         }
     }
 
@@ -433,64 +1192,356 @@ where
         self
     }
 
-    #[cfg(not(feature = "rayon-threads"))]
-    pub fn build(self) -> Result<CsvByteDiffLocal<T>, CsvByteDiffBuilderError> {
-        if !self.primary_key_columns.is_empty() {
-            Ok(CsvByteDiffLocal {
-                primary_key_columns: self.primary_key_columns,
-                hash_task_spawner: self.hash_task_spawner,
-            })
-        } else {
-            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+    /// Compare the fields of column `column` with `comparator` instead of byte-wise.
+    ///
+    /// Records, whose byte-wise changed fields are all equal according to their comparators, are not reported as modified.
+    /// See [`FieldComparator`](crate::field_comparator::FieldComparator) for more information.
+    pub fn field_comparator(
+        mut self,
+        column: usize,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.field_comparators.insert(column, Arc::new(comparator));
+        self
+    }
+
+    /// Like [`field_comparator`](Self::field_comparator), but for the column named `header`, which is looked up
+    /// in the header of the left CSV (or of the right CSV, if the left one has none), when comparing.
+    ///
+    /// It replaces a comparator of the same column, that has been registered by index.
+    /// If there is no column named `header`, the comparator is not used.
+    pub fn field_comparator_by_header(
+        mut self,
+        header: impl AsRef<[u8]>,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.field_comparators
+            .insert_by_header(header.as_ref().to_vec(), Arc::new(comparator));
+        self
+    }
+
+    /// Don't report changes in the columns `columns` as [`Modify`](crate::diff_row::DiffByteRecord::Modify)
+    /// and don't include them in `field_indices`, but still emit their current values as part of the records.
+    ///
+    /// This registers an [`IgnoreComparator`](crate::field_comparator::IgnoreComparator) for each of the columns
+    /// (replacing any other comparator of those columns).
+    pub fn ignore_columns_for_modify(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        let ignore_comparator: Arc<dyn FieldComparator> = Arc::new(IgnoreComparator);
+        for column in columns {
+            self.field_comparators
+                .insert(column, Arc::clone(&ignore_comparator));
         }
+        self
     }
-}
 
-#[cfg(feature = "rayon-threads")]
-impl<'tp> CsvByteDiffLocalBuilder<'tp, CsvHashTaskSpawnerLocalRayon<'tp>> {
-    pub fn new() -> Self {
-        Self {
-            primary_key_columns: std::iter::once(0).collect(),
-            hash_task_spawner: None,
-            _phantom: PhantomData::default(),
+    /// Compare the fields of the columns `columns` by their numeric value instead of byte-wise, so that different
+    /// representations of the same number (e.g. `1.0`, `1.00` and `+1`) are equal
+    /// (see [`NumericComparator`](crate::field_comparator::NumericComparator)).
+    ///
+    /// The fields are canonicalized before they are hashed, so this also applies to key columns, and the columns
+    /// only appear in `field_indices`, if their values differ. The emitted records keep their original fields.
+    pub fn numeric_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        for column in columns {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_numeric_column(column);
         }
+        self
     }
 
-    pub fn rayon_thread_pool(mut self, thread_pool: &'tp rayon::ThreadPool) -> Self {
-        self.hash_task_spawner =
-            Some(CsvHashTaskSpawnerLocalBuilderRayon::new(thread_pool).build());
+    /// Treat the fields `tokens` (e.g. `NULL`, `N/A` or `-`) in all columns as equivalent to an empty field,
+    /// e.g. for exports from different databases, which represent missing values differently.
+    ///
+    /// Like [`numeric_columns`](Self::numeric_columns), this is applied before the fields are hashed and
+    /// the emitted records keep their original fields.
+    pub fn null_tokens<N: Into<Vec<u8>>>(mut self, tokens: impl IntoIterator<Item = N>) -> Self {
+        for token in tokens {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_null_token(None, token.into());
+        }
         self
     }
 
-    #[cfg(feature = "rayon-threads")]
-    pub fn build(
-        self,
-    ) -> Result<CsvByteDiffLocal<CsvHashTaskSpawnerLocalRayon<'tp>>, CsvByteDiffBuilderError> {
-        if !self.primary_key_columns.is_empty() {
-            Ok(CsvByteDiffLocal {
-                primary_key_columns: self.primary_key_columns,
-                hash_task_spawner: match self.hash_task_spawner {
-                    Some(x) => x,
-                    None => CsvHashTaskSpawnerLocalRayon::new(RayonScope::with_thread_pool_owned(
-                        rayon::ThreadPoolBuilder::new().build()?,
-                    )),
-                },
-            })
-        } else {
-            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+    /// Like [`null_tokens`](Self::null_tokens), but only for the fields of column `column`.
+    pub fn null_tokens_for_column<N: Into<Vec<u8>>>(
+        mut self,
+        column: usize,
+        tokens: impl IntoIterator<Item = N>,
+    ) -> Self {
+        for token in tokens {
+            self.field_comparators
+                .canonicalizer_mut()
+                .insert_null_token(Some(column), token.into());
         }
+        self
     }
-}
 
-#[derive(Debug, Error)]
-pub enum CsvByteDiffBuilderError {
-    #[error("No primary key columns have been specified. You need to provide at least one column index.")]
-    NoPrimaryKeyColumns,
-    #[cfg(feature = "rayon-threads")]
-    #[error("An error occured when trying to build the rayon thread pool.")]
+    /// Blank the parts of the fields of column `column`, that match `ignore_pattern`, before comparing them,
+    /// e.g. to ignore the volatile suffix of a session id, while still comparing its prefix.
+    /// Several patterns of the same column are applied in the order they have been added.
+    ///
+    /// Like [`numeric_columns`](Self::numeric_columns), this is applied before the fields are hashed and
+    /// the emitted records keep their original fields.
+    pub fn ignore_pattern(mut self, column: usize, ignore_pattern: IgnorePattern) -> Self {
+        self.field_comparators
+            .canonicalizer_mut()
+            .insert_ignore_pattern(column, ignore_pattern);
+        self
+    }
+
+    /// Replace the fields of the columns `columns` with `***` in the emitted records, e.g. so that a report of
+    /// the differences can be shared without leaking personal data.
+    ///
+    /// The records are still compared by their real fields, so a change in a redacted column is reported as
+    /// [`Modify`](crate::diff_row::DiffByteRecord::Modify) with the column in its `field_indices`,
+    /// even though both of its fields read `***`.
+    pub fn redact_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        for column in columns {
+            self.field_comparators.insert_redacted_column(column);
+        }
+        self
+    }
+
+    /// Set how a completely empty CSV is compared with a non-empty CSV (default is [`EmptyCsvSemantics::NoRecords`]).
+    ///
+    /// See [`EmptyCsvSemantics`](crate::empty_csv::EmptyCsvSemantics) for more information.
+    pub fn empty_csv_semantics(mut self, empty_csv_semantics: EmptyCsvSemantics) -> Self {
+        self.empty_csv_semantics = empty_csv_semantics;
+        self
+    }
+
+    /// Check `cancellation_token` while comparing, so that the comparison can be cancelled from another thread.
+    ///
+    /// See [`CancellationToken`](crate::cancellation::CancellationToken) for more information.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Return at most `max_diffs` differences (default: unlimited).
+    ///
+    /// Both CSVs are still hashed completely (records can only be classified, after all hashes are known),
+    /// but records beyond the limit are never read a second time in order to emit them.
+    pub fn max_diffs(mut self, max_diffs: usize) -> Self {
+        self.max_diffs = Some(max_diffs);
+        self
+    }
+
+    /// Use the whole record as the key instead of the [`primary_key_columns`](Self::primary_key_columns) (default: `false`),
+    /// e.g. for CSVs without any usable primary key.
+    ///
+    /// Records then only match, when they are equal, which turns the comparison into a pure set difference:
+    /// differences are only reported as [`Add`](crate::diff_row::DiffByteRecord::Add) or
+    /// [`Delete`](crate::diff_row::DiffByteRecord::Delete) and never as [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+    pub fn whole_row_as_key(mut self, whole_row_as_key: bool) -> Self {
+        self.whole_row_as_key = whole_row_as_key;
+        self
+    }
+
+    /// Use all columns except `columns` as the primary key (instead of the [`primary_key_columns`](Self::primary_key_columns)),
+    /// e.g. for wide CSVs, where only a few columns hold values, that might change.
+    ///
+    /// The columns of the key are resolved, when comparing, against the header of each CSV (or, without a header,
+    /// against its first record). If all columns are excluded, the whole record acts as the key
+    /// (see [`whole_row_as_key`](Self::whole_row_as_key)).
+    pub fn primary_key_all_columns_except(
+        mut self,
+        columns: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        self.primary_key_excluded_columns = Some(columns.into_iter().collect());
+        self
+    }
+
+    /// Seed the hashes of keys and records and the internal hash maps with `hash_seed` (default: unseeded).
+    ///
+    /// Without a seed, the hashes are predictable, so CSVs could be crafted, whose different keys (or records)
+    /// have the same hash and would therefore be matched (or considered equal). Use a secret seed
+    /// (e.g. [`random_hash_seed`](Self::random_hash_seed)), when comparing untrusted input, e.g. user-uploaded files.
+    pub fn hash_seed(mut self, hash_seed: u64) -> Self {
+        self.hash_seed = Some(hash_seed);
+        self
+    }
+
+    /// Seed the hashes with a random seed (see [`hash_seed`](Self::hash_seed)).
+    pub fn random_hash_seed(self) -> Self {
+        self.hash_seed(rand::random())
+    }
+
+    /// Hash the records of each CSV in parallel on the thread pool (default: `false`), e.g. for very large files
+    /// on fast storage, where hashing in the single parsing task of a CSV is the bottleneck.
+    ///
+    /// The parsing task splits the CSV at record boundaries into chunks of records, which are hashed by additional tasks.
+    /// The records keep their positions, so the lines of the differences are the same as without chunks.
+    pub fn chunked_hashing(mut self, is_chunked_hashing: bool) -> Self {
+        self.is_chunked_hashing = is_chunked_hashing;
+        self
+    }
+
+    /// Set, how often the records, that have been matched, are flushed from the hash maps of the comparison
+    /// (default: every [one percent of the rows](FlushThreshold::default) of a CSV).
+    ///
+    /// Flush less often (or [never](FlushThreshold::Unlimited)), when the CSVs are very different or ordered differently,
+    /// because every flush scans all records, that are still unmatched.
+    pub fn flush_threshold(mut self, flush_threshold: FlushThreshold) -> Self {
+        self.flush_threshold = flush_threshold;
+        self
+    }
+
+    fn has_primary_key(&self) -> bool {
+        self.whole_row_as_key
+            || self.primary_key_excluded_columns.is_some()
+            || !self.primary_key_columns.is_empty()
+    }
+
+    fn primary_key_columns_or_whole_row(&self) -> HashSet<usize> {
+        if self.whole_row_as_key {
+            HashSet::new()
+        } else {
+            self.primary_key_columns.clone()
+        }
+    }
+
+    #[cfg(not(feature = "rayon-threads"))]
+    pub fn build(self) -> Result<CsvByteDiffLocal<T>, CsvByteDiffBuilderError> {
+        if self.has_primary_key() {
+            Ok(CsvByteDiffLocal {
+                primary_key_columns: self.primary_key_columns_or_whole_row(),
+                field_comparators: self.field_comparators,
+                empty_csv_semantics: self.empty_csv_semantics,
+                cancellation_token: self.cancellation_token,
+                max_diffs: self.max_diffs,
+                primary_key_excluded_columns: self
+                    .primary_key_excluded_columns
+                    .filter(|_| !self.whole_row_as_key),
+                hash_seed: self.hash_seed,
+                is_chunked_hashing: self.is_chunked_hashing,
+                flush_threshold: self.flush_threshold,
+                hash_task_spawner: self.hash_task_spawner,
+            })
+        } else {
+            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+        }
+    }
+}
+
+#[cfg(feature = "rayon-threads")]
+impl<'tp> CsvByteDiffLocalBuilder<'tp, CsvHashTaskSpawnerLocalRayon<'tp>> {
+    pub fn new() -> Self {
+        Self {
+            primary_key_columns: std::iter::once(0).collect(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_diffs: None,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            is_chunked_hashing: false,
+            flush_threshold: FlushThreshold::default(),
+            whole_row_as_key: false,
+            hash_task_spawner: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn rayon_thread_pool(mut self, thread_pool: &'tp rayon::ThreadPool) -> Self {
+        self.hash_task_spawner =
+            Some(CsvHashTaskSpawnerLocalBuilderRayon::new(thread_pool).build());
+        self
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    pub fn build(
+        self,
+    ) -> Result<CsvByteDiffLocal<CsvHashTaskSpawnerLocalRayon<'tp>>, CsvByteDiffBuilderError> {
+        if self.has_primary_key() {
+            Ok(CsvByteDiffLocal {
+                primary_key_columns: self.primary_key_columns_or_whole_row(),
+                field_comparators: self.field_comparators,
+                empty_csv_semantics: self.empty_csv_semantics,
+                cancellation_token: self.cancellation_token,
+                max_diffs: self.max_diffs,
+                primary_key_excluded_columns: self
+                    .primary_key_excluded_columns
+                    .filter(|_| !self.whole_row_as_key),
+                hash_seed: self.hash_seed,
+                is_chunked_hashing: self.is_chunked_hashing,
+                flush_threshold: self.flush_threshold,
+                hash_task_spawner: match self.hash_task_spawner {
+                    Some(x) => x,
+                    None => CsvHashTaskSpawnerLocalRayon::new(RayonScope::with_thread_pool_owned(
+                        rayon::ThreadPoolBuilder::new().build()?,
+                    )),
+                },
+            })
+        } else {
+            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CsvByteDiffBuilderError {
+    #[error("No primary key columns have been specified. You need to provide at least one column index.")]
+    NoPrimaryKeyColumns,
+    #[cfg(feature = "rayon-threads")]
+    #[error("An error occured when trying to build the rayon thread pool.")]
     ThreadPoolBuildError(#[from] rayon::ThreadPoolBuildError),
 }
 
+impl ErrorCode for CsvByteDiffBuilderError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NoPrimaryKeyColumns => "CSVDIFF_NO_PRIMARY_KEY",
+            #[cfg(feature = "rayon-threads")]
+            Self::ThreadPoolBuildError(_) => "CSVDIFF_THREAD_POOL",
+        }
+    }
+}
+
+/// A primary key column is out of bounds of a CSV, e.g. key column `5` of a CSV with only `3` columns
+/// (the number of columns is taken from the header or, without header, from the first record).
+///
+/// When comparing CSVs, it is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is a
+/// `PrimaryKeyColumnOutOfBoundsError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+#[error("the primary key column {column} is out of bounds of a CSV with {num_of_columns} columns")]
+pub struct PrimaryKeyColumnOutOfBoundsError {
+    column: usize,
+    num_of_columns: usize,
+}
+
+impl PrimaryKeyColumnOutOfBoundsError {
+    pub(crate) fn new(column: usize, num_of_columns: usize) -> Self {
+        Self {
+            column,
+            num_of_columns,
+        }
+    }
+
+    /// The index of the primary key column, that is out of bounds.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The number of columns of the CSV.
+    pub fn num_of_columns(&self) -> usize {
+        self.num_of_columns
+    }
+}
+
+impl ErrorCode for PrimaryKeyColumnOutOfBoundsError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_KEY_OUT_OF_BOUNDS"
+    }
+}
+
+impl From<PrimaryKeyColumnOutOfBoundsError> for csv::Error {
+    fn from(err: PrimaryKeyColumnOutOfBoundsError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::InvalidInput, err))
+    }
+}
+
 #[derive(Debug, Error)]
 #[cfg(feature = "rayon-threads")]
 pub enum CsvDiffNewError {
@@ -498,6 +1549,15 @@ pub enum CsvDiffNewError {
     ThreadPoolBuildError(#[from] rayon::ThreadPoolBuildError),
 }
 
+#[cfg(feature = "rayon-threads")]
+impl ErrorCode for CsvDiffNewError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::ThreadPoolBuildError(_) => "CSVDIFF_THREAD_POOL",
+        }
+    }
+}
+
 #[cfg(feature = "rayon-threads")]
 impl CsvByteDiffLocal<CsvHashTaskSpawnerLocalRayon<'_>> {
     /// Constructs a new `CsvByteDiffLocal<CsvHashTaskSpawnerRayon<'_>>` with a default configuration.
@@ -509,10 +1569,33 @@ impl CsvByteDiffLocal<CsvHashTaskSpawnerLocalRayon<'_>> {
     /// If you need to have more control over the configuration of `CsvByteDiffLocal<CsvHashTaskSpawnerRayon<'_>>`,
     /// consider using a [`CsvByteDiffLocalBuilder`](CsvByteDiffLocalBuilder) instead.
     pub fn new() -> Result<Self, CsvDiffNewError> {
+        Self::with_thread_pool_builder(rayon::ThreadPoolBuilder::new())
+    }
+
+    /// Like [`new`](CsvByteDiffLocal::new), but the rayon thread-pool has `num_threads` threads
+    /// (instead of one thread per CPU core), e.g. so that other work on a shared host is not starved.
+    /// If `num_threads` is `0`, the default number of threads is used.
+    pub fn with_num_threads(num_threads: usize) -> Result<Self, CsvDiffNewError> {
+        Self::with_thread_pool_builder(rayon::ThreadPoolBuilder::new().num_threads(num_threads))
+    }
+
+    /// Like [`new`](CsvByteDiffLocal::new), but the rayon thread-pool is built with `thread_pool_builder`,
+    /// e.g. to set the number of threads or their names.
+    pub fn with_thread_pool_builder(
+        thread_pool_builder: rayon::ThreadPoolBuilder,
+    ) -> Result<Self, CsvDiffNewError> {
         let mut instance = Self {
             primary_key_columns: HashSet::new(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_diffs: None,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            is_chunked_hashing: false,
+            flush_threshold: FlushThreshold::default(),
             hash_task_spawner: CsvHashTaskSpawnerLocalRayon::new(
-                RayonScope::with_thread_pool_owned(rayon::ThreadPoolBuilder::new().build()?),
+                RayonScope::with_thread_pool_owned(thread_pool_builder.build()?),
             ),
         };
         instance.primary_key_columns.insert(0);
@@ -525,6 +1608,14 @@ impl CsvByteDiffLocal<CsvHashTaskSpawnerLocalCrossbeam> {
     pub fn new() -> Self {
         let mut instance = Self {
             primary_key_columns: HashSet::new(),
+            field_comparators: FieldComparators::default(),
+            empty_csv_semantics: EmptyCsvSemantics::default(),
+            cancellation_token: CancellationToken::default(),
+            max_diffs: None,
+            primary_key_excluded_columns: None,
+            hash_seed: None,
+            is_chunked_hashing: false,
+            flush_threshold: FlushThreshold::default(),
             hash_task_spawner: CsvHashTaskSpawnerLocalCrossbeam::new(CrossbeamScope::new()),
         };
         instance.primary_key_columns.insert(0);
@@ -588,6 +1679,116 @@ where
         csv_left: Csv<R>,
         csv_right: Csv<R>,
     ) -> csv::Result<DiffByteRecords> {
+        self.diff_with_run_report(csv_left, csv_right)
+            .map(|(diff_byte_records, _)| diff_byte_records)
+    }
+
+    /// Compares `csv_left` with `csv_right` and returns the differences together with _all_ parse errors
+    /// of both CSVs as a [`DiffReport`], instead of failing at the first malformed record.
+    ///
+    /// Malformed records are skipped (regardless of the [`ErrorPolicy`] of the `Csv`s), so they are not part of the differences.
+    /// Errors, after which reading can't continue (e.g. I/O errors), are still returned as `Err`.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::csv_diff::CsvByteDiffLocal;
+    use csv_diff::csv::Csv;
+    use csv_diff::error_code::ErrorCode;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = "id,name,kind\n\
+                         1,lemon\n\
+                         2,strawberry,fruit\n\
+                         3,cherry,fruit,red";
+    let csv_data_right = "id,name,kind\n\
+                          1,lemon,fruit\n\
+                          2,strawberry,nut\n\
+                          3";
+
+    let diff_report = CsvByteDiffLocal::new()?.diff_with_report(
+        Csv::with_reader_seek(csv_data_left.as_bytes()),
+        Csv::with_reader_seek(csv_data_right.as_bytes()),
+    )?;
+
+    assert_eq!(diff_report.records().as_slice().len(), 2);
+    let errors = diff_report.errors();
+    assert_eq!(
+        errors.left().iter().map(|err| err.line()).collect::<Vec<_>>(),
+        vec![2, 4]
+    );
+    assert_eq!(
+        errors.right().iter().map(|err| (err.line(), err.code())).collect::<Vec<_>>(),
+        vec![(4, "CSVDIFF_RAGGED_ROW")]
+    );
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_report<R: Read + Seek + Send>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<DiffReport> {
+        let (diff_byte_records, run_report) = self.diff_with_run_report(
+            csv_left.with_error_policy(ErrorPolicy::SkipAndReport),
+            csv_right.with_error_policy(ErrorPolicy::SkipAndReport),
+        )?;
+        Ok(DiffReport::new(diff_byte_records, run_report))
+    }
+
+    /// Like [`diff`](CsvByteDiffLocal::diff), but additionally returns the malformed records, that have been skipped,
+    /// because `csv_left` or `csv_right` has been created with
+    /// [`ErrorPolicy::SkipAndReport`](crate::error_policy::ErrorPolicy::SkipAndReport)
+    /// (see [`CsvBuilder::error_policy`](crate::csv::CsvBuilder::error_policy)).
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder};
+    use csv_diff::error_policy::ErrorPolicy;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = "id,name,kind\n\
+                         1,lemon,fruit\n\
+                         2,strawberry,fruit,red\n\
+                         3,cherry,fruit";
+    let csv_data_right = "id,name,kind\n\
+                          1,lemon,fruit\n\
+                          3,cherry,fruit";
+    let csv_builder = CsvBuilder::new().error_policy(ErrorPolicy::SkipAndReport);
+
+    let (diff_byte_records, skipped_records) = CsvByteDiffLocal::new()?.diff_with_skipped_records(
+        csv_builder.build_with_reader_seek(csv_data_left.as_bytes()),
+        csv_builder.build_with_reader_seek(csv_data_right.as_bytes()),
+    )?;
+
+    assert!(diff_byte_records.as_slice().is_empty());
+    assert_eq!(skipped_records.left().len(), 1);
+    assert_eq!(skipped_records.left()[0].line(), 3);
+    assert!(skipped_records.right().is_empty());
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_skipped_records<R: Read + Seek + Send>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<(DiffByteRecords, SkippedRecords)> {
+        let (diff_byte_records, run_report) = self.diff_with_run_report(csv_left, csv_right)?;
+        Ok((
+            diff_byte_records,
+            SkippedRecords::new(
+                run_report.skipped_records_left().to_vec(),
+                run_report.skipped_records_right().to_vec(),
+            ),
+        ))
+    }
+
+    fn diff_with_run_report<R: Read + Seek + Send>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> csv::Result<(DiffByteRecords, DiffRunReport)> {
         use crossbeam_channel::unbounded;
 
         let (sender_total_lines_right, receiver_total_lines_right) = bounded(1);
@@ -596,103 +1797,537 @@ where
         let (sender_csv_reader_left, receiver_csv_reader_left) = bounded(1);
         let (sender_right, receiver) = unbounded();
         let sender_left = sender_right.clone();
+        // the records are read again for the differences, so they must be normalized again
+        let normalizers = (csv_left.normalizer(), csv_right.normalizer());
 
-        self.hash_task_spawner.spawn_hashing_tasks_and_send_result(
-            CsvHashTaskLineSenders::new(
-                sender_left,
-                sender_total_lines_left,
-                sender_csv_reader_left,
-                csv_left,
-            ),
-            CsvHashTaskLineSenders::new(
-                sender_right,
-                sender_total_lines_right,
-                sender_csv_reader_right,
-                csv_right,
-            ),
-            &self.primary_key_columns,
-        );
-
-        self.recv_hashes_and_compare(
-            receiver_total_lines_left,
-            receiver_total_lines_right,
-            receiver_csv_reader_left,
-            receiver_csv_reader_right,
-            receiver,
-        )
+        // the thread scope resumes a panic of one of its tasks, after all of them have finished
+        catch_panic(|| {
+            self.hash_task_spawner.spawn_hashing_tasks_and_send_result(
+                self.csv_hash_task_line_senders(
+                    sender_left,
+                    sender_total_lines_left,
+                    sender_csv_reader_left,
+                    csv_left,
+                ),
+                self.csv_hash_task_line_senders(
+                    sender_right,
+                    sender_total_lines_right,
+                    sender_csv_reader_right,
+                    csv_right,
+                ),
+                &self.primary_key_columns,
+            )
+        })?;
+        self.cancellation_token.check()?;
+
+        catch_panic(|| {
+            self.recv_hashes_and_compare(
+                receiver_total_lines_left,
+                receiver_total_lines_right,
+                receiver_csv_reader_left,
+                receiver_csv_reader_right,
+                receiver,
+                normalizers,
+            )
+        })?
     }
 
-    fn recv_hashes_and_compare<R>(
-        &self,
-        receiver_total_lines_left: Receiver<u64>,
-        receiver_total_lines_right: Receiver<u64>,
-        receiver_csv_reader_left: Receiver<csv::Result<Reader<R>>>,
-        receiver_csv_reader_right: Receiver<csv::Result<Reader<R>>>,
-        receiver: Receiver<CsvLeftRightParseResult<RecordHashWithPosition>>,
-    ) -> csv::Result<DiffByteRecords>
-    where
-        R: Read + Seek + Send,
-    {
-        let (total_lines_right, total_lines_left) = (
-            receiver_total_lines_right.recv().unwrap_or_default(),
-            receiver_total_lines_left.recv().unwrap_or_default(),
-        );
-        let (csv_reader_right_for_diff_seek, csv_reader_left_for_diff_seek) = (
-            receiver_csv_reader_right.recv().unwrap()?,
-            receiver_csv_reader_left.recv().unwrap()?,
-        );
-        let max_capacity_for_hash_map_right =
-            if total_lines_right / 100 < total_lines_right && total_lines_right / 100 == 0 {
-                total_lines_right
-            } else {
-                total_lines_right / 100
-            } as usize;
-        let max_capacity_for_hash_map_left =
-            if total_lines_left / 100 < total_lines_left && total_lines_left / 100 == 0 {
-                total_lines_left
-            } else {
-                total_lines_left / 100
-            } as usize;
-
-        let mut csv_hash_comparer = CsvHashComparer::with_capacity_and_reader(
-            max_capacity_for_hash_map_left,
-            max_capacity_for_hash_map_right,
-            csv_reader_left_for_diff_seek,
-            csv_reader_right_for_diff_seek,
-        );
-        csv_hash_comparer.compare_csv_left_right_parse_result(receiver)
+    /// Reads and hashes `csv` and returns the resulting [`HashIndex`], which can be saved and passed to
+    /// [`diff_with_hash_index`](CsvByteDiffLocal::diff_with_hash_index) later, in order to compare `csv`
+    /// as the left CSV without reading and hashing it again.
+    pub fn hash_index<R: Read + Seek + Send>(&self, csv: Csv<R>) -> csv::Result<HashIndex> {
+        let config = self.hash_index_config(csv.normalizer().is_some());
+        let (mut csv_reader, header_info, total_lines, records) = self.hash_csv(csv)?;
+        Ok(HashIndex::new(
+            config,
+            hash_index::csv_len_and_hash(&mut csv_reader)?,
+            total_lines,
+            header_info,
+            records,
+        ))
     }
-}
-
-#[cfg(test)]
-mod tests {
 
-    use super::*;
-    use crate::csv::CsvReaderBuilderExt;
-    use crate::diff_result::DiffByteRecords;
-    use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
-    use pretty_assertions::assert_eq;
-    use std::error::Error;
+    /// Reads and hashes `csv` and returns a [`HashSnapshot`], which only holds the key hash, the record hash and
+    /// the line of every record. Save it and compare a later version of `csv` against it with
+    /// [`diff_with_hash_snapshot`](CsvByteDiffLocal::diff_with_hash_snapshot), without keeping `csv` itself.
+    pub fn hash_snapshot<R: Read + Seek + Send>(&self, csv: Csv<R>) -> csv::Result<HashSnapshot> {
+        self.hash_index(csv).map(HashSnapshot::from)
+    }
 
-    fn csv_diff_local_with_sorting<T: CsvHashTaskSpawnerLocal>(
-        csv_left: &str,
-        csv_right: &str,
-        mut expected: DiffByteRecords,
-        csv_diff: CsvByteDiffLocal<T>,
-    ) -> Result<(), Box<dyn Error>> {
-        let mut diff_res_actual = csv_diff.diff(
-            Csv::with_reader_seek(csv_left.as_bytes()),
-            Csv::with_reader_seek(csv_right.as_bytes()),
-        )?;
+    /// Compares the CSV, that `hash_snapshot_left` has been taken of, with `csv_right` and returns the records of
+    /// `csv_right`, that are new or modified, and the lines of the snapshotted CSV, that have been deleted.
+    ///
+    /// Only `csv_right` is read and hashed. Since the fields of the snapshotted records are not known,
+    /// [`field_comparator`](CsvByteDiffLocalBuilder::field_comparator)s can't be applied to modified records
+    /// (their canonicalizations are part of the hashes, though). Returns a [`HashIndexMismatchError`],
+    /// if the snapshot has been taken with other primary key columns, another hash seed or another canonicalization of
+    /// fields (e.g. [`numeric_columns`](CsvByteDiffLocalBuilder::numeric_columns)) or if only one of the snapshotted CSV and
+    /// `csv_right` is [normalized](crate::csv::CsvBuilder::normalize).
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+    use csv_diff::diff_row::ByteRecordLineInfo;
+    use csv_diff::hash_snapshot::{HashSnapshot, HashSnapshotDiffRecord};
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_last_week = "id,name,kind\n\
+                              1,lemon,fruit\n\
+                              2,strawberry,fruit\n\
+                              3,cherry,fruit";
+    let csv_data_today = "id,name,kind\n\
+                          1,lemon,fruit\n\
+                          2,strawberry,nut";
+    let csv_byte_diff = CsvByteDiffLocal::new()?;
 
-        diff_res_actual.sort_by_line();
-        expected.sort_by_line();
+    let mut saved_snapshot = Vec::new();
+    csv_byte_diff
+        .hash_snapshot(Csv::with_reader_seek(csv_data_last_week.as_bytes()))?
+        .write_to(&mut saved_snapshot)?;
 
-        assert_eq!(diff_res_actual, expected, "csv_diff_local failed");
+    let diff_records = csv_byte_diff.diff_with_hash_snapshot(
+        &HashSnapshot::read_from(saved_snapshot.as_slice())?,
+        Csv::with_reader_seek(csv_data_today.as_bytes()),
+    )?;
+
+    assert_eq!(
+        diff_records,
+        vec![
+            HashSnapshotDiffRecord::Modify {
+                line_left: 3,
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "strawberry", "nut"]), 3),
+            },
+            HashSnapshotDiffRecord::Delete { line: 4 },
+        ]
+    );
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_hash_snapshot<R: Read + Seek + Send>(
+        &self,
+        hash_snapshot_left: &HashSnapshot,
+        csv_right: Csv<R>,
+    ) -> csv::Result<Vec<HashSnapshotDiffRecord>> {
+        let normalizer_right = csv_right.normalizer();
+        if hash_snapshot_left.config() != &self.hash_index_config(normalizer_right.is_some()) {
+            return Err(HashIndexMismatchError.into());
+        }
+        let (mut csv_reader_right, _, _, records_right) = self.hash_csv(csv_right)?;
+        let mut diff_records = hash_snapshot_left.diff(
+            &records_right,
+            &mut csv_reader_right,
+            normalizer_right.as_ref(),
+        )?;
+        if let Some(max_diffs) = self.max_diffs {
+            diff_records.truncate(max_diffs);
+        }
+        Ok(diff_records
+            .into_iter()
+            .map(|diff_record| match diff_record {
+                HashSnapshotDiffRecord::Add(add) => {
+                    HashSnapshotDiffRecord::Add(self.redact_line_info(add))
+                }
+                HashSnapshotDiffRecord::Modify { line_left, add } => {
+                    HashSnapshotDiffRecord::Modify {
+                        line_left,
+                        add: self.redact_line_info(add),
+                    }
+                }
+                delete @ HashSnapshotDiffRecord::Delete { .. } => delete,
+            })
+            .collect())
+    }
+
+    fn redact_line_info(&self, byte_record_line_info: ByteRecordLineInfo) -> ByteRecordLineInfo {
+        let line = byte_record_line_info.line();
+        ByteRecordLineInfo::new(
+            self.field_comparators
+                .redact_record(byte_record_line_info.into_byte_record()),
+            line,
+        )
+    }
+
+    /// Reads and hashes `csv` on the current thread and returns its reader (for reading records again),
+    /// its header info, its number of lines and its hashed records in the order of the CSV.
+    fn hash_csv<R: Read + Seek + Send>(
+        &self,
+        csv: Csv<R>,
+    ) -> csv::Result<(
+        csv::Reader<R>,
+        CsvHeaderInfo,
+        u64,
+        Vec<RecordHashWithPosition>,
+    )> {
+        use crossbeam_channel::unbounded;
+
+        let (sender, receiver) = unbounded();
+        let (sender_total_lines, receiver_total_lines) = bounded(1);
+        let (sender_csv_reader, receiver_csv_reader) = bounded(1);
+
+        catch_panic(|| {
+            T::parse_hash_and_send_for_compare::<R, CsvParseResultLeft<RecordHashWithPosition>>(
+                self.csv_hash_task_line_senders(sender, sender_total_lines, sender_csv_reader, csv),
+                &self.primary_key_columns,
+            )
+        })?;
+        self.cancellation_token.check()?;
+
+        let (csv_reader, header_info, _) = receiver_csv_reader.recv().unwrap()?;
+        let mut records: Vec<_> = receiver
+            .into_iter()
+            .map(
+                |csv_left_right_parse_result| match csv_left_right_parse_result {
+                    CsvLeftRightParseResult::Left(record)
+                    | CsvLeftRightParseResult::Right(record) => record,
+                },
+            )
+            .collect();
+        // chunks of records might have been hashed out of order
+        records.sort_unstable_by_key(|record| record.pos.line);
+        Ok((
+            csv_reader,
+            header_info,
+            receiver_total_lines.recv().unwrap_or_default(),
+            records,
+        ))
+    }
+
+    /// Like [`diff`](CsvByteDiffLocal::diff), but only `csv_right` is read and hashed, while the hashes of `csv_left`
+    /// are taken from `hash_index_left`, which has been built by [`hash_index`](CsvByteDiffLocal::hash_index).
+    /// `csv_left` is read once as a whole, in order to verify that it is unchanged (which is much cheaper than parsing
+    /// and hashing its records), and only parsed again for the records, that are different.
+    ///
+    /// Returns a [`HashIndexMismatchError`], if the content of `csv_left` has changed since the index has been built
+    /// or if the index has been built with other primary key columns, another hash seed, another canonicalization of fields
+    /// (e.g. [`numeric_columns`](CsvByteDiffLocalBuilder::numeric_columns)) or with(out) a
+    /// [normalizer](crate::csv::CsvBuilder::normalize) for `csv_left`.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+    use csv_diff::hash_index::HashIndex;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = "id,name,kind\n\
+                         1,lemon,fruit\n\
+                         2,strawberry,fruit";
+    let csv_data_right = "id,name,kind\n\
+                          1,lemon,fruit\n\
+                          2,strawberry,nut";
+    let csv_byte_diff = CsvByteDiffLocal::new()?;
+
+    // build the index once and save it
+    let mut saved_index = Vec::new();
+    csv_byte_diff
+        .hash_index(Csv::with_reader_seek(csv_data_left.as_bytes()))?
+        .write_to(&mut saved_index)?;
+
+    // in a later run, only the right CSV is hashed
+    let hash_index_left = HashIndex::read_from(saved_index.as_slice())?;
+    let diff_byte_records = csv_byte_diff.diff_with_hash_index(
+        Csv::with_reader_seek(csv_data_left.as_bytes()),
+        &hash_index_left,
+        Csv::with_reader_seek(csv_data_right.as_bytes()),
+    )?;
+
+    assert_eq!(diff_byte_records.as_slice().len(), 1);
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_with_hash_index<R: Read + Seek + Send>(
+        &self,
+        csv_left: Csv<R>,
+        hash_index_left: &HashIndex,
+        csv_right: Csv<R>,
+    ) -> csv::Result<DiffByteRecords> {
+        use crossbeam_channel::unbounded;
+
+        // the records are read again for the differences, so they must be normalized again
+        let normalizers = (csv_left.normalizer(), csv_right.normalizer());
+        let mut csv_reader_left = csv_left.into_csv_reader();
+        if hash_index_left.config() != &self.hash_index_config(normalizers.0.is_some())
+            || hash_index::csv_len_and_hash(&mut csv_reader_left)?
+                != hash_index_left.csv_len_and_hash()
+        {
+            return Err(HashIndexMismatchError.into());
+        }
+
+        let (sender_total_lines_right, receiver_total_lines_right) = bounded(1);
+        let (sender_total_lines_left, receiver_total_lines_left) = bounded(1);
+        let (sender_csv_reader_right, receiver_csv_reader_right) = bounded(1);
+        let (sender_csv_reader_left, receiver_csv_reader_left) = bounded(1);
+        let (sender_right, receiver) = unbounded();
+        let sender_left = sender_right.clone();
+
+        sender_total_lines_left
+            .send(hash_index_left.total_lines())
+            .unwrap();
+        sender_csv_reader_left
+            .send(Ok((
+                csv_reader_left,
+                hash_index_left.header_info().clone(),
+                Vec::new(),
+            )))
+            .unwrap();
+        for &record in hash_index_left.records() {
+            sender_left
+                .send(CsvLeftRightParseResult::Left(record))
+                .unwrap();
+        }
+        drop(sender_left);
+
+        catch_panic(|| {
+            T::parse_hash_and_send_for_compare::<R, CsvParseResultRight<RecordHashWithPosition>>(
+                self.csv_hash_task_line_senders(
+                    sender_right,
+                    sender_total_lines_right,
+                    sender_csv_reader_right,
+                    csv_right,
+                ),
+                &self.primary_key_columns,
+            )
+        })?;
+        self.cancellation_token.check()?;
+
+        catch_panic(|| {
+            self.recv_hashes_and_compare(
+                receiver_total_lines_left,
+                receiver_total_lines_right,
+                receiver_csv_reader_left,
+                receiver_csv_reader_right,
+                receiver,
+                normalizers,
+            )
+        })?
+        .map(|(diff_byte_records, _)| diff_byte_records)
+    }
+
+    fn csv_hash_task_line_senders<R: Read>(
+        &self,
+        sender: Sender<CsvLeftRightParseResult<RecordHashWithPosition>>,
+        sender_total_lines: Sender<u64>,
+        sender_csv_reader: Sender<HashedCsvReader<R>>,
+        csv: Csv<R>,
+    ) -> CsvHashTaskLineSenders<R> {
+        CsvHashTaskLineSenders::new(
+            sender,
+            sender_total_lines,
+            sender_csv_reader,
+            csv,
+            self.cancellation_token.clone(),
+        )
+        .primary_key_all_columns_except(self.primary_key_excluded_columns.clone())
+        .field_canonicalizer(self.field_comparators.canonicalizer().clone())
+        .hash_seed(self.hash_seed)
+        .chunked_hashing(self.is_chunked_hashing)
+    }
+
+    /// The options, that the hashes of a CSV depend on, when it is hashed by this instance and normalized
+    /// according to `is_normalized`.
+    fn hash_index_config(&self, is_normalized: bool) -> HashIndexConfig {
+        HashIndexConfig::new(
+            &self.primary_key_columns,
+            self.primary_key_excluded_columns.as_ref(),
+            self.hash_seed,
+            self.field_comparators.canonicalizer(),
+            is_normalized,
+        )
+    }
+
+    /// Compares `csv_left` with `csv_right`, which are held in memory, and returns the records that are different
+    /// as [`DiffByteRecordRef`](crate::diff_row::DiffByteRecordRef)s, sorted by line.
+    ///
+    /// In contrast to [`diff`](CsvByteDiffLocal::diff), the fields of the returned records borrow from `csv_left`
+    /// and `csv_right` instead of owning copies of them (except for quoted fields with escaped quotes).
+    /// Both CSVs are parsed with the default dialect (`,` as delimiter and `"` as quote character and with headers).
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::csv_diff::CsvByteDiffLocal;
+    use csv_diff::diff_row::DiffByteRecordRef;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = b"id,name,kind\n\
+                          1,lemon,fruit\n\
+                          2,strawberry,fruit";
+    let csv_data_right = b"id,name,kind\n\
+                           1,lemon,fruit\n\
+                           2,strawberry,nut";
+
+    let diff_byte_records = CsvByteDiffLocal::new()?.diff_slices(csv_data_left, csv_data_right)?;
+
+    match &diff_byte_records[..] {
+        [DiffByteRecordRef::Modify { add, field_indices, .. }] => {
+            assert_eq!(add.get(2), Some(&b"nut"[..]));
+            assert_eq!(field_indices, &vec![2]);
+            assert!(add.is_borrowed());
+        }
+        _ => panic!("expected exactly one modified record"),
+    }
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn diff_slices<'a>(
+        &self,
+        csv_left: &'a [u8],
+        csv_right: &'a [u8],
+    ) -> csv::Result<Vec<DiffByteRecordRef<'a>>> {
+        let mut diff_byte_records = self.diff(
+            Csv::with_reader_seek(csv_left),
+            Csv::with_reader_seek(csv_right),
+        )?;
+        diff_byte_records.sort_by_line();
+        Ok(diff_byte_records
+            .iter()
+            .map(|diff_record| DiffByteRecordRef::from_input(diff_record, csv_left, csv_right))
+            .collect())
+    }
+
+    fn recv_hashes_and_compare<R>(
+        &self,
+        receiver_total_lines_left: Receiver<u64>,
+        receiver_total_lines_right: Receiver<u64>,
+        receiver_csv_reader_left: Receiver<HashedCsvReader<R>>,
+        receiver_csv_reader_right: Receiver<HashedCsvReader<R>>,
+        receiver: Receiver<CsvLeftRightParseResult<RecordHashWithPosition>>,
+        (normalizer_left, normalizer_right): (Option<RecordNormalizer>, Option<RecordNormalizer>),
+    ) -> csv::Result<(DiffByteRecords, DiffRunReport)>
+    where
+        R: Read + Seek + Send,
+    {
+        let (total_lines_right, total_lines_left) = (
+            receiver_total_lines_right.recv().unwrap_or_default(),
+            receiver_total_lines_left.recv().unwrap_or_default(),
+        );
+        let (
+            (csv_reader_right_for_diff_seek, header_info_right, skipped_records_right),
+            (csv_reader_left_for_diff_seek, header_info_left, skipped_records_left),
+        ) = (
+            receiver_csv_reader_right.recv().unwrap()?,
+            receiver_csv_reader_left.recv().unwrap()?,
+        );
+        let header_diff = self
+            .empty_csv_semantics
+            .header_diff(&header_info_left, &header_info_right)?;
+        // the hashing tasks report one more than the number of records, they have read (or 0, if there is no record)
+        let mut run_report = DiffRunReport::new(
+            total_lines_left.saturating_sub(1),
+            total_lines_right.saturating_sub(1),
+        );
+        match header_diff {
+            Some(DiffByteRecord::Delete(_)) => run_report.record_row_left(),
+            Some(_) => run_report.record_row_right(),
+            None => {}
+        }
+        let max_capacity_for_hash_map_right =
+            self.flush_threshold.lines_of_total(total_lines_right);
+        let max_capacity_for_hash_map_left = self.flush_threshold.lines_of_total(total_lines_left);
+
+        let mut csv_hash_comparer = CsvHashComparer::with_capacity_and_reader(
+            max_capacity_for_hash_map_left,
+            max_capacity_for_hash_map_right,
+            csv_reader_left_for_diff_seek,
+            csv_reader_right_for_diff_seek,
+        )
+        // field comparators might drop records, so we can only limit them, after they have been applied
+        .max_diffs(self.max_diffs.filter(|_| self.field_comparators.is_empty()))
+        .normalizers(normalizer_left, normalizer_right)
+        .hash_seed(self.hash_seed);
+        let mut diff_byte_records = csv_hash_comparer.compare_csv_left_right_parse_result(
+            receiver
+                .into_iter()
+                .take_while(|_| !self.cancellation_token.is_cancelled()),
+        )?;
+        self.cancellation_token.check()?;
+        diff_byte_records.0.extend(header_diff);
+        diff_byte_records.1 = DiffHeaders::new(&header_info_left, &header_info_right);
+        let mut diff_byte_records = if self.field_comparators.is_empty() {
+            diff_byte_records
+        } else {
+            let mut field_comparators = self.field_comparators.clone();
+            field_comparators.resolve_headers(diff_byte_records.headers());
+            DiffByteRecords(
+                diff_byte_records
+                    .0
+                    .into_iter()
+                    .filter_map(|diff_record| field_comparators.apply(diff_record))
+                    .collect(),
+                diff_byte_records.1,
+            )
+        };
+        if let Some(max_diffs) = self.max_diffs {
+            if diff_byte_records.0.len() >= max_diffs {
+                diff_byte_records.0.truncate(max_diffs);
+                run_report.set_max_diffs_reached();
+            }
+        }
+        run_report.extend(diff_byte_records.iter());
+        run_report.set_empty_csv_semantics(self.empty_csv_semantics);
+        run_report.set_empty_csv_left(header_info_left.empty_reason());
+        run_report.set_empty_csv_right(header_info_right.empty_reason());
+        run_report.set_column_orders(
+            header_info_left.column_order().cloned(),
+            header_info_right.column_order().cloned(),
+        );
+        run_report.set_skipped_records_left(skipped_records_left);
+        run_report.set_skipped_records_right(skipped_records_right);
+        let diff_byte_records = DiffByteRecords(
+            diff_byte_records
+                .0
+                .into_iter()
+                .map(|diff_record| self.field_comparators.redact(diff_record))
+                .collect(),
+            diff_byte_records.1,
+        );
+        Ok((diff_byte_records, run_report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    #[cfg(feature = "rayon-threads")]
+    use crate::csv::CsvReaderBuilderExt;
+    use crate::diff_result::DiffByteRecords;
+    #[cfg(feature = "rayon-threads")]
+    use crate::diff_row::{AnnotatedByteRecord, LineNum};
+    use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    #[cfg(feature = "rayon-threads")]
+    use crate::empty_csv::EmptyCsvReason;
+    #[cfg(feature = "rayon-threads")]
+    use crate::hash_index::HashIndex;
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+
+    #[cfg(feature = "rayon-threads")]
+    fn csv_diff_local_with_sorting<T: CsvHashTaskSpawnerLocal>(
+        csv_left: &str,
+        csv_right: &str,
+        mut expected: DiffByteRecords,
+        csv_diff: CsvByteDiffLocal<T>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut diff_res_actual = csv_diff.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+
+        diff_res_actual.sort_by_line();
+        expected.sort_by_line();
+
+        assert_eq!(
+            diff_res_actual.as_slice(),
+            expected.as_slice(),
+            "csv_diff_local failed"
+        );
 
         Ok(())
     }
 
+    #[cfg(feature = "rayon-threads")]
     fn csv_diff_with_sorting<T: CsvHashTaskSpawner>(
         csv_left: &'static str,
         csv_right: &'static str,
@@ -706,7 +2341,7 @@ mod tests {
 
         let diff_byte_records: csv::Result<Vec<DiffByteRecord>> = diff_iter.collect();
         let diff_byte_records = diff_byte_records?;
-        let mut actual = DiffByteRecords(diff_byte_records);
+        let mut actual = DiffByteRecords::from(diff_byte_records);
         actual.sort_by_line();
         expected.sort_by_line();
 
@@ -719,7 +2354,7 @@ mod tests {
     fn diff_empty_no_diff() -> Result<(), Box<dyn Error>> {
         let csv_left = "";
         let csv_right = "";
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -749,7 +2384,7 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![]);
+        let diff_res_expected = DiffByteRecords::from(vec![]);
 
         assert_eq!(diff_res_actual, diff_res_expected);
         Ok(())
@@ -761,7 +2396,7 @@ mod tests {
         let csv_left = "header1,header2,header3";
         let csv_right = "header1,header2,header3";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -783,7 +2418,7 @@ mod tests {
                         header1,header2,header3\n\
                         a,b,c";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -815,7 +2450,7 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![]);
+        let diff_res_expected = DiffByteRecords::from(vec![]);
 
         assert_eq!(diff_res_actual, diff_res_expected);
         Ok(())
@@ -841,9 +2476,9 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![]);
+        let diff_res_expected = DiffByteRecords::from(vec![]);
 
-        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
         Ok(())
     }
 
@@ -855,7 +2490,7 @@ mod tests {
                         header1,header2,header3";
         let csv_right = "";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -877,7 +2512,7 @@ mod tests {
                         header1,header2,header3\n\
                         ༼,౪,༽";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -910,9 +2545,9 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![]);
+        let diff_res_expected = DiffByteRecords::from(vec![]);
 
-        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
         Ok(())
     }
 
@@ -926,7 +2561,7 @@ mod tests {
                         header1,header2,header3\n\
                         ༼,౪,༼";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["༼", "౪", "༽"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["༼", "౪", "༼"]), 2),
             field_indices: vec![2],
@@ -952,7 +2587,7 @@ mod tests {
                         header1,header2,header3\n\
                         a,b,c";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
             csv::ByteRecord::from(vec!["a", "b", "c"]),
             2,
         ))]);
@@ -988,11 +2623,11 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![DiffByteRecord::Add(
+        let diff_res_expected = DiffByteRecords::from(vec![DiffByteRecord::Add(
             ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 1),
         )]);
 
-        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
         Ok(())
     }
 
@@ -1006,10 +2641,9 @@ mod tests {
                         header1,header2,header3\n\
                         ";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Delete(ByteRecordLineInfo::new(
-            csv::ByteRecord::from(vec!["a", "b", "c"]),
-            2,
-        ))]);
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Delete(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+        )]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -1042,11 +2676,11 @@ mod tests {
                     .into(),
             )
             .unwrap();
-        let diff_res_expected = DiffByteRecords(vec![DiffByteRecord::Delete(
+        let diff_res_expected = DiffByteRecords::from(vec![DiffByteRecord::Delete(
             ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 1),
         )]);
 
-        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
         Ok(())
     }
 
@@ -1060,7 +2694,7 @@ mod tests {
                         header1,header2,header3\n\
                         a,b,d";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 2),
             field_indices: vec![2],
@@ -1086,7 +2720,7 @@ mod tests {
                         header1,header2,header3\n\
                         a,c,d";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "c", "d"]), 2),
             field_indices: vec![1, 2],
@@ -1112,7 +2746,7 @@ mod tests {
                         header1,header2,header3,header4,header5,header6,header7,header8\n\
                         a,c,d,e,f,g,h,i";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "b", "c", "d", "e", "f", "g", "h"]),
                 2,
@@ -1146,7 +2780,7 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(csv_left, csv_right, expected, CsvByteDiffLocal::new()?)
     }
@@ -1163,7 +2797,7 @@ mod tests {
                         d,e,f\n\
                         a,b,c";
 
-        let expected = DiffByteRecords(vec![]);
+        let expected = DiffByteRecords::from(vec![]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -1188,7 +2822,7 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
             csv::ByteRecord::from(vec!["x", "y", "z"]),
             2,
         ))]);
@@ -1216,7 +2850,7 @@ mod tests {
                         x,y,z\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
             csv::ByteRecord::from(vec!["x", "y", "z"]),
             3,
         ))]);
@@ -1244,7 +2878,7 @@ mod tests {
                         d,e,f\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
             csv::ByteRecord::from(vec!["x", "y", "z"]),
             4,
         ))]);
@@ -1272,10 +2906,9 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Delete(ByteRecordLineInfo::new(
-            csv::ByteRecord::from(vec!["x", "y", "z"]),
-            2,
-        ))]);
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Delete(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 2),
+        )]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -1300,10 +2933,9 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Delete(ByteRecordLineInfo::new(
-            csv::ByteRecord::from(vec!["x", "y", "z"]),
-            3,
-        ))]);
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Delete(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 3),
+        )]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -1328,10 +2960,9 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Delete(ByteRecordLineInfo::new(
-            csv::ByteRecord::from(vec!["x", "y", "z"]),
-            4,
-        ))]);
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Delete(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 4),
+        )]);
 
         csv_diff_local_with_sorting(
             csv_left,
@@ -1357,7 +2988,7 @@ mod tests {
                         d,e,f\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "c"]), 2),
             field_indices: vec![1],
@@ -1388,7 +3019,7 @@ mod tests {
                         a,x,c\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "c"]), 3),
             field_indices: vec![1],
@@ -1418,7 +3049,7 @@ mod tests {
                         d,x,f\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "x", "f"]), 3),
             field_indices: vec![1],
@@ -1449,7 +3080,7 @@ mod tests {
                         a,b,c\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "x", "f"]), 2),
             field_indices: vec![1],
@@ -1479,7 +3110,7 @@ mod tests {
                         d,e,f\n\
                         x,x,z";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 4),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "x", "z"]), 4),
             field_indices: vec![1],
@@ -1510,7 +3141,7 @@ mod tests {
                         a,b,c\n\
                         d,e,f";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 4),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "x", "z"]), 2),
             field_indices: vec![1],
@@ -1541,7 +3172,7 @@ mod tests {
                         g,h,i\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["d", "e", "f"]),
                 3,
@@ -1577,7 +3208,7 @@ mod tests {
                         x,y,z\n\
                         g,h,i";
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["d", "e", "f"]),
                 3,
@@ -1612,7 +3243,7 @@ mod tests {
                         a,b,d\n\
                         x,y,z";
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 3),
@@ -1635,48 +3266,2623 @@ mod tests {
             CsvByteDiffLocal::new()?,
         )?;
 
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_multiple_lines_with_header_modified_at_end_added_at_end() -> Result<(), Box<dyn Error>>
+    {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        x,y,z";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        x,y,a\n\
+                        g,h,i";
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "a"]), 3),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["g", "h", "i"]),
+                4,
+            )),
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocal::new()?,
+        )?;
+
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_multiple_lines_with_header_added_multiple() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i\n\
+                        j,k,l\n\
+                        m,n,o";
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["j", "k", "l"]),
+                5,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["m", "n", "o"]),
+                6,
+            )),
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocal::new()?,
+        )?;
+
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_multiple_lines_with_header_deleted_multiple() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                3,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["g", "h", "i"]),
+                4,
+            )),
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocal::new()?,
+        )?;
+
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_multiple_lines_with_header_modified_multiple() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        d,e,f\n\
+                        g,h,x";
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "i"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "x"]), 4),
+                field_indices: vec![2],
+            },
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocal::new()?,
+        )?;
+
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_multiple_lines_with_header_added_modified_deleted_multiple(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i\n\
+                        j,k,l\n\
+                        m,n,o";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        p,q,r\n\
+                        m,n,o\n\
+                        x,y,z\n\
+                        j,k,x\n";
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                3,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["p", "q", "r"]),
+                3,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["g", "h", "i"]),
+                4,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["j", "k", "l"]), 5),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["j", "k", "x"]), 6),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["x", "y", "z"]),
+                5,
+            )),
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocal::new()?,
+        )?;
+
+        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_run_report_is_consistent_after_exhausting_iterator(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i\n\
+                        j,k,l\n\
+                        m,n,o";
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        let num_of_diffs = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?.len();
+        let run_report = diff_iter.run_report();
+
+        assert_eq!(num_of_diffs, 4);
+        assert_eq!(run_report.rows_left(), 3);
+        assert_eq!(run_report.rows_right(), 4);
+        assert_eq!(run_report.added(), 2);
+        assert_eq!(run_report.deleted(), 1);
+        assert_eq!(run_report.modified(), 1);
+        assert!(run_report.is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_run_report_is_inconsistent_when_iterator_is_not_exhausted(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        d,e,f";
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        diff_iter.next().transpose()?;
+
+        assert!(!diff_iter.run_report().is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_with_report_counts_rows_of_compared_csvs() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i\n\
+                        j,k,l\n\
+                        m,n,o";
+
+        let diff_report = CsvByteDiffLocal::new()?.diff_with_report(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        let run_report = diff_report.run_report();
+
+        assert_eq!(diff_report.records().as_slice().len(), 4);
+        assert_eq!(run_report.rows_left(), 3);
+        assert_eq!(run_report.rows_right(), 4);
+        assert_eq!(run_report.added(), 2);
+        assert_eq!(run_report.deleted(), 1);
+        assert_eq!(run_report.modified(), 1);
+        assert!(run_report.is_consistent());
+
+        let diff_report = CsvByteDiffLocalBuilder::new()
+            .max_diffs(1)
+            .build()?
+            .diff_with_report(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+        let run_report = diff_report.run_report();
+
+        assert_eq!(diff_report.records().as_slice().len(), 1);
+        assert!(run_report.max_diffs_reached());
+        assert!(!run_report.is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_empty_left_all_different_reports_header_and_records_as_added(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["header1", "header2", "header3"]),
+                1,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b", "c"]),
+                2,
+            )),
+        ]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocalBuilder::new()
+                .empty_csv_semantics(EmptyCsvSemantics::AllDifferent)
+                .build()?,
+        )?;
+
+        csv_diff_with_sorting(
+            csv_left,
+            csv_right,
+            expected,
+            CsvByteDiffBuilder::new()
+                .empty_csv_semantics(EmptyCsvSemantics::AllDifferent)
+                .build()?,
+        )
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_empty_right_error_semantics_is_err() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "";
+
+        let diff_res = CsvByteDiffLocalBuilder::new()
+            .empty_csv_semantics(EmptyCsvSemantics::Error)
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            );
+        assert!(diff_res.is_err());
+
+        let diff_res = CsvByteDiffBuilder::new()
+            .empty_csv_semantics(EmptyCsvSemantics::Error)
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .try_to_diff_byte_records();
+        assert!(diff_res.is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_cancelled_is_err() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let err = CsvByteDiffLocalBuilder::new()
+            .cancellation_token(cancellation_token)
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+
+        assert_eq!(err.code(), "CSVDIFF_CANCELLED");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_primary_key_column_out_of_bounds_is_err() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+
+        let err = CsvByteDiffLocalBuilder::new()
+            .primary_key_columns(vec![0, 5])
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+
+        assert_eq!(err.code(), "CSVDIFF_KEY_OUT_OF_BOUNDS");
+        let out_of_bounds_err = match err.kind() {
+            csv::ErrorKind::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<PrimaryKeyColumnOutOfBoundsError>()),
+            _ => None,
+        };
+        assert_eq!(
+            out_of_bounds_err,
+            Some(&PrimaryKeyColumnOutOfBoundsError::new(5, 3))
+        );
+        assert_eq!(
+            err.to_string(),
+            "the primary key column 5 is out of bounds of a CSV with 3 columns"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_primary_key_column_out_of_bounds_without_headers_is_err(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "a,b,c";
+        let csv_right = "a,b,d";
+
+        let err = CsvByteDiffBuilder::new()
+            .primary_key_columns(vec![3])
+            .build()?
+            .diff(
+                csv::ReaderBuilder::new()
+                    .has_headers(false)
+                    .from_reader(csv_left.as_bytes())
+                    .into(),
+                csv::ReaderBuilder::new()
+                    .has_headers(false)
+                    .from_reader(csv_right.as_bytes())
+                    .into(),
+            )
+            .find_map(Result::err)
+            .expect("an error");
+
+        assert_eq!(err.code(), "CSVDIFF_KEY_OUT_OF_BOUNDS");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_with_non_seekable_spooled_readers_one_line_modified() -> Result<(), Box<dyn Error>>
+    {
+        use std::io::Read;
+
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+
+        let diff_res_actual = CsvByteDiffLocal::new()?
+            .diff(
+                // `Chain` can't seek
+                Csv::with_reader_spooled(csv_left.as_bytes().chain(&b""[..]))?,
+                crate::csv::CsvBuilder::new()
+                    .spool_memory_threshold(8)
+                    .build_with_reader_spooled(csv_right.as_bytes().chain(&b""[..]))?,
+            )?
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diff_res_actual,
+            vec![DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 2),
+                field_indices: vec![2]
+            }]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_comments_lines_are_physical_lines() -> Result<(), Box<dyn Error>> {
+        for line_terminator in ["\n", "\r\n"] {
+            let csv_left = [
+                "# comment",
+                "header1,header2,header3",
+                "a,b,c",
+                "# comment",
+                "",
+                "# comment",
+                "x,\"multi",
+                "line\",z",
+                "d,e,f",
+            ]
+            .join(line_terminator);
+            let csv_right = ["header1,header2,header3", "d,e,g", "# comment", "a,b,c", ""]
+                .join(line_terminator);
+            let csv_builder = crate::csv::CsvBuilder::new().comment(Some(b'#'));
+
+            let mut diff_res_actual = CsvByteDiff::new()?
+                .diff(
+                    csv_builder.build_with_reader(std::io::Cursor::new(csv_left.clone())),
+                    csv_builder.build_with_reader(std::io::Cursor::new(csv_right.clone())),
+                )
+                .collect::<csv::Result<Vec<_>>>()?;
+            diff_res_actual.sort_by_key(|diff_byte_record| match diff_byte_record {
+                DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli.line(),
+                DiffByteRecord::Modify { delete, add, .. } => delete.line().min(add.line()),
+                DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+            });
+            let mut diff_res_local = CsvByteDiffLocal::new()?.diff(
+                csv_builder.build_with_reader_seek(csv_left.as_bytes()),
+                csv_builder.build_with_reader_seek(csv_right.as_bytes()),
+            )?;
+            diff_res_local.sort_by_line();
+
+            let diff_res_expected = vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 9),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "g"]), 2),
+                    field_indices: vec![2],
+                },
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["x", &format!("multi{}line", line_terminator), "z"]),
+                    7,
+                )),
+            ];
+            assert_eq!(diff_res_actual, diff_res_expected);
+            assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_line_offset_lines_refer_to_original_file() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i";
+        let csv_builder_left = crate::csv::CsvBuilder::new()
+            .line_offset(1000)
+            .error_policy(ErrorPolicy::SkipAndReport);
+        let csv_builder_right = crate::csv::CsvBuilder::new().line_offset(10);
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            csv_builder_left.build_with_reader(csv_left.as_bytes()),
+            csv_builder_right.build_with_reader(csv_right.as_bytes()),
+        );
+        let diff_res_actual = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+        let (diff_res_local, skipped_records_local) = CsvByteDiffLocal::new()?
+            .diff_with_skipped_records(
+                csv_builder_left.build_with_reader_seek(csv_left.as_bytes()),
+                csv_builder_right.build_with_reader_seek(csv_right.as_bytes()),
+            )?;
+
+        let diff_res_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 1002),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 12),
+            field_indices: vec![2],
+        }];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        assert_eq!(
+            diff_iter.run_report().skipped_records_left()[0].line(),
+            1003
+        );
+        assert_eq!(skipped_records_local.left()[0].line(), 1003);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_numeric_columns_compared_by_value() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,price,name\n\
+                        1,1.0,lemon\n\
+                        2.0,+3,strawberry\n\
+                        3,5.50,cherry";
+        let csv_right = "\
+                        id,price,name\n\
+                        01,1.00,lemon\n\
+                        2,3,strawberry\n\
+                        3,5.5,sour cherry";
+
+        let diff_res_actual = CsvByteDiffBuilder::new()
+            .numeric_columns([0, 1])
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let diff_res_local = CsvByteDiffLocalBuilder::new()
+            .numeric_columns([0, 1])
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+
+        let diff_res_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["3", "5.50", "cherry"]), 4),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["3", "5.5", "sour cherry"]), 4),
+            field_indices: vec![2],
+        }];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_null_tokens_are_equivalent_to_empty_fields() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,name,comment\n\
+                        1,lemon,NULL\n\
+                        2,-,\n\
+                        3,cherry,-";
+        let csv_right = "\
+                        id,name,comment\n\
+                        1,lemon,\n\
+                        2,N/A,NULL\n\
+                        3,cherry,sour";
+
+        let diff_res_actual = CsvByteDiffBuilder::new()
+            .null_tokens(["NULL", "N/A"])
+            .null_tokens_for_column(1, ["-"])
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let diff_res_local = CsvByteDiffLocalBuilder::new()
+            .null_tokens(["NULL", "N/A"])
+            .null_tokens_for_column(1, ["-"])
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+
+        let diff_res_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["3", "cherry", "-"]), 4),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["3", "cherry", "sour"]), 4),
+            field_indices: vec![2],
+        }];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_ignore_pattern_only_compares_the_rest_of_the_field() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,session\n\
+                        1,web-1:8f3a\n\
+                        2,web-2:77c0";
+        let csv_right = "\
+                        id,session\n\
+                        1,web-1:d1e9\n\
+                        2,app-2:77c0";
+
+        let diff_res_actual = CsvByteDiffBuilder::new()
+            .ignore_pattern(1, IgnorePattern::KeepPrefix(5))
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let diff_res_local = CsvByteDiffLocalBuilder::new()
+            .ignore_pattern(1, IgnorePattern::KeepPrefix(5))
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+
+        let diff_res_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "web-2:77c0"]), 3),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "app-2:77c0"]), 3),
+            field_indices: vec![1],
+        }];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_hash_index_equals_diff_and_only_hashes_right() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,name,kind\n\
+                        1,lemon,fruit\n\
+                        2,strawberry,fruit\n\
+                        3,cherry,fruit";
+        let csv_right = "\
+                        id,name,kind\n\
+                        1,lemon,fruit\n\
+                        2,strawberry,nut\n\
+                        4,mango,fruit";
+        let csv_byte_diff = CsvByteDiffLocalBuilder::new().hash_seed(7).build()?;
+        let mut saved_index = Vec::new();
+        csv_byte_diff
+            .hash_index(Csv::with_reader_seek(csv_left.as_bytes()))?
+            .write_to(&mut saved_index)?;
+        let hash_index_left = HashIndex::read_from(saved_index.as_slice())?;
+
+        let mut diff_res_actual = csv_byte_diff.diff_with_hash_index(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            &hash_index_left,
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        diff_res_actual.sort_by_line();
+        let mut diff_res_expected = csv_byte_diff.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        diff_res_expected.sort_by_line();
+
+        assert_eq!(hash_index_left.len(), 3);
+        assert_eq!(diff_res_actual.as_slice().len(), 3);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
+        assert_eq!(diff_res_actual.headers(), diff_res_expected.headers());
+
+        let err = CsvByteDiffLocal::new()?
+            .diff_with_hash_index(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                &hash_index_left,
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+
+        // changed in place, so that its size stays the same
+        let csv_left_changed = csv_left.replace("cherry", "banana");
+        let err = csv_byte_diff
+            .diff_with_hash_index(
+                Csv::with_reader_seek(csv_left_changed.as_bytes()),
+                &hash_index_left,
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_hash_snapshot_of_other_canonicalization_or_normalization_is_err(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,price\n\
+                        1,1.0";
+        let csv_right = "\
+                        id,price\n\
+                        1,1.00";
+        let csv_byte_diff_numeric = CsvByteDiffLocalBuilder::new()
+            .numeric_columns([1])
+            .build()?;
+        let hash_snapshot_left =
+            csv_byte_diff_numeric.hash_snapshot(Csv::with_reader_seek(csv_left.as_bytes()))?;
+
+        assert_eq!(
+            csv_byte_diff_numeric.diff_with_hash_snapshot(
+                &hash_snapshot_left,
+                Csv::with_reader_seek(csv_right.as_bytes())
+            )?,
+            vec![]
+        );
+        let err = CsvByteDiffLocal::new()?
+            .diff_with_hash_snapshot(
+                &hash_snapshot_left,
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+        let err = csv_byte_diff_numeric
+            .diff_with_hash_snapshot(
+                &hash_snapshot_left,
+                crate::csv::CsvBuilder::new()
+                    .normalize(|byte_record: &mut csv::ByteRecord| byte_record.trim())
+                    .build_with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_hash_index_of_other_canonicalization_or_normalization_is_err(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,price\n\
+                        1,1.0";
+        let csv_right = "\
+                        id,price\n\
+                        1,1.00";
+        let hash_index_left = CsvByteDiffLocalBuilder::new()
+            .numeric_columns([1])
+            .build()?
+            .hash_index(Csv::with_reader_seek(csv_left.as_bytes()))?;
+
+        let err = CsvByteDiffLocal::new()?
+            .diff_with_hash_index(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                &hash_index_left,
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+
+        let csv_byte_diff = CsvByteDiffLocal::new()?;
+        let hash_index_left =
+            csv_byte_diff.hash_index(Csv::with_reader_seek(csv_left.as_bytes()))?;
+        let err = csv_byte_diff
+            .diff_with_hash_index(
+                crate::csv::CsvBuilder::new()
+                    .normalize(|byte_record: &mut csv::ByteRecord| byte_record.trim())
+                    .build_with_reader_seek(csv_left.as_bytes()),
+                &hash_index_left,
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "CSVDIFF_HASH_INDEX_MISMATCH");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_redact_columns_compares_real_fields_but_emits_stars() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,email,plan\n\
+                        1,ann@example.com,free\n\
+                        2,bob@example.com,free\n\
+                        3,cid@example.com,pro";
+        let csv_right = "\
+                        id,email,plan\n\
+                        1,ann@example.com,pro\n\
+                        2,bob@example.org,free";
+
+        let mut diff_res_actual = CsvByteDiffBuilder::new()
+            .redact_columns([1])
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        diff_res_actual.sort_by_key(|diff_byte_record| match diff_byte_record {
+            DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli.line(),
+            DiffByteRecord::Modify { delete, add, .. } => delete.line().min(add.line()),
+            DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+        });
+        let mut diff_res_local = CsvByteDiffLocalBuilder::new()
+            .redact_columns([1])
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+        diff_res_local.sort_by_line();
+
+        let diff_res_expected = vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "***", "free"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "***", "pro"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "***", "free"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "***", "free"]), 3),
+                field_indices: vec![1],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["3", "***", "pro"]),
+                4,
+            )),
+        ];
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_chunked_hashing_keeps_lines_of_records() -> Result<(), Box<dyn Error>> {
+        let num_records = 10_000;
+        let csv_left = std::iter::once("id,name,kind".to_string())
+            .chain((0..num_records).map(|i| format!("{},name{},kind", i, i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,name,kind".to_string())
+            .chain((0..num_records).map(|i| match i {
+                5_000 => format!("{},name{},changed", i, i),
+                9_999 => format!("{},name{},\"multi\nline\"", i + 1, i),
+                _ => format!("{},name{},kind", i, i),
+            }))
+            .collect::<Vec<_>>()
+            .join("\n");
+        // a single thread must not deadlock, although the chunks are hashed by additional tasks
+        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(1).build()?;
+
+        let mut diff_res_actual = CsvByteDiffLocalBuilder::new()
+            .rayon_thread_pool(&thread_pool)
+            .chunked_hashing(true)
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )?;
+        diff_res_actual.sort_by_line();
+
+        assert_eq!(
+            diff_res_actual.as_slice(),
+            &[
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["5000", "name5000", "kind"]),
+                        5_002
+                    ),
+                    add: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["5000", "name5000", "changed"]),
+                        5_002
+                    ),
+                    field_indices: vec![2],
+                },
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["9999", "name9999", "kind"]),
+                    10_001
+                )),
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["10000", "name9999", "multi\nline"]),
+                    10_001
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_random_hash_seed_is_the_same_as_unseeded() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i\n\
+                        j,k,l";
+
+        let diff_res_actual = CsvByteDiffBuilder::new()
+            .random_hash_seed()
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let diff_res_local = CsvByteDiffLocalBuilder::new().hash_seed(42).build()?.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+
+        let diff_res_expected = vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                3,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["j", "k", "l"]),
+                4,
+            )),
+        ];
+        for mut diff_res in [DiffByteRecords::from(diff_res_actual), diff_res_local] {
+            diff_res.sort_by_line();
+            assert_eq!(diff_res.as_slice(), diff_res_expected.as_slice());
+        }
+        Ok(())
+    }
+
+    #[cfg(all(feature = "rayon-threads", feature = "simd-parser"))]
+    #[test]
+    fn diff_with_simd_parser_is_the_same_as_with_csv_crate() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\u{feff}header1,header2,header3\r\n\
+                        a,\"b\r\nb\",c\r\n\
+                        \r\n\
+                        d,e,f\r\n\
+                        g,\"h\"\"\",i";
+        let csv_right = "header1,header2,header3\n\
+                         a,\"b\r\nb\",c\n\
+                         d,e,x\n\
+                         g,h,i\n";
+        let csv_builder = crate::csv::CsvBuilder::new().simd_parser(true);
+
+        let mut diff_res_expected = CsvByteDiffLocal::new()?.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        diff_res_expected.sort_by_line();
+        let mut diff_res_actual = DiffByteRecords::from(
+            CsvByteDiff::new()?
+                .diff(
+                    csv_builder.build_with_reader(csv_left.as_bytes()),
+                    csv_builder.build_with_reader(csv_right.as_bytes()),
+                )
+                .collect::<csv::Result<Vec<_>>>()?,
+        );
+        diff_res_actual.sort_by_line();
+        let mut diff_res_local = CsvByteDiffLocal::new()?.diff(
+            csv_builder.build_with_reader_seek(csv_left.as_bytes()),
+            csv_builder.build_with_reader_seek(csv_right.as_bytes()),
+        )?;
+        diff_res_local.sort_by_line();
+
+        assert_eq!(diff_res_expected.as_slice().len(), 2);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_normalized_left_records_before_hashing() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        \x20a,b ,c\n\
+                        d, e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,x\n\
+                        g,h,i";
+        let trim = |byte_record: &mut csv::ByteRecord| {
+            byte_record.trim();
+        };
+        let csv_builder_left = crate::csv::CsvBuilder::new().normalize(trim);
+
+        let diff_res_actual = CsvByteDiff::new()?
+            .diff(
+                csv_builder_left.build_with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let diff_res_local = CsvByteDiffLocal::new()?.diff(
+            csv_builder_left.build_with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+
+        let diff_res_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "x"]), 3),
+            field_indices: vec![2],
+        }];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_carries_headers_of_both_csvs() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header4\n\
+                        a,b,d";
+
+        let diff_res_local = CsvByteDiffLocal::new()?.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        diff_iter.by_ref().for_each(drop);
+        let diff_res_streamed = diff_iter.try_to_diff_byte_records()?;
+
+        let headers_left = csv::ByteRecord::from(vec!["header1", "header2", "header3"]);
+        let headers_right = csv::ByteRecord::from(vec!["header1", "header2", "header4"]);
+        for diff_res in [&diff_res_local, &diff_res_streamed] {
+            assert_eq!(diff_res.headers(), Some(&headers_left));
+            assert_eq!(diff_res.headers_left(), Some(&headers_left));
+            assert_eq!(diff_res.headers_right(), Some(&headers_right));
+        }
+        let inverted = diff_res_local.invert();
+        assert_eq!(inverted.headers_left(), Some(&headers_right));
+        assert_eq!(inverted.headers_right(), Some(&headers_left));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_headers_of_empty_csv_are_taken_from_the_other_csv() -> Result<(), Box<dyn Error>> {
+        let csv_left = "";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        diff_iter.by_ref().for_each(drop);
+
+        let headers_right = csv::ByteRecord::from(vec!["header1", "header2", "header3"]);
+        assert_eq!(diff_iter.headers_left(), None);
+        assert_eq!(diff_iter.headers(), Some(&headers_right));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_flexible_pads_shorter_records_with_empty_fields() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b\n\
+                        d,e,f,g\n\
+                        x,y,z";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,\n\
+                        d,e,f\n\
+                        x,y";
+        let csv_builder = crate::csv::CsvBuilder::new().flexible(true);
+
+        let mut diff_res_actual = CsvByteDiff::new()?
+            .diff(
+                csv_builder.build_with_reader(csv_left.as_bytes()),
+                csv_builder.build_with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        diff_res_actual.sort_by_key(|diff_byte_record| match diff_byte_record {
+            DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli.line(),
+            DiffByteRecord::Modify { delete, add, .. } => delete.line().min(add.line()),
+            DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+        });
+        let mut diff_res_local = CsvByteDiffLocal::new()?.diff(
+            csv_builder.build_with_reader_seek(csv_left.as_bytes()),
+            csv_builder.build_with_reader_seek(csv_right.as_bytes()),
+        )?;
+        diff_res_local.sort_by_line();
+
+        let diff_res_expected = vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f", "g"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
+                field_indices: vec![3],
+            },
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y"]), 4),
+                field_indices: vec![2],
+            },
+        ];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_skip_and_report_skips_malformed_records() -> Result<(), Box<dyn Error>> {
+        use crate::error_policy::ErrorPolicy;
+
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i,j\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_builder = crate::csv::CsvBuilder::new().error_policy(ErrorPolicy::SkipAndReport);
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            csv_builder.build_with_reader(csv_left.as_bytes()),
+            csv_builder.build_with_reader(csv_right.as_bytes()),
+        );
+        let mut diff_res_actual = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+        diff_res_actual.sort_by_key(|diff_byte_record| match diff_byte_record {
+            DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli.line(),
+            DiffByteRecord::Modify { delete, add, .. } => delete.line().min(add.line()),
+            DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+        });
+        let run_report = diff_iter.run_report();
+        let (mut diff_res_local, skipped_records_local) = CsvByteDiffLocal::new()?
+            .diff_with_skipped_records(
+                csv_builder.build_with_reader_seek(csv_left.as_bytes()),
+                csv_builder.build_with_reader_seek(csv_right.as_bytes()),
+            )?;
+        diff_res_local.sort_by_line();
+
+        let diff_res_expected = vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                4,
+            )),
+        ];
+        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_local.as_slice(), diff_res_expected.as_slice());
+        for (skipped_records_left, skipped_records_right) in [
+            (
+                run_report.skipped_records_left(),
+                run_report.skipped_records_right(),
+            ),
+            (skipped_records_local.left(), skipped_records_local.right()),
+        ] {
+            assert_eq!(
+                skipped_records_left
+                    .iter()
+                    .map(|skipped| (
+                        skipped.byte_record().clone(),
+                        skipped.line(),
+                        skipped.code()
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![(
+                    csv::ByteRecord::from(vec!["d", "e"]),
+                    3,
+                    "CSVDIFF_RAGGED_ROW"
+                )]
+            );
+            assert_eq!(
+                skipped_records_right
+                    .iter()
+                    .map(|skipped| (
+                        skipped.byte_record().clone(),
+                        skipped.line(),
+                        skipped.code()
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![(
+                    csv::ByteRecord::from(vec!["g", "h", "i", "j"]),
+                    3,
+                    "CSVDIFF_RAGGED_ROW"
+                )]
+            );
+        }
+        assert!(run_report.is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_report_collects_all_errors_of_both_sides() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b\n\
+                        d,e,f\n\
+                        g,h,i,j\n\
+                        m,n,o";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,x\n\
+                        g\n\
+                        m,n,o,p";
+
+        let diff_report = CsvByteDiff::new()?.diff_with_report(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        )?;
+        let diff_report_local = CsvByteDiffLocal::new()?.diff_with_report(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+
+        for diff_report in [diff_report, diff_report_local] {
+            let (mut diff_byte_records, errors) = diff_report.into_parts();
+            diff_byte_records.sort_by_line();
+            assert_eq!(
+                diff_byte_records.as_slice(),
+                &[
+                    DiffByteRecord::Add(ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["a", "b", "c"]),
+                        2
+                    )),
+                    DiffByteRecord::Modify {
+                        delete: ByteRecordLineInfo::new(
+                            csv::ByteRecord::from(vec!["d", "e", "f"]),
+                            3
+                        ),
+                        add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "x"]), 3),
+                        field_indices: vec![2],
+                    },
+                    DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["m", "n", "o"]),
+                        5
+                    )),
+                ]
+            );
+            assert_eq!(
+                errors
+                    .left()
+                    .iter()
+                    .map(|err| err.line())
+                    .collect::<Vec<_>>(),
+                vec![2, 4]
+            );
+            assert_eq!(
+                errors
+                    .right()
+                    .iter()
+                    .map(|err| err.line())
+                    .collect::<Vec<_>>(),
+                vec![4, 5]
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_cancelled_while_running_stops_and_is_err() -> Result<(), Box<dyn Error>> {
+        // more records than fit into the channel between hashing and comparing,
+        // so that the hashing tasks are still running, when the comparison is cancelled
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..50_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = csv_left.replace(",a", ",b");
+        let cancellation_token = CancellationToken::new();
+
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .cancellation_token(cancellation_token.clone())
+            .build()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left)),
+                Csv::with_reader(std::io::Cursor::new(csv_right)),
+            );
+        diff_iter.next().transpose()?;
+        cancellation_token.cancel();
+
+        let err = diff_iter.next().expect("an error").unwrap_err();
+
+        assert_eq!(err.code(), "CSVDIFF_CANCELLED");
+        assert!(diff_iter.next().is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_timed_out_emits_differences_so_far_and_then_err() -> Result<(), Box<dyn Error>>
+    {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..50_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = csv_left.replace(",a", ",b");
+
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .max_duration(Duration::from_secs(1))
+            .build()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left)),
+                Csv::with_reader(std::io::Cursor::new(csv_right)),
+            );
+        let first = diff_iter.next().expect("a difference")?;
+        std::thread::sleep(Duration::from_secs(1));
+
+        let err = diff_iter.next().expect("an error").unwrap_err();
+
+        assert!(matches!(first, DiffByteRecord::Modify { .. }));
+        assert_eq!(err.code(), "CSVDIFF_TIMED_OUT");
+        assert!(diff_iter.next().is_none());
+        let run_report = diff_iter.run_report();
+        assert!(run_report.timed_out());
+        assert_eq!(run_report.added() + run_report.deleted(), 0);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_max_diffs_stops_after_max_diffs() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..50_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = csv_left.replace(",a", ",b");
+
+        let mut diff_iter = CsvByteDiffBuilder::new().max_diffs(3).build()?.diff(
+            Csv::with_reader(std::io::Cursor::new(csv_left)),
+            Csv::with_reader(std::io::Cursor::new(csv_right)),
+        );
+        let diff_byte_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(diff_byte_records.len(), 3);
+        assert!(diff_byte_records
+            .iter()
+            .all(|diff_record| matches!(diff_record, DiffByteRecord::Modify { .. })));
+        assert!(diff_iter.next().is_none());
+        let run_report = diff_iter.run_report();
+        assert!(run_report.max_diffs_reached());
+        assert!(run_report.rows_left() + run_report.rows_right() < 100_000);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_key_sample_only_compares_sampled_keys() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..10_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = csv_left.replace(",a", ",b");
+        let csv_byte_diff = CsvByteDiffBuilder::new().sample_keys(10).build()?;
+        let diff_sampled = || -> csv::Result<_> {
+            let mut diff_iter = csv_byte_diff.diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+            );
+            let mut diff_byte_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+            diff_byte_records.sort_by_key(|diff_record| match diff_record {
+                DiffByteRecord::Modify { delete, .. } => delete.line(),
+                _ => unreachable!("all records are modified"),
+            });
+            Ok((diff_byte_records, diff_iter.run_report()))
+        };
+
+        let (diff_byte_records, run_report) = diff_sampled()?;
+
+        // roughly one in ten keys
+        assert!((500..2_000).contains(&diff_byte_records.len()));
+        assert!(run_report.is_approximate());
+        assert_eq!(run_report.key_sample(), Some(10));
+        assert_eq!(run_report.modified(), diff_byte_records.len() as u64);
+        assert_eq!(run_report.rows_left(), diff_byte_records.len() as u64);
+        assert!(run_report.is_consistent());
+        // the same keys are sampled in every run
+        assert_eq!(diff_sampled()?.0, diff_byte_records);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_small_channel_capacity_is_complete() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for channel_capacity in [0, 1, DEFAULT_CHANNEL_CAPACITY] {
+            let mut diff_res_actual = DiffByteRecords::from(
+                CsvByteDiffBuilder::new()
+                    .channel_capacity(channel_capacity)
+                    .build()?
+                    .diff(
+                        Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                        Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+                    )
+                    .collect::<csv::Result<Vec<_>>>()?,
+            );
+            diff_res_actual.sort_by_line();
+
+            assert_eq!(diff_res_actual.as_slice().len(), 10);
+            assert!(diff_res_actual
+                .as_slice()
+                .iter()
+                .zip((0..1_000).step_by(100))
+                .all(|(diff_record, i)| matches!(
+                    diff_record,
+                    DiffByteRecord::Modify { add, .. } if add.byte_record()[0] == *i.to_string().as_bytes()
+                )));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_is_fused_and_size_hint_bounds_remaining_records() -> Result<(), Box<dyn Error>>
+    {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((1..1_001).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(std::io::Cursor::new(csv_left)),
+            Csv::with_reader(std::io::Cursor::new(csv_right)),
+        );
+        let mut size_hints = vec![diff_iter.size_hint()];
+        while let Some(diff_record) = diff_iter.next() {
+            diff_record?;
+            size_hints.push(diff_iter.size_hint());
+        }
+
+        // 0 is deleted, 1_000 is added and 100 to 900 are modified
+        assert_eq!(size_hints.len(), 12);
+        for (num_emitted, (lower, upper)) in size_hints.iter().enumerate() {
+            let num_remaining = size_hints.len() - 1 - num_emitted;
+            assert!(*lower <= num_remaining);
+            assert!(upper.map_or(true, |upper| num_remaining <= upper));
+        }
+        assert_eq!(size_hints[0].1, None);
+        assert!(size_hints[1..].iter().any(|(_, upper)| upper.is_some()));
+        assert_eq!(diff_iter.size_hint(), (0, Some(0)));
+        assert!(diff_iter.next().is_none());
+        assert!(diff_iter.next().is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_iterator_can_be_bridged_into_parallel_iterator() -> Result<(), Box<dyn Error>>
+    {
+        use rayon::iter::{ParallelBridge, ParallelIterator};
+
+        fn assert_send<T: Send>() {}
+        assert_send::<DiffByteRecordsIterator>();
+        assert_send::<AnnotatedByteRecordsIterator>();
+
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let num_modified = CsvByteDiff::new()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left)),
+                Csv::with_reader(std::io::Cursor::new(csv_right)),
+            )
+            .par_bridge()
+            .filter(|diff_record| matches!(diff_record, Ok(DiffByteRecord::Modify { .. })))
+            .count();
+
+        assert_eq!(num_modified, 10);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_flush_threshold_is_the_same_for_every_threshold() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        // reversed, so that most records are only matched late
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain(
+                (0..1_000)
+                    .rev()
+                    .map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for flush_threshold in [
+            FlushThreshold::default(),
+            FlushThreshold::Fixed(0),
+            FlushThreshold::Fixed(1),
+            FlushThreshold::Fixed(usize::MAX),
+            FlushThreshold::FractionOfRows(0.0),
+            FlushThreshold::FractionOfRows(0.5),
+            FlushThreshold::FractionOfRows(2.0),
+            FlushThreshold::Unlimited,
+        ] {
+            let mut diff_res_streaming = DiffByteRecords::from(
+                CsvByteDiffBuilder::new()
+                    .flush_threshold(flush_threshold)
+                    .build()?
+                    .diff(
+                        Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                        Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+                    )
+                    .collect::<csv::Result<Vec<_>>>()?,
+            );
+            diff_res_streaming.sort_by_line();
+            let mut diff_res_local = CsvByteDiffLocalBuilder::new()
+                .flush_threshold(flush_threshold)
+                .build()?
+                .diff(
+                    Csv::with_reader_seek(csv_left.as_bytes()),
+                    Csv::with_reader_seek(csv_right.as_bytes()),
+                )?;
+            diff_res_local.sort_by_line();
+
+            assert_eq!(diff_res_streaming.as_slice().len(), 10);
+            assert_eq!(diff_res_streaming.as_slice(), diff_res_local.as_slice());
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_reports_metrics_to_metrics_sink() -> Result<(), Box<dyn Error>> {
+        use crate::empty_csv::CsvSide;
+        use crate::metrics::DiffMetricsSink;
+
+        #[derive(Default)]
+        struct CountingMetricsSink {
+            records_read: (u64, u64),
+            records_hashed: u64,
+            max_map_len: usize,
+            num_flushed: usize,
+            num_recycled: usize,
+        }
+
+        impl DiffMetricsSink for CountingMetricsSink {
+            fn record_read(&mut self, side: CsvSide) {
+                match side {
+                    CsvSide::Left => self.records_read.0 += 1,
+                    CsvSide::Right => self.records_read.1 += 1,
+                }
+            }
+            fn record_hashed(&mut self, _side: CsvSide) {
+                self.records_hashed += 1;
+            }
+            fn map_sizes(&mut self, len_left: usize, len_right: usize) {
+                self.max_map_len = self.max_map_len.max(len_left).max(len_right);
+            }
+            fn map_flushed(&mut self, _side: CsvSide, num_flushed: usize, _num_unmatched: usize) {
+                self.num_flushed += num_flushed;
+            }
+            fn records_recycled(&mut self, num_recycled: usize) {
+                self.num_recycled += num_recycled;
+            }
+        }
+
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut metrics_sink = CountingMetricsSink::default();
+        let num_of_diffs = CsvByteDiffBuilder::new()
+            .flush_threshold(FlushThreshold::Fixed(10))
+            // the hashing tasks must still be running (and receiving recycled records), when the maps are flushed
+            .channel_capacity(0)
+            .build()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left)),
+                Csv::with_reader(std::io::Cursor::new(csv_right)),
+            )
+            .with_metrics_sink(&mut metrics_sink)
+            .collect::<csv::Result<Vec<_>>>()?
+            .len();
+
+        assert_eq!(num_of_diffs, 10);
+        assert_eq!(metrics_sink.records_read, (1_000, 1_000));
+        assert_eq!(metrics_sink.records_hashed, 2_000);
+        assert!(metrics_sink.max_map_len > 0);
+        assert!(metrics_sink.num_flushed > 0);
+        // only equal records are recycled (both of their sides)
+        assert!(metrics_sink.num_recycled > 0 && metrics_sink.num_recycled <= 2 * 990);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_can_be_called_again_and_from_multiple_threads_while_iterating(
+    ) -> Result<(), Box<dyn Error>> {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<CsvByteDiff<CsvHashTaskSpawnerRayon>>();
+
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+        let csv_byte_diff = CsvByteDiff::new()?;
+        let diff = || {
+            csv_byte_diff
+                .diff(
+                    Csv::with_reader(csv_left.as_bytes()),
+                    Csv::with_reader(csv_right.as_bytes()),
+                )
+                .collect::<csv::Result<Vec<_>>>()
+        };
+
+        let mut diff_iter_first = csv_byte_diff.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        let (diff_res_thread_1, diff_res_thread_2) = std::thread::scope(|s| {
+            let thread_1 = s.spawn(diff);
+            let thread_2 = s.spawn(diff);
+            (thread_1.join().unwrap(), thread_2.join().unwrap())
+        });
+
+        let diff_res_first = diff_iter_first.by_ref().collect::<csv::Result<Vec<_>>>()?;
+        assert_eq!(diff_res_first.len(), 1);
+        assert_eq!(diff_res_thread_1?, diff_res_first);
+        assert_eq!(diff_res_thread_2?, diff_res_first);
+        assert_eq!(diff()?, diff_res_first);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_preset_is_the_same_for_every_preset() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..5_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((1..5_001).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut diff_results = Vec::new();
+        for preset in [Preset::Fast, Preset::LowMemory] {
+            let mut diff_res = DiffByteRecords::from(
+                CsvByteDiffBuilder::new()
+                    .preset(preset)
+                    .build()?
+                    .diff(
+                        Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                        Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+                    )
+                    .collect::<csv::Result<Vec<_>>>()?,
+            );
+            diff_res.sort_by_line();
+            diff_results.push(diff_res);
+        }
+
+        // 0 is deleted, 5_000 is added and 100 to 4_900 are modified
+        assert_eq!(diff_results[0].as_slice().len(), 51);
+        assert_eq!(diff_results[0].as_slice(), diff_results[1].as_slice());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn preset_is_overridden_by_options_set_after_it() {
+        let csv_byte_diff_builder = CsvByteDiffBuilder::new()
+            .preset(Preset::LowMemory)
+            .channel_capacity(5);
+
+        assert_eq!(csv_byte_diff_builder.channel_capacity, 5);
+        assert_eq!(
+            csv_byte_diff_builder.flush_threshold,
+            Preset::LowMemory.flush_threshold()
+        );
+        assert_eq!(
+            csv_byte_diff_builder.recycle_capacity,
+            Preset::LowMemory.recycle_capacity()
+        );
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_a_single_thread_from_num_threads_or_thread_pool_builder(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let num_of_diffs_streaming = CsvByteDiff::with_num_threads(1)?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+            )
+            .collect::<csv::Result<Vec<_>>>()?
+            .len();
+        let num_of_diffs_local = CsvByteDiffLocal::with_thread_pool_builder(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|idx| format!("csv-diff-{}", idx)),
+        )?
+        .diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?
+        .as_slice()
+        .len();
+
+        assert_eq!(num_of_diffs_streaming, 10);
+        assert_eq!(num_of_diffs_local, 10);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_max_diffs_not_reached_is_complete() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x";
+
+        let mut diff_iter = CsvByteDiffBuilder::new().max_diffs(3).build()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        let diff_byte_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(diff_byte_records.len(), 2);
+        assert!(!diff_iter.run_report().max_diffs_reached());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_with_max_diffs_returns_at_most_max_diffs() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        j,k,l";
+
+        let diff_byte_records = CsvByteDiffLocalBuilder::new().max_diffs(2).build()?.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+        let diff_byte_records_zero = CsvByteDiffLocalBuilder::new().max_diffs(0).build()?.diff(
+            Csv::with_reader_seek(csv_left.as_bytes()),
+            Csv::with_reader_seek(csv_right.as_bytes()),
+        )?;
+
+        assert_eq!(diff_byte_records.as_slice().len(), 2);
+        assert!(diff_byte_records_zero.as_slice().is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_equal_records_emits_equal_and_different_records(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        d,e,f\n\
+                        a,b,x\n\
+                        j,k,l";
+
+        let mut annotated_records = CsvByteDiff::new()?
+            .diff_with_equal_records(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        // an added record is sorted after a deleted record on the same line
+        annotated_records.sort_by_key(|annotated_record| match annotated_record {
+            AnnotatedByteRecord::Equal { left, .. } => (left.line(), false),
+            AnnotatedByteRecord::Diff(diff_record) => match diff_record.line_num() {
+                LineNum::OneSide(line) => (line, matches!(diff_record, DiffByteRecord::Add(..))),
+                LineNum::BothSides { for_deleted, .. } => (for_deleted, false),
+            },
+        });
+
+        assert_eq!(
+            annotated_records,
+            vec![
+                AnnotatedByteRecord::Diff(DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 3),
+                    field_indices: vec![2],
+                }),
+                AnnotatedByteRecord::Equal {
+                    left: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
+                    right: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 2),
+                },
+                AnnotatedByteRecord::Diff(DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["g", "h", "i"]),
+                    4
+                ))),
+                AnnotatedByteRecord::Diff(DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["j", "k", "l"]),
+                    4
+                ))),
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_moves_only_moves_records_out_of_order() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,name\n\
+                        1,a\n\
+                        2,b\n\
+                        3,c\n\
+                        4,d\n\
+                        5,e";
+        // `0` shifts all records by one line, but only `2` has moved relative to the other equal records
+        let csv_right = "\
+                        id,name\n\
+                        0,x\n\
+                        1,a\n\
+                        3,c\n\
+                        4,d\n\
+                        2,b\n\
+                        5,y";
+
+        let mut diff_byte_records = CsvByteDiff::new()?.diff_with_moves(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        )?;
+
+        assert_eq!(
+            diff_byte_records.as_slice().last(),
+            Some(&DiffByteRecord::Move {
+                record: csv::ByteRecord::from(vec!["2", "b"]),
+                from_line: 3,
+                to_line: 6,
+            })
+        );
+        diff_byte_records.sort_by_line();
+        assert_eq!(
+            diff_byte_records.as_slice(),
+            &[
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["0", "x"]),
+                    2
+                )),
+                DiffByteRecord::Move {
+                    record: csv::ByteRecord::from(vec!["2", "b"]),
+                    from_line: 3,
+                    to_line: 6,
+                },
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["5", "e"]), 6),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["5", "y"]), 7),
+                    field_indices: vec![1],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_equal_records_emits_all_equal_records_of_large_csvs(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..20_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..20_000).map(|i| format!("{},{}", i, if i % 10 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for memory_budget in [usize::MAX, 0] {
+            let annotated_records = CsvByteDiffBuilder::new()
+                .memory_budget(memory_budget)
+                .build()?
+                .diff_with_equal_records(
+                    Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                    Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+                )
+                .collect::<csv::Result<Vec<_>>>()?;
+
+            let num_of_equal = annotated_records
+                .iter()
+                .filter(|annotated_record| annotated_record.is_equal())
+                .count();
+            assert_eq!(num_of_equal, 18_000);
+            assert_eq!(annotated_records.len() - num_of_equal, 2_000);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_run_report_counts_unchanged_records() -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value,comment".to_string())
+            .chain((0..20_000).map(|i| format!("{},a,x", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value,comment".to_string())
+            .chain((0..20_000).map(|i| match i % 10 {
+                0 => format!("{},b,x", i),
+                1 => format!("{},a,y", i),
+                _ => format!("{},a,x", i),
+            }))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for memory_budget in [usize::MAX, 0] {
+            let mut diff_iter = CsvByteDiffBuilder::new()
+                .memory_budget(memory_budget)
+                .ignore_columns_for_modify(vec![2])
+                .build()?
+                .diff(
+                    Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                    Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+                );
+            let num_of_diffs = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?.len();
+
+            let run_report = diff_iter.run_report();
+            assert_eq!(num_of_diffs, 2_000);
+            // records, whose only change is ignored, are unchanged as well
+            assert_eq!(run_report.unchanged(), 18_000);
+            assert_eq!(
+                run_report.unchanged() + run_report.modified(),
+                run_report.rows_left()
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_exceeded_memory_budget_spills_and_yields_same_differences(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..2_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((1_000..3_000).map(|i| format!("{},{}", i, if i % 7 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let sorted = |diff_records: Vec<DiffByteRecord>| {
+            let mut diff_records = diff_records
+                .into_iter()
+                .map(|diff_record| format!("{:?}", diff_record))
+                .collect::<Vec<_>>();
+            diff_records.sort();
+            diff_records
+        };
+
+        let expected = CsvByteDiffBuilder::new()
+            .build()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left.clone())),
+                Csv::with_reader(std::io::Cursor::new(csv_right.clone())),
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .memory_budget(4 * 1024)
+            .build()?
+            .diff(
+                Csv::with_reader(std::io::Cursor::new(csv_left)),
+                Csv::with_reader(std::io::Cursor::new(csv_right)),
+            );
+        let actual = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(expected.len(), 1_000 + 1_000 + 1_000 / 7 + 1);
+        assert_eq!(sorted(actual), sorted(expected));
+        assert!(diff_iter.run_report().is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_with_canonical_column_order_ignores_reordered_columns(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,name,kind\n\
+                        1,lemon,fruit\n\
+                        2,strawberry,fruit";
+        let csv_right = "\
+                        kind,id,name\n\
+                        fruit,1,lemon\n\
+                        nut,2,strawberry";
+
+        // canonical order is "id,kind,name"
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .canonical_column_order(true)
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            );
+        let diff_byte_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            diff_byte_records,
+            vec![DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "fruit", "strawberry"]),
+                    3
+                ),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "nut", "strawberry"]),
+                    3
+                ),
+                field_indices: vec![1],
+            }]
+        );
+        let run_report = diff_iter.run_report();
+        assert!(run_report.columns_reordered());
+        assert_eq!(
+            run_report
+                .column_order_right()
+                .map(|column_order| column_order.original_indices()),
+            Some([1, 0, 2].as_slice())
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_within_max_duration_is_complete() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .max_duration(Duration::from_secs(60))
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            );
+        let num_of_diff_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?.len();
+
+        assert_eq!(num_of_diff_records, 1);
+        assert!(!diff_iter.run_report().timed_out());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_run_report_contains_empty_csv_semantics() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "";
+
+        let mut diff_iter = CsvByteDiffBuilder::new()
+            .empty_csv_semantics(EmptyCsvSemantics::AllDifferent)
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            );
+        for diff_record in &mut diff_iter {
+            diff_record?;
+        }
+        let run_report = diff_iter.run_report();
+
+        assert_eq!(
+            run_report.empty_csv_semantics(),
+            EmptyCsvSemantics::AllDifferent
+        );
+        assert_eq!(run_report.deleted(), 2);
+        assert!(run_report.is_consistent());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_blank_right_is_treated_as_empty_and_reported() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\u{feff}  \r\n\t\r\n\r\n";
+
+        let mut diff_iter = CsvByteDiffBuilder::new().build()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        let diff_records = diff_iter.by_ref().collect::<csv::Result<Vec<_>>>()?;
+        let run_report = diff_iter.run_report();
+
+        assert_eq!(
+            diff_records,
+            vec![DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b", "c"]),
+                2
+            ))]
+        );
+        assert_eq!(run_report.empty_csv_left(), None);
+        assert_eq!(run_report.empty_csv_right(), Some(EmptyCsvReason::Blank));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_blank_left_with_error_semantics_is_empty_csv_err() -> Result<(), Box<dyn Error>> {
+        let csv_left = " \n  ";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+
+        let err = CsvByteDiffLocalBuilder::new()
+            .empty_csv_semantics(EmptyCsvSemantics::Error)
+            .build()?
+            .diff(
+                Csv::with_reader_seek(csv_left.as_bytes()),
+                Csv::with_reader_seek(csv_right.as_bytes()),
+            )
+            .unwrap_err();
+
+        assert_eq!(err.code(), "CSVDIFF_EMPTY_CSV");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_compact_modify_round_trip() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3,header4\n\
+                        a,b,c,d";
+        let csv_right = "\
+                        header1,header2,header3,header4\n\
+                        a,b,x,d";
+
+        let actual = CsvByteDiff::new()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .compact()
+            .map(|res| res.map(DiffByteRecord::from))
+            .collect::<csv::Result<Vec<_>>>()?;
+
+        let expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c", "d"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x", "d"]), 2),
+            field_indices: vec![2],
+        }];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_compact_modify_round_trip_with_numeric_columns() -> Result<(), Box<dyn Error>>
+    {
+        let csv_left = "\
+                        id,price,name\n\
+                        1,1.0,x";
+        let csv_right = "\
+                        id,price,name\n\
+                        1,1.00,y";
+
+        let actual = CsvByteDiffBuilder::new()
+            .numeric_columns([1])
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .compact()
+            .map(|res| res.map(DiffByteRecord::from))
+            .collect::<csv::Result<Vec<_>>>()?;
+
+        let expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "1.0", "x"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "1.00", "y"]), 2),
+            field_indices: vec![2],
+        }];
+
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_outcome_short_circuit_is_different() -> Result<(), Box<dyn Error>> {
+        use crate::diff_outcome::DiffOutcome;
+
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i";
+
+        let outcome = DiffOutcome::short_circuit(CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        ));
+
+        assert!(outcome.is_different());
+        assert_eq!(outcome.exit_code(), 1);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_slices_fields_borrow_from_input_except_escaped_quotes() -> Result<(), Box<dyn Error>> {
+        let csv_left = b"\
+                        header1,header2,header3\n\
+                        a,\"b,c\",x\n\
+                        d,e,f";
+        let csv_right = b"\
+                        header1,header2,header3\n\
+                        a,\"b,c\",\"y \"\"z\"\"\"\n\
+                        g,h,i";
+
+        let actual = CsvByteDiffLocal::new()?.diff_slices(csv_left, csv_right)?;
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b,c", "x"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b,c", "y \"z\""]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                3,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["g", "h", "i"]),
+                3,
+            )),
+        ]);
+        assert_eq!(
+            DiffByteRecords::from(
+                actual
+                    .iter()
+                    .map(DiffByteRecordRef::to_diff_byte_record)
+                    .collect::<Vec<_>>()
+            ),
+            expected
+        );
+
+        let input_range = csv_right.as_ptr_range();
+        match &actual[0] {
+            DiffByteRecordRef::Modify { delete, add, .. } => {
+                assert!(delete.is_borrowed());
+                assert!(!add.is_borrowed());
+                // the quoted field without escaped quotes is borrowed from the input (without its quotes)
+                assert!(input_range.contains(&add.get(1).unwrap().as_ptr()));
+            }
+            diff_record => panic!("expected `Modify`, got {:?}", diff_record),
+        }
+        match &actual[2] {
+            DiffByteRecordRef::Add(add) => assert!(add.is_borrowed()),
+            diff_record => panic!("expected `Add`, got {:?}", diff_record),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[derive(Debug)]
+    struct CaseInsensitive;
+
+    #[cfg(feature = "rayon-threads")]
+    impl FieldComparator for CaseInsensitive {
+        fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+            field_left.eq_ignore_ascii_case(field_right)
+        }
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_field_comparator_equal_by_comparator_is_not_modified() -> Result<(), Box<dyn Error>>
+    {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,B,c\n\
+                        d,E,x";
+
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "E", "x"]), 3),
+            field_indices: vec![2],
+        }]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocalBuilder::new()
+                .field_comparator(1, CaseInsensitive)
+                .build()?,
+        )?;
+
+        csv_diff_with_sorting(
+            csv_left,
+            csv_right,
+            expected,
+            CsvByteDiffBuilder::new()
+                .field_comparator(1, CaseInsensitive)
+                .build()?,
+        )
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_ignored_columns_for_modify_keeps_their_values() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        id,name,updated_at\n\
+                        1,lemon,2021-01-01\n\
+                        2,strawberry,2021-01-01";
+        let csv_right = "\
+                        id,name,updated_at\n\
+                        1,lemon,2021-02-01\n\
+                        2,cherry,2021-02-01";
+
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["2", "strawberry", "2021-01-01"]),
+                3,
+            ),
+            add: ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["2", "cherry", "2021-02-01"]),
+                3,
+            ),
+            field_indices: vec![1],
+        }]);
+
+        csv_diff_local_with_sorting(
+            csv_left,
+            csv_right,
+            expected.clone(),
+            CsvByteDiffLocalBuilder::new()
+                .ignore_columns_for_modify(vec![2])
+                .build()?,
+        )?;
+
+        csv_diff_with_sorting(
+            csv_left,
+            csv_right,
+            expected,
+            CsvByteDiffBuilder::new()
+                .ignore_columns_for_modify(vec![2])
+                .build()?,
+        )
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    /// Reads like the wrapped reader, but panics as soon as it reaches its end.
+    struct PanicAtEof<R>(R);
+
+    #[cfg(feature = "rayon-threads")]
+    impl<R: std::io::Read> std::io::Read for PanicAtEof<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => panic!("reader broke at eof"),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    impl<R: std::io::Seek> std::io::Seek for PanicAtEof<R> {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    fn task_panic_message(err: &csv::Error) -> Option<&str> {
+        match err.kind() {
+            csv::ErrorKind::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<crate::task_panic::TaskPanicError>())
+                .map(crate::task_panic::TaskPanicError::message),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_local_with_panic_in_hashing_task_is_err_with_panic_message(
+    ) -> Result<(), Box<dyn Error>> {
+        let csv_left = "header1,header2\na,b";
+
+        let err = CsvByteDiffLocal::new()?
+            .diff(
+                Csv::with_reader_seek(PanicAtEof(std::io::Cursor::new(csv_left))),
+                Csv::with_reader_seek(PanicAtEof(std::io::Cursor::new(csv_left))),
+            )
+            .unwrap_err();
+
+        assert_eq!(task_panic_message(&err), Some("reader broke at eof"));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_with_panic_in_hashing_task_is_err_with_panic_message() -> Result<(), Box<dyn Error>> {
+        let csv_left = "header1,header2\na,b";
+
+        let diff_results = CsvByteDiff::new()?
+            .diff(
+                Csv::with_reader(PanicAtEof(csv_left.as_bytes())),
+                Csv::with_reader(PanicAtEof(csv_left.as_bytes())),
+            )
+            .collect::<Vec<_>>();
+
+        match diff_results.as_slice() {
+            [Err(err)] => assert_eq!(task_panic_message(err), Some("reader broke at eof")),
+            diff_results => panic!("expected exactly one error, got {:?}", diff_results),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[derive(Debug)]
+    struct PanickingComparator;
+
+    #[cfg(feature = "rayon-threads")]
+    impl FieldComparator for PanickingComparator {
+        fn eq(&self, _field_left: &[u8], _field_right: &[u8]) -> bool {
+            panic!("comparator broke")
+        }
     }
 
     #[cfg(feature = "rayon-threads")]
     #[test]
-    fn diff_multiple_lines_with_header_modified_at_end_added_at_end() -> Result<(), Box<dyn Error>>
-    {
-        let csv_left = "\
-                        header1,header2,header3\n\
-                        a,b,c\n\
-                        x,y,z";
-        let csv_right = "\
-                        header1,header2,header3\n\
-                        a,b,c\n\
-                        x,y,a\n\
-                        g,h,i";
+    fn diff_with_panic_while_comparing_is_err_with_panic_message() -> Result<(), Box<dyn Error>> {
+        let csv_left = "header1,header2\na,b";
+        let csv_right = "header1,header2\na,c";
 
-        let expected = DiffByteRecords(vec![
-            DiffByteRecord::Modify {
-                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "z"]), 3),
-                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "y", "a"]), 3),
-                field_indices: vec![2],
-            },
-            DiffByteRecord::Add(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["g", "h", "i"]),
-                4,
-            )),
-        ]);
+        let mut diff_byte_records_iter = CsvByteDiffBuilder::new()
+            .field_comparator(1, PanickingComparator)
+            .build()?
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            );
 
-        csv_diff_local_with_sorting(
-            csv_left,
-            csv_right,
-            expected.clone(),
-            CsvByteDiffLocal::new()?,
-        )?;
+        match diff_byte_records_iter.next() {
+            Some(Err(err)) => assert_eq!(task_panic_message(&err), Some("comparator broke")),
+            diff_result => panic!("expected an error, got {:?}", diff_result),
+        }
+        assert!(diff_byte_records_iter.next().is_none());
+        Ok(())
+    }
 
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn builder_without_primary_key_columns_is_no_primary_key_columns_err(
+    ) -> Result<(), Box<dyn Error>> {
+        let thread_pool = rayon::ThreadPoolBuilder::new().build()?;
+        let expected = CsvByteDiffBuilderError::NoPrimaryKeyColumns;
+        let actual = CsvByteDiffLocalBuilder::new()
+            .rayon_thread_pool(&thread_pool)
+            .primary_key_columns(std::iter::empty())
+            .build();
+
+        assert!(actual.is_err());
+        assert!(matches!(
+            actual,
+            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+        ));
+        assert_eq!(expected.to_string(), "No primary key columns have been specified. You need to provide at least one column index.");
+        Ok(())
     }
 
     #[cfg(feature = "rayon-threads")]
     #[test]
-    fn diff_multiple_lines_with_header_added_multiple() -> Result<(), Box<dyn Error>> {
+    fn diff_whole_row_as_key_is_set_difference_without_modify() -> Result<(), Box<dyn Error>> {
         let csv_left = "\
                         header1,header2,header3\n\
                         a,b,c\n\
@@ -1684,20 +5890,18 @@ mod tests {
                         g,h,i";
         let csv_right = "\
                         header1,header2,header3\n\
-                        a,b,c\n\
-                        d,e,f\n\
                         g,h,i\n\
-                        j,k,l\n\
-                        m,n,o";
+                        a,b,x\n\
+                        d,e,f";
 
-        let expected = DiffByteRecords(vec![
-            DiffByteRecord::Add(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["j", "k", "l"]),
-                5,
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b", "c"]),
+                2,
             )),
             DiffByteRecord::Add(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["m", "n", "o"]),
-                6,
+                csv::ByteRecord::from(vec!["a", "b", "x"]),
+                3,
             )),
         ]);
 
@@ -1705,31 +5909,56 @@ mod tests {
             csv_left,
             csv_right,
             expected.clone(),
-            CsvByteDiffLocal::new()?,
+            CsvByteDiffLocalBuilder::new()
+                .primary_key_columns(std::iter::empty())
+                .whole_row_as_key(true)
+                .build()?,
         )?;
 
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+        csv_diff_with_sorting(
+            csv_left,
+            csv_right,
+            expected,
+            CsvByteDiffBuilder::new()
+                .primary_key_columns(std::iter::empty())
+                .whole_row_as_key(true)
+                .build()?,
+        )
     }
 
     #[cfg(feature = "rayon-threads")]
     #[test]
-    fn diff_multiple_lines_with_header_deleted_multiple() -> Result<(), Box<dyn Error>> {
+    fn diff_primary_key_all_columns_except_value_column_reports_modify(
+    ) -> Result<(), Box<dyn Error>> {
         let csv_left = "\
-                        header1,header2,header3\n\
-                        a,b,c\n\
-                        d,e,f\n\
-                        g,h,i";
+                        region,product,month,amount\n\
+                        north,lemon,jan,10\n\
+                        north,lemon,feb,20\n\
+                        south,lemon,jan,30";
         let csv_right = "\
-                        header1,header2,header3\n\
-                        a,b,c";
+                        region,product,month,amount\n\
+                        north,lemon,jan,10\n\
+                        north,lemon,feb,25\n\
+                        south,lime,jan,30";
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["north", "lemon", "feb", "20"]),
+                    3,
+                ),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["north", "lemon", "feb", "25"]),
+                    3,
+                ),
+                field_indices: vec![3],
+            },
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["d", "e", "f"]),
-                3,
+                csv::ByteRecord::from(vec!["south", "lemon", "jan", "30"]),
+                4,
             )),
-            DiffByteRecord::Delete(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["g", "h", "i"]),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["south", "lime", "jan", "30"]),
                 4,
             )),
         ]);
@@ -1738,125 +5967,87 @@ mod tests {
             csv_left,
             csv_right,
             expected.clone(),
-            CsvByteDiffLocal::new()?,
+            CsvByteDiffLocalBuilder::new()
+                .primary_key_all_columns_except([3])
+                .build()?,
         )?;
 
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
-    }
-
-    #[cfg(feature = "rayon-threads")]
-    #[test]
-    fn diff_multiple_lines_with_header_modified_multiple() -> Result<(), Box<dyn Error>> {
-        let csv_left = "\
-                        header1,header2,header3\n\
-                        a,b,c\n\
-                        d,e,f\n\
-                        g,h,i";
-        let csv_right = "\
-                        header1,header2,header3\n\
-                        a,b,x\n\
-                        d,e,f\n\
-                        g,h,x";
-
-        let expected = DiffByteRecords(vec![
-            DiffByteRecord::Modify {
-                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
-                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
-                field_indices: vec![2],
-            },
-            DiffByteRecord::Modify {
-                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "i"]), 4),
-                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "x"]), 4),
-                field_indices: vec![2],
-            },
-        ]);
-
-        csv_diff_local_with_sorting(
+        csv_diff_with_sorting(
             csv_left,
             csv_right,
-            expected.clone(),
-            CsvByteDiffLocal::new()?,
-        )?;
-
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
+            expected,
+            CsvByteDiffBuilder::new()
+                .primary_key_all_columns_except([3])
+                .build()?,
+        )
     }
 
     #[cfg(feature = "rayon-threads")]
     #[test]
-    fn diff_multiple_lines_with_header_added_modified_deleted_multiple(
-    ) -> Result<(), Box<dyn Error>> {
+    fn diff_with_field_comparators_by_header_and_closure() -> Result<(), Box<dyn Error>> {
+        use crate::field_comparator::FnComparator;
+
         let csv_left = "\
-                        header1,header2,header3\n\
-                        a,b,c\n\
-                        d,e,f\n\
-                        g,h,i\n\
-                        j,k,l\n\
-                        m,n,o";
+                        id,name,price\n\
+                        1,lemon,1.00\n\
+                        2,lime,2.00\n\
+                        3,cherry,3.00";
         let csv_right = "\
-                        header1,header2,header3\n\
-                        a,b,x\n\
-                        p,q,r\n\
-                        m,n,o\n\
-                        x,y,z\n\
-                        j,k,x\n";
+                        id,name,price\n\
+                        1,LEMON,1.001\n\
+                        2,lime,2.50\n\
+                        3,Kirsche,3.00";
+        let within_cent = |field_left: &[u8], field_right: &[u8]| {
+            let parse = |field: &[u8]| std::str::from_utf8(field).ok()?.parse::<f64>().ok();
+            match (parse(field_left), parse(field_right)) {
+                (Some(left), Some(right)) => (left - right).abs() < 0.01,
+                _ => field_left == field_right,
+            }
+        };
+        let ignore_case =
+            |field_left: &[u8], field_right: &[u8]| field_left.eq_ignore_ascii_case(field_right);
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
-                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
-                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "lime", "2.00"]),
+                    3,
+                ),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "lime", "2.50"]), 3),
                 field_indices: vec![2],
             },
-            DiffByteRecord::Delete(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["d", "e", "f"]),
-                3,
-            )),
-            DiffByteRecord::Add(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["p", "q", "r"]),
-                3,
-            )),
-            DiffByteRecord::Delete(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["g", "h", "i"]),
-                4,
-            )),
             DiffByteRecord::Modify {
-                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["j", "k", "l"]), 5),
-                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["j", "k", "x"]), 6),
-                field_indices: vec![2],
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "cherry", "3.00"]),
+                    4,
+                ),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "Kirsche", "3.00"]),
+                    4,
+                ),
+                field_indices: vec![1],
             },
-            DiffByteRecord::Add(ByteRecordLineInfo::new(
-                csv::ByteRecord::from(vec!["x", "y", "z"]),
-                5,
-            )),
         ]);
 
         csv_diff_local_with_sorting(
             csv_left,
             csv_right,
             expected.clone(),
-            CsvByteDiffLocal::new()?,
+            CsvByteDiffLocalBuilder::new()
+                .field_comparator_by_header("name", FnComparator::new(ignore_case))
+                .field_comparator(2, FnComparator::new(within_cent))
+                .build()?,
         )?;
 
-        csv_diff_with_sorting(csv_left, csv_right, expected, CsvByteDiff::new()?)
-    }
-
-    #[cfg(feature = "rayon-threads")]
-    #[test]
-    fn builder_without_primary_key_columns_is_no_primary_key_columns_err(
-    ) -> Result<(), Box<dyn Error>> {
-        let thread_pool = rayon::ThreadPoolBuilder::new().build()?;
-        let expected = CsvByteDiffBuilderError::NoPrimaryKeyColumns;
-        let actual = CsvByteDiffLocalBuilder::new()
-            .rayon_thread_pool(&thread_pool)
-            .primary_key_columns(std::iter::empty())
-            .build();
-
-        assert!(actual.is_err());
-        assert!(matches!(
-            actual,
-            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
-        ));
-        assert_eq!(expected.to_string(), "No primary key columns have been specified. You need to provide at least one column index.");
-        Ok(())
+        csv_diff_with_sorting(
+            csv_left,
+            csv_right,
+            expected,
+            CsvByteDiffBuilder::new()
+                .field_comparator_by_header("name", FnComparator::new(ignore_case))
+                .field_comparator(2, FnComparator::new(within_cent))
+                .build()?,
+        )
     }
 
     #[cfg(feature = "rayon-threads")]
@@ -1885,7 +6076,7 @@ mod tests {
                         header1,header2,header3\n\
                         a,b,d";
 
-        let expected = DiffByteRecords(vec![DiffByteRecord::Modify {
+        let expected = DiffByteRecords::from(vec![DiffByteRecord::Modify {
             delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
             add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 2),
             field_indices: vec![2],
@@ -1916,11 +6107,11 @@ mod tests {
 
         let thread_pool = &rayon::ThreadPoolBuilder::new().build()?;
         let csv_diff = CsvByteDiffLocalBuilder::new()
-            .rayon_thread_pool(&thread_pool)
+            .rayon_thread_pool(thread_pool)
             .primary_key_columns(vec![0, 1])
             .build()?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
@@ -2012,6 +6203,32 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_streaming_next_batch_ends_with_error() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x";
+
+        let mut diff_iter = CsvByteDiff::new()?.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+        let batch = diff_iter.next_batch(10);
+
+        assert!(batch.len() < 10);
+        match batch.last() {
+            Some(Err(err)) => assert_eq!(err.code(), "CSVDIFF_RAGGED_ROW"),
+            res => panic!("expected the batch to end with an error, got {:#?}", res),
+        }
+        assert!(batch[..batch.len() - 1].iter().all(Result::is_ok));
+        Ok(())
+    }
+
     #[cfg(feature = "rayon-threads")]
     #[test]
     fn diff_local_one_line_with_header_error_right_has_different_num_of_fields(
@@ -2110,7 +6327,7 @@ mod tests {
             Csv::with_reader_seek(csv_right.as_bytes()),
         )
         .unwrap();
-        let mut diff_res_expected = DiffByteRecords(vec![
+        let mut diff_res_expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
@@ -2128,7 +6345,7 @@ mod tests {
 
         diff_res_actual.sort_by_line();
         diff_res_expected.sort_by_line();
-        assert_eq!(diff_res_actual, diff_res_expected);
+        assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
         Ok(())
     }
 
@@ -2165,9 +6382,9 @@ mod tests {
         );
 
         let diff_res_iter: csv::Result<Vec<_>> = diff_res_iter.collect();
-        let mut diff_res_actual: DiffByteRecords = DiffByteRecords(diff_res_iter?);
+        let mut diff_res_actual: DiffByteRecords = DiffByteRecords::from(diff_res_iter?);
 
-        let mut diff_res_expected = DiffByteRecords(vec![
+        let mut diff_res_expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
@@ -2188,4 +6405,103 @@ mod tests {
         assert_eq!(diff_res_actual, diff_res_expected);
         Ok(())
     }
+
+    #[cfg(not(feature = "rayon-threads"))]
+    #[test]
+    fn diff_streaming_sequential_more_records_than_channel_capacity() -> Result<(), Box<dyn Error>>
+    {
+        use crate::csv_hash_task_spawner::{
+            CsvHashTaskSpawnerBuilderSequential, CsvHashTaskSpawnerSequential,
+        };
+
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},{}", i, if i % 100 == 0 { "b" } else { "a" })))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // the hashing tasks run on the current thread, so they must not block on a full channel
+        let diff_res_iter = CsvByteDiffBuilder::<CsvHashTaskSpawnerSequential>::new(
+            CsvHashTaskSpawnerBuilderSequential::new(),
+        )
+        .channel_capacity(1)
+        .build()?
+        .diff(
+            Csv::with_reader(std::io::Cursor::new(csv_left)),
+            Csv::with_reader(std::io::Cursor::new(csv_right)),
+        );
+        let mut diff_res_actual =
+            DiffByteRecords::from(diff_res_iter.collect::<csv::Result<Vec<_>>>()?);
+        diff_res_actual.sort_by_line();
+
+        assert_eq!(diff_res_actual.as_slice().len(), 10);
+        assert!(diff_res_actual
+            .as_slice()
+            .iter()
+            .zip((0..1_000).step_by(100))
+            .all(|(diff_record, i)| matches!(
+                diff_record,
+                DiffByteRecord::Modify { add, .. } if add.byte_record()[0] == *i.to_string().as_bytes()
+            )));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "rayon-threads"))]
+    #[test]
+    fn diff_local_sequential_multiple_lines_with_header_combined_key_added_deleted_modified(
+    ) -> Result<(), Box<dyn Error>> {
+        use crate::csv_hash_task_spawner::{
+            CsvHashTaskSpawnerLocalBuilderSequential, CsvHashTaskSpawnerLocalSequential,
+        };
+
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f\n\
+                        g,h,i\n\
+                        m,n,o";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,x\n\
+                        g,h,i\n\
+                        d,f,f\n\
+                        m,n,o";
+
+        for is_chunked_hashing in [false, true] {
+            let mut diff_res_actual =
+                CsvByteDiffLocalBuilder::<CsvHashTaskSpawnerLocalSequential>::new(
+                    CsvHashTaskSpawnerLocalBuilderSequential::new(),
+                )
+                .primary_key_columns(vec![0, 1])
+                .chunked_hashing(is_chunked_hashing)
+                .build()?
+                .diff(
+                    Csv::with_reader_seek(csv_left.as_bytes()),
+                    Csv::with_reader_seek(csv_right.as_bytes()),
+                )?;
+            let mut diff_res_expected = DiffByteRecords::from(vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "x"]), 2),
+                    field_indices: vec![2],
+                },
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["d", "e", "f"]),
+                    3,
+                )),
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["d", "f", "f"]),
+                    4,
+                )),
+            ]);
+
+            diff_res_actual.sort_by_line();
+            diff_res_expected.sort_by_line();
+            assert_eq!(diff_res_actual.as_slice(), diff_res_expected.as_slice());
+        }
+        Ok(())
+    }
 }
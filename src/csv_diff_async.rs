@@ -0,0 +1,196 @@
+use crate::csv::Csv;
+use crate::csv_diff::CsvByteDiff;
+#[cfg(feature = "rayon-threads")]
+use crate::csv_diff::CsvDiffNewError;
+use crate::csv_hash_task_spawner::CsvHashTaskSpawner;
+#[cfg(feature = "rayon-threads")]
+use crate::csv_hash_task_spawner::CsvHashTaskSpawnerRayon;
+use crate::diff_row::DiffByteRecord;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc;
+use tokio_util::io::SyncIoBridge;
+
+/// The number of differences, that are buffered in a [`DiffByteRecordsStream`], before
+/// the comparison waits for the stream to be polled.
+const STREAM_BUFFER_SIZE: usize = 1_024;
+
+/// Compare two CSVs, that are read from [`AsyncRead`] sources, lazily with each other
+/// and receive the differences as a [`Stream`] (available with feature `tokio-async`).
+///
+/// Internally, the same pipeline as in [`CsvByteDiff`](crate::csv_diff::CsvByteDiff) is used,
+/// but reading from the sources and collecting the differences happens on tokio's blocking threads,
+/// so that no worker thread of the runtime is blocked.
+///
+/// A `CsvByteDiffAsync` can be created from a configured `CsvByteDiff` (e.g. with other primary key columns)
+/// with [`From`].
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::csv_diff_async::CsvByteDiffAsync;
+use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+# #[tokio::main]
+# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+header1,header2,header3\n\
+a,b,c";
+let csv_right = "\
+header1,header2,header3\n\
+a,b,d";
+
+let mut diff_stream = CsvByteDiffAsync::new()?.diff(csv_left.as_bytes(), csv_right.as_bytes());
+
+let diff_row_actual = diff_stream
+    .next()
+    .await
+    .ok_or("Expected a difference between the two CSVs, but got none")??;
+
+assert_eq!(
+    diff_row_actual,
+    DiffByteRecord::Modify {
+        delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+        add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 2),
+        field_indices: vec![2],
+    }
+);
+assert!(diff_stream.next().await.is_none());
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub struct CsvByteDiffAsync<T: CsvHashTaskSpawner> {
+    csv_byte_diff: CsvByteDiff<T>,
+}
+
+#[cfg(feature = "rayon-threads")]
+impl CsvByteDiffAsync<CsvHashTaskSpawnerRayon> {
+    pub fn new() -> Result<Self, CsvDiffNewError> {
+        Ok(Self {
+            csv_byte_diff: CsvByteDiff::new()?,
+        })
+    }
+}
+
+impl<T: CsvHashTaskSpawner> From<CsvByteDiff<T>> for CsvByteDiffAsync<T> {
+    fn from(csv_byte_diff: CsvByteDiff<T>) -> Self {
+        Self { csv_byte_diff }
+    }
+}
+
+impl<T: CsvHashTaskSpawner> CsvByteDiffAsync<T> {
+    /// Compare `csv_left` with `csv_right` and return a [`DiffByteRecordsStream`] of their differences.
+    ///
+    /// # Panics
+    /// Panics, when called outside of a tokio runtime.
+    pub fn diff<R>(&self, csv_left: R, csv_right: R) -> DiffByteRecordsStream
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let diff_byte_records_iter = self.csv_byte_diff.diff(
+            Csv::with_reader(SyncIoBridge::new(csv_left)),
+            Csv::with_reader(SyncIoBridge::new(csv_right)),
+        );
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+
+        tokio::task::spawn_blocking(move || {
+            for diff_byte_record in diff_byte_records_iter {
+                if sender.blocking_send(diff_byte_record).is_err() {
+                    // the stream has been dropped, so we stop the comparison
+                    break;
+                }
+            }
+        });
+
+        DiffByteRecordsStream { receiver }
+    }
+}
+
+/// Emits all differences between two CSVs, after they have been compared with
+/// [`CsvByteDiffAsync.diff`](CsvByteDiffAsync::diff).
+///
+/// Just like [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator), the differences are emitted _unordered_.
+/// Dropping the stream stops the comparison.
+#[derive(Debug)]
+pub struct DiffByteRecordsStream {
+    receiver: mpsc::Receiver<csv::Result<DiffByteRecord>>,
+}
+
+impl DiffByteRecordsStream {
+    /// Receive the next difference or `None`, if there are no more differences.
+    ///
+    /// This is a convenience method, so that the stream can be consumed without a `StreamExt` trait.
+    pub async fn next(&mut self) -> Option<csv::Result<DiffByteRecord>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for DiffByteRecordsStream {
+    type Item = csv::Result<DiffByteRecord>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_diff::CsvByteDiffBuilder;
+    use crate::diff_result::DiffByteRecords;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+
+    #[cfg(feature = "rayon-threads")]
+    #[tokio::test]
+    async fn diff_with_other_primary_key_emits_all_differences() -> Result<(), Box<dyn Error>> {
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c\n\
+                        d,e,f";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        x,e,f\n\
+                        g,h,i";
+
+        let mut diff_stream = CsvByteDiffAsync::from(
+            CsvByteDiffBuilder::new()
+                .primary_key_columns(std::iter::once(1))
+                .build()?,
+        )
+        .diff(csv_left.as_bytes(), csv_right.as_bytes());
+
+        let mut diff_byte_records = Vec::new();
+        while let Some(diff_byte_record) = diff_stream.next().await {
+            diff_byte_records.push(diff_byte_record?);
+        }
+        let mut diff_byte_records = DiffByteRecords::from(diff_byte_records);
+        diff_byte_records.sort_by_line();
+
+        assert_eq!(
+            diff_byte_records,
+            DiffByteRecords::from(vec![
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["a", "b", "c"]),
+                    2
+                )),
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "e", "f"]), 3),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["x", "e", "f"]), 2),
+                    field_indices: vec![0],
+                },
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["g", "h", "i"]),
+                    3
+                )),
+            ])
+        );
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
+use crate::csv::RecordNormalizer;
+use crate::csv_hasher::random_state;
 use crate::csv_parse_result::CsvLeftRightParseResult;
 use crate::csv_parse_result::RecordHash;
 use crate::csv_parse_result::RecordHashWithPosition;
 use crate::csv_parser_hasher::HashMapValue;
 use crate::diff_result::*;
 use crate::diff_row::*;
+use crate::simd::modified_field_indices;
 use ahash::AHashMap as HashMap;
 use std::io::Read;
 use std::io::Seek;
@@ -18,6 +21,9 @@ pub(crate) struct CsvHashComparer<R: Read + Seek> {
     csv_seek_left_reader: csv::Reader<R>,
     csv_seek_right_reader: csv::Reader<R>,
     diff_records: Vec<DiffByteRecord>,
+    max_diffs: Option<usize>,
+    normalizer_left: Option<RecordNormalizer>,
+    normalizer_right: Option<RecordNormalizer>,
 }
 
 impl<R: Read + std::io::Seek> CsvHashComparer<R> {
@@ -39,9 +45,40 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
             csv_seek_left_reader: left_reader,
             csv_seek_right_reader: right_reader,
             diff_records: Vec::new(),
+            max_diffs: None,
+            normalizer_left: None,
+            normalizer_right: None,
         }
     }
 
+    /// Don't read more than `max_diffs` of the unmatched records, that are emitted at the end.
+    pub fn max_diffs(mut self, max_diffs: Option<usize>) -> Self {
+        self.max_diffs = max_diffs;
+        self
+    }
+
+    /// Transform the records, that are read again, the same way they have been transformed before they were hashed.
+    pub fn normalizers(
+        mut self,
+        normalizer_left: Option<RecordNormalizer>,
+        normalizer_right: Option<RecordNormalizer>,
+    ) -> Self {
+        self.normalizer_left = normalizer_left;
+        self.normalizer_right = normalizer_right;
+        self
+    }
+
+    /// Seed the hash maps with `hash_seed` (see [`random_state`]).
+    pub fn hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.csv_records_left_map =
+            HashMap::with_capacity_and_hasher(self.max_capacity_left_map, random_state(hash_seed));
+        self.csv_records_right_map =
+            HashMap::with_capacity_and_hasher(self.max_capacity_right_map, random_state(hash_seed));
+        self.intermediate_left_map = HashMap::with_hasher(random_state(hash_seed));
+        self.intermediate_right_map = HashMap::with_hasher(random_state(hash_seed));
+        self
+    }
+
     pub fn compare_csv_left_right_parse_result(
         &mut self,
         csv_left_right_parse_results: impl IntoIterator<
@@ -98,24 +135,20 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                                     self.csv_seek_left_reader
                                         .read_byte_record(&mut left_byte_record)
                                         .expect("can be read");
+                                    normalize(self.normalizer_left.as_ref(), &mut left_byte_record);
                                     let mut right_byte_record = csv::ByteRecord::new();
                                     // TODO: proper error handling (although we are safe here)
                                     self.csv_seek_right_reader
                                         .read_byte_record(&mut right_byte_record)
                                         .expect("can be read");
-                                    let fields_modified = left_byte_record
-                                        .iter()
-                                        .enumerate()
-                                        .zip(right_byte_record.iter())
-                                        .fold(
-                                            Vec::new(),
-                                            |mut acc, ((idx, field_left), field_right)| {
-                                                if field_left != field_right {
-                                                    acc.push(idx);
-                                                }
-                                                acc
-                                            },
-                                        );
+                                    normalize(
+                                        self.normalizer_right.as_ref(),
+                                        &mut right_byte_record,
+                                    );
+                                    let fields_modified = modified_field_indices(
+                                        &left_byte_record,
+                                        &right_byte_record,
+                                    );
                                     self.diff_records.push(DiffByteRecord::Modify {
                                         add: ByteRecordLineInfo::new(
                                             right_byte_record,
@@ -185,24 +218,20 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                                     self.csv_seek_left_reader
                                         .read_byte_record(&mut left_byte_record)
                                         .expect("can be read");
+                                    normalize(self.normalizer_left.as_ref(), &mut left_byte_record);
                                     let mut right_byte_record = csv::ByteRecord::new();
                                     // TODO: proper error handling (although we are safe here)
                                     self.csv_seek_right_reader
                                         .read_byte_record(&mut right_byte_record)
                                         .expect("can be read");
-                                    let fields_modified = left_byte_record
-                                        .iter()
-                                        .enumerate()
-                                        .zip(right_byte_record.iter())
-                                        .fold(
-                                            Vec::new(),
-                                            |mut acc, ((idx, field_left), field_right)| {
-                                                if field_left != field_right {
-                                                    acc.push(idx);
-                                                }
-                                                acc
-                                            },
-                                        );
+                                    normalize(
+                                        self.normalizer_right.as_ref(),
+                                        &mut right_byte_record,
+                                    );
+                                    let fields_modified = modified_field_indices(
+                                        &left_byte_record,
+                                        &right_byte_record,
+                                    );
                                     self.diff_records.push(DiffByteRecord::Modify {
                                         add: ByteRecordLineInfo::new(
                                             right_byte_record,
@@ -226,7 +255,10 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
             }
         }
 
+        let max_diffs = self.max_diffs.unwrap_or(usize::MAX);
         let mut diff_records = std::mem::take(&mut self.diff_records);
+        diff_records.truncate(max_diffs);
+        let remaining = max_diffs - diff_records.len();
         diff_records.extend(
             std::mem::take(&mut self.csv_records_left_map)
                 .into_iter()
@@ -239,6 +271,7 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         self.csv_seek_left_reader
                             .read_byte_record(&mut byte_record)
                             .expect("can be read");
+                        normalize(self.normalizer_left.as_ref(), &mut byte_record);
                         Some(DiffByteRecord::Delete(ByteRecordLineInfo::new(
                             byte_record,
                             pos.line,
@@ -256,21 +289,15 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         self.csv_seek_left_reader
                             .read_byte_record(&mut left_byte_record)
                             .expect("can be read");
+                        normalize(self.normalizer_left.as_ref(), &mut left_byte_record);
                         let mut right_byte_record = csv::ByteRecord::new();
                         // TODO: proper error handling (although we are safe here)
                         self.csv_seek_right_reader
                             .read_byte_record(&mut right_byte_record)
                             .expect("can be read");
-                        let fields_modified = left_byte_record
-                            .iter()
-                            .enumerate()
-                            .zip(right_byte_record.iter())
-                            .fold(Vec::new(), |mut acc, ((idx, field_left), field_right)| {
-                                if field_left != field_right {
-                                    acc.push(idx);
-                                }
-                                acc
-                            });
+                        normalize(self.normalizer_right.as_ref(), &mut right_byte_record);
+                        let fields_modified =
+                            modified_field_indices(&left_byte_record, &right_byte_record);
                         Some(DiffByteRecord::Modify {
                             add: ByteRecordLineInfo::new(right_byte_record, pos_right.line),
                             delete: ByteRecordLineInfo::new(left_byte_record, pos_left.line),
@@ -278,9 +305,11 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         })
                     }
                     _ => None,
-                }),
+                })
+                .take(remaining),
         );
 
+        let remaining = max_diffs - diff_records.len();
         diff_records.extend(
             std::mem::take(&mut self.csv_records_right_map)
                 .into_iter()
@@ -293,6 +322,7 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         self.csv_seek_right_reader
                             .read_byte_record(&mut byte_record)
                             .expect("can be read");
+                        normalize(self.normalizer_right.as_ref(), &mut byte_record);
                         Some(DiffByteRecord::Add(ByteRecordLineInfo::new(
                             byte_record,
                             pos.line,
@@ -310,21 +340,15 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         self.csv_seek_left_reader
                             .read_byte_record(&mut left_byte_record)
                             .expect("can be read");
+                        normalize(self.normalizer_left.as_ref(), &mut left_byte_record);
                         let mut right_byte_record = csv::ByteRecord::new();
                         // TODO: proper error handling (although we are safe here)
                         self.csv_seek_right_reader
                             .read_byte_record(&mut right_byte_record)
                             .expect("can be read");
-                        let fields_modified = left_byte_record
-                            .iter()
-                            .enumerate()
-                            .zip(right_byte_record.iter())
-                            .fold(Vec::new(), |mut acc, ((idx, field_left), field_right)| {
-                                if field_left != field_right {
-                                    acc.push(idx);
-                                }
-                                acc
-                            });
+                        normalize(self.normalizer_right.as_ref(), &mut right_byte_record);
+                        let fields_modified =
+                            modified_field_indices(&left_byte_record, &right_byte_record);
                         Some(DiffByteRecord::Modify {
                             add: ByteRecordLineInfo::new(right_byte_record, pos_right.line),
                             delete: ByteRecordLineInfo::new(left_byte_record, pos_left.line),
@@ -332,9 +356,16 @@ impl<R: Read + std::io::Seek> CsvHashComparer<R> {
                         })
                     }
                     _ => None,
-                }),
+                })
+                .take(remaining),
         );
 
-        Ok(DiffByteRecords(diff_records))
+        Ok(DiffByteRecords::from(diff_records))
+    }
+}
+
+fn normalize(normalizer: Option<&RecordNormalizer>, byte_record: &mut csv::ByteRecord) {
+    if let Some(normalizer) = normalizer {
+        normalizer.apply(byte_record);
     }
 }
@@ -1,25 +1,61 @@
 use crate::{
     csv_parse_result::{CsvByteRecordWithHash, CsvLeftRightParseResult},
     diff_result::DiffByteRecordsIterator,
+    empty_csv::CsvHeaderInfo,
+    error_policy::SkippedRecord,
+    key_stats::KeyStats,
+    task_panic::TaskPanicError,
 };
 use crossbeam_channel::{Receiver, Sender};
 
 pub struct CsvHashReceiverStreamComparer {
     receiver: Receiver<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
     sender_csv_records_recycle: Sender<csv::ByteRecord>,
+    receiver_header_info_left: Receiver<CsvHeaderInfo>,
+    receiver_header_info_right: Receiver<CsvHeaderInfo>,
+    receiver_key_stats_left: Receiver<KeyStats>,
+    receiver_key_stats_right: Receiver<KeyStats>,
+    receiver_skipped_records_left: Receiver<Vec<SkippedRecord>>,
+    receiver_skipped_records_right: Receiver<Vec<SkippedRecord>>,
+    receiver_panic: Receiver<TaskPanicError>,
 }
 
 impl CsvHashReceiverStreamComparer {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         receiver: Receiver<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
         sender_csv_records_recycle: Sender<csv::ByteRecord>,
+        receiver_header_info_left: Receiver<CsvHeaderInfo>,
+        receiver_header_info_right: Receiver<CsvHeaderInfo>,
+        receiver_key_stats_left: Receiver<KeyStats>,
+        receiver_key_stats_right: Receiver<KeyStats>,
+        receiver_skipped_records_left: Receiver<Vec<SkippedRecord>>,
+        receiver_skipped_records_right: Receiver<Vec<SkippedRecord>>,
+        receiver_panic: Receiver<TaskPanicError>,
     ) -> Self {
         Self {
             receiver,
             sender_csv_records_recycle,
+            receiver_header_info_left,
+            receiver_header_info_right,
+            receiver_key_stats_left,
+            receiver_key_stats_right,
+            receiver_skipped_records_left,
+            receiver_skipped_records_right,
+            receiver_panic,
         }
     }
     pub fn recv_hashes_and_compare(self) -> DiffByteRecordsIterator {
-        DiffByteRecordsIterator::new(self.receiver, self.sender_csv_records_recycle)
+        DiffByteRecordsIterator::new(
+            self.receiver,
+            self.sender_csv_records_recycle,
+            self.receiver_header_info_left,
+            self.receiver_header_info_right,
+            self.receiver_key_stats_left,
+            self.receiver_key_stats_right,
+            self.receiver_skipped_records_left,
+            self.receiver_skipped_records_right,
+            self.receiver_panic,
+        )
     }
 }
@@ -1,9 +1,9 @@
 use std::{
     collections::HashSet,
     io::{Read, Seek},
-    ops::Deref,
-    sync::Arc,
 };
+#[cfg(feature = "rayon-threads")]
+use std::{ops::Deref, sync::Arc};
 
 use crate::csv_parse_result::{
     CsvLeftRightParseResult, CsvParseResult, CsvParseResultLeft, CsvParseResultRight,
@@ -12,13 +12,20 @@ use crate::csv_parse_result::{
 use crate::thread_scope_strategy::CrossbeamScope;
 #[cfg(feature = "rayon-threads")]
 use crate::thread_scope_strategy::RayonScope;
+#[cfg(any(feature = "rayon-threads", feature = "crossbeam-threads"))]
+use crate::thread_scope_strategy::ThreadScoper;
 use crate::{
+    cancellation::CancellationToken,
     csv::Csv,
     csv_hash_receiver_comparer::CsvHashReceiverStreamComparer,
     csv_parse_result::{CsvByteRecordWithHash, RecordHashWithPosition},
-    csv_parser_hasher::{CsvParserHasherLinesSender, CsvParserHasherSender},
+    csv_parser_hasher::{ChunkHashingTask, CsvParserHasherLinesSender, CsvParserHasherSender},
     diff_result::DiffByteRecordsIterator,
-    thread_scope_strategy::ThreadScoper,
+    empty_csv::CsvHeaderInfo,
+    error_policy::SkippedRecord,
+    field_comparator::FieldCanonicalizer,
+    key_stats::KeyStats,
+    task_panic::{catch_panic, TaskPanicError},
 };
 use crossbeam_channel::{bounded, Receiver, Sender};
 use csv::Reader;
@@ -27,57 +34,154 @@ pub struct CsvHashTaskSenderWithRecycleReceiver<R: Read> {
     sender: Sender<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
     csv: Csv<R>,
     receiver_recycle_csv: Receiver<csv::ByteRecord>,
+    sender_header_info: Sender<CsvHeaderInfo>,
+    sender_key_stats: Sender<KeyStats>,
+    sender_skipped_records: Sender<Vec<SkippedRecord>>,
+    sender_panic: Sender<TaskPanicError>,
+    cancellation_token: CancellationToken,
+    is_canonical_column_order: bool,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    field_canonicalizer: FieldCanonicalizer,
+    hash_seed: Option<u64>,
 }
 
 impl<R: Read> CsvHashTaskSenderWithRecycleReceiver<R> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         sender: Sender<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
         csv: Csv<R>,
         receiver_recycle_csv: Receiver<csv::ByteRecord>,
+        sender_header_info: Sender<CsvHeaderInfo>,
+        sender_key_stats: Sender<KeyStats>,
+        sender_skipped_records: Sender<Vec<SkippedRecord>>,
+        sender_panic: Sender<TaskPanicError>,
+        cancellation_token: CancellationToken,
     ) -> Self {
         Self {
             sender,
             csv,
             receiver_recycle_csv,
+            sender_header_info,
+            sender_key_stats,
+            sender_skipped_records,
+            sender_panic,
+            cancellation_token,
+            is_canonical_column_order: false,
+            primary_key_excluded_columns: None,
+            field_canonicalizer: FieldCanonicalizer::default(),
+            hash_seed: None,
         }
     }
+
+    /// Put the columns of the CSV into canonical order before hashing.
+    pub(crate) fn canonical_column_order(mut self, is_canonical_column_order: bool) -> Self {
+        self.is_canonical_column_order = is_canonical_column_order;
+        self
+    }
+
+    /// Use all columns except `primary_key_excluded_columns` as the primary key (if it is `Some`).
+    pub(crate) fn primary_key_all_columns_except(
+        mut self,
+        primary_key_excluded_columns: Option<HashSet<usize>>,
+    ) -> Self {
+        self.primary_key_excluded_columns = primary_key_excluded_columns;
+        self
+    }
+
+    /// Hash the canonical representation of the fields (see [`FieldCanonicalizer`]).
+    pub(crate) fn field_canonicalizer(mut self, field_canonicalizer: FieldCanonicalizer) -> Self {
+        self.field_canonicalizer = field_canonicalizer;
+        self
+    }
+
+    /// Seed the hashes of keys and records with `hash_seed` (if it is `Some`).
+    pub(crate) fn hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.hash_seed = hash_seed;
+        self
+    }
 }
 
+/// The reader of a CSV, that has been hashed by a [`CsvHashTaskLineSenders`] task, in order to seek to its records,
+/// together with its header and the records, that have been skipped.
+pub(crate) type HashedCsvReader<R> = csv::Result<(Reader<R>, CsvHeaderInfo, Vec<SkippedRecord>)>;
+
 pub struct CsvHashTaskLineSenders<R: Read> {
     sender: Sender<CsvLeftRightParseResult<RecordHashWithPosition>>,
     sender_total_lines: Sender<u64>,
-    sender_csv_reader: Sender<csv::Result<Reader<R>>>,
+    sender_csv_reader: Sender<HashedCsvReader<R>>,
     csv: Csv<R>,
+    cancellation_token: CancellationToken,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    field_canonicalizer: FieldCanonicalizer,
+    hash_seed: Option<u64>,
+    is_chunked_hashing: bool,
 }
 
 impl<R: Read> CsvHashTaskLineSenders<R> {
     pub(crate) fn new(
         sender: Sender<CsvLeftRightParseResult<RecordHashWithPosition>>,
         sender_total_lines: Sender<u64>,
-        sender_csv_reader: Sender<csv::Result<Reader<R>>>,
+        sender_csv_reader: Sender<HashedCsvReader<R>>,
         csv: Csv<R>,
+        cancellation_token: CancellationToken,
     ) -> Self {
         Self {
             sender,
             sender_total_lines,
             sender_csv_reader,
             csv,
+            cancellation_token,
+            primary_key_excluded_columns: None,
+            field_canonicalizer: FieldCanonicalizer::default(),
+            hash_seed: None,
+            is_chunked_hashing: false,
         }
     }
+
+    /// Hash the records in chunks, which are hashed by [`ChunkHashingTask`]s, instead of hashing them in the parsing task.
+    pub(crate) fn chunked_hashing(mut self, is_chunked_hashing: bool) -> Self {
+        self.is_chunked_hashing = is_chunked_hashing;
+        self
+    }
+
+    /// Use all columns except `primary_key_excluded_columns` as the primary key (if it is `Some`).
+    pub(crate) fn primary_key_all_columns_except(
+        mut self,
+        primary_key_excluded_columns: Option<HashSet<usize>>,
+    ) -> Self {
+        self.primary_key_excluded_columns = primary_key_excluded_columns;
+        self
+    }
+
+    /// Hash the canonical representation of the fields (see [`FieldCanonicalizer`]).
+    pub(crate) fn field_canonicalizer(mut self, field_canonicalizer: FieldCanonicalizer) -> Self {
+        self.field_canonicalizer = field_canonicalizer;
+        self
+    }
+
+    /// Seed the hashes of keys and records with `hash_seed` (if it is `Some`).
+    pub(crate) fn hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.hash_seed = hash_seed;
+        self
+    }
 }
 
 pub trait CsvHashTaskSpawner {
     fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
-        self,
+        &self,
         csv_hash_task_sender_left: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_task_sender_right: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_receiver_comparer: CsvHashReceiverStreamComparer,
         primary_key_columns: HashSet<usize>,
-    ) -> (Self, Receiver<DiffByteRecordsIterator>)
-    where
-        // TODO: this bound is only necessary, because we are returning `self` here;
-        // maybe we can do it differently
-        Self: Sized;
+    ) -> Receiver<DiffByteRecordsIterator>;
+
+    /// Whether the hashing tasks run to completion on the current thread, before
+    /// [`spawn_hashing_tasks_and_send_result`](Self::spawn_hashing_tasks_and_send_result) returns.
+    ///
+    /// The hashed records must then be buffered without any limit, because nobody receives them in the meantime.
+    fn is_sequential(&self) -> bool {
+        false
+    }
 
     fn parse_hash_and_send_for_compare<R, P>(
         csv_hash_task_sender: CsvHashTaskSenderWithRecycleReceiver<R>,
@@ -86,14 +190,32 @@ pub trait CsvHashTaskSpawner {
         R: Read + Send,
         P: CsvParseResult<CsvLeftRightParseResult<CsvByteRecordWithHash>, CsvByteRecordWithHash>,
     {
-        let mut csv_parser_hasher: CsvParserHasherSender<
-            CsvLeftRightParseResult<CsvByteRecordWithHash>,
-        > = CsvParserHasherSender::new(csv_hash_task_sender.sender);
-        csv_parser_hasher.parse_and_hash::<R, P>(
-            csv_hash_task_sender.csv,
-            &primary_key_columns,
-            csv_hash_task_sender.receiver_recycle_csv,
-        )
+        // keep the channel connected until a panic has been reported, so that the receiving side
+        // doesn't mistake a panic for the regular end of the CSV
+        let _sender_keep_alive = csv_hash_task_sender.sender.clone();
+        let sender_panic = csv_hash_task_sender.sender_panic;
+        let res = catch_panic(move || {
+            let mut csv_parser_hasher: CsvParserHasherSender<
+                CsvLeftRightParseResult<CsvByteRecordWithHash>,
+            > = CsvParserHasherSender::new(csv_hash_task_sender.sender)
+                .canonical_column_order(csv_hash_task_sender.is_canonical_column_order)
+                .primary_key_all_columns_except(csv_hash_task_sender.primary_key_excluded_columns)
+                .field_canonicalizer(csv_hash_task_sender.field_canonicalizer)
+                .hash_seed(csv_hash_task_sender.hash_seed);
+            csv_parser_hasher.parse_and_hash::<R, P>(
+                csv_hash_task_sender.csv,
+                &primary_key_columns,
+                csv_hash_task_sender.receiver_recycle_csv,
+                csv_hash_task_sender.sender_header_info,
+                csv_hash_task_sender.sender_key_stats,
+                csv_hash_task_sender.sender_skipped_records,
+                &csv_hash_task_sender.cancellation_token,
+            )
+        });
+        if let Err(task_panic_err) = res {
+            // we ignore any sending errors - the receiver is gone anyway
+            let _ = sender_panic.send(task_panic_err);
+        }
     }
 }
 
@@ -104,17 +226,19 @@ pub struct CsvHashTaskSpawnerRayon {
 }
 
 #[derive(Debug)]
+#[cfg(feature = "rayon-threads")]
 enum OwnOrArc<T> {
     Arced(Arc<T>),
     Owned(T),
 }
 
+#[cfg(feature = "rayon-threads")]
 impl<T> Deref for OwnOrArc<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
         match self {
-            Self::Arced(t) => &*t,
+            Self::Arced(t) => t,
             Self::Owned(t) => t,
         }
     }
@@ -138,12 +262,12 @@ impl CsvHashTaskSpawnerRayon {
 #[cfg(feature = "rayon-threads")]
 impl CsvHashTaskSpawner for CsvHashTaskSpawnerRayon {
     fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
-        self,
+        &self,
         csv_hash_task_sender_left: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_task_sender_right: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_receiver_comparer: CsvHashReceiverStreamComparer,
         primary_key_columns: HashSet<usize>,
-    ) -> (Self, Receiver<DiffByteRecordsIterator>) {
+    ) -> Receiver<DiffByteRecordsIterator> {
         let (sender, receiver) = bounded(1);
 
         let prim_key_columns_clone = primary_key_columns.clone();
@@ -168,7 +292,7 @@ impl CsvHashTaskSpawner for CsvHashTaskSpawnerRayon {
             );
         });
 
-        (self, receiver)
+        receiver
     }
 }
 
@@ -183,15 +307,12 @@ impl CsvHashTaskSpawnerStdThreads {
 
 impl CsvHashTaskSpawner for CsvHashTaskSpawnerStdThreads {
     fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
-        self,
+        &self,
         csv_hash_task_sender_left: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_task_sender_right: CsvHashTaskSenderWithRecycleReceiver<R>,
         csv_hash_receiver_comparer: CsvHashReceiverStreamComparer,
         primary_key_columns: HashSet<usize>,
-    ) -> (Self, Receiver<DiffByteRecordsIterator>)
-    where
-        Self: Sized,
-    {
+    ) -> Receiver<DiffByteRecordsIterator> {
         let (sender, receiver) = bounded(1);
 
         let prim_key_columns_clone = primary_key_columns.clone();
@@ -216,7 +337,53 @@ impl CsvHashTaskSpawner for CsvHashTaskSpawnerStdThreads {
             );
         });
 
-        (self, receiver)
+        receiver
+    }
+}
+
+/// Runs the hashing tasks one after the other on the current thread, without spawning any thread,
+/// e.g. on `wasm32` targets or in environments, that don't allow spawning threads.
+///
+/// Both CSVs are read completely, before [`CsvByteDiff::diff`](crate::csv_diff::CsvByteDiff::diff) returns,
+/// and their hashed records are buffered until the returned iterator compares them (so the
+/// [`channel_capacity`](crate::csv_diff::CsvByteDiffBuilder::channel_capacity) has no effect).
+/// It doesn't depend on `rayon` or `crossbeam-utils`, so it can be used without the default features.
+#[derive(Debug, Default)]
+pub struct CsvHashTaskSpawnerSequential;
+
+impl CsvHashTaskSpawnerSequential {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CsvHashTaskSpawner for CsvHashTaskSpawnerSequential {
+    fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
+        &self,
+        csv_hash_task_sender_left: CsvHashTaskSenderWithRecycleReceiver<R>,
+        csv_hash_task_sender_right: CsvHashTaskSenderWithRecycleReceiver<R>,
+        csv_hash_receiver_comparer: CsvHashReceiverStreamComparer,
+        primary_key_columns: HashSet<usize>,
+    ) -> Receiver<DiffByteRecordsIterator> {
+        let (sender, receiver) = bounded(1);
+
+        Self::parse_hash_and_send_for_compare::<R, CsvParseResultLeft<CsvByteRecordWithHash>>(
+            csv_hash_task_sender_left,
+            primary_key_columns.clone(),
+        );
+        Self::parse_hash_and_send_for_compare::<R, CsvParseResultRight<CsvByteRecordWithHash>>(
+            csv_hash_task_sender_right,
+            primary_key_columns,
+        );
+        sender
+            .send(csv_hash_receiver_comparer.recv_hashes_and_compare())
+            .unwrap();
+
+        receiver
+    }
+
+    fn is_sequential(&self) -> bool {
+        true
     }
 }
 
@@ -241,6 +408,110 @@ impl CsvHashTaskSpawnerBuilder<CsvHashTaskSpawnerStdThreads>
     }
 }
 
+#[derive(Debug, Default)]
+pub struct CsvHashTaskSpawnerBuilderSequential;
+
+impl CsvHashTaskSpawnerBuilderSequential {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CsvHashTaskSpawnerBuilder<CsvHashTaskSpawnerSequential>
+    for CsvHashTaskSpawnerBuilderSequential
+{
+    fn build(self) -> CsvHashTaskSpawnerSequential {
+        CsvHashTaskSpawnerSequential::new()
+    }
+}
+
+/// Spawns the hashing and comparing tasks onto the blocking thread pool of a tokio runtime
+/// (see [`spawn_blocking`](https://docs.rs/tokio/1/tokio/runtime/struct.Handle.html#method.spawn_blocking)).
+///
+/// Available with feature `tokio-async`.
+#[derive(Debug)]
+#[cfg(feature = "tokio-async")]
+pub struct CsvHashTaskSpawnerTokio {
+    handle: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "tokio-async")]
+impl CsvHashTaskSpawnerTokio {
+    /// Spawn tasks onto the runtime of `handle`.
+    pub fn with_handle(handle: tokio::runtime::Handle) -> Self {
+        Self { handle }
+    }
+}
+
+#[cfg(feature = "tokio-async")]
+impl CsvHashTaskSpawner for CsvHashTaskSpawnerTokio {
+    fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
+        &self,
+        csv_hash_task_sender_left: CsvHashTaskSenderWithRecycleReceiver<R>,
+        csv_hash_task_sender_right: CsvHashTaskSenderWithRecycleReceiver<R>,
+        csv_hash_receiver_comparer: CsvHashReceiverStreamComparer,
+        primary_key_columns: HashSet<usize>,
+    ) -> Receiver<DiffByteRecordsIterator> {
+        let (sender, receiver) = bounded(1);
+
+        let prim_key_columns_clone = primary_key_columns.clone();
+
+        self.handle.spawn_blocking(move || {
+            sender
+                .send(csv_hash_receiver_comparer.recv_hashes_and_compare())
+                .unwrap();
+        });
+
+        self.handle.spawn_blocking(move || {
+            Self::parse_hash_and_send_for_compare::<R, CsvParseResultLeft<CsvByteRecordWithHash>>(
+                csv_hash_task_sender_left,
+                primary_key_columns,
+            );
+        });
+
+        self.handle.spawn_blocking(move || {
+            Self::parse_hash_and_send_for_compare::<R, CsvParseResultRight<CsvByteRecordWithHash>>(
+                csv_hash_task_sender_right,
+                prim_key_columns_clone,
+            );
+        });
+
+        receiver
+    }
+}
+
+/// Builds a [`CsvHashTaskSpawnerTokio`](CsvHashTaskSpawnerTokio).
+///
+/// Available with feature `tokio-async`.
+#[derive(Debug)]
+#[cfg(feature = "tokio-async")]
+pub struct CsvHashTaskSpawnerBuilderTokio {
+    handle: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "tokio-async")]
+impl CsvHashTaskSpawnerBuilderTokio {
+    /// Use the runtime, that is currently running.
+    ///
+    /// # Panics
+    /// Panics, when called outside of a tokio runtime.
+    pub fn current() -> Self {
+        Self::with_handle(tokio::runtime::Handle::current())
+    }
+
+    /// Use the runtime of `handle`.
+    pub fn with_handle(handle: tokio::runtime::Handle) -> Self {
+        Self { handle }
+    }
+}
+
+#[cfg(feature = "tokio-async")]
+impl CsvHashTaskSpawnerBuilder<CsvHashTaskSpawnerTokio> for CsvHashTaskSpawnerBuilderTokio {
+    fn build(self) -> CsvHashTaskSpawnerTokio {
+        CsvHashTaskSpawnerTokio::with_handle(self.handle)
+    }
+}
+
 pub trait CsvHashTaskSpawnerLocal {
     fn spawn_hashing_tasks_and_send_result<R: Read + Seek + Send>(
         &self,
@@ -255,19 +526,45 @@ pub trait CsvHashTaskSpawnerLocal {
     ) where
         R: Read + Seek + Send,
         P: CsvParseResult<CsvLeftRightParseResult<RecordHashWithPosition>, RecordHashWithPosition>,
+    {
+        Self::parse_hash_and_send_for_compare_in_chunks::<R, P>(
+            csv_hash_task_senders,
+            primary_key_columns,
+            &|chunk_hashing_task| chunk_hashing_task(),
+        );
+    }
+
+    /// Like [`parse_hash_and_send_for_compare`](Self::parse_hash_and_send_for_compare), but if chunked hashing is enabled,
+    /// the chunks of records are hashed by the tasks, that are spawned with `spawn_chunk_hashing_task`.
+    ///
+    /// Each task only hashes the records, that it is given, so it never waits for the parsing task,
+    /// no matter in which order the tasks are run.
+    fn parse_hash_and_send_for_compare_in_chunks<R, P>(
+        csv_hash_task_senders: CsvHashTaskLineSenders<R>,
+        primary_key_columns: &HashSet<usize>,
+        spawn_chunk_hashing_task: &dyn Fn(ChunkHashingTask),
+    ) where
+        R: Read + Seek + Send,
+        P: CsvParseResult<CsvLeftRightParseResult<RecordHashWithPosition>, RecordHashWithPosition>,
     {
         let mut csv_parser_hasher: CsvParserHasherLinesSender<
             CsvLeftRightParseResult<RecordHashWithPosition>,
         > = CsvParserHasherLinesSender::new(
             csv_hash_task_senders.sender,
             csv_hash_task_senders.sender_total_lines,
-        );
+        )
+        .primary_key_all_columns_except(csv_hash_task_senders.primary_key_excluded_columns)
+        .field_canonicalizer(csv_hash_task_senders.field_canonicalizer)
+        .hash_seed(csv_hash_task_senders.hash_seed)
+        .chunked(csv_hash_task_senders.is_chunked_hashing);
         csv_hash_task_senders
             .sender_csv_reader
-            .send(
-                csv_parser_hasher
-                    .parse_and_hash::<R, P>(csv_hash_task_senders.csv, primary_key_columns),
-            )
+            .send(csv_parser_hasher.parse_and_hash::<R, P>(
+                csv_hash_task_senders.csv,
+                primary_key_columns,
+                &csv_hash_task_senders.cancellation_token,
+                spawn_chunk_hashing_task,
+            ))
             .unwrap();
     }
 }
@@ -297,17 +594,25 @@ impl CsvHashTaskSpawnerLocal for CsvHashTaskSpawnerLocalRayon<'_> {
     {
         self.thread_scoper.scope(move |s| {
             s.spawn(move |inner_scope| {
-                inner_scope.spawn(move |_s1| {
-                    Self::parse_hash_and_send_for_compare::<
+                inner_scope.spawn(move |s1| {
+                    Self::parse_hash_and_send_for_compare_in_chunks::<
                         R,
                         CsvParseResultLeft<RecordHashWithPosition>,
-                    >(csv_hash_task_senders_left, primary_key_columns);
+                    >(
+                        csv_hash_task_senders_left,
+                        primary_key_columns,
+                        &|chunk_hashing_task| s1.spawn(move |_| chunk_hashing_task()),
+                    );
                 });
-                inner_scope.spawn(move |_s2| {
-                    Self::parse_hash_and_send_for_compare::<
+                inner_scope.spawn(move |s2| {
+                    Self::parse_hash_and_send_for_compare_in_chunks::<
                         R,
                         CsvParseResultRight<RecordHashWithPosition>,
-                    >(csv_hash_task_senders_right, primary_key_columns);
+                    >(
+                        csv_hash_task_senders_right,
+                        primary_key_columns,
+                        &|chunk_hashing_task| s2.spawn(move |_| chunk_hashing_task()),
+                    );
                 });
             });
         });
@@ -339,27 +644,91 @@ impl CsvHashTaskSpawnerLocal for CsvHashTaskSpawnerLocalCrossbeam {
     {
         self.thread_scoper.scope(move |s| {
             s.spawn(move |inner_scope| {
-                inner_scope.spawn(move |_s1| {
-                    Self::parse_hash_and_send_for_compare::<
+                inner_scope.spawn(move |s1| {
+                    Self::parse_hash_and_send_for_compare_in_chunks::<
                         R,
                         CsvParseResultLeft<RecordHashWithPosition>,
-                    >(csv_hash_task_senders_left, primary_key_columns);
+                    >(
+                        csv_hash_task_senders_left,
+                        primary_key_columns,
+                        &|chunk_hashing_task| {
+                            s1.spawn(move |_| chunk_hashing_task());
+                        },
+                    );
                 });
-                inner_scope.spawn(move |_s2| {
-                    Self::parse_hash_and_send_for_compare::<
+                inner_scope.spawn(move |s2| {
+                    Self::parse_hash_and_send_for_compare_in_chunks::<
                         R,
                         CsvParseResultRight<RecordHashWithPosition>,
-                    >(csv_hash_task_senders_right, primary_key_columns);
+                    >(
+                        csv_hash_task_senders_right,
+                        primary_key_columns,
+                        &|chunk_hashing_task| {
+                            s2.spawn(move |_| chunk_hashing_task());
+                        },
+                    );
                 });
             });
         });
     }
 }
 
+/// Hashes both CSVs one after the other on the current thread, without spawning any thread,
+/// e.g. on `wasm32` targets or in environments, that don't allow spawning threads
+/// (see [`CsvHashTaskSpawnerSequential`] for the lazy variant).
+///
+/// With [chunked hashing](crate::csv_diff::CsvByteDiffLocalBuilder::chunked_hashing), the chunks are hashed
+/// on the current thread as well.
+#[derive(Debug, Default)]
+pub struct CsvHashTaskSpawnerLocalSequential;
+
+impl CsvHashTaskSpawnerLocalSequential {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CsvHashTaskSpawnerLocal for CsvHashTaskSpawnerLocalSequential {
+    fn spawn_hashing_tasks_and_send_result<R>(
+        &self,
+        csv_hash_task_senders_left: CsvHashTaskLineSenders<R>,
+        csv_hash_task_senders_right: CsvHashTaskLineSenders<R>,
+        primary_key_columns: &HashSet<usize>,
+    ) where
+        R: Read + Seek + Send,
+    {
+        Self::parse_hash_and_send_for_compare::<R, CsvParseResultLeft<RecordHashWithPosition>>(
+            csv_hash_task_senders_left,
+            primary_key_columns,
+        );
+        Self::parse_hash_and_send_for_compare::<R, CsvParseResultRight<RecordHashWithPosition>>(
+            csv_hash_task_senders_right,
+            primary_key_columns,
+        );
+    }
+}
+
 pub trait CsvHashTaskSpawnerLocalBuilder<T> {
     fn build(self) -> T;
 }
 
+#[derive(Debug, Default)]
+pub struct CsvHashTaskSpawnerLocalBuilderSequential;
+
+impl CsvHashTaskSpawnerLocalBuilderSequential {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CsvHashTaskSpawnerLocalBuilder<CsvHashTaskSpawnerLocalSequential>
+    for CsvHashTaskSpawnerLocalBuilderSequential
+{
+    fn build(self) -> CsvHashTaskSpawnerLocalSequential {
+        CsvHashTaskSpawnerLocalSequential::new()
+    }
+}
+
 #[cfg(feature = "rayon-threads")]
 pub struct CsvHashTaskSpawnerLocalBuilderRayon<'tp> {
     thread_pool: &'tp rayon::ThreadPool,
@@ -1,3 +1,4 @@
+use ahash::RandomState;
 use std::hash::Hasher;
 use xxhash_rust::xxh3::{xxh3_128, Xxh3};
 
@@ -30,3 +31,17 @@ impl CsvHasherExt for csv::ByteRecord {
         xxh3_128(self.as_slice())
     }
 }
+
+/// The state of the hash maps, that are keyed by hashes, which have been seeded with `hash_seed`
+/// (or a random state, if there is no seed).
+pub(crate) fn random_state(hash_seed: Option<u64>) -> RandomState {
+    match hash_seed {
+        Some(hash_seed) => RandomState::with_seeds(
+            hash_seed,
+            hash_seed.rotate_left(16),
+            hash_seed.rotate_left(32),
+            hash_seed.rotate_left(48),
+        ),
+        None => RandomState::new(),
+    }
+}
@@ -1,16 +1,21 @@
 use crossbeam_channel::{Receiver, Sender};
 use csv::Reader;
 use std::collections::HashSet;
-use std::hash::Hasher;
 use std::io::{Read, Seek};
-use xxhash_rust::xxh3::{xxh3_128, Xxh3};
+use std::sync::Arc;
 
+use crate::cancellation::CancellationToken;
 use crate::csv::Csv;
-use crate::csv_hasher::CsvHasherExt;
+use crate::csv_diff::PrimaryKeyColumnOutOfBoundsError;
 use crate::csv_parse_result::{
     CsvByteRecordWithHash, CsvLeftRightParseResult, CsvParseResult, CsvParseResultLeft,
     CsvParseResultRight, Position, RecordHash, RecordHashWithPosition,
 };
+use crate::empty_csv::{CsvHeaderInfo, CsvReadAhead};
+use crate::error_policy::SkippedRecord;
+use crate::field_comparator::FieldCanonicalizer;
+use crate::key_stats::{KeyStats, KeyStatsCollector};
+use crate::simd::KeyHasher;
 
 impl<R> CsvParseResult<CsvLeftRightParseResult<R>, R> for CsvParseResultLeft<R> {
     #[inline]
@@ -38,9 +43,62 @@ impl<R> CsvParseResult<CsvLeftRightParseResult<R>, R> for CsvParseResultRight<R>
     }
 }
 
+/// The number of records, that are hashed together by a [`ChunkHashingTask`].
+const RECORD_CHUNK_LEN: usize = 4096;
+
+/// A task, that hashes a chunk of records of a CSV (see [`CsvParserHasherLinesSender::chunked`]).
+pub type ChunkHashingTask = Box<dyn FnOnce() + Send>;
+
+/// How to hash the records of a CSV, once its key columns have been resolved.
+struct RecordHasher {
+    fields_as_key: Vec<usize>,
+    field_canonicalizer: FieldCanonicalizer,
+    hash_seed: u64,
+}
+
+impl RecordHasher {
+    /// Hash `records` and send their hashes with `sender`.
+    ///
+    /// The records have been read with their positions, so that the lines are correct, no matter which task hashes them.
+    fn hash_chunk<
+        T: CsvParseResult<CsvLeftRightParseResult<RecordHashWithPosition>, RecordHashWithPosition>,
+    >(
+        &self,
+        records: Vec<csv::ByteRecord>,
+        sender: Sender<CsvLeftRightParseResult<RecordHashWithPosition>>,
+    ) {
+        let mut key_hasher = KeyHasher::with_seed(self.hash_seed);
+        let mut canonical_record = csv::ByteRecord::new();
+        for record in &records {
+            let (key, hash_record) = hash_key_and_record(
+                &mut key_hasher,
+                record,
+                &self.fields_as_key,
+                &self.field_canonicalizer,
+                &mut canonical_record,
+            );
+            let pos = record.position().expect("a record position");
+            sender
+                .send(
+                    T::new(RecordHashWithPosition::new(
+                        key,
+                        hash_record,
+                        Position::new(pos.byte(), pos.line()),
+                    ))
+                    .into_payload(),
+                )
+                .unwrap();
+        }
+    }
+}
+
 pub(crate) struct CsvParserHasherLinesSender<T> {
     sender: Sender<T>,
     sender_total_lines: Sender<u64>,
+    is_chunked: bool,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    field_canonicalizer: FieldCanonicalizer,
+    hash_seed: Option<u64>,
 }
 
 impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>> {
@@ -51,8 +109,41 @@ impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>>
         Self {
             sender,
             sender_total_lines,
+            is_chunked: false,
+            primary_key_excluded_columns: None,
+            field_canonicalizer: FieldCanonicalizer::default(),
+            hash_seed: None,
         }
     }
+
+    /// Use all columns except `primary_key_excluded_columns` as the primary key (if it is `Some`).
+    pub fn primary_key_all_columns_except(
+        mut self,
+        primary_key_excluded_columns: Option<HashSet<usize>>,
+    ) -> Self {
+        self.primary_key_excluded_columns = primary_key_excluded_columns;
+        self
+    }
+
+    /// Hash the canonical representation of the fields (see [`FieldCanonicalizer`]).
+    pub fn field_canonicalizer(mut self, field_canonicalizer: FieldCanonicalizer) -> Self {
+        self.field_canonicalizer = field_canonicalizer;
+        self
+    }
+
+    /// Seed the hashes of keys and records with `hash_seed` (if it is `Some`).
+    pub fn hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.hash_seed = hash_seed;
+        self
+    }
+
+    /// Instead of hashing the records while parsing them, split them into chunks, which are hashed by
+    /// [`ChunkHashingTask`]s, so that they can be hashed in parallel.
+    pub fn chunked(mut self, is_chunked: bool) -> Self {
+        self.is_chunked = is_chunked;
+        self
+    }
+
     pub fn parse_and_hash<
         R: Read + Seek + Send,
         T: CsvParseResult<CsvLeftRightParseResult<RecordHashWithPosition>, RecordHashWithPosition>,
@@ -60,13 +151,33 @@ impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>>
         &mut self,
         csv: Csv<R>,
         primary_key_columns: &HashSet<usize>,
-    ) -> csv::Result<csv::Reader<R>> {
+        cancellation_token: &CancellationToken,
+        spawn_chunk_hashing_task: &dyn Fn(ChunkHashingTask),
+    ) -> csv::Result<(csv::Reader<R>, CsvHeaderInfo, Vec<SkippedRecord>)> {
+        let read_ahead = CsvReadAhead::default()
+            .physical_lines(csv.physical_lines())
+            .line_offset(csv.line_offset())
+            .normalizer(csv.normalizer())
+            .error_policy(csv.error_policy());
+        #[cfg(feature = "simd-parser")]
+        let read_ahead = read_ahead.simd_parser(csv.simd_parser());
+        let mut read_ahead = read_ahead;
         let mut csv_reader: Reader<R> = csv.into_csv_reader();
         let mut csv_record = csv::ByteRecord::new();
+        let header_info = read_ahead.read_start(&mut csv_reader)?;
         // read first record in order to get the number of fields
-        if csv_reader.read_byte_record(&mut csv_record)? {
+        if read_ahead.read_byte_record(&mut csv_reader, &mut csv_record)? {
             let csv_record_first = std::mem::take(&mut csv_record);
-            let fields_as_key: Vec<_> = primary_key_columns.iter().copied().collect();
+            let primary_key_columns = &resolve_primary_key_columns(
+                primary_key_columns,
+                self.primary_key_excluded_columns.as_ref(),
+                header_info
+                    .headers()
+                    .map_or(csv_record_first.len(), csv::ByteRecord::len),
+            )?;
+            let mut fields_as_key: Vec<_> = primary_key_columns.iter().copied().collect();
+            // the key columns of both CSVs are resolved independently, so their sets might iterate in different orders
+            fields_as_key.sort_unstable();
             // TODO: maybe use this in order to only hash fields that are values and not act
             // as primary keys. We should probably only do this, if primary key field indices are
             // contiguous, because otherwise we will have multiple calls to our hashing function,
@@ -78,10 +189,50 @@ impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>>
 
             let record = csv_record_first;
             let key_fields_iter = fields_as_key.iter().filter_map(|k_idx| record.get(*k_idx));
-            if key_fields_iter.peekable().peek().is_some() {
-                let key = record.hash_key_fields(fields_as_key.as_slice());
+            let has_key = fields_as_key.is_empty() || key_fields_iter.peekable().peek().is_some();
+            if has_key && self.is_chunked {
+                let record_hasher = Arc::new(RecordHasher {
+                    fields_as_key,
+                    field_canonicalizer: self.field_canonicalizer.clone(),
+                    hash_seed: self.hash_seed.unwrap_or_default(),
+                });
+                let spawn_chunk = |records: Vec<csv::ByteRecord>| {
+                    let record_hasher = Arc::clone(&record_hasher);
+                    let sender = self.sender.clone();
+                    spawn_chunk_hashing_task(Box::new(move || {
+                        record_hasher.hash_chunk::<T>(records, sender)
+                    }));
+                };
+                let mut records = Vec::with_capacity(RECORD_CHUNK_LEN);
+                records.push(record);
+                let mut line = 2;
+                while !cancellation_token.should_stop()
+                    && read_ahead.read_byte_record(&mut csv_reader, &mut csv_record)?
+                {
+                    records.push(std::mem::take(&mut csv_record));
+                    if records.len() == RECORD_CHUNK_LEN {
+                        spawn_chunk(std::mem::replace(
+                            &mut records,
+                            Vec::with_capacity(RECORD_CHUNK_LEN),
+                        ));
+                    }
+                    line += 1;
+                }
+                if !records.is_empty() {
+                    spawn_chunk(records);
+                }
+                self.sender_total_lines.send(line).unwrap();
+            } else if has_key {
+                let mut key_hasher = KeyHasher::with_seed(self.hash_seed.unwrap_or_default());
+                let mut canonical_record = csv::ByteRecord::new();
                 // TODO: don't hash all of it -> exclude the key fields (see below)
-                let hash_record = record.hash_record();
+                let (key, hash_record) = hash_key_and_record(
+                    &mut key_hasher,
+                    &record,
+                    &fields_as_key,
+                    &self.field_canonicalizer,
+                    &mut canonical_record,
+                );
                 let pos = record.position().expect("a record position");
                 self.sender
                     .send(
@@ -94,9 +245,16 @@ impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>>
                     )
                     .unwrap();
                 let mut line = 2;
-                while csv_reader.read_byte_record(&mut csv_record)? {
-                    let key = csv_record.hash_key_fields(fields_as_key.as_slice());
-                    let hash_record = csv_record.hash_record();
+                while !cancellation_token.should_stop()
+                    && read_ahead.read_byte_record(&mut csv_reader, &mut csv_record)?
+                {
+                    let (key, hash_record) = hash_key_and_record(
+                        &mut key_hasher,
+                        &csv_record,
+                        &fields_as_key,
+                        &self.field_canonicalizer,
+                        &mut canonical_record,
+                    );
                     {
                         let pos = csv_record.position().expect("a record position");
                         self.sender
@@ -117,18 +275,59 @@ impl CsvParserHasherLinesSender<CsvLeftRightParseResult<RecordHashWithPosition>>
         } else {
             self.sender_total_lines.send(0).unwrap();
         }
-        Ok(csv_reader)
+        #[cfg(feature = "simd-parser")]
+        read_ahead.rewind_for_seeking(&mut csv_reader)?;
+        Ok((csv_reader, header_info, read_ahead.take_skipped_records()))
     }
 }
 
 pub(crate) struct CsvParserHasherSender<T> {
     sender: Sender<T>,
+    is_canonical_column_order: bool,
+    primary_key_excluded_columns: Option<HashSet<usize>>,
+    field_canonicalizer: FieldCanonicalizer,
+    hash_seed: Option<u64>,
 }
 
 impl CsvParserHasherSender<CsvLeftRightParseResult<CsvByteRecordWithHash>> {
     pub fn new(sender: Sender<CsvLeftRightParseResult<CsvByteRecordWithHash>>) -> Self {
-        Self { sender }
+        Self {
+            sender,
+            is_canonical_column_order: false,
+            primary_key_excluded_columns: None,
+            field_canonicalizer: FieldCanonicalizer::default(),
+            hash_seed: None,
+        }
     }
+
+    /// Put the columns into canonical order (sorted by header name) before hashing.
+    pub fn canonical_column_order(mut self, is_canonical_column_order: bool) -> Self {
+        self.is_canonical_column_order = is_canonical_column_order;
+        self
+    }
+
+    /// Use all columns except `primary_key_excluded_columns` as the primary key (if it is `Some`).
+    pub fn primary_key_all_columns_except(
+        mut self,
+        primary_key_excluded_columns: Option<HashSet<usize>>,
+    ) -> Self {
+        self.primary_key_excluded_columns = primary_key_excluded_columns;
+        self
+    }
+
+    /// Hash the canonical representation of the fields (see [`FieldCanonicalizer`]).
+    pub fn field_canonicalizer(mut self, field_canonicalizer: FieldCanonicalizer) -> Self {
+        self.field_canonicalizer = field_canonicalizer;
+        self
+    }
+
+    /// Seed the hashes of keys and records with `hash_seed` (if it is `Some`).
+    pub fn hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.hash_seed = hash_seed;
+        self
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn parse_and_hash<
         R: Read + Send,
         T: CsvParseResult<CsvLeftRightParseResult<CsvByteRecordWithHash>, CsvByteRecordWithHash>,
@@ -137,14 +336,52 @@ impl CsvParserHasherSender<CsvLeftRightParseResult<CsvByteRecordWithHash>> {
         csv: Csv<R>,
         primary_key_columns: &HashSet<usize>,
         receiver_csv_recycle: Receiver<csv::ByteRecord>,
+        sender_header_info: Sender<CsvHeaderInfo>,
+        sender_key_stats: Sender<KeyStats>,
+        sender_skipped_records: Sender<Vec<SkippedRecord>>,
+        cancellation_token: &CancellationToken,
     ) {
+        let mut key_stats_collector = KeyStatsCollector::new(primary_key_columns);
+        let read_ahead = CsvReadAhead::default()
+            .canonical_column_order(self.is_canonical_column_order)
+            .physical_lines(csv.physical_lines())
+            .line_offset(csv.line_offset())
+            .normalizer(csv.normalizer())
+            .error_policy(csv.error_policy());
+        #[cfg(feature = "simd-parser")]
+        let read_ahead = read_ahead.simd_parser(csv.simd_parser());
+        let mut read_ahead = read_ahead;
         let mut csv_reader: Reader<R> = csv.into_csv_reader();
         let mut csv_record = csv::ByteRecord::new();
-        // read first record in order to get the number of fields
-        match csv_reader.read_byte_record(&mut csv_record) {
-            Ok(true) => {
+        let mut num_of_header_fields = None;
+        // read first record in order to get the number of fields, which the primary key columns are resolved with
+        let first_record_read = read_ahead
+            .read_start(&mut csv_reader)
+            .and_then(|header_info| {
+                num_of_header_fields = header_info.headers().map(csv::ByteRecord::len);
+                // we ignore any sending errors - the receiver is only interested in it for the run report
+                let _ = sender_header_info.send(header_info);
+                read_ahead.read_byte_record(&mut csv_reader, &mut csv_record)
+            })
+            .and_then(|is_read| {
+                if !is_read {
+                    return Ok(None);
+                }
+                Ok(Some(resolve_primary_key_columns(
+                    primary_key_columns,
+                    self.primary_key_excluded_columns.as_ref(),
+                    num_of_header_fields.unwrap_or(csv_record.len()),
+                )?))
+            });
+        drop(sender_header_info);
+        match first_record_read {
+            Ok(Some(primary_key_columns)) => {
                 let record = std::mem::take(&mut csv_record);
-                let fields_as_key: Vec<_> = primary_key_columns.iter().copied().collect();
+                let primary_key_columns = &primary_key_columns;
+                key_stats_collector = KeyStatsCollector::new(primary_key_columns);
+                let mut fields_as_key: Vec<_> = primary_key_columns.iter().copied().collect();
+                // the key columns of both CSVs are resolved independently, so their sets might iterate in different orders
+                fields_as_key.sort_unstable();
                 // TODO: maybe use this in order to only hash fields that are values and not act
                 // as primary keys. We should probably only do this, if primary key field indices are
                 // contiguous, because otherwise we will have multiple calls to our hashing function,
@@ -154,19 +391,22 @@ impl CsvParserHasherSender<CsvLeftRightParseResult<CsvByteRecordWithHash>> {
                 //     .filter(|x| !primary_key_columns.contains(x))
                 //     .collect();
 
-                let mut hasher = Xxh3::new();
+                let mut key_hasher = KeyHasher::with_seed(self.hash_seed.unwrap_or_default());
+                let mut canonical_record = csv::ByteRecord::new();
                 let mut key_fields_iter = fields_as_key
                     .iter()
                     .filter_map(|k_idx| record.get(*k_idx))
                     .peekable();
-                if key_fields_iter.peek().is_some() {
-                    // TODO: try to do it with as few calls to `write` as possible (see below)
-                    for key_field in key_fields_iter {
-                        hasher.write(key_field);
-                    }
-                    let key = hasher.digest128();
+                if fields_as_key.is_empty() || key_fields_iter.peek().is_some() {
                     // TODO: don't hash all of it -> exclude the key fields (see below)
-                    let hash_record = xxh3_128(record.as_slice());
+                    let (key, hash_record) = hash_key_and_record(
+                        &mut key_hasher,
+                        &record,
+                        &fields_as_key,
+                        &self.field_canonicalizer,
+                        &mut canonical_record,
+                    );
+                    key_stats_collector.record(&record);
                     // we ignore any sending errors
                     let _ = self.sender.send(
                         T::new(CsvByteRecordWithHash::new(
@@ -176,26 +416,24 @@ impl CsvParserHasherSender<CsvLeftRightParseResult<CsvByteRecordWithHash>> {
                         .into_payload(),
                     );
 
-                    loop {
+                    while !cancellation_token.should_stop() {
                         let mut csv_record = receiver_csv_recycle
                             .try_recv()
                             .unwrap_or_else(|_| csv::ByteRecord::new());
 
-                        match csv_reader.read_byte_record(&mut csv_record) {
+                        match read_ahead.read_byte_record(&mut csv_reader, &mut csv_record) {
                             Ok(true) => {
-                                hasher.reset();
-                                let key_fields = fields_as_key
-                                    .iter()
-                                    .filter_map(|k_idx| csv_record.get(*k_idx));
-                                // TODO: try to do it with as few calls to `write` as possible (see below)
-                                for key_field in key_fields {
-                                    hasher.write(key_field);
-                                }
-                                let key = hasher.digest128();
                                 // TODO: don't hash all of it -> exclude the key fields
                                 // in order to still be efficient and do as few `write` calls as possible
                                 // consider using `csv_record.range(...)` method
-                                let hash_record = xxh3_128(csv_record.as_slice());
+                                let (key, hash_record) = hash_key_and_record(
+                                    &mut key_hasher,
+                                    &csv_record,
+                                    &fields_as_key,
+                                    &self.field_canonicalizer,
+                                    &mut canonical_record,
+                                );
+                                key_stats_collector.record(&csv_record);
                                 if self
                                     .sender
                                     .send(
@@ -233,18 +471,85 @@ impl CsvParserHasherSender<CsvLeftRightParseResult<CsvByteRecordWithHash>> {
                     }
                 }
             }
-            Ok(false) => { /* Do nothing, we have reached EOF */ }
-            Err(e) => self
-                .sender
-                .send(
+            Ok(None) => { /* Do nothing, we have reached EOF */ }
+            Err(e) => {
+                // we ignore any sending errors - the receiver might have stopped comparing already
+                let _ = self.sender.send(
                     T::new(CsvByteRecordWithHash::new(Err(e), RecordHash::new(0, 0)))
                         .into_payload(),
-                )
-                .unwrap(),
+                );
+            }
         }
+        // we ignore any sending errors - the receiver is only interested in it for the run report
+        let _ = sender_key_stats.send(key_stats_collector.finish());
+        let _ = sender_skipped_records.send(read_ahead.take_skipped_records());
     }
 }
 
+/// Return the primary key columns of a CSV with `num_of_fields` fields (of its header or, without header, of its first record):
+/// if columns are excluded from the primary key, all other columns act as the primary key.
+///
+/// Fails with the smallest primary key column, that is out of bounds of the CSV.
+fn resolve_primary_key_columns(
+    primary_key_columns: &HashSet<usize>,
+    primary_key_excluded_columns: Option<&HashSet<usize>>,
+    num_of_fields: usize,
+) -> Result<HashSet<usize>, PrimaryKeyColumnOutOfBoundsError> {
+    match primary_key_excluded_columns {
+        Some(excluded_columns) => Ok((0..num_of_fields)
+            .filter(|idx| !excluded_columns.contains(idx))
+            .collect()),
+        None => match primary_key_columns
+            .iter()
+            .copied()
+            .filter(|&column| column >= num_of_fields)
+            .min()
+        {
+            Some(column) => Err(PrimaryKeyColumnOutOfBoundsError::new(column, num_of_fields)),
+            None => Ok(primary_key_columns.clone()),
+        },
+    }
+}
+
+/// Return the hash of the key of `record`. Without any key fields, the whole record acts as the key,
+/// so records only ever match, when they are equal.
+#[inline]
+fn key_of(
+    key_hasher: &mut KeyHasher,
+    record: &csv::ByteRecord,
+    fields_as_key: &[usize],
+    hash_record: u128,
+) -> u128 {
+    if fields_as_key.is_empty() {
+        hash_record
+    } else {
+        key_hasher.hash_key_fields(record, fields_as_key)
+    }
+}
+
+/// Return the key (see [`key_of`]) and the hash of `record`. The canonical representation of the fields is hashed
+/// (via `canonical_record`), so that different representations of the same value are equal.
+#[inline]
+fn hash_key_and_record(
+    key_hasher: &mut KeyHasher,
+    record: &csv::ByteRecord,
+    fields_as_key: &[usize],
+    field_canonicalizer: &FieldCanonicalizer,
+    canonical_record: &mut csv::ByteRecord,
+) -> (u128, u128) {
+    let record = if field_canonicalizer.is_empty() {
+        record
+    } else {
+        field_canonicalizer.canonicalize(record, canonical_record);
+        canonical_record
+    };
+    let hash_record = key_hasher.hash_record(record);
+    (
+        key_of(key_hasher, record, fields_as_key, hash_record),
+        hash_record,
+    )
+}
+
 #[derive(Debug)]
 pub(crate) enum HashMapValue<T, TEq = T> {
     Initial(u128, T),
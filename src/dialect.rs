@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+
+/// The default number of bytes at the start of CSV data, that are inspected in order to detect its [`Dialect`] (8 KiB).
+pub const DEFAULT_SNIFF_LEN: usize = 8 * 1024;
+
+/// The delimiters, that are considered when sniffing, in the order of their precedence.
+const DELIMITERS: [u8; 5] = *b",;\t|:";
+
+/// The quote characters, that are considered when sniffing, in the order of their precedence.
+const QUOTES: [u8; 2] = *b"\"'";
+
+/// The delimiter and quote character of CSV data.
+///
+/// It can be set explicitly with [`CsvBuilder::dialect`](crate::csv::CsvBuilder::dialect) or detected from
+/// the start of the data with [`Dialect::sniff`] (see [`Csv::with_reader_sniffed`](crate::csv::Csv::with_reader_sniffed)).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Dialect {
+    delimiter: u8,
+    quote: u8,
+}
+
+impl Default for Dialect {
+    /// A comma as delimiter and a double quote as quote character.
+    fn default() -> Self {
+        Self::new(b',', b'"')
+    }
+}
+
+impl Dialect {
+    pub fn new(delimiter: u8, quote: u8) -> Self {
+        Self { delimiter, quote }
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    pub fn quote(&self) -> u8 {
+        self.quote
+    }
+
+    /// Detect the dialect of the CSV data, that starts with `sample`.
+    ///
+    /// The delimiter is one of `,`, `;`, tab, `|` and `:` - the one, that splits most lines into the same number
+    /// (greater than one) of fields. The quote character is either `"` or `'`, depending on which of them
+    /// encloses more fields. When there is no evidence for anything else, the [default](Dialect::default) is returned.
+    /// The last line of `sample` is ignored, when it doesn't end with a line break, since it may be cut off.
+    pub fn sniff(sample: &[u8]) -> Self {
+        let quote = sniff_quote(sample);
+        let delimiter = DELIMITERS
+            .iter()
+            .rev()
+            .filter_map(|&delimiter| {
+                score_delimiter(sample, delimiter, quote).map(|score| (score, delimiter))
+            })
+            // `max_by_key` returns the last maximum, which is the first one in the order of precedence
+            .max_by_key(|&(score, _)| score)
+            .map_or(Self::default().delimiter, |(_, delimiter)| delimiter);
+        Self::new(delimiter, quote)
+    }
+
+    /// Read up to `sniff_len` bytes from `reader` and [detect](Dialect::sniff) the dialect of them.
+    ///
+    /// The bytes, that have been read, are returned as well, so that they can be chained with the rest of `reader`.
+    pub(crate) fn sniff_reader<R: Read>(
+        reader: &mut R,
+        sniff_len: usize,
+    ) -> io::Result<(Self, Vec<u8>)> {
+        let mut sample = Vec::new();
+        reader
+            .by_ref()
+            .take(u64::try_from(sniff_len).unwrap_or(u64::MAX))
+            .read_to_end(&mut sample)?;
+        Ok((Self::sniff(&sample), sample))
+    }
+}
+
+/// Count the fields, that are enclosed in `quote`, i.e. where `quote` is next to a delimiter or a line boundary.
+fn sniff_quote(sample: &[u8]) -> u8 {
+    let is_boundary = |byte: Option<&u8>| {
+        byte.map_or(true, |byte| {
+            DELIMITERS.contains(byte) || *byte == b'\n' || *byte == b'\r'
+        })
+    };
+    let count_enclosed = |quote: u8| {
+        sample
+            .iter()
+            .enumerate()
+            .filter(|&(i, &byte)| {
+                byte == quote
+                    && (is_boundary(i.checked_sub(1).and_then(|prev| sample.get(prev)))
+                        || is_boundary(sample.get(i + 1)))
+            })
+            .count()
+    };
+    QUOTES
+        .iter()
+        .rev()
+        .map(|&quote| (count_enclosed(quote), quote))
+        .max_by_key(|&(count, _)| count)
+        .filter(|&(count, _)| count > 0)
+        .map_or(Dialect::default().quote, |(_, quote)| quote)
+}
+
+/// The number of records with the most common number of fields and that number of fields, or `None`,
+/// when `delimiter` doesn't split records into more than one field.
+fn score_delimiter(sample: &[u8], delimiter: u8, quote: u8) -> Option<(usize, usize)> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .quote(quote)
+        .from_reader(sample);
+    let mut field_counts: Vec<usize> = csv_reader
+        .byte_records()
+        .map_while(Result::ok)
+        .map(|record| record.len())
+        .collect();
+    if field_counts.len() > 1 && !sample.ends_with(b"\n") {
+        field_counts.pop();
+    }
+    let mut frequencies = BTreeMap::new();
+    for num_fields in field_counts {
+        *frequencies.entry(num_fields).or_insert(0) += 1;
+    }
+    frequencies
+        .into_iter()
+        .map(|(num_fields, frequency)| (frequency, num_fields))
+        .max()
+        .filter(|&(_, num_fields)| num_fields > 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sniff_semicolon_with_single_quotes() {
+        let sample = b"id;name;note\n1;'lemon';'sour, yellow'\n2;'cherry';'red, sweet'\n3;'ban";
+
+        assert_eq!(Dialect::sniff(sample), Dialect::new(b';', b'\''));
+    }
+
+    #[test]
+    fn sniff_tab_with_commas_in_fields() {
+        let sample = b"id\tname\tnote\n1\tlemon\tsour, yellow\n2\tcherry\t\"red, sweet\"\n";
+
+        assert_eq!(Dialect::sniff(sample), Dialect::new(b'\t', b'"'));
+    }
+
+    #[test]
+    fn sniff_single_column_is_default() {
+        assert_eq!(Dialect::sniff(b"id\n1\n2\n"), Dialect::default());
+        assert_eq!(Dialect::sniff(b""), Dialect::default());
+    }
+}
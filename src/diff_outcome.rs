@@ -0,0 +1,270 @@
+use crate::diff_result::{DiffByteRecords, DiffByteRecordsIterator};
+use crate::diff_row::DiffByteRecord;
+
+/// The overall outcome of comparing two CSVs - either they are identical, different or the comparison has failed.
+///
+/// This maps naturally to process exit codes (see [`exit_code`](DiffOutcome::exit_code)) and is meant
+/// as a convenience for scripts and CI steps, that only need to know _whether_ two CSVs are different.
+///
+/// A `DiffOutcome` can be created from the result of [`CsvByteDiffLocal::diff`](crate::csv_diff::CsvByteDiffLocal::diff),
+/// from [`DiffByteRecords`](crate::diff_result::DiffByteRecords) or from a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator).
+/// For the latter, [`DiffOutcome::short_circuit`](DiffOutcome::short_circuit) stops the comparison at the first difference.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::diff_outcome::DiffOutcome;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,nut";
+
+let outcome = DiffOutcome::from(CsvByteDiff::new()?.diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+));
+
+assert!(outcome.is_different());
+assert_eq!(outcome.exit_code(), 1);
+if let DiffOutcome::Different { summary } = outcome {
+    assert_eq!(summary.modified(), 1);
+}
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub enum DiffOutcome {
+    /// No differences have been found.
+    Identical,
+    /// At least one difference has been found.
+    Different { summary: DiffSummary },
+    /// The comparison has failed (e.g. because a CSV could not be parsed).
+    Errored { error: csv::Error },
+}
+
+impl DiffOutcome {
+    /// Consume `diff_records` until the first difference or error occurs.
+    ///
+    /// In contrast to [`DiffOutcome::from`](DiffOutcome::from), the [`summary`](DiffOutcome::Different::summary)
+    /// only contains this first difference. When used with a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator),
+    /// the remaining comparison is stopped as soon as the iterator is dropped.
+    pub fn short_circuit<I>(diff_records: I) -> Self
+    where
+        I: IntoIterator<Item = csv::Result<DiffByteRecord>>,
+    {
+        match diff_records.into_iter().next() {
+            None => Self::Identical,
+            Some(Ok(diff_record)) => {
+                let mut summary = DiffSummary::default();
+                summary.record(&diff_record);
+                Self::Different { summary }
+            }
+            Some(Err(error)) => Self::Errored { error },
+        }
+    }
+
+    pub fn is_identical(&self) -> bool {
+        matches!(self, Self::Identical)
+    }
+
+    pub fn is_different(&self) -> bool {
+        matches!(self, Self::Different { .. })
+    }
+
+    pub fn is_errored(&self) -> bool {
+        matches!(self, Self::Errored { .. })
+    }
+
+    /// Return the exit code of this outcome, following the convention of `diff` and `cmp`:
+    /// `0` if identical, `1` if different and `2` if errored.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Identical => 0,
+            Self::Different { .. } => 1,
+            Self::Errored { .. } => 2,
+        }
+    }
+}
+
+impl From<&DiffByteRecords> for DiffOutcome {
+    fn from(diff_byte_records: &DiffByteRecords) -> Self {
+        let summary = diff_byte_records.iter().collect::<DiffSummary>();
+        if summary.total() == 0 {
+            Self::Identical
+        } else {
+            Self::Different { summary }
+        }
+    }
+}
+
+impl From<DiffByteRecords> for DiffOutcome {
+    fn from(diff_byte_records: DiffByteRecords) -> Self {
+        Self::from(&diff_byte_records)
+    }
+}
+
+impl From<csv::Result<DiffByteRecords>> for DiffOutcome {
+    fn from(diff_byte_records: csv::Result<DiffByteRecords>) -> Self {
+        match diff_byte_records {
+            Ok(diff_byte_records) => Self::from(&diff_byte_records),
+            Err(error) => Self::Errored { error },
+        }
+    }
+}
+
+/// Consumes the iterator completely (or until the first error occurs).
+impl From<DiffByteRecordsIterator> for DiffOutcome {
+    fn from(diff_byte_records_iter: DiffByteRecordsIterator) -> Self {
+        let mut summary = DiffSummary::default();
+        for diff_record in diff_byte_records_iter {
+            match diff_record {
+                Ok(diff_record) => summary.record(&diff_record),
+                Err(error) => return Self::Errored { error },
+            }
+        }
+        if summary.total() == 0 {
+            Self::Identical
+        } else {
+            Self::Different { summary }
+        }
+    }
+}
+
+/// The number of differences by kind, see [`DiffOutcome::Different`](DiffOutcome::Different).
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct DiffSummary {
+    added: u64,
+    deleted: u64,
+    modified: u64,
+    moved: u64,
+}
+
+impl DiffSummary {
+    pub fn added(&self) -> u64 {
+        self.added
+    }
+
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    pub fn modified(&self) -> u64 {
+        self.modified
+    }
+
+    /// Number of `Move`s, which are only detected by [`CsvByteDiff::diff_with_moves`](crate::csv_diff::CsvByteDiff::diff_with_moves).
+    pub fn moved(&self) -> u64 {
+        self.moved
+    }
+
+    /// Total number of differences.
+    pub fn total(&self) -> u64 {
+        self.added + self.deleted + self.modified + self.moved
+    }
+
+    fn record(&mut self, diff_record: &DiffByteRecord) {
+        match diff_record {
+            DiffByteRecord::Add(..) => self.added += 1,
+            DiffByteRecord::Modify { .. } => self.modified += 1,
+            DiffByteRecord::Delete(..) => self.deleted += 1,
+            DiffByteRecord::Move { .. } => self.moved += 1,
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a DiffByteRecord> for DiffSummary {
+    fn from_iter<T: IntoIterator<Item = &'a DiffByteRecord>>(iter: T) -> Self {
+        let mut summary = Self::default();
+        for diff_record in iter {
+            summary.record(diff_record);
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+    use std::io;
+
+    fn add() -> DiffByteRecord {
+        DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a", "b"]),
+            2,
+        ))
+    }
+
+    fn delete() -> DiffByteRecord {
+        DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["c", "d"]),
+            3,
+        ))
+    }
+
+    #[test]
+    fn from_empty_diff_byte_records_is_identical() {
+        let outcome = DiffOutcome::from(DiffByteRecords::from(vec![]));
+
+        assert!(outcome.is_identical());
+        assert_eq!(outcome.exit_code(), 0);
+    }
+
+    #[test]
+    fn from_diff_byte_records_is_different_with_summary() {
+        let outcome = DiffOutcome::from(DiffByteRecords::from(vec![add(), delete(), add()]));
+
+        match outcome {
+            DiffOutcome::Different { summary } => {
+                assert_eq!(summary.added(), 2);
+                assert_eq!(summary.deleted(), 1);
+                assert_eq!(summary.modified(), 0);
+                assert_eq!(summary.total(), 3);
+            }
+            outcome => panic!("expected `Different`, got {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn from_err_is_errored() {
+        let outcome = DiffOutcome::from(Err::<DiffByteRecords, _>(csv::Error::from(
+            io::Error::new(io::ErrorKind::Other, "broken"),
+        )));
+
+        assert!(outcome.is_errored());
+        assert_eq!(outcome.exit_code(), 2);
+    }
+
+    #[test]
+    fn short_circuit_stops_at_first_difference() {
+        let diff_records = vec![
+            Ok(add()),
+            Err(csv::Error::from(io::Error::new(
+                io::ErrorKind::Other,
+                "never reached",
+            ))),
+        ];
+
+        let outcome = DiffOutcome::short_circuit(diff_records);
+
+        match outcome {
+            DiffOutcome::Different { summary } => assert_eq!(summary.total(), 1),
+            outcome => panic!("expected `Different`, got {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn short_circuit_empty_is_identical() {
+        assert!(DiffOutcome::short_circuit(std::iter::empty()).is_identical());
+    }
+}
@@ -1,14 +1,32 @@
+use crate::simd::modified_field_indices;
 use crate::{
+    cancellation::{CancellationToken, CancelledError, TimedOutError},
+    column_stats::ColumnModificationFrequencies,
+    csv_hasher::random_state,
     csv_parse_result::{CsvByteRecordWithHash, CsvLeftRightParseResult, Position, RecordHash},
     csv_parser_hasher::HashMapValue,
     diff_row::*,
+    empty_csv::{CsvHeaderInfo, CsvSide, EmptyCsvSemantics},
+    error_code::ErrorCode,
+    error_policy::{SkippedRecord, SkippedRecords},
+    field_comparator::FieldComparators,
+    flush_threshold::FlushThreshold,
+    key_stats::KeyStats,
+    metrics::{DiffMetricsSink, NoDiffMetrics},
+    run_report::DiffRunReport,
+    severity::{DiffByteRecordsBySeverity, Severity, SeverityClassifiedIterator},
+    spill::{self, RecordSpill},
+    task_panic::{catch_panic, TaskPanicError},
 };
 use ahash::AHashMap as HashMap;
 use crossbeam_channel::{Receiver, Sender};
 use std::{
-    cmp::{max, Ordering},
-    collections::{hash_map::IntoIter, VecDeque},
-    convert::{TryFrom, TryInto},
+    cmp::{max, min, Ordering},
+    collections::{hash_map::IntoIter, BTreeMap, VecDeque},
+    convert::TryFrom,
+    io,
+    iter::FusedIterator,
+    num::NonZeroU64,
 };
 use thiserror::Error;
 
@@ -21,81 +39,89 @@ use thiserror::Error;
 ///
 /// See the example on [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal) for general usage.
 #[derive(Debug, PartialEq, Clone)]
-pub struct DiffByteRecords(pub(crate) Vec<DiffByteRecord>);
+pub struct DiffByteRecords(pub(crate) Vec<DiffByteRecord>, pub(crate) DiffHeaders);
 
 impl DiffByteRecords {
+    /// Return the header of the left CSV or, if the left CSV has no header (e.g. because it is empty),
+    /// the header of the right CSV.
+    ///
+    /// Fields of the header are in the same order as the fields of the records
+    /// (see [`CsvBuilder::canonical_column_order`](crate::csv::CsvBuilder::canonical_column_order)).
+    /// It is `None`, if both CSVs are read without headers or are empty.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit";
+let csv_right = "\
+id,name,color\n\
+1,lemon,yellow";
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek(csv_left.as_bytes()),
+    Csv::with_reader_seek(csv_right.as_bytes()),
+)?;
+
+// no need to read the header rows of the CSVs again after comparing them
+assert_eq!(
+    diff_byte_records.headers(),
+    Some(&csv::ByteRecord::from(vec!["id", "name", "kind"]))
+);
+assert_eq!(
+    diff_byte_records.headers_right(),
+    Some(&csv::ByteRecord::from(vec!["id", "name", "color"]))
+);
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn headers(&self) -> Option<&csv::ByteRecord> {
+        self.1.headers()
+    }
+
+    /// Return the header of the left CSV, if it is read with headers and is not empty.
+    pub fn headers_left(&self) -> Option<&csv::ByteRecord> {
+        self.1.left.as_ref()
+    }
+
+    /// Return the header of the right CSV, if it is read with headers and is not empty.
+    pub fn headers_right(&self) -> Option<&csv::ByteRecord> {
+        self.1.right.as_ref()
+    }
+
     /// Sort the underlying [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s by line.
     ///
     /// Note that comparison is done in parallel. Therefore, __without calling this method__, the resulting `DiffByteRecord`s are out of order
     /// after the comparison (with regard to their line in the original CSV).
+    ///
+    /// The resulting order only depends on the lines of the records, so it is the same for every comparison of the same CSVs
+    /// (see [`page`](DiffByteRecords::page)).
+    ///
+    /// With the Cargo feature `rayon-threads` (enabled by default), the records are sorted in parallel
+    /// on the global rayon thread-pool.
     pub fn sort_by_line(&mut self) {
-        self.0.sort_by(|a, b| match (a.line_num(), b.line_num()) {
-            (LineNum::OneSide(line_num_a), LineNum::OneSide(line_num_b)) => line_num_a
-                .cmp(&line_num_b)
-                .then(if matches!(a, DiffByteRecord::Delete(..)) {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }),
-            (
-                LineNum::OneSide(line_num_a),
-                LineNum::BothSides {
-                    for_deleted,
-                    for_added,
-                },
-            ) => line_num_a
-                .cmp(if for_deleted < for_added {
-                    &for_deleted
-                } else {
-                    &for_added
-                })
-                .then(if matches!(a, DiffByteRecord::Delete(..)) {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }),
-            (
-                LineNum::BothSides {
-                    for_deleted,
-                    for_added,
-                },
-                LineNum::OneSide(line_num_b),
-            ) => if for_deleted < for_added {
-                &for_deleted
-            } else {
-                &for_added
-            }
-            .cmp(&line_num_b)
-            .then(if matches!(b, DiffByteRecord::Add(..)) {
-                Ordering::Less
-            } else {
-                Ordering::Greater
-            }),
-            (
-                LineNum::BothSides {
-                    for_deleted: for_deleted_a,
-                    for_added: for_added_a,
-                },
-                LineNum::BothSides {
-                    for_deleted: for_deleted_b,
-                    for_added: for_added_b,
-                },
-            ) => if for_deleted_a < for_added_a {
-                &for_deleted_a
-            } else {
-                &for_added_a
-            }
-            .cmp(if for_deleted_b < for_added_b {
-                &for_deleted_b
-            } else {
-                &for_added_b
-            }),
-        })
+        #[cfg(feature = "rayon-threads")]
+        {
+            use rayon::slice::ParallelSliceMut;
+            // sorting tens of millions of records sequentially can take longer than comparing the CSVs
+            self.0.par_sort_by(cmp_by_line);
+        }
+        #[cfg(not(feature = "rayon-threads"))]
+        self.0.sort_by(cmp_by_line);
     }
 
-    // TODO: in the future, we might want to have something like Result<(), Vec<ColumnIdxError>> as a return value,
-    // so that we can report _all_ the errors that happened and not only the first one
-    pub fn sort_by_columns<E: Into<ColumnIdx>, I: IntoIterator<Item = E>>(
+    /// Sort the `DiffByteRecord`s by the values in the given columns (see [`SortColumn`] on how values are compared).
+    ///
+    /// Columns, that are out of bounds for a record, are skipped, when comparing that record, so the records are
+    /// still sorted by all other columns. Only the first [`ColumnIdxError`], that is encountered during the sort,
+    /// is returned (see [`sort_by_columns_all_errors`](DiffByteRecords::sort_by_columns_all_errors) for all of them).
+    pub fn sort_by_columns<E: Into<SortColumn>, I: IntoIterator<Item = E>>(
         &mut self,
         cols: I,
     ) -> Result<(), ColumnIdxError> {
@@ -108,11 +134,11 @@ impl DiffByteRecords {
                     .find_map(|col_idx| {
                         match (add_l, add_r)
                             .cmp_by_col(col_idx)
-                            .map(|ord| (!ord.is_eq()).then(|| ord))
+                            .map(|ord| ord.is_ne().then_some(ord))
                         {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -135,12 +161,12 @@ impl DiffByteRecords {
                             .and_then(|ord| match ord {
                                 Ordering::Equal => (left, mod_add)
                                     .cmp_by_col(col_idx)
-                                    .map(|ord| (!ord.is_eq()).then(|| ord)),
+                                    .map(|ord| ord.is_ne().then_some(ord)),
                                 _ => Ok(Some(ord)),
                             }) {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -154,11 +180,11 @@ impl DiffByteRecords {
                     .find_map(|col_idx| {
                         match (add, del)
                             .cmp_by_col(col_idx)
-                            .map(|ord| (!ord.is_eq()).then(|| ord))
+                            .map(|ord| ord.is_ne().then_some(ord))
                         {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -182,12 +208,12 @@ impl DiffByteRecords {
                             .and_then(|ord| match ord {
                                 Ordering::Equal => (mod_add, add)
                                     .cmp_by_col(col_idx)
-                                    .map(|ord| (!ord.is_eq()).then(|| ord)),
+                                    .map(|ord| ord.is_ne().then_some(ord)),
                                 _ => Ok(Some(ord)),
                             }) {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -215,12 +241,12 @@ impl DiffByteRecords {
                             .and_then(|ord| match ord {
                                 Ordering::Equal => (add_l, add_r)
                                     .cmp_by_col(col_idx)
-                                    .map(|ord| (!ord.is_eq()).then(|| ord)),
+                                    .map(|ord| ord.is_ne().then_some(ord)),
                                 _ => Ok(Some(ord)),
                             }) {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -243,12 +269,12 @@ impl DiffByteRecords {
                             .and_then(|ord| match ord {
                                 Ordering::Equal => (mod_add, del)
                                     .cmp_by_col(col_idx)
-                                    .map(|ord| (!ord.is_eq()).then(|| ord)),
+                                    .map(|ord| ord.is_ne().then_some(ord)),
                                 _ => Ok(Some(ord)),
                             }) {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -262,11 +288,11 @@ impl DiffByteRecords {
                     .find_map(|col_idx| {
                         match (del, add)
                             .cmp_by_col(col_idx)
-                            .map(|ord| (!ord.is_eq()).then(|| ord))
+                            .map(|ord| ord.is_ne().then_some(ord))
                         {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -290,12 +316,12 @@ impl DiffByteRecords {
                             .and_then(|ord| match ord {
                                 Ordering::Equal => (del, mod_add)
                                     .cmp_by_col(col_idx)
-                                    .map(|ord| (!ord.is_eq()).then(|| ord)),
+                                    .map(|ord| ord.is_ne().then_some(ord)),
                                 _ => Ok(Some(ord)),
                             }) {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -309,11 +335,11 @@ impl DiffByteRecords {
                     .find_map(|col_idx| {
                         match (del_l, del_r)
                             .cmp_by_col(col_idx)
-                            .map(|ord| (!ord.is_eq()).then(|| ord))
+                            .map(|ord| ord.is_ne().then_some(ord))
                         {
                             Ok(ord) => ord,
                             Err(e) => {
-                                if !error_maybe.is_err() {
+                                if error_maybe.is_ok() {
                                     error_maybe = Err(e);
                                 }
                                 None
@@ -321,12 +347,154 @@ impl DiffByteRecords {
                         }
                     })
                     .unwrap_or(Ordering::Equal),
+                (
+                    DiffByteRecord::Move {
+                        record: record_l, ..
+                    },
+                    DiffByteRecord::Move {
+                        record: record_r, ..
+                    },
+                ) => cols_to_sort
+                    .iter()
+                    .find_map(|col_idx| {
+                        match (record_l, record_r)
+                            .cmp_by_col(col_idx)
+                            .map(|ord| ord.is_ne().then_some(ord))
+                        {
+                            Ok(ord) => ord,
+                            Err(e) => {
+                                if error_maybe.is_ok() {
+                                    error_maybe = Err(e);
+                                }
+                                None
+                            }
+                        }
+                    })
+                    .unwrap_or(Ordering::Equal),
+                (DiffByteRecord::Move { record, .. }, other) => {
+                    let (other_first, other_second) = byte_records_of(other);
+                    cols_to_sort
+                        .iter()
+                        .find_map(|col_idx| {
+                            match (record, other_first).cmp_by_col(col_idx).and_then(
+                                |ord| match ord {
+                                    Ordering::Equal => (record, other_second)
+                                        .cmp_by_col(col_idx)
+                                        .map(|ord| ord.is_ne().then_some(ord)),
+                                    _ => Ok(Some(ord)),
+                                },
+                            ) {
+                                Ok(ord) => ord,
+                                Err(e) => {
+                                    if error_maybe.is_ok() {
+                                        error_maybe = Err(e);
+                                    }
+                                    None
+                                }
+                            }
+                        })
+                        // `Move` should be treated as greater than all other kinds
+                        .unwrap_or(Ordering::Greater)
+                }
+                (other, DiffByteRecord::Move { record, .. }) => {
+                    let (other_first, other_second) = byte_records_of(other);
+                    cols_to_sort
+                        .iter()
+                        .find_map(|col_idx| {
+                            match (other_first, record).cmp_by_col(col_idx).and_then(
+                                |ord| match ord {
+                                    Ordering::Equal => (other_second, record)
+                                        .cmp_by_col(col_idx)
+                                        .map(|ord| ord.is_ne().then_some(ord)),
+                                    _ => Ok(Some(ord)),
+                                },
+                            ) {
+                                Ok(ord) => ord,
+                                Err(e) => {
+                                    if error_maybe.is_ok() {
+                                        error_maybe = Err(e);
+                                    }
+                                    None
+                                }
+                            }
+                        })
+                        // all other kinds should be treated as less than `Move`
+                        .unwrap_or(Ordering::Less)
+                }
             });
         }
         error_maybe
     }
 
-    /// Return the `DiffByteRecord`s as a single slice.
+    /// Sort the `DiffByteRecord`s like [`sort_by_columns`](DiffByteRecords::sort_by_columns), but return an error for
+    /// every column, that is out of bounds for any record, instead of only the first error encountered during the sort.
+    ///
+    /// Every record is checked, so a column is reported, even if the sort never needs to compare it.
+    /// The errors are in the order of the columns (duplicate columns are only reported once) and each holds the length
+    /// of the first record, that the column is out of bounds for.
+    pub fn sort_by_columns_all_errors<E: Into<SortColumn>, I: IntoIterator<Item = E>>(
+        &mut self,
+        cols: I,
+    ) -> Result<(), Vec<ColumnIdxError>> {
+        let cols_to_sort = cols.into_iter().map(|e| e.into()).collect::<Vec<_>>();
+        let mut idxs_checked = Vec::with_capacity(cols_to_sort.len());
+        let errors = cols_to_sort
+            .iter()
+            .filter_map(|sort_column| {
+                let idx = sort_column
+                    .col_idx
+                    .idx_for_both()
+                    .expect("idx, because it is the only enum variant");
+                if idxs_checked.contains(&idx) {
+                    return None;
+                }
+                idxs_checked.push(idx);
+                self.0.iter().find_map(|diff_record| {
+                    let (byte_record_a, byte_record_b) = byte_records_of(diff_record);
+                    [byte_record_a, byte_record_b]
+                        .into_iter()
+                        .map(|byte_record| byte_record.len())
+                        .find(|&len| idx >= len)
+                        .map(|len| ColumnIdxError::IdxOutOfBounds { idx, len })
+                })
+            })
+            .collect::<Vec<_>>();
+        // all errors of the sort have already been found above
+        let _ = self.sort_by_columns(cols_to_sort);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Group the `DiffByteRecord`s by their value in column `col` (usually the first column of the primary key),
+    /// so that they can be reviewed group by group (e.g. by account or region) instead of as one flat list.
+    ///
+    /// The groups are ordered by their key. The order of the records within a group is preserved,
+    /// so you might want to call [`sort_by_line`](DiffByteRecords::sort_by_line) or
+    /// [`sort_by_columns`](DiffByteRecords::sort_by_columns) beforehand.
+    ///
+    /// Returns an error, if `col` is out of bounds for any of the records.
+    pub fn group_by_column<E: Into<ColumnIdx>>(
+        &self,
+        col: E,
+    ) -> Result<Vec<DiffByteRecordGroup<'_>>, ColumnIdxError> {
+        let idx = col
+            .into()
+            .idx_for_both()
+            .expect("idx, because it is the only enum variant");
+        if let Some(diff_record) = self
+            .0
+            .iter()
+            .find(|diff_record| group_key(diff_record, idx).is_none())
+        {
+            let len = byte_records_of(diff_record).0.len();
+            return Err(ColumnIdxError::IdxOutOfBounds { idx, len });
+        }
+        Ok(group_by_col(&self.0, idx))
+    }
+
     /// # Example
     #[cfg_attr(
         feature = "rayon-threads",
@@ -369,31 +537,579 @@ impl DiffByteRecords {
     pub fn iter(&self) -> core::slice::Iter<'_, DiffByteRecord> {
         self.0.iter()
     }
+
+    /// Return the page of at most `limit` `DiffByteRecord`s, that starts at `offset`, without copying them
+    /// (e.g. for a UI backend, that shows a large diff page by page).
+    ///
+    /// The page is empty, if `offset` is past the last record. Sort the records once (e.g. with
+    /// [`sort_by_line`](DiffByteRecords::sort_by_line)) before requesting pages, so that consecutive pages neither
+    /// overlap nor skip records and the same page is returned for every comparison of the same CSVs.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = "id,name,kind\n\
+                         1,lemon,fruit\n\
+                         2,strawberry,fruit";
+    let csv_data_right = "id,name,kind\n\
+                          1,lemon,nut\n\
+                          2,strawberry,nut\n\
+                          3,cherry,fruit";
+
+    let mut diff_byte_records = CsvByteDiffLocal::new()?.diff(
+        Csv::with_reader_seek(csv_data_left.as_bytes()),
+        Csv::with_reader_seek(csv_data_right.as_bytes()),
+    )?;
+    diff_byte_records.sort_by_line();
+
+    let first_page = diff_byte_records.page(0, 2);
+    let second_page = diff_byte_records.page(2, 2);
+
+    assert_eq!(first_page.len(), 2);
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page, &diff_byte_records.as_slice()[2..]);
+    assert!(diff_byte_records.page(4, 2).is_empty());
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn page(&self, offset: usize, limit: usize) -> &[DiffByteRecord] {
+        let start = offset.min(self.0.len());
+        let end = start.saturating_add(limit).min(self.0.len());
+        &self.0[start..end]
+    }
+
+    /// Classify every `DiffByteRecord` with the given `classifier` and group them by their [`Severity`](crate::severity::Severity).
+    pub fn classify_severity<F>(self, mut classifier: F) -> DiffByteRecordsBySeverity
+    where
+        F: FnMut(&DiffByteRecord) -> Severity,
+    {
+        self.0
+            .into_iter()
+            .map(|diff_record| (classifier(&diff_record), diff_record))
+            .collect()
+    }
+
+    /// Index the `DiffByteRecord`s by the values of their `primary_key_columns`, so that the difference of a single key
+    /// can be looked up in O(1) instead of scanning all records.
+    ///
+    /// The key of a `Modify` is taken from its deleted record. Columns, that are out of bounds for a record,
+    /// are not part of its key. If multiple records have the same key (e.g. after [`extend`](DiffByteRecords::extend)),
+    /// only the first one is indexed.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+    use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+    use csv_diff::diff_row::DiffByteRecord;
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let csv_data_left = "customer,region,name\n\
+                         4711,north,lemon\n\
+                         4712,south,strawberry";
+    let csv_data_right = "customer,region,name\n\
+                          4711,north,cherry\n\
+                          4712,south,strawberry";
+
+    let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+        Csv::with_reader_seek(csv_data_left.as_bytes()),
+        Csv::with_reader_seek(csv_data_right.as_bytes()),
+    )?;
+    let diff_byte_records_by_key = diff_byte_records.index_by_key(vec![0, 1]);
+
+    assert!(matches!(
+        diff_byte_records_by_key.get([&b"4711"[..], b"north"].as_slice()),
+        Some(DiffByteRecord::Modify { .. })
+    ));
+    assert_eq!(diff_byte_records_by_key.get([&b"4712"[..], b"south"].as_slice()), None);
+    Ok(())
+    # }
+    "##
+    )]
+    pub fn index_by_key<I: IntoIterator<Item = usize>>(
+        &self,
+        primary_key_columns: I,
+    ) -> std::collections::HashMap<Vec<&[u8]>, &DiffByteRecord> {
+        let primary_key_columns = primary_key_columns.into_iter().collect::<Vec<_>>();
+        let mut diff_byte_records_by_key = std::collections::HashMap::with_capacity(self.0.len());
+        for diff_record in self.0.iter() {
+            diff_byte_records_by_key
+                .entry(key_fields(diff_record, &primary_key_columns).collect())
+                .or_insert(diff_record);
+        }
+        diff_byte_records_by_key
+    }
+
+    /// Merge `other` into these `DiffByteRecords`, where records are matched by the values of their `primary_key_columns`.
+    /// This is useful for combining diffs, that have been produced from partitioned inputs.
+    ///
+    /// Records of `other`, whose key does not exist yet, are appended. For records with the same key:
+    /// - equal records are only kept once
+    /// - a `Delete` and an `Add` are combined into a `Modify` (or removed entirely, if both records are equal),
+    ///   because the record has only moved between partitions
+    /// - all other combinations are a conflict and an error is returned - in this case, `self` is left unchanged
+    ///
+    /// If you only want to concatenate records without any matching, use [`Extend`](DiffByteRecords::extend) instead.
+    pub fn merge<I: IntoIterator<Item = usize>>(
+        &mut self,
+        other: DiffByteRecords,
+        primary_key_columns: I,
+    ) -> Result<(), DiffByteRecordsMergeError> {
+        let primary_key_columns = primary_key_columns.into_iter().collect::<Vec<_>>();
+        let key_of = |diff_record: &DiffByteRecord| -> Vec<Vec<u8>> {
+            key_fields(diff_record, &primary_key_columns)
+                .map(<[u8]>::to_vec)
+                .collect()
+        };
+
+        let mut merged: Vec<Option<DiffByteRecord>> = self.0.iter().cloned().map(Some).collect();
+        let mut idx_by_key = std::collections::HashMap::with_capacity(merged.len());
+        for (idx, diff_record) in self.0.iter().enumerate() {
+            idx_by_key.entry(key_of(diff_record)).or_insert(idx);
+        }
+
+        for diff_record in other.0 {
+            let key = key_of(&diff_record);
+            match idx_by_key.get(&key).copied() {
+                None => {
+                    idx_by_key.insert(key, merged.len());
+                    merged.push(Some(diff_record));
+                }
+                Some(idx) => {
+                    let existing = merged[idx].take();
+                    merged[idx] = match (existing, diff_record) {
+                        (Some(existing), diff_record) if existing == diff_record => Some(existing),
+                        (Some(DiffByteRecord::Delete(delete)), DiffByteRecord::Add(add))
+                        | (Some(DiffByteRecord::Add(add)), DiffByteRecord::Delete(delete)) => {
+                            let field_indices = delete
+                                .byte_record()
+                                .iter()
+                                .enumerate()
+                                .zip(add.byte_record().iter())
+                                .filter(|((_, field_left), field_right)| field_left != field_right)
+                                .map(|((idx, _), _)| idx)
+                                .collect::<Vec<_>>();
+                            if field_indices.is_empty()
+                                && delete.byte_record().len() == add.byte_record().len()
+                            {
+                                None
+                            } else {
+                                Some(DiffByteRecord::Modify {
+                                    delete,
+                                    add,
+                                    field_indices,
+                                })
+                            }
+                        }
+                        (None, diff_record) => Some(diff_record),
+                        (Some(_), _) => {
+                            return Err(DiffByteRecordsMergeError::ConflictingRecords { key })
+                        }
+                    };
+                }
+            }
+        }
+        self.0 = merged.into_iter().flatten().collect();
+        Ok(())
+    }
+
+    /// Retain only the `DiffByteRecord`s, for which `f` returns `true`.
+    pub fn retain<F: FnMut(&DiffByteRecord) -> bool>(&mut self, f: F) {
+        self.0.retain(f);
+    }
+
+    /// Consume these `DiffByteRecords` and return only those, for which `f` returns `true`.
+    pub fn filter<F: FnMut(&DiffByteRecord) -> bool>(mut self, f: F) -> Self {
+        self.retain(f);
+        self
+    }
+
+    /// Retain only the `DiffByteRecord`s of the given `kinds`.
+    pub fn retain_by_kind<I: IntoIterator<Item = DiffByteRecordKind>>(&mut self, kinds: I) {
+        let kinds = kinds.into_iter().collect::<Vec<_>>();
+        self.retain(|diff_record| kinds.contains(&diff_record.kind()));
+    }
+
+    /// Retain only the `DiffByteRecord`s, whose field at column `col` satisfies `predicate`.
+    /// For a `Modify`, it is sufficient, when the field of either side satisfies `predicate`.
+    /// Records without a field at `col` are removed.
+    pub fn retain_by_column<F: FnMut(&[u8]) -> bool>(&mut self, col: usize, mut predicate: F) {
+        self.retain(|diff_record| match diff_record {
+            DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => {
+                brli.byte_record().get(col).map_or(false, &mut predicate)
+            }
+            DiffByteRecord::Modify { delete, add, .. } => {
+                delete.byte_record().get(col).map_or(false, &mut predicate)
+                    || add.byte_record().get(col).map_or(false, &mut predicate)
+            }
+            DiffByteRecord::Move { record, .. } => record.get(col).map_or(false, &mut predicate),
+        });
+    }
+
+    /// Retain only the `DiffByteRecord`s, whose fields at `primary_key_columns` are equal to `key`
+    /// (e.g. all changes of a single account).
+    pub fn retain_by_key<K: AsRef<[u8]>>(&mut self, primary_key_columns: &[usize], key: &[K]) {
+        self.retain(|diff_record| {
+            let byte_record = byte_records_of(diff_record).0;
+            primary_key_columns.len() == key.len()
+                && primary_key_columns
+                    .iter()
+                    .zip(key)
+                    .all(|(&idx, key_field)| byte_record.get(idx) == Some(key_field.as_ref()))
+        });
+    }
+
+    /// Count how often each column has been modified across all [`DiffByteRecord::Modify`](crate::diff_row::DiffByteRecord::Modify)s
+    /// (e.g. to find out, which columns churn the most).
+    pub fn column_modification_frequencies(&self) -> ColumnModificationFrequencies {
+        self.iter().collect()
+    }
+
+    /// [Invert](crate::diff_row::DiffByteRecord::invert) all `DiffByteRecord`s, so that they describe the changes,
+    /// that transform the right CSV back into the left CSV (e.g. for rolling back a change).
+    ///
+    /// Inverting twice yields the original differences.
+    pub fn invert(self) -> Self {
+        Self(
+            self.0.into_iter().map(DiffByteRecord::invert).collect(),
+            self.1.invert(),
+        )
+    }
+
+    /// Convert all `DiffByteRecord`s into their [compact representation](crate::diff_row::CompactDiffByteRecord),
+    /// where the add half of a `Modify` only holds the fields that have changed.
+    pub fn into_compact(self) -> Vec<CompactDiffByteRecord> {
+        self.0
+            .into_iter()
+            .map(CompactDiffByteRecord::from)
+            .collect()
+    }
+
+    /// Convert all `DiffByteRecord`s into [`DiffRecord`](crate::diff_row::DiffRecord)s with UTF-8 fields.
+    ///
+    /// Fails on the first record with a field, that is not valid UTF-8.
+    pub fn try_into_diff_records(self) -> Result<Vec<DiffRecord>, RecordUtf8Error> {
+        self.0.into_iter().map(DiffRecord::try_from).collect()
+    }
+
+    /// Like [`try_into_diff_records`](DiffByteRecords::try_into_diff_records), but invalid UTF-8 sequences are replaced
+    /// (see [`Utf8Conversion::Lossy`](crate::diff_row::Utf8Conversion::Lossy)).
+    pub fn into_diff_records_lossy(self) -> Vec<DiffRecord> {
+        self.0
+            .into_iter()
+            .map(DiffRecord::from_diff_byte_record_lossy)
+            .collect()
+    }
+
+    /// Write all `DiffByteRecord`s as CSV to `writer`, every record prefixed with its operation
+    /// (`add`, `delete`, `move` or, depending on the [`CsvWriteStyle`], `modify`).
+    ///
+    /// If there are [`headers`](DiffByteRecords::headers), they are written first, prefixed with `operation`.
+    /// Fields are written as bytes, so the delimiter, the quoting and the terminator are those of `writer`.
+    /// The records are written in their current order and `writer` is flushed afterwards.
+    pub fn write_csv<W: io::Write>(
+        &self,
+        writer: &mut csv::Writer<W>,
+        style: CsvWriteStyle,
+    ) -> csv::Result<()> {
+        let mut row = csv::ByteRecord::new();
+        let mut write_row = |operation: &[u8], byte_record: &csv::ByteRecord| {
+            row.clear();
+            row.push_field(operation);
+            row.extend(byte_record);
+            writer.write_byte_record(&row)
+        };
+        if let Some(headers) = self.headers() {
+            write_row(b"operation", headers)?;
+        }
+        for diff_record in self.iter() {
+            match (diff_record, style) {
+                (DiffByteRecord::Add(add), _) => write_row(b"add", add.byte_record())?,
+                (DiffByteRecord::Delete(delete), _) => write_row(b"delete", delete.byte_record())?,
+                (DiffByteRecord::Modify { add, .. }, CsvWriteStyle::Compact) => {
+                    write_row(b"modify", add.byte_record())?
+                }
+                (DiffByteRecord::Modify { delete, add, .. }, CsvWriteStyle::Split) => {
+                    write_row(b"modify_delete", delete.byte_record())?;
+                    write_row(b"modify_add", add.byte_record())?;
+                }
+                (DiffByteRecord::Move { record, .. }, _) => write_row(b"move", record)?,
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// How [`DiffByteRecords::write_csv`] writes a [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CsvWriteStyle {
+    /// Write one row with the operation `modify` and the fields of the right CSV.
+    Compact,
+    /// Write two rows: the fields of the left CSV with the operation `modify_delete`, followed by
+    /// the fields of the right CSV with the operation `modify_add`.
+    Split,
+}
+
+/// `DiffByteRecord`s, that share the same value in a column (e.g. all differences of one account or region).
+///
+/// Created by [`DiffByteRecords::group_by_column`](DiffByteRecords::group_by_column).
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiffByteRecordGroup<'a> {
+    key: &'a [u8],
+    diff_records: Vec<&'a DiffByteRecord>,
+}
+
+impl<'a> DiffByteRecordGroup<'a> {
+    /// The value of the column, by which the records have been grouped.
+    pub fn key(&self) -> &'a [u8] {
+        self.key
+    }
+
+    pub fn diff_records(&self) -> &[&'a DiffByteRecord] {
+        self.diff_records.as_slice()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diff_records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diff_records.is_empty()
+    }
+}
+
+/// Return the value of the field at `idx`, which is used as the group key of `diff_record`.
+/// For a `Modify`, the deleted side is used.
+#[inline]
+pub(crate) fn group_key(diff_record: &DiffByteRecord, idx: usize) -> Option<&[u8]> {
+    byte_records_of(diff_record).0.get(idx)
+}
+
+/// The records of `diff_record`: both halves of a `Modify` and the one record of any other kind twice.
+#[inline]
+pub(crate) fn byte_records_of(
+    diff_record: &DiffByteRecord,
+) -> (&csv::ByteRecord, &csv::ByteRecord) {
+    match diff_record {
+        DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => {
+            (brli.byte_record(), brli.byte_record())
+        }
+        DiffByteRecord::Modify { delete, add, .. } => (delete.byte_record(), add.byte_record()),
+        DiffByteRecord::Move { record, .. } => (record, record),
+    }
+}
+
+/// Group `diff_records` by their [`group_key`], ordered by key. Records without a key are grouped under an empty key.
+/// The order of the records within a group is preserved.
+pub(crate) fn group_by_col<'a, I>(diff_records: I, idx: usize) -> Vec<DiffByteRecordGroup<'a>>
+where
+    I: IntoIterator<Item = &'a DiffByteRecord>,
+{
+    let mut groups: BTreeMap<&'a [u8], Vec<&'a DiffByteRecord>> = BTreeMap::new();
+    for diff_record in diff_records {
+        groups
+            .entry(group_key(diff_record, idx).unwrap_or_default())
+            .or_default()
+            .push(diff_record);
+    }
+    groups
+        .into_iter()
+        .map(|(key, diff_records)| DiffByteRecordGroup { key, diff_records })
+        .collect()
 }
 
 trait CmpByColumn {
-    fn cmp_by_col(&self, col_idx: &ColumnIdx) -> Result<Ordering, ColumnIdxError>;
+    fn cmp_by_col(&self, sort_column: &SortColumn) -> Result<Ordering, ColumnIdxError>;
 }
 
 impl CmpByColumn for (&ByteRecordLineInfo, &ByteRecordLineInfo) {
     #[inline]
-    fn cmp_by_col(&self, col_idx: &ColumnIdx) -> Result<Ordering, ColumnIdxError> {
-        let idx_for_both = col_idx
+    fn cmp_by_col(&self, sort_column: &SortColumn) -> Result<Ordering, ColumnIdxError> {
+        let &(brli_left, brli_right) = self;
+        (brli_left.byte_record(), brli_right.byte_record()).cmp_by_col(sort_column)
+    }
+}
+
+impl CmpByColumn for (&csv::ByteRecord, &csv::ByteRecord) {
+    #[inline]
+    fn cmp_by_col(&self, sort_column: &SortColumn) -> Result<Ordering, ColumnIdxError> {
+        let idx_for_both = sort_column
+            .col_idx
             .idx_for_both()
             .expect("idx, because it is the only enum variant");
-        let &(brli_left, brli_right) = self;
-        brli_left
-            .byte_record()
+        let &(byte_record_left, byte_record_right) = self;
+        byte_record_left
             .get(idx_for_both)
-            .zip(brli_right.byte_record().get(idx_for_both))
-            .map(|(a, b)| a.cmp(b))
+            .zip(byte_record_right.get(idx_for_both))
+            .map(|(a, b)| sort_column.sort_type.cmp(a, b))
             .ok_or(ColumnIdxError::IdxOutOfBounds {
                 idx: idx_for_both,
-                len: brli_left.byte_record().len(),
+                len: byte_record_left.len(),
             })
     }
 }
 
+/// A column of [`DiffByteRecords::sort_by_columns`](DiffByteRecords::sort_by_columns) together with
+/// the [`ColumnSortType`], that its values are compared with.
+///
+/// A plain column index (or [`ColumnIdx`]) converts into a `SortColumn`, whose values are compared as raw bytes.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+use csv_diff::diff_result::{ColumnSortType, SortColumn};
+use csv_diff::diff_row::DiffByteRecord;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,price\n\
+                     1,lemon,10\n\
+                     2,strawberry,9\n\
+                     3,cherry,100";
+let csv_data_right = "id,name,price";
+
+let mut diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek(csv_data_left.as_bytes()),
+    Csv::with_reader_seek(csv_data_right.as_bytes()),
+)?;
+diff_byte_records.sort_by_columns(vec![SortColumn::new(2).sort_type(ColumnSortType::Integer)])?;
+
+let prices = diff_byte_records
+    .iter()
+    .map(|diff_record| match diff_record {
+        DiffByteRecord::Delete(delete) => delete.byte_record()[2].to_vec(),
+        _ => unreachable!("all records have been deleted"),
+    })
+    .collect::<Vec<_>>();
+// compared as raw bytes, `10` and `100` would sort before `9`
+assert_eq!(prices, vec![b"9".to_vec(), b"10".to_vec(), b"100".to_vec()]);
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortColumn {
+    col_idx: ColumnIdx,
+    sort_type: ColumnSortType,
+}
+
+impl SortColumn {
+    pub fn new<E: Into<ColumnIdx>>(col: E) -> Self {
+        Self {
+            col_idx: col.into(),
+            sort_type: ColumnSortType::default(),
+        }
+    }
+
+    /// Set how the values of this column are compared (default: [`ColumnSortType::Bytes`]).
+    pub fn sort_type(mut self, sort_type: ColumnSortType) -> Self {
+        self.sort_type = sort_type;
+        self
+    }
+}
+
+impl From<ColumnIdx> for SortColumn {
+    fn from(col_idx: ColumnIdx) -> Self {
+        Self::new(col_idx)
+    }
+}
+
+impl From<usize> for SortColumn {
+    fn from(idx: usize) -> Self {
+        Self::new(idx)
+    }
+}
+
+/// How the values of a [`SortColumn`] are compared.
+///
+/// Values, that can't be parsed as the given type (e.g. an empty field in a column of integers),
+/// are sorted after all values, that can be parsed, and are compared as raw bytes among each other.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ColumnSortType {
+    /// Compare the values lexicographically as raw bytes, so `10` sorts before `9`.
+    #[default]
+    Bytes,
+    /// Compare the values as signed integers (surrounding whitespace is ignored).
+    Integer,
+    /// Compare the values as floating point numbers (surrounding whitespace is ignored).
+    Float,
+    /// Compare the values as points in time, which are parsed with the given format
+    /// (see [`chrono::format::strftime`] for the syntax). A format without a time is parsed as a date
+    /// at midnight and a format without an offset is interpreted in UTC.
+    ///
+    /// This is only available with the Cargo feature `chrono`.
+    #[cfg(feature = "chrono")]
+    DateTime(String),
+}
+
+impl ColumnSortType {
+    fn cmp(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match self {
+            Self::Bytes => a.cmp(b),
+            Self::Integer => cmp_parsed(a, b, parse_trimmed::<i128>),
+            Self::Float => cmp_parsed(a, b, |field| parse_trimmed::<f64>(field).map(TotalF64)),
+            #[cfg(feature = "chrono")]
+            Self::DateTime(format) => cmp_parsed(a, b, |field| parse_date_time(field, format)),
+        }
+    }
+}
+
+/// Compare the parsed values of `a` and `b`; values, that can't be parsed, are greater than all values, that can.
+fn cmp_parsed<T: Ord>(a: &[u8], b: &[u8], parse: impl Fn(&[u8]) -> Option<T>) -> Ordering {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+fn parse_trimmed<T: std::str::FromStr>(field: &[u8]) -> Option<T> {
+    std::str::from_utf8(field).ok()?.trim().parse().ok()
+}
+
+/// An `f64` with a total order (see [`f64::total_cmp`]).
+struct TotalF64(f64);
+
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn parse_date_time(field: &[u8], format: &str) -> Option<chrono::NaiveDateTime> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime};
+    let field = std::str::from_utf8(field).ok()?.trim();
+    DateTime::parse_from_str(field, format)
+        .map(|date_time| date_time.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(field, format))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(field, format)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ColumnIdx {
     IdxForBoth(usize),
     // TODO: we will implement this later - right now it will be too complicated
@@ -442,6 +1158,151 @@ pub enum ColumnIdxError {
     IdxOutOfBounds { idx: usize, len: usize },
 }
 
+impl ErrorCode for ColumnIdxError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::IdxOutOfBounds { .. } => "CSVDIFF_COLUMN_OUT_OF_BOUNDS",
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DiffByteRecordsMergeError {
+    #[error("conflicting records for the same key ({})", .key.iter().map(|field| String::from_utf8_lossy(field)).collect::<Vec<_>>().join(", "))]
+    ConflictingRecords { key: Vec<Vec<u8>> },
+}
+
+impl ErrorCode for DiffByteRecordsMergeError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::ConflictingRecords { .. } => "CSVDIFF_MERGE_CONFLICT",
+        }
+    }
+}
+
+/// The fields of `diff_record` in its `primary_key_columns` (for a `Modify`, of its deleted record).
+fn key_fields<'a, 'b>(
+    diff_record: &'a DiffByteRecord,
+    primary_key_columns: &'b [usize],
+) -> impl Iterator<Item = &'a [u8]> + 'b
+where
+    'a: 'b,
+{
+    let byte_record = byte_records_of(diff_record).0;
+    primary_key_columns
+        .iter()
+        .filter_map(move |&idx| byte_record.get(idx))
+}
+
+/// The order of [`DiffByteRecords::sort_by_line`](DiffByteRecords::sort_by_line).
+fn cmp_by_line(a: &DiffByteRecord, b: &DiffByteRecord) -> Ordering {
+    match (a.line_num(), b.line_num()) {
+        (LineNum::OneSide(line_num_a), LineNum::OneSide(line_num_b)) => line_num_a
+            .cmp(&line_num_b)
+            .then(if matches!(a, DiffByteRecord::Delete(..)) {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }),
+        (
+            LineNum::OneSide(line_num_a),
+            LineNum::BothSides {
+                for_deleted,
+                for_added,
+            },
+        ) => line_num_a
+            .cmp(if for_deleted < for_added {
+                &for_deleted
+            } else {
+                &for_added
+            })
+            .then(if matches!(a, DiffByteRecord::Delete(..)) {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }),
+        (
+            LineNum::BothSides {
+                for_deleted,
+                for_added,
+            },
+            LineNum::OneSide(line_num_b),
+        ) => if for_deleted < for_added {
+            &for_deleted
+        } else {
+            &for_added
+        }
+        .cmp(&line_num_b)
+        .then(if matches!(b, DiffByteRecord::Add(..)) {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }),
+        (
+            LineNum::BothSides {
+                for_deleted: for_deleted_a,
+                for_added: for_added_a,
+            },
+            LineNum::BothSides {
+                for_deleted: for_deleted_b,
+                for_added: for_added_b,
+            },
+        ) => if for_deleted_a < for_added_a {
+            &for_deleted_a
+        } else {
+            &for_added_a
+        }
+        .cmp(if for_deleted_b < for_added_b {
+            &for_deleted_b
+        } else {
+            &for_added_b
+        })
+        // records, that have moved, might start at the same line (e.g. lines 3 -> 5 and 5 -> 3)
+        .then(for_deleted_a.cmp(&for_deleted_b)),
+    }
+}
+
+/// `DiffByteRecords` without any headers.
+impl From<Vec<DiffByteRecord>> for DiffByteRecords {
+    fn from(diff_records: Vec<DiffByteRecord>) -> Self {
+        Self(diff_records, DiffHeaders::default())
+    }
+}
+
+/// The headers of both CSVs, that have been compared.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct DiffHeaders {
+    left: Option<csv::ByteRecord>,
+    right: Option<csv::ByteRecord>,
+}
+
+impl DiffHeaders {
+    pub(crate) fn new(header_info_left: &CsvHeaderInfo, header_info_right: &CsvHeaderInfo) -> Self {
+        Self {
+            left: header_info_left.headers().cloned(),
+            right: header_info_right.headers().cloned(),
+        }
+    }
+
+    fn headers(&self) -> Option<&csv::ByteRecord> {
+        self.left.as_ref().or(self.right.as_ref())
+    }
+
+    fn invert(self) -> Self {
+        Self {
+            left: self.right,
+            right: self.left,
+        }
+    }
+}
+
+/// Append the `DiffByteRecord`s without any matching (see [`DiffByteRecords::merge`](DiffByteRecords::merge) for merging by key).
+impl Extend<DiffByteRecord> for DiffByteRecords {
+    fn extend<T: IntoIterator<Item = DiffByteRecord>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
 impl IntoIterator for DiffByteRecords {
     type Item = DiffByteRecord;
     type IntoIter = DiffByteRecordsIntoIterator;
@@ -466,24 +1327,73 @@ impl Iterator for DiffByteRecordsIntoIterator {
     }
 }
 
+/// The differences between two CSVs together with _all_ parse errors of both CSVs, as returned by
+/// [`CsvByteDiffLocal::diff_with_report`](crate::csv_diff::CsvByteDiffLocal::diff_with_report)
+/// and [`CsvByteDiff::diff_with_report`](crate::csv_diff::CsvByteDiff::diff_with_report).
+///
+/// Every malformed record is reported as a [`SkippedRecord`] with its line and error and is not part of the differences.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiffReport {
+    records: DiffByteRecords,
+    errors: SkippedRecords,
+    run_report: DiffRunReport,
+}
+
+impl DiffReport {
+    pub(crate) fn new(records: DiffByteRecords, run_report: DiffRunReport) -> Self {
+        Self {
+            records,
+            errors: SkippedRecords::new(
+                run_report.skipped_records_left().to_vec(),
+                run_report.skipped_records_right().to_vec(),
+            ),
+            run_report,
+        }
+    }
+
+    /// The differences between the CSVs (without the malformed records).
+    pub fn records(&self) -> &DiffByteRecords {
+        &self.records
+    }
+
+    /// The malformed records of both CSVs and their errors.
+    pub fn errors(&self) -> &SkippedRecords {
+        &self.errors
+    }
+
+    /// Return `true`, if any of the CSVs has a malformed record.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// The [`DiffRunReport`](crate::run_report::DiffRunReport) of the comparison, whose rows are the records,
+    /// that have actually been read from the CSVs.
+    pub fn run_report(&self) -> &DiffRunReport {
+        &self.run_report
+    }
+
+    /// Split the report into its differences and its malformed records.
+    pub fn into_parts(self) -> (DiffByteRecords, SkippedRecords) {
+        (self.records, self.errors)
+    }
+}
+
 pub(crate) type CsvHashValueMap = HashMap<u128, HashMapValue<Position, RecordHash>>;
 pub(crate) type CsvByteRecordValueMap = HashMap<u128, HashMapValue<csv::ByteRecord>>;
 
-struct MaxCapacityThreshold(usize);
+struct MaxCapacityThreshold(usize, FlushThreshold);
 
 impl MaxCapacityThreshold {
+    fn new(flush_threshold: FlushThreshold) -> Self {
+        Self(flush_threshold.initial_lines(), flush_threshold)
+    }
     #[inline]
     fn value(&self) -> usize {
         self.0
     }
     fn calc_new(&mut self, current_line: u64) {
-        if current_line % 100 == 0 {
-            self.0 = max(
-                10,
-                (current_line / 100)
-                    .try_into()
-                    .unwrap_or(usize::max_value()),
-            );
+        if let Some(lines) = self.1.next_lines(current_line) {
+            self.0 = lines;
         }
     }
 }
@@ -497,8 +1407,17 @@ impl MaxCapacityThreshold {
 /// If you want to have them ordered, you first need to collect them into [`DiffByteRecords`] and then use
 /// [`DiffByteRecords.sort_by_line`](DiffByteRecords::sort_by_line) to sort them in-place.
 ///
+/// The iterator is [fused](FusedIterator): after it has returned `None` (or an error, that ends the comparison, e.g. a cancellation),
+/// it keeps returning `None`. Its [`size_hint`](Iterator::size_hint) has no upper bound, while the CSVs are still being compared,
+/// and becomes the number of records, that are still unmatched, afterwards.
+///
+/// It is [`Send`], so it can be moved to another thread, e.g. to consume it there or to bridge it into a parallel iterator
+/// with [`par_bridge`](https://docs.rs/rayon/1.5.0/rayon/iter/trait.ParallelBridge.html#tymethod.par_bridge).
+///
+/// Metrics of the comparison can be observed with a [`DiffMetricsSink`] (see [`with_metrics_sink`](DiffByteRecordsIterator::with_metrics_sink)).
+///
 /// See the example on [`CsvByteDiff`](crate::csv_diff::CsvByteDiff) for general usage.
-pub struct DiffByteRecordsIterator {
+pub struct DiffByteRecordsIterator<M = NoDiffMetrics> {
     buf: VecDeque<csv::Result<DiffByteRecord>>,
     csv_left_right_parse_results: Receiver<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
     csv_records_left_map: CsvByteRecordValueMap,
@@ -510,12 +1429,43 @@ pub struct DiffByteRecordsIterator {
     max_capacity_left_map: MaxCapacityThreshold,
     max_capacity_right_map: MaxCapacityThreshold,
     sender_csv_records_recycle: Sender<csv::ByteRecord>,
+    run_report: DiffRunReport,
+    field_comparators: FieldComparators,
+    receiver_header_info_left: Receiver<CsvHeaderInfo>,
+    receiver_header_info_right: Receiver<CsvHeaderInfo>,
+    header_info_left: Option<CsvHeaderInfo>,
+    header_info_right: Option<CsvHeaderInfo>,
+    is_header_diff_checked: bool,
+    receiver_key_stats_left: Receiver<KeyStats>,
+    receiver_key_stats_right: Receiver<KeyStats>,
+    receiver_skipped_records_left: Receiver<Vec<SkippedRecord>>,
+    receiver_skipped_records_right: Receiver<Vec<SkippedRecord>>,
+    receiver_panic: Receiver<TaskPanicError>,
+    cancellation_token: CancellationToken,
+    memory_budget: Option<usize>,
+    bytes_unmatched: usize,
+    record_spill: Option<RecordSpill>,
+    is_spill_finalized: bool,
+    max_diffs: Option<usize>,
+    key_sample: Option<NonZeroU64>,
+    diffs_emitted: usize,
+    equal_buf: Option<VecDeque<(ByteRecordLineInfo, ByteRecordLineInfo)>>,
+    is_finished: bool,
+    metrics_sink: M,
 }
 
 impl DiffByteRecordsIterator {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         csv_left_right_parse_results: Receiver<CsvLeftRightParseResult<CsvByteRecordWithHash>>,
         sender_csv_records_recycle: Sender<csv::ByteRecord>,
+        receiver_header_info_left: Receiver<CsvHeaderInfo>,
+        receiver_header_info_right: Receiver<CsvHeaderInfo>,
+        receiver_key_stats_left: Receiver<KeyStats>,
+        receiver_key_stats_right: Receiver<KeyStats>,
+        receiver_skipped_records_left: Receiver<Vec<SkippedRecord>>,
+        receiver_skipped_records_right: Receiver<Vec<SkippedRecord>>,
+        receiver_panic: Receiver<TaskPanicError>,
     ) -> Self {
         Self {
             buf: Default::default(),
@@ -526,21 +1476,467 @@ impl DiffByteRecordsIterator {
             csv_records_right_map_iter: None,
             intermediate_left_map: HashMap::new(),
             intermediate_right_map: HashMap::new(),
-            max_capacity_left_map: MaxCapacityThreshold(10),
-            max_capacity_right_map: MaxCapacityThreshold(10),
+            max_capacity_left_map: MaxCapacityThreshold::new(FlushThreshold::default()),
+            max_capacity_right_map: MaxCapacityThreshold::new(FlushThreshold::default()),
+            sender_csv_records_recycle,
+            run_report: DiffRunReport::default(),
+            field_comparators: FieldComparators::default(),
+            receiver_header_info_left,
+            receiver_header_info_right,
+            header_info_left: None,
+            header_info_right: None,
+            is_header_diff_checked: false,
+            receiver_key_stats_left,
+            receiver_key_stats_right,
+            receiver_skipped_records_left,
+            receiver_skipped_records_right,
+            receiver_panic,
+            cancellation_token: CancellationToken::default(),
+            memory_budget: None,
+            bytes_unmatched: 0,
+            record_spill: None,
+            is_spill_finalized: false,
+            max_diffs: None,
+            key_sample: None,
+            diffs_emitted: 0,
+            equal_buf: None,
+            is_finished: false,
+            metrics_sink: NoDiffMetrics,
+        }
+    }
+
+    pub(crate) fn with_field_comparators(mut self, field_comparators: FieldComparators) -> Self {
+        self.field_comparators = field_comparators;
+        self
+    }
+
+    pub(crate) fn with_empty_csv_semantics(
+        mut self,
+        empty_csv_semantics: EmptyCsvSemantics,
+    ) -> Self {
+        self.run_report.set_empty_csv_semantics(empty_csv_semantics);
+        self
+    }
+
+    pub(crate) fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    pub(crate) fn with_memory_budget(mut self, memory_budget: Option<usize>) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    pub(crate) fn with_max_diffs(mut self, max_diffs: Option<usize>) -> Self {
+        self.max_diffs = max_diffs;
+        self
+    }
+
+    /// Only compare the records, whose key hash is a multiple of `key_sample` (see [`is_key_sampled`]).
+    pub(crate) fn with_key_sample(mut self, key_sample: Option<NonZeroU64>) -> Self {
+        self.key_sample = key_sample;
+        self.run_report.set_key_sample(key_sample);
+        self
+    }
+
+    pub(crate) fn with_flush_threshold(mut self, flush_threshold: FlushThreshold) -> Self {
+        self.max_capacity_left_map = MaxCapacityThreshold::new(flush_threshold);
+        self.max_capacity_right_map = MaxCapacityThreshold::new(flush_threshold);
+        self
+    }
+
+    /// Seed the hash maps of unmatched records with `hash_seed` (see [`random_state`]).
+    pub(crate) fn with_hash_seed(mut self, hash_seed: Option<u64>) -> Self {
+        self.csv_records_left_map = HashMap::with_hasher(random_state(hash_seed));
+        self.csv_records_right_map = HashMap::with_hasher(random_state(hash_seed));
+        self.intermediate_left_map = HashMap::with_hasher(random_state(hash_seed));
+        self.intermediate_right_map = HashMap::with_hasher(random_state(hash_seed));
+        self
+    }
+
+    /// Keep the records, that are equal in both CSVs, instead of recycling them, and emit them together with the differences.
+    pub(crate) fn with_equal_records(mut self) -> AnnotatedByteRecordsIterator {
+        self.equal_buf = Some(VecDeque::new());
+        AnnotatedByteRecordsIterator { inner: self }
+    }
+
+    /// Report the metrics of the comparison to `metrics_sink`, while this iterator is advanced.
+    ///
+    /// This should be done, before the first record is requested, because metrics, that have been produced before, are not reported.
+    pub fn with_metrics_sink<M: DiffMetricsSink>(
+        self,
+        metrics_sink: M,
+    ) -> DiffByteRecordsIterator<M> {
+        let Self {
+            buf,
+            csv_left_right_parse_results,
+            csv_records_left_map,
+            csv_records_left_map_iter,
+            csv_records_right_map,
+            csv_records_right_map_iter,
+            intermediate_left_map,
+            intermediate_right_map,
+            max_capacity_left_map,
+            max_capacity_right_map,
             sender_csv_records_recycle,
+            run_report,
+            field_comparators,
+            receiver_header_info_left,
+            receiver_header_info_right,
+            header_info_left,
+            header_info_right,
+            is_header_diff_checked,
+            receiver_key_stats_left,
+            receiver_key_stats_right,
+            receiver_skipped_records_left,
+            receiver_skipped_records_right,
+            receiver_panic,
+            cancellation_token,
+            memory_budget,
+            bytes_unmatched,
+            record_spill,
+            is_spill_finalized,
+            max_diffs,
+            key_sample,
+            diffs_emitted,
+            equal_buf,
+            is_finished,
+            metrics_sink: _,
+        } = self;
+        DiffByteRecordsIterator {
+            buf,
+            csv_left_right_parse_results,
+            csv_records_left_map,
+            csv_records_left_map_iter,
+            csv_records_right_map,
+            csv_records_right_map_iter,
+            intermediate_left_map,
+            intermediate_right_map,
+            max_capacity_left_map,
+            max_capacity_right_map,
+            sender_csv_records_recycle,
+            run_report,
+            field_comparators,
+            receiver_header_info_left,
+            receiver_header_info_right,
+            header_info_left,
+            header_info_right,
+            is_header_diff_checked,
+            receiver_key_stats_left,
+            receiver_key_stats_right,
+            receiver_skipped_records_left,
+            receiver_skipped_records_right,
+            receiver_panic,
+            cancellation_token,
+            memory_budget,
+            bytes_unmatched,
+            record_spill,
+            is_spill_finalized,
+            max_diffs,
+            key_sample,
+            diffs_emitted,
+            equal_buf,
+            is_finished,
+            metrics_sink,
+        }
+    }
+}
+
+impl<M: DiffMetricsSink> DiffByteRecordsIterator<M> {
+    fn next_equal(&mut self) -> Option<(ByteRecordLineInfo, ByteRecordLineInfo)> {
+        let (left, right) = match self.equal_buf.as_mut()?.pop_front() {
+            Some(equal_pair) => equal_pair,
+            None => self.record_spill.as_mut()?.next_equal()?,
+        };
+        let redact = |byte_record| self.field_comparators.redact_record(byte_record);
+        Some((left.map_record(redact), right.map_record(redact)))
+    }
+
+    /// Stop comparing and wait for the hashing tasks to finish, which stop as soon as they notice the cancellation
+    /// or the deadline (until then, we drain the channel, so that they are never blocked when sending).
+    fn stop(&mut self) {
+        self.is_finished = true;
+        for _ in self.csv_left_right_parse_results.iter() {}
+        self.buf.clear();
+    }
+
+    fn cancel(&mut self) -> csv::Error {
+        self.stop();
+        CancelledError.into()
+    }
+
+    /// Stop comparing, because `max_diffs` differences have been emitted. We drop the receiver of the parse results,
+    /// so that the hashing tasks stop reading, as soon as they try to send their next record.
+    fn stop_at_max_diffs(&mut self) {
+        self.is_finished = true;
+        self.csv_left_right_parse_results = crossbeam_channel::never();
+        self.buf.clear();
+        self.run_report.set_max_diffs_reached();
+    }
+
+    /// Records, that are still unmatched, are dropped, because they can't be classified anymore.
+    fn time_out(&mut self) -> csv::Error {
+        self.stop();
+        self.run_report.set_timed_out();
+        TimedOutError.into()
+    }
+
+    /// Return the header of the non-empty CSV, if it must be reported according to the [`EmptyCsvSemantics`]
+    /// (it is accounted for in the run report by the caller, like any other difference).
+    fn next_header_diff(&mut self) -> Option<csv::Result<DiffByteRecord>> {
+        // if any of them is missing, the hashing task has failed, which is reported separately
+        if self.header_info_left.is_none() {
+            self.header_info_left = Some(self.receiver_header_info_left.recv().ok()?);
+        }
+        if self.header_info_right.is_none() {
+            self.header_info_right = Some(self.receiver_header_info_right.recv().ok()?);
+        }
+        let (header_info_left, header_info_right) =
+            match (&self.header_info_left, &self.header_info_right) {
+                (Some(header_info_left), Some(header_info_right)) => {
+                    (header_info_left, header_info_right)
+                }
+                _ => return None,
+            };
+        self.run_report
+            .set_empty_csv_left(header_info_left.empty_reason());
+        self.run_report
+            .set_empty_csv_right(header_info_right.empty_reason());
+        self.run_report.set_column_orders(
+            header_info_left.column_order().cloned(),
+            header_info_right.column_order().cloned(),
+        );
+        let empty_csv_semantics = self.run_report.empty_csv_semantics();
+        if empty_csv_semantics == EmptyCsvSemantics::NoRecords {
+            return None;
+        }
+        match empty_csv_semantics.header_diff(header_info_left, header_info_right) {
+            Ok(Some(diff_record)) => {
+                match diff_record {
+                    DiffByteRecord::Delete(_) => self.run_report.record_row_left(),
+                    _ => self.run_report.record_row_right(),
+                }
+                Some(Ok(diff_record))
+            }
+            Ok(None) => None,
+            Err(empty_csv_err) => {
+                self.is_finished = true;
+                Some(Err(empty_csv_err.into()))
+            }
+        }
+    }
+
+    /// Receive the header infos, that the hashing tasks send before any record, without blocking.
+    fn try_recv_header_infos(&mut self) {
+        if self.header_info_left.is_none() {
+            self.header_info_left = self.receiver_header_info_left.try_recv().ok();
+        }
+        if self.header_info_right.is_none() {
+            self.header_info_right = self.receiver_header_info_right.try_recv().ok();
         }
     }
 
-    pub fn try_to_diff_byte_records(self) -> csv::Result<DiffByteRecords> {
-        Ok(DiffByteRecords(self.collect::<csv::Result<_>>()?))
+    /// Resolve the field comparators, that have been registered by header name, as soon as the headers are known.
+    /// A modified record can only be emitted after the headers have been sent, so they are resolved before they are used.
+    fn resolve_field_comparators_by_header(&mut self) {
+        if !self.field_comparators.has_unresolved_headers() {
+            return;
+        }
+        self.try_recv_header_infos();
+        if let (Some(header_info_left), Some(header_info_right)) =
+            (&self.header_info_left, &self.header_info_right)
+        {
+            self.field_comparators.resolve_headers(
+                header_info_left
+                    .headers()
+                    .or_else(|| header_info_right.headers()),
+            );
+        }
+    }
+
+    pub(crate) fn diff_headers(&self) -> DiffHeaders {
+        match (&self.header_info_left, &self.header_info_right) {
+            (Some(header_info_left), Some(header_info_right)) => {
+                DiffHeaders::new(header_info_left, header_info_right)
+            }
+            _ => DiffHeaders::default(),
+        }
+    }
+
+    /// Return the header of the left CSV or, if the left CSV has no header, the header of the right CSV
+    /// (see [`DiffByteRecords::headers`](DiffByteRecords::headers)).
+    ///
+    /// Headers are read before any record, so they are usually known after the first call to `next`
+    /// and always after this iterator has been exhausted.
+    pub fn headers(&self) -> Option<&csv::ByteRecord> {
+        self.headers_left().or_else(|| self.headers_right())
+    }
+
+    /// Return the header of the left CSV, if it is read with headers and is not empty
+    /// (see [`headers`](DiffByteRecordsIterator::headers) on when it is known).
+    pub fn headers_left(&self) -> Option<&csv::ByteRecord> {
+        self.header_info_left.as_ref()?.headers()
+    }
+
+    /// Return the header of the right CSV, if it is read with headers and is not empty
+    /// (see [`headers`](DiffByteRecordsIterator::headers) on when it is known).
+    pub fn headers_right(&self) -> Option<&csv::ByteRecord> {
+        self.header_info_right.as_ref()?.headers()
+    }
+
+    pub fn try_to_diff_byte_records(mut self) -> csv::Result<DiffByteRecords> {
+        let diff_records = self.by_ref().collect::<csv::Result<_>>()?;
+        Ok(DiffByteRecords(diff_records, self.diff_headers()))
+    }
+
+    /// Return up to `n` of the next items at once, e.g. for consumers, that process the differences in bulk
+    /// (like batched inserts into a database), so that they don't need to hand over every single item.
+    ///
+    /// The batch is only shorter than `n`, when this iterator has been exhausted or when it ends with an error,
+    /// so no item is ever lost. An empty batch (for `n > 0`) means, that this iterator has been exhausted.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit\n\
+3,cherry,fruit";
+let csv_right = "\
+id,name,kind";
+
+let mut diff_iter = CsvByteDiff::new()?.diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+);
+
+let mut batch_sizes = Vec::new();
+loop {
+    let batch = diff_iter.next_batch(2);
+    if batch.is_empty() {
+        break;
+    }
+    batch_sizes.push(batch.len());
+    for diff_record in batch {
+        // e.g. add `diff_record?` to a batched insert
+        diff_record?;
+    }
+}
+
+assert_eq!(batch_sizes, vec![2, 1]);
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn next_batch(&mut self, n: usize) -> Vec<csv::Result<DiffByteRecord>> {
+        let mut batch = Vec::new();
+        while batch.len() < n {
+            match self.next() {
+                Some(Ok(diff_record)) => batch.push(Ok(diff_record)),
+                Some(Err(err)) => {
+                    batch.push(Err(err));
+                    break;
+                }
+                None => break,
+            }
+        }
+        batch
+    }
+
+    /// Classify every emitted `DiffByteRecord` with the given `classifier`, while the CSVs are still being compared.
+    ///
+    /// See [`SeverityClassifiedIterator`](crate::severity::SeverityClassifiedIterator) for an example.
+    pub fn classify_severity<F>(self, classifier: F) -> SeverityClassifiedIterator<Self, F>
+    where
+        F: FnMut(&DiffByteRecord) -> Severity,
+    {
+        SeverityClassifiedIterator::new(self, classifier)
+    }
+
+    /// Return the [`DiffRunReport`](crate::run_report::DiffRunReport) of all records, that have been processed so far.
+    ///
+    /// The report is only complete, after this iterator has been exhausted (i.e. it has returned `None`).
+    pub fn run_report(&self) -> DiffRunReport {
+        self.run_report.clone()
+    }
+
+    /// Emit all differences in their [compact representation](crate::diff_row::CompactDiffByteRecord),
+    /// where the add half of a `Modify` only holds the fields that have changed.
+    pub fn compact(self) -> impl Iterator<Item = csv::Result<CompactDiffByteRecord>> {
+        self.map(|res| res.map(CompactDiffByteRecord::from))
+    }
+
+    fn spill_if_over_memory_budget(&mut self) -> io::Result<()> {
+        match self.memory_budget {
+            Some(memory_budget) if self.bytes_unmatched > memory_budget => self.spill_unmatched(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write all records, that haven't been matched yet, to the [`RecordSpill`] and remove them from memory.
+    fn spill_unmatched(&mut self) -> io::Result<()> {
+        let is_keeping_equal = self.equal_buf.is_some();
+        let record_spill = self
+            .record_spill
+            .get_or_insert_with(|| RecordSpill::new().keep_equal(is_keeping_equal));
+        let sender_csv_records_recycle = &self.sender_csv_records_recycle;
+        let metrics_sink = &mut self.metrics_sink;
+        let mut res = Ok(());
+        self.csv_records_left_map.retain(|&key, v| match v {
+            HashMapValue::Initial(record_hash, byte_record) => {
+                if res.is_ok() {
+                    res = record_spill.write_left(key, *record_hash, byte_record);
+                }
+                recycle(
+                    sender_csv_records_recycle,
+                    metrics_sink,
+                    std::mem::take(byte_record),
+                );
+                false
+            }
+            _ => true,
+        });
+        self.csv_records_right_map.retain(|&key, v| match v {
+            HashMapValue::Initial(record_hash, byte_record) => {
+                if res.is_ok() {
+                    res = record_spill.write_right(key, *record_hash, byte_record);
+                }
+                recycle(
+                    sender_csv_records_recycle,
+                    metrics_sink,
+                    std::mem::take(byte_record),
+                );
+                false
+            }
+            _ => true,
+        });
+        self.bytes_unmatched = 0;
+        res
+    }
+
+    /// Merge the spilled records, after all records have been received.
+    fn next_spilled_diff_byte_record(&mut self) -> Option<csv::Result<DiffByteRecord>> {
+        self.record_spill.as_ref()?;
+        if !self.is_spill_finalized {
+            self.is_spill_finalized = true;
+            // records, that are still unmatched, might match a spilled record, so they are merged with them
+            if let Err(spill_err) = self.spill_unmatched() {
+                return Some(Err(spill_err.into()));
+            }
+        }
+        self.record_spill
+            .as_mut()?
+            .next_merged()
+            .map(|res| res.map_err(csv::Error::from))
     }
-}
-
-impl Iterator for DiffByteRecordsIterator {
-    type Item = csv::Result<DiffByteRecord>;
 
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_diff_byte_record(&mut self) -> Option<csv::Result<DiffByteRecord>> {
         if !self.buf.is_empty() {
             return self.buf.pop_front();
         }
@@ -550,6 +1946,18 @@ impl Iterator for DiffByteRecordsIterator {
                     byte_record: Ok(byte_record_left),
                     record_hash: record_hash_left,
                 }) => {
+                    if !is_key_sampled(self.key_sample, record_hash_left.key) {
+                        self.metrics_sink.record_read(CsvSide::Left);
+                        recycle(
+                            &self.sender_csv_records_recycle,
+                            &mut self.metrics_sink,
+                            byte_record_left,
+                        );
+                        continue;
+                    }
+                    self.run_report.record_row_left();
+                    self.metrics_sink.record_read(CsvSide::Left);
+                    self.metrics_sink.record_hashed(CsvSide::Left);
                     let byte_record_left_line =
                         // TODO: the closure _might_ be a performance bottleneck!?
                         byte_record_left.position().map_or(0, |pos| pos.line());
@@ -558,6 +1966,9 @@ impl Iterator for DiffByteRecordsIterator {
                             if let HashMapValue::Initial(record_hash_right, byte_record_right) =
                                 hash_map_val
                             {
+                                self.bytes_unmatched = self
+                                    .bytes_unmatched
+                                    .saturating_sub(spill::approx_size(byte_record_right));
                                 if record_hash_left.record_hash != *record_hash_right {
                                     *hash_map_val = HashMapValue::Modified(
                                         byte_record_left,
@@ -572,6 +1983,7 @@ impl Iterator for DiffByteRecordsIterator {
                             }
                         }
                         None => {
+                            self.bytes_unmatched += spill::approx_size(&byte_record_left);
                             self.csv_records_left_map.insert(
                                 record_hash_left.key,
                                 HashMapValue::Initial(
@@ -581,38 +1993,51 @@ impl Iterator for DiffByteRecordsIterator {
                             );
                         }
                     }
+                    self.metrics_sink.map_sizes(
+                        self.csv_records_left_map.len(),
+                        self.csv_records_right_map.len(),
+                    );
+                    if let Err(spill_err) = self.spill_if_over_memory_budget() {
+                        self.buf.push_back(Err(spill_err.into()));
+                        break;
+                    }
                     if self.max_capacity_right_map.value() > 0
                         && byte_record_left_line % self.max_capacity_right_map.value() as u64 == 0
                     {
                         self.max_capacity_right_map.calc_new(byte_record_left_line);
+                        let len_before_flush = self.csv_records_right_map.len();
                         for (k, v) in self.csv_records_right_map.drain() {
                             match v {
                                 HashMapValue::Equal(byte_record_left, byte_record_right) => {
-                                    // can be recycled, so we send it upstream;
-                                    // if receiver is already gone, we ignore the error that occurs when sending,
-                                    // which only leads to the byte record not being recycled (it can't be recycled,
-                                    // because upstream has finished it's work)
-                                    let _ = self.sender_csv_records_recycle.send(byte_record_left);
-                                    let _ = self.sender_csv_records_recycle.send(byte_record_right);
+                                    self.run_report.record_unchanged();
+                                    if let Some(equal_buf) = &mut self.equal_buf {
+                                        equal_buf.push_back(spill::equal_pair(
+                                            byte_record_left,
+                                            byte_record_right,
+                                        ));
+                                        continue;
+                                    }
+                                    // can be recycled, so we send it upstream
+                                    recycle(
+                                        &self.sender_csv_records_recycle,
+                                        &mut self.metrics_sink,
+                                        byte_record_left,
+                                    );
+                                    recycle(
+                                        &self.sender_csv_records_recycle,
+                                        &mut self.metrics_sink,
+                                        byte_record_right,
+                                    );
                                 }
                                 HashMapValue::Initial(_hash, ref _byte_record) => {
                                     // put it back, because we don't know what to do with this value yet
                                     self.intermediate_right_map.insert(k, v);
                                 }
                                 HashMapValue::Modified(left_byte_record, right_byte_record) => {
-                                    let fields_modified = left_byte_record
-                                        .iter()
-                                        .enumerate()
-                                        .zip(right_byte_record.iter())
-                                        .fold(
-                                            Vec::new(),
-                                            |mut acc, ((idx, field_left), field_right)| {
-                                                if field_left != field_right {
-                                                    acc.push(idx);
-                                                }
-                                                acc
-                                            },
-                                        );
+                                    let fields_modified = modified_field_indices(
+                                        &left_byte_record,
+                                        &right_byte_record,
+                                    );
                                     let left_byte_record_line = left_byte_record
                                         .position()
                                         // TODO: handle error (although it shouldn't error here)
@@ -641,6 +2066,11 @@ impl Iterator for DiffByteRecordsIterator {
                             &mut self.intermediate_right_map,
                             &mut self.csv_records_right_map,
                         );
+                        self.metrics_sink.map_flushed(
+                            CsvSide::Right,
+                            len_before_flush - self.csv_records_right_map.len(),
+                            self.csv_records_right_map.len(),
+                        );
                         if !self.buf.is_empty() {
                             break;
                         }
@@ -650,6 +2080,7 @@ impl Iterator for DiffByteRecordsIterator {
                     byte_record: Err(byte_record_left_err),
                     ..
                 }) => {
+                    self.metrics_sink.record_read(CsvSide::Left);
                     self.buf.push_back(Err(byte_record_left_err));
                     break;
                 }
@@ -657,6 +2088,18 @@ impl Iterator for DiffByteRecordsIterator {
                     byte_record: Ok(byte_record_right),
                     record_hash: record_hash_right,
                 }) => {
+                    if !is_key_sampled(self.key_sample, record_hash_right.key) {
+                        self.metrics_sink.record_read(CsvSide::Right);
+                        recycle(
+                            &self.sender_csv_records_recycle,
+                            &mut self.metrics_sink,
+                            byte_record_right,
+                        );
+                        continue;
+                    }
+                    self.run_report.record_row_right();
+                    self.metrics_sink.record_read(CsvSide::Right);
+                    self.metrics_sink.record_hashed(CsvSide::Right);
                     // TODO: the closure _might_ be a performance bottleneck!?
                     let byte_record_right_line =
                         byte_record_right.position().map_or(0, |pos| pos.line());
@@ -665,6 +2108,9 @@ impl Iterator for DiffByteRecordsIterator {
                             if let HashMapValue::Initial(record_hash_left, byte_record_left) =
                                 hash_map_val
                             {
+                                self.bytes_unmatched = self
+                                    .bytes_unmatched
+                                    .saturating_sub(spill::approx_size(byte_record_left));
                                 if *record_hash_left != record_hash_right.record_hash {
                                     *hash_map_val = HashMapValue::Modified(
                                         std::mem::take(byte_record_left),
@@ -679,6 +2125,7 @@ impl Iterator for DiffByteRecordsIterator {
                             }
                         }
                         None => {
+                            self.bytes_unmatched += spill::approx_size(&byte_record_right);
                             self.csv_records_right_map.insert(
                                 record_hash_right.key,
                                 HashMapValue::Initial(
@@ -688,38 +2135,51 @@ impl Iterator for DiffByteRecordsIterator {
                             );
                         }
                     }
+                    self.metrics_sink.map_sizes(
+                        self.csv_records_left_map.len(),
+                        self.csv_records_right_map.len(),
+                    );
+                    if let Err(spill_err) = self.spill_if_over_memory_budget() {
+                        self.buf.push_back(Err(spill_err.into()));
+                        break;
+                    }
                     if self.max_capacity_left_map.value() > 0
                         && byte_record_right_line % self.max_capacity_left_map.value() as u64 == 0
                     {
                         self.max_capacity_left_map.calc_new(byte_record_right_line);
+                        let len_before_flush = self.csv_records_left_map.len();
                         for (k, v) in self.csv_records_left_map.drain() {
                             match v {
                                 HashMapValue::Equal(byte_record_left, byte_record_right) => {
-                                    // can be recycled, so we send it upstream;
-                                    // if receiver is already gone, we ignore the error that occurs when sending,
-                                    // which only leads to the byte record not being recycled (it can't be recycled,
-                                    // because upstream has finished it's work)
-                                    let _ = self.sender_csv_records_recycle.send(byte_record_left);
-                                    let _ = self.sender_csv_records_recycle.send(byte_record_right);
+                                    self.run_report.record_unchanged();
+                                    if let Some(equal_buf) = &mut self.equal_buf {
+                                        equal_buf.push_back(spill::equal_pair(
+                                            byte_record_left,
+                                            byte_record_right,
+                                        ));
+                                        continue;
+                                    }
+                                    // can be recycled, so we send it upstream
+                                    recycle(
+                                        &self.sender_csv_records_recycle,
+                                        &mut self.metrics_sink,
+                                        byte_record_left,
+                                    );
+                                    recycle(
+                                        &self.sender_csv_records_recycle,
+                                        &mut self.metrics_sink,
+                                        byte_record_right,
+                                    );
                                 }
                                 HashMapValue::Initial(_hash, ref _byte_record) => {
                                     // put it back, because we don't know what to do with this value yet
                                     self.intermediate_left_map.insert(k, v);
                                 }
                                 HashMapValue::Modified(left_byte_record, right_byte_record) => {
-                                    let fields_modified = left_byte_record
-                                        .iter()
-                                        .enumerate()
-                                        .zip(right_byte_record.iter())
-                                        .fold(
-                                            Vec::new(),
-                                            |mut acc, ((idx, field_left), field_right)| {
-                                                if field_left != field_right {
-                                                    acc.push(idx);
-                                                }
-                                                acc
-                                            },
-                                        );
+                                    let fields_modified = modified_field_indices(
+                                        &left_byte_record,
+                                        &right_byte_record,
+                                    );
                                     let left_byte_record_line = left_byte_record
                                         .position()
                                         .expect("a record position")
@@ -746,6 +2206,11 @@ impl Iterator for DiffByteRecordsIterator {
                             &mut self.intermediate_left_map,
                             &mut self.csv_records_left_map,
                         );
+                        self.metrics_sink.map_flushed(
+                            CsvSide::Left,
+                            len_before_flush - self.csv_records_left_map.len(),
+                            self.csv_records_left_map.len(),
+                        );
                         if !self.buf.is_empty() {
                             break;
                         }
@@ -755,6 +2220,7 @@ impl Iterator for DiffByteRecordsIterator {
                     byte_record: Err(e),
                     ..
                 }) => {
+                    self.metrics_sink.record_read(CsvSide::Right);
                     self.buf.push_back(Err(e));
                     break;
                 }
@@ -765,13 +2231,25 @@ impl Iterator for DiffByteRecordsIterator {
             return self.buf.pop_front();
         }
 
+        // both hashing tasks have finished, so their header infos have been sent (we must not wait for them earlier,
+        // because a hashing task might be blocked on sending its records, e.g. on a thread-pool with a single thread)
+        if !self.is_header_diff_checked {
+            self.is_header_diff_checked = true;
+            if let Some(header_diff) = self.next_header_diff() {
+                return Some(header_diff);
+            }
+        }
+
+        if let Some(spilled_diff) = self.next_spilled_diff_byte_record() {
+            return Some(spilled_diff);
+        }
+
         let iter_left_map = self
             .csv_records_left_map_iter
             .get_or_insert(std::mem::take(&mut self.csv_records_left_map).into_iter());
 
-        let mut iter_left_map =
-            iter_left_map.skip_while(|(_, v)| matches!(v, HashMapValue::Equal(_, _)));
-        match iter_left_map.next() {
+        let next_left = skip_equal(iter_left_map, &mut self.equal_buf, &mut self.run_report);
+        match next_left {
             Some((_, HashMapValue::Initial(_hash, byte_record))) => {
                 let line = byte_record.position().expect("a record position").line();
                 return Some(Ok(DiffByteRecord::Delete(ByteRecordLineInfo::new(
@@ -780,16 +2258,7 @@ impl Iterator for DiffByteRecordsIterator {
                 ))));
             }
             Some((_, HashMapValue::Modified(left_byte_record, right_byte_record))) => {
-                let fields_modified = left_byte_record
-                    .iter()
-                    .enumerate()
-                    .zip(right_byte_record.iter())
-                    .fold(Vec::new(), |mut acc, ((idx, field_left), field_right)| {
-                        if field_left != field_right {
-                            acc.push(idx);
-                        }
-                        acc
-                    });
+                let fields_modified = modified_field_indices(&left_byte_record, &right_byte_record);
                 let left_byte_record_line = left_byte_record
                     .position()
                     .expect("a record position")
@@ -811,9 +2280,8 @@ impl Iterator for DiffByteRecordsIterator {
             .csv_records_right_map_iter
             .get_or_insert(std::mem::take(&mut self.csv_records_right_map).into_iter());
 
-        let mut iter_right_map =
-            iter_right_map.skip_while(|(_, v)| matches!(v, HashMapValue::Equal(_, _)));
-        match iter_right_map.next() {
+        let next_right = skip_equal(iter_right_map, &mut self.equal_buf, &mut self.run_report);
+        match next_right {
             Some((_, HashMapValue::Initial(_hash, byte_record))) => {
                 let line = byte_record.position().expect("a record position").line();
                 return Some(Ok(DiffByteRecord::Add(ByteRecordLineInfo::new(
@@ -822,16 +2290,7 @@ impl Iterator for DiffByteRecordsIterator {
                 ))));
             }
             Some((_, HashMapValue::Modified(left_byte_record, right_byte_record))) => {
-                let fields_modified = left_byte_record
-                    .iter()
-                    .enumerate()
-                    .zip(right_byte_record.iter())
-                    .fold(Vec::new(), |mut acc, ((idx, field_left), field_right)| {
-                        if field_left != field_right {
-                            acc.push(idx);
-                        }
-                        acc
-                    });
+                let fields_modified = modified_field_indices(&left_byte_record, &right_byte_record);
                 let left_byte_record_line = left_byte_record
                     .position()
                     .expect("a record position")
@@ -852,21 +2311,297 @@ impl Iterator for DiffByteRecordsIterator {
     }
 }
 
+/// Send `byte_record` to the hashing tasks, so that it can be reused.
+///
+/// If the receiver is already gone or enough records are waiting to be reused (see [`Preset`](crate::preset::Preset)),
+/// we ignore the error that occurs when sending, which only leads to the byte record not being recycled.
+fn recycle<M: DiffMetricsSink>(
+    sender_csv_records_recycle: &Sender<csv::ByteRecord>,
+    metrics_sink: &mut M,
+    byte_record: csv::ByteRecord,
+) {
+    if sender_csv_records_recycle.try_send(byte_record).is_ok() {
+        metrics_sink.records_recycled(1);
+    }
+}
+
+/// Whether the records with the key hash `key` are part of the sample (all records are, without a sample).
+#[inline]
+fn is_key_sampled(key_sample: Option<NonZeroU64>, key: u128) -> bool {
+    key_sample.map_or(true, |one_in| key % u128::from(one_in.get()) == 0)
+}
+
+/// Return the next record of `map_iter`, that is not equal in both CSVs. Equal records are counted in `run_report`
+/// and put into `equal_buf`, if they are kept.
+fn skip_equal(
+    map_iter: &mut IntoIter<u128, HashMapValue<csv::ByteRecord>>,
+    equal_buf: &mut Option<VecDeque<(ByteRecordLineInfo, ByteRecordLineInfo)>>,
+    run_report: &mut DiffRunReport,
+) -> Option<(u128, HashMapValue<csv::ByteRecord>)> {
+    loop {
+        match map_iter.next() {
+            Some((_, HashMapValue::Equal(byte_record_left, byte_record_right))) => {
+                run_report.record_unchanged();
+                if let Some(equal_buf) = equal_buf {
+                    equal_buf.push_back(spill::equal_pair(byte_record_left, byte_record_right));
+                }
+            }
+            next => break next,
+        }
+    }
+}
+
+impl<M: DiffMetricsSink> Iterator for DiffByteRecordsIterator<M> {
+    type Item = csv::Result<DiffByteRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        self.try_recv_header_infos();
+        if self.max_diffs == Some(self.diffs_emitted) {
+            self.stop_at_max_diffs();
+            return None;
+        }
+        if self.cancellation_token.is_cancelled() {
+            return Some(Err(self.cancel()));
+        }
+        if self.cancellation_token.is_timed_out() {
+            return Some(Err(self.time_out()));
+        }
+        let next = catch_panic(|| loop {
+            match self.next_diff_byte_record() {
+                Some(Ok(diff_record)) => {
+                    self.resolve_field_comparators_by_header();
+                    // a modified record, that is equal according to the field comparators, is emitted as equal record
+                    let equal_candidate = match (&diff_record, &self.equal_buf) {
+                        (DiffByteRecord::Modify { delete, add, .. }, Some(_))
+                            if !self.field_comparators.is_empty() =>
+                        {
+                            Some((delete.clone(), add.clone()))
+                        }
+                        _ => None,
+                    };
+                    match self.field_comparators.apply(diff_record) {
+                        Some(diff_record) => {
+                            break Some(Ok(self.field_comparators.redact(diff_record)))
+                        }
+                        None => {
+                            self.run_report.record_unchanged();
+                            if let (Some(equal_pair), Some(equal_buf)) =
+                                (equal_candidate, &mut self.equal_buf)
+                            {
+                                equal_buf.push_back(equal_pair);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                next => break next,
+            }
+        });
+        // A hashing task reports its panic _before_ its channel disconnects, so we check for it
+        // after receiving - that way, a result, that is truncated due to a panic, is never emitted.
+        let next = match (next, self.receiver_panic.try_recv()) {
+            (_, Ok(task_panic_err)) | (Err(task_panic_err), _) => {
+                self.is_finished = true;
+                return Some(Err(task_panic_err.into()));
+            }
+            (Ok(next), _) => next,
+        };
+        if self.cancellation_token.is_cancelled() {
+            return Some(Err(self.cancel()));
+        }
+        // the hashing tasks might have stopped early, so `next` could be an unmatched record, that is not an actual difference
+        if self.cancellation_token.is_timed_out() {
+            return Some(Err(self.time_out()));
+        }
+        match &next {
+            Some(Ok(diff_record)) => {
+                self.run_report.record(diff_record);
+                self.diffs_emitted += 1;
+                // stop right away, in case the caller doesn't ask for another record
+                if self.max_diffs == Some(self.diffs_emitted) {
+                    self.stop_at_max_diffs();
+                }
+            }
+            // both hashing tasks have sent their key stats, before the channel of parse results has been disconnected
+            None => {
+                self.is_finished = true;
+                if let Some(record_spill) = &mut self.record_spill {
+                    self.run_report
+                        .add_unchanged(record_spill.take_num_of_equal());
+                }
+                if let Ok(key_stats_left) = self.receiver_key_stats_left.try_recv() {
+                    self.run_report.set_key_stats_left(key_stats_left);
+                }
+                if let Ok(key_stats_right) = self.receiver_key_stats_right.try_recv() {
+                    self.run_report.set_key_stats_right(key_stats_right);
+                }
+                if let Ok(skipped_records_left) = self.receiver_skipped_records_left.try_recv() {
+                    self.run_report
+                        .set_skipped_records_left(skipped_records_left);
+                }
+                if let Ok(skipped_records_right) = self.receiver_skipped_records_right.try_recv() {
+                    self.run_report
+                        .set_skipped_records_right(skipped_records_right);
+                }
+            }
+            _ => (),
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.is_finished {
+            return (0, Some(0));
+        }
+        // a buffered record is always emitted, unless a field comparator drops it (a cancellation replaces it with an error)
+        let lower = usize::from(!self.buf.is_empty() && self.field_comparators.is_empty());
+        // once the map of the left CSV is drained, the hashing tasks have finished and only the unmatched records are left
+        let upper = self
+            .csv_records_left_map_iter
+            .as_ref()
+            .map(|iter_left_map| {
+                let num_unmatched = self.buf.len()
+                    + iter_left_map.len()
+                    + self
+                        .csv_records_right_map_iter
+                        .as_ref()
+                        .map_or(self.csv_records_right_map.len(), ExactSizeIterator::len);
+                let num_unmatched = match self.max_diffs {
+                    Some(max_diffs) => min(num_unmatched, max_diffs - self.diffs_emitted),
+                    None => num_unmatched,
+                };
+                // a cancellation or timeout is still reported as an error
+                max(num_unmatched, 1)
+            });
+        (lower, upper)
+    }
+}
+
+impl<M: DiffMetricsSink> FusedIterator for DiffByteRecordsIterator<M> {}
+
+/// Emits the differences between two CSVs together with the records, that are equal in both CSVs,
+/// as [`Result`](::csv::Result)<[`AnnotatedByteRecord`](crate::diff_row::AnnotatedByteRecord)>, after they have been compared with
+/// [`CsvByteDiff.diff_with_equal_records`](crate::csv_diff::CsvByteDiff::diff_with_equal_records).
+///
+/// Like [`DiffByteRecordsIterator`], it produces values _unordered_ (with regard to the line in the CSV).
+pub struct AnnotatedByteRecordsIterator {
+    inner: DiffByteRecordsIterator,
+}
+
+impl AnnotatedByteRecordsIterator {
+    /// Return the [`DiffRunReport`](crate::run_report::DiffRunReport) of all records, that have been processed so far.
+    ///
+    /// The report is only complete, after this iterator has been exhausted (i.e. it has returned `None`).
+    pub fn run_report(&self) -> DiffRunReport {
+        self.inner.run_report()
+    }
+
+    pub(crate) fn diff_headers(&self) -> DiffHeaders {
+        self.inner.diff_headers()
+    }
+}
+
+impl Iterator for AnnotatedByteRecordsIterator {
+    type Item = csv::Result<AnnotatedByteRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((left, right)) = self.inner.next_equal() {
+            return Some(Ok(AnnotatedByteRecord::Equal { left, right }));
+        }
+        match self.inner.next() {
+            Some(diff_record_res) => Some(diff_record_res.map(AnnotatedByteRecord::Diff)),
+            // equal records might have been found, while looking for the next difference
+            None => self
+                .inner
+                .next_equal()
+                .map(|(left, right)| Ok(AnnotatedByteRecord::Equal { left, right })),
+        }
+    }
+}
+
+impl FusedIterator for AnnotatedByteRecordsIterator {}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        diff_result::{ColumnIdx, ColumnIdxError},
-        diff_row::{ByteRecordLineInfo, DiffByteRecord},
+        diff_result::{
+            ColumnIdx, ColumnIdxError, ColumnSortType, CsvWriteStyle, DiffByteRecordsMergeError,
+            DiffHeaders, SortColumn,
+        },
+        diff_row::{ByteRecordLineInfo, DiffByteRecord, DiffByteRecordKind, LineNum},
     };
     use pretty_assertions::assert_eq;
     use std::error::Error;
 
     use super::DiffByteRecords;
 
+    #[test]
+    fn sort_by_line_orders_moved_records_starting_at_the_same_line_by_deleted_line() {
+        let moved_up = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b"]), 5),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x"]), 3),
+            field_indices: vec![1],
+        };
+        let moved_down = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "d"]), 3),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "y"]), 5),
+            field_indices: vec![1],
+        };
+        let mut diff_records = DiffByteRecords::from(vec![moved_up.clone(), moved_down.clone()]);
+        let mut diff_records_reversed =
+            DiffByteRecords::from(vec![moved_down.clone(), moved_up.clone()]);
+
+        diff_records.sort_by_line();
+        diff_records_reversed.sort_by_line();
+
+        assert_eq!(diff_records.as_slice(), &[moved_down, moved_up]);
+        assert_eq!(diff_records, diff_records_reversed);
+        assert_eq!(diff_records.page(1, 5), &diff_records.as_slice()[1..]);
+    }
+
+    #[test]
+    fn sort_by_line_many_records_in_reverse_order() {
+        let num_of_records = 100_000;
+        let mut diff_records = DiffByteRecords::from(
+            (0..num_of_records)
+                .rev()
+                .map(|line| match line % 3 {
+                    0 => DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["a"]),
+                        line,
+                    )),
+                    1 => DiffByteRecord::Add(ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["b"]),
+                        line,
+                    )),
+                    _ => DiffByteRecord::Modify {
+                        delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c"]), line),
+                        add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d"]), line),
+                        field_indices: vec![0],
+                    },
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        diff_records.sort_by_line();
+
+        assert_eq!(diff_records.as_slice().len(), num_of_records as usize);
+        assert!(diff_records
+            .iter()
+            .map(|diff_record| match diff_record.line_num() {
+                LineNum::OneSide(line) => line,
+                LineNum::BothSides { for_deleted, .. } => for_deleted,
+            })
+            .eq(0..num_of_records));
+    }
+
     #[test]
     fn sort_by_col_selection_of_cols_is_empty_order_does_not_change() -> Result<(), Box<dyn Error>>
     {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["d", "e", "f"]),
                 3,
@@ -889,7 +2624,7 @@ mod tests {
     #[test]
     fn sort_by_col_all_equal_delete_before_add_order_does_not_change() -> Result<(), Box<dyn Error>>
     {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "x", "y"]),
                 3,
@@ -911,7 +2646,7 @@ mod tests {
 
     #[test]
     fn sort_by_second_col_a_in_add_is_less_than_b_in_modify_delete() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "b", "_"]),
                 3,
@@ -924,7 +2659,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![1])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "a", "_"]),
                 4,
@@ -942,7 +2677,7 @@ mod tests {
 
     #[test]
     fn sort_by_certain_col_idx_twice_is_ok() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["az", "_", "_"]),
                 3,
@@ -955,7 +2690,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0, 0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "_", "_"]),
                 4,
@@ -971,10 +2706,94 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sort_by_col_as_integer_sorts_numerically_and_unparsable_values_last(
+    ) -> Result<(), Box<dyn Error>> {
+        let delete = |value: &str, line| {
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["_", value]),
+                line,
+            ))
+        };
+        let mut diff_records = DiffByteRecords::from(vec![
+            delete("n/a", 2),
+            delete("10", 3),
+            delete(" 9", 4),
+            delete("", 5),
+            delete("-100", 6),
+        ]);
+
+        diff_records
+            .sort_by_columns(vec![SortColumn::new(1).sort_type(ColumnSortType::Integer)])?;
+
+        let expected = DiffByteRecords::from(vec![
+            delete("-100", 6),
+            delete(" 9", 4),
+            delete("10", 3),
+            delete("", 5),
+            delete("n/a", 2),
+        ]);
+        assert_eq!(diff_records, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn sort_by_col_as_float_then_by_bytes() -> Result<(), Box<dyn Error>> {
+        let add = |values: Vec<&str>, line| {
+            DiffByteRecord::Add(ByteRecordLineInfo::new(csv::ByteRecord::from(values), line))
+        };
+        let mut diff_records = DiffByteRecords::from(vec![
+            add(vec!["1e1", "b"], 2),
+            add(vec!["9.5", "a"], 3),
+            add(vec!["10.0", "a"], 4),
+        ]);
+
+        diff_records.sort_by_columns(vec![
+            SortColumn::new(0).sort_type(ColumnSortType::Float),
+            SortColumn::new(1),
+        ])?;
+
+        let expected = DiffByteRecords::from(vec![
+            add(vec!["9.5", "a"], 3),
+            add(vec!["10.0", "a"], 4),
+            add(vec!["1e1", "b"], 2),
+        ]);
+        assert_eq!(diff_records, expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn sort_by_col_as_date_time_with_format() -> Result<(), Box<dyn Error>> {
+        let delete = |value: &str, line| {
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec![value]),
+                line,
+            ))
+        };
+        let mut diff_records = DiffByteRecords::from(vec![
+            delete("02.01.2024", 2),
+            delete("31.12.2023", 3),
+            delete("01.02.2024", 4),
+        ]);
+
+        diff_records.sort_by_columns(vec![
+            SortColumn::new(0).sort_type(ColumnSortType::DateTime("%d.%m.%Y".to_owned()))
+        ])?;
+
+        let expected = DiffByteRecords::from(vec![
+            delete("31.12.2023", 3),
+            delete("02.01.2024", 2),
+            delete("01.02.2024", 4),
+        ]);
+        assert_eq!(diff_records, expected);
+        Ok(())
+    }
+
     #[test]
     fn sort_by_first_and_second_col_first_col_val_is_equal_so_second_col_decides_order(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["x", "b", "_"]),
                 3,
@@ -987,7 +2806,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0, 1])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["x", "a", "_"]),
                 4,
@@ -1006,7 +2825,7 @@ mod tests {
     #[test]
     fn sort_by_first_second_and_third_col_first_and_second_col_val_is_equal_so_third_col_decides_order(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["x", "a", "z"]),
                 3,
@@ -1019,7 +2838,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0, 1, 2])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["x", "a", "i"]),
                 4,
@@ -1038,7 +2857,7 @@ mod tests {
     #[test]
     fn sort_by_first_second_and_third_col_back_to_front_third_and_second_col_val_is_equal_so_first_col_decides_order(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["2", "a", "z"]),
                 3,
@@ -1051,7 +2870,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![2, 1, 0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["1", "a", "z"]),
                 4,
@@ -1070,7 +2889,7 @@ mod tests {
     #[test]
     fn sort_by_col_delete_must_be_smaller_than_add_when_otherwise_identical(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["same", "_", "_"]),
                 4,
@@ -1083,7 +2902,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["same", "_", "_"]),
                 5,
@@ -1102,7 +2921,7 @@ mod tests {
     #[test]
     fn sort_by_col_with_three_items_first_and_second_by_first_col_second_and_third_by_second_col(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["1", "b", "_"]),
                 3,
@@ -1119,7 +2938,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0, 1])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["0", "a", "_"]),
                 4,
@@ -1141,7 +2960,7 @@ mod tests {
 
     #[test]
     fn sort_by_col_delete_compared_with_modify_delete() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1165,7 +2984,7 @@ mod tests {
     #[test]
     fn sort_by_col_delete_compared_with_modify_delete_are_equal_fall_back_to_compare_with_modify_add(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 2),
@@ -1189,7 +3008,7 @@ mod tests {
     #[test]
     fn sort_by_col_delete_must_be_smaller_than_modify_when_otherwise_identical(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1203,7 +3022,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["c", "_", "_"]),
                 4,
@@ -1222,7 +3041,7 @@ mod tests {
 
     #[test]
     fn sort_by_col_modify_delete_compared_with_add() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1245,7 +3064,7 @@ mod tests {
 
     #[test]
     fn sort_by_col_add_compared_with_modify_delete() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["b", "_", "_"]),
                 4,
@@ -1259,7 +3078,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1279,7 +3098,7 @@ mod tests {
     #[test]
     fn sort_by_col_modify_delete_compared_with_add_are_equal_fall_back_to_compare_with_modify_add(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1293,7 +3112,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "_", "_"]),
                 4,
@@ -1313,7 +3132,7 @@ mod tests {
     #[test]
     fn sort_by_col_add_must_be_greater_than_modify_when_otherwise_identical(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 2),
@@ -1336,7 +3155,7 @@ mod tests {
 
     #[test]
     fn sort_by_col_modify_delete_compared_with_modify_delete() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["d", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 2),
@@ -1351,7 +3170,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["b", "_", "_"]), 2),
@@ -1372,7 +3191,7 @@ mod tests {
     #[test]
     fn sort_by_col_modify_delete_compared_with_modify_delete_are_equal_fall_back_to_compare_modify_add_with_modify_add(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["b", "_", "_"]), 2),
@@ -1387,7 +3206,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "_", "_"]), 2),
@@ -1408,7 +3227,7 @@ mod tests {
     #[test]
     fn sort_by_col_modify_cmp_with_add_cmp_with_modify_cmp_with_delete(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Modify {
                 delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "_", "_"]), 1),
                 add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["b", "_", "_"]), 2),
@@ -1431,7 +3250,7 @@ mod tests {
 
         diff_records.sort_by_columns(vec![0])?;
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "_", "_"]),
                 4,
@@ -1459,7 +3278,7 @@ mod tests {
 
     #[test]
     fn sort_by_col_idx_out_of_bounds_err() -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["a", "b", "c"]),
                 3,
@@ -1480,7 +3299,7 @@ mod tests {
     #[test]
     fn sort_by_col_first_idx_ok_and_cmp_as_equal_second_idx_out_of_bounds_err_order_stays_the_same(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "same", "_"]),
                 3,
@@ -1505,7 +3324,7 @@ mod tests {
     #[test]
     fn sort_by_col_first_idx_ok_and_cmp_not_equal_second_idx_out_of_bounds_but_no_err_because_first_idx_already_sorted(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "b", "_"]),
                 3,
@@ -1520,7 +3339,7 @@ mod tests {
 
         assert_eq!(res, Ok(()));
 
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "a", "_"]),
                 4,
@@ -1539,7 +3358,7 @@ mod tests {
     #[test]
     fn sort_by_col_first_idx_out_of_bounds_err_second_idx_ok_sort_by_second_idx(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "b", "_"]),
                 3,
@@ -1555,7 +3374,7 @@ mod tests {
         assert_eq!(res, Err(ColumnIdxError::IdxOutOfBounds { idx: 3, len: 3 }));
 
         // it is still sorted by the second column
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "a", "_"]),
                 4,
@@ -1574,7 +3393,7 @@ mod tests {
     #[test]
     fn sort_by_col_first_idx_out_of_bounds_err_second_idx_ok_third_idx_out_of_bounds_sort_by_second_idx(
     ) -> Result<(), Box<dyn Error>> {
-        let mut diff_records = DiffByteRecords(vec![
+        let mut diff_records = DiffByteRecords::from(vec![
             DiffByteRecord::Delete(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "b", "_"]),
                 3,
@@ -1591,7 +3410,7 @@ mod tests {
         assert_eq!(res, Err(ColumnIdxError::IdxOutOfBounds { idx: 3, len: 3 }));
 
         // but it is still sorted by the second column
-        let expected = DiffByteRecords(vec![
+        let expected = DiffByteRecords::from(vec![
             DiffByteRecord::Add(ByteRecordLineInfo::new(
                 csv::ByteRecord::from(vec!["_", "a", "_"]),
                 4,
@@ -1606,4 +3425,384 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn sort_by_col_all_errors_reports_every_out_of_bounds_col_once_and_sorts_by_the_others(
+    ) -> Result<(), Box<dyn Error>> {
+        let mut diff_records = DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["_", "b", "_"]),
+                3,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["_", "a", "_", "x"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["_", "a", "_"]), 4),
+                field_indices: vec![3],
+            },
+        ]);
+
+        let res = diff_records.sort_by_columns_all_errors(vec![3, 1, 4, 3]);
+
+        assert_eq!(
+            res,
+            Err(vec![
+                ColumnIdxError::IdxOutOfBounds { idx: 3, len: 3 },
+                ColumnIdxError::IdxOutOfBounds { idx: 4, len: 3 },
+            ])
+        );
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["_", "a", "_", "x"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["_", "a", "_"]), 4),
+                field_indices: vec![3],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["_", "b", "_"]),
+                3,
+            )),
+        ]);
+        assert_eq!(diff_records, expected);
+        assert_eq!(diff_records.sort_by_columns_all_errors(vec![1, 2]), Ok(()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn group_by_column_groups_are_ordered_by_key_and_keep_record_order(
+    ) -> Result<(), Box<dyn Error>> {
+        let diff_records = DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["north", "1"]),
+                2,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["east", "2"]),
+                3,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["north", "3"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["north", "4"]), 4),
+                field_indices: vec![1],
+            },
+        ]);
+
+        let groups = diff_records.group_by_column(0)?;
+
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (group.key(), group.diff_records().to_vec()))
+                .collect::<Vec<_>>(),
+            vec![
+                (&b"east"[..], vec![&diff_records.as_slice()[1]]),
+                (
+                    &b"north"[..],
+                    vec![&diff_records.as_slice()[0], &diff_records.as_slice()[2]]
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn group_by_column_idx_out_of_bounds_err() {
+        let diff_records = DiffByteRecords::from(vec![DiffByteRecord::Add(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b"]), 2),
+        )]);
+
+        assert_eq!(
+            diff_records.group_by_column(2),
+            Err(ColumnIdxError::IdxOutOfBounds { idx: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn invert_swaps_add_and_delete_and_sides_of_modify() {
+        let diff_records = DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b"]),
+                2,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "d"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "x"]), 4),
+                field_indices: vec![1],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["e", "f"]),
+                5,
+            )),
+        ]);
+
+        let expected = DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b"]),
+                2,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "x"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["c", "d"]), 3),
+                field_indices: vec![1],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["e", "f"]),
+                5,
+            )),
+        ]);
+
+        let inverted = diff_records.clone().invert();
+
+        assert_eq!(inverted, expected);
+        assert_eq!(inverted.invert(), diff_records);
+    }
+
+    #[test]
+    fn index_by_key_uses_deleted_record_of_modify_and_keeps_first_record_of_duplicate_keys() {
+        let modify = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "b"]), 2),
+            field_indices: vec![1],
+        };
+        let add = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["2", "c"]),
+            3,
+        ));
+        let add_same_key = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["2", "d"]),
+            4,
+        ));
+        let diff_records = DiffByteRecords::from(vec![modify.clone(), add.clone(), add_same_key]);
+
+        let diff_records_by_key = diff_records.index_by_key(vec![0]);
+
+        assert_eq!(diff_records_by_key.len(), 2);
+        assert_eq!(
+            diff_records_by_key.get([&b"1"[..]].as_slice()),
+            Some(&&modify)
+        );
+        assert_eq!(diff_records_by_key.get([&b"2"[..]].as_slice()), Some(&&add));
+        assert_eq!(diff_records_by_key.get([&b"3"[..]].as_slice()), None);
+    }
+
+    #[test]
+    fn merge_disjoint_keys_appends_and_duplicates_are_kept_once() -> Result<(), Box<dyn Error>> {
+        let add = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["1", "a"]),
+            2,
+        ));
+        let delete = DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["2", "b"]),
+            3,
+        ));
+        let mut diff_records = DiffByteRecords::from(vec![add.clone()]);
+
+        diff_records.merge(
+            DiffByteRecords::from(vec![delete.clone(), add.clone()]),
+            vec![0],
+        )?;
+
+        assert_eq!(diff_records, DiffByteRecords::from(vec![add, delete]));
+        Ok(())
+    }
+
+    #[test]
+    fn merge_delete_and_add_with_same_key_is_modify_or_removed() -> Result<(), Box<dyn Error>> {
+        let mut diff_records = DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["1", "a"]),
+                2,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["2", "b"]),
+                3,
+            )),
+        ]);
+
+        diff_records.merge(
+            DiffByteRecords::from(vec![
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["1", "x"]),
+                    7,
+                )),
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "b"]),
+                    8,
+                )),
+            ]),
+            vec![0],
+        )?;
+
+        assert_eq!(
+            diff_records,
+            DiffByteRecords::from(vec![DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "x"]), 7),
+                field_indices: vec![1],
+            }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge_conflicting_records_is_err_and_leaves_self_unchanged() {
+        let add = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["1", "a"]),
+            2,
+        ));
+        let mut diff_records = DiffByteRecords::from(vec![add.clone()]);
+
+        let res = diff_records.merge(
+            DiffByteRecords::from(vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["1", "b"]),
+                2,
+            ))]),
+            vec![0],
+        );
+
+        assert_eq!(
+            res,
+            Err(DiffByteRecordsMergeError::ConflictingRecords {
+                key: vec![b"1".to_vec()]
+            })
+        );
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "conflicting records for the same key (1)"
+        );
+        assert_eq!(diff_records, DiffByteRecords::from(vec![add]));
+    }
+
+    fn diff_records_for_retain() -> DiffByteRecords {
+        DiffByteRecords::from(vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["1", "north", "a"]),
+                2,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "east", "b"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "north", "b"]), 3),
+                field_indices: vec![1],
+            },
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["3", "south", "c"]),
+                4,
+            )),
+        ])
+    }
+
+    #[test]
+    fn retain_by_kind_keeps_only_given_kinds() {
+        let mut diff_records = diff_records_for_retain();
+
+        diff_records.retain_by_kind(vec![DiffByteRecordKind::Add, DiffByteRecordKind::Delete]);
+
+        assert_eq!(
+            diff_records
+                .iter()
+                .map(DiffByteRecord::kind)
+                .collect::<Vec<_>>(),
+            vec![DiffByteRecordKind::Add, DiffByteRecordKind::Delete]
+        );
+    }
+
+    #[test]
+    fn retain_by_column_modify_matches_on_either_side() {
+        let mut diff_records = diff_records_for_retain();
+
+        diff_records.retain_by_column(1, |field| field == b"north");
+
+        assert_eq!(
+            diff_records
+                .iter()
+                .map(DiffByteRecord::kind)
+                .collect::<Vec<_>>(),
+            vec![DiffByteRecordKind::Add, DiffByteRecordKind::Modify]
+        );
+    }
+
+    #[test]
+    fn write_csv_with_headers_and_quoted_fields() -> Result<(), Box<dyn Error>> {
+        let diff_records = DiffByteRecords(
+            vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["1", "lemon, yellow", "fruit"]),
+                        2,
+                    ),
+                    add: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["1", "lemon, yellow", "\"nut\""]),
+                        2,
+                    ),
+                    field_indices: vec![2],
+                },
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "cherry\nred", "fruit"]),
+                    3,
+                )),
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "banana", ""]),
+                    4,
+                )),
+            ],
+            DiffHeaders {
+                left: Some(csv::ByteRecord::from(vec!["id", "name", "kind"])),
+                right: None,
+            },
+        );
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        diff_records.write_csv(&mut writer, CsvWriteStyle::Compact)?;
+        assert_eq!(
+            String::from_utf8(writer.into_inner()?)?,
+            "operation,id,name,kind\n\
+             modify,1,\"lemon, yellow\",\"\"\"nut\"\"\"\n\
+             add,2,\"cherry\nred\",fruit\n\
+             delete,3,banana,\n"
+        );
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b';')
+            .from_writer(vec![]);
+        diff_records.write_csv(&mut writer, CsvWriteStyle::Split)?;
+        assert_eq!(
+            String::from_utf8(writer.into_inner()?)?,
+            "operation;id;name;kind\n\
+             modify_delete;1;lemon, yellow;fruit\n\
+             modify_add;1;lemon, yellow;\"\"\"nut\"\"\"\n\
+             add;2;\"cherry\nred\";fruit\n\
+             delete;3;banana;\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn write_csv_without_headers() -> Result<(), Box<dyn Error>> {
+        let diff_records = DiffByteRecords::from(vec![DiffByteRecord::Add(
+            ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 1),
+        )]);
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        diff_records.write_csv(&mut writer, CsvWriteStyle::Compact)?;
+
+        assert_eq!(String::from_utf8(writer.into_inner()?)?, "add,1,lemon\n");
+        Ok(())
+    }
+
+    #[test]
+    fn retain_by_key_and_filter() {
+        let mut diff_records = diff_records_for_retain();
+
+        diff_records.retain_by_key(&[0, 2], &["2", "b"]);
+
+        assert_eq!(
+            diff_records.as_slice(),
+            &diff_records_for_retain().as_slice()[1..2]
+        );
+        assert_eq!(
+            diff_records_for_retain()
+                .filter(|diff_record| diff_record.kind() == DiffByteRecordKind::Delete)
+                .as_slice(),
+            &diff_records_for_retain().as_slice()[2..]
+        );
+    }
 }
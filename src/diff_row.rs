@@ -1,3 +1,10 @@
+use crate::error_code::ErrorCode;
+use crate::simd::modified_field_indices;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io;
+use thiserror::Error;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum DiffByteRecord {
     Add(ByteRecordLineInfo),
@@ -7,6 +14,14 @@ pub enum DiffByteRecord {
         field_indices: Vec<usize>,
     },
     Delete(ByteRecordLineInfo),
+    /// A record, that is equal in both CSVs, but whose position has changed relative to the other equal records
+    /// (see [`CsvByteDiff::diff_with_moves`](crate::csv_diff::CsvByteDiff::diff_with_moves)).
+    /// `record` is the record of the left CSV.
+    Move {
+        record: csv::ByteRecord,
+        from_line: u64,
+        to_line: u64,
+    },
 }
 
 impl DiffByteRecord {
@@ -21,10 +36,187 @@ impl DiffByteRecord {
                 for_deleted: deleted.line,
                 for_added: added.line,
             },
+            Self::Move {
+                from_line, to_line, ..
+            } => LineNum::BothSides {
+                for_deleted: *from_line,
+                for_added: *to_line,
+            },
+        }
+    }
+
+    pub fn kind(&self) -> DiffByteRecordKind {
+        match self {
+            Self::Add(..) => DiffByteRecordKind::Add,
+            Self::Modify { .. } => DiffByteRecordKind::Modify,
+            Self::Delete(..) => DiffByteRecordKind::Delete,
+            Self::Move { .. } => DiffByteRecordKind::Move,
+        }
+    }
+
+    /// Invert this difference, so that it describes the change from right to left:
+    /// an `Add` becomes a `Delete` (and vice versa), the delete and add side of a `Modify` are swapped
+    /// and the lines of a `Move` are swapped.
+    pub fn invert(self) -> Self {
+        match self {
+            Self::Add(brli) => Self::Delete(brli),
+            Self::Delete(brli) => Self::Add(brli),
+            Self::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self::Modify {
+                delete: add,
+                add: delete,
+                field_indices,
+            },
+            Self::Move {
+                record,
+                from_line,
+                to_line,
+            } => Self::Move {
+                record,
+                from_line: to_line,
+                to_line: from_line,
+            },
+        }
+    }
+
+    /// Return the share of fields of a `Modify`, that are equal on both sides, as a value between `0.0` (all fields
+    /// have changed) and `1.0` (no field has changed), e.g. for ranking or thresholding modifications.
+    ///
+    /// Fields, that only exist on one side, count as changed. For an `Add` or a `Delete`, `None` is returned
+    /// and for a `Move`, whose fields are all equal, `1.0`.
+    /// # Example
+    /// ```
+    /// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    ///
+    /// let diff_record = DiffByteRecord::Modify {
+    ///     delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "3", "yellow"]), 2),
+    ///     add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lime", "3", "green"]), 2),
+    ///     field_indices: vec![1, 3],
+    /// };
+    ///
+    /// assert_eq!(diff_record.similarity(), Some(0.5));
+    /// ```
+    pub fn similarity(&self) -> Option<f64> {
+        match self {
+            Self::Add(..) | Self::Delete(..) => None,
+            Self::Move { .. } => Some(1.0),
+            Self::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                let num_of_fields = delete.byte_record.len().max(add.byte_record.len());
+                Some(if num_of_fields == 0 {
+                    1.0
+                } else {
+                    num_of_fields.saturating_sub(field_indices.len()) as f64 / num_of_fields as f64
+                })
+            }
+        }
+    }
+
+    /// Like [`similarity`](DiffByteRecord::similarity), but weighted by the bytes of the fields: return the share of bytes
+    /// of both sides of a `Modify`, that are in equal fields, as a value between `0.0` and `1.0`.
+    ///
+    /// This is useful, if a change of a short field (e.g. a flag) should weigh less than a change of a long one.
+    /// For an `Add` or a `Delete`, `None` is returned and for a `Move`, `1.0`.
+    pub fn byte_similarity(&self) -> Option<f64> {
+        match self {
+            Self::Add(..) | Self::Delete(..) => None,
+            Self::Move { .. } => Some(1.0),
+            Self::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                let num_of_bytes =
+                    delete.byte_record.as_slice().len() + add.byte_record.as_slice().len();
+                let num_of_bytes_changed = field_indices
+                    .iter()
+                    .map(|&idx| {
+                        delete.byte_record.get(idx).map_or(0, <[u8]>::len)
+                            + add.byte_record.get(idx).map_or(0, <[u8]>::len)
+                    })
+                    .sum::<usize>();
+                Some(if num_of_bytes == 0 {
+                    1.0
+                } else {
+                    num_of_bytes.saturating_sub(num_of_bytes_changed) as f64 / num_of_bytes as f64
+                })
+            }
+        }
+    }
+
+    /// Replace every record of this difference (both halves of a `Modify`) by the result of `f`, keeping the line numbers.
+    ///
+    /// This is meant for post-processing the fields (e.g. trimming or redacting them). The `field_indices` of a `Modify`
+    /// are not recomputed, so they still describe the fields, that have been different when comparing.
+    /// # Example
+    /// ```
+    /// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    ///
+    /// let diff_record = DiffByteRecord::Add(ByteRecordLineInfo::new(
+    ///     csv::ByteRecord::from(vec!["1", "secret"]),
+    ///     2,
+    /// ));
+    ///
+    /// let redacted = diff_record.map_records(|byte_record| {
+    ///     byte_record
+    ///         .iter()
+    ///         .enumerate()
+    ///         .map(|(idx, field)| if idx == 1 { b"***".as_slice() } else { field })
+    ///         .collect()
+    /// });
+    ///
+    /// assert_eq!(
+    ///     redacted,
+    ///     DiffByteRecord::Add(ByteRecordLineInfo::new(
+    ///         csv::ByteRecord::from(vec!["1", "***"]),
+    ///         2
+    ///     ))
+    /// );
+    /// ```
+    pub fn map_records<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(csv::ByteRecord) -> csv::ByteRecord,
+    {
+        match self {
+            Self::Add(brli) => Self::Add(brli.map_record(&mut f)),
+            Self::Delete(brli) => Self::Delete(brli.map_record(&mut f)),
+            Self::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self::Modify {
+                delete: delete.map_record(&mut f),
+                add: add.map_record(&mut f),
+                field_indices,
+            },
+            Self::Move {
+                record,
+                from_line,
+                to_line,
+            } => Self::Move {
+                record: f(record),
+                from_line,
+                to_line,
+            },
         }
     }
 }
 
+/// The kind of a [`DiffByteRecord`](DiffByteRecord) without its data.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum DiffByteRecordKind {
+    Add,
+    Modify,
+    Delete,
+    Move,
+}
+
 pub enum LineNum {
     OneSide(u64),
     BothSides { for_deleted: u64, for_added: u64 },
@@ -52,4 +244,906 @@ impl ByteRecordLineInfo {
     pub fn line(&self) -> u64 {
         self.line
     }
+
+    pub fn set_line(&mut self, line: u64) {
+        self.line = line;
+    }
+
+    /// Replace the record by the result of `f`, keeping the line number.
+    pub fn map_record<F>(self, f: F) -> Self
+    where
+        F: FnOnce(csv::ByteRecord) -> csv::ByteRecord,
+    {
+        Self {
+            byte_record: f(self.byte_record),
+            line: self.line,
+        }
+    }
+
+    /// Create a [`ByteRecordLineInfoBuilder`] in order to create a `ByteRecordLineInfo` field by field.
+    pub fn builder() -> ByteRecordLineInfoBuilder {
+        ByteRecordLineInfoBuilder::new()
+    }
+}
+
+/// Create a [`ByteRecordLineInfo`] field by field.
+/// # Example
+/// ```
+/// use csv_diff::diff_row::ByteRecordLineInfo;
+///
+/// let byte_record_line_info = ByteRecordLineInfo::builder()
+///     .field("1")
+///     .fields(["lemon", "fruit"])
+///     .line(2)
+///     .build();
+///
+/// assert_eq!(
+///     byte_record_line_info,
+///     ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "fruit"]), 2)
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ByteRecordLineInfoBuilder {
+    byte_record: csv::ByteRecord,
+    line: u64,
+}
+
+impl ByteRecordLineInfoBuilder {
+    /// Create a builder without fields and with line `0`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a field.
+    pub fn field(mut self, field: impl AsRef<[u8]>) -> Self {
+        self.byte_record.push_field(field.as_ref());
+        self
+    }
+
+    /// Append all `fields`.
+    pub fn fields<I, T>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        for field in fields {
+            self.byte_record.push_field(field.as_ref());
+        }
+        self
+    }
+
+    pub fn line(mut self, line: u64) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub fn build(self) -> ByteRecordLineInfo {
+        ByteRecordLineInfo::new(self.byte_record, self.line)
+    }
+}
+
+/// A [`DiffByteRecord`] with UTF-8 fields, for when working with bytes is not needed.
+///
+/// Use `DiffRecord::try_from(diff_byte_record)` (or [`DiffByteRecords::try_into_diff_records`](crate::diff_result::DiffByteRecords::try_into_diff_records))
+/// to create it, which fails with a [`RecordUtf8Error`], if any field is not valid UTF-8.
+/// # Example
+/// ```
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord, DiffRecord};
+/// use std::convert::TryFrom;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let diff_byte_record = DiffByteRecord::Add(ByteRecordLineInfo::new(
+///     csv::ByteRecord::from(vec!["1", "lemon"]),
+///     2,
+/// ));
+///
+/// match DiffRecord::try_from(diff_byte_record)? {
+///     DiffRecord::Add(added) => assert_eq!(&added.string_record()[1], "lemon"),
+///     _ => unreachable!(),
+/// }
+/// Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiffRecord {
+    Add(StringRecordLineInfo),
+    Modify {
+        delete: StringRecordLineInfo,
+        add: StringRecordLineInfo,
+        field_indices: Vec<usize>,
+    },
+    Delete(StringRecordLineInfo),
+    Move {
+        record: csv::StringRecord,
+        from_line: u64,
+        to_line: u64,
+    },
+}
+
+impl DiffRecord {
+    pub fn line_num(&self) -> LineNum {
+        match self {
+            Self::Add(srli) | Self::Delete(srli) => LineNum::OneSide(srli.line),
+            Self::Modify { delete, add, .. } => LineNum::BothSides {
+                for_deleted: delete.line,
+                for_added: add.line,
+            },
+            Self::Move {
+                from_line, to_line, ..
+            } => LineNum::BothSides {
+                for_deleted: *from_line,
+                for_added: *to_line,
+            },
+        }
+    }
+
+    pub fn kind(&self) -> DiffByteRecordKind {
+        match self {
+            Self::Add(..) => DiffByteRecordKind::Add,
+            Self::Modify { .. } => DiffByteRecordKind::Modify,
+            Self::Delete(..) => DiffByteRecordKind::Delete,
+            Self::Move { .. } => DiffByteRecordKind::Move,
+        }
+    }
+}
+
+impl DiffRecord {
+    /// Convert `diff_byte_record`, handling fields, that are not valid UTF-8, according to `utf8_conversion`.
+    ///
+    /// With [`Utf8Conversion::Lossy`], this never fails.
+    pub fn from_diff_byte_record(
+        diff_byte_record: DiffByteRecord,
+        utf8_conversion: Utf8Conversion,
+    ) -> Result<Self, RecordUtf8Error> {
+        let convert =
+            |brli| StringRecordLineInfo::from_byte_record_line_info(brli, utf8_conversion);
+        Ok(match diff_byte_record {
+            DiffByteRecord::Add(brli) => Self::Add(convert(brli)?),
+            DiffByteRecord::Delete(brli) => Self::Delete(convert(brli)?),
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self::Modify {
+                delete: convert(delete)?,
+                add: convert(add)?,
+                field_indices,
+            },
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => Self::Move {
+                record: convert(ByteRecordLineInfo::new(record, from_line))?.into_string_record(),
+                from_line,
+                to_line,
+            },
+        })
+    }
+
+    /// Convert `diff_byte_record` and replace invalid UTF-8 sequences (see [`Utf8Conversion::Lossy`]).
+    pub fn from_diff_byte_record_lossy(diff_byte_record: DiffByteRecord) -> Self {
+        Self::from_diff_byte_record(diff_byte_record, Utf8Conversion::Lossy)
+            .expect("a lossy conversion never fails")
+    }
+}
+
+impl TryFrom<DiffByteRecord> for DiffRecord {
+    type Error = RecordUtf8Error;
+
+    fn try_from(diff_byte_record: DiffByteRecord) -> Result<Self, Self::Error> {
+        Self::from_diff_byte_record(diff_byte_record, Utf8Conversion::Strict)
+    }
+}
+
+impl From<DiffRecord> for DiffByteRecord {
+    fn from(diff_record: DiffRecord) -> Self {
+        match diff_record {
+            DiffRecord::Add(srli) => Self::Add(srli.into()),
+            DiffRecord::Delete(srli) => Self::Delete(srli.into()),
+            DiffRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self::Modify {
+                delete: delete.into(),
+                add: add.into(),
+                field_indices,
+            },
+            DiffRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => Self::Move {
+                record: record.into_byte_record(),
+                from_line,
+                to_line,
+            },
+        }
+    }
+}
+
+/// A [`ByteRecordLineInfo`] with UTF-8 fields.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringRecordLineInfo {
+    string_record: csv::StringRecord,
+    line: u64,
+}
+
+impl StringRecordLineInfo {
+    pub fn new(string_record: csv::StringRecord, line: u64) -> Self {
+        Self {
+            string_record,
+            line,
+        }
+    }
+
+    pub fn string_record(&self) -> &csv::StringRecord {
+        &self.string_record
+    }
+
+    pub fn into_string_record(self) -> csv::StringRecord {
+        self.string_record
+    }
+
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+}
+
+impl StringRecordLineInfo {
+    /// Convert `brli`, handling fields, that are not valid UTF-8, according to `utf8_conversion`.
+    pub fn from_byte_record_line_info(
+        brli: ByteRecordLineInfo,
+        utf8_conversion: Utf8Conversion,
+    ) -> Result<Self, RecordUtf8Error> {
+        let line = brli.line;
+        match csv::StringRecord::from_byte_record(brli.byte_record) {
+            Ok(string_record) => Ok(Self::new(string_record, line)),
+            Err(from_utf8_err) => match utf8_conversion {
+                Utf8Conversion::Strict => Err(RecordUtf8Error::new(
+                    line,
+                    from_utf8_err.utf8_error().field(),
+                )),
+                Utf8Conversion::Lossy => Ok(Self::new(
+                    from_utf8_err
+                        .into_byte_record()
+                        .iter()
+                        .map(String::from_utf8_lossy)
+                        .collect(),
+                    line,
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<ByteRecordLineInfo> for StringRecordLineInfo {
+    type Error = RecordUtf8Error;
+
+    fn try_from(brli: ByteRecordLineInfo) -> Result<Self, Self::Error> {
+        Self::from_byte_record_line_info(brli, Utf8Conversion::Strict)
+    }
+}
+
+impl From<StringRecordLineInfo> for ByteRecordLineInfo {
+    fn from(srli: StringRecordLineInfo) -> Self {
+        Self::new(srli.string_record.into_byte_record(), srli.line)
+    }
+}
+
+/// How fields, that are not valid UTF-8, are handled, when they are converted into strings
+/// (e.g. into a [`DiffRecord`] or by the [`JsonLinesWriter`](crate::json_lines::JsonLinesWriter)).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Utf8Conversion {
+    /// Fail with a [`RecordUtf8Error`].
+    Strict,
+    /// Replace invalid sequences with `U+FFFD REPLACEMENT CHARACTER` (see [`String::from_utf8_lossy`]).
+    Lossy,
+}
+
+/// A field of a record is not valid UTF-8, so it can't be converted into a [`DiffRecord`].
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("field {field_idx} of the record in line {line} is not valid UTF-8")]
+pub struct RecordUtf8Error {
+    line: u64,
+    field_idx: usize,
+}
+
+impl RecordUtf8Error {
+    pub(crate) fn new(line: u64, field_idx: usize) -> Self {
+        Self { line, field_idx }
+    }
+
+    /// The line of the record with the invalid field.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// The index of the first field, that is not valid UTF-8.
+    pub fn field_idx(&self) -> usize {
+        self.field_idx
+    }
+}
+
+impl ErrorCode for RecordUtf8Error {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_INVALID_UTF8"
+    }
+}
+
+impl From<RecordUtf8Error> for csv::Error {
+    fn from(err: RecordUtf8Error) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// A record of an annotated comparison, where records, that are equal in both CSVs, are emitted as well
+/// (see [`CsvByteDiff::diff_with_equal_records`](crate::csv_diff::CsvByteDiff::diff_with_equal_records)).
+#[derive(Debug, PartialEq, Clone)]
+pub enum AnnotatedByteRecord {
+    /// A record with the same primary key, that has no differences in the left and the right CSV.
+    Equal {
+        left: ByteRecordLineInfo,
+        right: ByteRecordLineInfo,
+    },
+    Diff(DiffByteRecord),
+}
+
+impl AnnotatedByteRecord {
+    pub fn is_equal(&self) -> bool {
+        matches!(self, Self::Equal { .. })
+    }
+
+    /// Return the difference, if this is not an `Equal` record.
+    pub fn into_diff(self) -> Option<DiffByteRecord> {
+        match self {
+            Self::Diff(diff_record) => Some(diff_record),
+            Self::Equal { .. } => None,
+        }
+    }
+}
+
+impl From<DiffByteRecord> for AnnotatedByteRecord {
+    fn from(diff_record: DiffByteRecord) -> Self {
+        Self::Diff(diff_record)
+    }
+}
+
+/// A memory-saving alternative to [`DiffByteRecord`](DiffByteRecord).
+///
+/// For [`DiffByteRecord::Modify`](DiffByteRecord::Modify), the delete and add halves are mostly identical, when only a few fields
+/// have been changed in a wide record. `CompactDiffByteRecord::Modify` therefore only stores the delete half in full and
+/// the add half as a [`ByteRecordPatch`](ByteRecordPatch), which contains nothing but the changed fields.
+///
+/// Use `CompactDiffByteRecord::from(diff_byte_record)` to create it and
+/// [`into_diff_byte_record`](CompactDiffByteRecord::into_diff_byte_record) to get the full record back.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CompactDiffByteRecord {
+    Add(ByteRecordLineInfo),
+    Modify {
+        delete: ByteRecordLineInfo,
+        add: ByteRecordPatch,
+    },
+    Delete(ByteRecordLineInfo),
+    Move {
+        record: csv::ByteRecord,
+        from_line: u64,
+        to_line: u64,
+    },
+}
+
+impl CompactDiffByteRecord {
+    pub fn line_num(&self) -> LineNum {
+        match self {
+            Self::Add(rli) | Self::Delete(rli) => LineNum::OneSide(rli.line),
+            Self::Modify { delete, add } => LineNum::BothSides {
+                for_deleted: delete.line,
+                for_added: add.line,
+            },
+            Self::Move {
+                from_line, to_line, ..
+            } => LineNum::BothSides {
+                for_deleted: *from_line,
+                for_added: *to_line,
+            },
+        }
+    }
+
+    /// Restore the full [`DiffByteRecord`](DiffByteRecord).
+    pub fn into_diff_byte_record(self) -> DiffByteRecord {
+        match self {
+            Self::Add(rli) => DiffByteRecord::Add(rli),
+            Self::Delete(rli) => DiffByteRecord::Delete(rli),
+            Self::Modify { delete, add } => {
+                let add_byte_record = add.apply(delete.byte_record());
+                DiffByteRecord::Modify {
+                    add: ByteRecordLineInfo::new(add_byte_record, add.line),
+                    delete,
+                    field_indices: add.field_indices,
+                }
+            }
+            Self::Move {
+                record,
+                from_line,
+                to_line,
+            } => DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            },
+        }
+    }
+}
+
+impl From<DiffByteRecord> for CompactDiffByteRecord {
+    fn from(diff_record: DiffByteRecord) -> Self {
+        match diff_record {
+            DiffByteRecord::Add(rli) => Self::Add(rli),
+            DiffByteRecord::Delete(rli) => Self::Delete(rli),
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                let add = ByteRecordPatch::new(delete.byte_record(), add, field_indices);
+                Self::Modify { delete, add }
+            }
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => Self::Move {
+                record,
+                from_line,
+                to_line,
+            },
+        }
+    }
+}
+
+impl From<CompactDiffByteRecord> for DiffByteRecord {
+    fn from(compact_diff_record: CompactDiffByteRecord) -> Self {
+        compact_diff_record.into_diff_byte_record()
+    }
+}
+
+/// The add half of a [`CompactDiffByteRecord::Modify`](CompactDiffByteRecord::Modify), which only contains the fields that
+/// differ from the delete half.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ByteRecordPatch {
+    // the changed fields in the order of `changed_field_indices` (which includes every non-empty field
+    // that exceeds the length of the delete half)
+    changed_fields: csv::ByteRecord,
+    // the indices of all fields, whose bytes differ from the delete half; these can be more than
+    // `field_indices`, when fields are equal according to a field comparator
+    changed_field_indices: Vec<usize>,
+    field_indices: Vec<usize>,
+    len: usize,
+    line: u64,
+}
+
+impl ByteRecordPatch {
+    fn new(base: &csv::ByteRecord, add: ByteRecordLineInfo, field_indices: Vec<usize>) -> Self {
+        let add_byte_record = add.byte_record();
+        let changed_field_indices = modified_field_indices(base, add_byte_record);
+        let mut changed_fields = csv::ByteRecord::with_capacity(0, changed_field_indices.len());
+        for &idx in changed_field_indices.iter() {
+            changed_fields.push_field(add_byte_record.get(idx).unwrap_or_default());
+        }
+        Self {
+            changed_fields,
+            changed_field_indices,
+            field_indices,
+            len: add_byte_record.len(),
+            line: add.line,
+        }
+    }
+
+    /// The indices of the fields that are different in the add half (as reported by the original `Modify`, so without
+    /// fields that are equal according to a field comparator).
+    pub fn field_indices(&self) -> &[usize] {
+        &self.field_indices
+    }
+
+    /// Return the field at `idx` of the add half, if it has been changed.
+    pub fn changed_field(&self, idx: usize) -> Option<&[u8]> {
+        self.changed_field_indices
+            .binary_search(&idx)
+            .ok()
+            .and_then(|pos| self.changed_fields.get(pos))
+    }
+
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Reconstruct the full add half by applying this patch to `base` (the delete half).
+    pub fn apply(&self, base: &csv::ByteRecord) -> csv::ByteRecord {
+        let mut byte_record = csv::ByteRecord::with_capacity(base.as_slice().len(), self.len);
+        let mut changed_fields = self.changed_fields.iter();
+        let mut changed_field_indices = self.changed_field_indices.iter().peekable();
+        for idx in 0..self.len {
+            let field = if changed_field_indices.next_if_eq(&&idx).is_some() {
+                changed_fields.next()
+            } else {
+                base.get(idx)
+            };
+            byte_record.push_field(field.unwrap_or_default());
+        }
+        byte_record
+    }
+}
+
+/// A [`DiffByteRecord`](DiffByteRecord), whose fields borrow from the CSV data, that has been compared
+/// (see [`CsvByteDiffLocal::diff_slices`](crate::csv_diff::CsvByteDiffLocal::diff_slices)).
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiffByteRecordRef<'a> {
+    Add(ByteRecordLineInfoRef<'a>),
+    Modify {
+        delete: ByteRecordLineInfoRef<'a>,
+        add: ByteRecordLineInfoRef<'a>,
+        field_indices: Vec<usize>,
+    },
+    Delete(ByteRecordLineInfoRef<'a>),
+}
+
+impl<'a> DiffByteRecordRef<'a> {
+    pub fn line_num(&self) -> LineNum {
+        match self {
+            Self::Add(rli) | Self::Delete(rli) => LineNum::OneSide(rli.line),
+            Self::Modify {
+                delete: deleted,
+                add: added,
+                ..
+            } => LineNum::BothSides {
+                for_deleted: deleted.line,
+                for_added: added.line,
+            },
+        }
+    }
+
+    /// Create an owned [`DiffByteRecord`](DiffByteRecord) from this record.
+    pub fn to_diff_byte_record(&self) -> DiffByteRecord {
+        match self {
+            Self::Add(brli) => DiffByteRecord::Add(brli.to_byte_record_line_info()),
+            Self::Modify {
+                delete,
+                add,
+                field_indices,
+            } => DiffByteRecord::Modify {
+                delete: delete.to_byte_record_line_info(),
+                add: add.to_byte_record_line_info(),
+                field_indices: field_indices.clone(),
+            },
+            Self::Delete(brli) => DiffByteRecord::Delete(brli.to_byte_record_line_info()),
+        }
+    }
+
+    /// Create a `DiffByteRecordRef`, whose fields borrow from `input_left` (delete side) and `input_right` (add side),
+    /// which must be the CSV data `diff_record` has been read from with the default CSV dialect.
+    pub(crate) fn from_input(
+        diff_record: &DiffByteRecord,
+        input_left: &'a [u8],
+        input_right: &'a [u8],
+    ) -> Self {
+        match diff_record {
+            DiffByteRecord::Add(brli) => {
+                Self::Add(ByteRecordLineInfoRef::from_input(brli, input_right))
+            }
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self::Modify {
+                delete: ByteRecordLineInfoRef::from_input(delete, input_left),
+                add: ByteRecordLineInfoRef::from_input(add, input_right),
+                field_indices: field_indices.clone(),
+            },
+            DiffByteRecord::Delete(brli) => {
+                Self::Delete(ByteRecordLineInfoRef::from_input(brli, input_left))
+            }
+            DiffByteRecord::Move { .. } => {
+                unreachable!("moves are only detected by `CsvByteDiff::diff_with_moves`")
+            }
+        }
+    }
+}
+
+/// A CSV record with its line, whose fields borrow from the CSV data.
+///
+/// Fields can only be borrowed, when they appear verbatim in the CSV data. Quoted fields with escaped quotes
+/// (e.g. `"say ""hi"""`) are therefore owned.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ByteRecordLineInfoRef<'a> {
+    fields: Vec<Cow<'a, [u8]>>,
+    line: u64,
+}
+
+impl<'a> ByteRecordLineInfoRef<'a> {
+    pub fn get(&self, idx: usize) -> Option<&[u8]> {
+        self.fields.get(idx).map(|field| field.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.fields.iter().map(|field| field.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Return `true`, if no field had to be copied.
+    pub fn is_borrowed(&self) -> bool {
+        self.fields
+            .iter()
+            .all(|field| matches!(field, Cow::Borrowed(_)))
+    }
+
+    pub fn to_byte_record_line_info(&self) -> ByteRecordLineInfo {
+        ByteRecordLineInfo::new(self.iter().collect(), self.line)
+    }
+
+    fn from_input(brli: &ByteRecordLineInfo, input: &'a [u8]) -> Self {
+        let byte_record = brli.byte_record();
+        let mut cursor = byte_record
+            .position()
+            .and_then(|pos| usize::try_from(pos.byte()).ok())
+            .filter(|&start| start <= input.len());
+        let fields = byte_record
+            .iter()
+            .map(|field| {
+                let borrowed = cursor.and_then(|start| {
+                    let (borrowed, next) = locate_field(input, start, field);
+                    cursor = next;
+                    borrowed
+                });
+                match borrowed {
+                    Some(borrowed) => Cow::Borrowed(borrowed),
+                    None => Cow::Owned(field.to_vec()),
+                }
+            })
+            .collect();
+        Self {
+            fields,
+            line: brli.line(),
+        }
+    }
+}
+
+/// Find `field` (as parsed by the CSV reader) at `start` in `input` and return the borrowed field
+/// (if it appears verbatim) and the start of the next field.
+fn locate_field<'a>(
+    input: &'a [u8],
+    start: usize,
+    field: &[u8],
+) -> (Option<&'a [u8]>, Option<usize>) {
+    let rest = &input[start..];
+    let (borrowed, mut end) = if rest.first() == Some(&b'"') {
+        let mut end = 1;
+        let mut has_escaped_quotes = false;
+        loop {
+            match (rest.get(end), rest.get(end + 1)) {
+                (Some(b'"'), Some(b'"')) => {
+                    has_escaped_quotes = true;
+                    end += 2;
+                }
+                (Some(b'"'), _) => break,
+                (Some(_), _) => end += 1,
+                (None, _) => break,
+            }
+        }
+        let content = &rest[1..end.min(rest.len())];
+        (
+            (!has_escaped_quotes && content == field).then_some(content),
+            end + 1,
+        )
+    } else {
+        let end = field.len().min(rest.len());
+        ((&rest[..end] == field).then_some(&rest[..end]), end)
+    };
+    // skip everything up to the next delimiter
+    while let Some(&b) = rest.get(end) {
+        match b {
+            b',' => return (borrowed, Some(start + end + 1)),
+            b'\r' | b'\n' => return (borrowed, None),
+            _ => end += 1,
+        }
+    }
+    (borrowed, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn compact_modify_only_stores_changed_fields() {
+        let compact = CompactDiffByteRecord::from(DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c", "d"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "c", "y"]), 3),
+            field_indices: vec![1, 3],
+        });
+
+        match compact {
+            CompactDiffByteRecord::Modify { add, .. } => {
+                assert_eq!(add.changed_fields, csv::ByteRecord::from(vec!["x", "y"]));
+                assert_eq!(add.field_indices(), &[1, 3]);
+                assert_eq!(add.changed_field(1), Some(&b"x"[..]));
+                assert_eq!(add.changed_field(2), None);
+                assert_eq!(add.line(), 3);
+            }
+            _ => panic!("expected a compact modify"),
+        }
+    }
+
+    #[test]
+    fn similarity_of_modify_is_share_of_equal_fields_and_bytes() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ab", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ab", "cdef"]), 2),
+            field_indices: vec![2],
+        };
+
+        assert_eq!(diff_record.similarity(), Some(2.0 / 3.0));
+        assert_eq!(diff_record.byte_similarity(), Some(6.0 / 11.0));
+    }
+
+    #[test]
+    fn similarity_of_modify_counts_fields_of_one_side_only_as_changed() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a", "b", "c"]), 2),
+            field_indices: vec![2, 3],
+        };
+
+        assert_eq!(diff_record.similarity(), Some(0.5));
+        assert_eq!(diff_record.byte_similarity(), Some(4.0 / 6.0));
+    }
+
+    #[test]
+    fn similarity_of_add_and_delete_is_none() {
+        let brli = ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a"]), 2);
+
+        assert_eq!(DiffByteRecord::Add(brli.clone()).similarity(), None);
+        assert_eq!(DiffByteRecord::Delete(brli).byte_similarity(), None);
+    }
+
+    #[test]
+    fn map_records_maps_both_halves_of_modify_and_keeps_lines_and_field_indices() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", " lemon "]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", " lime"]), 4),
+            field_indices: vec![1],
+        };
+
+        let trimmed = diff_record.map_records(|mut byte_record| {
+            byte_record.trim();
+            byte_record
+        });
+
+        assert_eq!(
+            trimmed,
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lime"]), 4),
+                field_indices: vec![1],
+            }
+        );
+    }
+
+    #[test]
+    fn diff_record_try_from_modify_with_invalid_utf8_is_err_with_line_and_field() {
+        let valid = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lime"]), 3),
+            field_indices: vec![1],
+        };
+        let invalid = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 2),
+            add: ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec![&b"1"[..], &b"l\xffme"[..]]),
+                3,
+            ),
+            field_indices: vec![1],
+        };
+
+        let diff_record = DiffRecord::try_from(valid.clone()).unwrap();
+        let err = DiffRecord::try_from(invalid).unwrap_err();
+
+        assert_eq!(
+            diff_record,
+            DiffRecord::Modify {
+                delete: StringRecordLineInfo::new(csv::StringRecord::from(vec!["1", "lemon"]), 2),
+                add: StringRecordLineInfo::new(csv::StringRecord::from(vec!["1", "lime"]), 3),
+                field_indices: vec![1],
+            }
+        );
+        assert_eq!(DiffByteRecord::from(diff_record), valid);
+        assert_eq!((err.line(), err.field_idx()), (3, 1));
+    }
+
+    #[test]
+    fn diff_record_from_diff_byte_record_lossy_replaces_invalid_utf8() {
+        let diff_byte_record = DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec![&b"1"[..], &b"l\xffme"[..]]),
+            4,
+        ));
+
+        assert_eq!(
+            DiffRecord::from_diff_byte_record_lossy(diff_byte_record),
+            DiffRecord::Delete(StringRecordLineInfo::new(
+                csv::StringRecord::from(vec!["1", "l\u{fffd}me"]),
+                4
+            ))
+        );
+    }
+
+    #[test]
+    fn compact_modify_round_trip() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c", "d"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "c", "y"]), 3),
+            field_indices: vec![1, 3],
+        };
+
+        let actual = CompactDiffByteRecord::from(diff_record.clone()).into_diff_byte_record();
+
+        assert_eq!(actual, diff_record);
+    }
+
+    #[test]
+    fn compact_modify_round_trip_add_has_more_fields() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "", "z"]), 2),
+            field_indices: vec![1, 3],
+        };
+
+        let actual = CompactDiffByteRecord::from(diff_record.clone()).into_diff_byte_record();
+
+        assert_eq!(actual, diff_record);
+    }
+
+    #[test]
+    fn compact_modify_round_trip_add_has_less_fields() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x"]), 2),
+            field_indices: vec![1],
+        };
+
+        let actual = CompactDiffByteRecord::from(diff_record.clone()).into_diff_byte_record();
+
+        assert_eq!(actual, diff_record);
+    }
+
+    #[test]
+    fn compact_add_and_delete_are_unchanged() {
+        let add = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a", "b"]),
+            2,
+        ));
+        let delete = DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a", "b"]),
+            2,
+        ));
+
+        assert_eq!(
+            DiffByteRecord::from(CompactDiffByteRecord::from(add.clone())),
+            add
+        );
+        assert_eq!(
+            DiffByteRecord::from(CompactDiffByteRecord::from(delete.clone())),
+            delete
+        );
+    }
 }
@@ -0,0 +1,664 @@
+use crate::column_order::ColumnOrder;
+use crate::csv::RecordNormalizer;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::error_code::ErrorCode;
+use crate::error_policy::{ErrorPolicy, SkippedRecord};
+#[cfg(feature = "simd-parser")]
+use crate::simd_parser::{SimdParser, SimdRecordReader};
+use csv::Reader;
+use std::collections::VecDeque;
+use std::fmt;
+#[cfg(feature = "simd-parser")]
+use std::io::Seek;
+use std::io::{self, Read};
+use thiserror::Error;
+
+/// How to compare a CSV, that is _completely_ empty (it has neither a header nor records),
+/// with a CSV, that is not empty.
+///
+/// A CSV, that only consists of a byte order mark, whitespace and/or blank lines, is treated as completely empty, too
+/// (see [`EmptyCsvReason`]), so that placeholder files neither produce a bogus header nor parse errors.
+///
+/// The difference only matters, when the non-empty CSV is read with headers (which is the default of [`csv::Reader`]):
+/// its header can't be matched against anything, because the empty CSV has no header at all.
+/// When a CSV is read without headers, its first line is a record and is always reported.
+///
+/// The chosen semantics are exposed in the [`DiffRunReport`](crate::run_report::DiffRunReport) of a streaming comparison.
+/// # Example: an empty right CSV means, that everything (including the header) has been deleted
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocalBuilder, csv::Csv};
+use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use csv_diff::empty_csv::EmptyCsvSemantics;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "id,name,kind";
+let csv_right = "";
+
+let diff_byte_records = CsvByteDiffLocalBuilder::new()
+    .empty_csv_semantics(EmptyCsvSemantics::AllDifferent)
+    .build()?
+    .diff(
+        Csv::with_reader_seek(csv_left.as_bytes()),
+        Csv::with_reader_seek(csv_right.as_bytes()),
+    )?;
+
+assert_eq!(
+    diff_byte_records.as_slice(),
+    &[DiffByteRecord::Delete(ByteRecordLineInfo::new(
+        csv::ByteRecord::from(vec!["id", "name", "kind"]),
+        1
+    ))]
+);
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum EmptyCsvSemantics {
+    /// An empty CSV is treated like a CSV with the same header as the other CSV, but without any records.
+    /// So an empty left CSV means, that all records of the right CSV have been added (and vice versa),
+    /// but the header of the other CSV is never reported.
+    ///
+    /// Note that this also means, that comparing an empty CSV with a CSV, that consists of a single line,
+    /// yields no differences at all.
+    #[default]
+    NoRecords,
+    /// An empty left CSV means, that _all_ lines of the right CSV (including its header) have been added.
+    /// An empty right CSV means, that _all_ lines of the left CSV (including its header) have been deleted.
+    AllDifferent,
+    /// Comparing an empty CSV with a non-empty CSV fails with an [`EmptyCsvError`].
+    Error,
+}
+
+/// Why a CSV has been treated as completely empty.
+///
+/// It is recorded per side in the [`DiffRunReport`](crate::run_report::DiffRunReport) of a streaming comparison,
+/// see [`empty_csv_left`](crate::run_report::DiffRunReport::empty_csv_left) and
+/// [`empty_csv_right`](crate::run_report::DiffRunReport::empty_csv_right).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum EmptyCsvReason {
+    /// The CSV has no bytes at all.
+    NoBytes,
+    /// The CSV only consists of a byte order mark, whitespace and/or blank lines.
+    Blank,
+}
+
+/// The side of a comparison, see [`EmptyCsvError`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CsvSide {
+    Left,
+    Right,
+}
+
+impl fmt::Display for CsvSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// One of the CSVs is completely empty, while the other is not (see [`EmptyCsvSemantics::Error`]).
+///
+/// When comparing CSVs, it is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is an `EmptyCsvError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("the {side} CSV is completely empty (it has neither a header nor records)")]
+pub struct EmptyCsvError {
+    side: CsvSide,
+}
+
+impl EmptyCsvError {
+    /// The side of the CSV, that is empty.
+    pub fn side(&self) -> CsvSide {
+        self.side
+    }
+}
+
+impl ErrorCode for EmptyCsvError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_EMPTY_CSV"
+    }
+}
+
+impl From<EmptyCsvError> for csv::Error {
+    fn from(err: EmptyCsvError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+/// What has been found out about the beginning of a CSV by [`CsvReadAhead::read_start`].
+#[derive(Debug, Clone)]
+pub(crate) struct CsvHeaderInfo {
+    /// The header, if the CSV is read with headers.
+    headers: Option<csv::ByteRecord>,
+    empty_reason: Option<EmptyCsvReason>,
+    column_order: Option<ColumnOrder>,
+}
+
+impl CsvHeaderInfo {
+    pub(crate) fn new(
+        headers: Option<csv::ByteRecord>,
+        empty_reason: Option<EmptyCsvReason>,
+    ) -> Self {
+        Self {
+            headers,
+            empty_reason,
+            column_order: None,
+        }
+    }
+
+    /// The header of data, that isn't read from a CSV (e.g. the column names of Arrow record batches),
+    /// which is empty, if there is no data at all.
+    #[cfg(feature = "arrow")]
+    pub(crate) fn from_headers(headers: csv::ByteRecord) -> Self {
+        Self {
+            empty_reason: headers.is_empty().then_some(EmptyCsvReason::NoBytes),
+            headers: Some(headers),
+            column_order: None,
+        }
+    }
+
+    /// The header, if the CSV is read with headers and is not empty.
+    pub(crate) fn headers(&self) -> Option<&csv::ByteRecord> {
+        self.headers.as_ref().filter(|_| !self.is_empty())
+    }
+
+    /// The header, if the CSV is read with headers, even if it is empty.
+    pub(crate) fn raw_headers(&self) -> Option<&csv::ByteRecord> {
+        self.headers.as_ref()
+    }
+
+    pub(crate) fn empty_reason(&self) -> Option<EmptyCsvReason> {
+        self.empty_reason
+    }
+
+    pub(crate) fn column_order(&self) -> Option<&ColumnOrder> {
+        self.column_order.as_ref()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.empty_reason.is_some()
+    }
+}
+
+/// Records, that have been read ahead in order to find out, whether a CSV is blank.
+///
+/// They are handed out again by [`read_byte_record`](CsvReadAhead::read_byte_record), before reading any further,
+/// so for a CSV, that is not blank, no record is lost.
+///
+/// If the columns are put into canonical order, this is done for every record, that is handed out (and for the header).
+/// Malformed records, that are skipped due to the [`ErrorPolicy`], are never handed out, but collected.
+#[derive(Debug, Default)]
+pub(crate) struct CsvReadAhead {
+    pending_records: VecDeque<csv::ByteRecord>,
+    is_canonical_column_order: bool,
+    column_order: Option<ColumnOrder>,
+    scratch: csv::ByteRecord,
+    physical_lines: Option<PhysicalLines>,
+    line_offset: u64,
+    normalizer: Option<RecordNormalizer>,
+    error_policy: ErrorPolicy,
+    skipped_records: Vec<SkippedRecord>,
+    #[cfg(feature = "simd-parser")]
+    simd_reader: Option<SimdRecordReader>,
+}
+
+impl CsvReadAhead {
+    /// Correct the line of every record, that is read, to its physical line in the CSV data (see [`PhysicalLines`]).
+    pub(crate) fn physical_lines(mut self, physical_lines: Option<PhysicalLines>) -> Self {
+        self.physical_lines = physical_lines;
+        self
+    }
+
+    /// Add `line_offset` to the line of every record, that is read (after it has been corrected to its physical line).
+    pub(crate) fn line_offset(mut self, line_offset: u64) -> Self {
+        self.line_offset = line_offset;
+        self
+    }
+
+    /// Transform every record, that is handed out, with `normalizer` (after its columns have been put into canonical order).
+    pub(crate) fn normalizer(mut self, normalizer: Option<RecordNormalizer>) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// Skip malformed records according to `error_policy`.
+    pub(crate) fn error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Tokenize the records with `simd_parser` (if it is `Some`) instead of with the `csv` crate.
+    ///
+    /// The underlying reader of the CSV reader is read directly, so its header is handed over to it
+    /// (see [`read_start`](CsvReadAhead::read_start)), but it must not be read from otherwise.
+    #[cfg(feature = "simd-parser")]
+    pub(crate) fn simd_parser(mut self, simd_parser: Option<SimdParser>) -> Self {
+        self.simd_reader = simd_parser.map(SimdParser::record_reader);
+        self
+    }
+
+    /// The records, that have been skipped so far.
+    pub(crate) fn take_skipped_records(&mut self) -> Vec<SkippedRecord> {
+        std::mem::take(&mut self.skipped_records)
+    }
+
+    /// Put the columns into canonical order (sorted by header name). This has no effect for CSVs without headers.
+    pub(crate) fn canonical_column_order(mut self, is_canonical_column_order: bool) -> Self {
+        self.is_canonical_column_order = is_canonical_column_order;
+        self
+    }
+
+    /// Must be called _before_ the first record is read from `csv_reader`.
+    ///
+    /// If the header (or the first record without headers) is blank, records are read ahead until one of them is not blank.
+    /// If there is none, the CSV is treated as empty and none of its records are handed out.
+    pub(crate) fn read_start<R: Read>(
+        &mut self,
+        csv_reader: &mut Reader<R>,
+    ) -> csv::Result<CsvHeaderInfo> {
+        let has_headers = csv_reader.has_headers();
+        #[cfg(feature = "simd-parser")]
+        if csv_reader.position().byte() > 0 {
+            // the `csv` crate has already buffered data (e.g. due to `Csv::headers`), so it must keep on reading
+            self.simd_reader = None;
+        } else if let (true, Some(simd_reader)) = (has_headers, &mut self.simd_reader) {
+            let mut headers = csv::ByteRecord::new();
+            simd_reader.read_byte_record(csv_reader.get_mut(), &mut headers)?;
+            csv_reader.set_byte_headers(headers);
+        }
+        let mut csv_record = csv::ByteRecord::new();
+        if self.read_from_csv_reader(csv_reader, &mut csv_record)? {
+            self.pending_records.push_back(csv_record);
+        }
+        #[cfg(feature = "simd-parser")]
+        if let (false, Some(_)) = (has_headers, &self.simd_reader) {
+            // without headers, the `csv` crate returns the first record as the header
+            csv_reader.set_byte_headers(self.pending_records.front().cloned().unwrap_or_default());
+        }
+        // without headers, this is the first record - so in both cases, it is only empty for an empty CSV
+        // (the `csv` crate already skips a byte order mark and blank lines)
+        let mut headers = csv_reader.byte_headers()?.clone();
+        let empty_reason = if headers.is_empty() {
+            Some(if self.position(csv_reader).byte() == 0 {
+                EmptyCsvReason::NoBytes
+            } else {
+                EmptyCsvReason::Blank
+            })
+        } else if is_blank(&headers) && self.read_ahead_while_blank(csv_reader)? {
+            self.pending_records.clear();
+            Some(EmptyCsvReason::Blank)
+        } else {
+            None
+        };
+        if self.is_canonical_column_order && has_headers && empty_reason.is_none() {
+            let column_order = ColumnOrder::by_header_name(&headers);
+            column_order.apply(&mut headers, &mut self.scratch);
+            self.column_order = Some(column_order);
+        }
+        Ok(CsvHeaderInfo {
+            headers: has_headers.then_some(headers),
+            empty_reason,
+            column_order: self.column_order.clone(),
+        })
+    }
+
+    /// Like [`Reader::read_byte_record`], but records, that have been read ahead, are returned first.
+    pub(crate) fn read_byte_record<R: Read>(
+        &mut self,
+        csv_reader: &mut Reader<R>,
+        csv_record: &mut csv::ByteRecord,
+    ) -> csv::Result<bool> {
+        let is_record_read = match self.pending_records.pop_front() {
+            Some(pending_record) => {
+                *csv_record = pending_record;
+                true
+            }
+            None => self.read_from_csv_reader(csv_reader, csv_record)?,
+        };
+        if let (true, Some(column_order)) = (is_record_read, &self.column_order) {
+            column_order.apply(csv_record, &mut self.scratch);
+        }
+        if let (true, Some(normalizer)) = (is_record_read, &self.normalizer) {
+            normalizer.apply(csv_record);
+        }
+        Ok(is_record_read)
+    }
+
+    /// Let `csv_reader` seek to the records, that have been read by the [`SimdRecordReader`] (if any).
+    ///
+    /// The `csv` crate hasn't read any data in this case, so it would assume, that it is still at the start
+    /// and skip seeking to the first byte.
+    #[cfg(feature = "simd-parser")]
+    pub(crate) fn rewind_for_seeking<R: Read + Seek>(
+        &self,
+        csv_reader: &mut Reader<R>,
+    ) -> csv::Result<()> {
+        if self.simd_reader.is_some() {
+            csv_reader.seek_raw(io::SeekFrom::Start(0), csv::Position::new())?;
+        }
+        Ok(())
+    }
+
+    /// The position of the next record, that is read from `csv_reader`.
+    fn position<'a, R: Read>(&'a self, csv_reader: &'a Reader<R>) -> &'a csv::Position {
+        #[cfg(feature = "simd-parser")]
+        if let Some(simd_reader) = &self.simd_reader {
+            return simd_reader.position();
+        }
+        csv_reader.position()
+    }
+
+    fn read_from_csv_reader<R: Read>(
+        &mut self,
+        csv_reader: &mut Reader<R>,
+        csv_record: &mut csv::ByteRecord,
+    ) -> csv::Result<bool> {
+        loop {
+            #[cfg(feature = "simd-parser")]
+            let res = match &mut self.simd_reader {
+                Some(simd_reader) => simd_reader.read_byte_record(csv_reader.get_mut(), csv_record),
+                None => csv_reader.read_byte_record(csv_record),
+            };
+            #[cfg(not(feature = "simd-parser"))]
+            let res = csv_reader.read_byte_record(csv_record);
+            let is_skipped = matches!(&res, Err(err) if self.error_policy.is_skipped(err));
+            if let (true, Some(physical_lines)) = (
+                matches!(res, Ok(true)) || is_skipped,
+                &mut self.physical_lines,
+            ) {
+                // the position of a record is the position of the reader before the record has been read
+                if let Some(pos_before) = csv_record.position() {
+                    let mut pos = pos_before.clone();
+                    pos.set_line(physical_lines.record_line(
+                        pos_before,
+                        csv_reader.position(),
+                        csv_record,
+                    ));
+                    csv_record.set_position(Some(pos));
+                }
+            }
+            if self.line_offset > 0 && (matches!(res, Ok(true)) || is_skipped) {
+                if let Some(pos_before) = csv_record.position() {
+                    let mut pos = pos_before.clone();
+                    pos.set_line(pos_before.line() + self.line_offset);
+                    csv_record.set_position(Some(pos));
+                }
+            }
+            match res {
+                Err(err) if is_skipped => self
+                    .skipped_records
+                    .push(SkippedRecord::new(csv_record.clone(), &err)),
+                res => return res,
+            }
+        }
+    }
+
+    /// Return `true`, if all remaining records are blank.
+    fn read_ahead_while_blank<R: Read>(&mut self, csv_reader: &mut Reader<R>) -> csv::Result<bool> {
+        loop {
+            if self
+                .pending_records
+                .back()
+                .map_or(false, |csv_record| !is_blank(csv_record))
+            {
+                return Ok(false);
+            }
+            let mut csv_record = csv::ByteRecord::new();
+            if !self.read_from_csv_reader(csv_reader, &mut csv_record)? {
+                return Ok(true);
+            }
+            self.pending_records.push_back(csv_record);
+        }
+    }
+}
+
+/// Derives the physical line of records from the positions of the reader before and after reading them.
+///
+/// The `csv` crate reports the line of the reader _before_ a record has been read as its line, which is the line of
+/// the first comment or blank line in front of it, if there are any. Additionally, the line feed of a line, that ends
+/// with `\r\n`, is only consumed together with the next record. So the physical line is derived from the line _after_
+/// the record has been read, the line breaks within its fields and whether its line feed has been consumed.
+///
+/// The latter is told by the number of bytes, that have been consumed: quoted fields add an even number of bytes
+/// (two quotes plus one for every quote within the field), so without comment or blank lines in front of a record,
+/// this number is odd for a line feed and even for the `\r` and pending `\n` of `\r\n`. The line terminator found
+/// like this is assumed for all further records, except for a last record without line terminator.
+/// Until it has been found (i.e. while all records are preceded by comment or blank lines), a line feed is assumed.
+#[derive(Debug, Clone)]
+pub(crate) struct PhysicalLines {
+    quote: u8,
+    // `None` until the line terminator has been found
+    is_crlf: Option<bool>,
+}
+
+impl PhysicalLines {
+    pub(crate) fn new(quote: u8) -> Self {
+        Self {
+            quote,
+            is_crlf: None,
+        }
+    }
+
+    fn record_line(
+        &mut self,
+        pos_before: &csv::Position,
+        pos_after: &csv::Position,
+        csv_record: &csv::ByteRecord,
+    ) -> u64 {
+        let count = |byte: u8| {
+            csv_record
+                .iter()
+                .map(|field| field.iter().filter(|&&b| b == byte).count() as u64)
+                .sum::<u64>()
+        };
+        let line_breaks = count(b'\n');
+        let lines_consumed = pos_after.line() - pos_before.line();
+        let is_line_feed_consumed = if pos_before.byte() == 0 {
+            // nothing can be pending at the start
+            lines_consumed > line_breaks
+        } else {
+            // with the same line terminator for all records, the pending line feed of the previous record
+            // and the line feed of this one add up to one line
+            let lines_skipped = lines_consumed.saturating_sub(line_breaks + 1);
+            let is_odd_overhead = lines_skipped == 0 && {
+                let record_len = csv_record.as_slice().len() as u64
+                    + (csv_record.len() as u64).saturating_sub(1)
+                    + count(self.quote);
+                (pos_after.byte() - pos_before.byte()).saturating_sub(record_len) % 2 == 1
+            };
+            match self.is_crlf {
+                // `\r\n` or a last record without line terminator
+                Some(true) => false,
+                // an even overhead is only possible for a last record without line terminator
+                Some(false) => lines_skipped > 0 || is_odd_overhead,
+                None if lines_skipped == 0 => {
+                    self.is_crlf = Some(!is_odd_overhead);
+                    is_odd_overhead
+                }
+                None => true,
+            }
+        };
+        pos_after.line() - line_breaks - u64::from(is_line_feed_consumed)
+    }
+}
+
+/// A line, that only consists of whitespace, is parsed as a record with a single, non-empty field.
+fn is_blank(csv_record: &csv::ByteRecord) -> bool {
+    csv_record.len() == 1
+        && !csv_record[0].is_empty()
+        && csv_record[0].iter().all(u8::is_ascii_whitespace)
+}
+
+impl EmptyCsvSemantics {
+    /// Return the difference, that must be reported additionally to the differences of the records
+    /// (which can only be the header of the non-empty CSV).
+    pub(crate) fn header_diff(
+        &self,
+        header_info_left: &CsvHeaderInfo,
+        header_info_right: &CsvHeaderInfo,
+    ) -> Result<Option<DiffByteRecord>, EmptyCsvError> {
+        let empty_side = match (header_info_left.is_empty(), header_info_right.is_empty()) {
+            (true, false) => CsvSide::Left,
+            (false, true) => CsvSide::Right,
+            _ => return Ok(None),
+        };
+        match self {
+            Self::NoRecords => Ok(None),
+            Self::Error => Err(EmptyCsvError { side: empty_side }),
+            Self::AllDifferent => Ok(match empty_side {
+                CsvSide::Left => header_info_right
+                    .headers
+                    .clone()
+                    .map(|headers| DiffByteRecord::Add(ByteRecordLineInfo::new(headers, 1))),
+                CsvSide::Right => header_info_left
+                    .headers
+                    .clone()
+                    .map(|headers| DiffByteRecord::Delete(ByteRecordLineInfo::new(headers, 1))),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn header_info(csv_data: &str, has_headers: bool) -> csv::Result<CsvHeaderInfo> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(has_headers)
+            .from_reader(csv_data.as_bytes());
+        CsvReadAhead::default().read_start(&mut csv_reader)
+    }
+
+    fn read_all(csv_data: &str, has_headers: bool) -> csv::Result<Vec<csv::ByteRecord>> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(has_headers)
+            .from_reader(csv_data.as_bytes());
+        let mut read_ahead = CsvReadAhead::default();
+        read_ahead.read_start(&mut csv_reader)?;
+        let mut csv_records = Vec::new();
+        let mut csv_record = csv::ByteRecord::new();
+        while read_ahead.read_byte_record(&mut csv_reader, &mut csv_record)? {
+            csv_records.push(csv_record.clone());
+        }
+        Ok(csv_records)
+    }
+
+    #[test]
+    fn read_start_of_placeholder_csvs_is_empty() -> csv::Result<()> {
+        assert_eq!(
+            header_info("", true)?.empty_reason(),
+            Some(EmptyCsvReason::NoBytes)
+        );
+        for csv_data in ["\u{feff}", "\n\r\n\n", "\u{feff} \t\n  \r\n\n  "] {
+            for has_headers in [true, false] {
+                assert_eq!(
+                    header_info(csv_data, has_headers)?.empty_reason(),
+                    Some(EmptyCsvReason::Blank),
+                    "{:?}",
+                    csv_data
+                );
+                assert_eq!(
+                    read_all(csv_data, has_headers)?,
+                    Vec::<csv::ByteRecord>::new()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn read_start_with_blank_header_and_records_keeps_all_records() -> csv::Result<()> {
+        let csv_data = "  \n \n1\n ";
+
+        assert_eq!(header_info(csv_data, true)?.empty_reason(), None);
+        assert_eq!(
+            read_all(csv_data, true)?,
+            vec![
+                csv::ByteRecord::from(vec![" "]),
+                csv::ByteRecord::from(vec!["1"]),
+                csv::ByteRecord::from(vec![" "]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all_different_with_blank_right_reports_header_as_deleted() -> csv::Result<()> {
+        let diff = EmptyCsvSemantics::AllDifferent
+            .header_diff(&header_info("id,name", true)?, &header_info(" \n ", true)?);
+
+        assert_eq!(
+            diff,
+            Ok(Some(DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["id", "name"]),
+                1
+            ))))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_records_never_reports_header() -> csv::Result<()> {
+        let diff = EmptyCsvSemantics::NoRecords
+            .header_diff(&header_info("", true)?, &header_info("id,name", true)?);
+
+        assert_eq!(diff, Ok(None));
+        Ok(())
+    }
+
+    #[test]
+    fn all_different_with_empty_left_reports_header_as_added() -> csv::Result<()> {
+        let diff = EmptyCsvSemantics::AllDifferent.header_diff(
+            &header_info("", true)?,
+            &header_info("id,name\n1,lemon", true)?,
+        );
+
+        assert_eq!(
+            diff,
+            Ok(Some(DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["id", "name"]),
+                1
+            ))))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all_different_without_headers_reports_nothing_additionally() -> csv::Result<()> {
+        let diff = EmptyCsvSemantics::AllDifferent
+            .header_diff(&header_info("1,lemon", false)?, &header_info("", false)?);
+
+        assert_eq!(diff, Ok(None));
+        Ok(())
+    }
+
+    #[test]
+    fn error_with_empty_right_is_err() -> csv::Result<()> {
+        let diff = EmptyCsvSemantics::Error
+            .header_diff(&header_info("id,name", true)?, &header_info("", true)?);
+
+        assert_eq!(
+            diff,
+            Err(EmptyCsvError {
+                side: CsvSide::Right
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_with_both_empty_is_ok() -> csv::Result<()> {
+        let diff =
+            EmptyCsvSemantics::Error.header_diff(&header_info("", true)?, &header_info("", false)?);
+
+        assert_eq!(diff, Ok(None));
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::csv::{Csv, CsvBuilder};
+use crate::spooled::SpooledReader;
+use encoding_rs::{CoderResult, Decoder, Encoding};
+use std::io::{self, Read};
+
+/// The number of bytes, that are read from the inner reader at once.
+const INPUT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// A reader, that transcodes data of another character encoding (e.g. Latin-1, Windows-1252 or UTF-16)
+/// into UTF-8 on the fly, so that such CSVs can be compared.
+///
+/// A byte order mark (BOM) at the start takes precedence over the given encoding and is removed.
+/// Sequences, that are invalid in the encoding, are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// The transcoded data has different byte offsets than the original data and the `TranscodingReader` can't seek,
+/// so it can only be compared with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff) directly.
+/// In order to compare it with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), which needs to seek in the CSV data,
+/// use [`Csv::with_reader_transcoded_seek`], which spools the transcoded data.
+/// Line numbers are the same as in the original data.
+///
+/// This is only available with the Cargo feature `encoding`.
+pub struct TranscodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    input_pos: usize,
+    input_len: usize,
+    input_eof: bool,
+    output: Vec<u8>,
+    output_pos: usize,
+    finished: bool,
+}
+
+impl<R: Read> TranscodingReader<R> {
+    /// Create a new `TranscodingReader`, that transcodes the data of `reader` from `encoding` into UTF-8
+    /// (e.g. [`encoding_rs::WINDOWS_1252`]).
+    pub fn new(reader: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner: reader,
+            decoder: encoding.new_decoder(),
+            input: vec![0; INPUT_BUFFER_SIZE],
+            input_pos: 0,
+            input_len: 0,
+            input_eof: false,
+            output: Vec::new(),
+            output_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Like [`new`](TranscodingReader::new), but the encoding is given by its
+    /// [label](https://encoding.spec.whatwg.org/#names-and-labels) (e.g. `"latin1"` or `"utf-16le"`).
+    ///
+    /// Fails with an [`io::Error`] of kind [`InvalidInput`](io::ErrorKind::InvalidInput), if the label is unknown.
+    pub fn for_label(reader: R, label: &str) -> io::Result<Self> {
+        Encoding::for_label(label.as_bytes())
+            .map(|encoding| Self::new(reader, encoding))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown character encoding: {label}"),
+                )
+            })
+    }
+
+    /// Transcode the next chunk of the inner reader into `output`; `output` stays empty only at the end.
+    fn transcode_next(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.output_pos = 0;
+        while self.output.is_empty() && !self.finished {
+            if self.input_pos == self.input_len && !self.input_eof {
+                self.input_len = self.inner.read(&mut self.input)?;
+                self.input_pos = 0;
+                self.input_eof = self.input_len == 0;
+            }
+            let input = &self.input[self.input_pos..self.input_len];
+            let output_len = self
+                .decoder
+                .max_utf8_buffer_length(input.len())
+                .unwrap_or(INPUT_BUFFER_SIZE * 3);
+            self.output.resize(output_len, 0);
+            let (result, read, written, _) =
+                self.decoder
+                    .decode_to_utf8(input, &mut self.output, self.input_eof);
+            self.input_pos += read;
+            self.output.truncate(written);
+            self.finished = self.input_eof && result == CoderResult::InputEmpty;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for TranscodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output_pos == self.output.len() {
+            self.transcode_next()?;
+        }
+        let output = &self.output[self.output_pos..];
+        let len = output.len().min(buf.len());
+        buf[..len].copy_from_slice(&output[..len]);
+        self.output_pos += len;
+        Ok(len)
+    }
+}
+
+impl<R: Read> Csv<TranscodingReader<R>> {
+    /// Create a new `Csv` with something that reads CSV data in `encoding` (see [`TranscodingReader`]),
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    ///
+    /// This is only available with the Cargo feature `encoding`.
+    pub fn with_reader_transcoded(reader: R, encoding: &'static Encoding) -> Self {
+        CsvBuilder::new().build_with_reader_transcoded(reader, encoding)
+    }
+}
+
+impl Csv<SpooledReader> {
+    /// Create a new `Csv` with something that reads CSV data in `encoding`, in order to compare it with
+    /// [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal).
+    ///
+    /// `CsvByteDiffLocal` needs to seek in the CSV data, which a [`TranscodingReader`] can't do,
+    /// so the transcoded data is spooled first (see [`SpooledReader`]).
+    /// This is only available with the Cargo feature `encoding`.
+    /// # Example: compare a Windows-1252 export with a UTF-8 export
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+// "crème" in Windows-1252
+let csv_left = b"id,name\n1,cr\xe8me".as_slice();
+let csv_right = "id,name\n1,crème".as_bytes();
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_transcoded_seek(csv_left, encoding_rs::WINDOWS_1252)?,
+    Csv::with_reader_spooled(csv_right)?,
+)?;
+
+assert!(diff_byte_records.as_slice().is_empty());
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_reader_transcoded_seek<R: Read>(
+        reader: R,
+        encoding: &'static Encoding,
+    ) -> io::Result<Self> {
+        CsvBuilder::new().build_with_reader_transcoded_seek(reader, encoding)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but transcodes the data of `reader`
+    /// from `encoding` into UTF-8 on the fly (see [`Csv::with_reader_transcoded`]).
+    pub fn build_with_reader_transcoded<R: Read>(
+        &self,
+        reader: R,
+        encoding: &'static Encoding,
+    ) -> Csv<TranscodingReader<R>> {
+        self.build_with_reader(TranscodingReader::new(reader, encoding))
+    }
+
+    /// Like [`build_with_reader_spooled`](CsvBuilder::build_with_reader_spooled), but transcodes the data of `reader`
+    /// from `encoding` into UTF-8 first (see [`Csv::with_reader_transcoded_seek`]).
+    pub fn build_with_reader_transcoded_seek<R: Read>(
+        &self,
+        reader: R,
+        encoding: &'static Encoding,
+    ) -> io::Result<Csv<SpooledReader>> {
+        self.build_with_reader_spooled(TranscodingReader::new(reader, encoding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn latin1_records_are_transcoded() -> csv::Result<()> {
+        let mut csv_reader = Csv::with_reader_transcoded(
+            b"id,name\n1,cr\xe8me\n2,na\xefve".as_slice(),
+            encoding_rs::WINDOWS_1252,
+        )
+        .into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "crème"]),
+                csv::ByteRecord::from(vec!["2", "naïve"])
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn utf16_with_bom_is_transcoded_regardless_of_encoding() -> io::Result<()> {
+        let utf16le = std::iter::once(0xfeff)
+            .chain("id,name\n1,lemon".encode_utf16())
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        let mut transcoded = String::new();
+        TranscodingReader::for_label(utf16le.as_slice(), "latin1")?
+            .read_to_string(&mut transcoded)?;
+
+        assert_eq!(transcoded, "id,name\n1,lemon");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_label_is_err() {
+        let res = TranscodingReader::for_label("".as_bytes(), "no-such-encoding");
+
+        assert_eq!(
+            res.err().map(|err| err.kind()),
+            Some(io::ErrorKind::InvalidInput)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::cancellation::{CancelledError, TimedOutError};
+use crate::csv_diff::PrimaryKeyColumnOutOfBoundsError;
+use crate::diff_row::RecordUtf8Error;
+use crate::empty_csv::EmptyCsvError;
+use crate::hash_index::HashIndexMismatchError;
+use crate::multi_reader::InconsistentHeadersError;
+use crate::resumable_diff::InvalidCheckpointError;
+use crate::sorted_diff::UnsortedCsvError;
+use crate::task_panic::TaskPanicError;
+
+/// A stable, machine-readable code of an error (e.g. `CSVDIFF_RAGGED_ROW`).
+///
+/// In contrast to the `Display` output of an error, which is meant for humans and might change between releases,
+/// the codes are guaranteed to stay the same. All codes start with `CSVDIFF_`.
+///
+/// This is implemented for every error of this crate, as well as for [`csv::Error`], which is returned
+/// when comparing CSVs.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+use csv_diff::error_code::ErrorCode;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon";
+
+let err = CsvByteDiffLocal::new()?
+    .diff(
+        Csv::with_reader_seek(csv_left.as_bytes()),
+        Csv::with_reader_seek(csv_right.as_bytes()),
+    )
+    .unwrap_err();
+
+assert_eq!(err.code(), "CSVDIFF_RAGGED_ROW");
+Ok(())
+# }
+```
+"##
+)]
+pub trait ErrorCode {
+    /// The stable code of this error.
+    fn code(&self) -> &'static str;
+}
+
+impl ErrorCode for csv::Error {
+    fn code(&self) -> &'static str {
+        match self.kind() {
+            csv::ErrorKind::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| {
+                    inner
+                        .downcast_ref::<TaskPanicError>()
+                        .map(ErrorCode::code)
+                        .or_else(|| inner.downcast_ref::<EmptyCsvError>().map(ErrorCode::code))
+                        .or_else(|| inner.downcast_ref::<CancelledError>().map(ErrorCode::code))
+                        .or_else(|| inner.downcast_ref::<TimedOutError>().map(ErrorCode::code))
+                        .or_else(|| {
+                            inner
+                                .downcast_ref::<UnsortedCsvError>()
+                                .map(ErrorCode::code)
+                        })
+                        .or_else(|| {
+                            inner
+                                .downcast_ref::<InconsistentHeadersError>()
+                                .map(ErrorCode::code)
+                        })
+                        .or_else(|| inner.downcast_ref::<RecordUtf8Error>().map(ErrorCode::code))
+                        .or_else(|| {
+                            inner
+                                .downcast_ref::<PrimaryKeyColumnOutOfBoundsError>()
+                                .map(ErrorCode::code)
+                        })
+                        .or_else(|| {
+                            inner
+                                .downcast_ref::<InvalidCheckpointError>()
+                                .map(ErrorCode::code)
+                        })
+                        .or_else(|| {
+                            inner
+                                .downcast_ref::<HashIndexMismatchError>()
+                                .map(ErrorCode::code)
+                        })
+                })
+                .unwrap_or("CSVDIFF_IO"),
+            csv::ErrorKind::Utf8 { .. } => "CSVDIFF_INVALID_UTF8",
+            csv::ErrorKind::UnequalLengths { .. } => "CSVDIFF_RAGGED_ROW",
+            csv::ErrorKind::Seek => "CSVDIFF_SEEK",
+            csv::ErrorKind::Serialize(_) => "CSVDIFF_SERIALIZE",
+            csv::ErrorKind::Deserialize { .. } => "CSVDIFF_DESERIALIZE",
+            _ => "CSVDIFF_CSV",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx_diff::ApproxCsvDiffBuilderError;
+    #[cfg(feature = "rayon-threads")]
+    use crate::csv::Csv;
+    use crate::csv_diff::CsvByteDiffBuilderError;
+    #[cfg(feature = "rayon-threads")]
+    use crate::csv_diff::CsvByteDiffLocalBuilder;
+    use crate::diff_result::{ColumnIdxError, DiffByteRecordsMergeError};
+    use crate::empty_csv::CsvSide;
+    #[cfg(feature = "rayon-threads")]
+    use crate::empty_csv::EmptyCsvSemantics;
+    use crate::json_lines::JsonLinesError;
+    use crate::run_report::RunReportInternalError;
+    use crate::task_panic::catch_panic;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+    #[cfg(feature = "rayon-threads")]
+    use std::error::Error;
+    use std::io;
+
+    #[test]
+    fn csv_error_wrapping_task_panic_has_task_panic_code() {
+        let err = csv::Error::from(catch_panic(|| panic!("broken")).unwrap_err());
+
+        assert_eq!(err.code(), "CSVDIFF_TASK_PANIC");
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn csv_error_wrapping_empty_csv_error_has_empty_csv_code() -> Result<(), Box<dyn Error>> {
+        let err = CsvByteDiffLocalBuilder::new()
+            .empty_csv_semantics(EmptyCsvSemantics::Error)
+            .build()?
+            .diff(
+                Csv::with_reader_seek("".as_bytes()),
+                Csv::with_reader_seek("id,name".as_bytes()),
+            )
+            .unwrap_err();
+
+        assert_eq!(err.code(), "CSVDIFF_EMPTY_CSV");
+        Ok(())
+    }
+
+    #[test]
+    fn json_lines_error_of_csv_error_has_code_of_csv_error() {
+        let err = JsonLinesError::from(csv::Error::from(io::Error::new(
+            io::ErrorKind::Other,
+            "broken",
+        )));
+
+        assert_eq!(err.code(), "CSVDIFF_IO");
+    }
+
+    #[test]
+    fn codes_are_unique_and_prefixed() {
+        let codes = vec![
+            CsvByteDiffBuilderError::NoPrimaryKeyColumns.code(),
+            ApproxCsvDiffBuilderError::InvalidRelativeError(0.0).code(),
+            ApproxCsvDiffBuilderError::InvalidFailureProbability(0.0).code(),
+            ColumnIdxError::IdxOutOfBounds { idx: 3, len: 2 }.code(),
+            DiffByteRecordsMergeError::ConflictingRecords { key: vec![] }.code(),
+            JsonLinesError::from(io::Error::new(io::ErrorKind::Other, "broken")).code(),
+            RunReportInternalError::RecordCountMismatch {
+                added: 0,
+                deleted: 0,
+                rows_left: 0,
+                rows_right: 1,
+            }
+            .code(),
+            RunReportInternalError::TooManyDifferencesLeft {
+                modified: 1,
+                deleted: 1,
+                rows_left: 1,
+            }
+            .code(),
+            RunReportInternalError::TooManyDifferencesRight {
+                modified: 1,
+                added: 1,
+                rows_right: 1,
+            }
+            .code(),
+            catch_panic(|| panic!("broken")).unwrap_err().code(),
+            CancelledError.code(),
+            TimedOutError.code(),
+            UnsortedCsvError::new(CsvSide::Left, 2).code(),
+            InconsistentHeadersError::new(1).code(),
+            PrimaryKeyColumnOutOfBoundsError::new(5, 3).code(),
+            InvalidCheckpointError::new("diff.checkpoint".into()).code(),
+            HashIndexMismatchError.code(),
+            csv::Error::from(io::Error::new(io::ErrorKind::Other, "broken")).code(),
+        ];
+
+        assert!(codes.iter().all(|code| code.starts_with("CSVDIFF_")));
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error_code::ErrorCode;
+
+/// How to handle malformed records, e.g. a record with a different number of fields than the header
+/// (see [`CsvBuilder::error_policy`](crate::csv::CsvBuilder::error_policy)).
+///
+/// Only errors, after which reading can continue with the next record, are affected by the policy.
+/// An I/O error always aborts the comparison.
+/// # Example: skip a ragged record and inspect it afterwards
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::CsvBuilder};
+use csv_diff::error_code::ErrorCode;
+use csv_diff::error_policy::ErrorPolicy;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon\n\
+2,strawberry,nut";
+let csv_builder = CsvBuilder::new().error_policy(ErrorPolicy::SkipAndReport);
+
+let mut diff_iterator = CsvByteDiff::new()?.diff(
+    csv_builder.build_with_reader(csv_left.as_bytes()),
+    csv_builder.build_with_reader(csv_right.as_bytes()),
+);
+let diff_byte_records = diff_iterator.by_ref().collect::<csv::Result<Vec<_>>>()?;
+let run_report = diff_iterator.run_report();
+
+// "1,lemon" has been skipped, so the record with id 1 has been deleted
+assert_eq!(diff_byte_records.len(), 2);
+let skipped_records = run_report.skipped_records_right();
+assert_eq!(skipped_records.len(), 1);
+assert_eq!(skipped_records[0].line(), 2);
+assert_eq!(skipped_records[0].code(), "CSVDIFF_RAGGED_ROW");
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum ErrorPolicy {
+    /// The comparison fails with the error of the first malformed record.
+    #[default]
+    Abort,
+    /// Malformed records are skipped, as if they didn't exist, and the comparison continues.
+    /// The skipped records and their errors are reported as [`SkippedRecord`]s.
+    SkipAndReport,
+}
+
+impl ErrorPolicy {
+    /// Return `true`, if the record, that has caused `err`, is skipped according to this policy.
+    pub(crate) fn is_skipped(&self, err: &csv::Error) -> bool {
+        match self {
+            Self::Abort => false,
+            Self::SkipAndReport => matches!(
+                err.kind(),
+                csv::ErrorKind::UnequalLengths { .. } | csv::ErrorKind::Utf8 { .. }
+            ),
+        }
+    }
+}
+
+/// A malformed record, that has been skipped due to [`ErrorPolicy::SkipAndReport`], together with its error.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SkippedRecord {
+    byte_record: csv::ByteRecord,
+    line: u64,
+    code: &'static str,
+    message: String,
+}
+
+impl SkippedRecord {
+    pub(crate) fn new(byte_record: csv::ByteRecord, err: &csv::Error) -> Self {
+        Self {
+            line: byte_record.position().map_or(0, |pos| pos.line()),
+            byte_record,
+            code: err.code(),
+            message: err.to_string(),
+        }
+    }
+
+    /// The fields of the record, as far as they have been parsed.
+    pub fn byte_record(&self) -> &csv::ByteRecord {
+        &self.byte_record
+    }
+
+    /// The line, where the record starts.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// The message of the error, that has caused the record to be skipped.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl ErrorCode for SkippedRecord {
+    /// The code of the error, that has caused the record to be skipped (e.g. `CSVDIFF_RAGGED_ROW`).
+    fn code(&self) -> &'static str {
+        self.code
+    }
+}
+
+/// The malformed records of both CSVs, that have been skipped due to [`ErrorPolicy::SkipAndReport`]
+/// (see [`CsvByteDiffLocal::diff_with_skipped_records`](crate::csv_diff::CsvByteDiffLocal::diff_with_skipped_records)).
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct SkippedRecords {
+    left: Vec<SkippedRecord>,
+    right: Vec<SkippedRecord>,
+}
+
+impl SkippedRecords {
+    pub(crate) fn new(left: Vec<SkippedRecord>, right: Vec<SkippedRecord>) -> Self {
+        Self { left, right }
+    }
+
+    /// The skipped records of the left CSV.
+    pub fn left(&self) -> &[SkippedRecord] {
+        &self.left
+    }
+
+    /// The skipped records of the right CSV.
+    pub fn right(&self) -> &[SkippedRecord] {
+        &self.right
+    }
+
+    /// Return `true`, if no record has been skipped at all.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn only_recoverable_errors_are_skipped() {
+        let ragged_row_err = csv::ReaderBuilder::new()
+            .from_reader("id,name\n1".as_bytes())
+            .byte_records()
+            .find_map(Result::err)
+            .expect("a ragged row");
+        let io_err = csv::Error::from(io::Error::new(io::ErrorKind::Other, "broken pipe"));
+
+        assert!(!ErrorPolicy::Abort.is_skipped(&ragged_row_err));
+        assert!(ErrorPolicy::SkipAndReport.is_skipped(&ragged_row_err));
+        assert!(!ErrorPolicy::SkipAndReport.is_skipped(&io_err));
+    }
+}
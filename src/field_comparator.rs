@@ -0,0 +1,942 @@
+use crate::diff_row::DiffByteRecord;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Decides, whether two fields of the same column are equal.
+///
+/// By default, fields are compared byte-wise. A `FieldComparator` can be registered for a column
+/// (see [`CsvByteDiffLocalBuilder::field_comparator`](crate::csv_diff::CsvByteDiffLocalBuilder::field_comparator)
+/// and [`CsvByteDiffBuilder::field_comparator`](crate::csv_diff::CsvByteDiffBuilder::field_comparator)) in order to
+/// treat fields as equal, that are different byte-wise, but represent the same value (e.g. `2024-01-02 03:04:05` vs `2024-01-02T03:04:05Z`).
+///
+/// Comparators are only consulted for records that are different byte-wise. If all fields that are different
+/// byte-wise are equal according to their comparators, the record is no longer reported as
+/// [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+pub trait FieldComparator: Debug + Send + Sync {
+    fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool;
+}
+
+/// Treats all fields as equal, so that changes in its column never lead to a [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+///
+/// In contrast to removing the column from the CSVs, the values of the column are still part of the emitted records
+/// (e.g. for context), but its index never appears in `field_indices`.
+/// See also [`CsvByteDiffLocalBuilder::ignore_columns_for_modify`](crate::csv_diff::CsvByteDiffLocalBuilder::ignore_columns_for_modify)
+/// and [`CsvByteDiffBuilder::ignore_columns_for_modify`](crate::csv_diff::CsvByteDiffBuilder::ignore_columns_for_modify).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IgnoreComparator;
+
+impl FieldComparator for IgnoreComparator {
+    fn eq(&self, _field_left: &[u8], _field_right: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Compares fields by their numeric value in decimal notation, so that different representations of the same number
+/// are equal (e.g. `1.0`, `1.00` and `+1`).
+///
+/// Surrounding whitespace, a leading `+`, leading zeros of the integer part and trailing zeros of the fractional part
+/// are ignored, as well as the sign of zero. If any of the two fields is not a number, they are compared byte-wise.
+/// See also [`CsvByteDiffLocalBuilder::numeric_columns`](crate::csv_diff::CsvByteDiffLocalBuilder::numeric_columns)
+/// and [`CsvByteDiffBuilder::numeric_columns`](crate::csv_diff::CsvByteDiffBuilder::numeric_columns), which also
+/// apply this to hashing.
+/// # Example
+/// ```
+/// use csv_diff::field_comparator::{FieldComparator, NumericComparator};
+///
+/// let cmp = NumericComparator;
+/// assert!(cmp.eq(b"1.0", b"+1"));
+/// assert!(cmp.eq(b"-0.50", b"-.5"));
+/// assert!(!cmp.eq(b"1.0", b"1.01"));
+/// assert!(!cmp.eq(b"1e3", b"1000"));
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NumericComparator;
+
+impl FieldComparator for NumericComparator {
+    fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+        let mut canonical_left = Vec::new();
+        let mut canonical_right = Vec::new();
+        match (
+            canonical_number(field_left, &mut canonical_left),
+            canonical_number(field_right, &mut canonical_right),
+        ) {
+            (true, true) => canonical_left == canonical_right,
+            _ => field_left == field_right,
+        }
+    }
+}
+
+/// Write the canonical representation of the number `field` to `canonical`.
+/// Returns `false` (and leaves `canonical` in an unspecified state), if `field` is not a number.
+fn canonical_number(field: &[u8], canonical: &mut Vec<u8>) -> bool {
+    let start = field
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(field.len());
+    let end = field
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |idx| idx + 1);
+    let field = &field[start..end];
+    let (is_negative, digits) = match field.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, field),
+    };
+    let (int_part, frac_part) = match digits.iter().position(|b| *b == b'.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (digits, &[][..]),
+    };
+    if (int_part.is_empty() && frac_part.is_empty())
+        || !int_part.iter().chain(frac_part).all(u8::is_ascii_digit)
+    {
+        return false;
+    }
+    let int_part = &int_part[int_part
+        .iter()
+        .position(|b| *b != b'0')
+        .unwrap_or(int_part.len())..];
+    let frac_part = &frac_part[..frac_part
+        .iter()
+        .rposition(|b| *b != b'0')
+        .map_or(0, |idx| idx + 1)];
+    canonical.clear();
+    if is_negative && !(int_part.is_empty() && frac_part.is_empty()) {
+        canonical.push(b'-');
+    }
+    if int_part.is_empty() {
+        canonical.push(b'0');
+    } else {
+        canonical.extend_from_slice(int_part);
+    }
+    if !frac_part.is_empty() {
+        canonical.push(b'.');
+        canonical.extend_from_slice(frac_part);
+    }
+    true
+}
+
+/// A part of a field, that is blanked before the field is hashed and compared, e.g. the volatile suffix of a session id
+/// (see [`CsvByteDiffLocalBuilder::ignore_pattern`](crate::csv_diff::CsvByteDiffLocalBuilder::ignore_pattern)
+/// and [`CsvByteDiffBuilder::ignore_pattern`](crate::csv_diff::CsvByteDiffBuilder::ignore_pattern)).
+/// # Example
+/// ```
+/// use csv_diff::field_comparator::IgnorePattern;
+///
+/// let mut field = b"session-42:8f3a".to_vec();
+/// IgnorePattern::KeepPrefix(10).blank(&mut field);
+/// assert_eq!(field, b"session-42");
+/// ```
+#[derive(Debug, Clone)]
+pub enum IgnorePattern {
+    /// Blank everything after the first `n` bytes, so that only the prefix of the field is compared.
+    KeepPrefix(usize),
+    /// Blank every match of the regular expression.
+    ///
+    /// This is only available with the Cargo feature `regex`.
+    #[cfg(feature = "regex")]
+    Regex(regex::bytes::Regex),
+}
+
+impl IgnorePattern {
+    /// Remove the ignored parts of `field` in place.
+    pub fn blank(&self, field: &mut Vec<u8>) {
+        match self {
+            Self::KeepPrefix(n) => field.truncate(*n),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => {
+                if let std::borrow::Cow::Owned(blanked) = regex.replace_all(field, &b""[..]) {
+                    *field = blanked;
+                }
+            }
+        }
+    }
+}
+
+/// Maps fields to a canonical representation before they are hashed and compared, so that records, which only differ
+/// in the representation of their values, are equal: fields of numeric columns are replaced by their canonical number
+/// (see [`NumericComparator`]), null tokens (e.g. `NULL` or `N/A`) by an empty field and the ignored parts of fields
+/// (see [`IgnorePattern`]) are removed.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FieldCanonicalizer {
+    numeric_columns: Vec<usize>,
+    null_tokens: Vec<Vec<u8>>,
+    null_tokens_by_column: HashMap<usize, Vec<Vec<u8>>>,
+    ignore_patterns_by_column: HashMap<usize, Vec<IgnorePattern>>,
+}
+
+impl FieldCanonicalizer {
+    pub(crate) fn insert_numeric_column(&mut self, column: usize) {
+        if !self.numeric_columns.contains(&column) {
+            self.numeric_columns.push(column);
+        }
+    }
+
+    pub(crate) fn insert_null_token(&mut self, column: Option<usize>, null_token: Vec<u8>) {
+        match column {
+            Some(column) => self
+                .null_tokens_by_column
+                .entry(column)
+                .or_default()
+                .push(null_token),
+            None => self.null_tokens.push(null_token),
+        }
+    }
+
+    pub(crate) fn insert_ignore_pattern(&mut self, column: usize, ignore_pattern: IgnorePattern) {
+        self.ignore_patterns_by_column
+            .entry(column)
+            .or_default()
+            .push(ignore_pattern);
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.numeric_columns.is_empty()
+            && self.null_tokens.is_empty()
+            && self.null_tokens_by_column.is_empty()
+            && self.ignore_patterns_by_column.is_empty()
+    }
+
+    /// A hash of all settings, which is the same for canonicalizers, that produce the same canonical fields
+    /// (regardless of the order, in which columns and null tokens have been inserted).
+    pub(crate) fn fingerprint(&self) -> u128 {
+        fn update_tokens(hasher: &mut Xxh3, tokens: &[Vec<u8>]) {
+            let mut tokens = tokens.iter().collect::<Vec<_>>();
+            tokens.sort_unstable();
+            tokens.dedup();
+            hasher.update(&(tokens.len() as u64).to_le_bytes());
+            for token in tokens {
+                hasher.update(&(token.len() as u64).to_le_bytes());
+                hasher.update(token);
+            }
+        }
+
+        let mut hasher = Xxh3::new();
+        let mut numeric_columns = self.numeric_columns.clone();
+        numeric_columns.sort_unstable();
+        hasher.update(&(numeric_columns.len() as u64).to_le_bytes());
+        for column in numeric_columns {
+            hasher.update(&(column as u64).to_le_bytes());
+        }
+        update_tokens(&mut hasher, &self.null_tokens);
+        let mut null_tokens_by_column = self.null_tokens_by_column.iter().collect::<Vec<_>>();
+        null_tokens_by_column.sort_unstable_by_key(|(column, _)| **column);
+        hasher.update(&(null_tokens_by_column.len() as u64).to_le_bytes());
+        for (column, null_tokens) in null_tokens_by_column {
+            hasher.update(&(*column as u64).to_le_bytes());
+            update_tokens(&mut hasher, null_tokens);
+        }
+        // the ignore patterns of a column are applied one after another, so their order matters
+        let mut ignore_patterns_by_column =
+            self.ignore_patterns_by_column.iter().collect::<Vec<_>>();
+        ignore_patterns_by_column.sort_unstable_by_key(|(column, _)| **column);
+        hasher.update(&(ignore_patterns_by_column.len() as u64).to_le_bytes());
+        for (column, ignore_patterns) in ignore_patterns_by_column {
+            hasher.update(&(*column as u64).to_le_bytes());
+            hasher.update(&(ignore_patterns.len() as u64).to_le_bytes());
+            for ignore_pattern in ignore_patterns {
+                match ignore_pattern {
+                    IgnorePattern::KeepPrefix(n) => {
+                        hasher.update(&[0]);
+                        hasher.update(&(*n as u64).to_le_bytes());
+                    }
+                    #[cfg(feature = "regex")]
+                    IgnorePattern::Regex(regex) => {
+                        hasher.update(&[1]);
+                        hasher.update(&(regex.as_str().len() as u64).to_le_bytes());
+                        hasher.update(regex.as_str().as_bytes());
+                    }
+                }
+            }
+        }
+        hasher.digest128()
+    }
+
+    fn is_null(&self, column: usize, field: &[u8]) -> bool {
+        self.null_tokens
+            .iter()
+            .any(|null_token| null_token == field)
+            || self
+                .null_tokens_by_column
+                .get(&column)
+                .map_or(false, |null_tokens| {
+                    null_tokens.iter().any(|null_token| null_token == field)
+                })
+    }
+
+    /// Return the canonical representation of `field` of column `column` (which might be written to `canonical`).
+    ///
+    /// Null tokens and numbers are canonicalized as a whole, so the ignore patterns of their column are not applied.
+    fn canonical_field<'a>(
+        &self,
+        column: usize,
+        field: &'a [u8],
+        canonical: &'a mut Vec<u8>,
+    ) -> &'a [u8] {
+        if self.is_null(column, field) {
+            &[]
+        } else if self.numeric_columns.contains(&column) && canonical_number(field, canonical) {
+            canonical
+        } else if let Some(ignore_patterns) = self.ignore_patterns_by_column.get(&column) {
+            canonical.clear();
+            canonical.extend_from_slice(field);
+            for ignore_pattern in ignore_patterns {
+                ignore_pattern.blank(canonical);
+            }
+            canonical
+        } else {
+            field
+        }
+    }
+
+    /// Return `true`, if `field_left` and `field_right` of column `column` have the same canonical representation.
+    pub(crate) fn eq(&self, column: usize, field_left: &[u8], field_right: &[u8]) -> bool {
+        let mut canonical_left = Vec::new();
+        let mut canonical_right = Vec::new();
+        self.canonical_field(column, field_left, &mut canonical_left)
+            == self.canonical_field(column, field_right, &mut canonical_right)
+    }
+
+    /// Copy `record` to `canonical_record` with all fields replaced by their canonical representation.
+    pub(crate) fn canonicalize(
+        &self,
+        record: &csv::ByteRecord,
+        canonical_record: &mut csv::ByteRecord,
+    ) {
+        let mut canonical = Vec::new();
+        canonical_record.clear();
+        for (idx, field) in record.iter().enumerate() {
+            canonical_record.push_field(self.canonical_field(idx, field, &mut canonical));
+        }
+    }
+}
+
+/// Compares fields with a closure, e.g. for domain rules, that don't deserve their own type.
+/// # Example
+/// ```
+/// use csv_diff::field_comparator::{FieldComparator, FnComparator};
+///
+/// let cmp = FnComparator::new(|field_left: &[u8], field_right: &[u8]| {
+///     field_left.eq_ignore_ascii_case(field_right)
+/// });
+///
+/// assert!(cmp.eq(b"lemon", b"LEMON"));
+/// assert!(!cmp.eq(b"lemon", b"lime"));
+/// ```
+#[derive(Clone, Copy)]
+pub struct FnComparator<F> {
+    eq: F,
+}
+
+impl<F> FnComparator<F>
+where
+    F: Fn(&[u8], &[u8]) -> bool + Send + Sync,
+{
+    pub fn new(eq: F) -> Self {
+        Self { eq }
+    }
+}
+
+impl<F> Debug for FnComparator<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnComparator").finish_non_exhaustive()
+    }
+}
+
+impl<F> FieldComparator for FnComparator<F>
+where
+    F: Fn(&[u8], &[u8]) -> bool + Send + Sync,
+{
+    fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+        (self.eq)(field_left, field_right)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FieldComparators {
+    by_column: HashMap<usize, Arc<dyn FieldComparator>>,
+    by_header: Vec<(Vec<u8>, Arc<dyn FieldComparator>)>,
+    canonicalizer: FieldCanonicalizer,
+    redacted_columns: HashSet<usize>,
+}
+
+/// The value, that replaces the fields of redacted columns in the emitted records.
+pub(crate) const REDACTED_FIELD: &[u8] = b"***";
+
+impl FieldComparators {
+    pub(crate) fn insert(&mut self, column: usize, comparator: Arc<dyn FieldComparator>) {
+        self.by_column.insert(column, comparator);
+    }
+
+    /// The canonicalizer, that is applied to fields before they are hashed and before their comparators are consulted.
+    pub(crate) fn canonicalizer(&self) -> &FieldCanonicalizer {
+        &self.canonicalizer
+    }
+
+    pub(crate) fn canonicalizer_mut(&mut self) -> &mut FieldCanonicalizer {
+        &mut self.canonicalizer
+    }
+
+    /// Register `comparator` for the column named `header`, which is resolved by [`resolve_headers`](Self::resolve_headers).
+    pub(crate) fn insert_by_header(
+        &mut self,
+        header: Vec<u8>,
+        comparator: Arc<dyn FieldComparator>,
+    ) {
+        self.by_header.push((header, comparator));
+    }
+
+    /// Return `true`, if there are comparators, whose header has not been resolved to a column yet.
+    #[inline]
+    pub(crate) fn has_unresolved_headers(&self) -> bool {
+        !self.by_header.is_empty()
+    }
+
+    /// Register the comparators, that have been registered by header name, for the column of that name in `headers`
+    /// (replacing any comparator, that has been registered by index). Header names, that don't exist, are ignored,
+    /// as well as all of them, if there are no `headers`.
+    pub(crate) fn resolve_headers(&mut self, headers: Option<&csv::ByteRecord>) {
+        for (header, comparator) in std::mem::take(&mut self.by_header) {
+            if let Some(column) =
+                headers.and_then(|headers| headers.iter().position(|name| name == header))
+            {
+                self.by_column.insert(column, comparator);
+            }
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.by_column.is_empty() && self.by_header.is_empty() && self.canonicalizer.is_empty()
+    }
+
+    /// Replace the fields of `column` with [`REDACTED_FIELD`] in the emitted records (see [`redact`](Self::redact)).
+    pub(crate) fn insert_redacted_column(&mut self, column: usize) {
+        self.redacted_columns.insert(column);
+    }
+
+    /// Replace the fields of all redacted columns in the records of `diff_record`, which must already have been
+    /// compared, because the records lose their original fields. Lines and `field_indices` are kept.
+    pub(crate) fn redact(&self, diff_record: DiffByteRecord) -> DiffByteRecord {
+        if self.redacted_columns.is_empty() {
+            return diff_record;
+        }
+        diff_record.map_records(|byte_record| self.redact_record(byte_record))
+    }
+
+    pub(crate) fn redact_record(&self, byte_record: csv::ByteRecord) -> csv::ByteRecord {
+        if !self
+            .redacted_columns
+            .iter()
+            .any(|&column| column < byte_record.len())
+        {
+            return byte_record;
+        }
+        let mut redacted = byte_record
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                if self.redacted_columns.contains(&idx) {
+                    REDACTED_FIELD
+                } else {
+                    field
+                }
+            })
+            .collect::<csv::ByteRecord>();
+        redacted.set_position(byte_record.position().cloned());
+        redacted
+    }
+
+    /// Remove all field indices of a `Modify` that are equal according to their canonical representation
+    /// or their comparator.
+    /// Returns `None`, if no field index remains (i.e. the records are equal).
+    pub(crate) fn apply(&self, diff_record: DiffByteRecord) -> Option<DiffByteRecord> {
+        match diff_record {
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                mut field_indices,
+            } if !self.is_empty() && !field_indices.is_empty() => {
+                field_indices.retain(|idx| {
+                    let field_left = delete.byte_record().get(*idx).unwrap_or_default();
+                    let field_right = add.byte_record().get(*idx).unwrap_or_default();
+                    !self.canonicalizer.eq(*idx, field_left, field_right)
+                        && match self.by_column.get(idx) {
+                            Some(comparator) => !comparator.eq(field_left, field_right),
+                            None => true,
+                        }
+                });
+                (!field_indices.is_empty()).then_some(DiffByteRecord::Modify {
+                    delete,
+                    add,
+                    field_indices,
+                })
+            }
+            diff_record => Some(diff_record),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+pub use self::datetime::DateTimeComparator;
+
+#[cfg(feature = "chrono")]
+mod datetime {
+    use super::FieldComparator;
+    use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+    use std::time::Duration;
+
+    /// Compares fields as points in time, so that different representations of the same instant are equal.
+    ///
+    /// Both fields are parsed as [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) first and then with
+    /// every configured [`format`](DateTimeComparator::format) (see [`chrono::format::strftime`] for the syntax).
+    /// Formats without an offset are interpreted in the [`default_offset`](DateTimeComparator::default_offset) (UTC by default).
+    /// If any of the two fields can't be parsed, they are compared byte-wise.
+    ///
+    /// This is only available with the Cargo feature `chrono`.
+    /// # Example
+    /// ```
+    /// use csv_diff::field_comparator::{DateTimeComparator, FieldComparator};
+    /// use std::time::Duration;
+    ///
+    /// let cmp = DateTimeComparator::new()
+    ///     .format("%Y-%m-%d %H:%M:%S")
+    ///     .tolerance(Duration::from_secs(1));
+    ///
+    /// assert!(cmp.eq(b"2024-01-02 03:04:05", b"2024-01-02T03:04:05Z"));
+    /// assert!(cmp.eq(b"2024-01-02 03:04:05", b"2024-01-02T04:04:06+01:00"));
+    /// assert!(!cmp.eq(b"2024-01-02 03:04:05", b"2024-01-02T03:04:07Z"));
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct DateTimeComparator {
+        formats: Vec<String>,
+        default_offset: FixedOffset,
+        tolerance: chrono::Duration,
+    }
+
+    impl Default for DateTimeComparator {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl DateTimeComparator {
+        pub fn new() -> Self {
+            Self {
+                formats: Vec::new(),
+                default_offset: FixedOffset::east_opt(0).expect("UTC is a valid offset"),
+                tolerance: chrono::Duration::zero(),
+            }
+        }
+
+        /// Add a format, that is tried (in the order of registration), when a field is not in RFC 3339 format.
+        pub fn format(mut self, format: impl Into<String>) -> Self {
+            self.formats.push(format.into());
+            self
+        }
+
+        /// Set the offset for formats, that don't contain an offset themselves (default: UTC).
+        pub fn default_offset(mut self, offset: FixedOffset) -> Self {
+            self.default_offset = offset;
+            self
+        }
+
+        /// Treat two points in time as equal, when they are at most `tolerance` apart (default: zero).
+        pub fn tolerance(mut self, tolerance: Duration) -> Self {
+            self.tolerance = chrono::Duration::from_std(tolerance).unwrap_or(chrono::Duration::MAX);
+            self
+        }
+
+        fn parse(&self, field: &[u8]) -> Option<DateTime<FixedOffset>> {
+            let field = std::str::from_utf8(field).ok()?.trim();
+            DateTime::parse_from_rfc3339(field).ok().or_else(|| {
+                self.formats.iter().find_map(|format| {
+                    DateTime::parse_from_str(field, format).ok().or_else(|| {
+                        NaiveDateTime::parse_from_str(field, format)
+                            .ok()
+                            .and_then(|naive| {
+                                self.default_offset.from_local_datetime(&naive).single()
+                            })
+                    })
+                })
+            })
+        }
+    }
+
+    impl FieldComparator for DateTimeComparator {
+        fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+            match (self.parse(field_left), self.parse(field_right)) {
+                (Some(left), Some(right)) => {
+                    let diff = left.signed_duration_since(right);
+                    diff <= self.tolerance && -diff <= self.tolerance
+                }
+                _ => field_left == field_right,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn datetime_rfc3339_with_different_offsets_is_equal() {
+            let cmp = DateTimeComparator::new();
+
+            assert!(cmp.eq(b"2024-01-02T03:04:05Z", b"2024-01-02T05:04:05+02:00"));
+        }
+
+        #[test]
+        fn datetime_naive_format_uses_default_offset() {
+            let cmp = DateTimeComparator::new()
+                .format("%d.%m.%Y %H:%M")
+                .default_offset(FixedOffset::east_opt(3600).unwrap());
+
+            assert!(cmp.eq(b"02.01.2024 04:04", b"2024-01-02T03:04:00Z"));
+            assert!(!cmp.eq(b"02.01.2024 03:04", b"2024-01-02T03:04:00Z"));
+        }
+
+        #[test]
+        fn datetime_within_tolerance_is_equal() {
+            let cmp = DateTimeComparator::new().tolerance(Duration::from_secs(60));
+
+            assert!(cmp.eq(b"2024-01-02T03:04:05Z", b"2024-01-02T03:05:05Z"));
+            assert!(cmp.eq(b"2024-01-02T03:05:05Z", b"2024-01-02T03:04:05Z"));
+            assert!(!cmp.eq(b"2024-01-02T03:04:05Z", b"2024-01-02T03:05:06Z"));
+        }
+
+        #[test]
+        fn datetime_unparsable_falls_back_to_byte_comparison() {
+            let cmp = DateTimeComparator::new();
+
+            assert!(cmp.eq(b"n/a", b"n/a"));
+            assert!(!cmp.eq(b"n/a", b"2024-01-02T03:04:05Z"));
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+pub use self::decimal::DecimalComparator;
+
+#[cfg(feature = "rust_decimal")]
+mod decimal {
+    use super::FieldComparator;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    /// Compares fields as decimal numbers, so that different representations of the same amount are equal
+    /// (e.g. `$1,234.50` vs `1234.5`).
+    ///
+    /// Before parsing, surrounding whitespace and [`currency_symbols`](DecimalComparator::currency_symbols)
+    /// (in front of or behind the number) are removed as well as every [`thousands_separator`](DecimalComparator::thousands_separator).
+    /// Differences in precision (trailing zeros) are ignored.
+    /// If any of the two fields can't be parsed, they are compared byte-wise.
+    ///
+    /// This is only available with the Cargo feature `rust_decimal`.
+    /// # Example
+    /// ```
+    /// use csv_diff::field_comparator::{DecimalComparator, FieldComparator};
+    ///
+    /// let cmp = DecimalComparator::new();
+    /// assert!(cmp.eq(b"$1,234.50", b"1234.5"));
+    /// assert!(cmp.eq(b"-12.00 EUR", b"-12"));
+    /// assert!(!cmp.eq(b"1,234.50", b"1,234.51"));
+    ///
+    /// let cmp = DecimalComparator::new()
+    ///     .decimal_separator(',')
+    ///     .thousands_separator('.');
+    /// assert!(cmp.eq("1.234,50 €".as_bytes(), b"1234,5"));
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct DecimalComparator {
+        decimal_separator: char,
+        thousands_separator: Option<char>,
+        currency_symbols: Vec<String>,
+    }
+
+    impl Default for DecimalComparator {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl DecimalComparator {
+        pub fn new() -> Self {
+            Self {
+                decimal_separator: '.',
+                thousands_separator: Some(','),
+                currency_symbols: ["$", "€", "£", "¥", "USD", "EUR", "GBP", "JPY", "CHF"]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+            }
+        }
+
+        /// Set the decimal separator (default: `.`).
+        pub fn decimal_separator(mut self, decimal_separator: char) -> Self {
+            self.decimal_separator = decimal_separator;
+            self
+        }
+
+        /// Set the thousands separator (default: `,`).
+        pub fn thousands_separator(mut self, thousands_separator: char) -> Self {
+            self.thousands_separator = Some(thousands_separator);
+            self
+        }
+
+        /// Don't allow any thousands separator.
+        pub fn no_thousands_separator(mut self) -> Self {
+            self.thousands_separator = None;
+            self
+        }
+
+        /// Set the currency symbols, that are ignored in front of or behind the number
+        /// (default: `$`, `€`, `£`, `¥`, `USD`, `EUR`, `GBP`, `JPY` and `CHF`).
+        pub fn currency_symbols<S: Into<String>>(
+            mut self,
+            currency_symbols: impl IntoIterator<Item = S>,
+        ) -> Self {
+            self.currency_symbols = currency_symbols.into_iter().map(Into::into).collect();
+            self
+        }
+
+        fn parse(&self, field: &[u8]) -> Option<Decimal> {
+            let mut field = std::str::from_utf8(field).ok()?.trim();
+            let is_negative = match field.strip_prefix('-') {
+                Some(rest) => {
+                    field = rest;
+                    true
+                }
+                None => false,
+            };
+            for currency_symbol in &self.currency_symbols {
+                if let Some(rest) = field.strip_prefix(currency_symbol.as_str()) {
+                    field = rest;
+                } else if let Some(rest) = field.strip_suffix(currency_symbol.as_str()) {
+                    field = rest;
+                } else {
+                    continue;
+                }
+                break;
+            }
+            let field = field.trim();
+            let mut normalized = String::with_capacity(field.len() + 1);
+            if is_negative {
+                normalized.push('-');
+            }
+            for c in field.chars() {
+                if Some(c) == self.thousands_separator {
+                    continue;
+                } else if c == self.decimal_separator {
+                    normalized.push('.');
+                } else if c.is_ascii_digit() {
+                    normalized.push(c);
+                } else {
+                    return None;
+                }
+            }
+            Decimal::from_str(&normalized).ok()
+        }
+    }
+
+    impl FieldComparator for DecimalComparator {
+        fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+            match (self.parse(field_left), self.parse(field_right)) {
+                (Some(left), Some(right)) => left == right,
+                _ => field_left == field_right,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn decimal_different_precision_is_equal() {
+            let cmp = DecimalComparator::new();
+
+            assert!(cmp.eq(b"1.5", b"1.500"));
+            assert!(cmp.eq(b"2", b"2.0"));
+            assert!(!cmp.eq(b"1.5", b"1.05"));
+        }
+
+        #[test]
+        fn decimal_thousands_separator_and_currency_symbols_are_ignored() {
+            let cmp = DecimalComparator::new();
+
+            assert!(cmp.eq(b"$1,000,000", b"1000000.00"));
+            assert!(cmp.eq(b"-$3.10", b"-3.1 USD"));
+            assert!(!cmp.eq(b"-$3.10", b"3.1 USD"));
+        }
+
+        #[test]
+        fn decimal_custom_separators() {
+            let cmp = DecimalComparator::new()
+                .decimal_separator(',')
+                .thousands_separator(' ')
+                .currency_symbols(vec!["kr"]);
+
+            assert!(cmp.eq(b"1 234,5 kr", b"1234,50"));
+            assert!(!cmp.eq(b"1 234,5 kr", b"1234.50"));
+        }
+
+        #[test]
+        fn decimal_unparsable_falls_back_to_byte_comparison() {
+            let cmp = DecimalComparator::new();
+
+            assert!(cmp.eq(b"n/a", b"n/a"));
+            assert!(!cmp.eq(b"n/a", b"0"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug)]
+    struct CaseInsensitive;
+
+    impl FieldComparator for CaseInsensitive {
+        fn eq(&self, field_left: &[u8], field_right: &[u8]) -> bool {
+            field_left.eq_ignore_ascii_case(field_right)
+        }
+    }
+
+    fn field_comparators() -> FieldComparators {
+        let mut field_comparators = FieldComparators::default();
+        field_comparators.insert(1, Arc::new(CaseInsensitive));
+        field_comparators
+    }
+
+    #[test]
+    fn apply_all_changed_fields_equal_by_comparator_is_none() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "abc"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ABC"]), 2),
+            field_indices: vec![1],
+        };
+
+        assert_eq!(field_comparators().apply(diff_record), None);
+    }
+
+    #[test]
+    fn apply_only_removes_field_indices_equal_by_comparator() {
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "abc", "x"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ABC", "y"]), 2),
+            field_indices: vec![1, 2],
+        };
+
+        let expected = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "abc", "x"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ABC", "y"]), 2),
+            field_indices: vec![2],
+        };
+
+        assert_eq!(field_comparators().apply(diff_record), Some(expected));
+    }
+
+    #[test]
+    fn resolve_headers_registers_comparator_for_column_of_header() {
+        let mut field_comparators = FieldComparators::default();
+        field_comparators.insert_by_header(
+            b"name".to_vec(),
+            Arc::new(FnComparator::new(
+                |field_left: &[u8], field_right: &[u8]| {
+                    field_left.eq_ignore_ascii_case(field_right)
+                },
+            )),
+        );
+        field_comparators.insert_by_header(b"missing".to_vec(), Arc::new(IgnoreComparator));
+        field_comparators.resolve_headers(Some(&csv::ByteRecord::from(vec!["id", "name", "kind"])));
+        let diff_record = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "abc", "x"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ABC", "y"]), 2),
+            field_indices: vec![1, 2],
+        };
+
+        let expected = DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "abc", "x"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "ABC", "y"]), 2),
+            field_indices: vec![2],
+        };
+
+        assert!(!field_comparators.has_unresolved_headers());
+        assert_eq!(field_comparators.apply(diff_record), Some(expected));
+    }
+
+    #[test]
+    fn apply_add_is_unchanged() {
+        let diff_record = DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["1", "abc"]),
+            2,
+        ));
+
+        assert_eq!(
+            field_comparators().apply(diff_record.clone()),
+            Some(diff_record)
+        );
+    }
+
+    #[test]
+    fn canonicalize_numeric_fields_only_of_numeric_columns() {
+        let mut canonicalizer = FieldCanonicalizer::default();
+        for column in [0, 1, 2, 3] {
+            canonicalizer.insert_numeric_column(column);
+        }
+        let mut canonical_record = csv::ByteRecord::new();
+
+        canonicalizer.canonicalize(
+            &csv::ByteRecord::from(vec!["007", " +1.50 ", "-0.0", "n/a", "1.0"]),
+            &mut canonical_record,
+        );
+
+        assert_eq!(
+            canonical_record,
+            csv::ByteRecord::from(vec!["7", "1.5", "0", "n/a", "1.0"])
+        );
+    }
+
+    #[test]
+    fn canonicalize_null_tokens_globally_and_per_column() {
+        let mut canonicalizer = FieldCanonicalizer::default();
+        canonicalizer.insert_null_token(None, b"NULL".to_vec());
+        canonicalizer.insert_null_token(Some(1), b"-".to_vec());
+        let mut canonical_record = csv::ByteRecord::new();
+
+        canonicalizer.canonicalize(
+            &csv::ByteRecord::from(vec!["NULL", "-", "-", "null"]),
+            &mut canonical_record,
+        );
+
+        assert_eq!(
+            canonical_record,
+            csv::ByteRecord::from(vec!["", "", "-", "null"])
+        );
+        assert!(canonicalizer.eq(1, b"NULL", b"-"));
+        assert!(!canonicalizer.eq(2, b"NULL", b"-"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn canonicalizer_blanks_regex_matches_only_in_their_column() {
+        let mut canonicalizer = FieldCanonicalizer::default();
+        canonicalizer.insert_ignore_pattern(
+            1,
+            IgnorePattern::Regex(regex::bytes::Regex::new(r"\?sid=[0-9a-f]+").unwrap()),
+        );
+
+        assert!(canonicalizer.eq(1, b"/cart?sid=8f3a", b"/cart?sid=d1e9"));
+        assert!(!canonicalizer.eq(1, b"/cart?sid=8f3a", b"/home?sid=8f3a"));
+        assert!(!canonicalizer.eq(2, b"/cart?sid=8f3a", b"/cart?sid=d1e9"));
+    }
+}
@@ -0,0 +1,73 @@
+use std::cmp::{max, min};
+use std::convert::TryInto;
+
+/// Decides, how often the records, that have been matched, are flushed from the hash maps of a comparison.
+///
+/// While comparing, every record of one CSV is kept in a hash map, until the record with the same key
+/// has been read from the other CSV. Every `n` lines, the map is scanned: matched records are emitted (or dropped, when they are equal),
+/// so that their memory can be reused. Flushing often keeps the maps small, but costs a scan over all records,
+/// that are still unmatched; this pays off for CSVs, that are mostly equal and in the same order,
+/// but not for CSVs, that are very different or ordered differently.
+///
+/// Set it with [`CsvByteDiffBuilder::flush_threshold`](crate::csv_diff::CsvByteDiffBuilder::flush_threshold)
+/// or [`CsvByteDiffLocalBuilder::flush_threshold`](crate::csv_diff::CsvByteDiffLocalBuilder::flush_threshold).
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum FlushThreshold {
+    /// Flush every `n` lines. `0` never flushes.
+    Fixed(usize),
+    /// Flush every time the given fraction of rows has been read.
+    ///
+    /// [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal) knows the number of rows of each CSV up front
+    /// (if the fraction of them is `0`, the maps are flushed once, after all rows have been read).
+    /// [`CsvByteDiff`](crate::csv_diff::CsvByteDiff) doesn't, so it uses the fraction of the rows, that have been
+    /// read so far, but at least `10` rows, and recalculates it every `100` rows.
+    FractionOfRows(f64),
+    /// Never flush; all records are kept until the comparison has finished.
+    Unlimited,
+}
+
+impl Default for FlushThreshold {
+    /// One percent of the rows.
+    fn default() -> Self {
+        Self::FractionOfRows(0.01)
+    }
+}
+
+impl FlushThreshold {
+    /// The number of lines after which to flush, when a CSV has `total_lines` lines
+    /// (it is never more than that, because it is also the initial capacity of the hash map).
+    pub(crate) fn lines_of_total(self, total_lines: u64) -> usize {
+        let lines = match self {
+            Self::Fixed(lines) => min(lines as u64, total_lines),
+            Self::FractionOfRows(fraction) => match (total_lines as f64 * fraction) as u64 {
+                0 => total_lines,
+                lines => min(lines, total_lines),
+            },
+            Self::Unlimited => 0,
+        };
+        lines.try_into().unwrap_or(usize::MAX)
+    }
+
+    /// The number of lines after which to flush first, when the total number of lines is not known.
+    pub(crate) fn initial_lines(self) -> usize {
+        match self {
+            Self::Fixed(lines) => lines,
+            Self::FractionOfRows(_) => 10,
+            Self::Unlimited => 0,
+        }
+    }
+
+    /// The number of lines after which to flush next, after `current_line` lines have been read,
+    /// or `None`, if it stays the same.
+    pub(crate) fn next_lines(self, current_line: u64) -> Option<usize> {
+        match self {
+            Self::FractionOfRows(fraction) if current_line % 100 == 0 => Some(max(
+                10,
+                ((current_line as f64 * fraction) as u64)
+                    .try_into()
+                    .unwrap_or(usize::MAX),
+            )),
+            _ => None,
+        }
+    }
+}
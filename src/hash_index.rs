@@ -0,0 +1,397 @@
+use crate::csv_parse_result::{Position, RecordHashWithPosition};
+use crate::empty_csv::{CsvHeaderInfo, EmptyCsvReason};
+use crate::error_code::ErrorCode;
+use crate::field_comparator::FieldCanonicalizer;
+use crate::spill;
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, Write};
+use thiserror::Error;
+use xxhash_rust::xxh3::Xxh3;
+
+/// Identifies the file format of a hash index (followed by its version).
+const HASH_INDEX_MAGIC: &[u8; 8] = b"CSVDIFFI";
+const HASH_INDEX_VERSION: u8 = 2;
+
+/// The key hash, the record hash and the position of every record of a CSV, together with its header.
+///
+/// It is built by [`CsvByteDiffLocal::hash_index`](crate::csv_diff::CsvByteDiffLocal::hash_index) and can be
+/// saved with [`write_to`](HashIndex::write_to). When the left CSV is unchanged between runs, pass the index to
+/// [`CsvByteDiffLocal::diff_with_hash_index`](crate::csv_diff::CsvByteDiffLocal::diff_with_hash_index), so that only
+/// the right CSV needs to be parsed and hashed. The left CSV is only read again for the records, that are different.
+///
+/// An index can only be used by a `CsvByteDiffLocal` with the same primary key columns, hash seed and field comparators
+/// as the one, that has built it, and for a left CSV, that has been created with the same options.
+/// The primary key columns, the hash seed, the canonicalization of fields (e.g. `numeric_columns`, `null_tokens` and
+/// `ignore_pattern`), whether the left CSV is [normalized](crate::csv::CsvBuilder::normalize) and its size and
+/// content hash are checked, before it is used. Since a normalizer is a closure, only its presence can be checked.
+#[derive(Debug, Clone)]
+pub struct HashIndex {
+    config: HashIndexConfig,
+    csv_len: u64,
+    // the hash of all bytes of the CSV, so that a CSV, that has been changed in place, is detected
+    csv_hash: u128,
+    total_lines: u64,
+    header_info: CsvHeaderInfo,
+    records: Vec<RecordHashWithPosition>,
+}
+
+impl HashIndex {
+    pub(crate) fn new(
+        config: HashIndexConfig,
+        (csv_len, csv_hash): (u64, u128),
+        total_lines: u64,
+        header_info: CsvHeaderInfo,
+        records: Vec<RecordHashWithPosition>,
+    ) -> Self {
+        Self {
+            config,
+            csv_len,
+            csv_hash,
+            total_lines,
+            header_info,
+            records,
+        }
+    }
+
+    /// The number of indexed records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub(crate) fn config(&self) -> &HashIndexConfig {
+        &self.config
+    }
+
+    /// The size and the content hash of the indexed CSV (see [`csv_len_and_hash`]).
+    pub(crate) fn csv_len_and_hash(&self) -> (u64, u128) {
+        (self.csv_len, self.csv_hash)
+    }
+
+    pub(crate) fn total_lines(&self) -> u64 {
+        self.total_lines
+    }
+
+    pub(crate) fn header_info(&self) -> &CsvHeaderInfo {
+        &self.header_info
+    }
+
+    pub(crate) fn records(&self) -> &[RecordHashWithPosition] {
+        &self.records
+    }
+
+    /// Write the index into `wtr` in a compact binary format, that can be read again with [`read_from`](HashIndex::read_from).
+    pub fn write_to<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        wtr.write_all(HASH_INDEX_MAGIC)?;
+        wtr.write_all(&[HASH_INDEX_VERSION])?;
+        self.config.write(&mut wtr)?;
+        wtr.write_all(&self.csv_len.to_le_bytes())?;
+        wtr.write_all(&self.csv_hash.to_le_bytes())?;
+        wtr.write_all(&self.total_lines.to_le_bytes())?;
+        write_header_info(&mut wtr, &self.header_info)?;
+        wtr.write_all(&(self.records.len() as u64).to_le_bytes())?;
+        for record in &self.records {
+            wtr.write_all(&record.key().to_le_bytes())?;
+            wtr.write_all(&record.record_hash_num().to_le_bytes())?;
+            wtr.write_all(&record.pos.byte_offset.to_le_bytes())?;
+            wtr.write_all(&record.pos.line.to_le_bytes())?;
+        }
+        wtr.flush()
+    }
+
+    /// Read an index, that has been written by [`write_to`](HashIndex::write_to).
+    ///
+    /// Fails with an error of kind [`InvalidData`](io::ErrorKind::InvalidData), if `rdr` doesn't contain a hash index.
+    pub fn read_from<R: Read>(mut rdr: R) -> io::Result<Self> {
+        read_magic_and_version(&mut rdr, HASH_INDEX_MAGIC, HASH_INDEX_VERSION)?;
+        let config = HashIndexConfig::read(&mut rdr)?;
+        let csv_len = spill::read_u64(&mut rdr)?;
+        let csv_hash = spill::read_u128(&mut rdr)?;
+        let total_lines = spill::read_u64(&mut rdr)?;
+        let header_info = read_header_info(&mut rdr)?;
+        let num_of_records = spill::read_u64(&mut rdr)?;
+        let records = (0..num_of_records)
+            .map(|_| {
+                let key = spill::read_u128(&mut rdr)?;
+                let record_hash = spill::read_u128(&mut rdr)?;
+                let byte_offset = spill::read_u64(&mut rdr)?;
+                let line = spill::read_u64(&mut rdr)?;
+                Ok(RecordHashWithPosition::new(
+                    key,
+                    record_hash,
+                    Position::new(byte_offset, line),
+                ))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self {
+            config,
+            csv_len,
+            csv_hash,
+            total_lines,
+            header_info,
+            records,
+        })
+    }
+}
+
+/// The options of a comparison, that the hashes of a [`HashIndex`] depend on.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct HashIndexConfig {
+    primary_key_columns: Vec<usize>,
+    primary_key_excluded_columns: Option<Vec<usize>>,
+    hash_seed: Option<u64>,
+    // see `FieldCanonicalizer::fingerprint`
+    canonicalizer_fingerprint: u128,
+    is_normalized: bool,
+}
+
+impl HashIndexConfig {
+    pub(crate) fn new(
+        primary_key_columns: &HashSet<usize>,
+        primary_key_excluded_columns: Option<&HashSet<usize>>,
+        hash_seed: Option<u64>,
+        canonicalizer: &FieldCanonicalizer,
+        is_normalized: bool,
+    ) -> Self {
+        Self {
+            primary_key_columns: sorted(primary_key_columns),
+            primary_key_excluded_columns: primary_key_excluded_columns.map(sorted),
+            hash_seed,
+            canonicalizer_fingerprint: canonicalizer.fingerprint(),
+            is_normalized,
+        }
+    }
+
+    pub(crate) fn write<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        write_columns(wtr, &self.primary_key_columns)?;
+        match &self.primary_key_excluded_columns {
+            Some(columns) => {
+                wtr.write_all(&[1])?;
+                write_columns(wtr, columns)?;
+            }
+            None => wtr.write_all(&[0])?,
+        }
+        match self.hash_seed {
+            Some(hash_seed) => {
+                wtr.write_all(&[1])?;
+                wtr.write_all(&hash_seed.to_le_bytes())?;
+            }
+            None => wtr.write_all(&[0])?,
+        }
+        wtr.write_all(&self.canonicalizer_fingerprint.to_le_bytes())?;
+        wtr.write_all(&[u8::from(self.is_normalized)])
+    }
+
+    pub(crate) fn read<R: Read>(rdr: &mut R) -> io::Result<Self> {
+        let primary_key_columns = read_columns(rdr)?;
+        let primary_key_excluded_columns = match read_u8(rdr)? {
+            0 => None,
+            _ => Some(read_columns(rdr)?),
+        };
+        let hash_seed = match read_u8(rdr)? {
+            0 => None,
+            _ => Some(spill::read_u64(rdr)?),
+        };
+        let canonicalizer_fingerprint = spill::read_u128(rdr)?;
+        let is_normalized = read_u8(rdr)? != 0;
+        Ok(Self {
+            primary_key_columns,
+            primary_key_excluded_columns,
+            hash_seed,
+            canonicalizer_fingerprint,
+            is_normalized,
+        })
+    }
+}
+
+/// Read the magic bytes and the version, that identify a file format, and fail with an error of kind
+/// [`InvalidData`](io::ErrorKind::InvalidData), if they are not the expected ones.
+pub(crate) fn read_magic_and_version<R: Read>(
+    rdr: &mut R,
+    magic: &[u8; 8],
+    version: u8,
+) -> io::Result<()> {
+    let mut magic_and_version = [0; 9];
+    rdr.read_exact(&mut magic_and_version)
+        .map_err(|_| invalid_data_err())?;
+    if &magic_and_version[..8] != magic || magic_and_version[8] != version {
+        return Err(invalid_data_err());
+    }
+    Ok(())
+}
+
+pub(crate) fn write_header_info<W: Write>(
+    wtr: &mut W,
+    header_info: &CsvHeaderInfo,
+) -> io::Result<()> {
+    let empty_reason = match header_info.empty_reason() {
+        None => 0,
+        Some(EmptyCsvReason::NoBytes) => 1,
+        Some(EmptyCsvReason::Blank) => 2,
+    };
+    wtr.write_all(&[empty_reason])?;
+    match header_info.raw_headers() {
+        Some(headers) => {
+            wtr.write_all(&[1])?;
+            wtr.write_all(&(headers.len() as u64).to_le_bytes())?;
+            for field in headers {
+                wtr.write_all(&(field.len() as u64).to_le_bytes())?;
+                wtr.write_all(field)?;
+            }
+            Ok(())
+        }
+        None => wtr.write_all(&[0]),
+    }
+}
+
+/// Read all bytes of the CSV of `csv_reader` from its start and return their number and their hash.
+/// The underlying reader is seeked back to the start afterwards.
+pub(crate) fn csv_len_and_hash<R: Read + Seek>(
+    csv_reader: &mut csv::Reader<R>,
+) -> io::Result<(u64, u128)> {
+    let rdr = csv_reader.get_mut();
+    rdr.seek(io::SeekFrom::Start(0))?;
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut csv_len = 0;
+    loop {
+        let len = match rdr.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.update(&buf[..len]);
+        csv_len += len as u64;
+    }
+    rdr.seek(io::SeekFrom::Start(0))?;
+    Ok((csv_len, hasher.digest128()))
+}
+
+pub(crate) fn read_header_info<R: Read>(rdr: &mut R) -> io::Result<CsvHeaderInfo> {
+    let empty_reason = match read_u8(rdr)? {
+        0 => None,
+        1 => Some(EmptyCsvReason::NoBytes),
+        2 => Some(EmptyCsvReason::Blank),
+        _ => return Err(invalid_data_err()),
+    };
+    let headers = match read_u8(rdr)? {
+        0 => None,
+        1 => {
+            let num_of_fields = spill::read_u64(rdr)?;
+            let mut headers = csv::ByteRecord::new();
+            let mut field = Vec::new();
+            for _ in 0..num_of_fields {
+                spill::read_field(rdr, &mut field)?;
+                headers.push_field(&field);
+            }
+            Some(headers)
+        }
+        _ => return Err(invalid_data_err()),
+    };
+    Ok(CsvHeaderInfo::new(headers, empty_reason))
+}
+
+fn invalid_data_err() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a csv-diff hash file")
+}
+
+fn sorted(columns: &HashSet<usize>) -> Vec<usize> {
+    let mut columns: Vec<_> = columns.iter().copied().collect();
+    columns.sort_unstable();
+    columns
+}
+
+fn write_columns<W: Write>(wtr: &mut W, columns: &[usize]) -> io::Result<()> {
+    wtr.write_all(&(columns.len() as u64).to_le_bytes())?;
+    for &column in columns {
+        wtr.write_all(&(column as u64).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_columns<R: Read>(rdr: &mut R) -> io::Result<Vec<usize>> {
+    let num_of_columns = spill::read_u64(rdr)?;
+    (0..num_of_columns)
+        .map(|_| spill::read_u64(rdr).map(|column| column as usize))
+        .collect()
+}
+
+fn read_u8<R: Read>(rdr: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    rdr.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+/// A [`HashIndex`] has been used for a left CSV, that has changed since the index has been built,
+/// or a `HashIndex` or [`HashSnapshot`](crate::hash_snapshot::HashSnapshot) has been used by a comparison
+/// with other primary key columns, another hash seed, another canonicalization of fields or another normalization.
+///
+/// It is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is a `HashIndexMismatchError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+#[error("the hash index doesn't match the left CSV or the options of the comparison")]
+pub struct HashIndexMismatchError;
+
+impl ErrorCode for HashIndexMismatchError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_HASH_INDEX_MISMATCH"
+    }
+}
+
+impl From<HashIndexMismatchError> for csv::Error {
+    fn from(err: HashIndexMismatchError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn write_to_and_read_from_round_trip() -> io::Result<()> {
+        let hash_index = HashIndex::new(
+            HashIndexConfig::new(
+                &std::iter::once(0).collect(),
+                None,
+                Some(42),
+                &FieldCanonicalizer::default(),
+                true,
+            ),
+            (27, 3),
+            2,
+            CsvHeaderInfo::new(Some(csv::ByteRecord::from(vec!["id", "name"])), None),
+            vec![RecordHashWithPosition::new(1, 2, Position::new(8, 2))],
+        );
+        let mut buf = Vec::new();
+        hash_index.write_to(&mut buf)?;
+
+        let hash_index_read = HashIndex::read_from(buf.as_slice())?;
+
+        assert_eq!(hash_index_read.config(), hash_index.config());
+        assert_eq!(
+            (
+                hash_index_read.csv_len_and_hash(),
+                hash_index_read.total_lines()
+            ),
+            ((27, 3), 2)
+        );
+        assert_eq!(
+            hash_index_read.header_info().headers(),
+            Some(&csv::ByteRecord::from(vec!["id", "name"]))
+        );
+        assert_eq!(hash_index_read.records(), hash_index.records());
+        Ok(())
+    }
+
+    #[test]
+    fn read_from_foreign_data_is_invalid_data_err() {
+        let err = HashIndex::read_from(&b"id,name\n1,lemon"[..]).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
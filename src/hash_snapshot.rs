@@ -0,0 +1,253 @@
+use crate::csv::RecordNormalizer;
+use crate::csv_parse_result::{RecordHash, RecordHashWithPosition};
+use crate::diff_row::ByteRecordLineInfo;
+use crate::empty_csv::CsvHeaderInfo;
+use crate::hash_index::{self, HashIndex, HashIndexConfig};
+use crate::spill;
+use ahash::AHashMap as HashMap;
+use std::io::{self, Read, Seek, Write};
+
+/// Identifies the file format of a hash snapshot (followed by its version).
+const HASH_SNAPSHOT_MAGIC: &[u8; 8] = b"CSVDIFFS";
+const HASH_SNAPSHOT_VERSION: u8 = 2;
+
+/// A compact snapshot of a CSV, which only holds the key hash, the record hash and the line of every record
+/// together with the header.
+///
+/// It is taken by [`CsvByteDiffLocal::hash_snapshot`](crate::csv_diff::CsvByteDiffLocal::hash_snapshot) and saved
+/// with [`write_to`](HashSnapshot::write_to), so that a later version of the CSV can be compared against it with
+/// [`CsvByteDiffLocal::diff_with_hash_snapshot`](crate::csv_diff::CsvByteDiffLocal::diff_with_hash_snapshot)
+/// without archiving the CSV itself. Each record takes 40 bytes, no matter how many fields it has.
+///
+/// Since the fields of the snapshotted records are gone, the differences only tell, which records of the CSV
+/// are new or modified and which lines of the snapshot have been deleted (see [`HashSnapshotDiffRecord`]).
+#[derive(Debug, Clone)]
+pub struct HashSnapshot {
+    config: HashIndexConfig,
+    header_info: CsvHeaderInfo,
+    // the line of each record is kept, so that deleted records can still be told apart
+    records: Vec<(RecordHash, u64)>,
+}
+
+impl HashSnapshot {
+    /// The number of records in the snapshot.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The header of the snapshotted CSV, if it has been read with headers and is not empty.
+    pub fn headers(&self) -> Option<&csv::ByteRecord> {
+        self.header_info.headers()
+    }
+
+    pub(crate) fn config(&self) -> &HashIndexConfig {
+        &self.config
+    }
+
+    /// Write the snapshot into `wtr` in a compact binary format, that can be read again with
+    /// [`read_from`](HashSnapshot::read_from).
+    pub fn write_to<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        wtr.write_all(HASH_SNAPSHOT_MAGIC)?;
+        wtr.write_all(&[HASH_SNAPSHOT_VERSION])?;
+        self.config.write(&mut wtr)?;
+        hash_index::write_header_info(&mut wtr, &self.header_info)?;
+        wtr.write_all(&(self.records.len() as u64).to_le_bytes())?;
+        for (record_hash, line) in &self.records {
+            wtr.write_all(&record_hash.key.to_le_bytes())?;
+            wtr.write_all(&record_hash.record_hash.to_le_bytes())?;
+            wtr.write_all(&line.to_le_bytes())?;
+        }
+        wtr.flush()
+    }
+
+    /// Read a snapshot, that has been written by [`write_to`](HashSnapshot::write_to).
+    ///
+    /// Fails with an error of kind [`InvalidData`](io::ErrorKind::InvalidData), if `rdr` doesn't contain a hash snapshot.
+    pub fn read_from<R: Read>(mut rdr: R) -> io::Result<Self> {
+        hash_index::read_magic_and_version(&mut rdr, HASH_SNAPSHOT_MAGIC, HASH_SNAPSHOT_VERSION)?;
+        let config = HashIndexConfig::read(&mut rdr)?;
+        let header_info = hash_index::read_header_info(&mut rdr)?;
+        let num_of_records = spill::read_u64(&mut rdr)?;
+        let records = (0..num_of_records)
+            .map(|_| {
+                let key = spill::read_u128(&mut rdr)?;
+                let record_hash = spill::read_u128(&mut rdr)?;
+                let line = spill::read_u64(&mut rdr)?;
+                Ok((RecordHash::new(key, record_hash), line))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self {
+            config,
+            header_info,
+            records,
+        })
+    }
+
+    /// Compare the snapshot with the hashed `records_right` of a CSV, whose records are read from `csv_reader_right`.
+    ///
+    /// New and modified records are returned in the order of the CSV, followed by the deleted records
+    /// in the order of the snapshot.
+    pub(crate) fn diff<R: Read + Seek>(
+        &self,
+        records_right: &[RecordHashWithPosition],
+        csv_reader_right: &mut csv::Reader<R>,
+        normalizer_right: Option<&RecordNormalizer>,
+    ) -> csv::Result<Vec<HashSnapshotDiffRecord>> {
+        let mut records_left: HashMap<u128, (u128, u64)> = self
+            .records
+            .iter()
+            .map(|(record_hash, line)| (record_hash.key, (record_hash.record_hash, *line)))
+            .collect();
+        let mut diff_records = Vec::new();
+        for record_right in records_right {
+            let line_left = match records_left.remove(&record_right.key()) {
+                Some((record_hash_left, _))
+                    if record_hash_left == record_right.record_hash_num() =>
+                {
+                    continue
+                }
+                Some((_, line_left)) => Some(line_left),
+                None => None,
+            };
+            csv_reader_right.seek(record_right.pos.into())?;
+            let mut byte_record = csv::ByteRecord::new();
+            csv_reader_right.read_byte_record(&mut byte_record)?;
+            if let Some(normalizer) = normalizer_right {
+                normalizer.apply(&mut byte_record);
+            }
+            let add = ByteRecordLineInfo::new(byte_record, record_right.pos.line);
+            diff_records.push(match line_left {
+                Some(line_left) => HashSnapshotDiffRecord::Modify { line_left, add },
+                None => HashSnapshotDiffRecord::Add(add),
+            });
+        }
+        let mut lines_deleted: Vec<u64> = records_left
+            .into_iter()
+            .map(|(_, (_, line))| line)
+            .collect();
+        lines_deleted.sort_unstable();
+        diff_records.extend(
+            lines_deleted
+                .into_iter()
+                .map(|line| HashSnapshotDiffRecord::Delete { line }),
+        );
+        Ok(diff_records)
+    }
+}
+
+impl From<HashIndex> for HashSnapshot {
+    /// Drop the byte offsets and the size of the CSV, which are only needed in order to read the CSV again.
+    fn from(hash_index: HashIndex) -> Self {
+        Self {
+            config: hash_index.config().clone(),
+            header_info: hash_index.header_info().clone(),
+            records: hash_index
+                .records()
+                .iter()
+                .map(|record| (record.record_hash, record.pos.line))
+                .collect(),
+        }
+    }
+}
+
+/// A difference between a [`HashSnapshot`] and a CSV.
+///
+/// Only the records of the CSV are known, so a modified record only holds its new version and a deleted record
+/// only its line in the snapshotted CSV.
+#[derive(Debug, PartialEq, Clone)]
+pub enum HashSnapshotDiffRecord {
+    /// The record is in the CSV, but its key is not in the snapshot.
+    Add(ByteRecordLineInfo),
+    /// The key of the record is in the snapshot, but the record has been changed.
+    Modify {
+        /// The line of the record in the snapshotted CSV.
+        line_left: u64,
+        add: ByteRecordLineInfo,
+    },
+    /// The key of the record in `line` of the snapshotted CSV is not in the CSV anymore.
+    Delete { line: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_parse_result::Position;
+    use crate::field_comparator::FieldCanonicalizer;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn write_to_and_read_from_round_trip() -> io::Result<()> {
+        let hash_snapshot = HashSnapshot {
+            config: HashIndexConfig::new(
+                &std::iter::once(0).collect(),
+                None,
+                None,
+                &FieldCanonicalizer::default(),
+                false,
+            ),
+            header_info: CsvHeaderInfo::new(Some(csv::ByteRecord::from(vec!["id", "name"])), None),
+            records: vec![(RecordHash::new(1, 2), 2), (RecordHash::new(3, 4), 3)],
+        };
+        let mut buf = Vec::new();
+        hash_snapshot.write_to(&mut buf)?;
+
+        let hash_snapshot_read = HashSnapshot::read_from(buf.as_slice())?;
+
+        // magic and version, config, header info, number of records and the records
+        assert_eq!(buf.len(), 9 + 35 + 32 + 8 + 2 * 40);
+        assert_eq!(hash_snapshot_read.config(), hash_snapshot.config());
+        assert_eq!(hash_snapshot_read.headers(), hash_snapshot.headers());
+        assert_eq!(hash_snapshot_read.records, hash_snapshot.records);
+        Ok(())
+    }
+
+    #[test]
+    fn diff_reports_added_and_modified_records_and_deleted_lines() -> csv::Result<()> {
+        let hash_snapshot = HashSnapshot {
+            config: HashIndexConfig::new(
+                &std::iter::once(0).collect(),
+                None,
+                None,
+                &FieldCanonicalizer::default(),
+                false,
+            ),
+            header_info: CsvHeaderInfo::new(None, None),
+            records: vec![
+                (RecordHash::new(1, 10), 1),
+                (RecordHash::new(2, 20), 2),
+                (RecordHash::new(3, 30), 3),
+            ],
+        };
+        let csv_right = "1,a\n2,b\n4,c";
+        let mut csv_reader_right = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(io::Cursor::new(csv_right));
+        let records_right = vec![
+            RecordHashWithPosition::new(1, 10, Position::new(0, 1)),
+            RecordHashWithPosition::new(2, 21, Position::new(4, 2)),
+            RecordHashWithPosition::new(4, 40, Position::new(8, 3)),
+        ];
+
+        let diff_records = hash_snapshot.diff(&records_right, &mut csv_reader_right, None)?;
+
+        assert_eq!(
+            diff_records,
+            vec![
+                HashSnapshotDiffRecord::Modify {
+                    line_left: 2,
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "b"]), 2),
+                },
+                HashSnapshotDiffRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["4", "c"]),
+                    3
+                )),
+                HashSnapshotDiffRecord::Delete { line: 3 },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,436 @@
+use crate::csv::Csv;
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+
+/// Compare only the headers of `csv_left` and `csv_right` with default options (see [`HeaderDiffer`](HeaderDiffer)).
+///
+/// Only the header rows are read, so this is cheap even for huge CSVs and can be used to check schema compatibility
+/// before running a full comparison.
+/// # Example
+/// ```
+/// use csv_diff::{csv::Csv, header_diff::diff_headers};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let header_diff = diff_headers(
+///     Csv::with_reader("id,name,kind,price\n1,lemon,fruit,1.5".as_bytes()),
+///     Csv::with_reader("name,id,Kind,origin\nlemon,1,fruit,Spain".as_bytes()),
+/// )?;
+///
+/// assert_eq!(header_diff.added()[0].name(), "origin");
+/// assert_eq!(header_diff.removed()[0].name(), "price");
+/// assert_eq!(header_diff.renamed()[0].name_left(), "kind");
+/// assert_eq!(header_diff.renamed()[0].name_right(), "Kind");
+/// assert_eq!(header_diff.reordered()[0].name(), "name");
+/// assert!(!header_diff.is_identical());
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff_headers<R: Read>(csv_left: Csv<R>, csv_right: Csv<R>) -> csv::Result<HeaderDiff> {
+    HeaderDiffer::new().diff(csv_left, csv_right)
+}
+
+/// Compares the headers of two CSVs and reports added, removed, reordered and renamed columns.
+///
+/// Columns are matched by their exact name first. Of the remaining columns, a removed and an added column are
+/// treated as renamed, when their names are similar enough (see [`rename_threshold`](HeaderDiffer::rename_threshold)).
+/// Columns with the same name on both sides are reported as reordered, when their relative order to the other
+/// columns has changed (columns that merely shifted, because other columns have been added or removed, are not reported).
+#[derive(Debug, Clone)]
+pub struct HeaderDiffer {
+    rename_threshold: f64,
+}
+
+impl Default for HeaderDiffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderDiffer {
+    pub fn new() -> Self {
+        Self {
+            rename_threshold: 0.8,
+        }
+    }
+
+    /// Set the minimum similarity (between `0.0` and `1.0`) of two column names, so that they are treated as renamed
+    /// (default: `0.8`). The similarity is based on the edit distance of both names, ignoring case and all
+    /// characters that are not alphanumeric. Use a value greater than `1.0` to disable rename detection.
+    pub fn rename_threshold(mut self, rename_threshold: f64) -> Self {
+        self.rename_threshold = rename_threshold;
+        self
+    }
+
+    /// Read only the header rows of `csv_left` and `csv_right` and compare them.
+    pub fn diff<R: Read>(
+        &self,
+        mut csv_left: Csv<R>,
+        mut csv_right: Csv<R>,
+    ) -> csv::Result<HeaderDiff> {
+        Ok(self.diff_byte_records(csv_left.headers()?, csv_right.headers()?))
+    }
+
+    /// Compare already parsed headers.
+    pub fn diff_byte_records(
+        &self,
+        headers_left: &csv::ByteRecord,
+        headers_right: &csv::ByteRecord,
+    ) -> HeaderDiff {
+        let names_left = to_names(headers_left);
+        let names_right = to_names(headers_right);
+
+        let mut idxs_right_by_name: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (idx, name) in names_right.iter().enumerate() {
+            idxs_right_by_name
+                .entry(name.as_str())
+                .or_default()
+                .push_back(idx);
+        }
+
+        let mut matched = Vec::new();
+        let mut removed = Vec::new();
+        for (idx_left, name) in names_left.iter().enumerate() {
+            match idxs_right_by_name
+                .get_mut(name.as_str())
+                .and_then(VecDeque::pop_front)
+            {
+                Some(idx_right) => matched.push((idx_left, idx_right)),
+                None => removed.push(idx_left),
+            }
+        }
+        let mut added = idxs_right_by_name
+            .into_values()
+            .flatten()
+            .collect::<Vec<_>>();
+        added.sort_unstable();
+
+        let mut candidates = Vec::new();
+        for &idx_left in removed.iter() {
+            for &idx_right in added.iter() {
+                let similarity = similarity(&names_left[idx_left], &names_right[idx_right]);
+                if similarity >= self.rename_threshold {
+                    candidates.push((similarity, idx_left, idx_right));
+                }
+            }
+        }
+        // most similar first; ties are broken by position
+        candidates.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.cmp(&b.2))
+        });
+        let mut renamed = Vec::new();
+        for (similarity, idx_left, idx_right) in candidates {
+            if removed.contains(&idx_left) && added.contains(&idx_right) {
+                removed.retain(|&idx| idx != idx_left);
+                added.retain(|&idx| idx != idx_right);
+                renamed.push(RenamedColumn {
+                    name_left: names_left[idx_left].clone(),
+                    name_right: names_right[idx_right].clone(),
+                    idx_left,
+                    idx_right,
+                    similarity,
+                });
+            }
+        }
+        renamed.sort_by_key(|renamed| renamed.idx_left);
+
+        let in_order = longest_increasing_subsequence(&matched);
+        let reordered = matched
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !in_order.contains(i))
+            .map(|(_, &(idx_left, idx_right))| ReorderedColumn {
+                name: names_left[idx_left].clone(),
+                idx_left,
+                idx_right,
+            })
+            .collect();
+
+        HeaderDiff {
+            added: added
+                .into_iter()
+                .map(|idx| HeaderColumn {
+                    name: names_right[idx].clone(),
+                    idx,
+                })
+                .collect(),
+            removed: removed
+                .into_iter()
+                .map(|idx| HeaderColumn {
+                    name: names_left[idx].clone(),
+                    idx,
+                })
+                .collect(),
+            reordered,
+            renamed,
+        }
+    }
+}
+
+/// The differences between the headers of two CSVs, see [`HeaderDiffer`](HeaderDiffer).
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct HeaderDiff {
+    added: Vec<HeaderColumn>,
+    removed: Vec<HeaderColumn>,
+    reordered: Vec<ReorderedColumn>,
+    renamed: Vec<RenamedColumn>,
+}
+
+impl HeaderDiff {
+    /// Columns, that only exist in the right CSV (index is the column index in the right CSV).
+    pub fn added(&self) -> &[HeaderColumn] {
+        &self.added
+    }
+
+    /// Columns, that only exist in the left CSV (index is the column index in the left CSV).
+    pub fn removed(&self) -> &[HeaderColumn] {
+        &self.removed
+    }
+
+    /// Columns, that exist in both CSVs, but whose relative order has changed.
+    pub fn reordered(&self) -> &[ReorderedColumn] {
+        &self.reordered
+    }
+
+    /// Columns, whose name has changed (detected by similarity of their names).
+    pub fn renamed(&self) -> &[RenamedColumn] {
+        &self.renamed
+    }
+
+    /// Return `true`, if both headers have the same columns in the same order.
+    ///
+    /// Note that columns that only shifted their position (because other columns have been added or removed)
+    /// are not considered here.
+    pub fn is_identical(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reordered.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HeaderColumn {
+    name: String,
+    idx: usize,
+}
+
+impl HeaderColumn {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn idx(&self) -> usize {
+        self.idx
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReorderedColumn {
+    name: String,
+    idx_left: usize,
+    idx_right: usize,
+}
+
+impl ReorderedColumn {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn idx_left(&self) -> usize {
+        self.idx_left
+    }
+
+    pub fn idx_right(&self) -> usize {
+        self.idx_right
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenamedColumn {
+    name_left: String,
+    name_right: String,
+    idx_left: usize,
+    idx_right: usize,
+    similarity: f64,
+}
+
+impl RenamedColumn {
+    pub fn name_left(&self) -> &str {
+        &self.name_left
+    }
+
+    pub fn name_right(&self) -> &str {
+        &self.name_right
+    }
+
+    pub fn idx_left(&self) -> usize {
+        self.idx_left
+    }
+
+    pub fn idx_right(&self) -> usize {
+        self.idx_right
+    }
+
+    /// Similarity of both names between `0.0` (completely different) and `1.0` (equal, when ignoring case and
+    /// non-alphanumeric characters).
+    pub fn similarity(&self) -> f64 {
+        self.similarity
+    }
+}
+
+fn to_names(headers: &csv::ByteRecord) -> Vec<String> {
+    headers
+        .iter()
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+fn similarity(name_left: &str, name_right: &str) -> f64 {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let (left, right) = (normalize(name_left), normalize(name_right));
+    let max_len = left.len().max(right.len());
+    if max_len == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&left, &right) as f64 / max_len as f64
+}
+
+fn levenshtein(left: &[char], right: &[char]) -> usize {
+    let mut prev_row = (0..=right.len()).collect::<Vec<_>>();
+    let mut row = vec![0; right.len() + 1];
+    for (i, c_left) in left.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, c_right) in right.iter().enumerate() {
+            let cost = usize::from(c_left != c_right);
+            row[j + 1] = (prev_row[j + 1] + 1)
+                .min(row[j] + 1)
+                .min(prev_row[j] + cost);
+        }
+        std::mem::swap(&mut prev_row, &mut row);
+    }
+    prev_row[right.len()]
+}
+
+/// Return the positions in `matched` (which is ordered by left index), that form the longest run of
+/// columns, whose right indices are increasing as well - those columns are still in order.
+fn longest_increasing_subsequence(matched: &[(usize, usize)]) -> Vec<usize> {
+    // headers are small, so O(n^2) is fine here
+    let mut lengths = vec![1usize; matched.len()];
+    let mut predecessors = vec![None; matched.len()];
+    for i in 0..matched.len() {
+        for j in 0..i {
+            if matched[j].1 < matched[i].1 && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                predecessors[i] = Some(j);
+            }
+        }
+    }
+    let mut positions = Vec::new();
+    let mut current = lengths
+        .iter()
+        .enumerate()
+        .max_by(|(i_a, len_a), (i_b, len_b)| len_a.cmp(len_b).then(i_b.cmp(i_a)))
+        .map(|(i, _)| i);
+    while let Some(i) = current {
+        positions.push(i);
+        current = predecessors[i];
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn header_diff(left: Vec<&str>, right: Vec<&str>) -> HeaderDiff {
+        HeaderDiffer::new()
+            .diff_byte_records(&csv::ByteRecord::from(left), &csv::ByteRecord::from(right))
+    }
+
+    #[test]
+    fn diff_headers_equal_is_identical() {
+        assert!(header_diff(vec!["a", "b", "c"], vec!["a", "b", "c"]).is_identical());
+    }
+
+    #[test]
+    fn diff_headers_added_and_removed_columns_do_not_count_as_reordered() {
+        let actual = header_diff(vec!["id", "name", "price"], vec!["id", "origin", "name"]);
+
+        assert_eq!(
+            actual,
+            HeaderDiff {
+                added: vec![HeaderColumn {
+                    name: "origin".into(),
+                    idx: 1
+                }],
+                removed: vec![HeaderColumn {
+                    name: "price".into(),
+                    idx: 2
+                }],
+                reordered: vec![],
+                renamed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn diff_headers_swapped_columns_is_reordered() {
+        let actual = header_diff(vec!["a", "b", "c", "d"], vec!["a", "c", "d", "b"]);
+
+        assert_eq!(
+            actual.reordered(),
+            &[ReorderedColumn {
+                name: "b".into(),
+                idx_left: 1,
+                idx_right: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_headers_similar_names_are_renamed() {
+        let actual = header_diff(
+            vec!["id", "first_name", "zip"],
+            vec!["id", "FirstName", "country"],
+        );
+
+        assert_eq!(actual.renamed().len(), 1);
+        assert_eq!(actual.renamed()[0].name_left(), "first_name");
+        assert_eq!(actual.renamed()[0].name_right(), "FirstName");
+        assert_eq!(actual.renamed()[0].similarity(), 1.0);
+        assert_eq!(actual.removed()[0].name(), "zip");
+        assert_eq!(actual.added()[0].name(), "country");
+    }
+
+    #[test]
+    fn diff_headers_rename_threshold_above_one_disables_rename_detection() {
+        let actual = HeaderDiffer::new().rename_threshold(1.1).diff_byte_records(
+            &csv::ByteRecord::from(vec!["first_name"]),
+            &csv::ByteRecord::from(vec!["FirstName"]),
+        );
+
+        assert!(actual.renamed().is_empty());
+        assert_eq!(actual.removed().len(), 1);
+        assert_eq!(actual.added().len(), 1);
+    }
+
+    #[test]
+    fn diff_headers_only_reads_headers() -> Result<(), Box<dyn std::error::Error>> {
+        // the body is not valid CSV (different number of fields), but it is never read
+        let actual = diff_headers(
+            Csv::with_reader("a,b\n1,2,3\n4".as_bytes()),
+            Csv::with_reader("a,b\n1\n".as_bytes()),
+        )?;
+
+        assert!(actual.is_identical());
+        Ok(())
+    }
+}
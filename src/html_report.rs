@@ -0,0 +1,415 @@
+use crate::diff_result::group_by_col;
+use crate::diff_row::DiffByteRecord;
+use std::io::{self, Write};
+
+const STYLE: &str = "\
+body{font-family:sans-serif;margin:1em}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #ccc;padding:2px 6px;font-family:monospace;white-space:pre}\
+th{background:#eee}\
+td.line{color:#888;text-align:right}\
+td.sep{border-top:none;border-bottom:none;background:#fafafa}\
+tr.group th{text-align:left;background:#dde}\
+tr.add td.right{background:#e6ffed}\
+tr.delete td.left{background:#ffeef0}\
+tr.modify td.changed.left{background:#fdb8c0}\
+tr.modify td.changed.right{background:#acf2bd}\
+tr.move td{background:#f1f8ff}\
+";
+
+/// Renders [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s into a standalone HTML report.
+///
+/// Left and right records are shown side by side. Deleted records are highlighted on the left side, added records
+/// on the right side and for modified records only the cells that have been changed are highlighted.
+/// Moved records are shown on both sides with their line in each CSV.
+/// With [`group_by_column`](HtmlReport::group_by_column), records are grouped by their value in that column
+/// and every group starts with a header row.
+///
+/// This is only available with the Cargo feature `html-report`.
+/// # Example
+/// ```
+/// use csv_diff::html_report::HtmlReport;
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+///
+/// let diff_byte_records = vec![DiffByteRecord::Modify {
+///     delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "fruit"]), 2),
+///     add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "nut"]), 2),
+///     field_indices: vec![2],
+/// }];
+///
+/// let html = HtmlReport::new()
+///     .title("fruits.csv")
+///     .headers(csv::ByteRecord::from(vec!["id", "name", "kind"]))
+///     .render(&diff_byte_records);
+///
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains(r#"<td class="right changed">nut</td>"#));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HtmlReport {
+    title: Option<String>,
+    headers: Option<csv::ByteRecord>,
+    group_by_column: Option<usize>,
+}
+
+impl HtmlReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the title of the report (default: `CSV diff`).
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the headers of the CSVs, which are shown as column names on both sides.
+    pub fn headers(mut self, headers: csv::ByteRecord) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Group the records by their value in column `col` (usually the first column of the primary key)
+    /// and emit a header row before every group (default: no grouping).
+    ///
+    /// Groups are ordered by their value; records within a group keep their order.
+    pub fn group_by_column(mut self, col: usize) -> Self {
+        self.group_by_column = Some(col);
+        self
+    }
+
+    /// Render all `diff_records` into a `String`.
+    pub fn render<'a, I>(&self, diff_records: I) -> String
+    where
+        I: IntoIterator<Item = &'a DiffByteRecord>,
+    {
+        let mut buf = Vec::new();
+        self.render_to(diff_records, &mut buf)
+            .expect("writing to a Vec never fails");
+        // we only ever write valid UTF-8
+        String::from_utf8(buf).expect("is utf8")
+    }
+
+    /// Render all `diff_records` into `wtr`.
+    pub fn render_to<'a, I, W>(&self, diff_records: I, wtr: &mut W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a DiffByteRecord>,
+        W: Write,
+    {
+        self.write_head(wtr)?;
+        match self.group_by_column {
+            Some(col) => {
+                for group in group_by_col(diff_records, col) {
+                    let num_of_cells = group
+                        .diff_records()
+                        .iter()
+                        .map(|diff_record| self.num_of_cells(num_of_fields(diff_record)))
+                        .max()
+                        .unwrap_or_default();
+                    // line + cells on both sides and the separator
+                    write!(
+                        wtr,
+                        "<tr class=\"group\"><th colspan=\"{}\">",
+                        2 * (num_of_cells + 1) + 1
+                    )?;
+                    write_escaped(wtr, group.key())?;
+                    wtr.write_all(b"</th></tr>\n")?;
+                    for diff_record in group.diff_records() {
+                        self.write_row(wtr, diff_record)?;
+                    }
+                }
+            }
+            None => {
+                for diff_record in diff_records {
+                    self.write_row(wtr, diff_record)?;
+                }
+            }
+        }
+        write_foot(wtr)
+    }
+
+    fn write_head<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        let title = self.title.as_deref().unwrap_or("CSV diff");
+        wtr.write_all(b"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>")?;
+        write_escaped(wtr, title.as_bytes())?;
+        write!(
+            wtr,
+            "</title>\n<style>{}</style>\n</head>\n<body>\n<h1>",
+            STYLE
+        )?;
+        write_escaped(wtr, title.as_bytes())?;
+        wtr.write_all(b"</h1>\n<table>\n")?;
+        if let Some(headers) = &self.headers {
+            wtr.write_all(b"<thead><tr><th>line</th>")?;
+            self.write_header_cells(wtr, headers)?;
+            wtr.write_all(b"<th class=\"sep\"></th><th>line</th>")?;
+            self.write_header_cells(wtr, headers)?;
+            wtr.write_all(b"</tr></thead>\n")?;
+        }
+        wtr.write_all(b"<tbody>\n")
+    }
+
+    fn write_header_cells<W: Write>(
+        &self,
+        wtr: &mut W,
+        headers: &csv::ByteRecord,
+    ) -> io::Result<()> {
+        for header in headers.iter() {
+            wtr.write_all(b"<th>")?;
+            write_escaped(wtr, header)?;
+            wtr.write_all(b"</th>")?;
+        }
+        Ok(())
+    }
+
+    fn write_row<W: Write>(&self, wtr: &mut W, diff_record: &DiffByteRecord) -> io::Result<()> {
+        match diff_record {
+            DiffByteRecord::Add(add) => {
+                let num_of_cells = self.num_of_cells(add.byte_record().len());
+                wtr.write_all(b"<tr class=\"add\">")?;
+                write_empty_side(wtr, num_of_cells)?;
+                wtr.write_all(b"<td class=\"sep\"></td>")?;
+                write_side(
+                    wtr,
+                    "right",
+                    add.line(),
+                    add.byte_record(),
+                    num_of_cells,
+                    &[],
+                )?;
+            }
+            DiffByteRecord::Delete(delete) => {
+                let num_of_cells = self.num_of_cells(delete.byte_record().len());
+                wtr.write_all(b"<tr class=\"delete\">")?;
+                write_side(
+                    wtr,
+                    "left",
+                    delete.line(),
+                    delete.byte_record(),
+                    num_of_cells,
+                    &[],
+                )?;
+                wtr.write_all(b"<td class=\"sep\"></td>")?;
+                write_empty_side(wtr, num_of_cells)?;
+            }
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                let num_of_cells =
+                    self.num_of_cells(delete.byte_record().len().max(add.byte_record().len()));
+                wtr.write_all(b"<tr class=\"modify\">")?;
+                write_side(
+                    wtr,
+                    "left",
+                    delete.line(),
+                    delete.byte_record(),
+                    num_of_cells,
+                    field_indices,
+                )?;
+                wtr.write_all(b"<td class=\"sep\"></td>")?;
+                write_side(
+                    wtr,
+                    "right",
+                    add.line(),
+                    add.byte_record(),
+                    num_of_cells,
+                    field_indices,
+                )?;
+            }
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => {
+                let num_of_cells = self.num_of_cells(record.len());
+                wtr.write_all(b"<tr class=\"move\">")?;
+                write_side(wtr, "left", *from_line, record, num_of_cells, &[])?;
+                wtr.write_all(b"<td class=\"sep\"></td>")?;
+                write_side(wtr, "right", *to_line, record, num_of_cells, &[])?;
+            }
+        }
+        wtr.write_all(b"</tr>\n")
+    }
+
+    fn num_of_cells(&self, num_of_fields: usize) -> usize {
+        self.headers
+            .as_ref()
+            .map_or(num_of_fields, |headers| headers.len().max(num_of_fields))
+    }
+}
+
+fn num_of_fields(diff_record: &DiffByteRecord) -> usize {
+    match diff_record {
+        DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli.byte_record().len(),
+        DiffByteRecord::Modify { delete, add, .. } => {
+            delete.byte_record().len().max(add.byte_record().len())
+        }
+        DiffByteRecord::Move { record, .. } => record.len(),
+    }
+}
+
+fn write_side<W: Write>(
+    wtr: &mut W,
+    side: &str,
+    line: u64,
+    byte_record: &csv::ByteRecord,
+    num_of_cells: usize,
+    field_indices: &[usize],
+) -> io::Result<()> {
+    write!(wtr, "<td class=\"line\">{}</td>", line)?;
+    for idx in 0..num_of_cells {
+        if field_indices.contains(&idx) {
+            write!(wtr, "<td class=\"{} changed\">", side)?;
+        } else {
+            write!(wtr, "<td class=\"{}\">", side)?;
+        }
+        if let Some(field) = byte_record.get(idx) {
+            write_escaped(wtr, field)?;
+        }
+        wtr.write_all(b"</td>")?;
+    }
+    Ok(())
+}
+
+fn write_foot<W: Write>(wtr: &mut W) -> io::Result<()> {
+    wtr.write_all(b"</tbody>\n</table>\n</body>\n</html>\n")
+}
+
+fn write_empty_side<W: Write>(wtr: &mut W, num_of_cells: usize) -> io::Result<()> {
+    wtr.write_all(b"<td class=\"line\"></td>")?;
+    for _ in 0..num_of_cells {
+        wtr.write_all(b"<td></td>")?;
+    }
+    Ok(())
+}
+
+fn write_escaped<W: Write>(wtr: &mut W, field: &[u8]) -> io::Result<()> {
+    for c in String::from_utf8_lossy(field).chars() {
+        match c {
+            '&' => wtr.write_all(b"&amp;")?,
+            '<' => wtr.write_all(b"&lt;")?,
+            '>' => wtr.write_all(b"&gt;")?,
+            '"' => wtr.write_all(b"&quot;")?,
+            '\'' => wtr.write_all(b"&#39;")?,
+            c => write!(wtr, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rayon-threads")]
+impl crate::parallel_render::DiffRecordFormatter for HtmlReport {
+    fn write_prologue(&self, mut wtr: &mut dyn Write) -> io::Result<()> {
+        self.write_head(&mut wtr)
+    }
+
+    fn format_diff_byte_record(
+        &self,
+        diff_record: &DiffByteRecord,
+        mut wtr: &mut dyn Write,
+    ) -> io::Result<()> {
+        self.write_row(&mut wtr, diff_record)
+    }
+
+    fn write_epilogue(&self, mut wtr: &mut dyn Write) -> io::Result<()> {
+        write_foot(&mut wtr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    fn tbody(html: &str) -> &str {
+        let start = html.find("<tbody>\n").expect("has tbody") + "<tbody>\n".len();
+        let end = html.find("</tbody>").expect("has tbody");
+        &html[start..end]
+    }
+
+    #[test]
+    fn render_add_delete_and_modify_side_by_side() {
+        let diff_records = vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b"]),
+                2,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["c", "d"]),
+                3,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["e", "f"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["e", "g"]), 5),
+                field_indices: vec![1],
+            },
+        ];
+
+        let html = HtmlReport::new().render(&diff_records);
+
+        assert_eq!(
+            tbody(&html),
+            "<tr class=\"delete\"><td class=\"line\">2</td><td class=\"left\">a</td><td class=\"left\">b</td>\
+             <td class=\"sep\"></td><td class=\"line\"></td><td></td><td></td></tr>\n\
+             <tr class=\"add\"><td class=\"line\"></td><td></td><td></td>\
+             <td class=\"sep\"></td><td class=\"line\">3</td><td class=\"right\">c</td><td class=\"right\">d</td></tr>\n\
+             <tr class=\"modify\"><td class=\"line\">4</td><td class=\"left\">e</td><td class=\"left changed\">f</td>\
+             <td class=\"sep\"></td><td class=\"line\">5</td><td class=\"right\">e</td><td class=\"right changed\">g</td></tr>\n"
+        );
+    }
+
+    #[test]
+    fn render_with_title_and_headers() {
+        let html = HtmlReport::new()
+            .title("my <report>")
+            .headers(csv::ByteRecord::from(vec!["id", "name"]))
+            .render(&[]);
+
+        assert!(html.contains("<title>my &lt;report&gt;</title>"));
+        assert!(html.contains(
+            "<thead><tr><th>line</th><th>id</th><th>name</th>\
+             <th class=\"sep\"></th><th>line</th><th>id</th><th>name</th></tr></thead>"
+        ));
+    }
+
+    #[test]
+    fn render_grouped_by_column_emits_group_rows() {
+        let diff_records = vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["north", "1"]),
+                2,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["east", "2"]),
+                3,
+            )),
+        ];
+
+        let html = HtmlReport::new().group_by_column(0).render(&diff_records);
+
+        assert_eq!(
+            tbody(&html),
+            "<tr class=\"group\"><th colspan=\"7\">east</th></tr>\n\
+             <tr class=\"delete\"><td class=\"line\">3</td><td class=\"left\">east</td><td class=\"left\">2</td>\
+             <td class=\"sep\"></td><td class=\"line\"></td><td></td><td></td></tr>\n\
+             <tr class=\"group\"><th colspan=\"7\">north</th></tr>\n\
+             <tr class=\"add\"><td class=\"line\"></td><td></td><td></td>\
+             <td class=\"sep\"></td><td class=\"line\">2</td><td class=\"right\">north</td><td class=\"right\">1</td></tr>\n"
+        );
+    }
+
+    #[test]
+    fn render_escapes_fields() {
+        let diff_records = vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["<script>\"&'"]),
+            2,
+        ))];
+
+        let html = HtmlReport::new().render(&diff_records);
+
+        assert!(html.contains("<td class=\"right\">&lt;script&gt;&quot;&amp;&#39;</td>"));
+    }
+}
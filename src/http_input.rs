@@ -0,0 +1,154 @@
+use crate::csv::{Csv, CsvBuilder};
+use crate::error_code::ErrorCode;
+use std::io::Read;
+use thiserror::Error;
+
+/// A reader, that streams the body of an HTTP(S) response.
+///
+/// This is only available with the Cargo feature `http`.
+pub type HttpReader = Box<dyn Read + Send + Sync + 'static>;
+
+impl Csv<HttpReader> {
+    /// Create a new `Csv`, that streams the CSV data from `url` (with a `GET` request),
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    ///
+    /// Only the response head is received here; the body is read while the CSVs are being compared.
+    /// Fails, if the request can't be sent or the server doesn't respond with a success status.
+    /// Use [`CsvBuilder::build_with_http_request`] in order to send additional headers (e.g. for authentication).
+    ///
+    /// The body can't be seeked, so it can't be compared with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal).
+    /// This is only available with the Cargo feature `http`.
+    /// # Example: compare a local export with the canonical CSV on a server
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```no_run
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let local_export = std::fs::File::open("export.csv")?;
+
+let num_of_differences = CsvByteDiff::new()?
+    .diff(
+        Csv::with_reader(Box::new(local_export) as csv_diff::http_input::HttpReader),
+        Csv::with_url("https://data.example.com/canonical.csv")?,
+    )
+    .count();
+# let _ = num_of_differences;
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_url(url: &str) -> Result<Self, HttpInputError> {
+        CsvBuilder::new().build_with_url(url)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but streams the CSV data from `url`
+    /// (see [`Csv::with_url`]).
+    pub fn build_with_url(&self, url: &str) -> Result<Csv<HttpReader>, HttpInputError> {
+        self.build_with_http_request(ureq::get(url))
+    }
+
+    /// Like [`build_with_url`](CsvBuilder::build_with_url), but sends `request`, which can be configured
+    /// with headers, timeouts or a custom [`ureq::Agent`].
+    pub fn build_with_http_request(
+        &self,
+        request: ureq::Request,
+    ) -> Result<Csv<HttpReader>, HttpInputError> {
+        let url = request.url().to_owned();
+        match request.call() {
+            Ok(response) => Ok(self.build_with_reader(response.into_reader())),
+            Err(ureq::Error::Status(status, _)) => Err(HttpInputError::Status { url, status }),
+            Err(ureq::Error::Transport(transport)) => Err(HttpInputError::Transport {
+                url,
+                source: Box::new(transport),
+            }),
+        }
+    }
+}
+
+/// The CSV data couldn't be requested from a URL (see [`Csv::with_url`]).
+///
+/// Errors, that occur when reading the body of the response, are returned as [`csv::Error`] of kind
+/// [`csv::ErrorKind::Io`] when comparing.
+#[derive(Debug, Error)]
+pub enum HttpInputError {
+    #[error("the server responded to the request of {url} with status {status}")]
+    Status { url: String, status: u16 },
+    #[error("the request of {url} has failed: {source}")]
+    Transport {
+        url: String,
+        #[source]
+        source: Box<ureq::Transport>,
+    },
+}
+
+impl ErrorCode for HttpInputError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Status { .. } => "CSVDIFF_HTTP_STATUS",
+            Self::Transport { .. } => "CSVDIFF_HTTP_TRANSPORT",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve a single request with `status_line` and `body` on a local port and return its URL.
+    fn serve_once(status_line: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data.csv", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            // skip the request head
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                reader.get_mut(),
+                "{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status_line,
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn with_url_streams_records_of_body() -> Result<(), Box<dyn std::error::Error>> {
+        let url = serve_once("HTTP/1.1 200 OK", "id,name\n1,lemon\n2,cherry");
+
+        let mut csv_reader = Csv::with_url(&url)?.into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "cherry"])
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn with_url_not_found_is_status_err() {
+        let url = serve_once("HTTP/1.1 404 Not Found", "");
+
+        let err = Csv::with_url(&url).err().expect("an error");
+
+        assert!(matches!(err, HttpInputError::Status { status: 404, .. }));
+        assert_eq!(err.code(), "CSVDIFF_HTTP_STATUS");
+    }
+}
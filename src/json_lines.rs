@@ -0,0 +1,378 @@
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord, RecordUtf8Error, Utf8Conversion};
+use crate::error_code::ErrorCode;
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// Writes [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s as [JSON Lines](https://jsonlines.org/) to any [`Write`](std::io::Write),
+/// one JSON object per line.
+///
+/// The objects have the following shape (fields that are not valid UTF-8 are converted lossily by default,
+/// see [`utf8_conversion`](JsonLinesWriter::utf8_conversion)):
+/// ```text
+/// {"kind":"add","line":3,"record":["3","cherry","fruit"]}
+/// {"kind":"delete","line":4,"record":["4","banana","fruit"]}
+/// {"kind":"modify","delete":{"line":2,"record":["2","strawberry","fruit"]},"add":{"line":2,"record":["2","strawberry","nut"]},"field_indices":[2]}
+/// {"kind":"move","from_line":5,"to_line":2,"record":["5","lemon","fruit"]}
+/// ```
+///
+/// Together with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff), differences are written while the CSVs are still being compared,
+/// so they never have to be collected in memory.
+/// # Example: stream all differences as JSON Lines
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::json_lines::JsonLinesWriter;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,nut";
+
+let mut json_lines_writer = JsonLinesWriter::new(Vec::new());
+
+let num_of_records_written = json_lines_writer.write_all_from_iter(CsvByteDiff::new()?.diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+))?;
+
+assert_eq!(num_of_records_written, 1);
+assert_eq!(
+    String::from_utf8(json_lines_writer.into_inner())?,
+    "{\"kind\":\"modify\",\
+    \"delete\":{\"line\":2,\"record\":[\"1\",\"lemon\",\"fruit\"]},\
+    \"add\":{\"line\":2,\"record\":[\"1\",\"lemon\",\"nut\"]},\
+    \"field_indices\":[2]}\n"
+);
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    wtr: W,
+    utf8_conversion: Utf8Conversion,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(wtr: W) -> Self {
+        Self {
+            wtr,
+            utf8_conversion: Utf8Conversion::Lossy,
+        }
+    }
+
+    /// Choose how fields, that are not valid UTF-8, are written (default is [`Utf8Conversion::Lossy`]).
+    ///
+    /// With [`Utf8Conversion::Strict`], writing such a record fails with an [`io::Error`] of kind
+    /// [`InvalidData`](io::ErrorKind::InvalidData), which wraps a [`RecordUtf8Error`]. Nothing of the record is written then.
+    pub fn utf8_conversion(mut self, utf8_conversion: Utf8Conversion) -> Self {
+        self.utf8_conversion = utf8_conversion;
+        self
+    }
+
+    /// Write a single `DiffByteRecord` as one line of JSON.
+    pub fn write_diff_byte_record(&mut self, diff_record: &DiffByteRecord) -> io::Result<()> {
+        if self.utf8_conversion == Utf8Conversion::Strict {
+            check_utf8(diff_record)?;
+        }
+        match diff_record {
+            DiffByteRecord::Add(brli) => {
+                self.wtr.write_all(br#"{"kind":"add","#)?;
+                self.write_byte_record_line_info_fields(brli)?;
+            }
+            DiffByteRecord::Delete(brli) => {
+                self.wtr.write_all(br#"{"kind":"delete","#)?;
+                self.write_byte_record_line_info_fields(brli)?;
+            }
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                self.wtr.write_all(br#"{"kind":"modify","delete":{"#)?;
+                self.write_byte_record_line_info_fields(delete)?;
+                self.wtr.write_all(br#"},"add":{"#)?;
+                self.write_byte_record_line_info_fields(add)?;
+                self.wtr.write_all(br#"},"field_indices":["#)?;
+                for (i, field_idx) in field_indices.iter().enumerate() {
+                    if i > 0 {
+                        self.wtr.write_all(b",")?;
+                    }
+                    write!(self.wtr, "{}", field_idx)?;
+                }
+                self.wtr.write_all(b"]")?;
+            }
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => {
+                write!(
+                    self.wtr,
+                    r#"{{"kind":"move","from_line":{},"to_line":{},"#,
+                    from_line, to_line
+                )?;
+                self.write_record_field(record)?;
+            }
+        }
+        self.wtr.write_all(b"}\n")
+    }
+
+    /// Consume `diff_records` lazily and write every `DiffByteRecord` as one line of JSON.
+    ///
+    /// Returns the number of records that have been written or the first error that occurred
+    /// (either when comparing the CSVs or when writing).
+    pub fn write_all_from_iter<I>(&mut self, diff_records: I) -> Result<u64, JsonLinesError>
+    where
+        I: IntoIterator<Item = csv::Result<DiffByteRecord>>,
+    {
+        let mut num_written = 0;
+        for diff_record in diff_records {
+            self.write_diff_byte_record(&diff_record?)?;
+            num_written += 1;
+        }
+        Ok(num_written)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.wtr.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.wtr
+    }
+
+    fn write_byte_record_line_info_fields(&mut self, brli: &ByteRecordLineInfo) -> io::Result<()> {
+        write!(self.wtr, r#""line":{},"#, brli.line())?;
+        self.write_record_field(brli.byte_record())
+    }
+
+    fn write_record_field(&mut self, byte_record: &csv::ByteRecord) -> io::Result<()> {
+        self.wtr.write_all(br#""record":["#)?;
+        for (i, field) in byte_record.iter().enumerate() {
+            if i > 0 {
+                self.wtr.write_all(b",")?;
+            }
+            write_json_string(&mut self.wtr, &String::from_utf8_lossy(field))?;
+        }
+        self.wtr.write_all(b"]")
+    }
+}
+
+/// Fail, if any field of `diff_record` is not valid UTF-8.
+fn check_utf8(diff_record: &DiffByteRecord) -> io::Result<()> {
+    let records = match diff_record {
+        DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => {
+            [Some((brli.line(), brli.byte_record())), None]
+        }
+        DiffByteRecord::Modify { delete, add, .. } => [
+            Some((delete.line(), delete.byte_record())),
+            Some((add.line(), add.byte_record())),
+        ],
+        DiffByteRecord::Move {
+            record, from_line, ..
+        } => [Some((*from_line, record)), None],
+    };
+    for (line, byte_record) in records.into_iter().flatten() {
+        if let Some(field_idx) = byte_record
+            .iter()
+            .position(|field| std::str::from_utf8(field).is_err())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                RecordUtf8Error::new(line, field_idx),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn write_json_string<W: Write>(wtr: &mut W, s: &str) -> io::Result<()> {
+    wtr.write_all(b"\"")?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escaped: Option<&[u8]> = match c {
+            '"' => Some(br#"\""#),
+            '\\' => Some(br"\\"),
+            '\n' => Some(br"\n"),
+            '\r' => Some(br"\r"),
+            '\t' => Some(br"\t"),
+            c if c.is_control() => None,
+            _ => continue,
+        };
+        wtr.write_all(&s.as_bytes()[start..i])?;
+        match escaped {
+            Some(escaped) => wtr.write_all(escaped)?,
+            None => write!(wtr, "\\u{:04x}", c as u32)?,
+        }
+        start = i + c.len_utf8();
+    }
+    wtr.write_all(&s.as_bytes()[start..])?;
+    wtr.write_all(b"\"")
+}
+
+/// Formats [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s as JSON Lines (see [`JsonLinesWriter`])
+/// for a [`ParallelRenderer`](crate::parallel_render::ParallelRenderer).
+#[cfg(feature = "rayon-threads")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonLinesFormat;
+
+#[cfg(feature = "rayon-threads")]
+impl crate::parallel_render::DiffRecordFormatter for JsonLinesFormat {
+    fn format_diff_byte_record(
+        &self,
+        diff_record: &DiffByteRecord,
+        wtr: &mut dyn Write,
+    ) -> io::Result<()> {
+        JsonLinesWriter::new(wtr).write_diff_byte_record(diff_record)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JsonLinesError {
+    #[error("an error occured when comparing the CSVs: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("an error occured when writing JSON Lines: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl ErrorCode for JsonLinesError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Csv(csv_err) => csv_err.code(),
+            Self::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<RecordUtf8Error>())
+                .map_or("CSVDIFF_WRITE_IO", ErrorCode::code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn to_json_lines(diff_record: &DiffByteRecord) -> String {
+        let mut json_lines_writer = JsonLinesWriter::new(Vec::new());
+        json_lines_writer
+            .write_diff_byte_record(diff_record)
+            .expect("writing to a Vec never fails");
+        String::from_utf8(json_lines_writer.into_inner()).expect("is utf8")
+    }
+
+    #[test]
+    fn write_add() {
+        let actual = to_json_lines(&DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a", "b", "c"]),
+            3,
+        )));
+
+        assert_eq!(
+            actual,
+            "{\"kind\":\"add\",\"line\":3,\"record\":[\"a\",\"b\",\"c\"]}\n"
+        );
+    }
+
+    #[test]
+    fn write_delete() {
+        let actual = to_json_lines(&DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a"]),
+            2,
+        )));
+
+        assert_eq!(
+            actual,
+            "{\"kind\":\"delete\",\"line\":2,\"record\":[\"a\"]}\n"
+        );
+    }
+
+    #[test]
+    fn write_modify_with_multiple_field_indices() {
+        let actual = to_json_lines(&DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "y"]), 5),
+            field_indices: vec![1, 2],
+        });
+
+        assert_eq!(
+            actual,
+            "{\"kind\":\"modify\",\
+            \"delete\":{\"line\":2,\"record\":[\"a\",\"b\",\"c\"]},\
+            \"add\":{\"line\":5,\"record\":[\"a\",\"x\",\"y\"]},\
+            \"field_indices\":[1,2]}\n"
+        );
+    }
+
+    #[test]
+    fn write_fields_with_special_characters_are_escaped() {
+        let actual = to_json_lines(&DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["\"quoted\"", "back\\slash", "new\nline\u{1}", "༼౪༽"]),
+            2,
+        )));
+
+        assert_eq!(
+            actual,
+            r#"{"kind":"add","line":2,"record":["\"quoted\"","back\\slash","new\nline\u0001","༼౪༽"]}"#
+                .to_string()
+                + "\n"
+        );
+    }
+
+    #[test]
+    fn write_all_from_iter_stops_at_first_error() {
+        let diff_records: Vec<csv::Result<DiffByteRecord>> = vec![
+            Ok(DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a"]),
+                2,
+            ))),
+            Err(csv::Error::from(io::Error::new(
+                io::ErrorKind::Other,
+                "broken",
+            ))),
+            Ok(DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["b"]),
+                3,
+            ))),
+        ];
+        let mut json_lines_writer = JsonLinesWriter::new(Vec::new());
+
+        let res = json_lines_writer.write_all_from_iter(diff_records);
+
+        assert!(matches!(res, Err(JsonLinesError::Csv(_))));
+        assert_eq!(
+            String::from_utf8(json_lines_writer.into_inner()).expect("is utf8"),
+            "{\"kind\":\"add\",\"line\":2,\"record\":[\"a\"]}\n"
+        );
+    }
+
+    #[test]
+    fn write_invalid_utf8_with_strict_conversion_is_err() {
+        let mut json_lines_writer =
+            JsonLinesWriter::new(Vec::new()).utf8_conversion(Utf8Conversion::Strict);
+        let err = json_lines_writer
+            .write_diff_byte_record(&DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b"]), 2),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec![&b"a"[..], &b"\xff"[..]]),
+                    3,
+                ),
+                field_indices: vec![1],
+            })
+            .unwrap_err();
+
+        assert_eq!(
+            err.get_ref()
+                .and_then(|inner| inner.downcast_ref::<RecordUtf8Error>())
+                .map(|utf8_err| (utf8_err.line(), utf8_err.field_idx())),
+            Some((3, 1))
+        );
+        assert_eq!(JsonLinesError::from(err).code(), "CSVDIFF_INVALID_UTF8");
+        assert!(json_lines_writer.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::diff_result::DiffByteRecords;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+
+/// Pairs deleted and added records, whose primary key has changed (e.g. because an ID has been re-issued),
+/// into `Modify` records.
+///
+/// Records are compared by their primary key, so a record, whose key has changed, is reported as a `Delete` of the old
+/// and an `Add` of the new key. This opt-in post-pass pairs a `Delete` and an `Add`, when their non-key columns are
+/// similar enough (see [`min_similarity`](KeyChangePairing::min_similarity)), into a `Modify`, whose `field_indices`
+/// contain all columns, that differ - including the primary key columns. That is the marker of a key change
+/// (see [`is_key_change`](KeyChangePairing::is_key_change)), because a `Modify` of the comparison itself always has an equal key.
+///
+/// How similar two records are, is measured with the [`SimilarityMetric`] (see [`similarity`](KeyChangePairing::similarity)).
+/// Every deleted record is compared with every added record, so this is meant for diffs with a moderate number of
+/// `Delete`s and `Add`s.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+use csv_diff::diff_row::DiffByteRecord;
+use csv_diff::key_change::KeyChangePairing;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind,color\n\
+1,lemon,fruit,yellow\n\
+2,strawberry,fruit,red";
+let csv_right = "\
+id,name,kind,color\n\
+1,lemon,fruit,yellow\n\
+7,strawberry,fruit,red";
+
+let mut diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek(csv_left.as_bytes()),
+    Csv::with_reader_seek(csv_right.as_bytes()),
+)?;
+let key_change_pairing = KeyChangePairing::new(vec![0]);
+let num_of_pairs = key_change_pairing.pair(&mut diff_byte_records);
+
+assert_eq!(num_of_pairs, 1);
+let diff_record = &diff_byte_records.as_slice()[0];
+assert!(matches!(diff_record, DiffByteRecord::Modify { field_indices, .. } if field_indices == &vec![0]));
+assert!(key_change_pairing.is_key_change(diff_record));
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Clone)]
+pub struct KeyChangePairing {
+    primary_key_columns: Vec<usize>,
+    min_similarity: f64,
+    similarity_metric: SimilarityMetric,
+}
+
+/// How the similarity of the non-key columns of a deleted and an added record is measured
+/// (like [`DiffByteRecord::similarity`] and [`DiffByteRecord::byte_similarity`] for a `Modify`).
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum SimilarityMetric {
+    /// The share of non-key columns, whose fields are equal.
+    #[default]
+    Fields,
+    /// The share of bytes of the non-key fields of both records, that are in equal fields, so that a change of
+    /// a short field (e.g. a flag) weighs less than a change of a long one.
+    Bytes,
+}
+
+impl KeyChangePairing {
+    /// Pair records, whose `primary_key_columns` (the same as for the comparison) have changed.
+    pub fn new<I: IntoIterator<Item = usize>>(primary_key_columns: I) -> Self {
+        Self {
+            primary_key_columns: primary_key_columns.into_iter().collect(),
+            min_similarity: 0.8,
+            similarity_metric: SimilarityMetric::default(),
+        }
+    }
+
+    /// Set the minimum [`similarity`](KeyChangePairing::similarity) (between `0.0` and `1.0`), so that a deleted and
+    /// an added record are paired (default: `0.8`). A lower value pairs more aggressively.
+    pub fn min_similarity(mut self, min_similarity: f64) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+
+    /// Set how the similarity of two records is measured (default: [`SimilarityMetric::Fields`]).
+    pub fn similarity_metric(mut self, similarity_metric: SimilarityMetric) -> Self {
+        self.similarity_metric = similarity_metric;
+        self
+    }
+
+    /// Pair the `Delete`s and `Add`s of `diff_byte_records`, the most similar first, and return the number of pairs.
+    ///
+    /// Every `Modify` of a pair takes the place of its `Delete`, while its `Add` is removed. All other records keep
+    /// their order. Records without any non-key column are never paired.
+    pub fn pair(&self, diff_byte_records: &mut DiffByteRecords) -> usize {
+        let (idxs_delete, idxs_add): (Vec<_>, Vec<_>) = diff_byte_records
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, diff_record)| {
+                matches!(
+                    diff_record,
+                    DiffByteRecord::Add(..) | DiffByteRecord::Delete(..)
+                )
+            })
+            .partition(|(_, diff_record)| matches!(diff_record, DiffByteRecord::Delete(..)));
+
+        let mut candidates = Vec::new();
+        for &(idx_delete, delete) in idxs_delete.iter() {
+            for &(idx_add, add) in idxs_add.iter() {
+                if let Some(similarity) = self.similarity(brli_of(delete), brli_of(add)) {
+                    if similarity >= self.min_similarity {
+                        candidates.push((similarity, idx_delete, idx_add));
+                    }
+                }
+            }
+        }
+        // most similar first; ties are broken by position
+        candidates.sort_by(|a, b| {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.cmp(&b.2))
+        });
+
+        let mut is_paired = vec![false; diff_byte_records.0.len()];
+        let mut pairs = Vec::new();
+        for (_, idx_delete, idx_add) in candidates {
+            if !is_paired[idx_delete] && !is_paired[idx_add] {
+                is_paired[idx_delete] = true;
+                is_paired[idx_add] = true;
+                pairs.push((idx_delete, idx_add));
+            }
+        }
+        let num_of_pairs = pairs.len();
+        if num_of_pairs == 0 {
+            return 0;
+        }
+
+        let mut diff_records = std::mem::take(&mut diff_byte_records.0)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        for (idx_delete, idx_add) in pairs {
+            if let (Some(DiffByteRecord::Delete(delete)), Some(DiffByteRecord::Add(add))) = (
+                diff_records[idx_delete].take(),
+                diff_records[idx_add].take(),
+            ) {
+                let field_indices = changed_field_indices(&delete, &add);
+                diff_records[idx_delete] = Some(DiffByteRecord::Modify {
+                    delete,
+                    add,
+                    field_indices,
+                });
+            }
+        }
+        diff_byte_records.0 = diff_records.into_iter().flatten().collect();
+        num_of_pairs
+    }
+
+    /// Return, whether `diff_record` is a `Modify`, whose primary key has changed, i.e. whose `field_indices`
+    /// contain any of the primary key columns (see [`pair`](KeyChangePairing::pair)).
+    pub fn is_key_change(&self, diff_record: &DiffByteRecord) -> bool {
+        match diff_record {
+            DiffByteRecord::Modify { field_indices, .. } => field_indices
+                .iter()
+                .any(|idx| self.primary_key_columns.contains(idx)),
+            DiffByteRecord::Add(..) | DiffByteRecord::Delete(..) | DiffByteRecord::Move { .. } => {
+                false
+            }
+        }
+    }
+
+    /// Return the similarity of the non-key columns of `delete` and `add` according to the [`SimilarityMetric`]
+    /// as a value between `0.0` (nothing is equal) and `1.0` (all non-key fields are equal), e.g. to find
+    /// a [`min_similarity`](KeyChangePairing::min_similarity), that suits the data.
+    ///
+    /// Columns, that only exist in one of the records, count as different. `None` is returned, if the records
+    /// don't have any non-key column (or, for [`SimilarityMetric::Bytes`], all their non-key fields are empty).
+    /// # Example
+    /// ```
+    /// use csv_diff::diff_row::ByteRecordLineInfo;
+    /// use csv_diff::key_change::{KeyChangePairing, SimilarityMetric};
+    ///
+    /// let delete = ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon", "y"]), 2);
+    /// let add = ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["7", "lemon", "n"]), 2);
+    ///
+    /// let key_change_pairing = KeyChangePairing::new(vec![0]);
+    /// assert_eq!(key_change_pairing.similarity(&delete, &add), Some(0.5));
+    ///
+    /// let key_change_pairing = key_change_pairing.similarity_metric(SimilarityMetric::Bytes);
+    /// assert_eq!(key_change_pairing.similarity(&delete, &add), Some(10.0 / 12.0));
+    /// ```
+    pub fn similarity(&self, delete: &ByteRecordLineInfo, add: &ByteRecordLineInfo) -> Option<f64> {
+        let (record_delete, record_add) = (delete.byte_record(), add.byte_record());
+        let (total, equal) = (0..record_delete.len().max(record_add.len()))
+            .filter(|idx| !self.primary_key_columns.contains(idx))
+            .fold((0usize, 0usize), |(total, equal), idx| {
+                let (field_delete, field_add) = (record_delete.get(idx), record_add.get(idx));
+                let is_equal = field_delete.is_some() && field_delete == field_add;
+                let weight = match self.similarity_metric {
+                    SimilarityMetric::Fields => 1,
+                    SimilarityMetric::Bytes => {
+                        field_delete.map_or(0, <[u8]>::len) + field_add.map_or(0, <[u8]>::len)
+                    }
+                };
+                (
+                    total + weight,
+                    if is_equal { equal + weight } else { equal },
+                )
+            });
+        (total > 0).then(|| equal as f64 / total as f64)
+    }
+}
+
+fn brli_of(diff_record: &DiffByteRecord) -> &ByteRecordLineInfo {
+    match diff_record {
+        DiffByteRecord::Add(brli) | DiffByteRecord::Delete(brli) => brli,
+        DiffByteRecord::Modify { .. } | DiffByteRecord::Move { .. } => {
+            unreachable!("only `Delete`s and `Add`s are paired")
+        }
+    }
+}
+
+fn changed_field_indices(delete: &ByteRecordLineInfo, add: &ByteRecordLineInfo) -> Vec<usize> {
+    let (record_delete, record_add) = (delete.byte_record(), add.byte_record());
+    (0..record_delete.len().max(record_add.len()))
+        .filter(|&idx| record_delete.get(idx) != record_add.get(idx))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn delete(fields: Vec<&str>, line: u64) -> DiffByteRecord {
+        DiffByteRecord::Delete(ByteRecordLineInfo::new(csv::ByteRecord::from(fields), line))
+    }
+
+    fn add(fields: Vec<&str>, line: u64) -> DiffByteRecord {
+        DiffByteRecord::Add(ByteRecordLineInfo::new(csv::ByteRecord::from(fields), line))
+    }
+
+    #[test]
+    fn pair_most_similar_first_and_keep_dissimilar_records() {
+        let mut diff_records = DiffByteRecords::from(vec![
+            delete(vec!["1", "lemon", "fruit", "yellow"], 2),
+            delete(vec!["2", "cherry", "fruit", "red"], 3),
+            add(vec!["8", "lemon", "fruit", "green"], 4),
+            add(vec!["9", "lemon", "fruit", "yellow"], 5),
+            add(vec!["10", "cashew", "nut", "brown"], 6),
+        ]);
+        let key_change_pairing = KeyChangePairing::new(vec![0]).min_similarity(0.6);
+
+        let num_of_pairs = key_change_pairing.pair(&mut diff_records);
+
+        assert_eq!(num_of_pairs, 1);
+        assert_eq!(
+            diff_records,
+            DiffByteRecords::from(vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["1", "lemon", "fruit", "yellow"]),
+                        2
+                    ),
+                    add: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["9", "lemon", "fruit", "yellow"]),
+                        5
+                    ),
+                    field_indices: vec![0],
+                },
+                delete(vec!["2", "cherry", "fruit", "red"], 3),
+                add(vec!["8", "lemon", "fruit", "green"], 4),
+                add(vec!["10", "cashew", "nut", "brown"], 6),
+            ])
+        );
+        assert!(key_change_pairing.is_key_change(&diff_records.as_slice()[0]));
+        assert!(!key_change_pairing.is_key_change(&diff_records.as_slice()[1]));
+    }
+
+    #[test]
+    fn pair_with_byte_similarity_weighs_long_fields_more() {
+        let diff_records = DiffByteRecords::from(vec![
+            delete(vec!["1", "a long description of a lemon", "y", "n"], 2),
+            add(vec!["2", "a long description of a lemon", "n", "y"], 2),
+        ]);
+        let key_change_pairing = KeyChangePairing::new(vec![0]).min_similarity(0.8);
+
+        let mut diff_records_fields = diff_records.clone();
+        assert_eq!(key_change_pairing.pair(&mut diff_records_fields), 0);
+
+        let mut diff_records_bytes = diff_records;
+        let num_of_pairs = key_change_pairing
+            .similarity_metric(SimilarityMetric::Bytes)
+            .pair(&mut diff_records_bytes);
+        assert_eq!(num_of_pairs, 1);
+        assert_eq!(
+            diff_records_bytes,
+            DiffByteRecords::from(vec![DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["1", "a long description of a lemon", "y", "n"]),
+                    2
+                ),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "a long description of a lemon", "n", "y"]),
+                    2
+                ),
+                field_indices: vec![0, 2, 3],
+            }])
+        );
+    }
+
+    #[test]
+    fn pair_records_with_only_key_columns_is_no_pair() {
+        let mut diff_records = DiffByteRecords::from(vec![delete(vec!["1"], 2), add(vec!["2"], 2)]);
+
+        let num_of_pairs = KeyChangePairing::new(vec![0])
+            .min_similarity(0.0)
+            .pair(&mut diff_records);
+
+        assert_eq!(num_of_pairs, 0);
+        assert_eq!(
+            diff_records,
+            DiffByteRecords::from(vec![delete(vec!["1"], 2), add(vec!["2"], 2)])
+        );
+    }
+}
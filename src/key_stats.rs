@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Cheap metadata about the primary keys of one side of a comparison, that is collected while hashing.
+///
+/// Each key is a [`csv::ByteRecord`], that holds the primary key fields of a record, ordered by their column index.
+/// Keys are compared lexicographically (field by field and byte-wise).
+/// This can be used to verify, that the intended partitions of two CSVs have been compared (e.g. the same date range)
+/// without having to scan the CSVs separately.
+///
+/// It is part of the [`DiffRunReport`](crate::run_report::DiffRunReport) of a streaming comparison,
+/// see [`key_stats_left`](crate::run_report::DiffRunReport::key_stats_left) and
+/// [`key_stats_right`](crate::run_report::DiffRunReport::key_stats_right).
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+date,amount\n\
+2021-03-02,5\n\
+2021-03-01,3\n\
+2021-03-03,4";
+let csv_right = "\
+date,amount\n\
+2021-03-01,3";
+
+let mut diff_iter = CsvByteDiff::new()?.diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+);
+for diff_record in &mut diff_iter {
+    diff_record?;
+}
+let run_report = diff_iter.run_report();
+let key_stats_left = run_report.key_stats_left();
+
+assert_eq!(key_stats_left.min(), Some(&csv::ByteRecord::from(vec!["2021-03-01"])));
+assert_eq!(key_stats_left.max(), Some(&csv::ByteRecord::from(vec!["2021-03-03"])));
+assert_eq!(key_stats_left.first(), Some(&csv::ByteRecord::from(vec!["2021-03-02"])));
+assert_eq!(key_stats_left.last(), Some(&csv::ByteRecord::from(vec!["2021-03-03"])));
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct KeyStats {
+    min: Option<csv::ByteRecord>,
+    max: Option<csv::ByteRecord>,
+    first: Option<csv::ByteRecord>,
+    last: Option<csv::ByteRecord>,
+}
+
+impl KeyStats {
+    /// The lexicographically smallest key.
+    pub fn min(&self) -> Option<&csv::ByteRecord> {
+        self.min.as_ref()
+    }
+
+    /// The lexicographically greatest key.
+    pub fn max(&self) -> Option<&csv::ByteRecord> {
+        self.max.as_ref()
+    }
+
+    /// The key of the first record.
+    pub fn first(&self) -> Option<&csv::ByteRecord> {
+        self.first.as_ref()
+    }
+
+    /// The key of the last record.
+    pub fn last(&self) -> Option<&csv::ByteRecord> {
+        self.last.as_ref()
+    }
+
+    /// Whether no key has been seen at all (i.e. the CSV has no records).
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none()
+    }
+}
+
+/// Collects [`KeyStats`] for the records of one CSV.
+pub(crate) struct KeyStatsCollector {
+    primary_key_columns: Vec<usize>,
+    key_stats: KeyStats,
+    key: csv::ByteRecord,
+}
+
+impl KeyStatsCollector {
+    pub(crate) fn new(primary_key_columns: &HashSet<usize>) -> Self {
+        let mut primary_key_columns: Vec<_> = primary_key_columns.iter().copied().collect();
+        primary_key_columns.sort_unstable();
+        Self {
+            primary_key_columns,
+            key_stats: KeyStats::default(),
+            key: csv::ByteRecord::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, byte_record: &csv::ByteRecord) {
+        // the key buffer is reused, so that only new minimums/maximums need to be allocated
+        self.key.clear();
+        if self.primary_key_columns.is_empty() {
+            // the whole record acts as the key
+            self.key.extend(byte_record);
+        }
+        for key_field in self
+            .primary_key_columns
+            .iter()
+            .filter_map(|&k_idx| byte_record.get(k_idx))
+        {
+            self.key.push_field(key_field);
+        }
+        let key = &self.key;
+        if self.key_stats.first.is_none() {
+            self.key_stats.first = Some(key.clone());
+        }
+        if self
+            .key_stats
+            .min
+            .as_ref()
+            .map_or(true, |min| cmp_keys(key, min) == Ordering::Less)
+        {
+            self.key_stats.min = Some(key.clone());
+        }
+        if self
+            .key_stats
+            .max
+            .as_ref()
+            .map_or(true, |max| cmp_keys(key, max) == Ordering::Greater)
+        {
+            self.key_stats.max = Some(key.clone());
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> KeyStats {
+        if self.key_stats.first.is_some() {
+            self.key_stats.last = Some(self.key);
+        }
+        self.key_stats
+    }
+}
+
+fn cmp_keys(key: &csv::ByteRecord, other: &csv::ByteRecord) -> Ordering {
+    key.iter().cmp(other.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn collect_compound_key_ordered_by_column_idx() {
+        let mut collector = KeyStatsCollector::new(&vec![2, 0].into_iter().collect());
+        collector.record(&csv::ByteRecord::from(vec!["b", "x", "2"]));
+        collector.record(&csv::ByteRecord::from(vec!["a", "y", "9"]));
+        collector.record(&csv::ByteRecord::from(vec!["b", "z", "1"]));
+
+        let key_stats = collector.finish();
+
+        assert_eq!(
+            key_stats.min(),
+            Some(&csv::ByteRecord::from(vec!["a", "9"]))
+        );
+        assert_eq!(
+            key_stats.max(),
+            Some(&csv::ByteRecord::from(vec!["b", "2"]))
+        );
+        assert_eq!(
+            key_stats.first(),
+            Some(&csv::ByteRecord::from(vec!["b", "2"]))
+        );
+        assert_eq!(
+            key_stats.last(),
+            Some(&csv::ByteRecord::from(vec!["b", "1"]))
+        );
+    }
+
+    #[test]
+    fn collect_nothing_is_empty() {
+        let key_stats = KeyStatsCollector::new(&std::iter::once(0).collect()).finish();
+
+        assert!(key_stats.is_empty());
+        assert_eq!(key_stats, KeyStats::default());
+    }
+}
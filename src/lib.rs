@@ -21,9 +21,22 @@ The most important types you will use are:
 */
 
 #![forbid(unsafe_code)]
+#![cfg_attr(feature = "nightly-simd", feature(portable_simd))]
 
+pub mod aggregate_diff;
+#[cfg(feature = "ansi-renderer")]
+pub mod ansi_renderer;
+pub mod approx_diff;
+#[cfg(feature = "arrow")]
+pub mod arrow_diff;
+pub mod bom;
+pub mod cancellation;
+pub mod column_order;
+pub mod column_stats;
 pub mod csv;
 pub mod csv_diff;
+#[cfg(feature = "tokio-async")]
+pub mod csv_diff_async;
 mod csv_hash_comparer;
 // TODO: try to make it more private
 pub mod csv_hash_receiver_comparer;
@@ -31,9 +44,58 @@ pub mod csv_hash_task_spawner;
 mod csv_hasher;
 pub mod csv_parse_result;
 mod csv_parser_hasher;
+pub mod dialect;
+pub mod diff_outcome;
 pub mod diff_result;
 pub mod diff_row;
+pub mod empty_csv;
+#[cfg(feature = "encoding")]
+pub mod encoding_input;
+pub mod error_code;
+pub mod error_policy;
+pub mod field_comparator;
+pub mod flush_threshold;
+pub mod hash_index;
+pub mod hash_snapshot;
+pub mod header_diff;
+#[cfg(feature = "html-report")]
+pub mod html_report;
+#[cfg(feature = "http")]
+pub mod http_input;
+pub mod json_lines;
+pub mod key_change;
+pub mod key_stats;
+pub mod metrics;
+pub mod multi_reader;
+#[cfg(feature = "rayon-threads")]
+pub mod parallel_render;
+#[cfg(feature = "parquet")]
+pub mod parquet_input;
+pub mod positional_diff;
+pub mod preset;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod resumable_diff;
+pub mod run_report;
+#[cfg(feature = "aws")]
+pub mod s3_input;
+pub mod severity;
+mod simd;
+#[cfg(feature = "simd-parser")]
+mod simd_parser;
+pub mod sorted_diff;
+mod spill;
+pub mod split_writer;
+pub mod spooled;
+pub mod task_panic;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(any(feature = "rayon-threads", feature = "crossbeam-threads"))]
 mod thread_scope_strategy; // TODO: do we really need this?
+pub mod throttle;
+pub mod unified_diff;
+#[cfg(feature = "zstd")]
+pub mod zstd_input;
 
 #[doc(inline)]
 pub use ::csv::Result;
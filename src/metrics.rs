@@ -0,0 +1,112 @@
+use crate::empty_csv::CsvSide;
+
+/// Receives metrics of a streaming comparison, e.g. to feed them into a monitoring system.
+///
+/// A sink is attached to a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator) with
+/// [`with_metrics_sink`](crate::diff_result::DiffByteRecordsIterator::with_metrics_sink) and is called on the thread,
+/// that advances the iterator. All methods do nothing by default, so an implementation only needs to override
+/// the metrics it is interested in. Without a sink, the iterator uses [`NoDiffMetrics`], whose calls are compiled away.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::empty_csv::CsvSide;
+use csv_diff::metrics::DiffMetricsSink;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[derive(Default)]
+struct RecordsRead {
+    left: u64,
+    right: u64,
+}
+
+impl DiffMetricsSink for RecordsRead {
+    fn record_read(&mut self, side: CsvSide) {
+        match side {
+            CsvSide::Left => self.left += 1,
+            CsvSide::Right => self.right += 1,
+        }
+    }
+}
+
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,fruit";
+
+let mut records_read = RecordsRead::default();
+let num_of_diffs = CsvByteDiff::new()?
+    .diff(
+        Csv::with_reader(csv_left.as_bytes()),
+        Csv::with_reader(csv_right.as_bytes()),
+    )
+    .with_metrics_sink(&mut records_read)
+    .count();
+
+assert_eq!(num_of_diffs, 1);
+assert_eq!((records_read.left, records_read.right), (2, 1));
+Ok(())
+# }
+```
+"##
+)]
+pub trait DiffMetricsSink {
+    /// A record of the `side` CSV has been read (this includes malformed records).
+    #[inline]
+    fn record_read(&mut self, _side: CsvSide) {}
+
+    /// The key hash and the record hash of a record of the `side` CSV have been computed.
+    #[inline]
+    fn record_hashed(&mut self, _side: CsvSide) {}
+
+    /// The number of records in the maps, that hold the records of each CSV until they are matched,
+    /// after a record has been received.
+    #[inline]
+    fn map_sizes(&mut self, _len_left: usize, _len_right: usize) {}
+
+    /// The map of the `side` CSV has been flushed (see [`FlushThreshold`](crate::flush_threshold::FlushThreshold)):
+    /// `num_flushed` matched records have been removed and `num_unmatched` records are kept.
+    #[inline]
+    fn map_flushed(&mut self, _side: CsvSide, _num_flushed: usize, _num_unmatched: usize) {}
+
+    /// `num_recycled` records have been sent back to the hashing tasks, so that their allocations can be reused.
+    #[inline]
+    fn records_recycled(&mut self, _num_recycled: usize) {}
+}
+
+/// The default [`DiffMetricsSink`], that ignores all metrics.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoDiffMetrics;
+
+impl DiffMetricsSink for NoDiffMetrics {}
+
+impl<M: DiffMetricsSink + ?Sized> DiffMetricsSink for &mut M {
+    #[inline]
+    fn record_read(&mut self, side: CsvSide) {
+        (**self).record_read(side)
+    }
+
+    #[inline]
+    fn record_hashed(&mut self, side: CsvSide) {
+        (**self).record_hashed(side)
+    }
+
+    #[inline]
+    fn map_sizes(&mut self, len_left: usize, len_right: usize) {
+        (**self).map_sizes(len_left, len_right)
+    }
+
+    #[inline]
+    fn map_flushed(&mut self, side: CsvSide, num_flushed: usize, num_unmatched: usize) {
+        (**self).map_flushed(side, num_flushed, num_unmatched)
+    }
+
+    #[inline]
+    fn records_recycled(&mut self, num_recycled: usize) {
+        (**self).records_recycled(num_recycled)
+    }
+}
@@ -0,0 +1,236 @@
+use crate::bom::BomStrippingReader;
+use crate::csv::{Csv, CsvBuilder};
+use crate::error_code::ErrorCode;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use thiserror::Error;
+
+/// A reader, that concatenates the CSV data of several readers (e.g. the daily partitions of an export),
+/// so that they can be compared as one CSV.
+///
+/// Every reader must start with the same header, which is only kept for the first reader, so that records are
+/// numbered consecutively across all readers (the first record of the second reader follows the last record
+/// of the first one). Empty readers are skipped and a byte order mark at the start of a reader is stripped
+/// (see [`BomStrippingReader`]). Headers are compared line by line, so they must not contain
+/// quoted line breaks.
+///
+/// Usually, it is created with [`Csv::with_readers`]. In order to compare it with
+/// [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), which needs to seek in the CSV data,
+/// use [`CsvBuilder::build_with_reader_spooled`].
+#[derive(Debug)]
+pub struct MultiReader<R> {
+    readers: std::vec::IntoIter<R>,
+    current: Option<BufReader<BomStrippingReader<R>>>,
+    reader_idx: Option<usize>,
+    header: Option<Vec<u8>>,
+    pending: Cursor<Vec<u8>>,
+    ends_with_newline: bool,
+}
+
+impl<R: Read> MultiReader<R> {
+    pub fn new(readers: Vec<R>) -> Self {
+        Self {
+            readers: readers.into_iter(),
+            current: None,
+            reader_idx: None,
+            header: None,
+            pending: Cursor::new(Vec::new()),
+            ends_with_newline: true,
+        }
+    }
+
+    /// Start reading `reader`: keep its header, if it's the first one, or check it against the first one
+    /// and skip it otherwise.
+    fn open(&mut self, reader: R) -> io::Result<()> {
+        let reader_idx = self.reader_idx.map_or(0, |reader_idx| reader_idx + 1);
+        self.reader_idx = Some(reader_idx);
+        let mut reader = BufReader::new(BomStrippingReader::new(reader));
+        let mut header = Vec::new();
+        reader.read_until(b'\n', &mut header)?;
+        let has_records = !reader.fill_buf()?.is_empty();
+
+        let mut pending = Vec::new();
+        match &self.header {
+            _ if header.is_empty() => return Ok(()),
+            None => {
+                self.header = Some(trim_line_end(&header).to_vec());
+                pending = header;
+            }
+            Some(first_header) if first_header.as_slice() != trim_line_end(&header) => {
+                return Err(InconsistentHeadersError::new(reader_idx).into());
+            }
+            Some(_) if has_records && !self.ends_with_newline => pending.push(b'\n'),
+            Some(_) => (),
+        }
+        self.pending = Cursor::new(pending);
+        self.current = Some(reader);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for MultiReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let bytes_read = if (self.pending.position() as usize) < self.pending.get_ref().len() {
+                self.pending.read(buf)?
+            } else if let Some(current) = self.current.as_mut() {
+                current.read(buf)?
+            } else {
+                match self.readers.next() {
+                    Some(reader) => {
+                        self.open(reader)?;
+                        continue;
+                    }
+                    None => return Ok(0),
+                }
+            };
+            if bytes_read == 0 {
+                self.current = None;
+                continue;
+            }
+            self.ends_with_newline = buf[bytes_read - 1] == b'\n';
+            return Ok(bytes_read);
+        }
+    }
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+impl<R: Read> Csv<MultiReader<R>> {
+    /// Create a new `Csv` with several readers, whose CSV data is concatenated (see [`MultiReader`]),
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    /// # Example: compare daily partitions with a single export
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv, diff_row::LineNum};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let partitions_left = vec![
+    "id,name,kind\n1,lemon,fruit\n".as_bytes(),
+    "id,name,kind\n2,strawberry,fruit".as_bytes(),
+];
+let export_right = "id,name,kind\n1,lemon,fruit\n2,strawberry,nut".as_bytes();
+
+let diff_byte_records = CsvByteDiff::new()?
+    .diff(
+        Csv::with_readers(partitions_left),
+        Csv::with_readers(vec![export_right]),
+    )
+    .collect::<csv::Result<Vec<_>>>()?;
+
+assert_eq!(diff_byte_records.len(), 1);
+// the line of the modified record in the concatenated partitions
+assert!(matches!(
+    diff_byte_records[0].line_num(),
+    LineNum::BothSides { for_deleted: 3, for_added: 3 }
+));
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_readers(readers: Vec<R>) -> Self {
+        CsvBuilder::new().build_with_readers(readers)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but concatenates the CSV data of `readers`
+    /// (see [`Csv::with_readers`]).
+    pub fn build_with_readers<R: Read>(&self, readers: Vec<R>) -> Csv<MultiReader<R>> {
+        self.build_with_reader(MultiReader::new(readers))
+    }
+}
+
+/// The header of a reader is different from the header of the first reader of a [`MultiReader`].
+///
+/// It is returned as [`csv::Error`] of kind [`csv::ErrorKind::Io`] when comparing.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("the header of reader {reader_idx} is different from the header of the first reader")]
+pub struct InconsistentHeadersError {
+    reader_idx: usize,
+}
+
+impl InconsistentHeadersError {
+    pub(crate) fn new(reader_idx: usize) -> Self {
+        Self { reader_idx }
+    }
+
+    /// The index of the reader with the different header.
+    pub fn reader_idx(&self) -> usize {
+        self.reader_idx
+    }
+}
+
+impl ErrorCode for InconsistentHeadersError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_INCONSISTENT_HEADERS"
+    }
+}
+
+impl From<InconsistentHeadersError> for io::Error {
+    fn from(err: InconsistentHeadersError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn readers_are_concatenated_without_repeated_headers() -> csv::Result<()> {
+        let mut csv_reader = Csv::with_readers(vec![
+            "id,name\n1,lemon".as_bytes(),
+            "".as_bytes(),
+            "\u{feff}id,name\r\n".as_bytes(),
+            "id,name\n2,cherry\n3,apple".as_bytes(),
+        ])
+        .into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "cherry"]),
+                csv::ByteRecord::from(vec!["3", "apple"])
+            ]
+        );
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.position().map(|pos| pos.line()))
+                .collect::<Vec<_>>(),
+            vec![Some(2), Some(3), Some(4)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reader_with_different_header_is_err() {
+        let mut data = String::new();
+        let err = MultiReader::new(vec![
+            "id,name\n1,lemon\n".as_bytes(),
+            "id,kind\n2,fruit\n".as_bytes(),
+        ])
+        .read_to_string(&mut data)
+        .unwrap_err();
+
+        let inconsistent_headers_err = err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<InconsistentHeadersError>());
+        assert_eq!(
+            inconsistent_headers_err,
+            Some(&InconsistentHeadersError::new(1))
+        );
+        assert_eq!(csv::Error::from(err).code(), "CSVDIFF_INCONSISTENT_HEADERS");
+    }
+}
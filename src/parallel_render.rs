@@ -0,0 +1,308 @@
+use crate::diff_row::DiffByteRecord;
+use crate::error_code::ErrorCode;
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Formats single [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s independently of each other,
+/// so that they can be formatted in parallel by a [`ParallelRenderer`].
+///
+/// It is implemented for all renderers of this crate. Options, that need to see all records at once
+/// (like grouping records by a column), are not applied when formatting single records.
+pub trait DiffRecordFormatter {
+    /// Write everything, that comes before the first record (e.g. an HTML head), into `wtr`.
+    fn write_prologue(&self, _wtr: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Write `diff_record` into `wtr`.
+    fn format_diff_byte_record(
+        &self,
+        diff_record: &DiffByteRecord,
+        wtr: &mut dyn Write,
+    ) -> io::Result<()>;
+
+    /// Write everything, that comes after the last record, into `wtr`.
+    fn write_epilogue(&self, _wtr: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formats batches of [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s on a [rayon thread-pool](https://docs.rs/rayon/1.5.0/rayon/struct.ThreadPool.html),
+/// while the CSVs are still being compared, and writes them in their original order.
+///
+/// The differences are received on the calling thread (usually from a [`DiffByteRecordsIterator`](crate::diff_result::DiffByteRecordsIterator)),
+/// collected into batches of [`batch_size`](ParallelRenderer::batch_size) records and every batch is formatted by one task of the thread-pool.
+/// At most [`max_batches_in_flight`](ParallelRenderer::max_batches_in_flight) batches are formatted at the same time,
+/// so memory usage stays bounded, even if the writer is slow.
+///
+/// This is only available with the Cargo feature `rayon-threads`.
+/// # Example
+/// ```
+/// use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+/// use csv_diff::parallel_render::ParallelRenderer;
+/// use csv_diff::unified_diff::UnifiedDiffRenderer;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let csv_left = "\
+/// id,name,kind\n\
+/// 1,lemon,fruit";
+/// let csv_right = "\
+/// id,name,kind\n\
+/// 1,lemon,nut";
+///
+/// let mut output = Vec::new();
+/// let num_of_records_written = ParallelRenderer::new(UnifiedDiffRenderer::new())?.render_to(
+///     CsvByteDiff::new()?.diff(
+///         Csv::with_reader(csv_left.as_bytes()),
+///         Csv::with_reader(csv_right.as_bytes()),
+///     ),
+///     &mut output,
+/// )?;
+///
+/// assert_eq!(num_of_records_written, 1);
+/// assert_eq!(String::from_utf8(output)?, "~ 2 | 1,lemon,[-fruit-]{+nut+}\n");
+/// Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ParallelRenderer<F> {
+    formatter: Arc<F>,
+    thread_pool: Arc<rayon::ThreadPool>,
+    batch_size: usize,
+    max_batches_in_flight: usize,
+}
+
+impl<F> ParallelRenderer<F>
+where
+    F: DiffRecordFormatter + Send + Sync + 'static,
+{
+    /// Create a `ParallelRenderer`, that formats with `formatter` on a new rayon thread-pool.
+    pub fn new(formatter: F) -> Result<Self, rayon::ThreadPoolBuildError> {
+        Ok(Self::with_thread_pool(
+            formatter,
+            Arc::new(rayon::ThreadPoolBuilder::new().build()?),
+        ))
+    }
+
+    /// Create a `ParallelRenderer`, that formats with `formatter` on an existing rayon thread-pool.
+    pub fn with_thread_pool(formatter: F, thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        Self {
+            formatter: Arc::new(formatter),
+            max_batches_in_flight: 2 * thread_pool.current_num_threads(),
+            thread_pool,
+            batch_size: 1024,
+        }
+    }
+
+    /// Set the number of records, that are formatted by one task (default: 1024). A value of `0` is treated as `1`.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the maximum number of batches, that are formatted at the same time
+    /// (default: twice the number of threads of the thread-pool). A value of `0` is treated as `1`.
+    pub fn max_batches_in_flight(mut self, max_batches_in_flight: usize) -> Self {
+        self.max_batches_in_flight = max_batches_in_flight.max(1);
+        self
+    }
+
+    /// Consume `diff_records`, format them in parallel and write them into `wtr` in their original order.
+    ///
+    /// Returns the number of records that have been written or the first error that occurred
+    /// (either when comparing the CSVs or when writing). Batches, that are already being formatted,
+    /// are always waited for, so no task of the thread-pool outlives this call.
+    pub fn render_to<I, W>(&self, diff_records: I, wtr: &mut W) -> Result<u64, ParallelRenderError>
+    where
+        I: IntoIterator<Item = csv::Result<DiffByteRecord>>,
+        W: Write,
+    {
+        self.formatter.write_prologue(wtr)?;
+        let (sender, receiver) = unbounded();
+        let mut pending = PendingBatches {
+            receiver,
+            formatted_batches: BTreeMap::new(),
+            num_of_batches_written: 0,
+        };
+        let mut num_of_batches_spawned = 0;
+        let mut num_of_records = 0;
+        let mut result = Ok(());
+
+        let mut diff_records = diff_records.into_iter();
+        let mut is_exhausted = false;
+        while !is_exhausted && result.is_ok() {
+            let mut batch = Vec::with_capacity(self.batch_size);
+            for diff_record in diff_records.by_ref().take(self.batch_size) {
+                match diff_record {
+                    Ok(diff_record) => batch.push(diff_record),
+                    Err(csv_err) => {
+                        result = Err(csv_err.into());
+                        break;
+                    }
+                }
+            }
+            is_exhausted = batch.len() < self.batch_size;
+            if batch.is_empty() {
+                break;
+            }
+            num_of_records += batch.len() as u64;
+
+            let formatter = Arc::clone(&self.formatter);
+            let sender = sender.clone();
+            let batch_idx = num_of_batches_spawned;
+            self.thread_pool.spawn(move || {
+                let mut buf = Vec::new();
+                let formatted = batch
+                    .iter()
+                    .try_for_each(|diff_record| {
+                        formatter.format_diff_byte_record(diff_record, &mut buf)
+                    })
+                    .map(|()| buf);
+                // we ignore any sending errors - the receiver is never dropped before all batches have been received
+                let _ = sender.send((batch_idx, formatted));
+            });
+            num_of_batches_spawned += 1;
+
+            while num_of_batches_spawned - pending.num_of_batches_written
+                >= self.max_batches_in_flight
+            {
+                pending.recv_and_write(&mut result, wtr);
+            }
+        }
+        // wait for all remaining batches, even if an error has occurred
+        while pending.num_of_batches_written < num_of_batches_spawned {
+            pending.recv_and_write(&mut result, wtr);
+        }
+        result?;
+        self.formatter.write_epilogue(wtr)?;
+        Ok(num_of_records)
+    }
+}
+
+/// Batches, that are being formatted or that have been formatted, but can't be written yet,
+/// because a batch before them is still being formatted.
+struct PendingBatches {
+    receiver: Receiver<(usize, io::Result<Vec<u8>>)>,
+    formatted_batches: BTreeMap<usize, io::Result<Vec<u8>>>,
+    num_of_batches_written: usize,
+}
+
+impl PendingBatches {
+    /// Wait for the next formatted batch and write all batches, that are next in order.
+    /// After the first error, batches are only received, but not written anymore.
+    fn recv_and_write<W: Write>(
+        &mut self,
+        result: &mut Result<(), ParallelRenderError>,
+        wtr: &mut W,
+    ) {
+        let (batch_idx, formatted) = self.receiver.recv().expect("a sender is still alive");
+        self.formatted_batches.insert(batch_idx, formatted);
+        while let Some(formatted) = self.formatted_batches.remove(&self.num_of_batches_written) {
+            self.num_of_batches_written += 1;
+            if result.is_ok() {
+                *result = formatted
+                    .and_then(|buf| wtr.write_all(&buf))
+                    .map_err(ParallelRenderError::from);
+            }
+        }
+    }
+}
+
+/// The error of [`ParallelRenderer::render_to`].
+#[derive(Debug, Error)]
+pub enum ParallelRenderError {
+    #[error("an error occured when comparing the CSVs: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("an error occured when writing the rendered differences: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl ErrorCode for ParallelRenderError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Csv(csv_err) => csv_err.code(),
+            Self::Io(_) => "CSVDIFF_WRITE_IO",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use crate::json_lines::JsonLinesFormat;
+    use crate::unified_diff::UnifiedDiffRenderer;
+    use pretty_assertions::assert_eq;
+
+    fn diff_records(num: usize) -> Vec<DiffByteRecord> {
+        (0..num)
+            .map(|i| {
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec![i.to_string(), "lemon".to_string()]),
+                    i as u64 + 2,
+                ))
+            })
+            .collect()
+    }
+
+    fn thread_pool() -> Arc<rayon::ThreadPool> {
+        Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(4)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn render_to_keeps_order_of_records() -> Result<(), ParallelRenderError> {
+        let diff_records = diff_records(1_000);
+        let unified_diff_renderer = UnifiedDiffRenderer::new();
+
+        let mut output = Vec::new();
+        let num_written =
+            ParallelRenderer::with_thread_pool(unified_diff_renderer.clone(), thread_pool())
+                .batch_size(7)
+                .max_batches_in_flight(3)
+                .render_to(diff_records.iter().cloned().map(Ok), &mut output)?;
+
+        assert_eq!(num_written, 1_000);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            unified_diff_renderer.render(&diff_records)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_to_without_records_writes_nothing() -> Result<(), ParallelRenderError> {
+        let mut output = Vec::new();
+        let num_written = ParallelRenderer::with_thread_pool(JsonLinesFormat, thread_pool())
+            .render_to(std::iter::empty(), &mut output)?;
+
+        assert_eq!(num_written, 0);
+        assert!(output.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn render_to_stops_at_first_csv_error() {
+        let diff_records = diff_records(10)
+            .into_iter()
+            .map(Ok)
+            .chain(std::iter::once(Err(csv::Error::from(io::Error::new(
+                io::ErrorKind::Other,
+                "broken",
+            )))))
+            .chain(diff_records(10).into_iter().map(Ok));
+
+        let mut output = Vec::new();
+        let res = ParallelRenderer::with_thread_pool(JsonLinesFormat, thread_pool())
+            .batch_size(3)
+            .render_to(diff_records, &mut output);
+
+        assert!(matches!(res, Err(ParallelRenderError::Csv(_))));
+    }
+}
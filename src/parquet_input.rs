@@ -0,0 +1,199 @@
+use crate::csv::{Csv, CsvBuilder};
+use parquet::errors::ParquetError;
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use parquet::record::reader::RowIter;
+use parquet::record::Field;
+use std::io::{self, Cursor, Read};
+
+/// The number of rows, that are converted to CSV at once.
+const ROWS_PER_BATCH: usize = 1024;
+
+/// A reader, that converts the rows of a [Parquet](https://parquet.apache.org/) file into CSV data on the fly,
+/// so that Parquet exports can be compared with the same primary key semantics as CSVs.
+///
+/// The first line is a header with the names of the top-level columns; every row follows on its own line, in the order
+/// of the row groups. This means that the line of a row is its (zero-based) index plus two.
+/// Fields are converted as follows:
+/// - `NULL` becomes an empty field
+/// - strings and byte arrays are taken as they are
+/// - all other values (numbers, dates, timestamps, decimals and nested values) are formatted with their `Display` implementation
+///
+/// This is only available with the Cargo feature `parquet`.
+pub struct ParquetReader {
+    header: Option<csv::ByteRecord>,
+    rows: RowIter<'static>,
+    record: csv::ByteRecord,
+    csv_data: Cursor<Vec<u8>>,
+}
+
+impl ParquetReader {
+    /// Create a new `ParquetReader` for the Parquet file, that is read by `reader` (e.g. a [`File`](std::fs::File)).
+    ///
+    /// Only the metadata of the file is read here; the rows are read while they are being compared.
+    pub fn new<R: ChunkReader + 'static>(reader: R) -> Result<Self, ParquetError> {
+        let file_reader = SerializedFileReader::new(reader)?;
+        let header = file_reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .root_schema()
+            .get_fields()
+            .iter()
+            .map(|field| field.name())
+            .collect();
+        Ok(Self {
+            header: Some(header),
+            rows: RowIter::from_file_into(Box::new(file_reader)),
+            record: csv::ByteRecord::new(),
+            csv_data: Cursor::new(Vec::new()),
+        })
+    }
+
+    /// Convert the next batch of rows into CSV data; `csv_data` stays empty, when there are no more rows.
+    fn convert_next_rows(&mut self) -> io::Result<()> {
+        let mut csv_data = std::mem::take(self.csv_data.get_mut());
+        csv_data.clear();
+        {
+            let mut csv_writer = csv::Writer::from_writer(&mut csv_data);
+            if let Some(header) = self.header.take() {
+                csv_writer.write_byte_record(&header)?;
+            }
+            for row in self.rows.by_ref().take(ROWS_PER_BATCH) {
+                let row = row.map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                self.record.clear();
+                for (_, field) in row.get_column_iter() {
+                    match field {
+                        Field::Null => self.record.push_field(b""),
+                        Field::Str(s) => self.record.push_field(s.as_bytes()),
+                        Field::Bytes(bytes) => self.record.push_field(bytes.data()),
+                        field => self.record.push_field(field.to_string().as_bytes()),
+                    }
+                }
+                csv_writer.write_byte_record(&self.record)?;
+            }
+            csv_writer.flush()?;
+        }
+        self.csv_data = Cursor::new(csv_data);
+        Ok(())
+    }
+}
+
+impl Read for ParquetReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.csv_data.position() as usize >= self.csv_data.get_ref().len() {
+            self.convert_next_rows()?;
+        }
+        self.csv_data.read(buf)
+    }
+}
+
+impl Csv<ParquetReader> {
+    /// Create a new `Csv`, that converts the rows of a Parquet file into CSV data (see [`ParquetReader`]),
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    ///
+    /// In order to compare it with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal), which needs to seek
+    /// in the CSV data, use [`CsvBuilder::build_with_reader_spooled`] with a [`ParquetReader`].
+    /// This is only available with the Cargo feature `parquet`.
+    pub fn with_parquet_reader<R: ChunkReader + 'static>(reader: R) -> Result<Self, ParquetError> {
+        CsvBuilder::new().build_with_parquet_reader(reader)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but converts the rows of a Parquet file
+    /// into CSV data (see [`Csv::with_parquet_reader`]).
+    pub fn build_with_parquet_reader<R: ChunkReader + 'static>(
+        &self,
+        reader: R,
+    ) -> Result<Csv<ParquetReader>, ParquetError> {
+        Ok(self.build_with_reader(ParquetReader::new(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+    use std::fs::File;
+    use std::sync::Arc;
+
+    /// Write a Parquet file with one row group per element of `row_groups`.
+    fn parquet_file(row_groups: &[&[(i64, Option<&str>)]]) -> Result<File, Box<dyn Error>> {
+        let schema = Arc::new(parse_message_type(
+            "message fruits { REQUIRED INT64 id; OPTIONAL BYTE_ARRAY name (UTF8); }",
+        )?);
+        let file = tempfile::tempfile()?;
+        let mut file_writer =
+            SerializedFileWriter::new(file.try_clone()?, schema, Default::default())?;
+        for rows in row_groups {
+            let ids = rows.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            let names = rows
+                .iter()
+                .filter_map(|(_, name)| name.map(ByteArray::from))
+                .collect::<Vec<_>>();
+            let name_def_levels = rows
+                .iter()
+                .map(|(_, name)| i16::from(name.is_some()))
+                .collect::<Vec<_>>();
+
+            let mut row_group_writer = file_writer.next_row_group()?;
+            let mut column_writer = row_group_writer.next_column()?.expect("id column");
+            column_writer
+                .typed::<Int64Type>()
+                .write_batch(&ids, None, None)?;
+            column_writer.close()?;
+            let mut column_writer = row_group_writer.next_column()?.expect("name column");
+            column_writer.typed::<ByteArrayType>().write_batch(
+                &names,
+                Some(&name_def_levels),
+                None,
+            )?;
+            column_writer.close()?;
+            row_group_writer.close()?;
+        }
+        file_writer.close()?;
+        Ok(file)
+    }
+
+    #[test]
+    fn parquet_reader_yields_rows_of_all_row_groups_as_csv() -> Result<(), Box<dyn Error>> {
+        let file = parquet_file(&[&[(1, Some("lemon")), (2, None)], &[(3, Some("a, b"))]])?;
+
+        let mut csv_data = String::new();
+        ParquetReader::new(file)?.read_to_string(&mut csv_data)?;
+
+        assert_eq!(csv_data, "id,name\n1,lemon\n2,\n3,\"a, b\"\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon-threads")]
+    #[test]
+    fn diff_parquet_files_one_row_modified() -> Result<(), Box<dyn Error>> {
+        use crate::csv_diff::CsvByteDiff;
+
+        let file_left = parquet_file(&[&[(1, Some("lemon")), (2, Some("cherry"))]])?;
+        let file_right = parquet_file(&[&[(1, Some("lemon"))], &[(2, Some("apple"))]])?;
+
+        let diff_byte_records = CsvByteDiff::new()?
+            .diff(
+                Csv::with_parquet_reader(file_left)?,
+                Csv::with_parquet_reader(file_right)?,
+            )
+            .collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            diff_byte_records,
+            vec![DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "cherry"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "apple"]), 3),
+                field_indices: vec![1]
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use crate::cancellation::CancellationToken;
+use crate::csv::Csv;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::field_comparator::{FieldComparator, FieldComparators, IgnoreComparator};
+use crate::simd::modified_field_indices;
+use std::io::Read;
+use std::sync::Arc;
+
+/// Compare two [CSVs](https://en.wikipedia.org/wiki/Comma-separated_values) by the position of their records instead of a primary key.
+///
+/// The n-th record of the left CSV is compared with the n-th record of the right CSV. If they are different,
+/// a [`Modify`](crate::diff_row::DiffByteRecord::Modify) is emitted. If one CSV has more records than the other,
+/// its remaining records are emitted as [`Delete`](crate::diff_row::DiffByteRecord::Delete)s (left)
+/// or [`Add`](crate::diff_row::DiffByteRecord::Add)s (right).
+///
+/// This is not a general line-by-line differ: an inserted record shifts all records after it, so they are all reported as modified.
+/// It is meant for CSVs, whose records don't move, e.g. append-only logs, where new records are only ever added at the end.
+/// Like [`CsvByteDiffSorted`](crate::sorted_diff::CsvByteDiffSorted), it needs no hash maps and no threads,
+/// and the differences are emitted in the order of the records.
+///
+/// Headers are not compared.
+/// # Example
+/// ```
+/// use csv_diff::{positional_diff::CsvByteDiffPositional, csv::Csv};
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let csv_left = "\
+/// time,level,message\n\
+/// 10:00,info,started\n\
+/// 10:01,warn,slow";
+/// let csv_right = "\
+/// time,level,message\n\
+/// 10:00,info,started\n\
+/// 10:01,error,slow\n\
+/// 10:02,info,stopped";
+///
+/// let diff_byte_records = CsvByteDiffPositional::new()
+///     .diff(
+///         Csv::with_reader(csv_left.as_bytes()),
+///         Csv::with_reader(csv_right.as_bytes()),
+///     )
+///     .collect::<csv::Result<Vec<_>>>()?;
+///
+/// assert_eq!(
+///     diff_byte_records,
+///     vec![
+///         DiffByteRecord::Modify {
+///             delete: ByteRecordLineInfo::new(
+///                 csv::ByteRecord::from(vec!["10:01", "warn", "slow"]),
+///                 3
+///             ),
+///             add: ByteRecordLineInfo::new(
+///                 csv::ByteRecord::from(vec!["10:01", "error", "slow"]),
+///                 3
+///             ),
+///             field_indices: vec![1],
+///         },
+///         DiffByteRecord::Add(ByteRecordLineInfo::new(
+///             csv::ByteRecord::from(vec!["10:02", "info", "stopped"]),
+///             4
+///         )),
+///     ]
+/// );
+/// Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CsvByteDiffPositional {
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+}
+
+impl CsvByteDiffPositional {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare `csv_left` with `csv_right` lazily: records are only read, when the returned iterator is advanced.
+    pub fn diff<R: Read>(
+        &self,
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+    ) -> DiffByteRecordsPositional<R> {
+        DiffByteRecordsPositional {
+            reader_left: csv_left.into_csv_reader(),
+            reader_right: csv_right.into_csv_reader(),
+            field_comparators: self.field_comparators.clone(),
+            cancellation_token: self.cancellation_token.clone(),
+            is_finished: false,
+        }
+    }
+}
+
+/// Create a [`CsvByteDiffPositional`] with configuration options.
+#[derive(Debug, Default)]
+pub struct CsvByteDiffPositionalBuilder {
+    csv_byte_diff_positional: CsvByteDiffPositional,
+}
+
+impl CsvByteDiffPositionalBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare the fields of column `column` with `comparator` instead of byte-wise.
+    ///
+    /// See [`CsvByteDiffBuilder::field_comparator`](crate::csv_diff::CsvByteDiffBuilder::field_comparator).
+    pub fn field_comparator(
+        mut self,
+        column: usize,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.csv_byte_diff_positional
+            .field_comparators
+            .insert(column, Arc::new(comparator));
+        self
+    }
+
+    /// Don't report changes in the columns `columns` as [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+    ///
+    /// See [`CsvByteDiffBuilder::ignore_columns_for_modify`](crate::csv_diff::CsvByteDiffBuilder::ignore_columns_for_modify).
+    pub fn ignore_columns_for_modify(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        let ignore_comparator: Arc<dyn FieldComparator> = Arc::new(IgnoreComparator);
+        for column in columns {
+            self.csv_byte_diff_positional
+                .field_comparators
+                .insert(column, Arc::clone(&ignore_comparator));
+        }
+        self
+    }
+
+    /// Check `cancellation_token` while comparing, so that the comparison can be cancelled from another thread.
+    ///
+    /// See [`CancellationToken`](crate::cancellation::CancellationToken) for more information.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.csv_byte_diff_positional.cancellation_token = cancellation_token;
+        self
+    }
+
+    pub fn build(self) -> CsvByteDiffPositional {
+        self.csv_byte_diff_positional
+    }
+}
+
+/// Emits all [`DiffByteRecord`]s of a [`CsvByteDiffPositional`] in the order of the records.
+///
+/// After the first error, the iterator is finished.
+pub struct DiffByteRecordsPositional<R> {
+    reader_left: csv::Reader<R>,
+    reader_right: csv::Reader<R>,
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+    is_finished: bool,
+}
+
+impl<R: Read> DiffByteRecordsPositional<R> {
+    fn next_diff_byte_record(&mut self) -> csv::Result<Option<DiffByteRecord>> {
+        loop {
+            self.cancellation_token.check()?;
+            let mut byte_record_left = csv::ByteRecord::new();
+            let mut byte_record_right = csv::ByteRecord::new();
+            let is_read_left = self.reader_left.read_byte_record(&mut byte_record_left)?;
+            let is_read_right = self.reader_right.read_byte_record(&mut byte_record_right)?;
+            match (is_read_left, is_read_right) {
+                (false, false) => return Ok(None),
+                (true, false) => {
+                    return Ok(Some(DiffByteRecord::Delete(line_info(byte_record_left))));
+                }
+                (false, true) => {
+                    return Ok(Some(DiffByteRecord::Add(line_info(byte_record_right))));
+                }
+                (true, true) => {
+                    let field_indices =
+                        modified_field_indices(&byte_record_left, &byte_record_right);
+                    if field_indices.is_empty() {
+                        continue;
+                    }
+                    if let Some(modify) = self.field_comparators.apply(DiffByteRecord::Modify {
+                        delete: line_info(byte_record_left),
+                        add: line_info(byte_record_right),
+                        field_indices,
+                    }) {
+                        return Ok(Some(modify));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for DiffByteRecordsPositional<R> {
+    type Item = csv::Result<DiffByteRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        match self.next_diff_byte_record() {
+            Ok(Some(diff_record)) => Some(Ok(diff_record)),
+            Ok(None) => {
+                self.is_finished = true;
+                None
+            }
+            Err(csv_err) => {
+                self.is_finished = true;
+                Some(Err(csv_err))
+            }
+        }
+    }
+}
+
+fn line_info(byte_record: csv::ByteRecord) -> ByteRecordLineInfo {
+    let line = byte_record.position().map_or(0, |pos| pos.line());
+    ByteRecordLineInfo::new(byte_record, line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn diff(
+        csv_byte_diff_positional: &CsvByteDiffPositional,
+        csv_left: &str,
+        csv_right: &str,
+    ) -> csv::Result<Vec<DiffByteRecord>> {
+        csv_byte_diff_positional
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn diff_positional_reports_remaining_left_records_as_deleted() -> csv::Result<()> {
+        let csv_left = "\
+            id,name\n\
+            1,lemon\n\
+            2,strawberry\n\
+            3,cherry";
+        let csv_right = "\
+            id,name\n\
+            1,lime";
+
+        let diff_byte_records = diff(&CsvByteDiffPositional::new(), csv_left, csv_right)?;
+
+        assert_eq!(
+            diff_byte_records,
+            vec![
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 2),
+                    add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lime"]), 2),
+                    field_indices: vec![1],
+                },
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "strawberry"]),
+                    3
+                )),
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "cherry"]),
+                    4
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diff_positional_with_ignored_column_and_equal_csvs() -> csv::Result<()> {
+        let csv_left = "\
+            time,message\n\
+            10:00,started\n\
+            10:01,stopped";
+        let csv_right = "\
+            time,message\n\
+            11:00,started\n\
+            11:01,stopped";
+
+        let diff_byte_records = diff(
+            &CsvByteDiffPositionalBuilder::new()
+                .ignore_columns_for_modify([0])
+                .build(),
+            csv_left,
+            csv_right,
+        )?;
+        assert_eq!(diff_byte_records, vec![]);
+
+        let diff_byte_records = diff(&CsvByteDiffPositional::new(), csv_left, csv_left)?;
+        assert_eq!(diff_byte_records, vec![]);
+        Ok(())
+    }
+}
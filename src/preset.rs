@@ -0,0 +1,50 @@
+use crate::csv_diff::DEFAULT_CHANNEL_CAPACITY;
+use crate::flush_threshold::FlushThreshold;
+
+/// A configuration of the internals of [`CsvByteDiff`](crate::csv_diff::CsvByteDiff), that are tuned together
+/// for either speed or memory usage (see [`CsvByteDiffBuilder::preset`](crate::csv_diff::CsvByteDiffBuilder::preset)).
+///
+/// A preset sets
+/// - the [`channel_capacity`](crate::csv_diff::CsvByteDiffBuilder::channel_capacity),
+/// - the [`flush_threshold`](crate::csv_diff::CsvByteDiffBuilder::flush_threshold) and
+/// - the number of records, that are kept for recycling: records, that are equal in both CSVs, are handed back to
+///   the hashing tasks, so that their allocations can be reused for reading the next records.
+///
+/// Without a preset, the defaults of each option are used, which lie in between both presets.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Preset {
+    /// Let the hashing tasks read far ahead, flush rarely and recycle every record.
+    ///
+    /// This uses the most memory, especially for CSVs, whose matching records are far apart.
+    Fast,
+    /// Let the hashing tasks read only a little ahead, flush often and keep only a few records for recycling.
+    ///
+    /// This is slower, because the hashing tasks are blocked more often and every flush scans all records,
+    /// that are still unmatched. To bound the memory usage for CSVs, that are very different, additionally set a
+    /// [`memory_budget`](crate::csv_diff::CsvByteDiffBuilder::memory_budget).
+    LowMemory,
+}
+
+impl Preset {
+    pub(crate) fn channel_capacity(self) -> usize {
+        match self {
+            Self::Fast => 10 * DEFAULT_CHANNEL_CAPACITY,
+            Self::LowMemory => DEFAULT_CHANNEL_CAPACITY / 10,
+        }
+    }
+
+    pub(crate) fn flush_threshold(self) -> FlushThreshold {
+        match self {
+            Self::Fast => FlushThreshold::FractionOfRows(0.1),
+            Self::LowMemory => FlushThreshold::Fixed(1_000),
+        }
+    }
+
+    /// The capacity of the channel of recycled records (`None` is unbounded).
+    pub(crate) fn recycle_capacity(self) -> Option<usize> {
+        match self {
+            Self::Fast => None,
+            Self::LowMemory => Some(1_000),
+        }
+    }
+}
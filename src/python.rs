@@ -0,0 +1,229 @@
+//! Python bindings (the `csv_diff_py` module), available with the `python` feature.
+//!
+//! Build the extension module with [maturin](https://www.maturin.rs/) (`maturin develop --release`) and use it like this:
+//! ```python
+//! import csv_diff_py
+//!
+//! for record in csv_diff_py.diff("left.csv", "right.csv", key_columns=[0]):
+//!     print(record.kind, record.line_left, record.line_right, record.record_left, record.record_right)
+//! ```
+//! The records are produced by the streaming engine ([`CsvByteDiff`]) while Python iterates over them,
+//! so the memory usage stays bounded, no matter how big the CSVs are.
+
+// the expansion of `#[pyfunction]` converts the returned `PyErr` into itself
+#![allow(clippy::useless_conversion)]
+
+use crate::csv::Csv;
+use crate::csv_diff::{CsvByteDiff, CsvByteDiffBuilder};
+use crate::csv_hash_task_spawner::CsvHashTaskSpawnerRayon;
+use crate::diff_result::DiffByteRecordsIterator;
+use crate::diff_row::{DiffByteRecord, DiffRecord, StringRecordLineInfo};
+use crate::error_code::ErrorCode;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use std::fs::File;
+use std::path::PathBuf;
+
+// the expansion of `create_exception!` checks pyo3's own `gil-refs` feature
+#[allow(unexpected_cfgs)]
+mod exceptions {
+    use super::*;
+
+    create_exception!(
+        csv_diff_py,
+        CsvDiffError,
+        PyException,
+        "Raised, when a CSV can't be read or compared; the message ends with the stable error code."
+    );
+}
+use exceptions::CsvDiffError;
+
+/// A single difference between the left and the right CSV.
+///
+/// `kind` is one of `"add"`, `"delete"`, `"modify"` or `"move"`. An added record only has the right side,
+/// a deleted record only the left side and a modified record has both sides, together with the indices
+/// of the fields, that differ. A moved record has the same record on both sides, but different lines. Fields, that are not valid UTF-8, are converted lossily.
+#[pyclass(name = "DiffRecord", module = "csv_diff_py", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyDiffRecord {
+    kind: &'static str,
+    line_left: Option<u64>,
+    line_right: Option<u64>,
+    record_left: Option<Vec<String>>,
+    record_right: Option<Vec<String>>,
+    field_indices: Vec<usize>,
+}
+
+#[pymethods]
+impl PyDiffRecord {
+    fn __repr__(&self) -> String {
+        format!(
+            "DiffRecord(kind={:?}, line_left={}, line_right={}, record_left={}, record_right={}, field_indices={:?})",
+            self.kind,
+            py_option(self.line_left),
+            py_option(self.line_right),
+            py_option(self.record_left.as_ref().map(|record| format!("{:?}", record))),
+            py_option(self.record_right.as_ref().map(|record| format!("{:?}", record))),
+            self.field_indices,
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.line_left == other.line_left
+            && self.line_right == other.line_right
+            && self.record_left == other.record_left
+            && self.record_right == other.record_right
+            && self.field_indices == other.field_indices
+    }
+}
+
+fn py_option<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "None".to_owned(), |value| value.to_string())
+}
+
+fn record_fields(srli: &StringRecordLineInfo) -> Vec<String> {
+    srli.string_record().iter().map(str::to_owned).collect()
+}
+
+impl From<DiffByteRecord> for PyDiffRecord {
+    fn from(diff_byte_record: DiffByteRecord) -> Self {
+        match DiffRecord::from_diff_byte_record_lossy(diff_byte_record) {
+            DiffRecord::Add(add) => Self {
+                kind: "add",
+                line_left: None,
+                line_right: Some(add.line()),
+                record_left: None,
+                record_right: Some(record_fields(&add)),
+                field_indices: Vec::new(),
+            },
+            DiffRecord::Delete(delete) => Self {
+                kind: "delete",
+                line_left: Some(delete.line()),
+                line_right: None,
+                record_left: Some(record_fields(&delete)),
+                record_right: None,
+                field_indices: Vec::new(),
+            },
+            DiffRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => Self {
+                kind: "modify",
+                line_left: Some(delete.line()),
+                line_right: Some(add.line()),
+                record_left: Some(record_fields(&delete)),
+                record_right: Some(record_fields(&add)),
+                field_indices,
+            },
+            DiffRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => {
+                let fields = record.iter().map(str::to_owned).collect::<Vec<_>>();
+                Self {
+                    kind: "move",
+                    line_left: Some(from_line),
+                    line_right: Some(to_line),
+                    record_left: Some(fields.clone()),
+                    record_right: Some(fields),
+                    field_indices: Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+/// Lazily yields a [`PyDiffRecord`] for every difference, while the CSVs are being compared.
+#[pyclass(name = "DiffIterator", module = "csv_diff_py")]
+pub struct PyDiffIterator {
+    // the thread pool, that parses and hashes the CSVs, must live as long as the iterator
+    _csv_byte_diff: CsvByteDiff<CsvHashTaskSpawnerRayon>,
+    diff_records: DiffByteRecordsIterator,
+}
+
+#[pymethods]
+impl PyDiffIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyDiffRecord>> {
+        let diff_records = &mut slf.diff_records;
+        // waiting for the next record must not block other Python threads
+        match py.allow_threads(|| diff_records.next()) {
+            Some(Ok(diff_byte_record)) => Ok(Some(diff_byte_record.into())),
+            Some(Err(csv_err)) => Err(CsvDiffError::new_err(format!(
+                "{} ({})",
+                csv_err,
+                csv_err.code()
+            ))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Compare the CSV files at `path_a` (left) and `path_b` (right), whose records are identified by the
+/// `key_columns` (indices, starting at `0`), and return an iterator over the differences.
+#[pyfunction]
+#[pyo3(signature = (path_a, path_b, key_columns = vec![0]))]
+fn diff(path_a: PathBuf, path_b: PathBuf, key_columns: Vec<usize>) -> PyResult<PyDiffIterator> {
+    let csv_byte_diff = CsvByteDiffBuilder::new()
+        .primary_key_columns(key_columns)
+        .build()
+        .map_err(|err| PyValueError::new_err(format!("{} ({})", err, err.code())))?;
+    let open = |path: PathBuf| {
+        File::open(&path).map_err(|io_err| {
+            CsvDiffError::new_err(format!(
+                "can't open '{}': {} (CSVDIFF_IO)",
+                path.display(),
+                io_err
+            ))
+        })
+    };
+    let diff_records = csv_byte_diff.diff(
+        Csv::with_reader(open(path_a)?),
+        Csv::with_reader(open(path_b)?),
+    );
+    Ok(PyDiffIterator {
+        _csv_byte_diff: csv_byte_diff,
+        diff_records,
+    })
+}
+
+#[pymodule]
+fn csv_diff_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(diff, m)?)?;
+    m.add_class::<PyDiffRecord>()?;
+    m.add_class::<PyDiffIterator>()?;
+    m.add("CsvDiffError", m.py().get_type_bound::<CsvDiffError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn modify_is_converted_into_diff_record_with_both_sides() {
+        let diff_record = PyDiffRecord::from(DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "lemon"]), 2),
+            add: ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec![&b"1"[..], &b"lem\xFFon"[..]]),
+                3,
+            ),
+            field_indices: vec![1],
+        });
+
+        assert_eq!(
+            diff_record.__repr__(),
+            "DiffRecord(kind=\"modify\", line_left=2, line_right=3, record_left=[\"1\", \"lemon\"], \
+             record_right=[\"1\", \"lem\u{FFFD}on\"], field_indices=[1])"
+        );
+    }
+}
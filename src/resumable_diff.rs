@@ -0,0 +1,625 @@
+use crate::cancellation::CancellationToken;
+use crate::csv::Csv;
+use crate::csv_hasher::CsvHasherExt;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::empty_csv::CsvSide;
+use crate::error_code::ErrorCode;
+use crate::field_comparator::FieldComparators;
+use crate::simd::modified_field_indices;
+use crate::spill;
+use ahash::AHashMap as HashMap;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The default number of records, after which a [`DiffByteRecordsResumable`] writes its [`Checkpoint`].
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1_000_000;
+
+/// Identifies the file format of a checkpoint (followed by its version).
+const CHECKPOINT_MAGIC: &[u8; 8] = b"CSVDIFFC";
+const CHECKPOINT_VERSION: u8 = 1;
+
+/// The file, to which a [`DiffByteRecordsResumable`] writes its state, so that a comparison can be resumed
+/// after a crash (see [`CsvByteDiff::diff_resumable`](crate::csv_diff::CsvByteDiff::diff_resumable)).
+///
+/// A checkpoint holds the read positions of both CSVs and all records, that have not been matched yet.
+/// It is written to a temporary file next to `path` first and then renamed, so that a crash while writing
+/// never leaves a partial checkpoint behind.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether a checkpoint has been written, from which the next comparison resumes.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Remove the checkpoint (if it exists), so that the next comparison starts from the beginning.
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut tmp_path = OsString::from(self.path.as_os_str());
+        tmp_path.push(".tmp");
+        tmp_path.into()
+    }
+
+    fn write(
+        &self,
+        primary_key_columns: &[usize],
+        csv_lens: (u64, u64),
+        left: &ResumableCsvState,
+        right: &ResumableCsvState,
+    ) -> io::Result<()> {
+        let tmp_path = self.tmp_path();
+        let mut wtr = BufWriter::new(File::create(&tmp_path)?);
+        wtr.write_all(CHECKPOINT_MAGIC)?;
+        wtr.write_all(&[CHECKPOINT_VERSION])?;
+        wtr.write_all(&(primary_key_columns.len() as u64).to_le_bytes())?;
+        for &column in primary_key_columns {
+            wtr.write_all(&(column as u64).to_le_bytes())?;
+        }
+        wtr.write_all(&csv_lens.0.to_le_bytes())?;
+        wtr.write_all(&csv_lens.1.to_le_bytes())?;
+        left.write(&mut wtr)?;
+        right.write(&mut wtr)?;
+        wtr.into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        fs::rename(tmp_path, &self.path)
+    }
+
+    /// Read the state of both CSVs, if there is a checkpoint.
+    ///
+    /// The checkpoint must have been written for the same primary key columns and for CSVs of the same length, since
+    /// its positions and unmatched records would be meaningless otherwise.
+    fn read(
+        &self,
+        primary_key_columns: &[usize],
+        csv_lens: (u64, u64),
+    ) -> csv::Result<Option<(ResumableCsvState, ResumableCsvState)>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut rdr = BufReader::new(file);
+        let invalid_checkpoint_err = || InvalidCheckpointError::new(self.path.clone());
+        let mut magic_and_version = [0; 9];
+        rdr.read_exact(&mut magic_and_version)
+            .map_err(|_| invalid_checkpoint_err())?;
+        if &magic_and_version[..8] != CHECKPOINT_MAGIC || magic_and_version[8] != CHECKPOINT_VERSION
+        {
+            return Err(invalid_checkpoint_err().into());
+        }
+        // a truncated checkpoint is just as invalid as a foreign file
+        let map_short_read = |err: io::Error| -> csv::Error {
+            match err.kind() {
+                io::ErrorKind::UnexpectedEof => invalid_checkpoint_err().into(),
+                _ => err.into(),
+            }
+        };
+        let num_of_columns = spill::read_u64(&mut rdr).map_err(map_short_read)?;
+        let columns = (0..num_of_columns)
+            .map(|_| spill::read_u64(&mut rdr).map(|column| column as usize))
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(map_short_read)?;
+        let csv_lens_written = (
+            spill::read_u64(&mut rdr).map_err(map_short_read)?,
+            spill::read_u64(&mut rdr).map_err(map_short_read)?,
+        );
+        if columns != primary_key_columns || csv_lens_written != csv_lens {
+            return Err(invalid_checkpoint_err().into());
+        }
+        Ok(Some((
+            ResumableCsvState::read(&mut rdr).map_err(map_short_read)?,
+            ResumableCsvState::read(&mut rdr).map_err(map_short_read)?,
+        )))
+    }
+}
+
+/// The part of the state of one CSV, that is written to a [`Checkpoint`].
+#[derive(Debug)]
+struct ResumableCsvState {
+    position: csv::Position,
+    is_exhausted: bool,
+    unmatched: HashMap<u128, (u128, csv::ByteRecord)>,
+}
+
+impl Default for ResumableCsvState {
+    fn default() -> Self {
+        Self {
+            position: csv::Position::new(),
+            is_exhausted: false,
+            unmatched: HashMap::new(),
+        }
+    }
+}
+
+impl ResumableCsvState {
+    fn write<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        wtr.write_all(&[u8::from(self.is_exhausted)])?;
+        for pos in [
+            self.position.byte(),
+            self.position.line(),
+            self.position.record(),
+        ] {
+            wtr.write_all(&pos.to_le_bytes())?;
+        }
+        wtr.write_all(&(self.unmatched.len() as u64).to_le_bytes())?;
+        for (&key, (record_hash, byte_record)) in &self.unmatched {
+            spill::write_entry(wtr, key, *record_hash, byte_record)?;
+        }
+        Ok(())
+    }
+
+    fn read<R: Read>(rdr: &mut R) -> io::Result<Self> {
+        let mut is_exhausted = [0];
+        rdr.read_exact(&mut is_exhausted)?;
+        let mut position = csv::Position::new();
+        position
+            .set_byte(spill::read_u64(rdr)?)
+            .set_line(spill::read_u64(rdr)?)
+            .set_record(spill::read_u64(rdr)?);
+        let num_of_unmatched = spill::read_u64(rdr)?;
+        // not preallocated, since the number is read from the file
+        let mut unmatched = HashMap::new();
+        for _ in 0..num_of_unmatched {
+            let key = spill::read_u128(rdr)?;
+            let (key, record_hash, byte_record) = spill::read_entry(rdr, key)?;
+            unmatched.insert(key, (record_hash, byte_record));
+        }
+        Ok(Self {
+            position,
+            is_exhausted: is_exhausted[0] != 0,
+            unmatched,
+        })
+    }
+}
+
+/// One side of a [`DiffByteRecordsResumable`]: its reader and the records, that have not been matched yet.
+struct ResumableCsv<R> {
+    reader: csv::Reader<R>,
+    state: ResumableCsvState,
+}
+
+impl<R: Read + Seek> ResumableCsv<R> {
+    /// Continue reading at the position of `state`, if the CSV has been read before.
+    fn resume(mut reader: csv::Reader<R>, state: Option<ResumableCsvState>) -> csv::Result<Self> {
+        match state {
+            Some(state) => {
+                if !state.is_exhausted {
+                    reader.seek(state.position.clone())?;
+                }
+                Ok(Self { reader, state })
+            }
+            None => Ok(Self {
+                reader,
+                state: ResumableCsvState::default(),
+            }),
+        }
+    }
+
+    /// Read the next record, unless the CSV is exhausted.
+    fn read(&mut self) -> csv::Result<Option<csv::ByteRecord>> {
+        if self.state.is_exhausted {
+            return Ok(None);
+        }
+        let mut byte_record = csv::ByteRecord::new();
+        if self.reader.read_byte_record(&mut byte_record)? {
+            self.state.position = self.reader.position().clone();
+            Ok(Some(byte_record))
+        } else {
+            self.state.is_exhausted = true;
+            Ok(None)
+        }
+    }
+}
+
+/// Emits all [`DiffByteRecord`]s of a comparison, that can be resumed from a [`Checkpoint`]
+/// (see [`CsvByteDiff::diff_resumable`](crate::csv_diff::CsvByteDiff::diff_resumable)).
+///
+/// Both CSVs are read alternately on the thread, that advances the iterator. Every
+/// [`checkpoint_interval`](crate::csv_diff::CsvByteDiffBuilder::checkpoint_interval) records, the state of the
+/// comparison is written to the checkpoint; it can also be written at any time with [`checkpoint`](Self::checkpoint).
+/// Differences, that have been emitted after the last checkpoint, are emitted again, when the comparison is resumed.
+/// When the iterator is exhausted, the checkpoint is removed.
+///
+/// After the first error, the iterator is finished.
+pub struct DiffByteRecordsResumable<R> {
+    left: ResumableCsv<R>,
+    right: ResumableCsv<R>,
+    primary_key_columns: Vec<usize>,
+    csv_lens: (u64, u64),
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+    checkpoint: Checkpoint,
+    checkpoint_interval: u64,
+    records_since_checkpoint: u64,
+    is_left_next: bool,
+    unmatched_in_order: Option<(Vec<u128>, Vec<u128>)>,
+    is_finished: bool,
+}
+
+impl<R: Read + Seek> DiffByteRecordsResumable<R> {
+    /// Resume the comparison of `csv_left` and `csv_right` from `checkpoint` or start it, if there is no checkpoint.
+    ///
+    /// The primary key is given by `primary_key_columns`, by all columns except `primary_key_excluded_columns`
+    /// or by the whole record, if both are empty.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        csv_left: Csv<R>,
+        csv_right: Csv<R>,
+        primary_key_columns: &HashSet<usize>,
+        primary_key_excluded_columns: Option<&HashSet<usize>>,
+        field_comparators: FieldComparators,
+        cancellation_token: CancellationToken,
+        checkpoint: Checkpoint,
+        checkpoint_interval: u64,
+    ) -> csv::Result<Self> {
+        let mut reader_left = csv_left.into_csv_reader();
+        let mut reader_right = csv_right.into_csv_reader();
+        let csv_lens = (csv_len(&mut reader_left)?, csv_len(&mut reader_right)?);
+        let mut primary_key_columns = match primary_key_excluded_columns {
+            Some(excluded_columns) => (0..reader_left.byte_headers()?.len())
+                .filter(|column| !excluded_columns.contains(column))
+                .collect::<Vec<_>>(),
+            None => primary_key_columns.iter().copied().collect(),
+        };
+        primary_key_columns.sort_unstable();
+        let (state_left, state_right) = match checkpoint.read(&primary_key_columns, csv_lens)? {
+            Some((state_left, state_right)) => (Some(state_left), Some(state_right)),
+            None => (None, None),
+        };
+        let mut field_comparators = field_comparators;
+        if reader_left.has_headers() {
+            field_comparators.resolve_headers(Some(reader_left.byte_headers()?));
+        } else {
+            field_comparators.resolve_headers(None);
+        }
+        Ok(Self {
+            left: ResumableCsv::resume(reader_left, state_left)?,
+            right: ResumableCsv::resume(reader_right, state_right)?,
+            primary_key_columns,
+            csv_lens,
+            field_comparators,
+            cancellation_token,
+            checkpoint,
+            checkpoint_interval,
+            records_since_checkpoint: 0,
+            is_left_next: true,
+            unmatched_in_order: None,
+            is_finished: false,
+        })
+    }
+
+    /// Write the current state of the comparison to its [`Checkpoint`].
+    ///
+    /// When a comparison is resumed from this checkpoint, it emits exactly the differences, that have not been emitted
+    /// before this call, e.g. so that the checkpoint can be written together with the output of the differences.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        self.records_since_checkpoint = 0;
+        self.checkpoint.write(
+            &self.primary_key_columns,
+            self.csv_lens,
+            &self.left.state,
+            &self.right.state,
+        )
+    }
+
+    fn hashes_of(&self, byte_record: &csv::ByteRecord) -> (u128, u128) {
+        let record_hash = byte_record.hash_record();
+        let key = if self.primary_key_columns.is_empty() {
+            record_hash
+        } else {
+            byte_record.hash_key_fields(&self.primary_key_columns)
+        };
+        (key, record_hash)
+    }
+
+    fn next_diff_byte_record(&mut self) -> csv::Result<Option<DiffByteRecord>> {
+        if self.unmatched_in_order.is_some() {
+            return Ok(self.next_unmatched());
+        }
+        loop {
+            self.cancellation_token.check()?;
+            if self.checkpoint_interval > 0
+                && self.records_since_checkpoint >= self.checkpoint_interval
+            {
+                self.checkpoint()?;
+            }
+            let side = match (self.left.state.is_exhausted, self.right.state.is_exhausted) {
+                (true, true) => {
+                    self.unmatched_in_order = Some((
+                        keys_in_order(&self.left.state.unmatched),
+                        keys_in_order(&self.right.state.unmatched),
+                    ));
+                    return Ok(self.next_unmatched());
+                }
+                (false, false) if self.is_left_next => CsvSide::Left,
+                (false, true) => CsvSide::Left,
+                _ => CsvSide::Right,
+            };
+            self.is_left_next = side == CsvSide::Right;
+            let byte_record = match side {
+                CsvSide::Left => self.left.read()?,
+                CsvSide::Right => self.right.read()?,
+            };
+            let byte_record = match byte_record {
+                Some(byte_record) => byte_record,
+                None => continue,
+            };
+            self.records_since_checkpoint += 1;
+            let (key, record_hash) = self.hashes_of(&byte_record);
+            let (csv, csv_other) = match side {
+                CsvSide::Left => (&mut self.left, &mut self.right),
+                CsvSide::Right => (&mut self.right, &mut self.left),
+            };
+            let (record_hash_other, byte_record_other) =
+                match csv_other.state.unmatched.remove(&key) {
+                    Some(unmatched) => unmatched,
+                    None => {
+                        csv.state.unmatched.insert(key, (record_hash, byte_record));
+                        continue;
+                    }
+                };
+            if record_hash == record_hash_other {
+                continue;
+            }
+            let (byte_record_left, byte_record_right) = match side {
+                CsvSide::Left => (byte_record, byte_record_other),
+                CsvSide::Right => (byte_record_other, byte_record),
+            };
+            let field_indices = modified_field_indices(&byte_record_left, &byte_record_right);
+            if field_indices.is_empty() {
+                continue;
+            }
+            let modify = DiffByteRecord::Modify {
+                delete: line_info(byte_record_left),
+                add: line_info(byte_record_right),
+                field_indices,
+            };
+            if let Some(modify) = self.field_comparators.apply(modify) {
+                return Ok(Some(self.field_comparators.redact(modify)));
+            }
+        }
+    }
+
+    /// Emit the records, that have not been matched, after both CSVs have been read: first the deleted records
+    /// and then the added records, each in the order of their lines.
+    fn next_unmatched(&mut self) -> Option<DiffByteRecord> {
+        let (keys_left, keys_right) = self.unmatched_in_order.as_mut()?;
+        let diff_record = if let Some(key) = keys_left.pop() {
+            let (_, byte_record) = self.left.state.unmatched.remove(&key)?;
+            DiffByteRecord::Delete(line_info(byte_record))
+        } else {
+            let (_, byte_record) = self.right.state.unmatched.remove(&keys_right.pop()?)?;
+            DiffByteRecord::Add(line_info(byte_record))
+        };
+        Some(self.field_comparators.redact(diff_record))
+    }
+}
+
+impl<R: Read + Seek> Iterator for DiffByteRecordsResumable<R> {
+    type Item = csv::Result<DiffByteRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        match self.next_diff_byte_record() {
+            Ok(Some(diff_record)) => Some(Ok(diff_record)),
+            Ok(None) => {
+                self.is_finished = true;
+                self.checkpoint.remove().err().map(|err| Err(err.into()))
+            }
+            Err(csv_err) => {
+                self.is_finished = true;
+                Some(Err(csv_err))
+            }
+        }
+    }
+}
+
+/// The keys of `unmatched` in the reverse order of the lines of their records, so that they can be popped in order.
+fn keys_in_order(unmatched: &HashMap<u128, (u128, csv::ByteRecord)>) -> Vec<u128> {
+    let mut keys_with_line = unmatched
+        .iter()
+        .map(|(&key, (_, byte_record))| (line_of(byte_record), key))
+        .collect::<Vec<_>>();
+    keys_with_line.sort_unstable_by(|a, b| b.cmp(a));
+    keys_with_line.into_iter().map(|(_, key)| key).collect()
+}
+
+/// The length of the CSV of `reader`, from which nothing must have been read yet.
+fn csv_len<R: Read + Seek>(reader: &mut csv::Reader<R>) -> io::Result<u64> {
+    let csv_len = reader.get_mut().seek(io::SeekFrom::End(0))?;
+    reader.get_mut().seek(io::SeekFrom::Start(0))?;
+    Ok(csv_len)
+}
+
+fn line_info(byte_record: csv::ByteRecord) -> ByteRecordLineInfo {
+    let line = line_of(&byte_record);
+    ByteRecordLineInfo::new(byte_record, line)
+}
+
+fn line_of(byte_record: &csv::ByteRecord) -> u64 {
+    byte_record.position().map_or(0, |pos| pos.line())
+}
+
+/// A [`Checkpoint`] is not a checkpoint of this crate, is truncated or has been written by a comparison of CSVs with another
+/// length or with other primary key columns.
+///
+/// It is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is an `InvalidCheckpointError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("the checkpoint {} is invalid or belongs to a comparison of other CSVs or with other primary key columns", .path.display())]
+pub struct InvalidCheckpointError {
+    path: PathBuf,
+}
+
+impl InvalidCheckpointError {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ErrorCode for InvalidCheckpointError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_INVALID_CHECKPOINT"
+    }
+}
+
+impl From<InvalidCheckpointError> for csv::Error {
+    fn from(err: InvalidCheckpointError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Cursor;
+
+    fn diff_resumable(
+        csv_left: &str,
+        csv_right: &str,
+        checkpoint: &Checkpoint,
+    ) -> csv::Result<DiffByteRecordsResumable<Cursor<Vec<u8>>>> {
+        DiffByteRecordsResumable::new(
+            Csv::with_reader_seek(Cursor::new(csv_left.as_bytes().to_vec())),
+            Csv::with_reader_seek(Cursor::new(csv_right.as_bytes().to_vec())),
+            &std::iter::once(0).collect(),
+            None,
+            FieldComparators::default(),
+            CancellationToken::default(),
+            checkpoint.clone(),
+            0,
+        )
+    }
+
+    #[test]
+    fn resume_from_checkpoint_emits_only_differences_after_it(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let csv_left = std::iter::once("id,value".to_string())
+            .chain((0..1_000).map(|i| format!("{},a", i)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let csv_right = std::iter::once("id,value".to_string())
+            .chain((1..1_000).rev().map(|i| match i % 10 {
+                0 => format!("{},b", i),
+                _ => format!("{},a", i),
+            }))
+            .chain(std::iter::once("1000,a".to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tmp_dir = tempfile::tempdir()?;
+        let checkpoint = Checkpoint::new(tmp_dir.path().join("diff.checkpoint"));
+        let diff_all =
+            diff_resumable(&csv_left, &csv_right, &checkpoint)?.collect::<csv::Result<Vec<_>>>()?;
+
+        let mut diff_crashed = diff_resumable(&csv_left, &csv_right, &checkpoint)?;
+        let mut diff_byte_records = diff_crashed
+            .by_ref()
+            .take(50)
+            .collect::<csv::Result<Vec<_>>>()?;
+        diff_crashed.checkpoint()?;
+        // emitted again after resuming, because it has not been part of the checkpoint
+        diff_crashed.next().transpose()?;
+        drop(diff_crashed);
+        assert!(checkpoint.exists());
+        diff_byte_records.extend(
+            diff_resumable(&csv_left, &csv_right, &checkpoint)?.collect::<csv::Result<Vec<_>>>()?,
+        );
+
+        assert_eq!(diff_all.len(), 99 + 2);
+        assert_eq!(diff_byte_records, diff_all);
+        assert!(!checkpoint.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn resume_from_foreign_file_is_invalid_checkpoint_err() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let tmp_dir = tempfile::tempdir()?;
+        let checkpoint = Checkpoint::new(tmp_dir.path().join("diff.checkpoint"));
+        fs::write(checkpoint.path(), "id,value\n1,a")?;
+
+        let err = diff_resumable("id,value", "id,value", &checkpoint)
+            .err()
+            .expect("an invalid checkpoint");
+
+        assert_eq!(err.code(), "CSVDIFF_INVALID_CHECKPOINT");
+        Ok(())
+    }
+
+    #[test]
+    fn resume_with_changed_csv_is_invalid_checkpoint_err() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let tmp_dir = tempfile::tempdir()?;
+        let checkpoint = Checkpoint::new(tmp_dir.path().join("diff.checkpoint"));
+        diff_resumable("id,value\n1,a", "id,value\n1,a", &checkpoint)?.checkpoint()?;
+
+        let err = diff_resumable("id,value\n1,a", "id,value\n1,a\n2,a", &checkpoint)
+            .err()
+            .expect("an invalid checkpoint");
+
+        assert_eq!(err.code(), "CSVDIFF_INVALID_CHECKPOINT");
+        Ok(())
+    }
+
+    #[test]
+    fn resume_from_truncated_checkpoint_is_invalid_checkpoint_err(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir()?;
+        let checkpoint = Checkpoint::new(tmp_dir.path().join("diff.checkpoint"));
+        let mut state_right = ResumableCsvState::default();
+        state_right
+            .unmatched
+            .insert(3, (30, csv::ByteRecord::from(vec!["3", "a"])));
+        checkpoint.write(
+            &[0],
+            ("id,value".len() as u64, "id,value\n3,a".len() as u64),
+            &ResumableCsvState::default(),
+            &state_right,
+        )?;
+        let checkpoint_bytes = fs::read(checkpoint.path())?;
+        // the last field of the checkpoint is the `a` of the unmatched right record `3,a`
+        let truncated = &checkpoint_bytes[..checkpoint_bytes.len() - 1];
+        let mut huge_field_len = checkpoint_bytes.clone();
+        let len_pos = huge_field_len.len() - 1 - 8;
+        huge_field_len[len_pos..len_pos + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        for checkpoint_bytes in [truncated, &huge_field_len[..]] {
+            fs::write(checkpoint.path(), checkpoint_bytes)?;
+
+            let err = diff_resumable("id,value", "id,value\n3,a", &checkpoint)
+                .err()
+                .expect("an invalid checkpoint");
+
+            assert_eq!(err.code(), "CSVDIFF_INVALID_CHECKPOINT");
+        }
+        Ok(())
+    }
+}
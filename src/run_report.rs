@@ -0,0 +1,435 @@
+use crate::column_order::ColumnOrder;
+use crate::diff_row::DiffByteRecord;
+use crate::empty_csv::{EmptyCsvReason, EmptyCsvSemantics};
+use crate::error_code::ErrorCode;
+use crate::error_policy::SkippedRecord;
+use crate::key_stats::KeyStats;
+use std::num::NonZeroU64;
+use thiserror::Error;
+
+/// Summarizes a single comparison of two CSVs: how many records have been read on each side and
+/// how many differences have been found.
+///
+/// After a run, the report can be checked for consistency with [`internal_errors`](DiffRunReport::internal_errors).
+/// For every record, that has been read, exactly one of the following must be true: it is equal, modified,
+/// added (right side only) or deleted (left side only). Therefore, the following invariants must always hold:
+/// - `added - deleted == rows_right - rows_left`
+/// - `modified + deleted <= rows_left`
+/// - `modified + added <= rows_right`
+///
+/// A violation of any of those hints at a bug or at a run that has not been consumed completely
+/// (e.g. an iterator that has been dropped early or a run that has [timed out](DiffRunReport::timed_out)).
+///
+/// The report of a streaming comparison can be retrieved with [`DiffByteRecordsIterator::run_report`](crate::diff_result::DiffByteRecordsIterator::run_report).
+/// For an eager comparison, it is part of the [`DiffReport`](crate::diff_result::DiffReport::run_report) returned by
+/// [`CsvByteDiffLocal::diff_with_report`](crate::csv_diff::CsvByteDiffLocal::diff_with_report).
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct DiffRunReport {
+    rows_left: u64,
+    rows_right: u64,
+    added: u64,
+    deleted: u64,
+    modified: u64,
+    unchanged: u64,
+    empty_csv_semantics: EmptyCsvSemantics,
+    empty_csv_left: Option<EmptyCsvReason>,
+    empty_csv_right: Option<EmptyCsvReason>,
+    key_stats_left: KeyStats,
+    key_stats_right: KeyStats,
+    column_order_left: Option<ColumnOrder>,
+    column_order_right: Option<ColumnOrder>,
+    skipped_records_left: Vec<SkippedRecord>,
+    skipped_records_right: Vec<SkippedRecord>,
+    timed_out: bool,
+    max_diffs_reached: bool,
+    key_sample: Option<NonZeroU64>,
+}
+
+impl DiffRunReport {
+    /// Create an empty report for CSVs with the given number of records (excluding headers).
+    pub fn new(rows_left: u64, rows_right: u64) -> Self {
+        Self {
+            rows_left,
+            rows_right,
+            ..Default::default()
+        }
+    }
+
+    /// Number of records that have been read from the left CSV (excluding headers).
+    pub fn rows_left(&self) -> u64 {
+        self.rows_left
+    }
+
+    /// Number of records that have been read from the right CSV (excluding headers).
+    pub fn rows_right(&self) -> u64 {
+        self.rows_right
+    }
+
+    /// Number of [`DiffByteRecord::Add`](crate::diff_row::DiffByteRecord::Add)s.
+    pub fn added(&self) -> u64 {
+        self.added
+    }
+
+    /// Number of [`DiffByteRecord::Delete`](crate::diff_row::DiffByteRecord::Delete)s.
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    /// Number of [`DiffByteRecord::Modify`](crate::diff_row::DiffByteRecord::Modify)s.
+    pub fn modified(&self) -> u64 {
+        self.modified
+    }
+
+    /// Number of records, that have been matched by their primary key and are equal in both CSVs
+    /// (including records, whose changes have all been ignored by the field comparators).
+    ///
+    /// Every unchanged record has been read once on each side, so the number is the same for both CSVs.
+    /// It is only available for a streaming comparison, after its iterator has been exhausted; otherwise it is `0`.
+    pub fn unchanged(&self) -> u64 {
+        self.unchanged
+    }
+
+    pub(crate) fn record_unchanged(&mut self) {
+        self.unchanged += 1;
+    }
+
+    pub(crate) fn add_unchanged(&mut self, unchanged: u64) {
+        self.unchanged += unchanged;
+    }
+
+    /// The [`EmptyCsvSemantics`](crate::empty_csv::EmptyCsvSemantics), that have been used for the comparison.
+    pub fn empty_csv_semantics(&self) -> EmptyCsvSemantics {
+        self.empty_csv_semantics
+    }
+
+    pub(crate) fn set_empty_csv_semantics(&mut self, empty_csv_semantics: EmptyCsvSemantics) {
+        self.empty_csv_semantics = empty_csv_semantics;
+    }
+
+    /// Why the left CSV has been treated as completely empty, if it has been (e.g. because it only consists of blank lines).
+    ///
+    /// This is only available for a streaming comparison, after its iterator has been exhausted; otherwise it is `None`.
+    pub fn empty_csv_left(&self) -> Option<EmptyCsvReason> {
+        self.empty_csv_left
+    }
+
+    /// Why the right CSV has been treated as completely empty, if it has been (e.g. because it only consists of blank lines).
+    ///
+    /// This is only available for a streaming comparison, after its iterator has been exhausted; otherwise it is `None`.
+    pub fn empty_csv_right(&self) -> Option<EmptyCsvReason> {
+        self.empty_csv_right
+    }
+
+    pub(crate) fn set_empty_csv_left(&mut self, empty_csv_left: Option<EmptyCsvReason>) {
+        self.empty_csv_left = empty_csv_left;
+    }
+
+    pub(crate) fn set_empty_csv_right(&mut self, empty_csv_right: Option<EmptyCsvReason>) {
+        self.empty_csv_right = empty_csv_right;
+    }
+
+    /// The [`KeyStats`](crate::key_stats::KeyStats) of the left CSV.
+    ///
+    /// They are only available for a streaming comparison, after its iterator has been exhausted; otherwise they are empty.
+    pub fn key_stats_left(&self) -> &KeyStats {
+        &self.key_stats_left
+    }
+
+    /// The [`KeyStats`](crate::key_stats::KeyStats) of the right CSV.
+    ///
+    /// They are only available for a streaming comparison, after its iterator has been exhausted; otherwise they are empty.
+    pub fn key_stats_right(&self) -> &KeyStats {
+        &self.key_stats_right
+    }
+
+    pub(crate) fn set_key_stats_left(&mut self, key_stats_left: KeyStats) {
+        self.key_stats_left = key_stats_left;
+    }
+
+    pub(crate) fn set_key_stats_right(&mut self, key_stats_right: KeyStats) {
+        self.key_stats_right = key_stats_right;
+    }
+
+    /// The order, in which the columns of the left CSV have been compared, if they have been put into
+    /// [canonical order](crate::csv_diff::CsvByteDiffBuilder::canonical_column_order).
+    ///
+    /// This is only available for a streaming comparison, after its iterator has been exhausted; otherwise it is `None`.
+    pub fn column_order_left(&self) -> Option<&ColumnOrder> {
+        self.column_order_left.as_ref()
+    }
+
+    /// The order, in which the columns of the right CSV have been compared, if they have been put into
+    /// [canonical order](crate::csv_diff::CsvByteDiffBuilder::canonical_column_order).
+    ///
+    /// This is only available for a streaming comparison, after its iterator has been exhausted; otherwise it is `None`.
+    pub fn column_order_right(&self) -> Option<&ColumnOrder> {
+        self.column_order_right.as_ref()
+    }
+
+    /// Whether the columns of the left and the right CSV have been in a different order, that has been
+    /// canonicalized before comparing (see [`column_order_left`](DiffRunReport::column_order_left)).
+    pub fn columns_reordered(&self) -> bool {
+        match (&self.column_order_left, &self.column_order_right) {
+            (Some(column_order_left), Some(column_order_right)) => {
+                column_order_left != column_order_right
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn set_column_orders(
+        &mut self,
+        column_order_left: Option<ColumnOrder>,
+        column_order_right: Option<ColumnOrder>,
+    ) {
+        self.column_order_left = column_order_left;
+        self.column_order_right = column_order_right;
+    }
+
+    /// The malformed records of the left CSV, that have been skipped due to
+    /// [`ErrorPolicy::SkipAndReport`](crate::error_policy::ErrorPolicy::SkipAndReport).
+    ///
+    /// They are only available for a streaming comparison, after its iterator has been exhausted; otherwise they are empty.
+    /// Skipped records are not counted in [`rows_left`](DiffRunReport::rows_left).
+    pub fn skipped_records_left(&self) -> &[SkippedRecord] {
+        &self.skipped_records_left
+    }
+
+    /// The malformed records of the right CSV, that have been skipped due to
+    /// [`ErrorPolicy::SkipAndReport`](crate::error_policy::ErrorPolicy::SkipAndReport).
+    ///
+    /// They are only available for a streaming comparison, after its iterator has been exhausted; otherwise they are empty.
+    /// Skipped records are not counted in [`rows_right`](DiffRunReport::rows_right).
+    pub fn skipped_records_right(&self) -> &[SkippedRecord] {
+        &self.skipped_records_right
+    }
+
+    pub(crate) fn set_skipped_records_left(&mut self, skipped_records_left: Vec<SkippedRecord>) {
+        self.skipped_records_left = skipped_records_left;
+    }
+
+    pub(crate) fn set_skipped_records_right(&mut self, skipped_records_right: Vec<SkippedRecord>) {
+        self.skipped_records_right = skipped_records_right;
+    }
+
+    /// Whether the comparison has been stopped, because it has exceeded its
+    /// [`max_duration`](crate::csv_diff::CsvByteDiffBuilder::max_duration).
+    ///
+    /// In this case, the differences are only a partial result and the record count invariants usually don't hold.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    pub(crate) fn set_timed_out(&mut self) {
+        self.timed_out = true;
+    }
+
+    /// Whether the comparison has been stopped, because it has emitted
+    /// [`max_diffs`](crate::csv_diff::CsvByteDiffBuilder::max_diffs) differences.
+    ///
+    /// Like a run, that has [timed out](DiffRunReport::timed_out), the differences are only a partial result then.
+    pub fn max_diffs_reached(&self) -> bool {
+        self.max_diffs_reached
+    }
+
+    pub(crate) fn set_max_diffs_reached(&mut self) {
+        self.max_diffs_reached = true;
+    }
+
+    /// Whether only a sample of the keys has been compared (see
+    /// [`sample_keys`](crate::csv_diff::CsvByteDiffBuilder::sample_keys)), so that the differences are approximate.
+    ///
+    /// All counts of this report (including the rows) then only refer to the records of the sampled keys,
+    /// so the record count invariants still hold.
+    pub fn is_approximate(&self) -> bool {
+        self.key_sample.is_some()
+    }
+
+    /// The `one_in` of [`sample_keys`](crate::csv_diff::CsvByteDiffBuilder::sample_keys), if only a sample
+    /// of the keys has been compared.
+    pub fn key_sample(&self) -> Option<u64> {
+        self.key_sample.map(NonZeroU64::get)
+    }
+
+    pub(crate) fn set_key_sample(&mut self, key_sample: Option<NonZeroU64>) {
+        self.key_sample = key_sample;
+    }
+
+    /// Account for the given difference in this report.
+    pub fn record(&mut self, diff_record: &DiffByteRecord) {
+        match diff_record {
+            DiffByteRecord::Add(..) => self.added += 1,
+            DiffByteRecord::Modify { .. } => self.modified += 1,
+            DiffByteRecord::Delete(..) => self.deleted += 1,
+            // a moved record is equal in both CSVs, so it is accounted for as unchanged
+            DiffByteRecord::Move { .. } => {}
+        }
+    }
+
+    pub(crate) fn record_row_left(&mut self) {
+        self.rows_left += 1;
+    }
+
+    pub(crate) fn record_row_right(&mut self) {
+        self.rows_right += 1;
+    }
+
+    /// Check all record count invariants (see [`DiffRunReport`](DiffRunReport)) and return
+    /// every violation as an internal error.
+    ///
+    /// An empty `Vec` means, that the report is consistent.
+    pub fn internal_errors(&self) -> Vec<RunReportInternalError> {
+        let mut errors = Vec::new();
+        if i128::from(self.added) - i128::from(self.deleted)
+            != i128::from(self.rows_right) - i128::from(self.rows_left)
+        {
+            errors.push(RunReportInternalError::RecordCountMismatch {
+                added: self.added,
+                deleted: self.deleted,
+                rows_left: self.rows_left,
+                rows_right: self.rows_right,
+            });
+        }
+        if self.modified + self.deleted > self.rows_left {
+            errors.push(RunReportInternalError::TooManyDifferencesLeft {
+                modified: self.modified,
+                deleted: self.deleted,
+                rows_left: self.rows_left,
+            });
+        }
+        if self.modified + self.added > self.rows_right {
+            errors.push(RunReportInternalError::TooManyDifferencesRight {
+                modified: self.modified,
+                added: self.added,
+                rows_right: self.rows_right,
+            });
+        }
+        errors
+    }
+
+    /// Return `true`, if all record count invariants hold.
+    pub fn is_consistent(&self) -> bool {
+        self.internal_errors().is_empty()
+    }
+}
+
+impl<'a> Extend<&'a DiffByteRecord> for DiffRunReport {
+    fn extend<T: IntoIterator<Item = &'a DiffByteRecord>>(&mut self, iter: T) {
+        for diff_record in iter {
+            self.record(diff_record);
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum RunReportInternalError {
+    #[error("internal error: added ({added}) - deleted ({deleted}) does not equal rows right ({rows_right}) - rows left ({rows_left})")]
+    RecordCountMismatch {
+        added: u64,
+        deleted: u64,
+        rows_left: u64,
+        rows_right: u64,
+    },
+    #[error("internal error: modified ({modified}) + deleted ({deleted}) exceeds rows left ({rows_left})")]
+    TooManyDifferencesLeft {
+        modified: u64,
+        deleted: u64,
+        rows_left: u64,
+    },
+    #[error(
+        "internal error: modified ({modified}) + added ({added}) exceeds rows right ({rows_right})"
+    )]
+    TooManyDifferencesRight {
+        modified: u64,
+        added: u64,
+        rows_right: u64,
+    },
+}
+
+impl ErrorCode for RunReportInternalError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::RecordCountMismatch { .. } => "CSVDIFF_INTERNAL_RECORD_COUNT_MISMATCH",
+            Self::TooManyDifferencesLeft { .. } => "CSVDIFF_INTERNAL_TOO_MANY_DIFFERENCES_LEFT",
+            Self::TooManyDifferencesRight { .. } => "CSVDIFF_INTERNAL_TOO_MANY_DIFFERENCES_RIGHT",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    fn add() -> DiffByteRecord {
+        DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a", "b", "c"]),
+            2,
+        ))
+    }
+
+    fn delete() -> DiffByteRecord {
+        DiffByteRecord::Delete(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["d", "e", "f"]),
+            3,
+        ))
+    }
+
+    fn modify() -> DiffByteRecord {
+        DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "i"]), 4),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["g", "h", "x"]), 4),
+            field_indices: vec![2],
+        }
+    }
+
+    #[test]
+    fn run_report_added_modified_deleted_is_consistent() {
+        let mut report = DiffRunReport::new(3, 3);
+        report.extend(&[add(), delete(), modify()]);
+
+        assert_eq!(report.added(), 1);
+        assert_eq!(report.deleted(), 1);
+        assert_eq!(report.modified(), 1);
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn run_report_missing_add_is_record_count_mismatch() {
+        let mut report = DiffRunReport::new(2, 3);
+        report.extend(&[modify()]);
+
+        assert_eq!(
+            report.internal_errors(),
+            vec![RunReportInternalError::RecordCountMismatch {
+                added: 0,
+                deleted: 0,
+                rows_left: 2,
+                rows_right: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn run_report_more_differences_than_rows_reports_all_violations() {
+        let mut report = DiffRunReport::new(1, 1);
+        report.extend(&[modify(), modify()]);
+
+        assert_eq!(
+            report.internal_errors(),
+            vec![
+                RunReportInternalError::TooManyDifferencesLeft {
+                    modified: 2,
+                    deleted: 0,
+                    rows_left: 1
+                },
+                RunReportInternalError::TooManyDifferencesRight {
+                    modified: 2,
+                    added: 0,
+                    rows_right: 1
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use crate::csv::{Csv, CsvBuilder};
+use crate::error_code::ErrorCode;
+use std::io::{self, Cursor, Read};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// The default number of bytes, that are requested from S3 at once (8 MiB).
+pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// A client for opening S3 objects as [`S3ObjectReader`]s.
+///
+/// The S3 SDK is asynchronous, so the client brings its own (small) Tokio runtime, on which the requests
+/// are made. It is cheap to clone and can be shared between readers, so that they can be compared
+/// in parallel.
+///
+/// This is only available with the Cargo feature `aws`.
+#[derive(Debug, Clone)]
+pub struct S3Client {
+    runtime: Arc<Runtime>,
+    client: aws_sdk_s3::Client,
+    part_size: u64,
+}
+
+impl S3Client {
+    /// Create a new `S3Client` with the region and credentials of the environment
+    /// (see [`aws_config::load_from_env`]).
+    pub fn from_env() -> io::Result<Self> {
+        let runtime = Self::build_runtime()?;
+        let sdk_config = runtime.block_on(aws_config::load_from_env());
+        Ok(Self::with_runtime(
+            runtime,
+            aws_sdk_s3::Client::new(&sdk_config),
+        ))
+    }
+
+    /// Create a new `S3Client` with the given configuration (e.g. in order to use a custom endpoint).
+    pub fn from_conf(conf: aws_sdk_s3::Config) -> io::Result<Self> {
+        Ok(Self::with_runtime(
+            Self::build_runtime()?,
+            aws_sdk_s3::Client::from_conf(conf),
+        ))
+    }
+
+    /// The number of bytes, that are requested at once with a ranged `GetObject` request
+    /// (default is [`DEFAULT_PART_SIZE`]).
+    ///
+    /// While one part is being parsed, the next one is already downloaded, so at most two parts of an object
+    /// are held in memory at any time.
+    ///
+    /// # Panics
+    /// Panics, if `part_size` is `0`.
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        assert!(part_size > 0, "the part size must be greater than 0");
+        self.part_size = part_size;
+        self
+    }
+
+    /// Open the object `key` in `bucket` for reading.
+    ///
+    /// Only the object's metadata is requested here; its data is downloaded part by part, while it is being read.
+    pub fn open(
+        &self,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<S3ObjectReader, S3InputError> {
+        let (bucket, key) = (bucket.into(), key.into());
+        let head_object = self.runtime.block_on(
+            self.client
+                .head_object()
+                .bucket(bucket.as_str())
+                .key(key.as_str())
+                .send(),
+        );
+        let len = match head_object {
+            Ok(head_object) => head_object.content_length().unwrap_or(0).max(0) as u64,
+            Err(err) => return Err(S3InputError::new(bucket, key, err)),
+        };
+        let mut s3_object_reader = S3ObjectReader {
+            s3_client: self.clone(),
+            bucket,
+            key,
+            len,
+            next_part_start: 0,
+            next_part: None,
+            current_part: Cursor::new(Vec::new()),
+        };
+        s3_object_reader.spawn_next_part();
+        Ok(s3_object_reader)
+    }
+
+    fn build_runtime() -> io::Result<Runtime> {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("csv-diff-s3")
+            .enable_all()
+            .build()
+    }
+
+    fn with_runtime(runtime: Runtime, client: aws_sdk_s3::Client) -> Self {
+        Self {
+            runtime: Arc::new(runtime),
+            client,
+            part_size: DEFAULT_PART_SIZE,
+        }
+    }
+}
+
+/// A reader, that streams an S3 object with ranged `GetObject` requests (see [`S3Client::open`]).
+///
+/// This is only available with the Cargo feature `aws`.
+#[derive(Debug)]
+pub struct S3ObjectReader {
+    s3_client: S3Client,
+    bucket: String,
+    key: String,
+    len: u64,
+    next_part_start: u64,
+    next_part: Option<JoinHandle<Result<Vec<u8>, S3InputError>>>,
+    current_part: Cursor<Vec<u8>>,
+}
+
+impl S3ObjectReader {
+    /// The size of the object in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the object is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn spawn_next_part(&mut self) {
+        if self.next_part_start >= self.len {
+            self.next_part = None;
+            return;
+        }
+        let start = self.next_part_start;
+        let end = (start + self.s3_client.part_size).min(self.len);
+        self.next_part_start = end;
+        let get_object = self
+            .s3_client
+            .client
+            .get_object()
+            .bucket(self.bucket.as_str())
+            .key(self.key.as_str())
+            .range(format!("bytes={}-{}", start, end - 1));
+        let (bucket, key) = (self.bucket.clone(), self.key.clone());
+        self.next_part = Some(self.s3_client.runtime.spawn(async move {
+            let get_object = get_object
+                .send()
+                .await
+                .map_err(|err| S3InputError::new(bucket.as_str(), key.as_str(), err))?;
+            let body = get_object
+                .body
+                .collect()
+                .await
+                .map_err(|err| S3InputError::new(bucket, key, err))?;
+            Ok(body.into_bytes().to_vec())
+        }));
+    }
+}
+
+impl Read for S3ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.current_part.position() as usize >= self.current_part.get_ref().len() {
+            let next_part = match self.next_part.take() {
+                Some(next_part) => next_part,
+                None => return Ok(0),
+            };
+            let part = self
+                .s3_client
+                .runtime
+                .block_on(next_part)
+                .map_err(|join_err| io::Error::new(io::ErrorKind::Other, join_err))?
+                .map_err(|s3_err| io::Error::new(io::ErrorKind::Other, s3_err))?;
+            if part.is_empty() {
+                // the object has been truncated since we requested its size
+                self.next_part_start = self.len;
+            }
+            self.current_part = Cursor::new(part);
+            self.spawn_next_part();
+        }
+        self.current_part.read(buf)
+    }
+}
+
+impl Drop for S3ObjectReader {
+    fn drop(&mut self) {
+        if let Some(next_part) = self.next_part.take() {
+            next_part.abort();
+        }
+    }
+}
+
+impl Csv<S3ObjectReader> {
+    /// Create a new `Csv`, that streams the S3 object `key` in `bucket`,
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    ///
+    /// The object is downloaded part by part with ranged requests (see [`S3Client::part_size`]),
+    /// so large objects are never held in memory as a whole.
+    /// This is only available with the Cargo feature `aws`.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```no_run
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv, s3_input::S3Client};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let s3_client = S3Client::from_env()?;
+
+let num_of_differences = CsvByteDiff::new()?
+    .diff(
+        Csv::with_s3_object(&s3_client, "exports", "2024-01-01/users.csv")?,
+        Csv::with_s3_object(&s3_client, "exports", "2024-01-02/users.csv")?,
+    )
+    .count();
+# let _ = num_of_differences;
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_s3_object(
+        s3_client: &S3Client,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<Self, S3InputError> {
+        CsvBuilder::new().build_with_s3_object(s3_client, bucket, key)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but streams the S3 object `key` in `bucket`
+    /// (see [`Csv::with_s3_object`]).
+    pub fn build_with_s3_object(
+        &self,
+        s3_client: &S3Client,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<Csv<S3ObjectReader>, S3InputError> {
+        Ok(self.build_with_reader(s3_client.open(bucket, key)?))
+    }
+}
+
+/// A request for an S3 object has failed.
+///
+/// When it happens while the object is being read, it is returned as [`csv::Error`] of kind
+/// [`csv::ErrorKind::Io`].
+#[derive(Debug, Error)]
+#[error("the request of the S3 object {key} in bucket {bucket} has failed: {source}")]
+pub struct S3InputError {
+    bucket: String,
+    key: String,
+    #[source]
+    source: Box<dyn std::error::Error + Send + Sync + 'static>,
+}
+
+impl S3InputError {
+    fn new(
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            bucket: bucket.into(),
+            key: key.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// The bucket of the requested object.
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// The key of the requested object.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl ErrorCode for S3InputError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_S3"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    /// Serve `object` for `HEAD` and ranged `GET` requests on a local port and return its endpoint
+    /// and the ranges, that have been requested.
+    fn serve_object(object: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let requested_ranges = Arc::clone(&ranges);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let (mut line, mut request_line, mut range) =
+                    (String::new(), String::new(), None::<(usize, usize)>);
+                reader.read_line(&mut request_line).unwrap();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        let (start, end) = value.trim().split_once('-').unwrap();
+                        range = Some((start.parse().unwrap(), end.parse().unwrap()));
+                        requested_ranges
+                            .lock()
+                            .unwrap()
+                            .push(value.trim().to_owned());
+                    }
+                    line.clear();
+                }
+                let response = if request_line.starts_with("HEAD") {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        object.len()
+                    )
+                } else {
+                    let (start, end) = range.unwrap_or((0, object.len() - 1));
+                    let body = &object[start..=end];
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (endpoint, ranges)
+    }
+
+    fn s3_client(endpoint: String) -> S3Client {
+        let conf = aws_sdk_s3::Config::builder()
+            .behavior_version_latest()
+            .endpoint_url(endpoint)
+            .region(aws_sdk_s3::config::Region::new("us-east-1"))
+            .credentials_provider(aws_sdk_s3::config::Credentials::new(
+                "access-key",
+                "secret-key",
+                None,
+                None,
+                "test",
+            ))
+            .force_path_style(true)
+            .build();
+        S3Client::from_conf(conf).unwrap()
+    }
+
+    #[test]
+    fn s3_object_is_streamed_in_parts() -> Result<(), Box<dyn std::error::Error>> {
+        let (endpoint, ranges) = serve_object("id,name\n1,lemon\n2,cherry");
+        let s3_client = s3_client(endpoint).part_size(10);
+
+        let mut csv_reader =
+            Csv::with_s3_object(&s3_client, "exports", "fruits.csv")?.into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "cherry"])
+            ]
+        );
+        assert_eq!(*ranges.lock().unwrap(), vec!["0-9", "10-19", "20-23"]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_s3_object_is_not_requested() -> Result<(), Box<dyn std::error::Error>> {
+        let (endpoint, ranges) = serve_object("");
+        let s3_client = s3_client(endpoint);
+
+        let mut s3_object_reader = s3_client.open("exports", "empty.csv")?;
+        let mut data = Vec::new();
+        s3_object_reader.read_to_end(&mut data)?;
+
+        assert!(s3_object_reader.is_empty());
+        assert!(data.is_empty());
+        assert!(ranges.lock().unwrap().is_empty());
+        Ok(())
+    }
+}
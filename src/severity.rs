@@ -0,0 +1,256 @@
+use crate::diff_row::DiffByteRecord;
+use std::collections::BTreeMap;
+
+/// How important a single [`DiffByteRecord`](crate::diff_row::DiffByteRecord) is, as decided by
+/// a user-supplied classifier.
+///
+/// Severities are ordered, so that `Severity::Low < Severity::Critical`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Applies a classifier to every [`DiffByteRecord`](crate::diff_row::DiffByteRecord) while it is being
+/// emitted by the underlying iterator, so that the records don't have to be collected first.
+///
+/// This `struct` is created by [`DiffByteRecordsIterator::classify_severity`](crate::diff_result::DiffByteRecordsIterator::classify_severity).
+/// # Example: treat every change in the `amount` column as critical
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::diff_row::DiffByteRecord;
+use csv_diff::severity::Severity;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,amount\n\
+1,lemon,10\n\
+2,strawberry,20";
+let csv_right = "\
+id,name,amount\n\
+1,lemon,11\n\
+2,raspberry,20";
+
+let diff_by_severity = CsvByteDiff::new()?
+    .diff(
+        Csv::with_reader(csv_left.as_bytes()),
+        Csv::with_reader(csv_right.as_bytes()),
+    )
+    .classify_severity(|diff_record| match diff_record {
+        DiffByteRecord::Modify { field_indices, .. } if field_indices.contains(&2) => {
+            Severity::Critical
+        }
+        _ => Severity::Low,
+    })
+    .try_to_diff_byte_records_by_severity()?;
+
+assert_eq!(diff_by_severity.count(Severity::Critical), 1);
+assert_eq!(diff_by_severity.count(Severity::Low), 1);
+assert_eq!(diff_by_severity.max_severity(), Some(Severity::Critical));
+Ok(())
+# }
+```
+"##
+)]
+pub struct SeverityClassifiedIterator<I, F> {
+    inner: I,
+    classifier: F,
+}
+
+impl<I, F> SeverityClassifiedIterator<I, F>
+where
+    I: Iterator<Item = csv::Result<DiffByteRecord>>,
+    F: FnMut(&DiffByteRecord) -> Severity,
+{
+    pub(crate) fn new(inner: I, classifier: F) -> Self {
+        Self { inner, classifier }
+    }
+
+    /// Collect all classified records into [`DiffByteRecordsBySeverity`](DiffByteRecordsBySeverity).
+    ///
+    /// Returns the first error that is emitted by the underlying iterator.
+    pub fn try_to_diff_byte_records_by_severity(self) -> csv::Result<DiffByteRecordsBySeverity> {
+        self.collect()
+    }
+}
+
+impl<I, F> Iterator for SeverityClassifiedIterator<I, F>
+where
+    I: Iterator<Item = csv::Result<DiffByteRecord>>,
+    F: FnMut(&DiffByteRecord) -> Severity,
+{
+    type Item = csv::Result<(Severity, DiffByteRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let classifier = &mut self.classifier;
+        self.inner
+            .next()
+            .map(|res| res.map(|diff_record| (classifier(&diff_record), diff_record)))
+    }
+}
+
+/// Holds [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s grouped by their [`Severity`](Severity).
+///
+/// It can be created with [`DiffByteRecords::classify_severity`](crate::diff_result::DiffByteRecords::classify_severity)
+/// or by collecting a [`SeverityClassifiedIterator`](SeverityClassifiedIterator).
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct DiffByteRecordsBySeverity {
+    inner: BTreeMap<Severity, Vec<DiffByteRecord>>,
+}
+
+impl DiffByteRecordsBySeverity {
+    /// Return all records that have been classified with the given `severity`.
+    pub fn get(&self, severity: Severity) -> &[DiffByteRecord] {
+        self.inner
+            .get(&severity)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Return the number of records that have been classified with the given `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.get(severity).len()
+    }
+
+    /// Return the number of records that have been classified with at least the given `severity`.
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.inner.range(severity..).map(|(_, v)| v.len()).sum()
+    }
+
+    /// Return the highest severity of all records or `None`, if there are no records.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.inner.keys().next_back().copied()
+    }
+
+    /// Return the total number of records over all severities.
+    pub fn len(&self) -> usize {
+        self.inner.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return an iterator over all severities (from highest to lowest) together with their records.
+    pub fn iter(&self) -> impl Iterator<Item = (Severity, &[DiffByteRecord])> {
+        self.inner
+            .iter()
+            .rev()
+            .map(|(severity, records)| (*severity, records.as_slice()))
+    }
+
+    fn push(&mut self, severity: Severity, diff_record: DiffByteRecord) {
+        self.inner.entry(severity).or_default().push(diff_record);
+    }
+}
+
+impl FromIterator<(Severity, DiffByteRecord)> for DiffByteRecordsBySeverity {
+    fn from_iter<T: IntoIterator<Item = (Severity, DiffByteRecord)>>(iter: T) -> Self {
+        let mut by_severity = Self::default();
+        for (severity, diff_record) in iter {
+            by_severity.push(severity, diff_record);
+        }
+        by_severity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_result::DiffByteRecords;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+
+    fn classify_by_amount_col(diff_record: &DiffByteRecord) -> Severity {
+        match diff_record {
+            DiffByteRecord::Modify { field_indices, .. } if field_indices.contains(&2) => {
+                Severity::Critical
+            }
+            DiffByteRecord::Modify { .. } => Severity::Medium,
+            DiffByteRecord::Add(..) | DiffByteRecord::Delete(..) | DiffByteRecord::Move { .. } => {
+                Severity::Low
+            }
+        }
+    }
+
+    fn diff_records() -> DiffByteRecords {
+        DiffByteRecords::from(vec![
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a", "10"]), 2),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["1", "a", "11"]), 2),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "b", "10"]), 3),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["2", "x", "10"]), 3),
+                field_indices: vec![1],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["3", "c", "10"]),
+                4,
+            )),
+        ])
+    }
+
+    #[test]
+    fn classify_severity_groups_records_by_severity() {
+        let diff_records = diff_records();
+        let expected_critical = vec![diff_records.as_slice()[0].clone()];
+        let expected_low = vec![diff_records.as_slice()[2].clone()];
+
+        let by_severity = diff_records.classify_severity(classify_by_amount_col);
+
+        assert_eq!(
+            by_severity.get(Severity::Critical),
+            expected_critical.as_slice()
+        );
+        assert_eq!(by_severity.get(Severity::Low), expected_low.as_slice());
+        assert_eq!(by_severity.count(Severity::Medium), 1);
+        assert_eq!(by_severity.count(Severity::High), 0);
+        assert_eq!(by_severity.count_at_least(Severity::Medium), 2);
+        assert_eq!(by_severity.max_severity(), Some(Severity::Critical));
+        assert_eq!(by_severity.len(), 3);
+    }
+
+    #[test]
+    fn classify_severity_iter_is_ordered_from_highest_to_lowest() {
+        let severities = diff_records()
+            .classify_severity(classify_by_amount_col)
+            .iter()
+            .map(|(severity, _)| severity)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            severities,
+            vec![Severity::Critical, Severity::Medium, Severity::Low]
+        );
+    }
+
+    #[test]
+    fn classify_severity_of_empty_diff_has_no_max_severity() {
+        let by_severity = DiffByteRecords::from(vec![]).classify_severity(classify_by_amount_col);
+
+        assert!(by_severity.is_empty());
+        assert_eq!(by_severity.max_severity(), None);
+    }
+
+    #[test]
+    fn severity_classified_iterator_stops_at_first_error() {
+        let records: Vec<csv::Result<DiffByteRecord>> = vec![
+            Ok(diff_records().as_slice()[0].clone()),
+            Err(csv::Error::from(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "broken",
+            ))),
+        ];
+
+        let res = SeverityClassifiedIterator::new(records.into_iter(), classify_by_amount_col)
+            .try_to_diff_byte_records_by_severity();
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! The hot loops of hashing and comparing records.
+//!
+//! With the Cargo feature `nightly-simd` (which requires a nightly toolchain), they are vectorized
+//! with [`std::simd`]; otherwise the scalar versions are used. Both produce the same hashes and results.
+
+use std::hash::Hasher;
+use xxhash_rust::xxh3::{xxh3_128_with_seed, Xxh3};
+
+/// Hashes the primary key fields of records with a reusable hasher.
+#[derive(Default)]
+pub(crate) struct KeyHasher {
+    hasher: Xxh3,
+    seed: u64,
+}
+
+impl KeyHasher {
+    /// Seed all hashes with `seed` (`0` yields the same hashes as an unseeded hasher).
+    pub(crate) fn with_seed(seed: u64) -> Self {
+        Self {
+            hasher: Xxh3::with_seed(seed),
+            seed,
+        }
+    }
+
+    /// Hash all fields of `byte_record` with the seed of this hasher.
+    #[inline]
+    pub(crate) fn hash_record(&self, byte_record: &csv::ByteRecord) -> u128 {
+        xxh3_128_with_seed(byte_record.as_slice(), self.seed)
+    }
+
+    #[inline]
+    pub(crate) fn hash_key_fields(
+        &mut self,
+        byte_record: &csv::ByteRecord,
+        key_fields_idx: &[usize],
+    ) -> u128 {
+        #[cfg(feature = "nightly-simd")]
+        if let Some(key_fields) = contiguous_key_fields(byte_record, key_fields_idx) {
+            // the one-shot function processes its whole input with SIMD accumulators and yields
+            // the same hash as the streaming hasher, that would have been fed field by field
+            return xxh3_128_with_seed(key_fields, self.seed);
+        }
+        self.hasher.reset();
+        for key_field in key_fields_idx
+            .iter()
+            .filter_map(|k_idx| byte_record.get(*k_idx))
+        {
+            self.hasher.write(key_field);
+        }
+        self.hasher.digest128()
+    }
+}
+
+/// Return the bytes of all key fields, if they are adjacent columns in ascending order (e.g. `[2, 3, 4]`).
+///
+/// The fields of a `ByteRecord` are stored without separators, so they form a single slice.
+#[cfg(feature = "nightly-simd")]
+#[inline]
+fn contiguous_key_fields<'a>(
+    byte_record: &'a csv::ByteRecord,
+    key_fields_idx: &[usize],
+) -> Option<&'a [u8]> {
+    let (&first, &last) = (key_fields_idx.first()?, key_fields_idx.last()?);
+    if last.checked_sub(first)? + 1 != key_fields_idx.len()
+        || key_fields_idx.windows(2).any(|w| w[1] != w[0] + 1)
+    {
+        return None;
+    }
+    let start = byte_record.range(first)?.start;
+    let end = byte_record.range(last)?.end;
+    byte_record.as_slice().get(start..end)
+}
+
+/// Whether `field_left` and `field_right` are equal byte-wise.
+#[cfg(not(feature = "nightly-simd"))]
+#[inline]
+pub(crate) fn fields_eq(field_left: &[u8], field_right: &[u8]) -> bool {
+    field_left == field_right
+}
+
+/// Whether `field_left` and `field_right` are equal byte-wise.
+///
+/// Fields are usually short, so comparing them 32 bytes at a time avoids the call overhead of `memcmp`.
+#[cfg(feature = "nightly-simd")]
+#[inline]
+pub(crate) fn fields_eq(field_left: &[u8], field_right: &[u8]) -> bool {
+    use std::simd::{cmp::SimdPartialEq, u8x32};
+
+    const LANES: usize = 32;
+    if field_left.len() != field_right.len() {
+        return false;
+    }
+    let mut chunks_left = field_left.chunks_exact(LANES);
+    let mut chunks_right = field_right.chunks_exact(LANES);
+    for (chunk_left, chunk_right) in chunks_left.by_ref().zip(chunks_right.by_ref()) {
+        if u8x32::from_slice(chunk_left)
+            .simd_ne(u8x32::from_slice(chunk_right))
+            .any()
+        {
+            return false;
+        }
+    }
+    let (rest_left, rest_right) = (chunks_left.remainder(), chunks_right.remainder());
+    u8x32::load_or_default(rest_left)
+        .simd_eq(u8x32::load_or_default(rest_right))
+        .all()
+}
+
+/// The indices of all fields, that are different in `byte_record_left` and `byte_record_right`
+/// (when the records have a different number of fields, the shorter one is padded with empty fields).
+#[inline]
+pub(crate) fn modified_field_indices(
+    byte_record_left: &csv::ByteRecord,
+    byte_record_right: &csv::ByteRecord,
+) -> Vec<usize> {
+    let num_fields = byte_record_left.len().max(byte_record_right.len());
+    (0..num_fields)
+        .filter(|&idx| {
+            let field_left = byte_record_left.get(idx).unwrap_or_default();
+            let field_right = byte_record_right.get(idx).unwrap_or_default();
+            !fields_eq(field_left, field_right)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_hasher::CsvHasherExt;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hash_key_fields_is_the_same_as_field_by_field() {
+        let byte_record = csv::ByteRecord::from(vec!["1", "lemon", "fruit", "", "a long field"]);
+        let mut key_hasher = KeyHasher::default();
+
+        for key_fields_idx in [vec![0], vec![1, 2], vec![2, 3, 4], vec![0, 2], vec![4, 3]] {
+            assert_eq!(
+                key_hasher.hash_key_fields(&byte_record, &key_fields_idx),
+                byte_record.hash_key_fields(&key_fields_idx),
+                "{:?}",
+                key_fields_idx
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_hashes_differ_from_unseeded_hashes() {
+        let byte_record = csv::ByteRecord::from(vec!["1", "lemon", "fruit"]);
+        let mut key_hasher_unseeded = KeyHasher::with_seed(0);
+        let mut key_hasher_seeded = KeyHasher::with_seed(42);
+
+        assert_eq!(
+            key_hasher_unseeded.hash_key_fields(&byte_record, &[0, 1]),
+            byte_record.hash_key_fields(&[0, 1])
+        );
+        assert_eq!(
+            key_hasher_unseeded.hash_record(&byte_record),
+            byte_record.hash_record()
+        );
+        assert_ne!(
+            key_hasher_seeded.hash_key_fields(&byte_record, &[0, 1]),
+            key_hasher_unseeded.hash_key_fields(&byte_record, &[0, 1])
+        );
+        assert_ne!(
+            key_hasher_seeded.hash_record(&byte_record),
+            key_hasher_unseeded.hash_record(&byte_record)
+        );
+    }
+
+    #[test]
+    fn modified_field_indices_of_long_and_short_fields() {
+        let long = "x".repeat(70);
+        let long_changed = format!("{}y", "x".repeat(69));
+        let byte_record_left =
+            csv::ByteRecord::from(vec!["1", long.as_str(), "abc", long.as_str()]);
+        let byte_record_right =
+            csv::ByteRecord::from(vec!["1", long_changed.as_str(), "abd", long.as_str()]);
+
+        assert_eq!(
+            modified_field_indices(&byte_record_left, &byte_record_right),
+            vec![1, 2]
+        );
+    }
+}
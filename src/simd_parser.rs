@@ -0,0 +1,370 @@
+use memchr::{memchr, memchr3};
+use std::io::{self, Read};
+
+/// The capacity (in bytes) of the buffer of a [`SimdRecordReader`], if none has been chosen.
+const DEFAULT_BUFFER_CAPACITY: usize = 64 * (1 << 10);
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How a CSV is tokenized by a [`SimdRecordReader`] (see [`CsvBuilder::simd_parser`](crate::csv::CsvBuilder::simd_parser)).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SimdParser {
+    delimiter: u8,
+    quote: u8,
+    flexible: bool,
+    buffer_capacity: Option<usize>,
+}
+
+impl SimdParser {
+    pub(crate) fn new(
+        delimiter: u8,
+        quote: u8,
+        flexible: bool,
+        buffer_capacity: Option<usize>,
+    ) -> Self {
+        Self {
+            delimiter,
+            quote,
+            flexible,
+            buffer_capacity,
+        }
+    }
+
+    pub(crate) fn record_reader(self) -> SimdRecordReader {
+        SimdRecordReader {
+            parser: self,
+            buffer: vec![
+                0;
+                self.buffer_capacity
+                    .unwrap_or(DEFAULT_BUFFER_CAPACITY)
+                    .max(1)
+            ],
+            start: 0,
+            end: 0,
+            is_eof: false,
+            is_bom_checked: false,
+            num_uncounted_bytes: 0,
+            position: csv::Position::new(),
+            num_fields: None,
+            field: Vec::new(),
+        }
+    }
+}
+
+/// The outcome of tokenizing the buffered data of a [`SimdRecordReader`].
+enum Parsed {
+    /// A record has been read, which ends after the given number of bytes.
+    Record(usize),
+    /// There are no more records; the given number of bytes (blank lines) has been skipped.
+    End(usize),
+    /// More data needs to be buffered, before the record can be read.
+    Incomplete,
+}
+
+/// Reads [`csv::ByteRecord`]s like [`csv::Reader::read_byte_record`], but finds the delimiters, quotes and line terminators
+/// with the vectorized search of [`memchr`], instead of looking at one byte after the other.
+///
+/// The records have the same positions as the ones of the `csv` crate (which are the positions of the reader
+/// _before_ a record has been read), so records can be re-read by seeking a [`csv::Reader`] of the same data.
+/// Like with the `csv` crate, a byte order mark at the start and blank lines are skipped, and `\r`, `\n` and `\r\n`
+/// terminate records.
+///
+/// Only the dialect of [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180) with a custom delimiter and quote character
+/// is supported, i.e. quotes within quoted fields are escaped by doubling them. The `csv` crate's error kinds can't be
+/// created outside of it, so a record with a different number of fields than the first record (unless the reader is
+/// flexible) is reported as an error of kind [`io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub(crate) struct SimdRecordReader {
+    parser: SimdParser,
+    buffer: Vec<u8>,
+    // the data in `buffer[start..end]` has not been tokenized yet
+    start: usize,
+    end: usize,
+    is_eof: bool,
+    is_bom_checked: bool,
+    // like with the `csv` crate, the byte order mark only counts towards the position after the first record
+    num_uncounted_bytes: usize,
+    position: csv::Position,
+    num_fields: Option<usize>,
+    field: Vec<u8>,
+}
+
+impl SimdRecordReader {
+    /// The position of the next record, that is read.
+    pub(crate) fn position(&self) -> &csv::Position {
+        &self.position
+    }
+
+    /// Read the next record from `reader` into `csv_record`. Return `false`, if there are no more records.
+    pub(crate) fn read_byte_record<R: Read>(
+        &mut self,
+        reader: &mut R,
+        csv_record: &mut csv::ByteRecord,
+    ) -> csv::Result<bool> {
+        loop {
+            if !self.is_bom_checked {
+                if self.end - self.start < UTF8_BOM.len() && !self.is_eof {
+                    self.fill_buffer(reader)?;
+                    continue;
+                }
+                if self.buffer[self.start..self.end].starts_with(UTF8_BOM) {
+                    self.start += UTF8_BOM.len();
+                    self.num_uncounted_bytes = UTF8_BOM.len();
+                }
+                self.is_bom_checked = true;
+            }
+            match self.parse(csv_record) {
+                Parsed::Record(len) => {
+                    csv_record.set_position(Some(self.position.clone()));
+                    self.consume(len);
+                    self.position
+                        .set_record(self.position.record().saturating_add(1));
+                    return self.check_num_fields(csv_record).map(|_| true);
+                }
+                Parsed::End(len) => {
+                    self.consume(len);
+                    return Ok(false);
+                }
+                Parsed::Incomplete => self.fill_buffer(reader)?,
+            }
+        }
+    }
+
+    fn check_num_fields(&mut self, csv_record: &csv::ByteRecord) -> csv::Result<()> {
+        match self.num_fields {
+            Some(num_fields) if !self.parser.flexible && csv_record.len() != num_fields => {
+                let pos = csv_record.position().expect("a record position");
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "record {} (line: {}, byte: {}): found record with {} fields, but the previous record has {} fields",
+                        pos.record(),
+                        pos.line(),
+                        pos.byte(),
+                        csv_record.len(),
+                        num_fields
+                    ),
+                )
+                .into())
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.num_fields = Some(csv_record.len());
+                Ok(())
+            }
+        }
+    }
+
+    /// Advance past the first `len` bytes of the data, that has not been tokenized yet.
+    fn consume(&mut self, len: usize) {
+        let data = &self.buffer[self.start..self.start + len];
+        let num_lines = data.iter().filter(|&&byte| byte == b'\n').count() as u64;
+        let (byte, line) = (self.position.byte(), self.position.line());
+        self.position
+            .set_byte(byte + (std::mem::take(&mut self.num_uncounted_bytes) + len) as u64)
+            .set_line(line + num_lines);
+        self.start += len;
+    }
+
+    /// Read more data into the buffer, after moving the data, that has not been tokenized yet, to its start.
+    /// The buffer grows, if a single record doesn't fit into it.
+    fn fill_buffer<R: Read>(&mut self, reader: &mut R) -> io::Result<()> {
+        self.buffer.copy_within(self.start..self.end, 0);
+        self.end -= self.start;
+        self.start = 0;
+        if self.end == self.buffer.len() {
+            self.buffer.resize(self.buffer.len() * 2, 0);
+        }
+        loop {
+            match reader.read(&mut self.buffer[self.end..]) {
+                Ok(0) => {
+                    self.is_eof = true;
+                    return Ok(());
+                }
+                Ok(len) => {
+                    self.end += len;
+                    return Ok(());
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Tokenize the next record of the data, that has not been tokenized yet, into `csv_record`.
+    fn parse(&mut self, csv_record: &mut csv::ByteRecord) -> Parsed {
+        let SimdParser {
+            delimiter, quote, ..
+        } = self.parser;
+        let data = &self.buffer[self.start..self.end];
+        let is_eof = self.is_eof;
+        let field = &mut self.field;
+        let len = data.len();
+        // blank lines are skipped, but belong to the next record
+        let mut i = data
+            .iter()
+            .position(|&byte| byte != b'\r' && byte != b'\n')
+            .unwrap_or(len);
+        if i == len {
+            return if is_eof {
+                Parsed::End(len)
+            } else {
+                Parsed::Incomplete
+            };
+        }
+        csv_record.clear();
+        loop {
+            if data[i] == quote {
+                field.clear();
+                let mut j = i + 1;
+                loop {
+                    match memchr(quote, &data[j..]) {
+                        Some(quote_idx) => {
+                            field.extend_from_slice(&data[j..j + quote_idx]);
+                            j += quote_idx + 1;
+                            if j == len && !is_eof {
+                                return Parsed::Incomplete;
+                            }
+                            if j < len && data[j] == quote {
+                                field.push(quote);
+                                j += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None if is_eof => {
+                            // an unterminated quoted field extends to the end of the data
+                            field.extend_from_slice(&data[j..]);
+                            j = len;
+                            break;
+                        }
+                        None => return Parsed::Incomplete,
+                    }
+                }
+                // like with the `csv` crate, data after the closing quote belongs to the field
+                match memchr3(delimiter, b'\n', b'\r', &data[j..]) {
+                    Some(end_idx) => {
+                        field.extend_from_slice(&data[j..j + end_idx]);
+                        i = j + end_idx;
+                    }
+                    None if is_eof => {
+                        field.extend_from_slice(&data[j..]);
+                        csv_record.push_field(field);
+                        return Parsed::Record(len);
+                    }
+                    None => return Parsed::Incomplete,
+                }
+                csv_record.push_field(field);
+            } else {
+                match memchr3(delimiter, b'\n', b'\r', &data[i..]) {
+                    Some(end_idx) => {
+                        csv_record.push_field(&data[i..i + end_idx]);
+                        i += end_idx;
+                    }
+                    None if is_eof => {
+                        csv_record.push_field(&data[i..]);
+                        return Parsed::Record(len);
+                    }
+                    None => return Parsed::Incomplete,
+                }
+            }
+            if data[i] != delimiter {
+                // the line terminator ends the record (the `\n` of `\r\n` is skipped with the next record)
+                return Parsed::Record(i + 1);
+            }
+            i += 1;
+            if i == len {
+                if !is_eof {
+                    return Parsed::Incomplete;
+                }
+                csv_record.push_field(b"");
+                return Parsed::Record(len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn read_all_with_csv(csv_data: &str) -> csv::Result<Vec<csv::ByteRecord>> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(csv_data.as_bytes());
+        let mut csv_records = Vec::new();
+        let mut csv_record = csv::ByteRecord::new();
+        while csv_reader.read_byte_record(&mut csv_record)? {
+            csv_records.push(csv_record.clone());
+        }
+        Ok(csv_records)
+    }
+
+    fn read_all_with_simd(
+        csv_data: &str,
+        buffer_capacity: usize,
+    ) -> csv::Result<Vec<csv::ByteRecord>> {
+        let mut simd_reader =
+            SimdParser::new(b',', b'"', true, Some(buffer_capacity)).record_reader();
+        let mut reader = csv_data.as_bytes();
+        let mut csv_records = Vec::new();
+        let mut csv_record = csv::ByteRecord::new();
+        while simd_reader.read_byte_record(&mut reader, &mut csv_record)? {
+            csv_records.push(csv_record.clone());
+        }
+        Ok(csv_records)
+    }
+
+    #[test]
+    fn records_and_positions_are_the_same_as_with_csv_crate() -> csv::Result<()> {
+        let csv_data = [
+            "\u{feff}id,name,kind\n1,lemon,fruit\n\n\n2,\"straw\"\"berry\",fruit\r\n",
+            "3,\"multi\r\nline\",\r\n\r\n4,\"quoted\"suffix,\"\"\r5,,\n6,\"\",x,too many\n",
+            ",\n\"unterminated,field",
+        ]
+        .concat();
+        let csv_records_expected = read_all_with_csv(&csv_data)?;
+
+        // the small buffers make records cross the boundaries of the buffered data
+        for buffer_capacity in [1, 2, 3, 7, 64, 1024] {
+            let csv_records_actual = read_all_with_simd(&csv_data, buffer_capacity)?;
+            assert_eq!(csv_records_actual, csv_records_expected);
+            assert_eq!(
+                csv_records_actual
+                    .iter()
+                    .map(|csv_record| csv_record.position().cloned())
+                    .collect::<Vec<_>>(),
+                csv_records_expected
+                    .iter()
+                    .map(|csv_record| csv_record.position().cloned())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn record_with_different_number_of_fields_is_err_unless_flexible() {
+        let mut simd_reader = SimdParser::new(b';', b'\'', false, None).record_reader();
+        let mut reader = "id;name\n1;'lemon;lime'\n2;strawberry;fruit".as_bytes();
+        let mut csv_record = csv::ByteRecord::new();
+
+        assert!(matches!(
+            simd_reader.read_byte_record(&mut reader, &mut csv_record),
+            Ok(true)
+        ));
+        assert!(matches!(
+            simd_reader.read_byte_record(&mut reader, &mut csv_record),
+            Ok(true)
+        ));
+        assert_eq!(csv_record, csv::ByteRecord::from(vec!["1", "lemon;lime"]));
+        let err = simd_reader
+            .read_byte_record(&mut reader, &mut csv_record)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("found record with 3 fields, but the previous record has 2 fields"));
+    }
+}
@@ -0,0 +1,480 @@
+use crate::cancellation::CancellationToken;
+use crate::csv::Csv;
+use crate::csv_diff::CsvByteDiffBuilderError;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::empty_csv::CsvSide;
+use crate::error_code::ErrorCode;
+use crate::field_comparator::{FieldComparator, FieldComparators, IgnoreComparator};
+use crate::simd::modified_field_indices;
+use std::cmp::Ordering;
+use std::io::{self, Read};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Compare two [CSVs](https://en.wikipedia.org/wiki/Comma-separated_values), that are both sorted by their primary key,
+/// with a streaming merge-join.
+///
+/// In contrast to [`CsvByteDiff`](crate::csv_diff::CsvByteDiff) and [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal),
+/// no hash maps and no threads are needed: both CSVs are read in lockstep and only the current record of each side is held in memory,
+/// so CSVs of any size can be compared with constant memory. The differences are emitted in the order of the primary key.
+///
+/// Both CSVs must be sorted in ascending order by the fields of the primary key columns
+/// (compared byte-wise and in the order in which the columns have been given) and the primary key must be unique.
+/// Otherwise, the [`DiffByteRecordsSorted`] iterator stops with an [`UnsortedCsvError`].
+///
+/// Headers are not compared.
+/// # Example
+/// ```
+/// use csv_diff::{sorted_diff::CsvByteDiffSorted, csv::Csv};
+/// use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let csv_left = "\
+/// id,name,kind\n\
+/// 1,lemon,fruit\n\
+/// 2,strawberry,fruit";
+/// let csv_right = "\
+/// id,name,kind\n\
+/// 1,lemon,fruit\n\
+/// 3,cherry,fruit";
+///
+/// let diff_byte_records = CsvByteDiffSorted::new()
+///     .diff(
+///         Csv::with_reader(csv_left.as_bytes()),
+///         Csv::with_reader(csv_right.as_bytes()),
+///     )
+///     .collect::<csv::Result<Vec<_>>>()?;
+///
+/// assert_eq!(
+///     diff_byte_records,
+///     vec![
+///         DiffByteRecord::Delete(ByteRecordLineInfo::new(
+///             csv::ByteRecord::from(vec!["2", "strawberry", "fruit"]),
+///             3
+///         )),
+///         DiffByteRecord::Add(ByteRecordLineInfo::new(
+///             csv::ByteRecord::from(vec!["3", "cherry", "fruit"]),
+///             3
+///         )),
+///     ]
+/// );
+/// Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvByteDiffSorted {
+    primary_key_columns: Vec<usize>,
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+}
+
+impl Default for CsvByteDiffSorted {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvByteDiffSorted {
+    /// Create a `CsvByteDiffSorted` with column 0 as primary key.
+    pub fn new() -> Self {
+        Self {
+            primary_key_columns: vec![0],
+            field_comparators: FieldComparators::default(),
+            cancellation_token: CancellationToken::default(),
+        }
+    }
+
+    /// Compare `csv_left` with `csv_right` lazily: records are only read, when the returned iterator is advanced.
+    pub fn diff<R: Read>(&self, csv_left: Csv<R>, csv_right: Csv<R>) -> DiffByteRecordsSorted<R> {
+        DiffByteRecordsSorted {
+            left: SortedCsv::new(csv_left.into_csv_reader(), CsvSide::Left),
+            right: SortedCsv::new(csv_right.into_csv_reader(), CsvSide::Right),
+            primary_key_columns: self.primary_key_columns.clone(),
+            field_comparators: self.field_comparators.clone(),
+            cancellation_token: self.cancellation_token.clone(),
+            is_started: false,
+            is_finished: false,
+        }
+    }
+}
+
+/// Create a [`CsvByteDiffSorted`] with configuration options.
+#[derive(Debug, Default)]
+pub struct CsvByteDiffSortedBuilder {
+    csv_byte_diff_sorted: CsvByteDiffSorted,
+}
+
+impl CsvByteDiffSortedBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the primary key columns, by which both CSVs are sorted (in this order).
+    pub fn primary_key_columns(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        self.csv_byte_diff_sorted.primary_key_columns = columns.into_iter().collect();
+        self
+    }
+
+    /// Compare the fields of column `column` with `comparator` instead of byte-wise.
+    ///
+    /// See [`CsvByteDiffBuilder::field_comparator`](crate::csv_diff::CsvByteDiffBuilder::field_comparator).
+    pub fn field_comparator(
+        mut self,
+        column: usize,
+        comparator: impl FieldComparator + 'static,
+    ) -> Self {
+        self.csv_byte_diff_sorted
+            .field_comparators
+            .insert(column, Arc::new(comparator));
+        self
+    }
+
+    /// Don't report changes in the columns `columns` as [`Modify`](crate::diff_row::DiffByteRecord::Modify).
+    ///
+    /// See [`CsvByteDiffBuilder::ignore_columns_for_modify`](crate::csv_diff::CsvByteDiffBuilder::ignore_columns_for_modify).
+    pub fn ignore_columns_for_modify(mut self, columns: impl IntoIterator<Item = usize>) -> Self {
+        let ignore_comparator: Arc<dyn FieldComparator> = Arc::new(IgnoreComparator);
+        for column in columns {
+            self.csv_byte_diff_sorted
+                .field_comparators
+                .insert(column, Arc::clone(&ignore_comparator));
+        }
+        self
+    }
+
+    /// Check `cancellation_token` while comparing, so that the comparison can be cancelled from another thread.
+    ///
+    /// See [`CancellationToken`](crate::cancellation::CancellationToken) for more information.
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.csv_byte_diff_sorted.cancellation_token = cancellation_token;
+        self
+    }
+
+    pub fn build(self) -> Result<CsvByteDiffSorted, CsvByteDiffBuilderError> {
+        if self.csv_byte_diff_sorted.primary_key_columns.is_empty() {
+            Err(CsvByteDiffBuilderError::NoPrimaryKeyColumns)
+        } else {
+            Ok(self.csv_byte_diff_sorted)
+        }
+    }
+}
+
+/// Emits all [`DiffByteRecord`]s of a [`CsvByteDiffSorted`] in the order of the primary key.
+///
+/// After the first error, the iterator is finished.
+pub struct DiffByteRecordsSorted<R> {
+    left: SortedCsv<R>,
+    right: SortedCsv<R>,
+    primary_key_columns: Vec<usize>,
+    field_comparators: FieldComparators,
+    cancellation_token: CancellationToken,
+    is_started: bool,
+    is_finished: bool,
+}
+
+impl<R: Read> DiffByteRecordsSorted<R> {
+    fn next_diff_byte_record(&mut self) -> csv::Result<Option<DiffByteRecord>> {
+        if !self.is_started {
+            self.is_started = true;
+            self.left.advance(&self.primary_key_columns)?;
+            self.right.advance(&self.primary_key_columns)?;
+        }
+        loop {
+            self.cancellation_token.check()?;
+            let ordering = match (&self.left.current, &self.right.current) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(byte_record_left), Some(byte_record_right)) => cmp_keys(
+                    byte_record_left,
+                    byte_record_right,
+                    &self.primary_key_columns,
+                ),
+            };
+            match ordering {
+                Ordering::Less => {
+                    let delete = self.left.take_current();
+                    self.left.advance(&self.primary_key_columns)?;
+                    return Ok(Some(DiffByteRecord::Delete(delete)));
+                }
+                Ordering::Greater => {
+                    let add = self.right.take_current();
+                    self.right.advance(&self.primary_key_columns)?;
+                    return Ok(Some(DiffByteRecord::Add(add)));
+                }
+                Ordering::Equal => {
+                    let delete = self.left.take_current();
+                    let add = self.right.take_current();
+                    self.left.advance(&self.primary_key_columns)?;
+                    self.right.advance(&self.primary_key_columns)?;
+                    let field_indices =
+                        modified_field_indices(delete.byte_record(), add.byte_record());
+                    if field_indices.is_empty() {
+                        continue;
+                    }
+                    if let Some(modify) = self.field_comparators.apply(DiffByteRecord::Modify {
+                        delete,
+                        add,
+                        field_indices,
+                    }) {
+                        return Ok(Some(modify));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for DiffByteRecordsSorted<R> {
+    type Item = csv::Result<DiffByteRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        match self.next_diff_byte_record() {
+            Ok(Some(diff_record)) => Some(Ok(diff_record)),
+            Ok(None) => {
+                self.is_finished = true;
+                None
+            }
+            Err(csv_err) => {
+                self.is_finished = true;
+                Some(Err(csv_err))
+            }
+        }
+    }
+}
+
+/// One side of a [`DiffByteRecordsSorted`]: its current record and the key of the record before it,
+/// so that the order of the keys can be checked.
+struct SortedCsv<R> {
+    reader: csv::Reader<R>,
+    side: CsvSide,
+    current: Option<csv::ByteRecord>,
+    previous_key: Option<csv::ByteRecord>,
+}
+
+impl<R: Read> SortedCsv<R> {
+    fn new(reader: csv::Reader<R>, side: CsvSide) -> Self {
+        Self {
+            reader,
+            side,
+            current: None,
+            previous_key: None,
+        }
+    }
+
+    fn advance(&mut self, primary_key_columns: &[usize]) -> csv::Result<()> {
+        let mut byte_record = csv::ByteRecord::new();
+        if !self.reader.read_byte_record(&mut byte_record)? {
+            self.current = None;
+            return Ok(());
+        }
+        let key = key_fields(&byte_record, primary_key_columns).collect::<csv::ByteRecord>();
+        if let Some(previous_key) = &self.previous_key {
+            if previous_key.iter().cmp(key.iter()) != Ordering::Less {
+                return Err(UnsortedCsvError::new(self.side, line_of(&byte_record)).into());
+            }
+        }
+        self.previous_key = Some(key);
+        self.current = Some(byte_record);
+        Ok(())
+    }
+
+    fn take_current(&mut self) -> ByteRecordLineInfo {
+        let byte_record = self.current.take().expect("a current record");
+        let line = line_of(&byte_record);
+        ByteRecordLineInfo::new(byte_record, line)
+    }
+}
+
+fn cmp_keys(
+    byte_record_left: &csv::ByteRecord,
+    byte_record_right: &csv::ByteRecord,
+    primary_key_columns: &[usize],
+) -> Ordering {
+    key_fields(byte_record_left, primary_key_columns)
+        .cmp(key_fields(byte_record_right, primary_key_columns))
+}
+
+fn key_fields<'a>(
+    byte_record: &'a csv::ByteRecord,
+    primary_key_columns: &'a [usize],
+) -> impl Iterator<Item = &'a [u8]> + 'a {
+    primary_key_columns
+        .iter()
+        .map(move |&idx| byte_record.get(idx).unwrap_or_default())
+}
+
+fn line_of(byte_record: &csv::ByteRecord) -> u64 {
+    byte_record.position().map_or(0, |pos| pos.line())
+}
+
+/// A CSV, that is compared with [`CsvByteDiffSorted`], is not sorted by its primary key or has a duplicate primary key.
+///
+/// It is returned as a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is an `UnsortedCsvError`.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error(
+    "the {side} CSV is not sorted by its primary key (or the key is not unique) at line {line}"
+)]
+pub struct UnsortedCsvError {
+    side: CsvSide,
+    line: u64,
+}
+
+impl UnsortedCsvError {
+    pub(crate) fn new(side: CsvSide, line: u64) -> Self {
+        Self { side, line }
+    }
+
+    /// The side of the CSV, that is not sorted.
+    pub fn side(&self) -> CsvSide {
+        self.side
+    }
+
+    /// The line of the first record, whose key is not greater than the key before it.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+}
+
+impl ErrorCode for UnsortedCsvError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_UNSORTED_CSV"
+    }
+}
+
+impl From<UnsortedCsvError> for csv::Error {
+    fn from(err: UnsortedCsvError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn diff(
+        csv_byte_diff_sorted: &CsvByteDiffSorted,
+        csv_left: &str,
+        csv_right: &str,
+    ) -> csv::Result<Vec<DiffByteRecord>> {
+        csv_byte_diff_sorted
+            .diff(
+                Csv::with_reader(csv_left.as_bytes()),
+                Csv::with_reader(csv_right.as_bytes()),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn diff_sorted_emits_differences_in_key_order() -> csv::Result<()> {
+        let csv_left = "\
+            id,name,kind\n\
+            1,lemon,fruit\n\
+            2,strawberry,fruit\n\
+            4,peanut,nut";
+        let csv_right = "\
+            id,name,kind\n\
+            1,lemon,fruit\n\
+            3,cherry,fruit\n\
+            4,peanut,legume\n\
+            5,apple,fruit";
+
+        let diff_byte_records = diff(&CsvByteDiffSorted::new(), csv_left, csv_right)?;
+
+        assert_eq!(
+            diff_byte_records,
+            vec![
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "strawberry", "fruit"]),
+                    3
+                )),
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["3", "cherry", "fruit"]),
+                    3
+                )),
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["4", "peanut", "nut"]),
+                        4
+                    ),
+                    add: ByteRecordLineInfo::new(
+                        csv::ByteRecord::from(vec!["4", "peanut", "legume"]),
+                        4
+                    ),
+                    field_indices: vec![2],
+                },
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["5", "apple", "fruit"]),
+                    5
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diff_sorted_with_composite_key_and_ignored_column() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let csv_left = "\
+            id,version,name\n\
+            1,a,lemon\n\
+            1,b,lime";
+        let csv_right = "\
+            id,version,name\n\
+            1,a,LEMON\n\
+            1,c,lime";
+
+        let diff_byte_records = diff(
+            &CsvByteDiffSortedBuilder::new()
+                .primary_key_columns([0, 1])
+                .ignore_columns_for_modify([2])
+                .build()?,
+            csv_left,
+            csv_right,
+        )?;
+
+        assert_eq!(
+            diff_byte_records,
+            vec![
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["1", "b", "lime"]),
+                    3
+                )),
+                DiffByteRecord::Add(ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["1", "c", "lime"]),
+                    3
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn diff_unsorted_right_is_err_with_line() {
+        let csv_left = "\
+            id,name\n\
+            1,lemon\n\
+            2,strawberry";
+        let csv_right = "\
+            id,name\n\
+            2,strawberry\n\
+            1,lemon";
+
+        let err = diff(&CsvByteDiffSorted::new(), csv_left, csv_right).unwrap_err();
+
+        let unsorted_err = match err.kind() {
+            csv::ErrorKind::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<UnsortedCsvError>()),
+            _ => None,
+        };
+        assert_eq!(
+            unsorted_err,
+            Some(&UnsortedCsvError::new(CsvSide::Right, 3))
+        );
+        assert_eq!(err.code(), "CSVDIFF_UNSORTED_CSV");
+    }
+}
@@ -0,0 +1,341 @@
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use crate::simd::modified_field_indices;
+use ahash::AHashMap as HashMap;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::mem::size_of;
+
+/// Number of temporary files per side. When merging, only one partition of each side is held in memory at a time.
+const NUM_OF_PARTITIONS: usize = 64;
+
+/// Rough estimate of the memory, that is needed by a record in one of the hash maps of the comparer,
+/// besides its fields (the hash map entry, the record itself and its field bounds).
+const RECORD_OVERHEAD: usize = 128;
+
+/// Estimate the number of bytes, that `byte_record` occupies in a hash map of the comparer.
+pub(crate) fn approx_size(byte_record: &csv::ByteRecord) -> usize {
+    byte_record.as_slice().len() + byte_record.len() * size_of::<usize>() + RECORD_OVERHEAD
+}
+
+/// Unmatched records of one side, that have been written to temporary files, partitioned by their key.
+struct SpilledRecords {
+    partitions: Vec<Option<BufWriter<File>>>,
+}
+
+impl SpilledRecords {
+    fn new() -> Self {
+        Self {
+            partitions: (0..NUM_OF_PARTITIONS).map(|_| None).collect(),
+        }
+    }
+
+    fn write(
+        &mut self,
+        key: u128,
+        record_hash: u128,
+        byte_record: &csv::ByteRecord,
+    ) -> io::Result<()> {
+        let wtr = match &mut self.partitions[partition_of(key)] {
+            Some(wtr) => wtr,
+            partition @ None => partition.insert(BufWriter::new(tempfile::tempfile()?)),
+        };
+        write_entry(wtr, key, record_hash, byte_record)
+    }
+
+    /// Read all records of partition `partition_idx` back (in the order they have been written) and remove the partition.
+    fn take_partition(
+        &mut self,
+        partition_idx: usize,
+    ) -> io::Result<Vec<(u128, u128, csv::ByteRecord)>> {
+        let wtr = match self.partitions[partition_idx].take() {
+            Some(wtr) => wtr,
+            None => return Ok(Vec::new()),
+        };
+        let mut file = wtr.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.seek(SeekFrom::Start(0))?;
+        let mut rdr = BufReader::new(file);
+        let mut entries = Vec::new();
+        while let Some(key) = read_u128_or_eof(&mut rdr)? {
+            entries.push(read_entry(&mut rdr, key)?);
+        }
+        Ok(entries)
+    }
+}
+
+/// Unmatched records of both sides, that have been spilled to disk, because the
+/// [`memory_budget`](crate::csv_diff::CsvByteDiffBuilder::memory_budget) has been exceeded.
+///
+/// After all records have been received, the spilled records are merged partition by partition:
+/// records of both sides with the same key are compared and all others are emitted as added or deleted.
+pub(crate) struct RecordSpill {
+    left: SpilledRecords,
+    right: SpilledRecords,
+    num_of_partitions_merged: usize,
+    merged: VecDeque<DiffByteRecord>,
+    equal: Option<VecDeque<(ByteRecordLineInfo, ByteRecordLineInfo)>>,
+    num_of_equal: u64,
+}
+
+impl RecordSpill {
+    pub(crate) fn new() -> Self {
+        Self {
+            left: SpilledRecords::new(),
+            right: SpilledRecords::new(),
+            num_of_partitions_merged: 0,
+            merged: VecDeque::new(),
+            equal: None,
+            num_of_equal: 0,
+        }
+    }
+
+    /// Keep the records, that are equal on both sides, when merging (they are dropped by default).
+    pub(crate) fn keep_equal(mut self, is_keeping_equal: bool) -> Self {
+        self.equal = is_keeping_equal.then(VecDeque::new);
+        self
+    }
+
+    /// Return the number of equal records of the partitions, that have been merged since the last call.
+    pub(crate) fn take_num_of_equal(&mut self) -> u64 {
+        std::mem::take(&mut self.num_of_equal)
+    }
+
+    /// Return the next pair of equal records of the partitions, that have been merged so far.
+    pub(crate) fn next_equal(&mut self) -> Option<(ByteRecordLineInfo, ByteRecordLineInfo)> {
+        self.equal.as_mut()?.pop_front()
+    }
+
+    pub(crate) fn write_left(
+        &mut self,
+        key: u128,
+        record_hash: u128,
+        byte_record: &csv::ByteRecord,
+    ) -> io::Result<()> {
+        self.left.write(key, record_hash, byte_record)
+    }
+
+    pub(crate) fn write_right(
+        &mut self,
+        key: u128,
+        record_hash: u128,
+        byte_record: &csv::ByteRecord,
+    ) -> io::Result<()> {
+        self.right.write(key, record_hash, byte_record)
+    }
+
+    /// Return the next difference of the merged partitions or `None`, when all of them have been merged.
+    pub(crate) fn next_merged(&mut self) -> Option<io::Result<DiffByteRecord>> {
+        while self.merged.is_empty() && self.num_of_partitions_merged < NUM_OF_PARTITIONS {
+            let partition_idx = self.num_of_partitions_merged;
+            self.num_of_partitions_merged += 1;
+            if let Err(io_err) = self.merge_partition(partition_idx) {
+                // the remaining partitions can't be trusted anymore
+                self.num_of_partitions_merged = NUM_OF_PARTITIONS;
+                return Some(Err(io_err));
+            }
+        }
+        self.merged.pop_front().map(Ok)
+    }
+
+    fn merge_partition(&mut self, partition_idx: usize) -> io::Result<()> {
+        let mut left_map: HashMap<u128, (u128, csv::ByteRecord)> = self
+            .left
+            .take_partition(partition_idx)?
+            .into_iter()
+            .map(|(key, record_hash, byte_record)| (key, (record_hash, byte_record)))
+            .collect();
+        let right_map: HashMap<u128, (u128, csv::ByteRecord)> = self
+            .right
+            .take_partition(partition_idx)?
+            .into_iter()
+            .map(|(key, record_hash, byte_record)| (key, (record_hash, byte_record)))
+            .collect();
+        for (key, (record_hash_right, byte_record_right)) in right_map {
+            match left_map.remove(&key) {
+                Some((record_hash_left, byte_record_left)) => {
+                    if record_hash_left != record_hash_right {
+                        self.merged
+                            .push_back(modify(byte_record_left, byte_record_right));
+                    } else {
+                        self.num_of_equal += 1;
+                        if let Some(equal) = &mut self.equal {
+                            equal.push_back(equal_pair(byte_record_left, byte_record_right));
+                        }
+                    }
+                }
+                None => {
+                    let line = line_of(&byte_record_right);
+                    self.merged
+                        .push_back(DiffByteRecord::Add(ByteRecordLineInfo::new(
+                            byte_record_right,
+                            line,
+                        )));
+                }
+            }
+        }
+        for (_, (_, byte_record_left)) in left_map {
+            let line = line_of(&byte_record_left);
+            self.merged
+                .push_back(DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                    byte_record_left,
+                    line,
+                )));
+        }
+        Ok(())
+    }
+}
+
+fn modify(byte_record_left: csv::ByteRecord, byte_record_right: csv::ByteRecord) -> DiffByteRecord {
+    let field_indices = modified_field_indices(&byte_record_left, &byte_record_right);
+    let line_left = line_of(&byte_record_left);
+    let line_right = line_of(&byte_record_right);
+    DiffByteRecord::Modify {
+        delete: ByteRecordLineInfo::new(byte_record_left, line_left),
+        add: ByteRecordLineInfo::new(byte_record_right, line_right),
+        field_indices,
+    }
+}
+
+pub(crate) fn equal_pair(
+    byte_record_left: csv::ByteRecord,
+    byte_record_right: csv::ByteRecord,
+) -> (ByteRecordLineInfo, ByteRecordLineInfo) {
+    let line_left = line_of(&byte_record_left);
+    let line_right = line_of(&byte_record_right);
+    (
+        ByteRecordLineInfo::new(byte_record_left, line_left),
+        ByteRecordLineInfo::new(byte_record_right, line_right),
+    )
+}
+
+/// Write a record together with its key, its hash and its line (but no other part of its position).
+pub(crate) fn write_entry<W: Write>(
+    wtr: &mut W,
+    key: u128,
+    record_hash: u128,
+    byte_record: &csv::ByteRecord,
+) -> io::Result<()> {
+    wtr.write_all(&key.to_le_bytes())?;
+    wtr.write_all(&record_hash.to_le_bytes())?;
+    wtr.write_all(&line_of(byte_record).to_le_bytes())?;
+    wtr.write_all(&(byte_record.len() as u64).to_le_bytes())?;
+    for field in byte_record {
+        wtr.write_all(&(field.len() as u64).to_le_bytes())?;
+        wtr.write_all(field)?;
+    }
+    Ok(())
+}
+
+/// Read the rest of an entry, that has been written by [`write_entry`] and whose `key` has already been read.
+pub(crate) fn read_entry<R: Read>(
+    rdr: &mut R,
+    key: u128,
+) -> io::Result<(u128, u128, csv::ByteRecord)> {
+    let record_hash = read_u128(rdr)?;
+    let line = read_u64(rdr)?;
+    let num_of_fields = read_u64(rdr)?;
+    let mut byte_record = csv::ByteRecord::new();
+    let mut field = Vec::new();
+    for _ in 0..num_of_fields {
+        read_field(rdr, &mut field)?;
+        byte_record.push_field(&field);
+    }
+    let mut pos = csv::Position::new();
+    pos.set_line(line);
+    byte_record.set_position(Some(pos));
+    Ok((key, record_hash, byte_record))
+}
+
+fn line_of(byte_record: &csv::ByteRecord) -> u64 {
+    byte_record.position().map_or(0, |pos| pos.line())
+}
+
+fn partition_of(key: u128) -> usize {
+    // the key is already a hash, so its upper bits are evenly distributed
+    (key >> 122) as usize % NUM_OF_PARTITIONS
+}
+
+fn read_u128_or_eof<R: Read>(rdr: &mut R) -> io::Result<Option<u128>> {
+    let mut buf = [0; 16];
+    match rdr.read(&mut buf[..1])? {
+        0 => Ok(None),
+        _ => {
+            rdr.read_exact(&mut buf[1..])?;
+            Ok(Some(u128::from_le_bytes(buf)))
+        }
+    }
+}
+
+/// Read a field, that has been written after its length, into `field`.
+///
+/// The length is not trusted for allocating the field, so that a corrupt length fails with an error of kind
+/// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) at the end of `rdr` instead of allocating that many bytes.
+pub(crate) fn read_field<R: Read>(rdr: &mut R, field: &mut Vec<u8>) -> io::Result<()> {
+    let len = read_u64(rdr)?;
+    field.clear();
+    rdr.take(len).read_to_end(field)?;
+    if field.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+pub(crate) fn read_u128<R: Read>(rdr: &mut R) -> io::Result<u128> {
+    let mut buf = [0; 16];
+    rdr.read_exact(&mut buf)?;
+    Ok(u128::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(rdr: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    rdr.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn byte_record(fields: Vec<&str>, line: u64) -> csv::ByteRecord {
+        let mut byte_record = csv::ByteRecord::from(fields);
+        let mut pos = csv::Position::new();
+        pos.set_line(line);
+        byte_record.set_position(Some(pos));
+        byte_record
+    }
+
+    #[test]
+    fn merge_matches_spilled_records_by_key() -> io::Result<()> {
+        let mut record_spill = RecordSpill::new();
+        record_spill.write_left(1, 10, &byte_record(vec!["1", "a"], 2))?;
+        record_spill.write_left(2, 20, &byte_record(vec!["2", "b"], 3))?;
+        record_spill.write_left(u128::MAX, 30, &byte_record(vec!["3", "c"], 4))?;
+        record_spill.write_right(2, 21, &byte_record(vec!["2", "x"], 5))?;
+        record_spill.write_right(u128::MAX, 30, &byte_record(vec!["3", "c"], 2))?;
+        record_spill.write_right(4, 40, &byte_record(vec!["4", "d"], 6))?;
+
+        let mut merged =
+            std::iter::from_fn(|| record_spill.next_merged()).collect::<io::Result<Vec<_>>>()?;
+        merged.sort_by_key(|diff_record| match diff_record {
+            DiffByteRecord::Add(add) => add.line(),
+            DiffByteRecord::Modify { delete, .. } => delete.line(),
+            DiffByteRecord::Delete(delete) => delete.line(),
+            DiffByteRecord::Move { .. } => unreachable!("moves are only detected on request"),
+        });
+
+        assert_eq!(
+            merged,
+            vec![
+                DiffByteRecord::Delete(ByteRecordLineInfo::new(byte_record(vec!["1", "a"], 2), 2)),
+                DiffByteRecord::Modify {
+                    delete: ByteRecordLineInfo::new(byte_record(vec!["2", "b"], 3), 3),
+                    add: ByteRecordLineInfo::new(byte_record(vec!["2", "x"], 5), 5),
+                    field_indices: vec![1],
+                },
+                DiffByteRecord::Add(ByteRecordLineInfo::new(byte_record(vec!["4", "d"], 6), 6)),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::diff_result::{CsvWriteStyle, DiffByteRecords};
+use crate::diff_row::DiffByteRecord;
+use std::io::Write;
+
+/// Writes [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s as CSV into three separate sinks (e.g. three files),
+/// one for the [`Add`](crate::diff_row::DiffByteRecord::Add)s, one for the [`Delete`](crate::diff_row::DiffByteRecord::Delete)s
+/// and one for the [`Modify`](crate::diff_row::DiffByteRecord::Modify)s.
+///
+/// The records are written as they are, without an operation column (unlike [`DiffByteRecords::write_csv`]),
+/// because the sink already tells the operation. Modified records are written with the fields of the right CSV or,
+/// with [`CsvWriteStyle::Split`], twice: first with the fields of the left CSV and then with the fields of the right CSV
+/// (see [`modify_style`](SplitCsvWriter::modify_style)).
+///
+/// Together with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff), differences are written while the CSVs are still being compared,
+/// so they never have to be collected in memory.
+/// # Example: write the added, deleted and modified records into separate sinks
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiff, csv::Csv};
+use csv_diff::diff_result::CsvWriteStyle;
+use csv_diff::split_writer::SplitCsvWriter;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = "\
+id,name,kind\n\
+1,lemon,fruit\n\
+2,strawberry,fruit";
+let csv_right = "\
+id,name,kind\n\
+1,lemon,nut\n\
+3,cherry,fruit";
+
+let mut split_writer =
+    SplitCsvWriter::from_writers(Vec::new(), Vec::new(), Vec::new()).modify_style(CsvWriteStyle::Split);
+split_writer.write_headers(&csv::ByteRecord::from(vec!["id", "name", "kind"]))?;
+
+let num_of_records_written = split_writer.write_all_from_iter(CsvByteDiff::new()?.diff(
+    Csv::with_reader(csv_left.as_bytes()),
+    Csv::with_reader(csv_right.as_bytes()),
+))?;
+
+assert_eq!(num_of_records_written, 3);
+let (added, deleted, modified) = split_writer.into_inner()?;
+assert_eq!(String::from_utf8(added)?, "id,name,kind\n3,cherry,fruit\n");
+assert_eq!(String::from_utf8(deleted)?, "id,name,kind\n2,strawberry,fruit\n");
+assert_eq!(String::from_utf8(modified)?, "id,name,kind\n1,lemon,fruit\n1,lemon,nut\n");
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub struct SplitCsvWriter<A: Write, D: Write, M: Write> {
+    added: csv::Writer<A>,
+    deleted: csv::Writer<D>,
+    modified: csv::Writer<M>,
+    modify_style: CsvWriteStyle,
+}
+
+impl<A: Write, D: Write, M: Write> SplitCsvWriter<A, D, M> {
+    /// Write into the sinks with the default configuration of [`csv::Writer`].
+    pub fn from_writers(added: A, deleted: D, modified: M) -> Self {
+        Self::from_csv_writers(
+            csv::Writer::from_writer(added),
+            csv::Writer::from_writer(deleted),
+            csv::Writer::from_writer(modified),
+        )
+    }
+
+    /// Write through the given [`csv::Writer`]s, e.g. to use another delimiter.
+    pub fn from_csv_writers(
+        added: csv::Writer<A>,
+        deleted: csv::Writer<D>,
+        modified: csv::Writer<M>,
+    ) -> Self {
+        Self {
+            added,
+            deleted,
+            modified,
+            modify_style: CsvWriteStyle::Compact,
+        }
+    }
+
+    /// Choose, whether modified records are written once with the fields of the right CSV
+    /// or twice with the fields of both CSVs (default is [`CsvWriteStyle::Compact`]).
+    pub fn modify_style(mut self, modify_style: CsvWriteStyle) -> Self {
+        self.modify_style = modify_style;
+        self
+    }
+
+    /// Write `headers` into all three sinks (this should be done before writing any record).
+    pub fn write_headers(&mut self, headers: &csv::ByteRecord) -> csv::Result<()> {
+        self.added.write_byte_record(headers)?;
+        self.deleted.write_byte_record(headers)?;
+        self.modified.write_byte_record(headers)
+    }
+
+    /// Write a single `DiffByteRecord` into the sink of its kind.
+    ///
+    /// A [`Move`](crate::diff_row::DiffByteRecord::Move) has no sink, because its record is unchanged, so it is skipped.
+    pub fn write_diff_byte_record(&mut self, diff_record: &DiffByteRecord) -> csv::Result<()> {
+        match diff_record {
+            DiffByteRecord::Add(add) => self.added.write_byte_record(add.byte_record()),
+            DiffByteRecord::Delete(delete) => self.deleted.write_byte_record(delete.byte_record()),
+            DiffByteRecord::Modify { delete, add, .. } => {
+                if self.modify_style == CsvWriteStyle::Split {
+                    self.modified.write_byte_record(delete.byte_record())?;
+                }
+                self.modified.write_byte_record(add.byte_record())
+            }
+            DiffByteRecord::Move { .. } => Ok(()),
+        }
+    }
+
+    /// Write the [`headers`](DiffByteRecords::headers) (if there are any) and all records of `diff_records`
+    /// in their current order.
+    pub fn write_diff_byte_records(&mut self, diff_records: &DiffByteRecords) -> csv::Result<()> {
+        if let Some(headers) = diff_records.headers() {
+            self.write_headers(headers)?;
+        }
+        for diff_record in diff_records.iter() {
+            self.write_diff_byte_record(diff_record)?;
+        }
+        Ok(())
+    }
+
+    /// Consume `diff_records` lazily and write every `DiffByteRecord` into the sink of its kind.
+    ///
+    /// Returns the number of records that have been written or the first error that occurred
+    /// (either when comparing the CSVs or when writing).
+    pub fn write_all_from_iter<I>(&mut self, diff_records: I) -> csv::Result<u64>
+    where
+        I: IntoIterator<Item = csv::Result<DiffByteRecord>>,
+    {
+        let mut num_written = 0;
+        for diff_record in diff_records {
+            self.write_diff_byte_record(&diff_record?)?;
+            num_written += 1;
+        }
+        Ok(num_written)
+    }
+
+    pub fn flush(&mut self) -> csv::Result<()> {
+        self.added.flush()?;
+        self.deleted.flush()?;
+        self.modified.flush()?;
+        Ok(())
+    }
+
+    /// Flush all sinks and return them in the order added, deleted, modified.
+    pub fn into_inner(self) -> csv::Result<(A, D, M)> {
+        Ok((
+            into_inner(self.added)?,
+            into_inner(self.deleted)?,
+            into_inner(self.modified)?,
+        ))
+    }
+}
+
+fn into_inner<W: Write>(csv_writer: csv::Writer<W>) -> csv::Result<W> {
+    csv_writer
+        .into_inner()
+        .map_err(|into_inner_err| into_inner_err.into_error().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_row::ByteRecordLineInfo;
+    use pretty_assertions::assert_eq;
+    use std::error::Error;
+
+    fn diff_records() -> DiffByteRecords {
+        DiffByteRecords::from(vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["1", "lemon", "fruit"]),
+                2,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "strawberry", "fruit"]),
+                    3,
+                ),
+                add: ByteRecordLineInfo::new(
+                    csv::ByteRecord::from(vec!["2", "strawberry", "nut; sweet"]),
+                    3,
+                ),
+                field_indices: vec![2],
+            },
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["3", "cherry", "fruit"]),
+                4,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["4", "banana", "fruit"]),
+                5,
+            )),
+        ])
+    }
+
+    #[test]
+    fn write_diff_byte_records_routes_records_by_kind() -> Result<(), Box<dyn Error>> {
+        let mut split_writer = SplitCsvWriter::from_writers(Vec::new(), Vec::new(), Vec::new());
+
+        split_writer.write_diff_byte_records(&diff_records())?;
+
+        let (added, deleted, modified) = split_writer.into_inner()?;
+        assert_eq!(
+            String::from_utf8(added)?,
+            "3,cherry,fruit\n4,banana,fruit\n"
+        );
+        assert_eq!(String::from_utf8(deleted)?, "1,lemon,fruit\n");
+        assert_eq!(String::from_utf8(modified)?, "2,strawberry,nut; sweet\n");
+        Ok(())
+    }
+
+    #[test]
+    fn write_diff_byte_records_modified_split_with_other_delimiter() -> Result<(), Box<dyn Error>> {
+        let csv_writer = || {
+            csv::WriterBuilder::new()
+                .delimiter(b';')
+                .from_writer(Vec::new())
+        };
+        let mut split_writer =
+            SplitCsvWriter::from_csv_writers(csv_writer(), csv_writer(), csv_writer())
+                .modify_style(CsvWriteStyle::Split);
+
+        split_writer.write_diff_byte_records(&diff_records())?;
+
+        let (_, _, modified) = split_writer.into_inner()?;
+        assert_eq!(
+            String::from_utf8(modified)?,
+            "2;strawberry;fruit\n2;strawberry;\"nut; sweet\"\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+
+/// The default number of bytes, up to which a [`SpooledReader`] keeps the data in memory (16 MiB).
+pub const DEFAULT_SPOOL_MEMORY_THRESHOLD: usize = 16 * (1 << 20);
+
+/// A reader, that makes a non-seekable reader (e.g. stdin or a pipe) seekable by reading it completely
+/// into memory or - when it's larger than a threshold - into a temporary file (see [`std::env::temp_dir`]).
+///
+/// This allows to compare data from such readers with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal),
+/// which needs to seek in the CSV data. The temporary file is removed, when the `SpooledReader` is dropped.
+/// Usually, it is created with [`Csv::with_reader_spooled`](crate::csv::Csv::with_reader_spooled).
+/// # Example: compare data from stdin
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```no_run
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_spooled(std::io::stdin())?,
+    Csv::with_reader_spooled(std::fs::File::open("expected.csv")?)?,
+)?;
+# let _ = diff_byte_records;
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub struct SpooledReader {
+    inner: Spooled,
+}
+
+#[derive(Debug)]
+enum Spooled {
+    Memory(Cursor<Vec<u8>>),
+    File(File),
+}
+
+impl SpooledReader {
+    /// Read `reader` completely and keep its data in memory, if it's at most [`DEFAULT_SPOOL_MEMORY_THRESHOLD`] bytes,
+    /// or in a temporary file otherwise.
+    pub fn new<R: Read>(reader: R) -> io::Result<Self> {
+        Self::with_memory_threshold(reader, DEFAULT_SPOOL_MEMORY_THRESHOLD)
+    }
+
+    /// Like [`new`](SpooledReader::new), but keep the data in memory up to `memory_threshold` bytes.
+    /// A value of `0` always spools to a temporary file.
+    pub fn with_memory_threshold<R: Read>(
+        mut reader: R,
+        memory_threshold: usize,
+    ) -> io::Result<Self> {
+        let mut data = Vec::new();
+        // read one byte more than the threshold, in order to know whether it has been exceeded
+        let limit = u64::try_from(memory_threshold)
+            .unwrap_or(u64::MAX)
+            .saturating_add(1);
+        reader.by_ref().take(limit).read_to_end(&mut data)?;
+        if data.len() <= memory_threshold {
+            return Ok(Self {
+                inner: Spooled::Memory(Cursor::new(data)),
+            });
+        }
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&data)?;
+        drop(data);
+        io::copy(&mut reader, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self {
+            inner: Spooled::File(file),
+        })
+    }
+
+    /// Whether the data has been spooled to a temporary file.
+    pub fn is_spooled_to_file(&self) -> bool {
+        matches!(self.inner, Spooled::File(_))
+    }
+}
+
+impl Read for SpooledReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Spooled::Memory(cursor) => cursor.read(buf),
+            Spooled::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl Seek for SpooledReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.inner {
+            Spooled::Memory(cursor) => cursor.seek(pos),
+            Spooled::File(file) => file.seek(pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn data_up_to_threshold_is_kept_in_memory() -> io::Result<()> {
+        let mut reader = SpooledReader::with_memory_threshold("id,name".as_bytes(), 7)?;
+
+        reader.seek(SeekFrom::Start(3))?;
+        let mut read_data = String::new();
+        reader.read_to_string(&mut read_data)?;
+
+        assert!(!reader.is_spooled_to_file());
+        assert_eq!(read_data, "name");
+        Ok(())
+    }
+
+    #[test]
+    fn data_above_threshold_is_spooled_to_file() -> io::Result<()> {
+        let mut reader = SpooledReader::with_memory_threshold("id,name".as_bytes(), 6)?;
+
+        let mut read_data = String::new();
+        reader.read_to_string(&mut read_data)?;
+        reader.seek(SeekFrom::Start(3))?;
+        let mut read_data_after_seek = String::new();
+        reader.read_to_string(&mut read_data_after_seek)?;
+
+        assert!(reader.is_spooled_to_file());
+        assert_eq!(read_data, "id,name");
+        assert_eq!(read_data_after_seek, "name");
+        Ok(())
+    }
+}
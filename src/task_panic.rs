@@ -0,0 +1,94 @@
+use crate::error_code::ErrorCode;
+use std::any::Any;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use thiserror::Error;
+
+/// A task, that has been hashing or comparing CSV records, panicked.
+///
+/// Instead of unwinding into the caller (or aborting the process, which is the default for tasks spawned on a rayon thread-pool),
+/// a panic is converted into a [`csv::Error`] of kind [`csv::ErrorKind::Io`], whose inner error is a `TaskPanicError`.
+/// The panic message is preserved and can be retrieved like this:
+/// ```
+/// use csv_diff::task_panic::TaskPanicError;
+///
+/// fn panic_message(err: &csv::Error) -> Option<&str> {
+///     match err.kind() {
+///         csv::ErrorKind::Io(io_err) => io_err
+///             .get_ref()
+///             .and_then(|inner| inner.downcast_ref::<TaskPanicError>())
+///             .map(TaskPanicError::message),
+///         _ => None,
+///     }
+/// }
+/// ```
+#[derive(Debug, Error)]
+#[error("a task panicked while comparing the CSVs: {message}")]
+pub struct TaskPanicError {
+    message: String,
+}
+
+impl TaskPanicError {
+    pub(crate) fn from_payload(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_string(),
+                Err(_) => "unknown panic payload".to_string(),
+            },
+        };
+        Self { message }
+    }
+
+    /// The message of the panic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl ErrorCode for TaskPanicError {
+    fn code(&self) -> &'static str {
+        "CSVDIFF_TASK_PANIC"
+    }
+}
+
+impl From<TaskPanicError> for csv::Error {
+    fn from(err: TaskPanicError) -> Self {
+        csv::Error::from(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+/// Run `f` and convert a panic into a [`TaskPanicError`].
+pub(crate) fn catch_panic<F, T>(f: F) -> Result<T, TaskPanicError>
+where
+    F: FnOnce() -> T,
+{
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(TaskPanicError::from_payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn catch_panic_preserves_formatted_message() {
+        let err = catch_panic(|| panic!("broken at line {}", 42)).unwrap_err();
+
+        assert_eq!(err.message(), "broken at line 42");
+    }
+
+    #[test]
+    fn task_panic_error_into_csv_error_can_be_downcast() {
+        let err = csv::Error::from(catch_panic(|| panic!("static message")).unwrap_err());
+
+        let message = match err.kind() {
+            csv::ErrorKind::Io(io_err) => io_err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<TaskPanicError>())
+                .map(TaskPanicError::message),
+            _ => None,
+        };
+        assert_eq!(message, Some("static message"));
+    }
+}
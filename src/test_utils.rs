@@ -0,0 +1,171 @@
+//! Generate CSVs for benchmarks and tests, available with the `test-utils` feature.
+//!
+//! These are the generators, that the benchmarks of this crate use. They are not meant for production code.
+
+use rand::distributions::Alphanumeric;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// How the values of all columns (except the first one) are generated.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValueDistribution {
+    /// Lowercase words with a length in the given range.
+    Words(Range<usize>),
+    /// Integers, that are uniformly distributed in the given range.
+    Integers(Range<i64>),
+    /// Alphanumeric strings with a length in the given range.
+    Alphanumeric(Range<usize>),
+    /// One of the given values, each with the same probability (e.g. for a column with few distinct values).
+    OneOf(Vec<String>),
+}
+
+impl Default for ValueDistribution {
+    /// Words with `3` to `10` letters.
+    fn default() -> Self {
+        Self::Words(3..11)
+    }
+}
+
+/// Generates a CSV with a header row (`header1,header2,...`) and the given number of rows and columns.
+///
+/// The first column of every row is its index (starting at `0`), so it can be used as the primary key.
+/// The values of the other columns are drawn from the [`ValueDistribution`] of the generator.
+///
+/// # Example
+/// ```
+/// use csv_diff::test_utils::{CsvGenerator, ValueDistribution};
+///
+/// let csv_generator = CsvGenerator::new(2, 3)
+///     .value_distribution(ValueDistribution::OneOf(vec!["lemon".to_owned()]))
+///     .seed(42);
+///
+/// assert_eq!(
+///     String::from_utf8(csv_generator.generate()).unwrap(),
+///     "header1,header2,header3\n0,lemon,lemon\n1,lemon,lemon\n"
+/// );
+/// assert_eq!(csv_generator.to_string(), "2x3");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvGenerator {
+    rows: usize,
+    columns: usize,
+    value_distribution: ValueDistribution,
+    seed: Option<u64>,
+}
+
+impl CsvGenerator {
+    /// # Panics
+    /// Panics, if `columns` is `0`.
+    pub fn new(rows: usize, columns: usize) -> Self {
+        assert!(columns > 0, "a CSV must have at least one column");
+        Self {
+            rows,
+            columns,
+            value_distribution: ValueDistribution::default(),
+            seed: None,
+        }
+    }
+
+    pub fn value_distribution(mut self, value_distribution: ValueDistribution) -> Self {
+        self.value_distribution = value_distribution;
+        self
+    }
+
+    /// Seed the random number generator, so that the same CSV is generated every time
+    /// (by default, it is seeded from the operating system).
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Generate the CSV, including its header row.
+    pub fn generate(&self) -> Vec<u8> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut csv = (1..=self.columns)
+            .map(|col| format!("header{}", col))
+            .collect::<Vec<_>>()
+            .join(",")
+            .into_bytes();
+        csv.push(b'\n');
+
+        for row_idx in 0..self.rows {
+            csv.extend_from_slice(row_idx.to_string().as_bytes());
+            for _ in 1..self.columns {
+                csv.push(b',');
+                csv.extend_from_slice(self.generate_value(&mut rng).as_bytes());
+            }
+            csv.push(b'\n');
+        }
+        csv
+    }
+
+    fn generate_value(&self, rng: &mut StdRng) -> String {
+        match &self.value_distribution {
+            ValueDistribution::Words(len) => {
+                let len = rng.gen_range(len.clone());
+                (0..len)
+                    .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                    .collect()
+            }
+            ValueDistribution::Integers(range) => rng.gen_range(range.clone()).to_string(),
+            ValueDistribution::Alphanumeric(len) => {
+                let len = rng.gen_range(len.clone());
+                rng.sample_iter(&Alphanumeric)
+                    .take(len)
+                    .map(char::from)
+                    .collect()
+            }
+            ValueDistribution::OneOf(values) => values[rng.gen_range(0..values.len())].clone(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+}
+
+impl Display for CsvGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn generate_with_same_seed_is_reproducible_and_has_rows_and_columns() {
+        let csv_generator = CsvGenerator::new(100, 4)
+            .value_distribution(ValueDistribution::Integers(-5..5))
+            .seed(7);
+
+        let csv = csv_generator.generate();
+
+        assert_eq!(csv, csv_generator.generate());
+        let mut csv_reader = csv::Reader::from_reader(csv.as_slice());
+        assert_eq!(csv_reader.headers().unwrap().len(), 4);
+        let records = csv_reader
+            .records()
+            .collect::<csv::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 100);
+        for (row_idx, record) in records.iter().enumerate() {
+            assert_eq!(record[0], row_idx.to_string());
+            assert!(record
+                .iter()
+                .skip(1)
+                .all(|value| (-5..5).contains(&value.parse::<i64>().unwrap())));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::num::NonZeroU64;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A reader, that limits the throughput of the wrapped reader to a maximum number of bytes per second,
+/// by putting the reading thread to sleep whenever it is ahead of the configured rate.
+///
+/// It implements [`Seek`] if the wrapped reader does, so it can be used with
+/// [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal) as well as with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+/// Seeking does not count towards the throughput.
+///
+/// A `ThrottledReader` without a limit (see [`ThrottledReader::unlimited`]) reads as fast as the wrapped reader.
+/// This is useful when only one side of a comparison should be throttled, since both sides need to be of the same type.
+/// Usually, it is created with [`CsvBuilder::max_bytes_per_second`](crate::csv::CsvBuilder::max_bytes_per_second).
+/// # Example: throttle only the left CSV
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::CsvBuilder};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,kind\n\
+                    1,lemon,fruit\n\
+                    2,strawberry,fruit";
+let csv_data_right = "id,name,kind\n\
+                    1,lemon,fruit\n\
+                    2,strawberry,nut";
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    CsvBuilder::new()
+        .max_bytes_per_second(10 * (1 << 20))
+        .build_with_throttled_reader_seek(csv_data_left.as_bytes()),
+    CsvBuilder::new().build_with_throttled_reader_seek(csv_data_right.as_bytes()),
+)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug)]
+pub struct ThrottledReader<R> {
+    inner: R,
+    bytes_per_second: Option<NonZeroU64>,
+    started_at: Option<Instant>,
+    bytes_read: u64,
+}
+
+impl<R> ThrottledReader<R> {
+    /// Wrap `inner`, so that at most `bytes_per_second` bytes are read per second.
+    pub fn new(inner: R, bytes_per_second: NonZeroU64) -> Self {
+        Self::with_limit(inner, Some(bytes_per_second))
+    }
+
+    /// Wrap `inner` without limiting its throughput.
+    pub fn unlimited(inner: R) -> Self {
+        Self::with_limit(inner, None)
+    }
+
+    pub(crate) fn with_limit(inner: R, bytes_per_second: Option<NonZeroU64>) -> Self {
+        Self {
+            inner,
+            bytes_per_second,
+            started_at: None,
+            bytes_read: 0,
+        }
+    }
+
+    /// The maximum number of bytes per second or `None`, if the throughput is not limited.
+    pub fn bytes_per_second(&self) -> Option<NonZeroU64> {
+        self.bytes_per_second
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_per_second = match self.bytes_per_second {
+            Some(bytes_per_second) => bytes_per_second.get(),
+            None => return self.inner.read(buf),
+        };
+        let started_at = *self.started_at.get_or_insert_with(Instant::now);
+        // never read more than a second's worth of bytes at once, so that a large buffer
+        // doesn't lead to long bursts followed by long pauses
+        let max_len = usize::try_from(bytes_per_second).unwrap_or(usize::MAX);
+        let len = buf.len().min(max_len);
+        let bytes_read = self.inner.read(&mut buf[..len])?;
+        self.bytes_read += bytes_read as u64;
+
+        let due = Duration::from_secs_f64(self.bytes_read as f64 / bytes_per_second as f64);
+        let elapsed = started_at.elapsed();
+        if due > elapsed {
+            thread::sleep(due - elapsed);
+        }
+        Ok(bytes_read)
+    }
+}
+
+impl<R: Seek> Seek for ThrottledReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn throttled_reader_reads_everything_not_faster_than_limit() -> io::Result<()> {
+        let data = vec![b'x'; 200];
+        let mut reader = ThrottledReader::new(data.as_slice(), NonZeroU64::new(2_000).unwrap());
+
+        let started_at = Instant::now();
+        let mut read_data = Vec::new();
+        reader.read_to_end(&mut read_data)?;
+
+        assert_eq!(read_data, data);
+        assert!(started_at.elapsed() >= Duration::from_millis(100));
+        Ok(())
+    }
+
+    #[test]
+    fn unlimited_reader_seeks_like_inner_reader() -> io::Result<()> {
+        let mut reader = ThrottledReader::unlimited(io::Cursor::new("id,name"));
+
+        reader.seek(SeekFrom::Start(3))?;
+        let mut read_data = String::new();
+        reader.read_to_string(&mut read_data)?;
+
+        assert_eq!(read_data, "name");
+        assert_eq!(reader.bytes_per_second(), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+use crate::diff_result::group_by_col;
+use crate::diff_row::{ByteRecordLineInfo, DiffByteRecord};
+use std::io::{self, Write};
+
+/// Renders [`DiffByteRecord`](crate::diff_row::DiffByteRecord)s as human-readable text, similar to a unified diff.
+///
+/// Every difference is rendered on its own line, prefixed with its kind and its line number(s) in the CSVs:
+/// - `- <line> | <record>` for a [`Delete`](crate::diff_row::DiffByteRecord::Delete)
+/// - `+ <line> | <record>` for an [`Add`](crate::diff_row::DiffByteRecord::Add)
+/// - `~ <line> | <record>` (or `~ <line left> -> <line right> | <record>`) for a [`Modify`](crate::diff_row::DiffByteRecord::Modify),
+///   where every changed field is highlighted as `[-old-]{+new+}`
+/// - `> <line left> -> <line right> | <record>` for a [`Move`](crate::diff_row::DiffByteRecord::Move)
+///
+/// Fields are separated by the [`delimiter`](UnifiedDiffRenderer::delimiter) and quoted, when necessary.
+/// Fields that are not valid UTF-8 are converted lossily.
+///
+/// With [`group_by_column`](UnifiedDiffRenderer::group_by_column), records are grouped by their value in that column
+/// and every group starts with a header line `@@ <value> @@`.
+/// # Example
+#[cfg_attr(
+    feature = "rayon-threads",
+    doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+use csv_diff::unified_diff::UnifiedDiffRenderer;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_data_left = "id,name,kind\n\
+                     1,lemon,fruit\n\
+                     2,strawberry,fruit";
+let csv_data_right = "id,name,kind\n\
+                      1,lemon,fruit\n\
+                      2,strawberry,nut\n\
+                      3,cherry,fruit";
+
+let mut diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_reader_seek(csv_data_left.as_bytes()),
+    Csv::with_reader_seek(csv_data_right.as_bytes()),
+)?;
+diff_byte_records.sort_by_line();
+
+assert_eq!(
+    UnifiedDiffRenderer::new().render(diff_byte_records.iter()),
+    "~ 3 | 2,strawberry,[-fruit-]{+nut+}\n\
+     + 4 | 3,cherry,fruit\n"
+);
+Ok(())
+# }
+```
+"##
+)]
+#[derive(Debug, Clone)]
+pub struct UnifiedDiffRenderer {
+    delimiter: u8,
+    group_by_column: Option<usize>,
+}
+
+impl Default for UnifiedDiffRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnifiedDiffRenderer {
+    pub fn new() -> Self {
+        Self {
+            delimiter: b',',
+            group_by_column: None,
+        }
+    }
+
+    /// Set the delimiter, that is used to separate fields (default: `,`).
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Group the records by their value in column `col` (usually the first column of the primary key)
+    /// and emit a header line before every group (default: no grouping).
+    ///
+    /// Groups are ordered by their value; records within a group keep their order.
+    pub fn group_by_column(mut self, col: usize) -> Self {
+        self.group_by_column = Some(col);
+        self
+    }
+
+    /// Render all `diff_records` into a `String`.
+    pub fn render<'a, I>(&self, diff_records: I) -> String
+    where
+        I: IntoIterator<Item = &'a DiffByteRecord>,
+    {
+        let mut buf = Vec::new();
+        match self.group_by_column {
+            Some(col) => {
+                for group in group_by_col(diff_records, col) {
+                    buf.extend_from_slice(b"@@ ");
+                    self.write_field(group.key(), &mut buf)
+                        .expect("writing to a Vec never fails");
+                    buf.extend_from_slice(b" @@\n");
+                    for diff_record in group.diff_records() {
+                        self.render_diff_byte_record(diff_record, &mut buf)
+                            .expect("writing to a Vec never fails");
+                    }
+                }
+            }
+            None => {
+                for diff_record in diff_records {
+                    self.render_diff_byte_record(diff_record, &mut buf)
+                        .expect("writing to a Vec never fails");
+                }
+            }
+        }
+        // we only ever write valid UTF-8
+        String::from_utf8(buf).expect("is utf8")
+    }
+
+    /// Render a single `DiffByteRecord` (including a trailing newline) into `wtr`.
+    pub fn render_diff_byte_record<W: Write>(
+        &self,
+        diff_record: &DiffByteRecord,
+        wtr: &mut W,
+    ) -> io::Result<()> {
+        match diff_record {
+            DiffByteRecord::Delete(brli) => self.render_one_side(b'-', brli, wtr),
+            DiffByteRecord::Add(brli) => self.render_one_side(b'+', brli, wtr),
+            DiffByteRecord::Modify {
+                delete,
+                add,
+                field_indices,
+            } => {
+                if delete.line() == add.line() {
+                    write!(wtr, "~ {} | ", delete.line())?;
+                } else {
+                    write!(wtr, "~ {} -> {} | ", delete.line(), add.line())?;
+                }
+                let (fields_deleted, fields_added) = (delete.byte_record(), add.byte_record());
+                let num_of_fields = fields_deleted.len().max(fields_added.len());
+                let mut field_indices = field_indices.iter().peekable();
+                for idx in 0..num_of_fields {
+                    if idx > 0 {
+                        wtr.write_all(&[self.delimiter])?;
+                    }
+                    let is_changed = field_indices.next_if_eq(&&idx).is_some();
+                    match (fields_deleted.get(idx), fields_added.get(idx)) {
+                        (Some(field_deleted), Some(_)) if !is_changed => {
+                            self.write_field(field_deleted, wtr)?
+                        }
+                        (field_deleted, field_added) => {
+                            if let Some(field_deleted) = field_deleted {
+                                wtr.write_all(b"[-")?;
+                                self.write_field(field_deleted, wtr)?;
+                                wtr.write_all(b"-]")?;
+                            }
+                            if let Some(field_added) = field_added {
+                                wtr.write_all(b"{+")?;
+                                self.write_field(field_added, wtr)?;
+                                wtr.write_all(b"+}")?;
+                            }
+                        }
+                    }
+                }
+                wtr.write_all(b"\n")
+            }
+            DiffByteRecord::Move {
+                record,
+                from_line,
+                to_line,
+            } => {
+                write!(wtr, "> {} -> {} | ", from_line, to_line)?;
+                self.write_record(record, wtr)?;
+                wtr.write_all(b"\n")
+            }
+        }
+    }
+
+    fn render_one_side<W: Write>(
+        &self,
+        prefix: u8,
+        brli: &ByteRecordLineInfo,
+        wtr: &mut W,
+    ) -> io::Result<()> {
+        write!(wtr, "{} {} | ", prefix as char, brli.line())?;
+        self.write_record(brli.byte_record(), wtr)?;
+        wtr.write_all(b"\n")
+    }
+
+    fn write_record<W: Write>(&self, byte_record: &csv::ByteRecord, wtr: &mut W) -> io::Result<()> {
+        for (idx, field) in byte_record.iter().enumerate() {
+            if idx > 0 {
+                wtr.write_all(&[self.delimiter])?;
+            }
+            self.write_field(field, wtr)?;
+        }
+        Ok(())
+    }
+
+    fn write_field<W: Write>(&self, field: &[u8], wtr: &mut W) -> io::Result<()> {
+        write_field(field, self.delimiter, wtr)
+    }
+}
+
+/// Write `field` lossily as UTF-8 into `wtr` and quote it, when it contains the `delimiter`, a quote or a line break.
+pub(crate) fn write_field<W: Write>(field: &[u8], delimiter: u8, wtr: &mut W) -> io::Result<()> {
+    let field = String::from_utf8_lossy(field);
+    let needs_quotes = field
+        .bytes()
+        .any(|b| b == delimiter || b == b'"' || b == b'\n' || b == b'\r');
+    if needs_quotes {
+        write!(wtr, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        wtr.write_all(field.as_bytes())
+    }
+}
+
+#[cfg(feature = "rayon-threads")]
+impl crate::parallel_render::DiffRecordFormatter for UnifiedDiffRenderer {
+    fn format_diff_byte_record(
+        &self,
+        diff_record: &DiffByteRecord,
+        mut wtr: &mut dyn Write,
+    ) -> io::Result<()> {
+        self.render_diff_byte_record(diff_record, &mut wtr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn render_add_and_delete() {
+        let diff_records = vec![
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["a", "b", "c"]),
+                2,
+            )),
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["d", "e", "f"]),
+                3,
+            )),
+        ];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new().render(&diff_records),
+            "- 2 | a,b,c\n\
+             + 3 | d,e,f\n"
+        );
+    }
+
+    #[test]
+    fn render_move_with_both_lines() {
+        let diff_records = vec![DiffByteRecord::Move {
+            record: csv::ByteRecord::from(vec!["a", "b;c"]),
+            from_line: 2,
+            to_line: 7,
+        }];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new()
+                .delimiter(b';')
+                .render(&diff_records),
+            "> 2 -> 7 | a;\"b;c\"\n"
+        );
+    }
+
+    #[test]
+    fn render_modify_on_different_lines_highlights_changed_fields() {
+        let diff_records = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "x", "y"]), 5),
+            field_indices: vec![1, 2],
+        }];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new().render(&diff_records),
+            "~ 2 -> 5 | a,[-b-]{+x+},[-c-]{+y+}\n"
+        );
+    }
+
+    #[test]
+    fn render_modify_with_different_num_of_fields() {
+        let diff_records = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            field_indices: vec![],
+        }];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new().render(&diff_records),
+            "~ 2 | a,b,{+c+}\n"
+        );
+    }
+
+    #[test]
+    fn render_grouped_by_column_emits_group_headers_in_key_order() {
+        let diff_records = vec![
+            DiffByteRecord::Add(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["north", "1"]),
+                2,
+            )),
+            DiffByteRecord::Delete(ByteRecordLineInfo::new(
+                csv::ByteRecord::from(vec!["east", "2"]),
+                3,
+            )),
+            DiffByteRecord::Modify {
+                delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["north", "3"]), 4),
+                add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["north", "4"]), 4),
+                field_indices: vec![1],
+            },
+        ];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new()
+                .group_by_column(0)
+                .render(&diff_records),
+            "@@ east @@\n\
+             - 3 | east,2\n\
+             @@ north @@\n\
+             + 2 | north,1\n\
+             ~ 4 | north,[-3-]{+4+}\n"
+        );
+    }
+
+    #[test]
+    fn render_with_custom_delimiter_quotes_fields_when_necessary() {
+        let diff_records = vec![DiffByteRecord::Add(ByteRecordLineInfo::new(
+            csv::ByteRecord::from(vec!["a;b", "say \"hi\"", "c,d"]),
+            2,
+        ))];
+
+        assert_eq!(
+            UnifiedDiffRenderer::new()
+                .delimiter(b';')
+                .render(&diff_records),
+            "+ 2 | \"a;b\";\"say \"\"hi\"\"\";c,d\n"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use crate::csv::{Csv, CsvBuilder};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+/// A reader, that decompresses [zstd](https://facebook.github.io/zstd/)-compressed data on the fly.
+///
+/// This is only available with the Cargo feature `zstd`.
+pub type ZstdReader<R> = zstd::stream::read::Decoder<'static, BufReader<R>>;
+
+impl<R: Read> Csv<ZstdReader<R>> {
+    /// Create a new `Csv` with something that reads zstd-compressed CSV data (e.g. a `.csv.zst` file),
+    /// in order to compare it with [`CsvByteDiff`](crate::csv_diff::CsvByteDiff).
+    ///
+    /// The data is decompressed while it is being compared. Fails, if the zstd decoder can't be created.
+    /// This is only available with the Cargo feature `zstd`.
+    pub fn with_zstd_reader(reader: R) -> io::Result<Self> {
+        CsvBuilder::new().build_with_zstd_reader(reader)
+    }
+}
+
+impl Csv<File> {
+    /// Create a new `Csv` with something that reads zstd-compressed CSV data (e.g. a `.csv.zst` file),
+    /// in order to compare it with [`CsvByteDiffLocal`](crate::csv_diff::CsvByteDiffLocal).
+    ///
+    /// `CsvByteDiffLocal` needs to seek in the CSV data, which a zstd stream can't do, so `reader` is decompressed
+    /// into a temporary file first (see [`std::env::temp_dir`]), which is removed, when the `Csv` is dropped.
+    /// This is only available with the Cargo feature `zstd`.
+    /// # Example
+    #[cfg_attr(
+        feature = "rayon-threads",
+        doc = r##"
+```
+use csv_diff::{csv_diff::CsvByteDiffLocal, csv::Csv};
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let csv_left = zstd::encode_all("id,name,kind\n1,lemon,fruit".as_bytes(), 0)?;
+let csv_right = zstd::encode_all("id,name,kind\n1,lemon,nut".as_bytes(), 0)?;
+
+let diff_byte_records = CsvByteDiffLocal::new()?.diff(
+    Csv::with_zstd_reader_seek(csv_left.as_slice())?,
+    Csv::with_zstd_reader_seek(csv_right.as_slice())?,
+)?;
+
+assert_eq!(diff_byte_records.as_slice().len(), 1);
+Ok(())
+# }
+```
+"##
+    )]
+    pub fn with_zstd_reader_seek<R: Read>(reader: R) -> io::Result<Self> {
+        CsvBuilder::new().build_with_zstd_reader_seek(reader)
+    }
+}
+
+impl CsvBuilder {
+    /// Like [`build_with_reader`](CsvBuilder::build_with_reader), but decompresses the zstd-compressed data of `reader`
+    /// on the fly (see [`Csv::with_zstd_reader`]).
+    pub fn build_with_zstd_reader<R: Read>(&self, reader: R) -> io::Result<Csv<ZstdReader<R>>> {
+        Ok(self.build_with_reader(zstd::stream::read::Decoder::new(reader)?))
+    }
+
+    /// Like [`build_with_reader_seek`](CsvBuilder::build_with_reader_seek), but decompresses the zstd-compressed data
+    /// of `reader` into a temporary file first (see [`Csv::with_zstd_reader_seek`]).
+    pub fn build_with_zstd_reader_seek<R: Read>(&self, reader: R) -> io::Result<Csv<File>> {
+        let mut file = tempfile::tempfile()?;
+        zstd::stream::copy_decode(reader, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(self.build_with_reader_seek(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn zstd_reader_yields_decompressed_records() -> Result<(), Box<dyn std::error::Error>> {
+        let compressed = zstd::encode_all("id,name\n1,lemon\n2,cherry".as_bytes(), 0)?;
+
+        let mut csv_reader = Csv::with_zstd_reader(compressed.as_slice())?.into_csv_reader();
+        let records = csv_reader.byte_records().collect::<csv::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            records,
+            vec![
+                csv::ByteRecord::from(vec!["1", "lemon"]),
+                csv::ByteRecord::from(vec!["2", "cherry"])
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn zstd_reader_of_uncompressed_data_is_err() {
+        let res = Csv::with_zstd_reader_seek("id,name\n1,lemon".as_bytes());
+
+        assert!(res.is_err());
+    }
+}
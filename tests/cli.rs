@@ -0,0 +1,94 @@
+#![cfg(feature = "cli")]
+
+use pretty_assertions::assert_eq;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use tempfile::NamedTempFile;
+
+fn csv_file(csv_data: &str) -> Result<NamedTempFile, Box<dyn Error>> {
+    let mut csv_file = NamedTempFile::new()?;
+    csv_file.write_all(csv_data.as_bytes())?;
+    Ok(csv_file)
+}
+
+fn csv_diff(args: &[&str], left: &NamedTempFile, right: &NamedTempFile) -> std::io::Result<Output> {
+    Command::new(PathBuf::from(env!("CARGO_BIN_EXE_csv-diff")))
+        .args(args)
+        .arg(left.path())
+        .arg(right.path())
+        .output()
+}
+
+#[test]
+fn equal_csvs_exit_with_0_and_print_nothing() -> Result<(), Box<dyn Error>> {
+    let left = csv_file("id,name\n1,lemon\n2,strawberry")?;
+    let right = csv_file("id,name\n2,strawberry\n1,lemon")?;
+
+    let output = csv_diff(&[], &left, &right)?;
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout)?, "");
+    Ok(())
+}
+
+#[test]
+fn different_csvs_exit_with_1_and_print_unified_diff() -> Result<(), Box<dyn Error>> {
+    let left = csv_file("id,name,kind\n1,lemon,fruit\n2,strawberry,fruit")?;
+    let right = csv_file("id,name,kind\n1,lemon,fruit\n2,strawberry,nut\n3,cherry,fruit")?;
+
+    let output = csv_diff(&[], &left, &right)?;
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "~ 3 | 2,strawberry,[-fruit-]{+nut+}\n\
+         + 4 | 3,cherry,fruit\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn key_delimiter_and_csv_format() -> Result<(), Box<dyn Error>> {
+    let left = csv_file("name;id;kind\nlemon;1;fruit\nstrawberry;2;fruit")?;
+    let right = csv_file("name;id;kind\nlemon;1;fruit\nstrawberry;2;nut")?;
+
+    let output = csv_diff(&["--key", "1", "-d", ";", "--format=csv"], &left, &right)?;
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "operation;name;id;kind\n\
+         modify_delete;strawberry;2;fruit\n\
+         modify_add;strawberry;2;nut\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn quiet_only_sets_exit_code() -> Result<(), Box<dyn Error>> {
+    let left = csv_file("1,lemon")?;
+    let right = csv_file("1,strawberry")?;
+
+    let output = csv_diff(&["-q", "--no-headers", "-f", "json"], &left, &right)?;
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout)?, "");
+    Ok(())
+}
+
+#[test]
+fn errors_exit_with_2() -> Result<(), Box<dyn Error>> {
+    let left = csv_file("id,name\n1,lemon")?;
+    let right = csv_file("id,name\n1,lemon,fruit")?;
+
+    let ragged_output = csv_diff(&[], &left, &right)?;
+    let usage_output = csv_diff(&["--format", "xml"], &left, &right)?;
+
+    assert_eq!(ragged_output.status.code(), Some(2));
+    assert!(String::from_utf8(ragged_output.stderr)?.contains("CSVDIFF_RAGGED_ROW"));
+    assert_eq!(usage_output.status.code(), Some(2));
+    assert!(String::from_utf8(usage_output.stderr)?.contains("unknown output format 'xml'"));
+    Ok(())
+}
@@ -9,8 +9,6 @@ mod integration_test {
     use csv_diff::diff_result::DiffByteRecords;
     use csv_diff::diff_row::{ByteRecordLineInfo, DiffByteRecord};
     use pretty_assertions::assert_eq;
-    #[cfg(not(feature = "rayon-threads"))]
-    use std::convert::TryInto;
     use std::{error::Error, io::Cursor};
 
     #[cfg(feature = "rayon-threads")]
@@ -495,6 +493,43 @@ mod integration_test {
         Ok(())
     }
 
+    #[cfg(all(feature = "tokio-async", not(feature = "rayon-threads")))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streaming_create_instance_with_builder_tokio_and_diff_without_cursor(
+    ) -> Result<(), Box<dyn Error>> {
+        use csv_diff::csv_hash_task_spawner::{
+            CsvHashTaskSpawnerBuilderTokio, CsvHashTaskSpawnerTokio,
+        };
+        let csv_byte_diff = csv_diff::csv_diff::CsvByteDiffBuilder::<CsvHashTaskSpawnerTokio>::new(
+            CsvHashTaskSpawnerBuilderTokio::current(),
+        )
+        .build()?;
+        let csv_left = "\
+                        header1,header2,header3\n\
+                        a,b,c";
+        let csv_right = "\
+                        header1,header2,header3\n\
+                        a,b,d";
+        let diff_res = csv_byte_diff.diff(
+            Csv::with_reader(csv_left.as_bytes()),
+            Csv::with_reader(csv_right.as_bytes()),
+        );
+
+        let diff_rows_actual = diff_res.collect::<csv::Result<Vec<DiffByteRecord>>>()?;
+
+        let diff_rows_expected = vec![DiffByteRecord::Modify {
+            delete: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "c"]), 2),
+            add: ByteRecordLineInfo::new(csv::ByteRecord::from(vec!["a", "b", "d"]), 2),
+            field_indices: vec![2],
+        }];
+
+        assert_eq!(diff_rows_actual, diff_rows_expected);
+
+        Ok(())
+    }
+
+    // the tests of the custom spawners only run without `rayon-threads`
+    #[cfg_attr(feature = "rayon-threads", allow(dead_code))]
     mod custom_scoped_threads {
         #[cfg(not(feature = "rayon-threads"))]
         use super::*;
@@ -608,6 +643,7 @@ mod integration_test {
         }
     }
 
+    #[cfg_attr(feature = "rayon-threads", allow(dead_code))]
     mod custom_threads {
         #[cfg(not(feature = "rayon-threads"))]
         use super::*;
@@ -630,17 +666,12 @@ mod integration_test {
 
         impl CsvHashTaskSpawner for CsvHashTaskSpawnerCustom {
             fn spawn_hashing_tasks_and_send_result<R: Read + Send + 'static>(
-                self,
+                &self,
                 csv_hash_task_sender_left: csv_diff::csv_hash_task_spawner::CsvHashTaskSenderWithRecycleReceiver<R>,
                 csv_hash_task_sender_right: csv_diff::csv_hash_task_spawner::CsvHashTaskSenderWithRecycleReceiver<R>,
                 csv_hash_receiver_comparer: csv_diff::csv_hash_receiver_comparer::CsvHashReceiverStreamComparer,
                 primary_key_columns: HashSet<usize>,
-            ) -> (
-                Self,
-                crossbeam_channel::Receiver<csv_diff::diff_result::DiffByteRecordsIterator>,
-            )
-            where
-                Self: Sized,
+            ) -> crossbeam_channel::Receiver<csv_diff::diff_result::DiffByteRecordsIterator>
             {
                 let (sender, receiver) = bounded(1);
 
@@ -666,7 +697,7 @@ mod integration_test {
                     >(csv_hash_task_sender_right, prim_key_columns_clone);
                 });
 
-                (self, receiver)
+                receiver
             }
         }
 